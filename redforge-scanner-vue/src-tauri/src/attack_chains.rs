@@ -0,0 +1,216 @@
+/**
+ * Attack Chain Detection
+ *
+ * 個別發現會低估組合後的風險 (開放重定向 + 缺少 SameSite + 反射參數 = 帳號接管)。
+ * 掃描完成後依規則檢查同時出現的 check id，組成具名的攻擊鏈，附上合併後的嚴重度、敘述與組成發現的 id。
+ *
+ * 內建規則之外，使用者可在 attack-chain-rules.json 新增或取代規則 (相同 id 時取代內建規則)。
+ */
+
+use crate::models::{ScanResult, Severity};
+use crate::output::write_atomic;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const RULES_FILE: &str = "attack-chain-rules.json";
+
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 啟動時呼叫：自訂規則檔存放目錄
+pub fn init(config_dir: &Path) {
+    let _ = CONFIG_DIR.set(config_dir.to_path_buf());
+}
+
+/// 攻擊鏈規則
+///
+/// requires 的每一組至少要有一個 check id 出現在發現中 (組內為「或」，組間為「且」)。
+/// narrative 可使用 {target} 與 {findings} (組成發現的標題)。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChainRule {
+    pub id: String,
+    pub name: String,
+    pub requires: Vec<Vec<String>>,
+    pub severity: Severity,
+    pub narrative: String,
+}
+
+/// 偵測到的攻擊鏈，附在報告上
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AttackChain {
+    pub rule_id: String,
+    pub name: String,
+    pub severity: Severity,
+    pub narrative: String,
+    /// 組成此攻擊鏈的發現 id，前端用來連結
+    pub finding_ids: Vec<String>,
+    /// 實際符合的 check id，依規則的分組順序
+    pub check_ids: Vec<String>,
+}
+
+fn rule(id: &str, name: &str, requires: &[&[&str]], severity: Severity, narrative: &str) -> ChainRule {
+    ChainRule {
+        id: id.to_string(),
+        name: name.to_string(),
+        requires: requires.iter().map(|group| group.iter().map(|c| c.to_string()).collect()).collect(),
+        severity,
+        narrative: narrative.to_string(),
+    }
+}
+
+fn builtin_rules() -> Vec<ChainRule> {
+    vec![
+        rule(
+            "cors-credentials",
+            "CORS 憑證竊取",
+            &[&["cors_reflected_origin", "cors_null_origin", "cors_wildcard_origin"], &["cors_allow_credentials"]],
+            Severity::High,
+            "{target} 接受任意來源的跨網域請求且允許攜帶憑證，攻擊者的網頁可以使用者的 session 讀取 API 回應。組成發現: {findings}",
+        ),
+        rule(
+            "subdomain-takeover-cookie-scope",
+            "子網域接管竊取 Session",
+            &[&["subdomain_takeover"], &["cookie_parent_domain"]],
+            Severity::Critical,
+            "可被接管的子網域與設定在上層網域的 cookie 同時存在：攻擊者接管子網域後即可收到 {target} 使用者的 session cookie。組成發現: {findings}",
+        ),
+        rule(
+            "idor-user-enumeration",
+            "列舉使用者後越權存取",
+            &[&["idor"], &["user_enumeration"]],
+            Severity::High,
+            "{target} 可列舉有效帳號，搭配物件層級授權缺失，攻擊者可依列舉結果逐一存取其他使用者的資料。組成發現: {findings}",
+        ),
+        rule(
+            "redirect-account-takeover",
+            "重定向帳號接管",
+            &[&["open_redirect"], &["cookie_missing_samesite"], &["xss", "reflected_parameter"]],
+            Severity::Critical,
+            "開放重定向可把使用者導向攻擊者控制的頁面，缺少 SameSite 的 session cookie 會隨跨站請求送出，反射參數讓攻擊者在 {target} 執行腳本，串連後可接管帳號。組成發現: {findings}",
+        ),
+    ]
+}
+
+/// 內建與自訂的規則；自訂規則與內建規則 id 相同時取代之
+pub fn list_rules() -> Result<Vec<ChainRule>, String> {
+    let mut rules = builtin_rules();
+    for custom in load_custom_rules()? {
+        rules.retain(|r| r.id != custom.id);
+        rules.push(custom);
+    }
+    Ok(rules)
+}
+
+/// 新增或取代自訂規則
+pub fn save_rule(rule: ChainRule) -> Result<(), String> {
+    validate_rule(&rule)?;
+
+    let mut rules = load_custom_rules()?;
+    rules.retain(|r| r.id != rule.id);
+    rules.push(rule);
+    write_custom_rules(&rules)
+}
+
+/// 以 rules 取代所有自訂規則 (工作區設定匯入)
+pub fn replace_custom_rules(rules: Vec<ChainRule>) -> Result<(), String> {
+    for rule in &rules {
+        validate_rule(rule)?;
+    }
+    write_custom_rules(&rules)
+}
+
+pub fn validate_rule(rule: &ChainRule) -> Result<(), String> {
+    if rule.id.trim().is_empty() {
+        return Err("規則 id 不可為空".to_string());
+    }
+    if rule.requires.is_empty() || rule.requires.iter().any(|group| group.is_empty()) {
+        return Err(format!("規則 {} 的 requires 不可包含空的分組", rule.id));
+    }
+    Ok(())
+}
+
+fn write_custom_rules(rules: &[ChainRule]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(rules).map_err(|e| e.to_string())?;
+    write_atomic(&config_dir()?.join(RULES_FILE), content.as_bytes(), true)?;
+    Ok(())
+}
+
+/// 對完成的報告套用所有規則；規則檔無法讀取時只使用內建規則
+pub fn detect(target: &str, findings: &[ScanResult]) -> Vec<AttackChain> {
+    let rules = list_rules().unwrap_or_else(|e| {
+        tracing::warn!("⚠️  {}，只使用內建攻擊鏈規則", e);
+        builtin_rules()
+    });
+    let checks: Vec<(Option<String>, &ScanResult)> = findings.iter().map(|f| (finding_check_id(f), f)).collect();
+
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let mut finding_ids = Vec::new();
+            let mut check_ids = Vec::new();
+            let mut titles = Vec::new();
+
+            for group in &rule.requires {
+                let matched: Vec<&(Option<String>, &ScanResult)> = checks
+                    .iter()
+                    .filter(|(check, _)| check.as_ref().is_some_and(|c| group.contains(c)))
+                    .collect();
+                if matched.is_empty() {
+                    return None;
+                }
+                for (check, finding) in matched {
+                    if !finding_ids.contains(&finding.id) {
+                        finding_ids.push(finding.id.clone());
+                        titles.push(finding.title.clone());
+                    }
+                    if let Some(check) = check {
+                        if !check_ids.contains(check) {
+                            check_ids.push(check.clone());
+                        }
+                    }
+                }
+            }
+
+            tracing::info!("⛓️  偵測到攻擊鏈: {} ({} 個發現)", rule.name, finding_ids.len());
+            Some(AttackChain {
+                rule_id: rule.id.clone(),
+                name: rule.name.clone(),
+                severity: rule.severity.clone(),
+                narrative: rule.narrative.replace("{target}", target).replace("{findings}", &titles.join("、")),
+                finding_ids,
+                check_ids,
+            })
+        })
+        .collect()
+}
+
+/// 發現的 check id：raw_data 中的 check_id，沒有時由 type 轉換 (例如 "Open Redirect" → open_redirect)
+pub fn finding_check_id(finding: &ScanResult) -> Option<String> {
+    let raw: serde_json::Value = serde_json::from_str(finding.raw_data.as_deref()?).ok()?;
+    if let Some(check_id) = raw.get("check_id").and_then(|v| v.as_str()) {
+        return Some(check_id.to_string());
+    }
+    let kind = raw.get("type").and_then(|v| v.as_str())?;
+    let slug = kind
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("_");
+    (!slug.is_empty()).then_some(slug)
+}
+
+/// 使用者自訂的規則 (不含內建規則)
+pub fn load_custom_rules() -> Result<Vec<ChainRule>, String> {
+    let path = config_dir()?.join(RULES_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("攻擊鏈規則檔格式無效 {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("無法讀取攻擊鏈規則檔 {}: {}", path.display(), e)),
+    }
+}
+
+fn config_dir() -> Result<&'static PathBuf, String> {
+    CONFIG_DIR.get().ok_or_else(|| "設定目錄未初始化".to_string())
+}
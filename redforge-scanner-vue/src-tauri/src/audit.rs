@@ -0,0 +1,164 @@
+/**
+ * Request Audit Log
+ *
+ * 依授權測試的交戰規則，記錄掃描送出的每個請求：時間、掃描 id、檢查 id、method、URL 與內容雜湊。
+ * 每個掃描一個只附加的 JSONL 檔 (audit/<scan_id>.jsonl)，寫入先進緩衝區，
+ * 在階段切換、累積一定數量與掃描結束 (包含取消或 panic 時的 drop) 時寫出。
+ *
+ * URL 的帳密與看似憑證的查詢參數會遮蔽，內容只保留截斷的 SHA-256，不記錄任何憑證。
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+const AUDIT_DIR: &str = "audit";
+/// 累積超過此數量的紀錄時立即寫出，限制異常結束時可能遺失的範圍
+const FLUSH_EVERY: usize = 64;
+/// 內容雜湊保留的十六進位字元數
+const BODY_HASH_LEN: usize = 16;
+/// 查詢參數名稱包含這些字串時遮蔽其值
+const SENSITIVE_PARAMS: &[&str] = &["pass", "pwd", "token", "secret", "key", "auth", "session", "credential", "signature"];
+
+static AUDIT_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// 啟動時呼叫：設定稽核紀錄目錄
+pub fn init(config_dir: &std::path::Path) {
+    let dir = config_dir.join(AUDIT_DIR);
+    let _ = std::fs::create_dir_all(&dir);
+    let _ = AUDIT_ROOT.set(dir);
+}
+
+/// 單一請求的稽核紀錄
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub scan_id: String,
+    pub check_id: String,
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub body_sha256: Option<String>,
+}
+
+/// 掃描的稽核紀錄寫入器，由 ScanControl 持有
+#[derive(Clone)]
+pub struct AuditLog {
+    scan_id: String,
+    writer: Arc<Mutex<AuditWriter>>,
+}
+
+struct AuditWriter {
+    file: BufWriter<File>,
+    pending: usize,
+}
+
+impl AuditWriter {
+    fn flush(&mut self) {
+        if let Err(e) = self.file.flush() {
+            tracing::warn!("⚠️  稽核紀錄寫入失敗: {}", e);
+        }
+        self.pending = 0;
+    }
+}
+
+impl Drop for AuditWriter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl AuditLog {
+    /// 開啟掃描的稽核紀錄；目錄未初始化或無法建立檔案時回傳 None (掃描照常執行)
+    pub fn open(scan_id: &str) -> Option<Self> {
+        let path = log_path(scan_id).ok()?;
+        let file = match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                tracing::warn!("⚠️  無法開啟稽核紀錄 {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        Some(Self {
+            scan_id: scan_id.to_string(),
+            writer: Arc::new(Mutex::new(AuditWriter { file: BufWriter::new(file), pending: 0 })),
+        })
+    }
+
+    pub fn record(&self, check_id: &str, method: &str, url: &str, body: Option<&[u8]>) {
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            scan_id: self.scan_id.clone(),
+            check_id: check_id.to_string(),
+            method: method.to_string(),
+            url: sanitize_url(url),
+            body_sha256: body.filter(|b| !b.is_empty()).map(|b| {
+                let digest = format!("{:x}", Sha256::digest(b));
+                digest[..BODY_HASH_LEN].to_string()
+            }),
+        };
+        let Ok(line) = serde_json::to_string(&entry) else { return };
+
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(writer.file, "{}", line);
+        writer.pending += 1;
+        if writer.pending >= FLUSH_EVERY {
+            writer.flush();
+        }
+    }
+
+    /// 寫出緩衝區 (階段切換與掃描結束時呼叫)
+    pub fn flush(&self) {
+        self.writer.lock().unwrap_or_else(|e| e.into_inner()).flush();
+    }
+}
+
+/// 讀取掃描的稽核紀錄原文 (JSONL)
+pub fn read_log(scan_id: &str) -> Result<String, String> {
+    let path = log_path(scan_id)?;
+    std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("掃描 {} 沒有稽核紀錄", scan_id),
+        _ => format!("無法讀取稽核紀錄 {}: {}", path.display(), e),
+    })
+}
+
+fn log_path(scan_id: &str) -> Result<PathBuf, String> {
+    // scan id 為 UUID，其他字元一律拒絕，避免路徑跳脫
+    if scan_id.is_empty() || !scan_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("無效的掃描 ID: {}", scan_id));
+    }
+    let root = AUDIT_ROOT.get().ok_or("稽核紀錄目錄未初始化")?;
+    Ok(root.join(format!("{}.jsonl", scan_id)))
+}
+
+/// 移除 URL 中的帳密，並遮蔽看似憑證的查詢參數值
+fn sanitize_url(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    let _ = parsed.set_username("");
+    let _ = parsed.set_password(None);
+
+    if parsed.query().is_some() {
+        let pairs: Vec<(String, String)> = parsed
+            .query_pairs()
+            .map(|(name, value)| {
+                let lower = name.to_lowercase();
+                let value = if SENSITIVE_PARAMS.iter().any(|s| lower.contains(s)) {
+                    "<redacted>".to_string()
+                } else {
+                    value.to_string()
+                };
+                (name.to_string(), value)
+            })
+            .collect();
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    parsed.to_string()
+}
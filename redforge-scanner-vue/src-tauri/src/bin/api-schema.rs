@@ -0,0 +1,20 @@
+// 重新產生 API schema 快照 (schema/api-schema.json)
+//
+//   cargo run --bin api-schema
+//
+// schema 變動時必須同時調整 API_SCHEMA_VERSION 並提交新的快照；快照是否一致由 `cargo test` 檢查。
+
+use redforge_scanner_vue_lib::{api_schema, API_SCHEMA_VERSION};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schema").join("api-schema.json");
+    let content = serde_json::to_string_pretty(&api_schema()).expect("schema 序列化失敗") + "\n";
+    if let Err(e) = std::fs::create_dir_all(path.parent().unwrap()).and_then(|_| std::fs::write(&path, content)) {
+        eprintln!("❌ 無法寫入 {}: {}", path.display(), e);
+        return ExitCode::FAILURE;
+    }
+    println!("✅ 已寫入 API schema {} 到 {}", API_SCHEMA_VERSION, path.display());
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,192 @@
+/**
+ * Canary Values
+ *
+ * 每個掃描產生獨特且易辨識的標記 (rfg-<掃描 id 前 6 碼>-<序號>)，嵌入 XSS / SSTI / CRLF / SSRF payload
+ * 與 PUT / 上傳探測的內容。通用字串 ("XSS"、evil.com) 會混在其他流量中，同時執行的掃描也無法區分；
+ * 在目標日誌或 OOB 回呼中看到 canary 時，可以追溯到確切的掃描與檢查。
+ *
+ * 每個掃描一個 JSONL 檔 (canaries/<scan_id>.jsonl)，每個 canary 產生時立即寫出，掃描中斷後仍可查詢。
+ */
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const CANARY_DIR: &str = "canaries";
+const CANARY_PREFIX: &str = "rfg";
+/// 取自掃描 id 的字元數
+const SCAN_TAG_LEN: usize = 6;
+
+static CANARY_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// 啟動時呼叫：設定 canary 紀錄目錄
+pub fn init(config_dir: &std::path::Path) {
+    let dir = config_dir.join(CANARY_DIR);
+    let _ = std::fs::create_dir_all(&dir);
+    let _ = CANARY_ROOT.set(dir);
+}
+
+/// 一個已發出的 canary 與其來源
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CanaryRecord {
+    pub value: String,
+    pub scan_id: String,
+    /// 產生時的檢查 id (目前階段，例如 owasp.a03)
+    pub check_id: String,
+    /// 用途，例如 xss、ssrf、open_redirect
+    pub purpose: String,
+    pub issued_at: DateTime<Utc>,
+}
+
+/// 掃描的 canary 產生器，由 ScanControl 持有
+#[derive(Clone)]
+pub struct CanaryRegistry {
+    scan_id: String,
+    tag: String,
+    counter: Arc<AtomicU64>,
+    file: Option<Arc<Mutex<File>>>,
+}
+
+impl CanaryRegistry {
+    /// 目錄未初始化或無法建立檔案時仍可產生 canary，只是無法事後查詢
+    pub fn open(scan_id: &str) -> Self {
+        let file = record_path(scan_id).ok().and_then(|path| {
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => Some(Arc::new(Mutex::new(file))),
+                Err(e) => {
+                    tracing::warn!("⚠️  無法開啟 canary 紀錄 {}: {}", path.display(), e);
+                    None
+                }
+            }
+        });
+
+        Self {
+            scan_id: scan_id.to_string(),
+            tag: scan_tag(scan_id),
+            counter: Arc::new(AtomicU64::new(0)),
+            file,
+        }
+    }
+
+    /// 產生下一個 canary 並記錄其檢查與用途
+    pub fn issue(&self, check_id: &str, purpose: &str) -> String {
+        let value = format!("{}-{}-{}", CANARY_PREFIX, self.tag, self.counter.fetch_add(1, Ordering::SeqCst) + 1);
+
+        if let Some(file) = &self.file {
+            let record = CanaryRecord {
+                value: value.clone(),
+                scan_id: self.scan_id.clone(),
+                check_id: check_id.to_string(),
+                purpose: purpose.to_string(),
+                issued_at: Utc::now(),
+            };
+            if let Ok(line) = serde_json::to_string(&record) {
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = writeln!(file, "{}", line) {
+                    tracing::warn!("⚠️  canary 紀錄寫入失敗: {}", e);
+                }
+            }
+        }
+
+        value
+    }
+}
+
+/// 掃描 id 前 6 個英數字元 (小寫)，不足時補 0
+fn scan_tag(scan_id: &str) -> String {
+    let mut tag: String = scan_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(SCAN_TAG_LEN)
+        .collect::<String>()
+        .to_lowercase();
+    while tag.len() < SCAN_TAG_LEN {
+        tag.push('0');
+    }
+    tag
+}
+
+fn canary_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(&format!(r"(?i){}-([0-9a-z]{{{}}})-\d+", CANARY_PREFIX, SCAN_TAG_LEN)).unwrap())
+}
+
+/// 從任意文字 (日誌行、回呼的主機名稱或路徑) 中找出所有 canary
+pub fn extract(text: &str) -> Vec<String> {
+    canary_pattern().find_iter(text).map(|m| m.as_str().to_lowercase()).collect()
+}
+
+/// 解析 canary 所屬的掃描與檢查；value 可以是包含 canary 的任意文字
+pub fn lookup(value: &str) -> Result<CanaryRecord, String> {
+    let canary = extract(value).into_iter().next().ok_or_else(|| format!("{} 不包含 RedForge canary", value))?;
+    let tag = &canary[CANARY_PREFIX.len() + 1..CANARY_PREFIX.len() + 1 + SCAN_TAG_LEN];
+    let root = CANARY_ROOT.get().ok_or("canary 紀錄目錄未初始化")?;
+
+    let entries = std::fs::read_dir(root).map_err(|e| format!("無法讀取 canary 紀錄目錄: {}", e))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(scan_id) = name.strip_suffix(".jsonl") else { continue };
+        if scan_tag(scan_id) != tag {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else { continue };
+        let found = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<CanaryRecord>(line).ok())
+            .find(|record| record.value == canary);
+        if let Some(record) = found {
+            return Ok(record);
+        }
+    }
+
+    Err(format!("找不到 canary {} 的紀錄", canary))
+}
+
+fn record_path(scan_id: &str) -> Result<PathBuf, String> {
+    // scan id 為 UUID，其他字元一律拒絕，避免路徑跳脫
+    if scan_id.is_empty() || !scan_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("無效的掃描 ID: {}", scan_id));
+    }
+    let root = CANARY_ROOT.get().ok_or("canary 紀錄目錄未初始化")?;
+    Ok(root.join(format!("{}.jsonl", scan_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_canaries_from_any_text() {
+        let line = r#"10.0.0.5 - - "GET /?q=<script>rfg-3f9a2c-12</script> HTTP/1.1" 200 ref=RFG-3F9A2C-7.oob.example"#;
+        assert_eq!(extract(line), vec!["rfg-3f9a2c-12", "rfg-3f9a2c-7"]);
+        assert!(extract("rfg-3f9a-1 XSS evil.com").is_empty());
+    }
+
+    #[test]
+    fn issued_canaries_resolve_to_their_scan_and_check() {
+        init(&std::env::temp_dir().join(format!("redforge-canary-{}", uuid::Uuid::new_v4())));
+        let scan_id = uuid::Uuid::new_v4().to_string();
+        let registry = CanaryRegistry::open(&scan_id);
+
+        let first = registry.issue("owasp.a03", "xss");
+        let second = registry.issue("owasp.a10", "ssrf");
+        assert_eq!(first, format!("rfg-{}-1", scan_tag(&scan_id)));
+
+        let record = lookup(&format!("http://{}.callback.example/", second)).unwrap();
+        assert_eq!(record.scan_id, scan_id);
+        assert_eq!(record.check_id, "owasp.a10");
+        assert_eq!(record.purpose, "ssrf");
+    }
+
+    #[test]
+    fn scan_tag_is_padded_and_lowercase() {
+        assert_eq!(scan_tag("AB-c"), "abc000");
+        assert_eq!(scan_tag("3F9A2C1D-0000"), "3f9a2c");
+    }
+}
@@ -0,0 +1,22 @@
+/**
+ * Attack Chain Rule Commands
+ *
+ * 列出與擴充將同時出現的發現組成攻擊鏈的規則
+ */
+
+use crate::attack_chains::{self, ChainRule};
+
+/// 列出內建與自訂的攻擊鏈規則
+#[tauri::command]
+pub async fn list_attack_chain_rules() -> Result<Vec<ChainRule>, String> {
+    attack_chains::list_rules()
+}
+
+/// 新增或取代自訂攻擊鏈規則；之後完成的掃描才會套用
+#[tauri::command]
+pub async fn save_attack_chain_rule(rule: ChainRule) -> Result<(), String> {
+    let id = rule.id.clone();
+    attack_chains::save_rule(rule)?;
+    tracing::info!("⛓️  已儲存攻擊鏈規則 {}", id);
+    Ok(())
+}
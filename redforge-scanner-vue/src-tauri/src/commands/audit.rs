@@ -0,0 +1,71 @@
+/**
+ * Audit Log Commands
+ *
+ * 匯出掃描的請求稽核紀錄，以協作金鑰 (Ed25519) 簽章後交付給客戶，證明送出了哪些請求與時間
+ */
+
+use crate::audit;
+use crate::commands::scan::ScanState;
+use crate::keys;
+use crate::output::{write_atomic, WrittenFile};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+/// 簽章的稽核紀錄檔
+///
+/// signature 對 payload 的 UTF-8 位元組簽章；payload 第一行為掃描資訊，其後每行一筆請求紀錄 (JSONL)
+#[derive(Debug, Serialize)]
+struct SignedAuditLog {
+    format: &'static str,
+    scan_id: String,
+    exported_at: String,
+    entry_count: usize,
+    algorithm: &'static str,
+    public_key: String,
+    signature: String,
+    payload: String,
+}
+
+/// 匯出簽章的稽核紀錄到 path；目的地已存在時改用編號檔名，除非指定 overwrite
+#[tauri::command]
+pub async fn export_audit_log(
+    scan_id: String,
+    path: String,
+    overwrite: Option<bool>,
+    state: State<'_, ScanState>,
+) -> Result<WrittenFile, String> {
+    // 執行中的掃描先寫出緩衝區，匯出內容才會包含到目前為止的請求
+    if let Some(control) = state.controls.lock().await.get(&scan_id) {
+        control.flush_audit();
+    }
+
+    let log = audit::read_log(&scan_id)?;
+    let entries: Vec<&str> = log.lines().filter(|line| !line.trim().is_empty()).collect();
+    let exported_at = Utc::now().to_rfc3339();
+
+    let header = serde_json::json!({
+        "scan_id": scan_id,
+        "exported_at": exported_at,
+        "entry_count": entries.len(),
+    });
+    let payload = format!("{}\n{}\n", header, entries.join("\n"));
+    let signature = keys::sign(payload.as_bytes())?;
+
+    let signed = SignedAuditLog {
+        format: "redforge-audit-log/1",
+        scan_id: scan_id.clone(),
+        exported_at,
+        entry_count: entries.len(),
+        algorithm: signature.algorithm,
+        public_key: signature.public_key,
+        signature: signature.signature,
+        payload,
+    };
+    let content = serde_json::to_string_pretty(&signed).map_err(|e| e.to_string())?;
+
+    let written = write_atomic(Path::new(&path), content.as_bytes(), overwrite.unwrap_or(false))?;
+    tracing::info!("📜 已匯出稽核紀錄 {} ({} 筆請求) 到 {}", scan_id, entries.len(), written.path);
+    Ok(written)
+}
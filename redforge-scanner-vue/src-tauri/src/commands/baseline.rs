@@ -0,0 +1,266 @@
+/**
+ * Baseline Commands
+ *
+ * 為已強化的目標保存核可的基準 (發現、安全標頭、TLS 評級)，
+ * 之後的掃描只突顯與基準不同的部分
+ */
+
+use crate::commands::collaboration::{finding_fingerprint, finding_location};
+use crate::commands::scan::{ScanReport, ScanState};
+use crate::models::{ScanResult, ScanType};
+use crate::scanners::ssl_scanner::grade_rank;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use tauri::State;
+
+/// 目標的核可基準
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Baseline {
+    pub target: String,
+    pub source_task_id: String,
+    pub findings: Vec<BaselineFinding>,
+    pub secure_headers: Vec<String>,
+    pub ssl_grade: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BaselineFinding {
+    pub fingerprint: String,
+    pub title: String,
+    pub severity: Option<String>,
+}
+
+/// 掃描結果與基準的比較
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BaselineComparison {
+    pub baseline_task_id: String,
+    pub baseline_created_at: DateTime<Utc>,
+    /// 與基準相同的發現 id，視為已接受 (baseline)
+    pub accepted_finding_ids: Vec<String>,
+    /// 基準中沒有的新發現 id
+    pub new_finding_ids: Vec<String>,
+    /// 基準中有、這次沒有重現的發現 (improved since baseline)
+    pub improved: Vec<BaselineFinding>,
+    /// 基準中安全、這次缺少或不安全的標頭
+    pub header_regressions: Vec<String>,
+    pub ssl_grade_before: Option<String>,
+    pub ssl_grade_after: Option<String>,
+    pub ssl_grade_regressed: bool,
+}
+
+impl BaselineComparison {
+    pub fn has_deviations(&self) -> bool {
+        !self.new_finding_ids.is_empty() || !self.header_regressions.is_empty() || self.ssl_grade_regressed
+    }
+}
+
+/// 以指定掃描的結果作為該目標的核可基準，覆蓋既有基準
+#[tauri::command]
+pub async fn set_baseline(
+    task_id: String,
+    state: State<'_, ScanState>,
+) -> Result<Baseline, String> {
+    let results = state.scan_results.lock().await;
+    let report = results.get(&task_id).ok_or_else(|| "找不到掃描報告".to_string())?;
+    let baseline = snapshot_baseline(report);
+    drop(results);
+
+    tracing::info!("📌 設定基準: {} (來源掃描 {})", baseline.target, task_id);
+    if let Some(store) = state.store() {
+        store.save_baseline(&baseline).await.map_err(|e| format!("無法保存基準: {}", e))?;
+    }
+    state.baselines.lock().await.insert(baseline.target.clone(), baseline.clone());
+    Ok(baseline)
+}
+
+/// 移除目標的基準，回傳是否有基準被移除
+#[tauri::command]
+pub async fn clear_baseline(
+    target: String,
+    state: State<'_, ScanState>,
+) -> Result<bool, String> {
+    let key = baseline_key(&target);
+    if let Some(store) = state.store() {
+        store.delete_baseline(&key).await.map_err(|e| format!("無法移除基準: {}", e))?;
+    }
+    Ok(state.baselines.lock().await.remove(&key).is_some())
+}
+
+#[tauri::command]
+pub async fn get_baseline(
+    target: String,
+    state: State<'_, ScanState>,
+) -> Result<Option<Baseline>, String> {
+    Ok(state.baselines.lock().await.get(&baseline_key(&target)).cloned())
+}
+
+/// 基準以目標 URL 為鍵，忽略大小寫與結尾的 "/"
+pub fn baseline_key(target: &str) -> String {
+    target.trim().trim_end_matches('/').to_lowercase()
+}
+
+pub fn snapshot_baseline(report: &ScanReport) -> Baseline {
+    let target = &report.task.target_url;
+
+    Baseline {
+        target: baseline_key(target),
+        source_task_id: report.task.id.clone(),
+        findings: report.vulnerabilities
+            .iter()
+            .map(|finding| BaselineFinding {
+                fingerprint: fingerprint(finding, target),
+                title: finding.title.clone(),
+                severity: finding.severity.as_ref().map(|s| s.to_string()),
+            })
+            .collect(),
+        secure_headers: report.headers
+            .iter()
+            .filter(|h| h.is_present && h.is_secure)
+            .map(|h| h.header_name.to_lowercase())
+            .collect(),
+        ssl_grade: report.primary_ssl().and_then(|ssl| ssl.grade.clone()),
+        created_at: Utc::now(),
+    }
+}
+
+/// 發現的識別：與匯入去重相同，位置優先使用 affected_url，沒有時為掃描目標
+fn fingerprint(finding: &ScanResult, target: &str) -> String {
+    let affected_url = finding.details.as_ref().and_then(|d| d.affected_url.as_deref());
+    finding_fingerprint(
+        &format!("{:?}", finding.result_type).to_lowercase(),
+        &finding.title,
+        finding_location(affected_url, target),
+    )
+}
+
+/// 比較掃描報告與基準
+pub fn compare_to_baseline(report: &ScanReport, baseline: &Baseline) -> BaselineComparison {
+    let target = &report.task.target_url;
+    let mut accepted_finding_ids = Vec::new();
+    let mut new_finding_ids = Vec::new();
+    let mut reproduced = std::collections::HashSet::new();
+
+    for finding in &report.vulnerabilities {
+        let fingerprint = fingerprint(finding, target);
+        if baseline.findings.iter().any(|b| b.fingerprint == fingerprint) {
+            accepted_finding_ids.push(finding.id.clone());
+            reproduced.insert(fingerprint);
+        } else {
+            new_finding_ids.push(finding.id.clone());
+        }
+    }
+
+    // 只掃描標頭或 SSL 時沒有執行漏洞檢查，不能視為已改善
+    let improved = if matches!(report.task.scan_type, ScanType::Headers | ScanType::Ssl) {
+        Vec::new()
+    } else {
+        baseline.findings
+            .iter()
+            .filter(|b| !reproduced.contains(&b.fingerprint))
+            .cloned()
+            .collect()
+    };
+
+    // 只在這次有掃描標頭時比較，避免 SSL 掃描被誤判為標頭全部退化
+    let header_regressions = if report.headers.is_empty() {
+        Vec::new()
+    } else {
+        baseline.secure_headers
+            .iter()
+            .filter(|name| {
+                !report.headers
+                    .iter()
+                    .any(|h| h.header_name.to_lowercase() == **name && h.is_present && h.is_secure)
+            })
+            .cloned()
+            .collect()
+    };
+
+    let ssl_grade_after = report.primary_ssl().and_then(|ssl| ssl.grade.clone());
+    let ssl_grade_regressed = match (&baseline.ssl_grade, &ssl_grade_after) {
+        (Some(before), Some(after)) => grade_rank(after) > grade_rank(before),
+        _ => false,
+    };
+
+    BaselineComparison {
+        baseline_task_id: baseline.source_task_id.clone(),
+        baseline_created_at: baseline.created_at,
+        accepted_finding_ids,
+        new_finding_ids,
+        improved,
+        header_regressions,
+        ssl_grade_before: baseline.ssl_grade.clone(),
+        ssl_grade_after,
+        ssl_grade_regressed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::store::Store;
+
+    fn finding(id: &str, affected_url: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "task_id": "scan-1",
+            "result_type": "vulnerability",
+            "severity": "high",
+            "title": "Reflected XSS",
+            "description": null,
+            "raw_data": null,
+            "created_at": "2026-01-05T10:00:00Z",
+            "details": { "affected_url": affected_url },
+        })
+    }
+
+    fn report(findings: Vec<serde_json::Value>) -> ScanReport {
+        serde_json::from_value(serde_json::json!({
+            "task": {
+                "id": "scan-1",
+                "target_url": "https://example.com/",
+                "scan_type": "full",
+                "status": "completed",
+                "created_at": "2026-01-05T10:00:00Z",
+            },
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": findings,
+        }))
+        .expect("valid report")
+    }
+
+    #[test]
+    fn same_title_at_another_url_is_a_new_finding() {
+        let baseline = snapshot_baseline(&report(vec![finding("xss-search", "https://example.com/search")]));
+        let later = report(vec![
+            finding("xss-search-again", "https://example.com/search/"),
+            finding("xss-profile", "https://example.com/profile"),
+        ]);
+
+        let comparison = compare_to_baseline(&later, &baseline);
+        assert_eq!(comparison.accepted_finding_ids, vec!["xss-search-again"]);
+        assert_eq!(comparison.new_finding_ids, vec!["xss-profile"]);
+        assert!(comparison.improved.is_empty());
+    }
+
+    #[tokio::test]
+    async fn baselines_survive_a_restart() {
+        let store = Store::open_migrated().await;
+        let baseline = snapshot_baseline(&report(vec![finding("xss-search", "https://example.com/search")]));
+        store.save_baseline(&baseline).await.expect("save baseline");
+
+        let state = ScanState::new(Some(store.clone()), None, None);
+        state.restore().await;
+        let restored = state.baselines.lock().await.get(&baseline_key("https://EXAMPLE.com")).cloned().expect("restored baseline");
+        assert_eq!(restored.source_task_id, "scan-1");
+        assert_eq!(restored.findings[0].fingerprint, baseline.findings[0].fingerprint);
+
+        store.delete_baseline(&baseline.target).await.expect("delete baseline");
+        let state = ScanState::new(Some(store), None, None);
+        state.restore().await;
+        assert!(state.baselines.lock().await.is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+/**
+ * Canary Commands
+ *
+ * 將目標日誌或 OOB 回呼中看到的 canary 解析回產生它的掃描與檢查
+ */
+
+use crate::canary::{self, CanaryRecord};
+
+/// 查詢 canary 的來源；value 可以是包含 canary 的整行日誌
+#[tauri::command]
+pub async fn lookup_canary(value: String) -> Result<CanaryRecord, String> {
+    canary::lookup(&value)
+}
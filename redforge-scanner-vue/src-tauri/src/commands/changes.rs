@@ -0,0 +1,72 @@
+/**
+ * Target Change Detection Commands
+ *
+ * 只重新取得上次掃描記錄的頁面並比較內容雜湊，判斷目標是否有變動，不需要完整的重新掃描
+ */
+
+use crate::commands::baseline::baseline_key;
+use crate::commands::scan::ScanState;
+use crate::models::ScanOptions;
+use crate::scanners::content_hash::{self, ContentHasher, PageChange, PageHash};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use tauri::State;
+
+/// 與上次掃描相比的變動
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TargetChanges {
+    pub target: String,
+    /// 比較對象：最近一次記錄了內容雜湊的掃描
+    pub previous_task_id: String,
+    pub checked_at: DateTime<Utc>,
+    /// 任一頁面變動
+    pub changed: bool,
+    pub pages: Vec<PageChange>,
+}
+
+/// 目標最近一次記錄了內容雜湊的掃描
+pub(crate) async fn latest_hashes(state: &ScanState, target: &str) -> Option<(String, Vec<PageHash>)> {
+    let key = baseline_key(target);
+    state
+        .scan_results
+        .lock()
+        .await
+        .values()
+        .filter(|report| baseline_key(&report.task.target_url) == key && !report.content_hashes.is_empty())
+        .max_by_key(|report| report.task.completed_at.or(report.task.started_at))
+        .map(|report| (report.task.id.clone(), report.content_hashes.clone()))
+}
+
+/// 重新取得 previous 中的 URL 並比較
+pub(crate) async fn compare_with(target: &str, previous_task_id: String, previous: &[PageHash], options: &ScanOptions) -> Result<TargetChanges, String> {
+    let hasher = ContentHasher::new(options).map_err(|e| format!("無法建立 HTTP 用戶端: {}", e))?;
+    let urls: Vec<String> = previous.iter().map(|page| page.url.clone()).collect();
+    let pages = content_hash::compare(previous, hasher.hash_all(&urls).await);
+
+    Ok(TargetChanges {
+        target: target.to_string(),
+        previous_task_id,
+        checked_at: Utc::now(),
+        changed: pages.iter().any(|page| page.changed),
+        pages,
+    })
+}
+
+/// 檢查目標自上次掃描後是否有變動，用來決定是否值得執行完整的重新掃描
+#[tauri::command]
+pub async fn check_target_changes(target: String, state: State<'_, ScanState>) -> Result<TargetChanges, String> {
+    let (previous_task_id, previous) = latest_hashes(state.inner(), &target)
+        .await
+        .ok_or_else(|| format!("目標 {} 沒有記錄內容雜湊的掃描", target))?;
+    let options = state.scan_options.lock().await.get(&previous_task_id).cloned().unwrap_or_default();
+
+    let changes = compare_with(&target, previous_task_id, &previous, &options).await?;
+    let changed: Vec<&str> = changes.pages.iter().filter(|p| p.changed).map(|p| p.url.as_str()).collect();
+    if changed.is_empty() {
+        tracing::info!("🟰 {} 自上次掃描後沒有變動", target);
+    } else {
+        tracing::info!("🔄 {} 有 {} 個頁面變動: {}", target, changed.len(), changed.join(", "));
+    }
+    Ok(changes)
+}
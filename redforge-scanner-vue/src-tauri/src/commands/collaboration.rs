@@ -5,16 +5,22 @@
  * Provides data export, import, and deduplication functionality
  */
 
+use crate::database::store::StoreError;
 use crate::models::*;
+use crate::output::{write_atomic, WrittenFile};
+use crate::redaction::{RedactedField, Redactor};
+use crate::scanners::tech_detector::canonical_technology_name;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashSet;
+use std::path::Path;
 
 // ============================================================================
 // Export Data Structures
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExportMetadata {
     pub version: String,
     pub format: String,
@@ -23,9 +29,12 @@ pub struct ExportMetadata {
     pub team_id: Option<String>,
     pub exported_at: String,
     pub checksum: Option<String>,
+    /// Set when a redaction profile was applied to this export
+    #[serde(default)]
+    pub redaction: Option<crate::redaction::RedactionInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExportScanTask {
     pub id: String,
     pub name: String,
@@ -35,9 +44,16 @@ pub struct ExportScanTask {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub created_by: String,
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
+    #[serde(default)]
+    pub config_hash: Option<String>,
+    /// What each check and OWASP category actually tested, so an empty finding list is not read as a clean result
+    #[serde(default)]
+    pub coverage: Vec<CheckCoverage>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExportFinding {
     pub id: String,
     pub scan_id: String,
@@ -53,18 +69,28 @@ pub struct ExportFinding {
     pub discovered_by: String,
     pub cvss_score: Option<f64>,
     pub cve_id: Option<String>,
+    /// e.g. ["CWE-89"]; findings from other tools may leave this empty and carry CWE ids in the evidence
+    #[serde(default)]
+    pub cwe_ids: Vec<String>,
+    /// e.g. "A03:2021"
+    #[serde(default)]
+    pub owasp_category: Option<String>,
+    #[serde(default)]
+    pub payload: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExportData {
     pub metadata: ExportMetadata,
     pub scans: Vec<ExportScanTask>,
     pub findings: Vec<ExportFinding>,
     pub annotations: Option<Vec<Annotation>>,
     pub assets: Option<Vec<Asset>>,
+    #[serde(default)]
+    pub baselines: Option<Vec<crate::commands::baseline::Baseline>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Annotation {
     pub id: String,
     pub finding_id: String,
@@ -75,7 +101,7 @@ pub struct Annotation {
     pub priority: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Asset {
     pub id: String,
     pub hostname: String,
@@ -90,15 +116,58 @@ pub struct Asset {
 // Import Structures
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImportResult {
     pub success: bool,
     pub imported: ImportCounts,
     pub skipped: ImportCounts,
     pub errors: Vec<String>,
+    /// Nothing was written; `imported` and `plan` describe what the import would do
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Exactly which records were (or would be) imported, skipped and merged
+    #[serde(default)]
+    pub plan: Option<ImportPlan>,
+    /// Pass back as `plan_token` to apply this plan; the import fails if the data or state changed since
+    #[serde(default)]
+    pub plan_token: Option<String>,
+    /// How many existing records the import replaces, and whether that crosses the guard threshold
+    #[serde(default)]
+    pub impact: Option<ImportImpact>,
+    /// Nothing was written because the impact crossed the threshold; retry with `confirm_large_import`
+    #[serde(default)]
+    pub requires_confirmation: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Existing records an import would overwrite, checked against the mass-overwrite guard
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImportImpact {
+    pub overwritten_scans: u32,
+    /// Findings of the overwritten scans, including their triage
+    pub overwritten_findings: u32,
+    /// Scans currently loaded, the base for `overwritten_percent`
+    pub existing_scans: u32,
+    pub overwritten_percent: f64,
+    pub max_overwrites: u32,
+    pub max_overwrite_percent: f64,
+    pub exceeds_threshold: bool,
+}
+
+/// The records an import touches, by id
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ImportPlan {
+    pub scans: Vec<String>,
+    pub findings: Vec<String>,
+    pub skipped_scans: Vec<String>,
+    pub skipped_findings: Vec<String>,
+    /// Scans that replace one already loaded (only when duplicates are not skipped)
+    pub overwritten_scans: Vec<String>,
+    /// Targets whose shared baseline is added (targets with a local baseline keep it)
+    pub merged_baselines: Vec<String>,
+    pub skipped_baselines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImportCounts {
     pub scans: i32,
     pub findings: i32,
@@ -106,16 +175,47 @@ pub struct ImportCounts {
     pub assets: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DeduplicateResult {
     pub duplicates: DuplicateIds,
     pub unique: ExportData,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct DuplicateIds {
-    pub scans: Vec<String>,
-    pub findings: Vec<String>,
+    pub scans: Vec<DuplicateRecord>,
+    pub findings: Vec<DuplicateRecord>,
+    pub annotations: Vec<DuplicateRecord>,
+    pub assets: Vec<DuplicateRecord>,
+}
+
+/// A record from the import file that already exists, and where
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateRecord {
+    pub id: String,
+    pub source: DuplicateSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateSource {
+    /// Repeated within the import file itself
+    ImportFile,
+    /// Already loaded in the running app
+    InMemory,
+    /// Already stored in redforge.db
+    Persisted,
+}
+
+/// Records the import is checked against, gathered before touching state
+#[derive(Debug, Default)]
+struct ExistingRecords {
+    memory_scan_ids: HashSet<String>,
+    memory_fingerprints: HashSet<String>,
+    persisted_scan_ids: HashSet<String>,
+    persisted_fingerprints: HashSet<String>,
+    persisted_annotation_ids: HashSet<String>,
+    persisted_asset_hostnames: HashSet<String>,
 }
 
 // ============================================================================
@@ -126,15 +226,29 @@ pub struct DuplicateIds {
 ///
 /// Retrieves scan data from the database and formats it for export
 /// Encryption is handled on the frontend
+///
+/// `include_scans` and `include_findings` both default to true. The deprecated
+/// `include_findings_only` flag is still accepted and maps to scans off, findings on.
+/// Findings are only exported when their parent scan passes the `scan_ids` / `since` filters.
+///
+/// When `redaction_profile` is set the profile is applied to the whole export before it is returned,
+/// and `metadata.redaction` records the profile and the local pseudonym mapping id.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn export_scan_data(
     scan_ids: Option<Vec<String>>,
-    include_findings_only: bool,
+    include_scans: Option<bool>,
+    include_findings: Option<bool>,
+    include_findings_only: Option<bool>,
     include_annotations: bool,
     include_assets: bool,
+    include_baselines: Option<bool>,
     since: Option<String>,
+    redaction_profile: Option<String>,
     state: tauri::State<'_, crate::commands::scan::ScanState>,
 ) -> Result<ExportData, String> {
+    let (include_scans, include_findings) = resolve_export_sections(include_scans, include_findings, include_findings_only);
+
     // Convert since string to DateTime if provided
     let since_dt: Option<DateTime<Utc>> = since
         .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
@@ -148,6 +262,7 @@ pub async fn export_scan_data(
         team_id: None,
         exported_at: Utc::now().to_rfc3339(),
         checksum: None,
+        redaction: None,
     };
 
     // Get real scan data from state
@@ -155,59 +270,7 @@ pub async fn export_scan_data(
     let results = state.scan_results.lock().await;
 
     // Filter scans based on scan_ids or since
-    let mut scans = Vec::new();
-    let mut findings = Vec::new();
-
-    for task in tasks.iter() {
-        // Filter by scan_ids if provided
-        if let Some(ref ids) = scan_ids {
-            if !ids.contains(&task.id) {
-                continue;
-            }
-        }
-
-        // Filter by since date if provided
-        if let Some(ref since_date) = since_dt {
-            if &task.created_at < since_date {
-                continue;
-            }
-        }
-
-        // Add scan task
-        scans.push(ExportScanTask {
-            id: task.id.clone(),
-            name: format!("{} - {}", task.scan_type.to_string(), task.target_url),
-            target: task.target_url.clone(),
-            status: task.status.to_string(),
-            created_at: task.created_at.to_rfc3339(),
-            started_at: task.started_at.map(|dt| dt.to_rfc3339()),
-            completed_at: task.completed_at.map(|dt| dt.to_rfc3339()),
-            created_by: "user".to_string(),
-        });
-
-        // Get scan results/findings
-        if !include_findings_only {
-            if let Some(report) = results.get(&task.id) {
-                for vuln in &report.vulnerabilities {
-                    findings.push(ExportFinding {
-                        id: vuln.id.clone(),
-                        scan_id: task.id.clone(),
-                        finding_type: format!("{:?}", vuln.result_type).to_lowercase(),
-                        severity: vuln.severity.as_ref().map(|s| s.to_string()).unwrap_or("info".to_string()),
-                        title: vuln.title.clone(),
-                        description: vuln.description.clone().unwrap_or_default(),
-                        affected_url: Some(task.target_url.clone()),
-                        evidence: vuln.raw_data.clone(),
-                        recommendation: None,
-                        discovered_at: vuln.created_at.to_rfc3339(),
-                        discovered_by: "redforge".to_string(),
-                        cvss_score: None,
-                        cve_id: None,
-                    });
-                }
-            }
-        }
-    }
+    let (selected_scans, findings) = select_for_export(&tasks, &results, scan_ids.as_deref(), since_dt, include_findings);
 
     // Optional annotations (empty for now)
     let annotations = if include_annotations {
@@ -216,126 +279,375 @@ pub async fn export_scan_data(
         None
     };
 
-    // Optional assets (extract from scan results)
+    // Optional assets, one per scanned host, enriched from the persisted inventory
     let assets = if include_assets {
-        let mut asset_list = Vec::new();
-        for task in scans.iter() {
-            if let Some(report) = results.get(&task.id) {
-                // Extract hostname from URL
-                let hostname = task.target
-                    .trim_start_matches("https://")
-                    .trim_start_matches("http://")
-                    .split('/')
-                    .next()
-                    .unwrap_or(&task.target)
-                    .to_string();
-
-                // Collect technologies
-                let technologies: Vec<String> = report.technologies
-                    .iter()
-                    .map(|t| t.technology_name.clone())
-                    .collect();
-
-                if !technologies.is_empty() || report.ssl_analysis.is_some() {
-                    asset_list.push(Asset {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        hostname: hostname.clone(),
-                        ip_address: None,
-                        ports: None,
-                        services: None,
-                        technologies: Some(technologies),
-                        discovered_at: task.created_at.clone(),
-                    });
-                }
-            }
+        let mut asset_list = build_assets(&selected_scans, &results);
+        drop(results);
+        drop(tasks);
+
+        if let Some(store) = state.store() {
+            let hostnames: Vec<String> = asset_list.iter().map(|a| a.hostname.clone()).collect();
+            let persisted = store.assets_by_hostnames(&hostnames).await
+                .map_err(|e| format!("資料庫查詢失敗: {}", e))?;
+            merge_inventory(&mut asset_list, persisted);
         }
         Some(asset_list)
     } else {
         None
     };
 
-    Ok(ExportData {
+    // Optional baselines for the exported targets, for sharing approved states with the team
+    let baselines = if include_baselines.unwrap_or(false) {
+        let stored = state.baselines.lock().await;
+        let mut list: Vec<_> = stored
+            .values()
+            .filter(|b| selected_scans.iter().any(|s| crate::commands::baseline::baseline_key(&s.target) == b.target))
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.target.cmp(&b.target));
+        Some(list)
+    } else {
+        None
+    };
+
+    // Scan rows are still needed above for asset extraction, drop them only at the end
+    let scans = if include_scans { selected_scans } else { Vec::new() };
+
+    let data = ExportData {
         metadata,
         scans,
         findings,
         annotations,
         assets,
-    })
+        baselines,
+    };
+
+    match redaction_profile {
+        Some(profile) => redact_export(data, &profile),
+        None => Ok(data),
+    }
 }
 
-/// Deduplicate imported data before inserting into database
+/// Evidence for an exported finding: the raw data with the captured request/response under `http_exchange`
 ///
-/// Checks for duplicate scans and findings based on IDs and similarity
-#[tauri::command]
-pub async fn deduplicate_import_data(data: ExportData) -> Result<ExportData, String> {
-    // TODO: Implement actual database queries to check for existing data
-    // For now, we'll just check for duplicates within the import data itself
+/// Raw data that is not a JSON object is kept under `raw_data` so nothing is lost when an exchange is added
+pub(crate) fn export_evidence(finding: &ScanResult) -> Option<String> {
+    let Some(exchange) = finding.details.as_ref().and_then(|d| d.exchange.as_ref()) else {
+        return finding.raw_data.clone();
+    };
+    let mut evidence = match finding.raw_data.as_deref().map(serde_json::from_str::<serde_json::Value>) {
+        Some(Ok(value)) if value.is_object() => value,
+        Some(_) => serde_json::json!({ "raw_data": finding.raw_data }),
+        None => serde_json::json!({}),
+    };
+    evidence["http_exchange"] = serde_json::to_value(exchange).unwrap_or_default();
+    Some(evidence.to_string())
+}
 
-    let mut unique_scans = Vec::new();
-    let mut unique_findings = Vec::new();
-    let mut seen_scan_ids = HashSet::new();
-    let mut seen_finding_ids = HashSet::new();
+/// The request/response captured by RedForge, when the evidence carries one
+fn imported_exchange(finding: &ExportFinding) -> Option<HttpExchange> {
+    let evidence: serde_json::Value = serde_json::from_str(finding.evidence.as_deref()?).ok()?;
+    serde_json::from_value(evidence.get("http_exchange")?.clone()).ok()
+}
 
-    // Deduplicate scans
-    for scan in data.scans {
-        if !seen_scan_ids.contains(&scan.id) {
-            seen_scan_ids.insert(scan.id.clone());
-            unique_scans.push(scan);
+/// Apply a redaction profile to an export
+///
+/// Dropped fields are removed first, then every remaining string value goes through the
+/// credential, regex and hostname rules, so nothing matching them survives in any field
+fn redact_export(mut data: ExportData, profile: &str) -> Result<ExportData, String> {
+    let mut redactor = Redactor::new(profile, None)?;
+
+    for finding in &mut data.findings {
+        if redactor.drops(RedactedField::Evidence) {
+            finding.evidence = None;
+        }
+        if redactor.drops(RedactedField::Description) {
+            finding.description = String::new();
+        }
+        if redactor.drops(RedactedField::Recommendation) {
+            finding.recommendation = None;
+        }
+    }
+    if redactor.drops(RedactedField::ScanConfig) {
+        for scan in &mut data.scans {
+            scan.config = None;
+            scan.config_hash = None;
         }
     }
+    if redactor.drops(RedactedField::Annotations) {
+        data.annotations = None;
+    }
+    if redactor.drops(RedactedField::Assets) {
+        data.assets = None;
+    }
+    if redactor.drops(RedactedField::Baselines) {
+        data.baselines = None;
+    }
 
-    // Deduplicate findings
-    for finding in data.findings {
-        if !seen_finding_ids.contains(&finding.id) {
-            seen_finding_ids.insert(finding.id.clone());
-            unique_findings.push(finding);
+    // Asset hostnames and IPs are not URLs, so they are registered explicitly
+    let hosts: Vec<String> = data
+        .assets
+        .iter()
+        .flatten()
+        .flat_map(|asset| std::iter::once(asset.hostname.clone()).chain(asset.ip_address.clone()))
+        .collect();
+
+    let value = serde_json::to_value(&data).map_err(|e| e.to_string())?;
+    let mut data: ExportData = serde_json::from_value(redactor.redact_value(value, &hosts))
+        .map_err(|e| format!("遮蔽後的匯出資料無效: {}", e))?;
+
+    let info = redactor.finish()?;
+    tracing::info!("🕶️  已套用遮蔽設定檔 {} 到匯出資料", info.profile);
+    data.metadata.redaction = Some(info);
+    Ok(data)
+}
+
+/// Build one asset per scanned host from the selected scan reports
+///
+/// Ports and services come from the scanned URL and any open ports found; discovered_at is the
+/// earliest time the host was observed
+fn build_assets(
+    scans: &[ExportScanTask],
+    results: &std::collections::HashMap<String, crate::commands::scan::ScanReport>,
+) -> Vec<Asset> {
+    let mut assets: Vec<(Asset, DateTime<Utc>)> = Vec::new();
+
+    for scan in scans {
+        let Some(report) = results.get(&scan.id) else { continue };
+        let Ok(url) = reqwest::Url::parse(&report.task.target_url) else { continue };
+        let Some(hostname) = url.host_str().map(|h| h.trim_matches(|c| c == '[' || c == ']').to_string()) else { continue };
+        let observed_at = report.task.completed_at
+            .or(report.task.started_at)
+            .unwrap_or(report.task.created_at);
+
+        let index = match assets.iter().position(|(a, _)| a.hostname == hostname) {
+            Some(index) => index,
+            None => {
+                assets.push((
+                    Asset {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        ip_address: hostname.parse::<std::net::IpAddr>().ok().map(|ip| ip.to_string()),
+                        hostname,
+                        ports: Some(Vec::new()),
+                        services: Some(Vec::new()),
+                        technologies: Some(Vec::new()),
+                        discovered_at: observed_at.to_rfc3339(),
+                    },
+                    observed_at,
+                ));
+                assets.len() - 1
+            }
+        };
+
+        let (asset, first_seen) = &mut assets[index];
+        if observed_at < *first_seen {
+            *first_seen = observed_at;
+            asset.discovered_at = observed_at.to_rfc3339();
+        }
+
+        if let Some(port) = url.port_or_known_default() {
+            push_unique(&mut asset.ports, port);
+            push_unique(&mut asset.services, url.scheme().to_string());
+        }
+        for open_port in &report.open_ports {
+            push_unique(&mut asset.ports, open_port.port);
+            if let Some(service) = &open_port.service_name {
+                push_unique(&mut asset.services, service.clone());
+            }
+        }
+        for technology in &report.technologies {
+            push_unique(&mut asset.technologies, canonical_technology_name(&technology.technology_name));
         }
     }
 
-    // Deduplicate annotations if present
-    let unique_annotations = data.annotations.map(|annotations| {
-        let mut unique = Vec::new();
-        let mut seen_ids = HashSet::new();
-        for annotation in annotations {
-            if !seen_ids.contains(&annotation.id) {
-                seen_ids.insert(annotation.id.clone());
-                unique.push(annotation);
+    assets
+        .into_iter()
+        .map(|(mut asset, _)| {
+            if let Some(ports) = asset.ports.as_mut() {
+                ports.sort_unstable();
             }
+            asset
+        })
+        .collect()
+}
+
+/// Merge persisted inventory entries into exported assets, keeping the inventory id
+/// so a re-import maps back onto the same entry
+fn merge_inventory(assets: &mut [Asset], persisted: Vec<crate::database::store::PersistedAsset>) {
+    for entry in persisted {
+        let Some(asset) = assets.iter_mut().find(|a| a.hostname == entry.hostname) else { continue };
+
+        asset.id = entry.id;
+        if asset.ip_address.is_none() {
+            asset.ip_address = entry.ip_address;
+        }
+        for port in entry.ports {
+            push_unique(&mut asset.ports, port);
+        }
+        for service in entry.services {
+            push_unique(&mut asset.services, service);
+        }
+        for technology in entry.technologies {
+            push_unique(&mut asset.technologies, canonical_technology_name(&technology));
+        }
+        if let Some(ports) = asset.ports.as_mut() {
+            ports.sort_unstable();
         }
-        unique
-    });
 
-    // Deduplicate assets if present
-    let unique_assets = data.assets.map(|assets| {
-        let mut unique = Vec::new();
-        let mut seen_ids = HashSet::new();
-        for asset in assets {
-            if !seen_ids.contains(&asset.id) {
-                seen_ids.insert(asset.id.clone());
-                unique.push(asset);
+        let persisted_first_seen = DateTime::parse_from_rfc3339(&entry.discovered_at)
+            .map(|dt| dt.with_timezone(&Utc));
+        let exported_first_seen = DateTime::parse_from_rfc3339(&asset.discovered_at)
+            .map(|dt| dt.with_timezone(&Utc));
+        if let (Ok(persisted), Ok(exported)) = (persisted_first_seen, exported_first_seen) {
+            if persisted < exported {
+                asset.discovered_at = persisted.to_rfc3339();
             }
         }
-        unique
-    });
+    }
+}
 
-    Ok(ExportData {
-        metadata: data.metadata,
-        scans: unique_scans,
-        findings: unique_findings,
-        annotations: unique_annotations,
-        assets: unique_assets,
-    })
+/// Write imported assets to the inventory
+///
+/// An asset whose hostname is already in the inventory is merged into that entry and keeps its id.
+/// Hostnames must be unique within `assets`. Returns the number of entries written
+async fn import_assets(store: &crate::database::store::Store, mut assets: Vec<Asset>) -> Result<usize, StoreError> {
+    if assets.is_empty() {
+        return Ok(0);
+    }
+
+    let hostnames: Vec<String> = assets.iter().map(|a| a.hostname.clone()).collect();
+    merge_inventory(&mut assets, store.assets_by_hostnames(&hostnames).await?);
+
+    let entries: Vec<crate::database::store::PersistedAsset> = assets
+        .into_iter()
+        .map(|asset| crate::database::store::PersistedAsset {
+            id: asset.id,
+            hostname: asset.hostname,
+            ip_address: asset.ip_address,
+            ports: asset.ports.unwrap_or_default(),
+            services: asset.services.unwrap_or_default(),
+            technologies: asset.technologies.unwrap_or_default(),
+            discovered_at: asset.discovered_at,
+        })
+        .collect();
+    store.save_assets(&entries).await?;
+    Ok(entries.len())
+}
+
+fn push_unique<T: PartialEq>(list: &mut Option<Vec<T>>, value: T) {
+    let list = list.get_or_insert_with(Vec::new);
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+/// Resolve which export sections to include, honouring the deprecated `include_findings_only`
+///
+/// Explicit `include_scans` / `include_findings` values always take precedence
+fn resolve_export_sections(
+    include_scans: Option<bool>,
+    include_findings: Option<bool>,
+    include_findings_only: Option<bool>,
+) -> (bool, bool) {
+    let findings_only = include_findings_only.unwrap_or(false);
+    (
+        include_scans.unwrap_or(!findings_only),
+        include_findings.unwrap_or(true),
+    )
+}
+
+/// Write an export (Markdown or encrypted Markdown) to disk
+///
+/// The file is written to a temp file next to `path`, verified, then renamed into place.
+/// An existing file at `path` is kept and a numbered name is used instead, unless `overwrite` is set.
+///
+/// With `redaction_profile` the content is redacted once more before writing. Passing the
+/// `redaction_mapping_id` from the export metadata keeps hostname pseudonyms consistent with the data.
+#[tauri::command]
+pub async fn export_scan_data_to_file(
+    content: String,
+    path: String,
+    overwrite: Option<bool>,
+    redaction_profile: Option<String>,
+    redaction_mapping_id: Option<String>,
+) -> Result<WrittenFile, String> {
+    let content = match redaction_profile {
+        Some(profile) => {
+            let mut redactor = Redactor::new(&profile, redaction_mapping_id.as_deref())?;
+            let redacted = redactor.redact_text(&content);
+            redactor.finish()?;
+            redacted
+        }
+        None => content,
+    };
+
+    let written = write_atomic(Path::new(&path), content.as_bytes(), overwrite.unwrap_or(false))?;
+    tracing::info!("💾 已匯出到 {} ({} bytes)", written.path, written.size);
+    Ok(written)
+}
+
+/// Deduplicate imported data before inserting into database
+///
+/// Checks incoming scans, findings, annotations and assets against the import file itself,
+/// the in-memory state and the persisted database, reporting where each duplicate was found
+#[tauri::command]
+pub async fn deduplicate_import_data(
+    data: ExportData,
+    state: tauri::State<'_, crate::commands::scan::ScanState>,
+) -> Result<DeduplicateResult, String> {
+    let existing = load_existing_records(&data, &state).await?;
+    Ok(classify_import(data, &existing))
 }
 
 /// Import deduplicated data into database
 ///
 /// Inserts scans, findings, annotations, and assets into the database
+///
+/// With `dry_run` the full dedup and plan is computed and returned without touching state.
+/// Passing the returned `plan_token` to the real import applies exactly that plan, and fails
+/// if the import data, the duplicates found or the current state changed in between.
+///
+/// An import that would overwrite more than `max_overwrites` scans (default 10) or more than
+/// `max_overwrite_percent` of the loaded scans (default 20%) writes nothing and returns
+/// `requires_confirmation` with the impact, unless `confirm_large_import` is set.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn import_scan_data(
     data: ExportData,
     skip_duplicates: bool,
     merge_strategy: String,
+    dry_run: Option<bool>,
+    plan_token: Option<String>,
+    confirm_large_import: Option<bool>,
+    max_overwrites: Option<u32>,
+    max_overwrite_percent: Option<f64>,
     state: tauri::State<'_, crate::commands::scan::ScanState>,
+) -> Result<ImportResult, String> {
+    import_into(
+        &state,
+        data,
+        skip_duplicates,
+        merge_strategy,
+        dry_run,
+        plan_token,
+        confirm_large_import,
+        max_overwrites,
+        max_overwrite_percent,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn import_into(
+    state: &crate::commands::scan::ScanState,
+    data: ExportData,
+    skip_duplicates: bool,
+    merge_strategy: String,
+    dry_run: Option<bool>,
+    plan_token: Option<String>,
+    confirm_large_import: Option<bool>,
+    max_overwrites: Option<u32>,
+    max_overwrite_percent: Option<f64>,
 ) -> Result<ImportResult, String> {
     use crate::commands::scan::ScanReport;
     use crate::models::*;
@@ -355,16 +667,45 @@ pub async fn import_scan_data(
     };
 
     let mut errors = Vec::new();
+    let dry_run = dry_run.unwrap_or(false);
+    let mut plan = ImportPlan::default();
+
+    // Classify against memory and the database before taking the state locks
+    let duplicates = if skip_duplicates {
+        let existing = load_existing_records(&data, state).await?;
+        classify_import(data.clone(), &existing).duplicates
+    } else {
+        DuplicateIds::default()
+    };
+    let duplicate_scans: HashSet<&str> = duplicates.scans.iter().map(|d| d.id.as_str()).collect();
+    let duplicate_findings: HashSet<&str> = duplicates.findings.iter().map(|d| d.id.as_str()).collect();
+    skipped_counts.annotations += duplicates.annotations.len() as i32;
+    skipped_counts.assets += duplicates.assets.len() as i32;
 
     // Get current state
     let mut tasks = state.current_tasks.lock().await;
     let mut results = state.scan_results.lock().await;
+    let mut stored_baselines = state.baselines.lock().await;
+
+    // The plan is deterministic for the same input, duplicates and state, so the token pins all three
+    let token = plan_token_for(&data, skip_duplicates, &merge_strategy, &duplicates, &tasks, &results, &stored_baselines);
+    if let Some(expected) = &plan_token {
+        if *expected != token {
+            return Err("匯入計畫已失效：匯入資料或目前狀態在試執行後已變更，請重新試執行".to_string());
+        }
+    }
 
-    // Import scans
+    // Build every scan first; nothing is inserted until the plan is complete
+    let mut planned: Vec<(ScanTask, ScanReport)> = Vec::new();
+    let mut overwritten_findings = 0u32;
     for export_scan in data.scans {
         // Check if scan already exists
-        if skip_duplicates && tasks.iter().any(|t| t.id == export_scan.id) {
+        if duplicate_scans.contains(export_scan.id.as_str()) {
+            let findings: Vec<String> = data.findings.iter().filter(|f| f.scan_id == export_scan.id).map(|f| f.id.clone()).collect();
             skipped_counts.scans += 1;
+            skipped_counts.findings += findings.len() as i32;
+            plan.skipped_scans.push(export_scan.id);
+            plan.skipped_findings.extend(findings);
             continue;
         }
 
@@ -383,8 +724,10 @@ pub async fn import_scan_data(
         let status = match export_scan.status.as_str() {
             "pending" => ScanStatus::Pending,
             "running" => ScanStatus::Running,
+            "paused" => ScanStatus::Paused,
             "completed" => ScanStatus::Completed,
             "failed" => ScanStatus::Failed,
+            "cancelled" => ScanStatus::Cancelled,
             _ => ScanStatus::Completed,
         };
 
@@ -410,12 +753,30 @@ pub async fn import_scan_data(
             started_at,
             completed_at,
             created_at,
+            config: export_scan.config,
+            config_hash: export_scan.config_hash,
+            requested_target: None,
+            error: None,
+            error_kind: None,
+            redirect: None,
+            unchanged_since: None,
+            title: None,
+            favicon_path: None,
+            request_budget: None,
         };
 
         // Collect findings for this scan
         let scan_findings: Vec<ScanResult> = data.findings
             .iter()
             .filter(|f| f.scan_id == export_scan.id)
+            .filter(|f| {
+                let duplicate = duplicate_findings.contains(f.id.as_str());
+                if duplicate {
+                    skipped_counts.findings += 1;
+                    plan.skipped_findings.push(f.id.clone());
+                }
+                !duplicate
+            })
             .map(|f| {
                 let result_type = match f.finding_type.as_str() {
                     "port" => ResultType::Port,
@@ -448,6 +809,17 @@ pub async fn import_scan_data(
                     description: Some(f.description.clone()),
                     raw_data: f.evidence.clone(),
                     created_at: discovered_at,
+                    low_confidence: false,
+                    cwe_ids: imported_cwe_ids(f),
+                    details: Some(FindingDetails {
+                        owasp_category: f.owasp_category.clone(),
+                        cwe_id: imported_cwe_ids(f).into_iter().next(),
+                        affected_url: f.affected_url.clone(),
+                        payload: f.payload.clone(),
+                        confidence: None,
+                        exchange: imported_exchange(f),
+                        ..FindingDetails::default()
+                    }),
                 }
             })
             .collect();
@@ -456,29 +828,459 @@ pub async fn import_scan_data(
         let report = ScanReport {
             task: task.clone(),
             headers: Vec::new(), // TODO: Extract from findings if available
-            ssl_analysis: None,  // TODO: Extract from findings if available
+            ssl_analysis: Vec::new(),  // TODO: Extract from findings if available
             technologies: Vec::new(), // TODO: Extract from assets if available
             vulnerabilities: scan_findings.clone(),
+            open_ports: Vec::new(),
+            coverage_notes: Vec::new(),
+            baseline_comparison: None,
+            stages: Vec::new(),
+            warmup: None,
+            consent: None,
+            crawl_skipped: Vec::new(),
+            crawl: None,
+            scan_mode: ScanMode::default(),
+            owasp_categories: None,
+            time_box: None,
+            attack_chains: crate::attack_chains::detect(&task.target_url, &scan_findings),
+            content_hashes: Vec::new(),
+            uploaded_files: Vec::new(),
+            check_metrics: Vec::new(),
+            request_rate: None,
+            discovered_content: Vec::new(),
+            summary: Some(crate::summary::summarize(scan_findings.iter().map(crate::summary::SummaryInput::from_result))),
+            authentication: None,
+            resource_inventory: Vec::new(),
+            coverage: export_scan.coverage,
         };
 
-        // Add to state
-        tasks.push(task);
-        results.insert(export_scan.id.clone(), report);
-
+        if let Some(existing) = results.get(&export_scan.id) {
+            plan.overwritten_scans.push(export_scan.id.clone());
+            overwritten_findings += existing.vulnerabilities.len() as u32;
+        }
+        plan.scans.push(export_scan.id.clone());
+        plan.findings.extend(scan_findings.iter().map(|f| f.id.clone()));
         imported_counts.scans += 1;
         imported_counts.findings += scan_findings.len() as i32;
+        planned.push((task, report));
+    }
+
+    // TODO: Import annotations
+
+    // Assets go to the persisted inventory once the state locks are released
+    let duplicate_assets: HashSet<&str> = duplicates.assets.iter().map(|d| d.id.as_str()).collect();
+    let mut imported_hostnames = HashSet::new();
+    let assets: Vec<Asset> = data.assets
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|a| !duplicate_assets.contains(a.id.as_str()) && imported_hostnames.insert(a.hostname.clone()))
+        .collect();
+    imported_counts.assets = assets.len() as i32;
+
+    // Shared baselines never replace one that was set locally
+    let mut merged_baselines = Vec::new();
+    for baseline in data.baselines.unwrap_or_default() {
+        if stored_baselines.contains_key(&baseline.target) || plan.merged_baselines.contains(&baseline.target) {
+            plan.skipped_baselines.push(baseline.target);
+        } else {
+            plan.merged_baselines.push(baseline.target.clone());
+            merged_baselines.push(baseline);
+        }
+    }
+
+    let impact = import_impact(
+        plan.overwritten_scans.len() as u32,
+        overwritten_findings,
+        results.len() as u32,
+        max_overwrites.unwrap_or(DEFAULT_MAX_OVERWRITES),
+        max_overwrite_percent.unwrap_or(DEFAULT_MAX_OVERWRITE_PERCENT),
+    );
+    if impact.exceeds_threshold && !dry_run && !confirm_large_import.unwrap_or(false) {
+        tracing::warn!(
+            "⚠️  匯入會覆寫 {} 個掃描 ({:.1}%)，超過上限，需要確認",
+            impact.overwritten_scans, impact.overwritten_percent
+        );
+        return Ok(ImportResult {
+            success: false,
+            imported: ImportCounts { scans: 0, findings: 0, annotations: 0, assets: 0 },
+            skipped: skipped_counts,
+            errors: vec![format!(
+                "匯入會覆寫 {} 個現有掃描與 {} 個發現，超過上限 ({} 個或 {}%)，請確認後再匯入",
+                impact.overwritten_scans, impact.overwritten_findings, impact.max_overwrites, impact.max_overwrite_percent
+            )],
+            dry_run: false,
+            plan: Some(plan),
+            plan_token: Some(token),
+            impact: Some(impact),
+            requires_confirmation: true,
+        });
+    }
+
+    if !dry_run {
+        // Add to state; an overwritten scan replaces its task instead of adding a second one
+        for (task, report) in planned {
+            results.insert(task.id.clone(), report);
+            tasks.retain(|t| t.id != task.id);
+            tasks.push(task);
+        }
+        for baseline in merged_baselines {
+            stored_baselines.insert(baseline.target.clone(), baseline);
+        }
+    }
+
+    drop(tasks);
+    drop(results);
+    drop(stored_baselines);
+
+    if !dry_run && !assets.is_empty() {
+        let written = match state.store() {
+            Some(store) => import_assets(store, assets).await.map_err(|e| format!("資產清單寫入失敗: {}", e)),
+            None => Err("資料庫無法使用，資產未匯入".to_string()),
+        };
+        match written {
+            Ok(count) => imported_counts.assets = count as i32,
+            Err(error) => {
+                imported_counts.assets = 0;
+                errors.push(error);
+            }
+        }
     }
 
-    // TODO: Import annotations and assets
+    if !dry_run && imported_counts.scans > 0 {
+        crate::commands::dashboard::invalidate_dashboard_cache(state).await;
+    }
 
     Ok(ImportResult {
         success: true,
         imported: imported_counts,
         skipped: skipped_counts,
         errors,
+        dry_run,
+        plan: Some(plan),
+        plan_token: Some(token),
+        impact: Some(impact),
+        requires_confirmation: false,
     })
 }
 
+const DEFAULT_MAX_OVERWRITES: u32 = 10;
+const DEFAULT_MAX_OVERWRITE_PERCENT: f64 = 20.0;
+
+/// Overwrite impact of an import; crossing either limit exceeds the threshold, reaching it does not
+fn import_impact(overwritten_scans: u32, overwritten_findings: u32, existing_scans: u32, max_overwrites: u32, max_overwrite_percent: f64) -> ImportImpact {
+    let overwritten_percent = if existing_scans == 0 {
+        0.0
+    } else {
+        f64::from(overwritten_scans) * 100.0 / f64::from(existing_scans)
+    };
+    ImportImpact {
+        overwritten_scans,
+        overwritten_findings,
+        existing_scans,
+        overwritten_percent,
+        max_overwrites,
+        max_overwrite_percent,
+        exceeds_threshold: overwritten_scans > max_overwrites || overwritten_percent > max_overwrite_percent,
+    }
+}
+
+/// Token identifying an import plan: the import input and options, the duplicates found and a
+/// checksum of the scans, findings and baselines currently in memory
+fn plan_token_for(
+    data: &ExportData,
+    skip_duplicates: bool,
+    merge_strategy: &str,
+    duplicates: &DuplicateIds,
+    tasks: &[crate::models::ScanTask],
+    results: &std::collections::HashMap<String, crate::commands::scan::ScanReport>,
+    baselines: &std::collections::HashMap<String, crate::commands::baseline::Baseline>,
+) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(data).unwrap_or_default());
+    hasher.update([skip_duplicates as u8]);
+    hasher.update(merge_strategy.as_bytes());
+    hasher.update(serde_json::to_vec(duplicates).unwrap_or_default());
+
+    // State checksum; map iteration order is unstable, so everything is sorted first
+    let mut task_ids: Vec<(&str, String)> = tasks.iter().map(|t| (t.id.as_str(), t.status.to_string())).collect();
+    task_ids.sort();
+    let mut reports: Vec<(&String, Vec<&str>)> = results
+        .iter()
+        .map(|(id, report)| (id, report.vulnerabilities.iter().map(|v| v.id.as_str()).collect()))
+        .collect();
+    reports.sort();
+    let mut baseline_entries: Vec<(&String, &str)> = baselines.iter().map(|(target, b)| (target, b.source_task_id.as_str())).collect();
+    baseline_entries.sort();
+    hasher.update(serde_json::to_vec(&(task_ids, reports, baseline_entries)).unwrap_or_default());
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// Gather existing scan ids, finding fingerprints, annotation ids and asset hostnames
+/// from memory and, when available, the persistent database
+async fn load_existing_records(
+    data: &ExportData,
+    state: &crate::commands::scan::ScanState,
+) -> Result<ExistingRecords, String> {
+    let mut existing = ExistingRecords::default();
+
+    {
+        let tasks = state.current_tasks.lock().await;
+        let results = state.scan_results.lock().await;
+        existing.memory_scan_ids = tasks.iter().map(|t| t.id.clone()).collect();
+        for report in results.values() {
+            for result in &report.vulnerabilities {
+                let affected_url = result.details.as_ref().and_then(|d| d.affected_url.as_deref());
+                existing.memory_fingerprints.insert(finding_fingerprint(
+                    &format!("{:?}", result.result_type).to_lowercase(),
+                    &result.title,
+                    finding_location(affected_url, &report.task.target_url),
+                ));
+            }
+        }
+    }
+
+    if let Some(store) = state.store() {
+        let db_error = |e: StoreError| format!("資料庫查詢失敗: {}", e);
+
+        let scan_ids: Vec<String> = data.scans.iter().map(|s| s.id.clone()).collect();
+        existing.persisted_scan_ids = store.existing_scan_ids(&scan_ids).await.map_err(db_error)?;
+
+        existing.persisted_fingerprints = store.findings().await.map_err(db_error)?
+            .iter()
+            .map(|f| finding_fingerprint(&f.result_type, &f.title, finding_location(f.affected_url.as_deref(), &f.target_url)))
+            .collect();
+
+        if let Some(annotations) = &data.annotations {
+            let ids: Vec<String> = annotations.iter().map(|a| a.id.clone()).collect();
+            existing.persisted_annotation_ids = store.existing_annotation_ids(&ids).await.map_err(db_error)?;
+        }
+
+        if let Some(assets) = &data.assets {
+            let hostnames: Vec<String> = assets.iter().map(|a| a.hostname.clone()).collect();
+            existing.persisted_asset_hostnames = store.existing_asset_hostnames(&hostnames).await.map_err(db_error)?;
+        }
+    }
+
+    Ok(existing)
+}
+
+/// Split import data into unique records and duplicates
+///
+/// Repeats within the file win over in-memory matches, which win over persisted matches
+fn classify_import(data: ExportData, existing: &ExistingRecords) -> DeduplicateResult {
+    let mut duplicates = DuplicateIds::default();
+
+    let classify = |seen: &mut HashSet<String>, key: &str, in_memory: bool, persisted: bool| {
+        if !seen.insert(key.to_string()) {
+            Some(DuplicateSource::ImportFile)
+        } else if in_memory {
+            Some(DuplicateSource::InMemory)
+        } else if persisted {
+            Some(DuplicateSource::Persisted)
+        } else {
+            None
+        }
+    };
+
+    // Findings without an affected_url are located by their parent scan's target
+    let targets: std::collections::HashMap<String, String> = data.scans
+        .iter()
+        .map(|s| (s.id.clone(), s.target.clone()))
+        .collect();
+
+    // Deduplicate scans
+    let mut seen = HashSet::new();
+    let mut scans = Vec::new();
+    for scan in data.scans {
+        match classify(
+            &mut seen,
+            &scan.id,
+            existing.memory_scan_ids.contains(&scan.id),
+            existing.persisted_scan_ids.contains(&scan.id),
+        ) {
+            Some(source) => duplicates.scans.push(DuplicateRecord { id: scan.id, source }),
+            None => scans.push(scan),
+        }
+    }
+
+    // Deduplicate findings by id within the file, and by fingerprint against existing data
+    let mut seen_ids = HashSet::new();
+    let mut seen_fingerprints = HashSet::new();
+    let mut findings = Vec::new();
+    for finding in data.findings {
+        let target = targets.get(&finding.scan_id).unwrap_or(&finding.scan_id);
+        let fingerprint = finding_fingerprint(&finding.finding_type, &finding.title, finding_location(finding.affected_url.as_deref(), target));
+
+        let source = if !seen_ids.insert(finding.id.clone()) {
+            Some(DuplicateSource::ImportFile)
+        } else {
+            classify(
+                &mut seen_fingerprints,
+                &fingerprint,
+                existing.memory_fingerprints.contains(&fingerprint),
+                existing.persisted_fingerprints.contains(&fingerprint),
+            )
+        };
+
+        match source {
+            Some(source) => duplicates.findings.push(DuplicateRecord { id: finding.id, source }),
+            None => findings.push(finding),
+        }
+    }
+
+    // Deduplicate annotations if present
+    let annotations = data.annotations.map(|annotations| {
+        let mut seen = HashSet::new();
+        let mut unique = Vec::new();
+        for annotation in annotations {
+            match classify(&mut seen, &annotation.id, false, existing.persisted_annotation_ids.contains(&annotation.id)) {
+                Some(source) => duplicates.annotations.push(DuplicateRecord { id: annotation.id, source }),
+                None => unique.push(annotation),
+            }
+        }
+        unique
+    });
+
+    // Deduplicate assets by hostname if present
+    let assets = data.assets.map(|assets| {
+        let mut seen = HashSet::new();
+        let mut unique = Vec::new();
+        for asset in assets {
+            match classify(&mut seen, &asset.hostname, false, existing.persisted_asset_hostnames.contains(&asset.hostname)) {
+                Some(source) => duplicates.assets.push(DuplicateRecord { id: asset.id, source }),
+                None => unique.push(asset),
+            }
+        }
+        unique
+    });
+
+    DeduplicateResult {
+        duplicates,
+        unique: ExportData {
+            metadata: data.metadata,
+            scans,
+            findings,
+            annotations,
+            assets,
+            baselines: data.baselines,
+        },
+    }
+}
+
+/// Scans passing the `scan_ids` / `since` filters, and the findings of exactly those scans
+///
+/// Findings of a filtered-out scan are never exported on their own, even when scan rows are left out
+fn select_for_export(
+    tasks: &[ScanTask],
+    results: &std::collections::HashMap<String, crate::commands::scan::ScanReport>,
+    scan_ids: Option<&[String]>,
+    since: Option<DateTime<Utc>>,
+    include_findings: bool,
+) -> (Vec<ExportScanTask>, Vec<ExportFinding>) {
+    let mut selected_scans = Vec::new();
+    let mut findings = Vec::new();
+
+    for task in tasks {
+        // Filter by scan_ids if provided
+        if scan_ids.is_some_and(|ids| !ids.contains(&task.id)) {
+            continue;
+        }
+
+        // Filter by since date if provided
+        if since.is_some_and(|since| task.created_at < since) {
+            continue;
+        }
+
+        let report = results.get(&task.id);
+        selected_scans.push(export_scan_task(task, report));
+
+        // Get scan results/findings
+        if include_findings {
+            if let Some(report) = report {
+                findings.extend(report.vulnerabilities.iter().map(|vuln| export_finding(task, vuln)));
+            }
+        }
+    }
+
+    (selected_scans, findings)
+}
+
+/// A scan as written to the export file
+fn export_scan_task(task: &ScanTask, report: Option<&crate::commands::scan::ScanReport>) -> ExportScanTask {
+    ExportScanTask {
+        id: task.id.clone(),
+        name: format!("{} - {}", task.scan_type, task.target_url),
+        target: task.target_url.clone(),
+        status: task.status.to_string(),
+        created_at: task.created_at.to_rfc3339(),
+        started_at: task.started_at.map(|dt| dt.to_rfc3339()),
+        completed_at: task.completed_at.map(|dt| dt.to_rfc3339()),
+        created_by: "user".to_string(),
+        config: task.config.clone(),
+        config_hash: task.config_hash.clone(),
+        coverage: report.map(|report| report.coverage.clone()).unwrap_or_default(),
+    }
+}
+
+/// A scan finding as written to the export file; findings without an affected URL are located at the scan target
+fn export_finding(task: &ScanTask, vuln: &ScanResult) -> ExportFinding {
+    ExportFinding {
+        id: vuln.id.clone(),
+        scan_id: task.id.clone(),
+        finding_type: format!("{:?}", vuln.result_type).to_lowercase(),
+        severity: vuln.severity.as_ref().map(|s| s.to_string()).unwrap_or("info".to_string()),
+        title: vuln.title.clone(),
+        description: vuln.description.clone().unwrap_or_default(),
+        affected_url: Some(
+            vuln.details
+                .as_ref()
+                .and_then(|d| d.affected_url.clone())
+                .unwrap_or_else(|| task.target_url.clone()),
+        ),
+        evidence: export_evidence(vuln),
+        recommendation: None,
+        discovered_at: vuln.created_at.to_rfc3339(),
+        discovered_by: "redforge".to_string(),
+        cvss_score: None,
+        cve_id: None,
+        cwe_ids: vuln.cwe_ids.clone(),
+        owasp_category: vuln.details.as_ref().and_then(|d| d.owasp_category.clone()),
+        payload: vuln.details.as_ref().and_then(|d| d.payload.clone()),
+    }
+}
+
+/// Location used in finding fingerprints: the affected URL, otherwise the parent scan's target.
+/// In-memory, persisted and imported findings must all go through this so they compare equal
+pub(crate) fn finding_location<'a>(affected_url: Option<&'a str>, target_url: &'a str) -> &'a str {
+    affected_url.filter(|url| !url.trim().is_empty()).unwrap_or(target_url)
+}
+
+/// Stable identity of a finding across scans: type, title and the affected location
+pub fn finding_fingerprint(finding_type: &str, title: &str, location: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(finding_type.to_lowercase().as_bytes());
+    hasher.update(b"\0");
+    hasher.update(title.trim().to_lowercase().as_bytes());
+    hasher.update(b"\0");
+    hasher.update(location.trim_end_matches('/').to_lowercase().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// CWE ids of an imported finding: the explicit list, otherwise CWE ids written out in the
+/// evidence (nuclei classification, Burp vulnerability classifications); never inferred from the title
+fn imported_cwe_ids(finding: &ExportFinding) -> Vec<String> {
+    let explicit = crate::cwe::from_classification(&finding.cwe_ids.join(" "));
+    if !explicit.is_empty() {
+        return explicit;
+    }
+    finding.evidence.as_deref().map(crate::cwe::from_classification).unwrap_or_default()
+}
+
 /// Check if a finding is duplicate based on similarity
 ///
 /// Uses fuzzy matching on title and description
@@ -489,18 +1291,428 @@ fn is_finding_similar(finding1: &ExportFinding, finding2: &ExportFinding) -> boo
         && finding1.severity == finding2.severity
 }
 
-/// Calculate similarity score between two strings (0.0 - 1.0)
-fn calculate_similarity(s1: &str, s2: &str) -> f64 {
-    // Simple Jaccard similarity - in production, use Levenshtein or other algorithms
-    let set1: HashSet<&str> = s1.split_whitespace().collect();
-    let set2: HashSet<&str> = s2.split_whitespace().collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::scan::{ScanReport, ScanState};
+    use crate::database::store::Store;
 
-    let intersection = set1.intersection(&set2).count();
-    let union = set1.union(&set2).count();
+    fn sample_report() -> ScanReport {
+        serde_json::from_value(serde_json::json!({
+            "task": {
+                "id": "scan-1",
+                "target_url": "https://example.com/",
+                "scan_type": "full",
+                "status": "completed",
+                "created_at": "2026-01-05T10:00:00Z",
+            },
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": [
+                {
+                    "id": "finding-1",
+                    "task_id": "scan-1",
+                    "result_type": "vulnerability",
+                    "severity": "high",
+                    "title": "SQL Injection",
+                    "description": null,
+                    "raw_data": null,
+                    "created_at": "2026-01-05T10:05:00Z",
+                    "details": { "affected_url": "https://example.com/search?q=1" },
+                },
+                {
+                    "id": "finding-2",
+                    "task_id": "scan-1",
+                    "result_type": "header",
+                    "severity": "low",
+                    "title": "Missing Content-Security-Policy",
+                    "description": null,
+                    "raw_data": null,
+                    "created_at": "2026-01-05T10:05:00Z",
+                },
+            ],
+        }))
+        .expect("valid report")
+    }
 
-    if union == 0 {
-        0.0
-    } else {
-        intersection as f64 / union as f64
+    fn export_of(report: &ScanReport, scan_id: &str) -> ExportData {
+        let findings = report.vulnerabilities
+            .iter()
+            .map(|vuln| ExportFinding {
+                id: format!("{}-{}", scan_id, vuln.id),
+                scan_id: scan_id.to_string(),
+                ..export_finding(&report.task, vuln)
+            })
+            .collect();
+        ExportData {
+            metadata: ExportMetadata {
+                version: "1.0.0".to_string(),
+                format: "encrypted-markdown".to_string(),
+                encryption: None,
+                exported_by: "system".to_string(),
+                team_id: None,
+                exported_at: "2026-01-06T00:00:00Z".to_string(),
+                checksum: None,
+                redaction: None,
+            },
+            scans: vec![ExportScanTask { id: scan_id.to_string(), ..export_scan_task(&report.task, Some(report)) }],
+            findings,
+            annotations: None,
+            assets: None,
+            baselines: None,
+        }
+    }
+
+    fn sources(records: &[DuplicateRecord]) -> Vec<DuplicateSource> {
+        records.iter().map(|r| r.source).collect()
+    }
+
+    fn scan_at(id: &str, created_at: &str) -> ScanReport {
+        let mut report = sample_report();
+        report.task.id = id.to_string();
+        report.task.created_at = DateTime::parse_from_rfc3339(created_at).unwrap().with_timezone(&Utc);
+        for finding in &mut report.vulnerabilities {
+            finding.id = format!("{}-{}", id, finding.id);
+            finding.task_id = id.to_string();
+        }
+        report
+    }
+
+    fn history() -> (Vec<ScanTask>, std::collections::HashMap<String, ScanReport>) {
+        let reports = [scan_at("old", "2026-01-01T00:00:00Z"), scan_at("new", "2026-01-10T00:00:00Z")];
+        let tasks = reports.iter().map(|r| r.task.clone()).collect();
+        (tasks, reports.into_iter().map(|r| (r.task.id.clone(), r)).collect())
+    }
+
+    #[test]
+    fn resolves_export_sections() {
+        let cases = [
+            // (include_scans, include_findings, include_findings_only) -> (scans, findings)
+            ((None, None, None), (true, true)),
+            ((None, None, Some(true)), (false, true)),
+            ((None, None, Some(false)), (true, true)),
+            ((Some(false), None, None), (false, true)),
+            ((None, Some(false), None), (true, false)),
+            ((Some(false), Some(false), None), (false, false)),
+            // Explicit values win over the deprecated flag
+            ((Some(true), None, Some(true)), (true, true)),
+            ((Some(true), Some(false), Some(true)), (true, false)),
+        ];
+        for ((scans, findings, findings_only), expected) in cases {
+            assert_eq!(resolve_export_sections(scans, findings, findings_only), expected, "{:?}", (scans, findings, findings_only));
+        }
+    }
+
+    #[test]
+    fn selects_scans_and_their_findings() {
+        let (tasks, results) = history();
+
+        let (scans, findings) = select_for_export(&tasks, &results, None, None, true);
+        assert_eq!(scans.len(), 2);
+        assert_eq!(findings.len(), 4);
+
+        let (scans, findings) = select_for_export(&tasks, &results, None, None, false);
+        assert_eq!(scans.len(), 2);
+        assert!(findings.is_empty());
+
+        let ids = ["new".to_string()];
+        let (scans, findings) = select_for_export(&tasks, &results, Some(&ids), None, true);
+        assert_eq!(scans.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["new"]);
+        assert!(findings.iter().all(|f| f.scan_id == "new"));
+    }
+
+    #[test]
+    fn since_filter_never_exports_orphan_findings() {
+        let (tasks, results) = history();
+        let since = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z").unwrap().with_timezone(&Utc);
+
+        let (scans, findings) = select_for_export(&tasks, &results, None, Some(since), true);
+        assert_eq!(scans.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["new"]);
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().all(|f| scans.iter().any(|s| s.id == f.scan_id)));
+
+        // Findings-only exports apply the same scan filters
+        let ids = ["old".to_string()];
+        let (_, findings) = select_for_export(&tasks, &results, Some(&ids), Some(since), true);
+        assert!(findings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn exported_findings_match_persisted_ones_after_restart() {
+        let store = Store::open_migrated().await;
+        let report = sample_report();
+        store.save_report(&report).await.expect("save report");
+
+        // While the scan is in memory, its own export is recognized there
+        let state = ScanState::new(Some(store.clone()), None, None);
+        state.scan_results.lock().await.insert(report.task.id.clone(), report.clone());
+        let existing = load_existing_records(&export_of(&report, "scan-copy"), &state).await.unwrap();
+        let result = classify_import(export_of(&report, "scan-copy"), &existing);
+        assert_eq!(sources(&result.duplicates.findings), vec![DuplicateSource::InMemory; 2]);
+
+        // After a restart only the database knows the scan
+        let state = ScanState::new(Some(store), None, None);
+        let data = export_of(&report, "scan-1");
+        let existing = load_existing_records(&data, &state).await.unwrap();
+        let result = classify_import(data, &existing);
+        assert_eq!(sources(&result.duplicates.scans), vec![DuplicateSource::Persisted]);
+        assert_eq!(sources(&result.duplicates.findings), vec![DuplicateSource::Persisted; 2]);
+        assert!(result.unique.findings.is_empty());
+
+        // The same findings under another scan id are still duplicates, matched by fingerprint
+        let data = export_of(&report, "scan-copy");
+        let existing = load_existing_records(&data, &state).await.unwrap();
+        let result = classify_import(data, &existing);
+        assert!(result.duplicates.scans.is_empty());
+        assert_eq!(result.unique.scans.len(), 1);
+        assert_eq!(sources(&result.duplicates.findings), vec![DuplicateSource::Persisted; 2]);
+    }
+
+    #[tokio::test]
+    async fn importing_the_same_export_twice_skips_every_finding() {
+        let store = Store::open_migrated().await;
+        let state = ScanState::new(Some(store.clone()), None, None);
+        let data = export_of(&sample_report(), "scan-1");
+        let import = |data: ExportData| import_into(&state, data, true, "overwrite".to_string(), None, None, None, None, None);
+
+        let first = import(data.clone()).await.unwrap();
+        assert_eq!((first.imported.scans, first.imported.findings), (1, data.findings.len() as i32));
+
+        let second = import(data.clone()).await.unwrap();
+        assert!(second.success);
+        assert_eq!(second.skipped.findings, data.findings.len() as i32);
+        assert_eq!((second.imported.scans, second.imported.findings), (0, 0));
+        assert_eq!(state.current_tasks.lock().await.len(), 1);
+        let results = state.scan_results.lock().await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results["scan-1"].vulnerabilities.len(), data.findings.len());
+        store.save_report(&results["scan-1"]).await.expect("save report");
+        drop(results);
+
+        // After a restart the database recognizes the same export
+        let state = ScanState::new(Some(store.clone()), None, None);
+        let third = import_into(&state, data.clone(), true, "overwrite".to_string(), None, None, None, None, None).await.unwrap();
+        assert_eq!(third.skipped.findings, data.findings.len() as i32);
+        assert_eq!(third.imported.findings, 0);
+        assert!(state.scan_results.lock().await.is_empty());
+        assert_eq!(store.findings().await.unwrap().len(), data.findings.len());
+    }
+
+    #[tokio::test]
+    async fn exported_assets_reimport_into_an_equivalent_inventory_entry() {
+        let mut report = sample_report();
+        report.open_ports = serde_json::from_value(serde_json::json!([{
+            "id": "port-1",
+            "task_id": "scan-1",
+            "port": 8443,
+            "protocol": "tcp",
+            "service_name": "https-alt",
+            "service_version": null,
+            "banner": null,
+            "created_at": "2026-01-05T10:01:00Z",
+        }]))
+        .unwrap();
+        report.technologies = serde_json::from_value(serde_json::json!([{
+            "id": "tech-1",
+            "task_id": "scan-1",
+            "technology_name": "nginx",
+            "technology_version": "1.24",
+            "category": "server",
+            "confidence": 90,
+            "created_at": "2026-01-05T10:01:00Z",
+        }]))
+        .unwrap();
+        let tasks = vec![report.task.clone()];
+        let results: std::collections::HashMap<String, ScanReport> = [(report.task.id.clone(), report)].into_iter().collect();
+        let (scans, _) = select_for_export(&tasks, &results, None, None, false);
+
+        let exported = build_assets(&scans, &results);
+        assert_eq!(exported.len(), 1);
+        let store = Store::open_migrated().await;
+        assert_eq!(import_assets(&store, exported.clone()).await.unwrap(), 1);
+
+        let hostnames = vec!["example.com".to_string()];
+        let entries = store.assets_by_hostnames(&hostnames).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        let (asset, entry) = (&exported[0], &entries[0]);
+        assert_eq!(entry.id, asset.id);
+        assert_eq!(entry.hostname, asset.hostname);
+        assert_eq!(entry.ip_address, asset.ip_address);
+        assert_eq!(Some(&entry.ports), asset.ports.as_ref());
+        assert_eq!(Some(&entry.services), asset.services.as_ref());
+        assert_eq!(Some(&entry.technologies), asset.technologies.as_ref());
+        assert_eq!(entry.discovered_at, asset.discovered_at);
+        assert_eq!(entry.ports, vec![443, 8443]);
+
+        // Another export of the same host carries a new id; it merges into the existing entry
+        let mut other = build_assets(&scans, &results);
+        other[0].ports = Some(vec![22]);
+        other[0].discovered_at = "2026-01-01T00:00:00+00:00".to_string();
+        import_assets(&store, other).await.unwrap();
+
+        let merged = store.assets_by_hostnames(&hostnames).await.unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].id, entry.id);
+        assert_eq!(merged[0].ports, vec![22, 443, 8443]);
+        assert_eq!(merged[0].technologies, entry.technologies);
+        assert_eq!(merged[0].discovered_at, "2026-01-01T00:00:00+00:00");
+    }
+
+    /// Loaded state and the database files, as bytes
+    async fn snapshot(state: &ScanState, store: &Store) -> (String, Vec<u8>, Option<Vec<u8>>) {
+        let tasks = state.current_tasks.lock().await;
+        let results = state.scan_results.lock().await;
+        let baselines = state.baselines.lock().await;
+        let memory = serde_json::to_string(&(
+            &*tasks,
+            results.iter().collect::<std::collections::BTreeMap<_, _>>(),
+            baselines.iter().collect::<std::collections::BTreeMap<_, _>>(),
+        ))
+        .unwrap();
+        let path = store.path();
+        let wal = std::fs::read(format!("{}-wal", path.display())).ok();
+        (memory, std::fs::read(&path).unwrap(), wal)
+    }
+
+    #[tokio::test]
+    async fn dry_run_import_leaves_state_byte_identical() {
+        let store = Store::open_migrated().await;
+        let report = sample_report();
+        store.save_report(&report).await.expect("save report");
+        let state = ScanState::new(Some(store.clone()), None, None);
+        state.current_tasks.lock().await.push(report.task.clone());
+        state.scan_results.lock().await.insert(report.task.id.clone(), report.clone());
+
+        // One scan overwrites the loaded one, one is new; the asset would go to the inventory
+        let mut data = export_of(&report, "scan-1");
+        let other = export_of(&report, "scan-2");
+        data.scans.extend(other.scans);
+        data.findings.extend(other.findings);
+        data.assets = Some(vec![Asset {
+            id: "asset-1".to_string(),
+            hostname: "example.com".to_string(),
+            ip_address: None,
+            ports: Some(vec![443]),
+            services: Some(vec!["https".to_string()]),
+            technologies: None,
+            discovered_at: "2026-01-05T10:00:00+00:00".to_string(),
+        }]);
+
+        let before = snapshot(&state, &store).await;
+        for skip_duplicates in [true, false] {
+            let result = import_into(&state, data.clone(), skip_duplicates, "overwrite".to_string(), Some(true), None, None, None, None)
+                .await
+                .unwrap();
+            assert!(result.dry_run);
+            assert!(result.plan.as_ref().is_some_and(|plan| plan.scans.contains(&"scan-2".to_string())));
+            assert_eq!(result.imported.assets, 1);
+            assert_eq!(snapshot(&state, &store).await, before, "skip_duplicates = {}", skip_duplicates);
+        }
+
+        // The dry run's token applies exactly that plan; overwriting the only loaded scan needs confirmation
+        let plan = import_into(&state, data.clone(), false, "overwrite".to_string(), Some(true), None, None, None, None)
+            .await
+            .unwrap();
+        let applied = import_into(&state, data, false, "overwrite".to_string(), None, plan.plan_token, Some(true), None, None)
+            .await
+            .unwrap();
+        assert!(applied.success && !applied.dry_run);
+        assert_eq!(applied.plan.unwrap().scans, plan.plan.unwrap().scans);
+        assert!(state.scan_results.lock().await.contains_key("scan-2"));
+        assert_eq!(store.assets_by_hostnames(&["example.com".to_string()]).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn overwrite_guard_trips_only_above_its_limits() {
+        let cases = [
+            // (overwritten, existing, max_overwrites, max_percent) -> exceeds
+            ((0, 0, 10, 20.0), false),
+            ((10, 100, 10, 20.0), false),
+            ((11, 100, 10, 20.0), true),
+            ((20, 100, 50, 20.0), false),
+            ((21, 100, 50, 20.0), true),
+            ((1, 4, 10, 25.0), false),
+            ((2, 4, 10, 25.0), true),
+            // Every scan is new: nothing loaded yet is never overwritten
+            ((0, 0, 0, 0.0), false),
+        ];
+        for ((overwritten, existing, max_overwrites, max_percent), exceeds) in cases {
+            let impact = import_impact(overwritten, 0, existing, max_overwrites, max_percent);
+            assert_eq!(impact.exceeds_threshold, exceeds, "{:?}", (overwritten, existing, max_overwrites, max_percent));
+        }
+        assert_eq!(import_impact(1, 3, 4, 10, 20.0).overwritten_percent, 25.0);
+    }
+
+    async fn state_with_loaded_scan() -> (ScanState, ExportData) {
+        let store = Store::open_migrated().await;
+        let report = sample_report();
+        store.save_report(&report).await.expect("save report");
+        let state = ScanState::new(Some(store), None, None);
+        state.current_tasks.lock().await.push(report.task.clone());
+        state.scan_results.lock().await.insert(report.task.id.clone(), report.clone());
+
+        // Overwrites the loaded scan and adds a new one
+        let mut data = export_of(&report, "scan-1");
+        let other = export_of(&report, "scan-2");
+        data.scans.extend(other.scans);
+        data.findings.extend(other.findings);
+        (state, data)
+    }
+
+    #[tokio::test]
+    async fn large_overwrite_requires_confirmation() {
+        let (state, data) = state_with_loaded_scan().await;
+
+        let blocked = import_into(&state, data.clone(), false, "overwrite".to_string(), None, None, None, None, None)
+            .await
+            .unwrap();
+        assert!(blocked.requires_confirmation && !blocked.success);
+        assert_eq!(blocked.imported.scans, 0);
+        assert!(blocked.errors[0].contains("請確認"), "{:?}", blocked.errors);
+        let impact = blocked.impact.unwrap();
+        assert_eq!((impact.overwritten_scans, impact.overwritten_findings, impact.existing_scans), (1, 2, 1));
+        assert_eq!(impact.overwritten_percent, 100.0);
+        assert!(impact.exceeds_threshold);
+        assert!(!state.scan_results.lock().await.contains_key("scan-2"));
+        assert_eq!(state.current_tasks.lock().await.len(), 1);
+
+        // The dry run reports the same numbers without asking for confirmation
+        let preview = import_into(&state, data.clone(), false, "overwrite".to_string(), Some(true), None, None, None, None)
+            .await
+            .unwrap();
+        assert!(!preview.requires_confirmation);
+        let preview_impact = preview.impact.unwrap();
+        assert_eq!(
+            (preview_impact.overwritten_scans, preview_impact.overwritten_findings, preview_impact.exceeds_threshold),
+            (1, 2, true)
+        );
+
+        let confirmed = import_into(&state, data, false, "overwrite".to_string(), None, None, Some(true), None, None)
+            .await
+            .unwrap();
+        assert!(confirmed.success && !confirmed.requires_confirmation);
+        assert_eq!(confirmed.imported.scans, 2);
+        assert!(state.scan_results.lock().await.contains_key("scan-2"));
+        // The overwritten scan replaces its task instead of adding a second one
+        assert_eq!(state.current_tasks.lock().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn overwrite_at_the_configured_limits_needs_no_confirmation() {
+        let (state, data) = state_with_loaded_scan().await;
+
+        let applied = import_into(&state, data.clone(), false, "overwrite".to_string(), None, None, None, Some(1), Some(100.0))
+            .await
+            .unwrap();
+        assert!(applied.success && !applied.requires_confirmation);
+        assert!(!applied.impact.unwrap().exceeds_threshold);
+
+        // Skipping duplicates overwrites nothing, so the default limits never trip
+        let (state, data) = state_with_loaded_scan().await;
+        let skipped = import_into(&state, data, true, "overwrite".to_string(), None, None, None, Some(0), Some(0.0))
+            .await
+            .unwrap();
+        assert!(skipped.success);
+        assert_eq!(skipped.impact.unwrap().overwritten_scans, 0);
     }
 }
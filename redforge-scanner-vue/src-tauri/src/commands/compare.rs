@@ -0,0 +1,514 @@
+/**
+ * Scan Comparison Commands
+ *
+ * 比較同一目標的兩次掃描，回傳具型別的變更紀錄 (標頭、TLS 評級、技術版本、發現)，
+ * 附上前後的實際值，供 UI 與差異報告直接呈現。
+ *
+ * 各區段分別比較：每次只取出兩份報告中的同一區段，比較完再取下一個區段，
+ * 不需要同時持有兩份完整的報告。
+ *
+ * diff_scans 另外比對兩次掃描的發現 (標題 + 受影響 URL + OWASP 類別，找不到時以相似度模糊比對)，
+ * 分成已修正、新出現、持續存在與嚴重度改變四類，也可以經由 generate_report 輸出為報告的一個章節。
+ */
+
+use crate::commands::collaboration::finding_fingerprint;
+use crate::commands::report::dismissed_findings;
+use crate::commands::scan::{ScanReport, ScanState};
+use crate::models::*;
+use crate::scanners::response_diff::calculate_similarity;
+use crate::scanners::ssl_scanner::grade_rank;
+use crate::summary::owasp_category;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+use tauri::State;
+
+/// 證據摘錄的最大長度 (字元)
+const EXCERPT_LIMIT: usize = 300;
+/// 模糊比對發現時的最低相似度
+const FUZZY_MATCH_THRESHOLD: f64 = 0.6;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanComparison {
+    pub base_task_id: String,
+    pub task_id: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+/// 兩次掃描之間的一項變更；before 為基準掃描的值，after 為比較掃描的值
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChangeRecord {
+    Header {
+        name: String,
+        before: Option<String>,
+        after: Option<String>,
+        before_secure: bool,
+        after_secure: bool,
+        /// 由安全變為缺少或不安全
+        regressed: bool,
+    },
+    TlsGrade {
+        before: Option<String>,
+        after: Option<String>,
+        factors_before: Vec<String>,
+        factors_after: Vec<String>,
+        regressed: bool,
+    },
+    Technology {
+        name: String,
+        before_version: Option<String>,
+        after_version: Option<String>,
+    },
+    /// 兩次都出現但嚴重度或證據不同的發現
+    Finding {
+        fingerprint: String,
+        title: String,
+        severity_before: Option<String>,
+        severity_after: Option<String>,
+        evidence_before: Option<String>,
+        evidence_after: Option<String>,
+    },
+}
+
+/// 兩次掃描的發現比對結果與標頭、技術、SSL 評級的變化；A 為基準 (較早) 的掃描
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanDiff {
+    pub task_id_a: String,
+    pub task_id_b: String,
+    /// 只出現在 A 的發現
+    pub fixed: Vec<DiffFinding>,
+    /// 只出現在 B 的發現
+    pub new: Vec<DiffFinding>,
+    /// 兩次都出現且嚴重度相同的發現
+    pub persisting: Vec<MatchedFinding>,
+    /// 兩次都出現但嚴重度改變的發現，不計入 fixed 與 new
+    pub severity_changed: Vec<MatchedFinding>,
+    /// ChangeRecord::Header
+    pub header_changes: Vec<ChangeRecord>,
+    /// ChangeRecord::Technology
+    pub technology_changes: Vec<ChangeRecord>,
+    /// ChangeRecord::TlsGrade；評級與評分因素都相同或其中一次沒有分析 TLS 時為 None
+    pub ssl_grade: Option<ChangeRecord>,
+}
+
+/// 比對用的發現欄位
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiffFinding {
+    pub id: String,
+    pub title: String,
+    pub severity: Option<Severity>,
+    pub affected_url: Option<String>,
+    /// 例如 "A03"
+    pub owasp_category: Option<String>,
+}
+
+/// 兩次掃描中對應到同一個問題的發現
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MatchedFinding {
+    pub before: DiffFinding,
+    pub after: DiffFinding,
+    /// 模糊比對時的相似度 (0.0 - 1.0)；標題、受影響 URL 與 OWASP 類別完全相同時為 None
+    pub similarity: Option<f64>,
+}
+
+impl DiffFinding {
+    fn from_result(finding: &ScanResult) -> Self {
+        let details = finding.details.as_ref();
+        // details 的類別帶有年份 (A03:2021)，舊資料只有 raw_data 中的 owasp 欄位
+        let owasp_category = details
+            .and_then(|d| d.owasp_category.as_deref())
+            .and_then(|category| category.split(':').next())
+            .map(|category| category.trim().to_uppercase())
+            .or_else(|| owasp_category(finding.raw_data.as_deref()));
+        Self {
+            id: finding.id.clone(),
+            title: finding.title.clone(),
+            severity: finding.severity.clone(),
+            affected_url: details.and_then(|d| d.affected_url.clone()),
+            owasp_category,
+        }
+    }
+
+    /// 完全比對的鍵：標題、受影響 URL 與 OWASP 類別
+    fn key(&self) -> (String, String, String) {
+        (
+            self.title.trim().to_lowercase(),
+            self.affected_url.as_deref().unwrap_or_default().trim().to_lowercase(),
+            self.owasp_category.clone().unwrap_or_default(),
+        )
+    }
+
+    /// 模糊比對的文字：標題與受影響 URL
+    fn text(&self) -> String {
+        format!("{} {}", self.title, self.affected_url.as_deref().unwrap_or_default()).to_lowercase()
+    }
+}
+
+/// 比較兩次掃描，回傳標頭、TLS 評級、技術版本與發現的變更
+#[tauri::command]
+pub async fn compare_scans(
+    base_task_id: String,
+    task_id: String,
+    state: State<'_, ScanState>,
+) -> Result<ScanComparison, String> {
+    let mut changes = Vec::new();
+
+    let before = section(&state, &base_task_id, |r| r.headers.clone()).await?;
+    let after = section(&state, &task_id, |r| r.headers.clone()).await?;
+    changes.extend(header_changes(before, after));
+
+    let before = section(&state, &base_task_id, |r| r.primary_ssl().cloned()).await?;
+    let after = section(&state, &task_id, |r| r.primary_ssl().cloned()).await?;
+    changes.extend(tls_change(before, after));
+
+    let before = section(&state, &base_task_id, |r| r.technologies.clone()).await?;
+    let after = section(&state, &task_id, |r| r.technologies.clone()).await?;
+    changes.extend(technology_changes(before, after));
+
+    let before = section(&state, &base_task_id, finding_evidence).await?;
+    let after = section(&state, &task_id, finding_evidence).await?;
+    changes.extend(finding_changes(before, after));
+
+    Ok(ScanComparison { base_task_id, task_id, changes })
+}
+
+/// 比對兩次掃描的發現 (A 為基準掃描)，並列出標頭、技術與 SSL 評級的變化；已抑制或標記為誤報的發現不列入
+#[tauri::command]
+pub async fn diff_scans(task_id_a: String, task_id_b: String, state: State<'_, ScanState>) -> Result<ScanDiff, String> {
+    scan_diff(&state, &task_id_a, &task_id_b).await
+}
+
+pub(crate) async fn scan_diff(state: &ScanState, task_id_a: &str, task_id_b: &str) -> Result<ScanDiff, String> {
+    if task_id_a == task_id_b {
+        return Err("請選擇兩次不同的掃描".to_string());
+    }
+
+    let before = section(state, task_id_a, |r| r.headers.clone()).await?;
+    let after = section(state, task_id_b, |r| r.headers.clone()).await?;
+    let header_changes = header_changes(before, after);
+
+    let before = section(state, task_id_a, |r| r.technologies.clone()).await?;
+    let after = section(state, task_id_b, |r| r.technologies.clone()).await?;
+    let technology_changes = technology_changes(before, after);
+
+    let before = section(state, task_id_a, |r| r.primary_ssl().cloned()).await?;
+    let after = section(state, task_id_b, |r| r.primary_ssl().cloned()).await?;
+    let ssl_grade = tls_change(before, after);
+
+    let before = diff_findings(state, task_id_a).await?;
+    let after = diff_findings(state, task_id_b).await?;
+    let (fixed, new, matched) = match_findings(before, after);
+    let (severity_changed, persisting) = matched.into_iter().partition(|m| m.before.severity != m.after.severity);
+
+    Ok(ScanDiff {
+        task_id_a: task_id_a.to_string(),
+        task_id_b: task_id_b.to_string(),
+        fixed,
+        new,
+        persisting,
+        severity_changed,
+        header_changes,
+        technology_changes,
+        ssl_grade,
+    })
+}
+
+/// 掃描中未抑制、未標記為誤報的發現
+async fn diff_findings(state: &ScanState, task_id: &str) -> Result<Vec<DiffFinding>, String> {
+    let findings: Vec<DiffFinding> =
+        section(state, task_id, |r| r.vulnerabilities.iter().map(DiffFinding::from_result).collect()).await?;
+    let dismissed = dismissed_findings(state, task_id).await;
+    Ok(findings.into_iter().filter(|finding| !dismissed.contains(&finding.id)).collect())
+}
+
+/// 先以標題、受影響 URL 與 OWASP 類別完全比對，剩下的依 A 的順序挑選相似度最高且不低於門檻的發現；
+/// 兩者都有 OWASP 類別時必須相同。回傳 (只在 A, 只在 B, 配對)
+fn match_findings(
+    before: Vec<DiffFinding>,
+    after: Vec<DiffFinding>,
+) -> (Vec<DiffFinding>, Vec<DiffFinding>, Vec<MatchedFinding>) {
+    let mut after: Vec<Option<DiffFinding>> = after.into_iter().map(Some).collect();
+    let mut matched = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for finding in before {
+        let key = finding.key();
+        let exact = after.iter_mut().find(|candidate| candidate.as_ref().is_some_and(|c| c.key() == key));
+        match exact.and_then(Option::take) {
+            Some(other) => matched.push(MatchedFinding { before: finding, after: other, similarity: None }),
+            None => unmatched.push(finding),
+        }
+    }
+
+    let mut fixed = Vec::new();
+    for finding in unmatched {
+        let text = finding.text();
+        let best = after
+            .iter()
+            .enumerate()
+            .filter_map(|(index, candidate)| Some((index, candidate.as_ref()?)))
+            .filter(|(_, candidate)| match (&finding.owasp_category, &candidate.owasp_category) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            })
+            .map(|(index, candidate)| (index, calculate_similarity(&text, &candidate.text())))
+            .filter(|(_, similarity)| *similarity >= FUZZY_MATCH_THRESHOLD)
+            // 相似度相同時取 B 中較前面的發現
+            .fold(None, |best: Option<(usize, f64)>, (index, similarity)| match best {
+                Some((_, top)) if top >= similarity => best,
+                _ => Some((index, similarity)),
+            });
+        match best.and_then(|(index, similarity)| Some((after[index].take()?, similarity))) {
+            Some((other, similarity)) => {
+                matched.push(MatchedFinding { before: finding, after: other, similarity: Some(similarity) })
+            }
+            None => fixed.push(finding),
+        }
+    }
+
+    let new = after.into_iter().flatten().collect();
+    (fixed, new, matched)
+}
+
+/// 只取出報告的一個區段
+async fn section<T>(state: &ScanState, task_id: &str, project: impl FnOnce(&ScanReport) -> T) -> Result<T, String> {
+    let results = state.scan_results.lock().await;
+    let report = results.get(task_id).ok_or_else(|| format!("找不到掃描報告: {}", task_id))?;
+    Ok(project(report))
+}
+
+fn header_changes(before: Vec<SecurityHeader>, after: Vec<SecurityHeader>) -> Vec<ChangeRecord> {
+    // 只掃描 SSL 時沒有標頭，不能視為全部移除
+    if before.is_empty() || after.is_empty() {
+        return Vec::new();
+    }
+    let index = |headers: Vec<SecurityHeader>| -> BTreeMap<String, SecurityHeader> {
+        headers.into_iter().map(|h| (h.header_name.to_lowercase(), h)).collect()
+    };
+    let mut before = index(before);
+    let after = index(after);
+
+    let mut changes = Vec::new();
+    for (name, after) in after {
+        let before = before.remove(&name);
+        let before_value = before.as_ref().filter(|h| h.is_present).and_then(|h| h.header_value.clone());
+        let after_value = after.is_present.then(|| after.header_value.clone()).flatten();
+        let before_secure = before.as_ref().is_some_and(|h| h.is_present && h.is_secure);
+        let after_secure = after.is_present && after.is_secure;
+        if before_value != after_value || before_secure != after_secure {
+            changes.push(ChangeRecord::Header {
+                name: after.header_name,
+                before: before_value,
+                after: after_value,
+                before_secure,
+                after_secure,
+                regressed: before_secure && !after_secure,
+            });
+        }
+    }
+    for (_, before) in before.into_iter().filter(|(_, h)| h.is_present) {
+        let before_secure = before.is_secure;
+        changes.push(ChangeRecord::Header {
+            name: before.header_name,
+            before: before.header_value,
+            after: None,
+            before_secure,
+            after_secure: false,
+            regressed: before_secure,
+        });
+    }
+    changes
+}
+
+fn tls_change(before: Option<SslAnalysis>, after: Option<SslAnalysis>) -> Option<ChangeRecord> {
+    // 其中一次沒有分析 TLS 時無從比較
+    let (before, after) = (before?, after?);
+    if before.grade == after.grade && before.grade_factors == after.grade_factors {
+        return None;
+    }
+    let regressed = match (&before.grade, &after.grade) {
+        (Some(b), Some(a)) => grade_rank(a) > grade_rank(b),
+        _ => false,
+    };
+    Some(ChangeRecord::TlsGrade {
+        before: before.grade,
+        after: after.grade,
+        factors_before: before.grade_factors.unwrap_or_default(),
+        factors_after: after.grade_factors.unwrap_or_default(),
+        regressed,
+    })
+}
+
+fn technology_changes(before: Vec<DetectedTechnology>, after: Vec<DetectedTechnology>) -> Vec<ChangeRecord> {
+    let index = |technologies: Vec<DetectedTechnology>| -> BTreeMap<String, (String, Option<String>)> {
+        technologies
+            .into_iter()
+            .map(|t| (t.technology_name.to_lowercase(), (t.technology_name, t.technology_version)))
+            .collect()
+    };
+    let before = index(before);
+    let mut after = index(after);
+
+    let mut changes = Vec::new();
+    for (key, (name, before_version)) in before {
+        match after.remove(&key) {
+            Some((_, after_version)) if after_version == before_version => {}
+            Some((_, after_version)) => changes.push(ChangeRecord::Technology { name, before_version, after_version }),
+            // 技術不再偵測到：版本變為無
+            None => changes.push(ChangeRecord::Technology { name, before_version, after_version: None }),
+        }
+    }
+    for (_, (name, after_version)) in after {
+        changes.push(ChangeRecord::Technology { name, before_version: None, after_version });
+    }
+    changes
+}
+
+/// 發現的指紋 -> (標題, 嚴重度, 證據摘錄)
+type FindingEvidence = BTreeMap<String, (String, Option<String>, Option<String>)>;
+
+fn finding_evidence(report: &ScanReport) -> FindingEvidence {
+    let target = &report.task.target_url;
+    report
+        .vulnerabilities
+        .iter()
+        .map(|finding| {
+            let fingerprint = finding_fingerprint(&format!("{:?}", finding.result_type).to_lowercase(), &finding.title, target);
+            let severity = finding.severity.as_ref().map(|s| s.to_string());
+            (fingerprint, (finding.title.clone(), severity, evidence_excerpt(finding)))
+        })
+        .collect()
+}
+
+fn finding_changes(before: FindingEvidence, mut after: FindingEvidence) -> Vec<ChangeRecord> {
+    before
+        .into_iter()
+        .filter_map(|(fingerprint, (title, severity_before, evidence_before))| {
+            let (_, severity_after, evidence_after) = after.remove(&fingerprint)?;
+            if severity_before == severity_after && evidence_before == evidence_after {
+                return None;
+            }
+            Some(ChangeRecord::Finding { fingerprint, title, severity_before, severity_after, evidence_before, evidence_after })
+        })
+        .collect()
+}
+
+/// raw_data 中的證據欄位，沒有時使用描述
+fn evidence_excerpt(finding: &ScanResult) -> Option<String> {
+    let raw: Option<serde_json::Value> = finding.raw_data.as_deref().and_then(|raw| serde_json::from_str(raw).ok());
+    let evidence = raw
+        .as_ref()
+        .and_then(|raw| ["evidence", "excerpt", "matched"].iter().find_map(|key| raw.get(*key)))
+        .map(|value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .or_else(|| finding.description.clone())?;
+    Some(evidence.chars().take(EXCERPT_LIMIT).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(id: &str, title: &str, severity: Severity, url: &str, owasp: &str) -> DiffFinding {
+        DiffFinding {
+            id: id.to_string(),
+            title: title.to_string(),
+            severity: Some(severity),
+            affected_url: Some(url.to_string()),
+            owasp_category: Some(owasp.to_string()),
+        }
+    }
+
+    fn ids(findings: &[DiffFinding]) -> Vec<&str> {
+        findings.iter().map(|f| f.id.as_str()).collect()
+    }
+
+    #[test]
+    fn exact_match_ignores_case() {
+        let before = vec![finding("a", "Reflected XSS", Severity::High, "https://example.com/search", "A03")];
+        let after = vec![finding("b", "reflected xss", Severity::High, "HTTPS://example.com/search", "A03")];
+
+        let (fixed, new, matched) = match_findings(before, after);
+        assert!(fixed.is_empty() && new.is_empty());
+        assert_eq!(matched.len(), 1);
+        assert_eq!((matched[0].before.id.as_str(), matched[0].after.id.as_str()), ("a", "b"));
+        assert_eq!(matched[0].similarity, None);
+    }
+
+    #[test]
+    fn fuzzy_match_requires_the_threshold() {
+        let url = "https://example.com/search";
+        // 6 個詞中有 5 個相同：5/6 ≈ 0.83
+        let before = vec![finding("a", "Reflected XSS in search", Severity::High, url, "A03")];
+        let after = vec![finding("b", "Reflected XSS in search box", Severity::High, url, "A03")];
+        let (fixed, new, matched) = match_findings(before, after);
+        assert!(fixed.is_empty() && new.is_empty());
+        let similarity = matched[0].similarity.expect("fuzzy match");
+        assert!(similarity >= FUZZY_MATCH_THRESHOLD && similarity < 1.0, "{}", similarity);
+
+        // 8 個詞中只有 xss 與 in 相同：2/8 = 0.25
+        let before = vec![finding("a", "Reflected XSS in search", Severity::High, url, "A03")];
+        let after = vec![finding("b", "Stored XSS in profile", Severity::High, "https://example.com/profile", "A03")];
+        let (fixed, new, matched) = match_findings(before, after);
+        assert!(matched.is_empty());
+        assert_eq!(ids(&fixed), vec!["a"]);
+        assert_eq!(ids(&new), vec!["b"]);
+    }
+
+    #[test]
+    fn different_owasp_categories_never_fuzzy_match() {
+        let url = "https://example.com/search";
+        let before = vec![finding("a", "Reflected XSS in search", Severity::High, url, "A03")];
+        let after = vec![finding("b", "Reflected XSS in search box", Severity::High, url, "A01")];
+
+        let (fixed, new, matched) = match_findings(before, after);
+        assert!(matched.is_empty());
+        assert_eq!(ids(&fixed), vec!["a"]);
+        assert_eq!(ids(&new), vec!["b"]);
+    }
+
+    fn report(task_id: &str, severity: &str) -> ScanReport {
+        serde_json::from_value(serde_json::json!({
+            "task": {
+                "id": task_id,
+                "target_url": "https://example.com/",
+                "scan_type": "full",
+                "status": "completed",
+                "created_at": "2026-01-05T10:00:00Z",
+            },
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": [{
+                "id": format!("{}-xss", task_id),
+                "task_id": task_id,
+                "result_type": "vulnerability",
+                "severity": severity,
+                "title": "Reflected XSS",
+                "description": null,
+                "raw_data": null,
+                "created_at": "2026-01-05T10:00:00Z",
+                "details": { "affected_url": "https://example.com/search", "owasp_category": "A03:2021" },
+            }],
+        }))
+        .expect("valid report")
+    }
+
+    #[tokio::test]
+    async fn severity_change_is_neither_fixed_nor_new() {
+        let state = ScanState::new(None, None, None);
+        {
+            let mut results = state.scan_results.lock().await;
+            results.insert("scan-1".to_string(), report("scan-1", "medium"));
+            results.insert("scan-2".to_string(), report("scan-2", "high"));
+        }
+
+        let diff = scan_diff(&state, "scan-1", "scan-2").await.expect("diff");
+        assert!(diff.fixed.is_empty() && diff.new.is_empty() && diff.persisting.is_empty());
+        assert_eq!(diff.severity_changed.len(), 1);
+        assert_eq!(diff.severity_changed[0].before.severity, Some(Severity::Medium));
+        assert_eq!(diff.severity_changed[0].after.severity, Some(Severity::High));
+    }
+}
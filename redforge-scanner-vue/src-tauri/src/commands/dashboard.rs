@@ -0,0 +1,697 @@
+/**
+ * Dashboard Commands
+ *
+ * 提供儀表板所需的彙總統計，避免前端透過 IPC 取回所有報告再自行計算
+ */
+
+use crate::commands::scan::{ScanReport, ScanState};
+use crate::csv_export::escape_field;
+use crate::models::*;
+use crate::scanners::check_metrics::CheckMetrics;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
+
+const TOP_TARGETS_LIMIT: usize = 5;
+const TOP_CHECKS_LIMIT: usize = 10;
+pub(crate) const CERT_EXPIRY_WINDOW_DAYS: i64 = 30;
+/// 檢查在期間內至少送出這麼多請求且沒有任何發現時，列為建議從快速掃描停用
+const DISABLE_CANDIDATE_MIN_REQUESTS: u32 = 50;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DashboardStats {
+    pub range_since: Option<DateTime<Utc>>,
+    pub range_until: Option<DateTime<Utc>>,
+    pub scans_by_status: HashMap<String, usize>,
+    pub scans_last_7_days: usize,
+    pub scans_last_30_days: usize,
+    pub open_findings_by_severity: HashMap<String, usize>,
+    pub top_targets: Vec<TargetRisk>,
+    pub expiring_certificates: Vec<ExpiringCertificate>,
+    pub top_checks: Vec<CheckCount>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// 依未處理的 Critical/High 數量排序的目標
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TargetRisk {
+    pub target: String,
+    pub critical: usize,
+    pub high: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExpiringCertificate {
+    pub target: String,
+    pub subject: Option<String>,
+    pub valid_to: DateTime<Utc>,
+    pub days_remaining: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckCount {
+    pub check_id: String,
+    pub count: usize,
+}
+
+/// 使用量摘要，只包含計數，不含目標或發現內容
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ActivitySummary {
+    pub range_since: Option<DateTime<Utc>>,
+    pub range_until: Option<DateTime<Utc>>,
+    pub total_scans: usize,
+    pub scans_per_day: BTreeMap<String, usize>,
+    pub scans_per_type: BTreeMap<String, usize>,
+    pub average_duration_secs: Option<f64>,
+    pub failure_rate: f64,
+    pub top_error_kinds: Vec<ErrorKindCount>,
+    pub markdown: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorKindCount {
+    pub kind: String,
+    pub count: usize,
+}
+
+/// 各檢查跨掃描的執行效率
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckEfficiencyReport {
+    pub range_since: Option<DateTime<Utc>>,
+    pub range_until: Option<DateTime<Utc>>,
+    /// 有檢查統計的掃描數
+    pub scans: usize,
+    /// 建議停用的檢查在前，其次依每百個請求的發現數由低到高
+    pub checks: Vec<CheckEfficiency>,
+    pub csv: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckEfficiency {
+    pub check_id: String,
+    /// 執行過此檢查的掃描數
+    pub runs: u32,
+    pub requests: u64,
+    pub findings: u64,
+    pub average_duration_ms: u64,
+    /// 以逾時失敗的請求比例 (0-1)
+    pub timeout_rate: f64,
+    pub findings_per_100_requests: f64,
+    /// 送出大量請求卻從未產生發現，建議從快速掃描停用
+    pub disable_candidate: bool,
+}
+
+/// 統計用的單筆掃描紀錄
+struct ActivityRecord {
+    scan_type: String,
+    status: String,
+    created_at: DateTime<Utc>,
+    duration_secs: Option<f64>,
+    error_kind: Option<&'static str>,
+}
+
+/// 取得儀表板統計
+///
+/// `since` / `until` 為 RFC 3339 時間，限制納入統計的掃描建立時間，
+/// 前端可分別查詢本月與上月來比較。結果依時間範圍快取，掃描完成、匯入或發現狀態變更後失效。
+///
+/// 統計在 redforge.db 上以 SQL 計算，未處理的發現只計入 open / confirmed 且未抑制的發現；
+/// 資料庫無法使用時改從記憶體中的報告計算 (此時無法變更發現狀態，所有發現都是未處理)。
+#[tauri::command]
+pub async fn get_dashboard_stats(
+    since: Option<String>,
+    until: Option<String>,
+    state: State<'_, ScanState>,
+) -> Result<DashboardStats, String> {
+    let since = parse_range_bound(since.as_deref())?;
+    let until = parse_range_bound(until.as_deref())?;
+    let cache_key = format!("{:?}|{:?}", since, until);
+
+    if let Some(stats) = state.dashboard_cache.lock().await.get(&cache_key) {
+        return Ok(stats.clone());
+    }
+
+    let now = Utc::now();
+    let stats = match state.store() {
+        Some(store) => store.dashboard_stats(since, until, now).await.map_err(|e| format!("資料庫查詢失敗: {}", e))?,
+        None => {
+            let tasks = state.current_tasks.lock().await.clone();
+            let results = state.scan_results.lock().await;
+            compute_stats(&tasks, &results, since, until, now)
+        }
+    };
+    let stats = rank(stats);
+
+    state.dashboard_cache.lock().await.insert(cache_key, stats.clone());
+    Ok(stats)
+}
+
+/// 清除統計快取，掃描完成或匯入資料後呼叫
+pub async fn invalidate_dashboard_cache(state: &ScanState) {
+    state.dashboard_cache.lock().await.clear();
+}
+
+/// 產生使用量摘要 (JSON 與可直接貼到狀態更新的 Markdown)
+///
+/// 只在本機從記憶體與 redforge.db 中的掃描任務計算，不會傳送到外部，
+/// 輸出也不包含任何目標或發現內容
+#[tauri::command]
+pub async fn generate_activity_summary(
+    since: Option<String>,
+    until: Option<String>,
+    state: State<'_, ScanState>,
+) -> Result<ActivitySummary, String> {
+    let since = parse_range_bound(since.as_deref())?;
+    let until = parse_range_bound(until.as_deref())?;
+
+    let mut records = activity_records(&state).await?;
+    records.retain(|r| since.map_or(true, |s| r.created_at >= s) && until.map_or(true, |u| r.created_at < u));
+    Ok(summarize_activity(&records, since, until))
+}
+
+/// 記憶體中的任務，加上已持久化但不在記憶體中的歷史任務；兩者的失敗都依任務記錄的 error_kind 歸類
+async fn activity_records(state: &ScanState) -> Result<Vec<ActivityRecord>, String> {
+    let tasks = state.current_tasks.lock().await.clone();
+    let mut records: Vec<ActivityRecord> = tasks
+        .iter()
+        .map(|task| ActivityRecord {
+            scan_type: task.scan_type.to_string(),
+            status: task.status.to_string(),
+            created_at: task.created_at,
+            duration_secs: duration_secs(task.started_at, task.completed_at),
+            error_kind: failure_kind(task.error.as_deref(), task.error_kind),
+        })
+        .collect();
+
+    // 已持久化但不在記憶體中的歷史任務
+    if let Some(store) = state.store() {
+        let persisted = store.tasks().await.map_err(|e| format!("資料庫查詢失敗: {}", e))?;
+        let parse = |s: &str| DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc));
+        for task in persisted {
+            if tasks.iter().any(|t| t.id == task.id) {
+                continue;
+            }
+            let Some(created_at) = parse(&task.created_at) else { continue };
+            records.push(ActivityRecord {
+                duration_secs: duration_secs(
+                    task.started_at.as_deref().and_then(parse),
+                    task.completed_at.as_deref().and_then(parse),
+                ),
+                scan_type: task.scan_type,
+                status: task.status,
+                created_at,
+                error_kind: failure_kind(task.error.as_deref(), task.error_kind),
+            });
+        }
+    }
+
+    Ok(records)
+}
+
+/// 彙總各檢查的請求數、發現數、平均耗時與逾時率 (JSON 與 CSV)
+///
+/// 資料來自記憶體中的報告與 redforge.db 中已保存的掃描；`since` / `until` 為 RFC 3339 時間，
+/// 限制納入統計的掃描建立時間
+#[tauri::command]
+pub async fn get_check_efficiency(
+    since: Option<String>,
+    until: Option<String>,
+    state: State<'_, ScanState>,
+) -> Result<CheckEfficiencyReport, String> {
+    let since = parse_range_bound(since.as_deref())?;
+    let until = parse_range_bound(until.as_deref())?;
+    let in_range = |created_at: DateTime<Utc>| {
+        since.map_or(true, |s| created_at >= s) && until.map_or(true, |u| created_at < u)
+    };
+
+    let mut scans: HashMap<String, Vec<CheckMetrics>> = state
+        .scan_results
+        .lock()
+        .await
+        .iter()
+        .filter(|(_, report)| !report.check_metrics.is_empty() && in_range(report.task.created_at))
+        .map(|(id, report)| (id.clone(), report.check_metrics.clone()))
+        .collect();
+
+    if let Some(store) = state.store() {
+        let persisted = store.check_metrics().await.map_err(|e| format!("資料庫查詢失敗: {}", e))?;
+        for (id, created_at, metrics) in persisted {
+            let Ok(created_at) = DateTime::parse_from_rfc3339(&created_at) else { continue };
+            if in_range(created_at.with_timezone(&Utc)) {
+                scans.entry(id).or_insert(metrics);
+            }
+        }
+    }
+
+    let scan_count = scans.len();
+    let checks = aggregate_check_efficiency(scans.into_values().flatten());
+    let csv = render_check_efficiency_csv(&checks);
+    Ok(CheckEfficiencyReport { range_since: since, range_until: until, scans: scan_count, checks, csv })
+}
+
+fn aggregate_check_efficiency(metrics: impl Iterator<Item = CheckMetrics>) -> Vec<CheckEfficiency> {
+    // check_id -> (runs, requests, timeouts, findings, duration_ms)
+    let mut totals: BTreeMap<String, (u32, u64, u64, u64, u64)> = BTreeMap::new();
+    for m in metrics {
+        let total = totals.entry(m.check_id).or_default();
+        total.0 += 1;
+        total.1 += u64::from(m.requests);
+        total.2 += u64::from(m.timeouts);
+        total.3 += u64::from(m.findings);
+        total.4 += m.duration_ms;
+    }
+
+    let mut checks: Vec<CheckEfficiency> = totals
+        .into_iter()
+        .map(|(check_id, (runs, requests, timeouts, findings, duration_ms))| CheckEfficiency {
+            check_id,
+            runs,
+            requests,
+            findings,
+            average_duration_ms: duration_ms / u64::from(runs.max(1)),
+            timeout_rate: if requests == 0 { 0.0 } else { timeouts as f64 / requests as f64 },
+            findings_per_100_requests: if requests == 0 { 0.0 } else { findings as f64 * 100.0 / requests as f64 },
+            disable_candidate: findings == 0 && requests >= u64::from(DISABLE_CANDIDATE_MIN_REQUESTS),
+        })
+        .collect();
+
+    checks.sort_by(|a, b| {
+        b.disable_candidate
+            .cmp(&a.disable_candidate)
+            .then_with(|| a.findings_per_100_requests.total_cmp(&b.findings_per_100_requests))
+            .then_with(|| b.requests.cmp(&a.requests))
+    });
+    checks
+}
+
+fn render_check_efficiency_csv(checks: &[CheckEfficiency]) -> String {
+    let mut csv = String::from("check_id,runs,requests,findings,average_duration_ms,timeout_rate,findings_per_100_requests,disable_candidate\n");
+    for check in checks {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{:.4},{:.2},{}\n",
+            escape_field(&check.check_id),
+            check.runs,
+            check.requests,
+            check.findings,
+            check.average_duration_ms,
+            check.timeout_rate,
+            check.findings_per_100_requests,
+            check.disable_candidate
+        ));
+    }
+    csv
+}
+
+fn summarize_activity(
+    records: &[ActivityRecord],
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> ActivitySummary {
+    let mut scans_per_day = BTreeMap::new();
+    let mut scans_per_type = BTreeMap::new();
+    let mut error_kinds: HashMap<&str, usize> = HashMap::new();
+
+    for record in records {
+        *scans_per_day.entry(record.created_at.format("%Y-%m-%d").to_string()).or_insert(0) += 1;
+        *scans_per_type.entry(record.scan_type.clone()).or_insert(0) += 1;
+        if let Some(kind) = record.error_kind {
+            *error_kinds.entry(kind).or_insert(0) += 1;
+        }
+    }
+
+    let durations: Vec<f64> = records.iter().filter_map(|r| r.duration_secs).collect();
+    let average_duration_secs = if durations.is_empty() {
+        None
+    } else {
+        Some(durations.iter().sum::<f64>() / durations.len() as f64)
+    };
+
+    // 失敗率只計算已結束的掃描
+    let finished = records.iter().filter(|r| r.status == "completed" || r.status == "failed").count();
+    let failed = records.iter().filter(|r| r.status == "failed").count();
+    let failure_rate = if finished == 0 { 0.0 } else { failed as f64 / finished as f64 };
+
+    let mut top_error_kinds: Vec<ErrorKindCount> = error_kinds
+        .into_iter()
+        .map(|(kind, count)| ErrorKindCount { kind: kind.to_string(), count })
+        .collect();
+    top_error_kinds.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.kind.cmp(&b.kind)));
+    top_error_kinds.truncate(5);
+
+    let mut summary = ActivitySummary {
+        range_since: since,
+        range_until: until,
+        total_scans: records.len(),
+        scans_per_day,
+        scans_per_type,
+        average_duration_secs,
+        failure_rate,
+        top_error_kinds,
+        markdown: String::new(),
+    };
+    summary.markdown = render_activity_markdown(&summary);
+    summary
+}
+
+fn render_activity_markdown(summary: &ActivitySummary) -> String {
+    let range = match (summary.range_since, summary.range_until) {
+        (Some(s), Some(u)) => format!("{} ~ {}", s.format("%Y-%m-%d"), u.format("%Y-%m-%d")),
+        (Some(s), None) => format!("{} 起", s.format("%Y-%m-%d")),
+        (None, Some(u)) => format!("{} 前", u.format("%Y-%m-%d")),
+        (None, None) => "全部期間".to_string(),
+    };
+
+    let mut md = String::new();
+    md.push_str(&format!("## RedForge 掃描使用摘要 ({})\n\n", range));
+    md.push_str(&format!("- 掃描次數: {}\n", summary.total_scans));
+    md.push_str(&format!(
+        "- 平均耗時: {}\n",
+        summary.average_duration_secs
+            .map(|d| format!("{:.1} 秒", d))
+            .unwrap_or_else(|| "-".to_string())
+    ));
+    md.push_str(&format!("- 失敗率: {:.1}%\n\n", summary.failure_rate * 100.0));
+
+    md.push_str("### 掃描類型\n\n| 類型 | 次數 |\n|------|------|\n");
+    for (scan_type, count) in &summary.scans_per_type {
+        md.push_str(&format!("| {} | {} |\n", scan_type, count));
+    }
+
+    md.push_str("\n### 每日掃描\n\n| 日期 | 次數 |\n|------|------|\n");
+    for (day, count) in &summary.scans_per_day {
+        md.push_str(&format!("| {} | {} |\n", day, count));
+    }
+
+    if !summary.top_error_kinds.is_empty() {
+        md.push_str("\n### 常見錯誤類型\n\n| 類型 | 次數 |\n|------|------|\n");
+        for error in &summary.top_error_kinds {
+            md.push_str(&format!("| {} | {} |\n", error.kind, error.count));
+        }
+    }
+
+    md
+}
+
+fn duration_secs(started_at: Option<DateTime<Utc>>, completed_at: Option<DateTime<Utc>>) -> Option<f64> {
+    match (started_at, completed_at) {
+        (Some(start), Some(end)) if end >= start => Some((end - start).num_milliseconds() as f64 / 1000.0),
+        _ => None,
+    }
+}
+
+/// 失敗的種類，摘要中只出現種類，不出現可能包含目標的原始訊息；沒有記錄種類的舊任務歸為 other
+fn failure_kind(error: Option<&str>, kind: Option<ScanErrorKind>) -> Option<&'static str> {
+    if error.is_none() && kind.is_none() {
+        return None;
+    }
+    Some(match kind {
+        Some(ScanErrorKind::Network) => "network",
+        Some(ScanErrorKind::Timeout) => "timeout",
+        Some(ScanErrorKind::Dns) => "dns",
+        Some(ScanErrorKind::Tls) => "tls",
+        Some(ScanErrorKind::Parse) => "parse",
+        Some(ScanErrorKind::InvalidTarget) => "invalid_target",
+        Some(ScanErrorKind::Config) => "config",
+        Some(ScanErrorKind::Blocked) => "blocked",
+        Some(ScanErrorKind::Interrupted) => "interrupted",
+        Some(ScanErrorKind::Internal) => "internal",
+        Some(ScanErrorKind::Proxy) => "proxy",
+        None => "other",
+    })
+}
+
+fn parse_range_bound(value: Option<&str>) -> Result<Option<DateTime<Utc>>, String> {
+    match value {
+        None | Some("") => Ok(None),
+        Some(s) => DateTime::parse_from_rfc3339(s)
+            .map(|dt| Some(dt.with_timezone(&Utc)))
+            .map_err(|_| format!("無效的日期格式: {}", s)),
+    }
+}
+
+/// 從記憶體中的任務與報告計算統計，資料庫無法使用時使用
+fn compute_stats(
+    tasks: &[ScanTask],
+    results: &HashMap<String, ScanReport>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> DashboardStats {
+    let in_range = |created_at: DateTime<Utc>| {
+        since.map_or(true, |s| created_at >= s) && until.map_or(true, |u| created_at < u)
+    };
+
+    let mut stats = DashboardStats {
+        range_since: since,
+        range_until: until,
+        generated_at: now,
+        ..Default::default()
+    };
+
+    for task in tasks.iter().filter(|t| in_range(t.created_at)) {
+        *stats.scans_by_status.entry(task.status.to_string()).or_insert(0) += 1;
+    }
+    stats.scans_last_7_days = tasks.iter().filter(|t| t.created_at >= now - Duration::days(7)).count();
+    stats.scans_last_30_days = tasks.iter().filter(|t| t.created_at >= now - Duration::days(30)).count();
+
+    // 每個目標只看範圍內最近一次完成的掃描，較舊掃描中的發現視為已被取代
+    let mut latest: HashMap<&str, &ScanReport> = HashMap::new();
+    for report in results.values() {
+        if report.task.status != ScanStatus::Completed || !in_range(report.task.created_at) {
+            continue;
+        }
+        let entry = latest.entry(report.task.target_url.as_str()).or_insert(report);
+        if report.task.created_at > entry.task.created_at {
+            *entry = report;
+        }
+    }
+
+    let mut check_counts: HashMap<String, usize> = HashMap::new();
+    for (target, report) in &latest {
+        // 讀取報告上的摘要，與摘要卡片、匯出的數字一致；舊報告沒有摘要時當場計算
+        let summary = report.summary.clone().unwrap_or_else(|| {
+            crate::summary::summarize(report.vulnerabilities.iter().map(crate::summary::SummaryInput::from_result))
+        });
+        let risk = TargetRisk {
+            target: target.to_string(),
+            critical: summary.count("critical") as usize,
+            high: summary.count("high") as usize,
+        };
+
+        for (severity, count) in &summary.by_severity {
+            *stats.open_findings_by_severity.entry(severity.clone()).or_insert(0) += *count as usize;
+        }
+        for (check_id, count) in summary.by_check_id {
+            *check_counts.entry(check_id).or_insert(0) += count as usize;
+        }
+
+        if risk.critical + risk.high > 0 {
+            stats.top_targets.push(risk);
+        }
+
+        // 每個分析過的 TLS 連接埠都可能有各自的憑證
+        for (index, ssl) in report.ssl_analysis.iter().enumerate() {
+            let Some(valid_to) = ssl.valid_to else { continue };
+            let days_remaining = (valid_to - now).num_days();
+            if valid_to >= now && days_remaining <= CERT_EXPIRY_WINDOW_DAYS {
+                stats.expiring_certificates.push(ExpiringCertificate {
+                    target: match ssl.analyzed_port {
+                        Some(port) if index > 0 => format!("{}:{}", target, port),
+                        _ => target.to_string(),
+                    },
+                    subject: ssl.certificate_subject.clone(),
+                    valid_to,
+                    days_remaining,
+                });
+            }
+        }
+    }
+
+    stats.top_checks = check_counts
+        .into_iter()
+        .map(|(check_id, count)| CheckCount { check_id, count })
+        .collect();
+
+    stats
+}
+
+/// 依風險排序目標、依數量排序檢查並各取前幾名，憑證依到期日排序
+fn rank(mut stats: DashboardStats) -> DashboardStats {
+    stats.top_targets.sort_by(|a, b| {
+        (b.critical, b.high).cmp(&(a.critical, a.high)).then_with(|| a.target.cmp(&b.target))
+    });
+    stats.top_targets.truncate(TOP_TARGETS_LIMIT);
+
+    stats.expiring_certificates.sort_by_key(|c| c.valid_to);
+
+    stats.top_checks.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.check_id.cmp(&b.check_id)));
+    stats.top_checks.truncate(TOP_CHECKS_LIMIT);
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::store::Store;
+
+    /// error 為 (種類, 訊息)；沒有種類的是記錄種類之前的舊任務
+    fn task(id: &str, status: &str, error: Option<(Option<&str>, &str)>, created_at: &str) -> ScanTask {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "target_url": "https://example.com/",
+            "scan_type": "full",
+            "status": status,
+            "created_at": created_at,
+            "started_at": created_at,
+            "completed_at": created_at,
+            "error": error.map(|(_, message)| message),
+            "error_kind": error.and_then(|(kind, _)| kind),
+        }))
+        .expect("valid task")
+    }
+
+    /// 一個仍在記憶體中的掃描，其餘只存在於資料庫 (重新啟動後的歷史)
+    async fn seeded_state() -> ScanState {
+        let store = Store::open_migrated().await;
+        let history = [
+            task("scan-1", "completed", None, "2026-03-01T09:00:00Z"),
+            task("scan-2", "failed", Some((Some("timeout"), "連線逾時 (30 秒)")), "2026-03-01T10:00:00Z"),
+            task("scan-3", "failed", Some((Some("tls"), "TLS handshake failed: certificate verify failed")), "2026-03-02T09:00:00Z"),
+            task("scan-4", "failed", Some((None, "request timed out")), "2026-03-02T10:00:00Z"),
+            task("scan-5", "cancelled", None, "2026-03-03T09:00:00Z"),
+        ];
+        for task in &history {
+            store.save_task(task).await.expect("save task");
+        }
+
+        let state = ScanState::new(Some(store), None, None);
+        state.current_tasks.lock().await.push(task("scan-6", "failed", Some((Some("dns"), "DNS 解析失敗，無法連線")), "2026-03-03T10:00:00Z"));
+        state
+    }
+
+    fn finding(id: &str, task_id: &str, severity: &str, check_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "task_id": task_id,
+            "result_type": "vulnerability",
+            "severity": severity,
+            "title": format!("Finding {}", id),
+            "description": null,
+            "raw_data": serde_json::json!({ "check_id": check_id }).to_string(),
+            "created_at": "2026-03-01T09:00:00Z",
+        })
+    }
+
+    fn report(id: &str, created_at: &str, findings: Vec<serde_json::Value>, ssl: Vec<serde_json::Value>) -> ScanReport {
+        serde_json::from_value(serde_json::json!({
+            "task": task(id, "completed", None, created_at),
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": findings,
+            "ssl_analysis": ssl,
+        }))
+        .expect("valid report")
+    }
+
+    #[tokio::test]
+    async fn open_findings_respect_triage_and_group_by_check_id() {
+        let store = Store::open_migrated().await;
+        let now: DateTime<Utc> = "2026-03-10T00:00:00Z".parse().unwrap();
+        let expires = |days: i64| (now + Duration::days(days)).to_rfc3339();
+
+        // 較舊的掃描被同目標的最新掃描取代
+        store.save_report(&report("old", "2026-03-01T09:00:00Z", vec![finding("old-sqli", "old", "critical", "sqli")], vec![]))
+            .await
+            .unwrap();
+        let latest = report(
+            "new",
+            "2026-03-02T09:00:00Z",
+            vec![
+                finding("sqli-1", "new", "critical", "sqli"),
+                finding("sqli-2", "new", "high", "sqli"),
+                finding("xss", "new", "high", "xss"),
+                finding("fixed", "new", "critical", "sqli"),
+                finding("suppressed", "new", "high", "cors"),
+                finding("false-positive", "new", "medium", "cors"),
+                finding("confirmed", "new", "medium", "csp"),
+            ],
+            vec![
+                serde_json::json!({ "id": "ssl-1", "task_id": "new", "valid_to": expires(10), "created_at": "2026-03-02T09:00:00Z" }),
+                serde_json::json!({ "id": "ssl-2", "task_id": "new", "valid_to": expires(90), "created_at": "2026-03-02T09:00:00Z", "analyzed_port": 8443 }),
+                serde_json::json!({ "id": "ssl-3", "task_id": "new", "valid_to": expires(5), "created_at": "2026-03-02T09:00:00Z", "analyzed_port": 9443 }),
+            ],
+        );
+        store.save_report(&latest).await.unwrap();
+        store.update_finding_triage("fixed", Some("fixed"), None).await.unwrap();
+        store.update_finding_triage("suppressed", None, Some(true)).await.unwrap();
+        store.update_finding_triage("false-positive", Some("false_positive"), None).await.unwrap();
+        store.update_finding_triage("confirmed", Some("confirmed"), None).await.unwrap();
+
+        let stats = rank(store.dashboard_stats(None, None, now).await.unwrap());
+        assert_eq!(stats.scans_by_status.get("completed"), Some(&2));
+        assert_eq!(stats.scans_last_30_days, 2);
+        let by_severity: std::collections::BTreeMap<_, _> = stats.open_findings_by_severity.clone().into_iter().collect();
+        assert_eq!(by_severity, [("critical".to_string(), 1), ("high".to_string(), 2), ("medium".to_string(), 1)].into_iter().collect());
+
+        let checks: Vec<(&str, usize)> = stats.top_checks.iter().map(|c| (c.check_id.as_str(), c.count)).collect();
+        assert_eq!(checks, vec![("sqli", 2), ("csp", 1), ("xss", 1)]);
+        assert_eq!(stats.top_targets.len(), 1);
+        assert_eq!((stats.top_targets[0].critical, stats.top_targets[0].high), (1, 2));
+
+        let certificates: Vec<&str> = stats.expiring_certificates.iter().map(|c| c.target.as_str()).collect();
+        assert_eq!(certificates, vec!["https://example.com/:9443", "https://example.com/"]);
+
+        // 時間範圍只含較舊的掃描時，該掃描就是範圍內的最新掃描
+        let until: DateTime<Utc> = "2026-03-02T00:00:00Z".parse().unwrap();
+        let stats = store.dashboard_stats(None, Some(until), now).await.unwrap();
+        assert_eq!(stats.open_findings_by_severity.get("critical"), Some(&1));
+        assert_eq!(stats.scans_by_status.get("completed"), Some(&1));
+    }
+
+    #[test]
+    fn check_ids_are_escaped_in_the_efficiency_csv() {
+        let check = |check_id: &str| CheckEfficiency {
+            check_id: check_id.to_string(),
+            runs: 1,
+            requests: 10,
+            findings: 0,
+            average_duration_ms: 5,
+            timeout_rate: 0.0,
+            findings_per_100_requests: 0.0,
+            disable_candidate: false,
+        };
+        let csv = render_check_efficiency_csv(&[check("sqli"), check("path,\"traversal\"")]);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[1], "sqli,1,10,0,5,0.0000,0.00,false");
+        assert_eq!(rows[2], "\"path,\"\"traversal\"\"\",1,10,0,5,0.0000,0.00,false");
+    }
+
+    #[tokio::test]
+    async fn classifies_errors_of_persisted_tasks() {
+        let state = seeded_state().await;
+        let records = activity_records(&state).await.unwrap();
+        let summary = summarize_activity(&records, None, None);
+
+        assert_eq!(summary.total_scans, 6);
+        let kinds: Vec<(&str, usize)> = summary.top_error_kinds.iter().map(|e| (e.kind.as_str(), e.count)).collect();
+        // 種類取自任務記錄的 error_kind，不從訊息文字推測
+        assert_eq!(kinds, vec![("dns", 1), ("other", 1), ("timeout", 1), ("tls", 1)]);
+        // 取消的掃描不算失敗，也不會被資料庫的 status 欄位當成 failed
+        assert_eq!(summary.failure_rate, 4.0 / 5.0);
+        assert_eq!(summary.scans_per_day.get("2026-03-03"), Some(&2));
+    }
+
+    #[tokio::test]
+    async fn in_memory_tasks_are_not_counted_twice() {
+        let state = seeded_state().await;
+        let persisted = task("scan-2", "failed", Some((Some("timeout"), "連線逾時 (30 秒)")), "2026-03-01T10:00:00Z");
+        state.current_tasks.lock().await.push(persisted);
+
+        let records = activity_records(&state).await.unwrap();
+        assert_eq!(records.len(), 6);
+        assert_eq!(records.iter().filter(|r| r.error_kind == Some("timeout")).count(), 1);
+    }
+}
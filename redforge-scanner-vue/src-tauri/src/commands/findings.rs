@@ -0,0 +1,419 @@
+/**
+ * Findings Commands
+ *
+ * 跨掃描查詢已保存的發現 (例如「所有目標中未處理的 Critical」)，供全域發現列表與批次匯出使用。
+ * 篩選、排序與分頁都在 SQL 中完成，不把報告載入記憶體。
+ * 發現的處理狀態與抑制變更後重新計算掃描摘要。
+ * export_findings_csv 將一或多次掃描的發現匯出成 CSV，供在試算表中分派處理。
+ */
+
+use crate::commands::collaboration::export_evidence;
+use crate::commands::dashboard::invalidate_dashboard_cache;
+use crate::commands::scan::ScanState;
+use crate::csv_export;
+use crate::database::store::{FindingFilters, FindingPage};
+use crate::models::{ScanResult, Severity};
+use crate::output::{check_writable, write_atomic, WrittenFile};
+use crate::report::severity_label;
+use crate::summary::{owasp_category, summarize, ReportSummary};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use tauri::State;
+
+/// CSV 的欄位，匯出多次掃描時最前面加上 task_id
+const CSV_COLUMNS: [&str; 10] =
+    ["id", "severity", "owasp_category", "title", "affected_url", "description", "cvss", "cve", "discovered_at", "status"];
+/// 可能含有目標回應內容的欄位，以 = + - @ 開頭時加上單引號避免被當成公式
+const CSV_TEXT_COLUMNS: [&str; 3] = ["title", "description", "evidence"];
+
+/// export_findings_csv 的選項
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CsvExportOptions {
+    /// 只匯出此嚴重程度以上的發現；沒有嚴重程度的發現視為 info
+    pub min_severity: Option<Severity>,
+    /// 加上 evidence 欄位 (raw_data 與擷取的請求/回應，格式與 JSON 匯出相同)
+    pub include_evidence: bool,
+    /// 目的地已存在時覆寫，否則改用編號檔名
+    pub overwrite: bool,
+}
+
+/// 依篩選條件查詢所有掃描的發現，每筆附上所屬掃描與目標
+#[tauri::command]
+pub async fn get_findings(filters: Option<FindingFilters>, state: State<'_, ScanState>) -> Result<FindingPage, String> {
+    let filters = filters.unwrap_or_default();
+    filters.validate()?;
+
+    let store = state.store().ok_or("資料庫無法使用，無法查詢歷史發現")?;
+    store.query_findings(&filters).await.map_err(|e| format!("查詢發現失敗: {}", e))
+}
+
+/// 變更發現的處理狀態或抑制，並重新計算所屬掃描的摘要
+///
+/// status 為 open / confirmed / fixed / accepted_risk / false_positive；省略的欄位維持不變
+#[tauri::command]
+pub async fn update_finding_status(
+    finding_id: String,
+    status: Option<String>,
+    suppressed: Option<bool>,
+    state: State<'_, ScanState>,
+) -> Result<ReportSummary, String> {
+    update_triage(state.inner(), &finding_id, status.as_deref(), suppressed).await
+}
+
+async fn update_triage(state: &ScanState, finding_id: &str, status: Option<&str>, suppressed: Option<bool>) -> Result<ReportSummary, String> {
+    let store = state.store().ok_or("資料庫無法使用，無法更新發現狀態")?;
+    let task_id = store
+        .update_finding_triage(finding_id, status, suppressed)
+        .await
+        .map_err(|e| format!("更新發現狀態失敗: {}", e))?
+        .ok_or_else(|| format!("找不到發現 {}", finding_id))?;
+
+    let summary = recompute_summary(state, &task_id).await?;
+    tracing::info!("📊 已更新發現 {} 並重新計算掃描 {} 的摘要", finding_id, task_id);
+    Ok(summary)
+}
+
+/// 以資料庫中的狀態與抑制重新計算掃描的摘要，寫回資料庫與記憶體中的報告
+///
+/// 信心度沒有保存在資料庫，報告仍在記憶體中時沿用報告上的標記
+pub(crate) async fn recompute_summary(state: &ScanState, task_id: &str) -> Result<ReportSummary, String> {
+    let store = state.store().ok_or("資料庫無法使用，無法重新計算摘要")?;
+    let mut inputs = store.summary_inputs(task_id).await.map_err(|e| format!("讀取發現失敗: {}", e))?;
+
+    let mut results = state.scan_results.lock().await;
+    if let Some(report) = results.get(task_id) {
+        for (id, input) in &mut inputs {
+            input.low_confidence = report.vulnerabilities.iter().any(|f| f.id == *id && f.low_confidence);
+        }
+    }
+    let summary = summarize(inputs.into_iter().map(|(_, input)| input));
+    if let Some(report) = results.get_mut(task_id) {
+        report.summary = Some(summary.clone());
+    }
+    drop(results);
+
+    store.save_summary(task_id, &summary).await.map_err(|e| format!("保存摘要失敗: {}", e))?;
+    invalidate_dashboard_cache(state).await;
+    Ok(summary)
+}
+
+/// 將掃描的發現匯出成 CSV 檔案；指定多個 task_id 時合併成一個檔案並加上 task_id 欄位
+///
+/// 已抑制的發現不列入，標記為誤報的發現仍列出並在 status 欄註明。資料庫無法使用時 status 為 open
+#[tauri::command]
+pub async fn export_findings_csv(
+    task_ids: Vec<String>,
+    path: String,
+    options: Option<CsvExportOptions>,
+    state: State<'_, ScanState>,
+) -> Result<WrittenFile, String> {
+    let options = options.unwrap_or_default();
+    if task_ids.is_empty() {
+        return Err("請至少選擇一次掃描".to_string());
+    }
+    check_writable(Path::new(&path))?;
+
+    let multiple = task_ids.len() > 1;
+    let mut header: Vec<&str> = Vec::new();
+    if multiple {
+        header.push("task_id");
+    }
+    header.extend(CSV_COLUMNS);
+    if options.include_evidence {
+        header.push("evidence");
+    }
+
+    let rows = csv_rows(&state, &task_ids, &options, header.len()).await?;
+    let written = write_atomic(Path::new(&path), csv_export::render(&header, &rows, &CSV_TEXT_COLUMNS).as_bytes(), options.overwrite)?;
+    tracing::info!("💾 已匯出 {} 次掃描的 {} 個發現到 {} ({} bytes)", task_ids.len(), rows.len(), written.path, written.size);
+    Ok(written)
+}
+
+/// 匯出的資料列，依 task_ids 順序；已抑制與低於 min_severity 的發現不列入
+async fn csv_rows(state: &ScanState, task_ids: &[String], options: &CsvExportOptions, columns: usize) -> Result<Vec<Vec<String>>, String> {
+    let multiple = task_ids.len() > 1;
+    let mut rows = Vec::new();
+    for task_id in task_ids {
+        let findings = state
+            .scan_results
+            .lock()
+            .await
+            .get(task_id)
+            .map(|report| report.vulnerabilities.clone())
+            .ok_or_else(|| format!("找不到掃描報告: {}", task_id))?;
+        let triage = finding_triage(state, task_id).await;
+
+        for finding in &findings {
+            let (status, suppressed) = triage.get(&finding.id).cloned().unwrap_or_else(|| ("open".to_string(), false));
+            let severity = finding.severity.clone().unwrap_or(Severity::Info);
+            if suppressed || !meets_min_severity(&severity, options.min_severity.as_ref()) {
+                continue;
+            }
+            let mut row = Vec::with_capacity(columns);
+            if multiple {
+                row.push(task_id.clone());
+            }
+            row.extend(csv_fields(finding, &severity, status));
+            if options.include_evidence {
+                row.push(export_evidence(finding).unwrap_or_default());
+            }
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+/// 嚴重程度是否達到下限；比較排名而非 derive 的 Ord (Critical 在 Ord 中最小)
+fn meets_min_severity(severity: &Severity, min: Option<&Severity>) -> bool {
+    min.map_or(true, |min| severity.rank() >= min.rank())
+}
+
+/// 發現 id -> (處理狀態, 是否抑制)；資料庫無法使用時為空
+async fn finding_triage(state: &ScanState, task_id: &str) -> HashMap<String, (String, bool)> {
+    let Some(store) = state.store() else {
+        return HashMap::new();
+    };
+    match store.summary_inputs(task_id).await {
+        Ok(inputs) => inputs.into_iter().map(|(id, input)| (id, (input.status, input.suppressed))).collect(),
+        Err(e) => {
+            tracing::warn!("⚠️  無法讀取發現的處理狀態，CSV 的 status 一律為 open: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// CSV_COLUMNS 順序的欄位值；CVSS 與 CVE 取自 raw_data (匯入的發現)，沒有時列出標題與說明中提到的 CVE
+fn csv_fields(finding: &ScanResult, severity: &Severity, status: String) -> Vec<String> {
+    let details = finding.details.clone().unwrap_or_default();
+    let raw: Option<serde_json::Value> = finding.raw_data.as_deref().and_then(|raw| serde_json::from_str(raw).ok());
+    let raw_text = |keys: &[&str]| -> Option<String> {
+        let raw = raw.as_ref()?;
+        keys.iter().find_map(|key| match raw.get(*key)? {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            serde_json::Value::Array(items) => {
+                Some(items.iter().filter_map(|item| item.as_str()).collect::<Vec<_>>().join("; "))
+            }
+            _ => None,
+        })
+    };
+
+    vec![
+        finding.id.clone(),
+        severity_label(severity).to_string(),
+        details.owasp_category.or_else(|| owasp_category(finding.raw_data.as_deref())).unwrap_or_default(),
+        finding.title.clone(),
+        details.affected_url.unwrap_or_default(),
+        finding.description.clone().unwrap_or_default(),
+        raw_text(&["cvss_score", "cvss"]).unwrap_or_default(),
+        raw_text(&["cve_ids", "cve_id", "cve"]).unwrap_or_else(|| mentioned_cves(finding)),
+        finding.created_at.to_rfc3339(),
+        status,
+    ]
+}
+
+/// 標題與說明中提到的 CVE 編號，例如過時元件的發現
+fn mentioned_cves(finding: &ScanResult) -> String {
+    static CVE: OnceLock<Regex> = OnceLock::new();
+    let cve = CVE.get_or_init(|| Regex::new(r"CVE-\d{4}-\d{4,}").unwrap());
+    let text = format!("{} {}", finding.title, finding.description.as_deref().unwrap_or_default());
+    let mut ids: Vec<&str> = Vec::new();
+    for id in cve.find_iter(&text).map(|m| m.as_str()) {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::scan::ScanReport;
+    use crate::database::store::Store;
+    use crate::summary::SummaryInput;
+
+    /// 每個嚴重程度各一個發現，另有一個沒有嚴重程度的發現
+    async fn state_with_mixed_severities() -> ScanState {
+        let severities = ["low", "critical", "info", "medium", "high"];
+        let mut findings: Vec<serde_json::Value> = severities
+            .iter()
+            .map(|severity| serde_json::json!({
+                "id": severity,
+                "task_id": "scan-1",
+                "result_type": "vulnerability",
+                "severity": severity,
+                "title": format!("{} finding", severity),
+                "description": null,
+                "raw_data": null,
+                "created_at": "2026-01-05T10:05:00Z",
+            }))
+            .collect();
+        findings.push(serde_json::json!({
+            "id": "unrated",
+            "task_id": "scan-1",
+            "result_type": "technology",
+            "severity": null,
+            "title": "nginx",
+            "description": null,
+            "raw_data": null,
+            "created_at": "2026-01-05T10:05:00Z",
+        }));
+        let report: ScanReport = serde_json::from_value(serde_json::json!({
+            "task": {
+                "id": "scan-1",
+                "target_url": "https://example.com/",
+                "scan_type": "full",
+                "status": "completed",
+                "created_at": "2026-01-05T10:00:00Z",
+            },
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": findings,
+        }))
+        .expect("valid report");
+
+        let state = ScanState::new(None, None, None);
+        state.scan_results.lock().await.insert("scan-1".to_string(), report);
+        state
+    }
+
+    async fn exported_ids(state: &ScanState, min_severity: Option<Severity>) -> Vec<String> {
+        let options = CsvExportOptions { min_severity, ..CsvExportOptions::default() };
+        let rows = csv_rows(state, &["scan-1".to_string()], &options, CSV_COLUMNS.len()).await.unwrap();
+        rows.into_iter().map(|row| row[0].clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn min_severity_keeps_that_severity_and_above() {
+        let state = state_with_mixed_severities().await;
+
+        assert_eq!(exported_ids(&state, Some(Severity::Critical)).await, vec!["critical"]);
+        assert_eq!(exported_ids(&state, Some(Severity::High)).await, vec!["critical", "high"]);
+        assert_eq!(exported_ids(&state, Some(Severity::Medium)).await, vec!["critical", "medium", "high"]);
+        assert_eq!(exported_ids(&state, Some(Severity::Low)).await, vec!["low", "critical", "medium", "high"]);
+        // 沒有嚴重程度的發現視為 info
+        assert_eq!(exported_ids(&state, Some(Severity::Info)).await.len(), 6);
+        assert_eq!(exported_ids(&state, None).await.len(), 6);
+    }
+
+    #[test]
+    fn compares_by_rank_not_declaration_order() {
+        assert!(meets_min_severity(&Severity::Critical, Some(&Severity::Low)));
+        assert!(meets_min_severity(&Severity::High, Some(&Severity::High)));
+        assert!(!meets_min_severity(&Severity::Medium, Some(&Severity::High)));
+        assert!(!meets_min_severity(&Severity::Info, Some(&Severity::Low)));
+        assert!(meets_min_severity(&Severity::Info, None));
+    }
+
+    /// 已保存的掃描：兩個 A03 的 high (其中一個為低信心度)、一個 A05 的 medium 與一個沒有類別的 low
+    async fn state_with_saved_scan() -> ScanState {
+        let finding = |id: &str, severity: &str, raw_data: &str, cwe_ids: &[&str], low_confidence: bool| serde_json::json!({
+            "id": id,
+            "task_id": "scan-1",
+            "result_type": "vulnerability",
+            "severity": severity,
+            "title": format!("{} finding", id),
+            "description": null,
+            "raw_data": raw_data,
+            "created_at": "2026-01-05T10:05:00Z",
+            "low_confidence": low_confidence,
+            "cwe_ids": cwe_ids,
+        });
+        let mut report: ScanReport = serde_json::from_value(serde_json::json!({
+            "task": {
+                "id": "scan-1",
+                "target_url": "https://example.com/",
+                "scan_type": "full",
+                "status": "completed",
+                "created_at": "2026-01-05T10:00:00Z",
+            },
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": [
+                finding("sqli", "high", r#"{"check_id":"sqli","owasp":"A03:2021"}"#, &["CWE-89"], false),
+                finding("xss", "high", r#"{"check_id":"xss","owasp":"A03:2021"}"#, &["CWE-79"], true),
+                finding("cors", "medium", r#"{"check_id":"cors","owasp":"A05:2021"}"#, &[], false),
+                finding("csp", "low", r#"{"check_id":"missing_csp"}"#, &[], false),
+            ],
+        }))
+        .expect("valid report");
+        report.summary = Some(summarize(report.vulnerabilities.iter().map(SummaryInput::from_result)));
+
+        let store = Store::open_migrated().await;
+        store.save_report(&report).await.unwrap();
+        let state = ScanState::new(Some(store), None, None);
+        state.scan_results.lock().await.insert("scan-1".to_string(), report);
+        state
+    }
+
+    /// 摘要的統計內容，不含計算時間
+    fn counts(summary: &ReportSummary) -> serde_json::Value {
+        let mut value = serde_json::to_value(summary).unwrap();
+        value.as_object_mut().unwrap().remove("computed_at");
+        value
+    }
+
+    /// 回傳的摘要、記憶體中報告上的摘要與資料庫中的摘要必須一致
+    async fn assert_consistent(state: &ScanState, summary: &ReportSummary) {
+        let in_report = state.scan_results.lock().await["scan-1"].summary.clone().expect("report summary");
+        let saved = state.store().unwrap().saved_summary("scan-1").await.expect("saved summary");
+        assert_eq!(counts(&in_report), counts(summary));
+        assert_eq!(counts(&saved), counts(summary));
+    }
+
+    #[tokio::test]
+    async fn recomputed_summary_matches_the_completion_summary() {
+        let state = state_with_saved_scan().await;
+        let at_completion = state.scan_results.lock().await["scan-1"].summary.clone().unwrap();
+
+        let recomputed = recompute_summary(&state, "scan-1").await.unwrap();
+        assert_eq!(counts(&recomputed), counts(&at_completion));
+        assert_eq!(recomputed.total, 4);
+        assert_eq!((recomputed.count("high"), recomputed.count("medium"), recomputed.count("low")), (2, 1, 1));
+        assert_eq!(recomputed.by_owasp.get("A03"), Some(&2));
+        assert_eq!((recomputed.confidence.normal, recomputed.confidence.low), (3, 1));
+        assert_consistent(&state, &recomputed).await;
+    }
+
+    #[tokio::test]
+    async fn summary_stays_consistent_after_each_triage_change() {
+        let state = state_with_saved_scan().await;
+
+        let summary = update_triage(&state, "sqli", Some("confirmed"), None).await.unwrap();
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.by_status.get("confirmed"), Some(&1));
+        assert_eq!(summary.by_status.get("open"), Some(&3));
+        assert_consistent(&state, &summary).await;
+
+        // 誤報不計入任何分類，低信心度的標記沿用記憶體中的報告
+        let summary = update_triage(&state, "xss", Some("false_positive"), None).await.unwrap();
+        assert_eq!((summary.total, summary.false_positive), (3, 1));
+        assert_eq!(summary.count("high"), 1);
+        assert_eq!(summary.by_owasp.get("A03"), Some(&1));
+        assert!(!summary.by_cwe.contains_key("CWE-79"));
+        assert_eq!((summary.confidence.normal, summary.confidence.low), (3, 0));
+        assert_consistent(&state, &summary).await;
+
+        let summary = update_triage(&state, "csp", None, Some(true)).await.unwrap();
+        assert_eq!((summary.total, summary.suppressed), (2, 1));
+        assert_eq!(summary.count("low"), 0);
+        assert!(!summary.by_check_id.contains_key("missing_csp"));
+        assert_consistent(&state, &summary).await;
+
+        let summary = update_triage(&state, "csp", None, Some(false)).await.unwrap();
+        assert_eq!((summary.total, summary.suppressed), (3, 0));
+        assert_eq!(summary.count("low"), 1);
+        assert_consistent(&state, &summary).await;
+
+        // 失敗的變更不影響已保存的摘要
+        assert!(update_triage(&state, "missing", Some("fixed"), None).await.unwrap_err().contains("找不到發現"));
+        assert!(update_triage(&state, "cors", Some("wontfix"), None).await.is_err());
+        assert_consistent(&state, &summary).await;
+    }
+}
@@ -0,0 +1,31 @@
+/**
+ * Log Commands
+ *
+ * 查詢掃描的日誌 (回報問題時附上)，以及在執行中調整日誌層級以取得 debug 日誌
+ */
+
+use crate::logging::{self, LogLevel, ScanLogEntry};
+
+/// 掃描最近的日誌，依時間排序；min_level 預設為 info，limit 預設為 500 行
+///
+/// 日誌只保留在記憶體中，程式重新啟動前的掃描回傳空陣列 (完整日誌在 app 資料目錄的 logs/)
+#[tauri::command]
+pub async fn get_scan_logs(
+    task_id: String,
+    min_level: Option<LogLevel>,
+    limit: Option<usize>,
+) -> Result<Vec<ScanLogEntry>, String> {
+    Ok(logging::scan_logs(
+        &task_id,
+        min_level.unwrap_or(LogLevel::Info),
+        limit.unwrap_or(logging::DEFAULT_LIMIT),
+    ))
+}
+
+/// 調整日誌層級，立即套用到進行中的掃描，不需重新啟動
+#[tauri::command]
+pub async fn set_log_level(level: LogLevel) -> Result<(), String> {
+    logging::set_level(level)?;
+    tracing::info!("📝 日誌層級已調整為 {:?}", level);
+    Ok(())
+}
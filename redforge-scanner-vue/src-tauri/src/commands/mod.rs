@@ -1,5 +1,35 @@
 pub mod scan;
 pub mod collaboration;
+pub mod dashboard;
+pub mod baseline;
+pub mod storage;
+pub mod schema;
+pub mod audit;
+pub mod redaction;
+pub mod findings;
+pub mod canary;
+pub mod attack_chains;
+pub mod changes;
+pub mod compare;
+pub mod workspace;
+pub mod logs;
+pub mod settings;
+pub mod report;
 
 pub use scan::*;
 pub use collaboration::*;
+pub use dashboard::*;
+pub use baseline::*;
+pub use storage::*;
+pub use schema::*;
+pub use audit::*;
+pub use redaction::*;
+pub use findings::*;
+pub use canary::*;
+pub use attack_chains::*;
+pub use changes::*;
+pub use compare::*;
+pub use workspace::*;
+pub use logs::*;
+pub use settings::*;
+pub use report::*;
@@ -0,0 +1,28 @@
+/**
+ * Redaction Profile Commands
+ *
+ * 管理匯出遮蔽設定檔，並讀取本機的主機假名對照表以還原第三方回覆中的主機名稱
+ */
+
+use crate::redaction::{self, PseudonymMapping, RedactionProfile};
+
+/// 列出內建與自訂的遮蔽設定檔
+#[tauri::command]
+pub async fn list_redaction_profiles() -> Result<Vec<RedactionProfile>, String> {
+    redaction::list_profiles()
+}
+
+/// 新增或取代自訂遮蔽設定檔
+#[tauri::command]
+pub async fn save_redaction_profile(profile: RedactionProfile) -> Result<(), String> {
+    let name = profile.name.clone();
+    redaction::save_profile(profile)?;
+    tracing::info!("🕶️  已儲存遮蔽設定檔 {}", name);
+    Ok(())
+}
+
+/// 取得匯出時保存的假名對照表 (假名 → 原始主機)
+#[tauri::command]
+pub async fn get_redaction_mapping(mapping_id: String) -> Result<PseudonymMapping, String> {
+    redaction::load_mapping(&mapping_id)
+}
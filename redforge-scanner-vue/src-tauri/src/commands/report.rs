@@ -0,0 +1,135 @@
+/**
+ * Report Commands
+ *
+ * 產生交付給客戶的報告檔案 (Markdown、HTML、PDF 或 JSON)。已抑制或標記為誤報的發現不列入報告，
+ * 統計與掃描摘要一致。
+ */
+
+use crate::commands::compare::scan_diff;
+use crate::commands::scan::{ScanReport, ScanState};
+use crate::executive_summary::{self, ExecutiveSummary};
+use crate::models::{Report, ReportType};
+use crate::output::{check_writable, write_atomic};
+use crate::report::{self, ReportView};
+use crate::report_pdf;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::State;
+use uuid::Uuid;
+
+/// 產生掃描的報告檔案到 output_path 並回傳檔案路徑與大小；目的地已存在時改用編號檔名，除非指定 overwrite。
+/// 指定 compare_task_id 時在 Markdown 報告最後加上與該次掃描的差異 (diff_scans)
+#[tauri::command]
+pub async fn generate_report(
+    task_id: String,
+    report_type: ReportType,
+    output_path: String,
+    overwrite: Option<bool>,
+    compare_task_id: Option<String>,
+    state: State<'_, ScanState>,
+) -> Result<Report, String> {
+    if compare_task_id.is_some() && report_type != ReportType::Markdown {
+        return Err("與前次掃描的差異只能輸出到 Markdown 報告".to_string());
+    }
+    let scan = reportable_scan(&state, &task_id).await?;
+    let diff = match &compare_task_id {
+        Some(compare_task_id) => Some(scan_diff(&state, compare_task_id, &task_id).await?),
+        None => None,
+    };
+    check_writable(Path::new(&output_path))?;
+
+    let summary = report::report_summary(&scan);
+    let created_at = Utc::now();
+    let content = match report_type {
+        ReportType::Markdown => {
+            let mut md = report::render_markdown(&ReportView::new(&scan, &summary, created_at));
+            if let Some(diff) = &diff {
+                if !md.ends_with("\n\n") {
+                    md.push('\n');
+                }
+                md.push_str(&report::render_diff_markdown(diff));
+            }
+            md.into_bytes()
+        }
+        ReportType::Html => report::render_html(&ReportView::new(&scan, &summary, created_at))?.into_bytes(),
+        ReportType::Json => serde_json::to_vec_pretty(&scan).map_err(|e| e.to_string())?,
+        ReportType::Pdf => {
+            // 掃描系統字型與排版都是同步的檔案與 CPU 工作
+            let view = ReportView::new(&scan, &summary, created_at);
+            tokio::task::spawn_blocking(move || report_pdf::render_pdf(&view))
+                .await
+                .map_err(|e| format!("PDF 報告產生失敗: {}", e))??
+        }
+    };
+
+    let written = write_atomic(Path::new(&output_path), &content, overwrite.unwrap_or(false))?;
+    tracing::info!(
+        "📄 已產生掃描 {} 的 {:?} 報告 ({} 個發現，{} bytes) 到 {}",
+        task_id,
+        report_type,
+        summary.total,
+        written.size,
+        written.path
+    );
+
+    Ok(Report {
+        id: Uuid::new_v4().to_string(),
+        task_id,
+        report_type,
+        file_path: Some(written.path),
+        file_size: Some(written.size),
+        executive_summary: Some(executive_summary::build(&scan, &summary).text()),
+        total_vulnerabilities: summary.total as i32,
+        critical_count: summary.count("critical") as i32,
+        high_count: summary.count("high") as i32,
+        medium_count: summary.count("medium") as i32,
+        low_count: summary.count("low") as i32,
+        info_count: summary.count("info") as i32,
+        created_at,
+    })
+}
+
+/// 掃描的執行摘要，內容與報告檔案中的相同，不寫入檔案
+#[tauri::command]
+pub async fn get_executive_summary(task_id: String, state: State<'_, ScanState>) -> Result<ExecutiveSummary, String> {
+    let scan = reportable_scan(&state, &task_id).await?;
+    Ok(executive_summary::build(&scan, &report::report_summary(&scan)))
+}
+
+/// 報告使用的掃描結果：已排除抑制與誤報的發現
+async fn reportable_scan(state: &ScanState, task_id: &str) -> Result<ScanReport, String> {
+    let mut scan = state
+        .scan_results
+        .lock()
+        .await
+        .get(task_id)
+        .cloned()
+        .ok_or_else(|| "找不到掃描報告".to_string())?;
+    exclude_dismissed(state, &mut scan).await;
+    Ok(scan)
+}
+
+/// 移除資料庫中已抑制或標記為誤報的發現
+async fn exclude_dismissed(state: &ScanState, scan: &mut ScanReport) {
+    let dismissed = dismissed_findings(state, &scan.task.id).await;
+    scan.vulnerabilities.retain(|finding| !dismissed.contains(&finding.id));
+}
+
+/// 掃描中已抑制或標記為誤報的發現 id；資料庫無法使用時為空 (報告包含所有發現)
+pub(crate) async fn dismissed_findings(state: &ScanState, task_id: &str) -> HashSet<String> {
+    let Some(store) = state.store() else {
+        return HashSet::new();
+    };
+    match store.summary_inputs(task_id).await {
+        Ok(inputs) => inputs
+            .into_iter()
+            .filter(|(_, input)| input.suppressed || input.status == "false_positive")
+            .map(|(id, _)| id)
+            .collect(),
+        Err(e) => {
+            tracing::warn!("⚠️  無法讀取發現的處理狀態，報告包含所有發現: {}", e);
+            HashSet::new()
+        }
+    }
+}
@@ -1,305 +1,1594 @@
+use crate::attack_chains::{self, AttackChain};
+use crate::cwe;
+use crate::summary::{self, ReportSummary, SummaryInput};
+use crate::commands::changes;
+use crate::commands::baseline::{baseline_key, compare_to_baseline, Baseline, BaselineComparison};
+use crate::commands::dashboard::{invalidate_dashboard_cache, DashboardStats};
+use crate::database::store::{StorageMonitor, Store};
+use crate::events::EventCoalescer;
+use crate::logging;
+use crate::messages;
+use crate::settings;
 use crate::models::*;
 use crate::scanners::{
     http_scanner::HttpScanner,
     ssl_scanner::SslScanner,
-    tech_detector::TechDetector,
+    tech_detector::{merge_technologies, TechDetector},
     vulnerability_scanner::VulnerabilityScanner,
     owasp_scanner::OwaspScanner,
+    target::normalize_target,
+    control::{self, ScanControl, MAX_PAUSE},
+    preconditions::TechContext,
+    warmup::{WarmupProber, WarmupResult},
+    consent::{AnalyzedPage, ConsentCheck, ConsentChecker},
+    crawl_policy::{CrawlDecision, CrawlPolicy},
+    crawler::CrawlSummary,
+    proxy_disagreement::ProxyDisagreementScanner,
+    time_box::TimeBoxReport,
+    redirect::RedirectDetector,
+    content_hash::{ContentHasher, PageHash},
+    upload::{UploadScanner, UploadedFile},
+    page_identity::IdentityFetcher,
+    check_metrics::CheckMetrics,
+    rate_limit::RequestRateReport,
+    finding_caps::{self, DiscoveredContent},
+    port_scanner::{self, PortScanner},
+    proxy::{self, ProxyRoute},
+    auth::{AuthSession, AuthenticationReport},
+    sri::ExternalResource,
+    ScannerError, ScannerResult,
 };
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use uuid::Uuid;
 use chrono::Utc;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use std::collections::HashMap;
+use tokio::sync::{Mutex, Notify};
+use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+use tracing::Instrument;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScanReport {
     pub task: ScanTask,
     pub headers: Vec<SecurityHeader>,
-    pub ssl_analysis: Option<SslAnalysis>,
+    // 每個分析的 TLS 連接埠一筆，第一筆為掃描目標本身；舊報告為單一物件
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub ssl_analysis: Vec<SslAnalysis>,
     pub technologies: Vec<DetectedTechnology>,
     pub vulnerabilities: Vec<ScanResult>,
+    #[serde(default)]
+    pub open_ports: Vec<OpenPort>,
+    #[serde(default)]
+    pub coverage_notes: Vec<CoverageNote>,
+    #[serde(default)]
+    pub baseline_comparison: Option<BaselineComparison>,
+    // 各階段耗時
+    #[serde(default)]
+    pub stages: Vec<StageTiming>,
+    // 掃描開始前的連線暖身結果
+    #[serde(default)]
+    pub warmup: Option<WarmupResult>,
+    // 基礎頁面的同意頁偵測與繞過結果，沒有同意管理平台時為 None
+    #[serde(default)]
+    pub consent: Option<ConsentCheck>,
+    // 爬取規則略過的 URL 與原因
+    #[serde(default)]
+    pub crawl_skipped: Vec<CrawlDecision>,
+    // 注入檢查前爬取的頁面、參數與表單數量，沒有執行爬取時為 None
+    #[serde(default)]
+    pub crawl: Option<CrawlSummary>,
+    // 掃描模式，不同模式的發現清單不能直接比較；舊報告沒有此欄位時為 full
+    #[serde(default)]
+    pub scan_mode: ScanMode,
+    // OWASP 類別的執行與略過情況，沒有執行漏洞掃描時為 None
+    #[serde(default)]
+    pub owasp_categories: Option<OwaspCategoryReport>,
+    // 限時掃描的時間限制與未執行的檢查，沒有設定時間限制時為 None
+    #[serde(default)]
+    pub time_box: Option<TimeBoxReport>,
+    // 同時出現的發現組成的攻擊鏈，由 attack-chain 規則產生
+    #[serde(default)]
+    pub attack_chains: Vec<AttackChain>,
+    // 嚴重度、檢查與類別的統計，掃描完成與發現狀態變更時計算
+    #[serde(default)]
+    pub summary: Option<ReportSummary>,
+    // 基礎頁面與主要路徑的正規化內容雜湊，供 check_target_changes 比較
+    #[serde(default)]
+    pub content_hashes: Vec<PageHash>,
+    // 上傳測試留在目標上的檔案，供測試人員事後移除
+    #[serde(default)]
+    pub uploaded_files: Vec<UploadedFile>,
+    // 各檢查送出的請求、逾時、發現與耗時，供 get_check_efficiency 跨掃描彙總
+    #[serde(default)]
+    pub check_metrics: Vec<CheckMetrics>,
+    // 送出的請求總數與實際速率，供稽核是否遵守速率限制
+    #[serde(default)]
+    pub request_rate: Option<RequestRateReport>,
+    // 單一檢查的發現超過上限時，合併前的完整清單
+    #[serde(default)]
+    pub discovered_content: Vec<DiscoveredContent>,
+    // 以已登入身分掃描時的身分名稱與登入狀態確認結果 (不含機密值)，未設定登入時為 None
+    #[serde(default)]
+    pub authentication: Option<AuthenticationReport>,
+    // 基礎頁面載入的外部腳本與樣式表 (是否跨來源、SRI、是否以 HTTP 載入)，沒有執行 A08 時為空
+    #[serde(default)]
+    pub resource_inventory: Vec<ExternalResource>,
+    // 各檢查與 OWASP 類別的執行狀態 (完成、略過、部分完成、錯誤) 與請求數；舊報告沒有此欄位時為空
+    #[serde(default)]
+    pub coverage: Vec<CheckCoverage>,
 }
 
+impl ScanReport {
+    /// 掃描目標本身的 SSL 分析 (其他連接埠的分析排在後面)
+    pub fn primary_ssl(&self) -> Option<&SslAnalysis> {
+        self.ssl_analysis.first()
+    }
+}
+
+#[derive(Clone)]
 pub struct ScanState {
     pub current_tasks: Arc<Mutex<Vec<ScanTask>>>,
     pub scan_results: Arc<Mutex<HashMap<String, ScanReport>>>,
+    pub dashboard_cache: Arc<Mutex<HashMap<String, DashboardStats>>>,
+    // 資料庫無法使用時為 None，掃描仍在記憶體中運作；請透過 store() 取得
+    store: Option<Store>,
+    pub storage: Option<StorageMonitor>,
+    // 未遮蔽的掃描設定，只保留在記憶體中供 rescan 使用
+    pub scan_options: Arc<Mutex<HashMap<String, ScanOptions>>>,
+    // 依目標保存的核可基準
+    pub baselines: Arc<Mutex<HashMap<String, Baseline>>>,
+    // 執行中掃描的暫停控制，掃描結束時移除
+    pub controls: Arc<Mutex<HashMap<String, ScanControl>>>,
+    // 節流後送到前端的掃描事件；沒有 AppHandle 時為 None
+    pub events: Option<EventCoalescer>,
+    // 掃描進入終止狀態時喚醒 wait_for_scan 的等待者
+    completions: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
+}
+
+impl ScanState {
+    pub fn new(store: Option<Store>, storage: Option<StorageMonitor>, app: Option<AppHandle>) -> Self {
+        Self {
+            current_tasks: Arc::new(Mutex::new(Vec::new())),
+            scan_results: Arc::new(Mutex::new(HashMap::new())),
+            dashboard_cache: Arc::new(Mutex::new(HashMap::new())),
+            store,
+            storage,
+            scan_options: Arc::new(Mutex::new(HashMap::new())),
+            baselines: Arc::new(Mutex::new(HashMap::new())),
+            controls: Arc::new(Mutex::new(HashMap::new())),
+            events: app.map(event_coalescer),
+            completions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 任務的完成通知；同一任務的所有等待者共用
+    async fn completion(&self, task_id: &str) -> Arc<Notify> {
+        self.completions.lock().await.entry(task_id.to_string()).or_default().clone()
+    }
+
+    /// 等待者結束時移除完成通知；仍有其他等待者時保留
+    async fn release_completion(&self, task_id: &str, notify: Arc<Notify>) {
+        let mut completions = self.completions.lock().await;
+        // 表中一份加上這個等待者的一份；其他等待者取得通知時也需要此鎖，計數不會同時變動
+        if completions.get(task_id).is_some_and(|shared| Arc::ptr_eq(shared, &notify) && Arc::strong_count(&notify) == 2) {
+            completions.remove(task_id);
+        }
+    }
+
+    /// 可用的資料庫；啟動時或執行中切換為唯讀記憶體模式後回傳 None
+    pub fn store(&self) -> Option<&Store> {
+        self.store.as_ref().filter(|store| store.is_available())
+    }
+
+    /// 啟動時從資料庫載入先前的掃描；上次關閉時仍在執行的掃描標記為失敗
+    pub async fn restore(&self) {
+        let Some(store) = self.store() else {
+            return;
+        };
+        match store.recover_interrupted("應用程式在掃描進行中關閉，掃描未完成").await {
+            Ok(recovered) if !recovered.is_empty() => tracing::warn!("⚠️  {} 個未完成的掃描已標記為失敗", recovered.len()),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("⚠️  無法復原未完成的掃描: {}", e),
+        }
+        match store.load_scans().await {
+            Ok((tasks, reports)) => {
+                tracing::info!("📂 已載入 {} 個掃描 ({} 份報告)", tasks.len(), reports.len());
+                *self.current_tasks.lock().await = tasks;
+                let mut results = self.scan_results.lock().await;
+                for report in reports {
+                    results.insert(report.task.id.clone(), report);
+                }
+            }
+            Err(e) => tracing::warn!("⚠️  無法載入掃描紀錄: {}", e),
+        }
+        match store.load_baselines().await {
+            Ok(baselines) => {
+                let mut loaded = self.baselines.lock().await;
+                for baseline in baselines {
+                    loaded.insert(baseline.target.clone(), baseline);
+                }
+            }
+            Err(e) => tracing::warn!("⚠️  無法載入核可基準: {}", e),
+        }
+    }
+
+    /// 將任務目前的狀態寫入資料庫；資料庫無法使用時只保留在記憶體中
+    async fn persist_task(&self, task_id: &str) {
+        let Some(store) = self.store() else {
+            return;
+        };
+        let task = self.current_tasks.lock().await.iter().find(|t| t.id == task_id).cloned();
+        if let Some(task) = task {
+            if let Err(e) = store.save_task(&task).await {
+                tracing::warn!("⚠️  無法保存掃描任務 {}: {}", task_id, e);
+            }
+        }
+    }
+
+    /// 將完成 (或失敗、取消) 的掃描報告寫入資料庫
+    async fn persist_report(&self, task_id: &str) {
+        let Some(store) = self.store() else {
+            return;
+        };
+        let report = self.scan_results.lock().await.get(task_id).cloned();
+        if let Some(report) = report {
+            if let Err(e) = store.save_report(&report).await {
+                tracing::warn!("⚠️  無法保存掃描報告 {}: {}", task_id, e);
+            }
+        }
+    }
+}
+
+/// 將節流後的掃描事件 (scan-progress、scan-log、scan-finding) 轉發到前端
+fn event_coalescer(app: AppHandle) -> EventCoalescer {
+    let coalescer = EventCoalescer::new(Arc::new(move |name: &str, payload: serde_json::Value| {
+        if let Err(e) = app.emit(name, payload) {
+            tracing::warn!("⚠️  無法發送 {} 事件: {}", name, e);
+        }
+    }));
+    coalescer.spawn_flusher();
+    coalescer
 }
 
 #[tauri::command]
 pub async fn start_scan(
     url: String,
     scan_type: String,
+    options: Option<ScanOptions>,
+    state: State<'_, ScanState>,
+) -> Result<String, String> {
+    launch_scan(url, scan_type, options.unwrap_or_default(), state.inner()).await
+}
+
+/// 以原掃描記錄的設定快照重新掃描同一目標
+///
+/// 本次執行期間建立的掃描會沿用原始設定 (含密碼等敏感值)；
+/// 重新啟動後快照中沒有敏感值，原掃描用過 cookie、token 或密碼時拒絕並要求以 start_scan 重新提供。
+#[tauri::command]
+pub async fn rescan(
+    task_id: String,
+    state: State<'_, ScanState>,
+) -> Result<String, String> {
+    rescan_task(&task_id, state.inner()).await
+}
+
+async fn rescan_task(task_id: &str, state: &ScanState) -> Result<String, String> {
+    let task = state.current_tasks.lock().await
+        .iter()
+        .find(|t| t.id == task_id)
+        .cloned()
+        .ok_or_else(|| "找不到該任務".to_string())?;
+
+    let options = task_options(state, &task).await?;
+    launch_scan(task.target_url, task.scan_type.to_string(), options, state).await
+}
+
+/// 掃描使用的設定：本次執行期間保留的原始設定，沒有時使用快照
+///
+/// 快照不含敏感值，原掃描設定過 cookie、token 或密碼時無法重現，回傳錯誤而不是不帶憑證掃描
+async fn task_options(state: &ScanState, task: &ScanTask) -> Result<ScanOptions, String> {
+    if let Some(options) = state.scan_options.lock().await.get(&task.id) {
+        return Ok(options.clone());
+    }
+    let Some(config) = &task.config else {
+        return Ok(ScanOptions::default());
+    };
+    let secrets = snapshot_secrets(config);
+    if !secrets.is_empty() {
+        return Err(format!(
+            "原掃描使用的敏感設定 ({}) 未保存在設定快照中，請以 start_scan 重新提供後掃描",
+            secrets.join(", ")
+        ));
+    }
+    match config.get("options") {
+        Some(options) => serde_json::from_value(options.clone()).map_err(|e| format!("無法讀取原掃描的設定快照: {}", e)),
+        None => Ok(ScanOptions::default()),
+    }
+}
+
+/// 處理因目標永久重定向到其他網域而停止的掃描
+///
+/// rescan_new 以相同設定掃描重定向後的目標；force_original 重新掃描原目標，所有請求固定在原主機。
+/// 決定與後續掃描 id 記錄在原任務的 redirect 上，回傳後續掃描 id。
+#[tauri::command]
+pub async fn resolve_target_redirect(
+    task_id: String,
+    decision: RedirectDecision,
     state: State<'_, ScanState>,
 ) -> Result<String, String> {
-    // 驗證 URL
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        return Err("無效的 URL 格式".to_string());
+    let task = state.current_tasks.lock().await
+        .iter()
+        .find(|t| t.id == task_id)
+        .cloned()
+        .ok_or_else(|| "找不到該任務".to_string())?;
+    let redirect = task.redirect.clone()
+        .filter(|r| r.decision == RedirectDecision::Stopped)
+        .ok_or_else(|| "此掃描沒有等待處理的跨網域重定向".to_string())?;
+
+    let mut options = task_options(state.inner(), &task).await?;
+    let url = match decision {
+        RedirectDecision::RescanNew => redirect.to.clone(),
+        RedirectDecision::ForceOriginal => {
+            options.force_original_host = true;
+            task.target_url.clone()
+        }
+        RedirectDecision::Stopped => return Err("請選擇掃描新目標或強制掃描原目標".to_string()),
+    };
+
+    let follow_up = launch_scan(url, task.scan_type.to_string(), options, state.inner()).await?;
+    tracing::info!("↪️  跨網域重定向 {} → {}: {:?}，後續掃描 {}", redirect.from, redirect.to, decision, follow_up);
+
+    let resolved = TargetRedirect { decision, follow_up_task_id: Some(follow_up.clone()), ..redirect };
+    if let Some(task) = state.current_tasks.lock().await.iter_mut().find(|t| t.id == task_id) {
+        task.redirect = Some(resolved.clone());
+    }
+    if let Some(report) = state.scan_results.lock().await.get_mut(&task_id) {
+        report.task.redirect = Some(resolved);
+    }
+
+    Ok(follow_up)
+}
+
+async fn launch_scan(url: String, scan_type: String, mut options: ScanOptions, state: &ScanState) -> Result<String, String> {
+    // 掃描設定沒有指定代理時使用全域設定的代理，之後的請求 (包含目標正規化) 都經過代理
+    options.proxy = settings::resolve_proxy(options.proxy)?;
+    let proxy_route = match &options.proxy {
+        Some(proxy) => Some(ProxyRoute::parse(proxy).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    options.http.validate()?;
+    options.owasp.validate()?;
+    options.backup_files.validate()?;
+    options.rate_limit_probe.validate()?;
+    if let Some(auth) = &options.auth {
+        auth.validate()?;
     }
+    if let Some(collaborator) = &options.collaborator {
+        collaborator.validate()?;
+    }
+
+    // 正規化目標：接受主機名稱、IP 與 host:port，拒絕非 http(s) 的通訊協定
+    let target = normalize_target(&url, &options).await?;
+    let url = target.url;
 
     let task_id = Uuid::new_v4().to_string();
+    let scan_type_value = match scan_type.as_str() {
+        "full" => ScanType::Full,
+        "quick" => ScanType::Quick,
+        "vulnerability" => ScanType::Vulnerability,
+        "port" => ScanType::Port,
+        "ssl" => ScanType::Ssl,
+        "headers" => ScanType::Headers,
+        _ => return Err("未知的掃描類型".to_string()),
+    };
+    let (config, config_hash) = config_snapshot(&scan_type_value, &options);
 
     let task = ScanTask {
         id: task_id.clone(),
         target_url: url.clone(),
-        scan_type: match scan_type.as_str() {
-            "full" => ScanType::Full,
-            "quick" => ScanType::Quick,
-            "vulnerability" => ScanType::Vulnerability,
-            "port" => ScanType::Port,
-            "ssl" => ScanType::Ssl,
-            "headers" => ScanType::Headers,
-            _ => return Err("未知的掃描類型".to_string()),
-        },
+        scan_type: scan_type_value,
         status: ScanStatus::Pending,
         started_at: None,
         completed_at: None,
         created_at: Utc::now(),
+        config: Some(config),
+        config_hash: Some(config_hash),
+        requested_target: target.requested,
+        error: None,
+        error_kind: None,
+        redirect: None,
+        unchanged_since: None,
+        title: None,
+        favicon_path: None,
+        request_budget: None,
     };
 
     // 添加到任務列表
     let mut tasks = state.current_tasks.lock().await;
     tasks.push(task.clone());
     drop(tasks);
+    state.persist_task(&task_id).await;
+    state.scan_options.lock().await.insert(task_id.clone(), options.clone());
+    let control = ScanControl::new(&task_id, state.events.clone());
+    control.limit_requests(&options.http);
+    control.limit_evidence(options.http.evidence_body_bytes);
+    control.route_through(proxy_route);
+    control.enter_stage(ScanStage::Queued, 0, "掃描已排入佇列".to_string());
+    state.controls.lock().await.insert(task_id.clone(), control);
 
-    // 在背景執行掃描
-    let current_tasks = state.current_tasks.clone();
-    let scan_results = state.scan_results.clone();
-    let state_clone = ScanState { current_tasks, scan_results };
-    let state_arc = Arc::new(state_clone);
-    let task_id_clone = task_id.clone();
-    tokio::spawn(async move {
-        execute_scan(task_id_clone, url, scan_type, state_arc).await;
-    });
+    // 在背景執行掃描；期間的日誌都歸屬到此掃描
+    let state_arc = Arc::new(state.clone());
+    let span = tracing::info_span!("scan", task_id = %task_id, scan_type = %scan_type);
+    tokio::spawn(
+        async move {
+            execute_scan(task, scan_type, options, state_arc).await;
+        }
+        .instrument(span),
+    );
 
     Ok(task_id)
 }
 
-async fn execute_scan(task_id: String, url: String, scan_type: String, state: Arc<ScanState>) {
+/// 有設定的敏感欄位名稱 (區段.欄位)：用戶端憑證密碼、登入 cookie 與 token、OOB 輪詢 token、代理密碼
+///
+/// 這些欄位不會序列化，快照中只能從型別欄位得知是否設定過
+fn configured_secrets(options: &ScanOptions) -> Vec<String> {
+    let auth = options.auth.as_ref();
+    let configured = [
+        ("client_identity.passphrase", options.client_identity.as_ref().is_some_and(|c| c.passphrase.is_some())),
+        ("auth.cookies", auth.is_some_and(|a| a.cookies.is_some())),
+        ("auth.bearer_token", auth.is_some_and(|a| a.bearer_token.is_some())),
+        ("auth.password", auth.is_some_and(|a| a.password.is_some())),
+        ("collaborator.poll_token", options.collaborator.as_ref().is_some_and(|c| c.poll_token.is_some())),
+        ("proxy.password", options.proxy.as_ref().is_some_and(|p| p.password.is_some())),
+    ];
+    configured.into_iter().filter(|(_, set)| *set).map(|(name, _)| name.to_string()).collect()
+}
+
+/// 產生掃描開始時的有效設定快照與其 SHA-256 雜湊
+///
+/// 敏感值不會序列化進快照，只在 secrets_configured 列出有設定過的欄位，例如 "auth.cookies"
+pub fn config_snapshot(scan_type: &ScanType, options: &ScanOptions) -> (serde_json::Value, String) {
+    let snapshot = serde_json::json!({
+        "scan_type": scan_type.to_string(),
+        "options": serde_json::to_value(options).unwrap_or(serde_json::Value::Null),
+        "secrets_configured": configured_secrets(options),
+    });
+
+    // serde_json 的 Map 依鍵排序，序列化結果穩定
+    let hash = format!("{:x}", Sha256::digest(snapshot.to_string().as_bytes()));
+    (snapshot, hash)
+}
+
+/// 快照中有設定過但未保存的敏感欄位
+fn snapshot_secrets(config: &serde_json::Value) -> Vec<String> {
+    config
+        .get("secrets_configured")
+        .and_then(|list| list.as_array())
+        .map(|list| list.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+async fn execute_scan(task: ScanTask, scan_type: String, mut options: ScanOptions, state: Arc<ScanState>) {
+    let task_id = task.id.clone();
+    let url = task.target_url.clone();
+
     // 更新狀態為 Running
     update_task_status(&state, &task_id, ScanStatus::Running).await;
 
     // 初始化報告
     let mut report = ScanReport {
         task: ScanTask {
-            id: task_id.clone(),
-            target_url: url.clone(),
-            scan_type: match scan_type.as_str() {
-                "full" => ScanType::Full,
-                "quick" => ScanType::Quick,
-                "vulnerability" => ScanType::Vulnerability,
-                "headers" => ScanType::Headers,
-                "ssl" => ScanType::Ssl,
-                _ => ScanType::Full,
-            },
             status: ScanStatus::Running,
             started_at: Some(Utc::now()),
-            completed_at: None,
-            created_at: Utc::now(),
+            ..task
         },
         headers: Vec::new(),
-        ssl_analysis: None,
+        ssl_analysis: Vec::new(),
         technologies: Vec::new(),
         vulnerabilities: Vec::new(),
+        open_ports: Vec::new(),
+        coverage_notes: Vec::new(),
+        baseline_comparison: None,
+        stages: Vec::new(),
+        warmup: None,
+        consent: None,
+        crawl_skipped: Vec::new(),
+        crawl: None,
+        scan_mode: options.scan_mode,
+        owasp_categories: None,
+        time_box: None,
+        attack_chains: Vec::new(),
+        content_hashes: Vec::new(),
+        uploaded_files: Vec::new(),
+        check_metrics: Vec::new(),
+        request_rate: None,
+        discovered_content: Vec::new(),
+        summary: None,
+        authentication: None,
+        resource_inventory: Vec::new(),
+        coverage: Vec::new(),
     };
 
-    let result = match scan_type.as_str() {
-        "headers" => scan_headers_with_results(&task_id, &url, &mut report).await,
-        "ssl" => scan_ssl_with_results(&task_id, &url, &mut report).await,
-        "vulnerability" => scan_vulnerabilities_with_results(&task_id, &url, &mut report).await,
-        "full" => scan_full_with_results(&task_id, &url, &mut report).await,
-        _ => Err("未實現的掃描類型".to_string()),
+    let control = state.controls.lock().await.get(&task_id).cloned().unwrap_or_else(|| ScanControl::new(&task_id, None));
+    if let Some(max_duration_secs) = options.max_duration_secs {
+        control.start_time_box(max_duration_secs);
+    }
+
+    // 設定用戶端憑證時，先確認憑證可載入且目標接受，避免每個請求都以 TLS 錯誤失敗
+    let preflight = match preflight_proxy(&url, &options, &control).await {
+        Ok(()) if options.client_identity.is_some() => {
+            control.enter_stage(ScanStage::Resolving, 0, "確認用戶端憑證與目標連線".to_string());
+            preflight_client_identity(&url, &options, &control).await
+        }
+        other => other,
+    };
+    let preflight = match preflight {
+        Ok(()) => preflight_auth(&url, &options, &control).await,
+        Err(e) => Err(e),
     };
 
-    // 更新狀態
-    let status = if result.is_ok() {
-        println!("✅ 掃描完成: {}", task_id);
-        ScanStatus::Completed
-    } else {
-        println!("❌ 掃描失敗: {} - {:?}", task_id, result.err());
-        ScanStatus::Failed
+    // 掃描在控制 scope 內執行，每個請求送出前會檢查是否被暫停
+    let result = match preflight {
+        Err(e) => Err(e),
+        Ok(()) => control::scope(control.clone(), async {
+            // 純 SSL 分析與連接埠掃描不經過 HTTP，不需要暖身
+            if !matches!(scan_type.as_str(), "ssl" | "port") {
+                check_redirect(&url, &mut options, &mut report).await?;
+                if options.skip_if_unchanged && skip_unchanged(&state, &url, &options, &mut report).await {
+                    return Ok(());
+                }
+                warm_up(&url, &options, &mut report).await?;
+                check_consent(&url, &mut options, &mut report).await;
+            }
+            capture_identity(&url, &options, &mut report).await;
+
+            let result = match scan_type.as_str() {
+                "headers" => scan_headers_with_results(&task_id, &url, &options, &mut report).await,
+                "ssl" => scan_ssl_with_results(&task_id, &url, &options, &mut report).await,
+                "port" => control::check("ports", scan_ports_with_results(&task_id, &url, &options, &mut report)).await,
+                "vulnerability" => {
+                    // 單獨的漏洞掃描沒有技術檢測，前置條件無法評估，所有檢查照常執行
+                    let context = TechContext::undetected(options.ignore_preconditions);
+                    scan_vulnerabilities_with_results(&task_id, &url, &options, context, &mut report).await
+                }
+                "full" => scan_full_with_results(&task_id, &url, &options, &mut report).await,
+                "quick" => scan_quick_with_results(&task_id, &url, &options, &mut report).await,
+                _ => Err(ScannerError::Config("未實現的掃描類型".to_string())),
+            };
+            emit_findings(&report);
+            if !matches!(scan_type.as_str(), "ssl" | "port") {
+                record_content_hashes(&url, &options, &mut report).await;
+            }
+            result
+        })
+        .await,
     };
+    state.controls.lock().await.remove(&task_id);
+
+    // 更新狀態；暫停逾時等終止原因優先於部分成功的結果
+    let error = match control.abort_reason() {
+        Some(reason) => Some(ScannerError::Interrupted(reason)),
+        None => result.err(),
+    };
+    let status = match &error {
+        Some(_) if control.is_cancelled() => {
+            tracing::info!("🛑 掃描已取消: {}", task_id);
+            ScanStatus::Cancelled
+        }
+        None => {
+            tracing::info!("✅ 掃描完成: {}", task_id);
+            ScanStatus::Completed
+        }
+        Some(e) if e.kind().retryable() => {
+            tracing::error!("❌ 掃描失敗 (暫時性錯誤，可重新掃描): {} - {}", task_id, e);
+            ScanStatus::Failed
+        }
+        Some(e) => {
+            tracing::error!("❌ 掃描失敗: {} - {}", task_id, e);
+            ScanStatus::Failed
+        }
+    };
+    // 錯誤在此轉成訊息，種類另外保存供介面判斷是否建議重試
+    let error_kind = error.as_ref().map(ScannerError::kind);
+    let error = error.map(|e| e.to_string());
 
     report.task.status = status.clone();
     report.task.completed_at = Some(Utc::now());
+    report.task.error = error.clone();
+    report.task.error_kind = error_kind;
+
+    control.enter_stage(ScanStage::Reporting, 95, messages::stage_label(&ScanStage::Reporting));
+
+    if report.warmup.as_ref().is_some_and(|w| w.proceeded) {
+        for finding in &mut report.vulnerabilities {
+            finding.low_confidence = true;
+        }
+    }
+
+    if let Some(time_box) = control.time_box_report() {
+        tracing::info!("⏱️  {}", time_box.banner);
+        for check in &time_box.skipped {
+            report.coverage_notes.push(CoverageNote {
+                check: check.clone(),
+                message: format!("限時掃描 ({} 秒) 的時間已用盡，未執行", time_box.max_duration_secs),
+                details: Some(serde_json::json!({ "reason": "time_box_exhausted" })),
+                degraded: true,
+            });
+        }
+        report.time_box = Some(time_box);
+    }
+
+    let budget = control.request_budget();
+    for check in &budget.skipped {
+        report.coverage_notes.push(CoverageNote {
+            check: check.clone(),
+            message: "請求預算已用盡，未執行".to_string(),
+            details: Some(serde_json::json!({ "reason": "request_budget_exhausted", "max_requests": budget.max_requests })),
+            degraded: true,
+        });
+    }
+    for check in &budget.truncated {
+        report.coverage_notes.push(CoverageNote {
+            check: check.clone(),
+            message: "達到請求預算，檢查提前結束，結果可能不完整".to_string(),
+            details: Some(serde_json::json!({ "reason": "request_budget_truncated", "max_requests": budget.max_requests })),
+            degraded: true,
+        });
+    }
+    report.task.request_budget = Some(budget);
+
+    for finding in &mut report.vulnerabilities {
+        finding.cwe_ids = cwe::for_finding(finding);
+        if let Some(details) = finding.details.as_mut() {
+            details.cwe_id = finding.cwe_ids.first().cloned();
+            // 建立時沒有附上證據的發現 (OWASP 以外的掃描器) 取受影響 URL 最近一次的請求與回應
+            if details.exchange.is_none() {
+                details.exchange = details.affected_url.as_deref().and_then(|url| control.exchange(url));
+            }
+        }
+    }
+    report.attack_chains = attack_chains::detect(&url, &report.vulnerabilities);
+    report.summary = Some(summary::summarize(report.vulnerabilities.iter().map(SummaryInput::from_result)));
+
+    // 不同檢測器可能回報同一技術，合併為單一項目
+    report.technologies = merge_technologies(std::mem::take(&mut report.technologies));
+
+    // 與核可基準比較
+    if options.compare_to_baseline && status == ScanStatus::Completed {
+        let baselines = state.baselines.lock().await;
+        match baselines.get(&baseline_key(&url)) {
+            Some(baseline) => {
+                let comparison = compare_to_baseline(&report, baseline);
+                if comparison.has_deviations() {
+                    tracing::warn!(
+                        "⚠️  與基準相比有 {} 個新發現、{} 個標頭退化",
+                        comparison.new_finding_ids.len(),
+                        comparison.header_regressions.len()
+                    );
+                } else {
+                    tracing::info!("✅ 掃描結果與基準一致");
+                }
+                report.baseline_comparison = Some(comparison);
+            }
+            None => tracing::warn!("⚠️  目標 {} 尚未設定基準，略過比較", url),
+        }
+    }
+
+    control.enter_stage(ScanStage::Finalizing, 98, messages::stage_label(&ScanStage::Finalizing));
+    report.stages = control.finish_stages();
+    report.check_metrics = control.check_metrics();
+    report.coverage = control.coverage();
+    report.request_rate = Some(control.request_rate_report());
+    report.discovered_content = control.take_discovered();
+    report.authentication = control.authentication_report();
 
     // 存儲報告
+    let redirect = report.task.redirect.clone();
+    let (title, favicon_path) = (report.task.title.clone(), report.task.favicon_path.clone());
+    let request_budget = report.task.request_budget.clone();
     let mut results = state.scan_results.lock().await;
     results.insert(task_id.clone(), report);
     drop(results);
 
-    update_task_status(&state, &task_id, status).await;
+    if let Some(task) = state.current_tasks.lock().await.iter_mut().find(|t| t.id == task_id) {
+        task.error = error.clone();
+        task.error_kind = error_kind;
+        task.redirect = redirect;
+        task.title = title;
+        task.favicon_path = favicon_path;
+        task.request_budget = request_budget;
+    }
+    update_task_status(&state, &task_id, status.clone()).await;
+    state.persist_report(&task_id).await;
+    invalidate_dashboard_cache(&state).await;
+
+    match error {
+        None => control.emit(ScanStage::Completed, 100, "掃描完成".to_string()),
+        Some(e) if status == ScanStatus::Cancelled => control.emit(ScanStage::Cancelled, control.current().1, e),
+        Some(e) => control.emit(ScanStage::Failed, control.current().1, e),
+    }
+    control.finish_events();
+}
+
+async fn preflight_client_identity(url: &str, options: &ScanOptions, control: &ScanControl) -> ScannerResult<()> {
+    // 憑證分析、TLS 探測與原始請求自行交握，需要與 reqwest client 相同的用戶端憑證
+    if let Some(identity) = &options.client_identity {
+        let identity = crate::scanners::client::load_tls_identity(identity).map_err(|e| e.context("用戶端憑證載入失敗"))?;
+        control.present_identity(Some(identity));
+    }
+
+    let client = crate::scanners::client::client_builder(options)
+        .map_err(|e| e.context("用戶端憑證載入失敗"))?
+        .danger_accept_invalid_certs(true)
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+
+    match client.get(url).send().await {
+        Ok(response) if response.status().as_u16() == 495 || response.status().as_u16() == 496 => {
+            Err(ScannerError::Tls(format!("目標拒絕用戶端憑證 (HTTP {})", response.status().as_u16())))
+        }
+        Ok(_) => Ok(()),
+        // 連線失敗與逾時保留原本的種類 (可重試)，TLS 錯誤多半是目標拒絕用戶端憑證
+        Err(e) => Err(match ScannerError::from(e) {
+            ScannerError::Tls(message) => ScannerError::Tls(format!("目標拒絕用戶端憑證或 TLS 交握失敗: {}", message)),
+            other => other.context("用戶端憑證預檢失敗"),
+        }),
+    }
+}
+
+/// 設定代理時先經由代理連線到目標，代理設定錯誤時掃描立即失敗，而不是每個請求各自逾時
+async fn preflight_proxy(url: &str, options: &ScanOptions, control: &ScanControl) -> ScannerResult<()> {
+    let Some(settings) = &options.proxy else {
+        return Ok(());
+    };
+    let label = proxy::display_url(settings);
+    control.enter_stage(ScanStage::Resolving, 0, format!("確認代理 {} 的連線", label));
+    let (status, elapsed) = proxy::probe(settings, url, std::time::Duration::from_secs(10)).await?;
+    control.log(format!("🛰️ 經由代理 {} 掃描 (目標回應 HTTP {}，{} ms)", label, status, elapsed.as_millis()));
+    Ok(())
 }
 
-async fn update_task_status(state: &Arc<ScanState>, task_id: &str, status: ScanStatus) {
+/// 設定登入時確認登入狀態有效，之後的請求以此身分送出
+///
+/// 沒有設定登入狀態確認頁面時無法確認，也無法偵測掃描期間登入失效，只記錄警告
+async fn preflight_auth(url: &str, options: &ScanOptions, control: &ScanControl) -> ScannerResult<()> {
+    let Some(auth) = &options.auth else {
+        return Ok(());
+    };
+    control.enter_stage(ScanStage::Resolving, 0, "確認登入狀態".to_string());
+    let session = AuthSession::new(auth, url, options)?;
+    session.verify().await.map_err(|e| e.context("登入狀態確認失敗"))?;
+
+    if auth.indicator_url.is_none() {
+        control.log(format!("⚠️  身分 {} 未設定登入狀態確認頁面，無法確認登入是否有效", session.label()));
+    }
+    control.log(format!("🔑 以身分 {} 掃描 ({})", session.label(), auth.method()));
+    control.authenticate(Arc::new(session));
+    Ok(())
+}
+
+async fn update_task_status(state: &ScanState, task_id: &str, status: ScanStatus) {
     let mut tasks = state.current_tasks.lock().await;
     if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
         task.status = status;
         if task.started_at.is_none() {
             task.started_at = Some(Utc::now());
         }
-        if matches!(task.status, ScanStatus::Completed | ScanStatus::Failed) {
+        if matches!(task.status, ScanStatus::Completed | ScanStatus::Failed | ScanStatus::Cancelled) {
             task.completed_at = Some(Utc::now());
         }
     }
+    drop(tasks);
+    state.persist_task(task_id).await;
+
+    if is_terminal(&status_of(state, task_id).await) {
+        if let Some(notify) = state.completions.lock().await.remove(task_id) {
+            notify.notify_waiters();
+        }
+    }
+}
+
+async fn status_of(state: &ScanState, task_id: &str) -> Option<ScanStatus> {
+    state.current_tasks.lock().await.iter().find(|t| t.id == task_id).map(|t| t.status.clone())
+}
+
+fn is_terminal(status: &Option<ScanStatus>) -> bool {
+    matches!(status, Some(ScanStatus::Completed | ScanStatus::Failed | ScanStatus::Cancelled))
+}
+
+/// 偵測基礎 URL 的永久跨網域重定向
+///
+/// 未設定 force_original_host 時停止掃描，由使用者決定改掃新目標或強制掃描原目標；
+/// 強制時所有 client 的重定向只能停留在原主機。偵測失敗不影響掃描 (由後續階段回報連線錯誤)。
+async fn check_redirect(url: &str, options: &mut ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    let detection = match RedirectDetector::new(options) {
+        Ok(detector) => detector.detect(url).await,
+        Err(e) => Err(e),
+    };
+    let redirect = match detection {
+        Ok(Some(redirect)) => redirect,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            tracing::warn!("⚠️  重定向偵測失敗: {}", e);
+            return Ok(());
+        }
+    };
+
+    if options.force_original_host {
+        options.pinned_host = reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string));
+        control::log(format!("📌 目標重定向到 {}，依設定固定掃描原主機", redirect.to));
+        report.task.redirect = Some(TargetRedirect { decision: RedirectDecision::ForceOriginal, ..redirect });
+        return Ok(());
+    }
+
+    let message = format!("目標重定向到 {} (target redirects to {})", redirect.to, redirect.to);
+    report.task.redirect = Some(redirect);
+    Err(ScannerError::InvalidTarget(message))
+}
+
+/// 目標自上次掃描後沒有變動時略過本次掃描，記錄為「未變動，已略過」並保存最新的雜湊
+async fn skip_unchanged(state: &ScanState, url: &str, options: &ScanOptions, report: &mut ScanReport) -> bool {
+    let Some((previous_task_id, previous)) = changes::latest_hashes(state, url).await else {
+        return false;
+    };
+    match changes::compare_with(url, previous_task_id.clone(), &previous, options).await {
+        Ok(result) if !result.changed => {
+            control::log(format!("🟰 目標自掃描 {} 後沒有變動，略過完整掃描", previous_task_id));
+            report.content_hashes = result.pages.into_iter().map(|page| page.current).collect();
+            report.task.unchanged_since = Some(previous_task_id);
+            true
+        }
+        Ok(_) => false,
+        Err(e) => {
+            tracing::warn!("⚠️  變動檢查失敗，執行完整掃描: {}", e);
+            false
+        }
+    }
+}
+
+/// 記錄基礎頁面、主要路徑與暖身時請求的資源的內容雜湊
+async fn record_content_hashes(url: &str, options: &ScanOptions, report: &mut ScanReport) {
+    let hasher = match ContentHasher::new(options) {
+        Ok(hasher) => hasher,
+        Err(e) => {
+            tracing::warn!("⚠️  無法記錄內容雜湊: {}", e);
+            return;
+        }
+    };
+    let extra = report.warmup.iter().flat_map(|w| w.probes.iter().map(|p| p.url.clone())).collect::<Vec<_>>();
+    let urls = ContentHasher::key_urls(url, extra);
+    report.content_hashes = hasher.hash_all(&urls).await;
+}
+
+/// 暖身探測；目標封鎖掃描流量且未設定 proceed_when_blocked 時回傳錯誤，掃描提早失敗
+async fn warm_up(url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    control::enter_stage(ScanStage::Resolving, 0);
+    let prober = WarmupProber::new(options).map_err(|e| e.context("暖身探測初始化失敗"))?;
+    let mut policy = CrawlPolicy::new(url, &options.crawl);
+    let mut warmup = prober.probe(url, &mut policy).await;
+    report.crawl_skipped.extend(policy.skipped().iter().cloned());
+
+    if warmup.blocked {
+        let summary = warmup.summary();
+        if !options.proceed_when_blocked {
+            report.warmup = Some(warmup);
+            return Err(ScannerError::Blocked(format!("目標封鎖掃描流量 (target blocking scanner traffic): {}", summary)));
+        }
+
+        tracing::warn!("⚠️  目標封鎖掃描流量 ({})，依設定繼續掃描", summary);
+        warmup.proceeded = true;
+        report.coverage_notes.push(CoverageNote {
+            check: "warmup".to_string(),
+            message: format!("目標封鎖掃描流量 ({})，仍依設定繼續掃描，所有發現標記為低信心度", summary),
+            details: None,
+            degraded: false,
+        });
+    }
+
+    report.warmup = Some(warmup);
+    Ok(())
 }
 
-async fn scan_headers_with_results(task_id: &str, url: &str, report: &mut ScanReport) -> Result<(), String> {
-    println!("🔍 開始掃描 HTTP 標頭: {}", url);
-    let scanner = HttpScanner::new();
+/// 記錄頁面標題並快取圖示，供掃描列表顯示；暖身已取得基礎頁面，完整掃描不需要額外的頁面請求。
+/// 沒有暖身的掃描只在 capture_identity_without_warmup 啟用時取得一次基礎頁面，失敗時不影響掃描
+async fn capture_identity(url: &str, options: &ScanOptions, report: &mut ScanReport) {
+    let fetcher = match IdentityFetcher::new(options) {
+        Ok(fetcher) => fetcher,
+        Err(e) => {
+            tracing::warn!("⚠️  無法取得頁面標題: {}", e);
+            return;
+        }
+    };
+
+    let identity = match report.warmup.as_ref() {
+        Some(warmup) => warmup.identity.clone(),
+        None if options.capture_identity_without_warmup => fetcher.fetch_page(url).await,
+        None => None,
+    };
+    let Some(identity) = identity else { return };
+
+    report.task.title = identity.title;
+    if let Some(icon) = &identity.favicon_url {
+        report.task.favicon_path = fetcher.cache_icon(url, icon).await;
+    }
+}
 
-    match scanner.scan_headers(task_id, url).await {
-        Ok(headers) => {
-            println!("✅ 掃描到 {} 個 HTTP 標頭", headers.len());
-            report.headers = headers;
+/// 偵測同意頁並嘗試繞過；成功時後續請求帶上同意 cookie，失敗時將內容相關檢查標記為降級
+async fn check_consent(url: &str, options: &mut ScanOptions, report: &mut ScanReport) {
+    let result = match ConsentChecker::new(options) {
+        Ok(checker) => checker.check(url, &options.consent_cookies).await,
+        Err(e) => Err(e),
+    };
+
+    let consent = match result {
+        Ok(Some(consent)) => consent,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("⚠️  同意頁偵測失敗: {}", e);
+            return;
+        }
+    };
+
+    match consent.analyzed {
+        AnalyzedPage::RealPage if consent.interstitial => {
+            tracing::info!("🍪 已繞過 {:?} 同意頁，後續請求帶上同意 cookie", consent.manager);
+            options.consent_cookie_header = consent.cookie_header.clone();
+            if let Some(cookie) = &consent.cookie_header {
+                control::extend_session_cookies(cookie);
+            }
+        }
+        AnalyzedPage::RealPage => {}
+        AnalyzedPage::Interstitial => {
+            tracing::warn!("⚠️  無法繞過 {:?} 同意頁，內容分析只針對同意頁", consent.manager);
+            for check in ["tech_detection", "vulnerability"] {
+                report.coverage_notes.push(CoverageNote {
+                    check: check.to_string(),
+                    message: format!("只取得 {:?} 同意頁，無法分析實際頁面內容，未發現問題不代表沒有問題", consent.manager),
+                    details: Some(serde_json::json!({ "consent_manager": consent.manager })),
+                    degraded: true,
+                });
+            }
+        }
+    }
+
+    report.consent = Some(consent);
+}
+
+async fn scan_headers_with_results(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    tracing::info!("🔍 開始掃描 HTTP 標頭: {}", url);
+    control::enter_stage(ScanStage::Headers, 0);
+    let scanner = HttpScanner::new(options).map_err(|e| {
+        let e = e.context("HTTP Scanner 初始化失敗");
+        control::cover("headers", CoverageStatus::Error, Some(e.to_string()));
+        e
+    })?;
+
+    let result = match scanner.scan_headers(task_id, url).await {
+        Ok(scan) => {
+            tracing::info!("✅ 掃描到 {} 個 HTTP 標頭", scan.headers.len());
+            report.headers = scan.headers;
+            report.vulnerabilities.extend(scan.findings);
+            control::cover("headers", CoverageStatus::Completed, None);
             Ok(())
         }
         Err(e) => {
-            let error_msg = format!("HTTP 標頭掃描失敗: {}", e);
-            println!("❌ {}", error_msg);
-            Err(error_msg)
+            let e = e.context("HTTP 標頭掃描失敗");
+            tracing::error!("❌ {}", e);
+            control::cover("headers", CoverageStatus::Error, Some(e.to_string()));
+            Err(e)
         }
+    };
+
+    // 標頭中的 Server / X-Powered-By 也能判斷技術，結果在掃描結束時與其他檢測器合併
+    match scanner.detect_technologies(task_id, url).await {
+        Ok(technologies) => report.technologies.extend(technologies),
+        Err(e) => tracing::warn!("⚠️  標頭技術檢測失敗: {}", e),
+    }
+
+    result
+}
+
+async fn scan_ssl_with_results(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    let result = analyze_ssl(task_id, url, options, report).await;
+    if let Err(e) = &result {
+        control::cover("ssl", CoverageStatus::Error, Some(e.to_string()));
     }
+    result
+}
+
+/// http 目標改為分析 HTTPS 前探測的連接埠
+const HTTPS_PORT: u16 = 443;
+
+async fn analyze_ssl(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    analyze_ssl_probing(task_id, url, options, report, HTTPS_PORT).await
 }
 
-async fn scan_ssl_with_results(task_id: &str, url: &str, report: &mut ScanReport) -> Result<(), String> {
-    println!("🔍 開始 SSL/TLS 分析: {}", url);
+/// https_port 為 http 目標探測 HTTPS 的連接埠
+async fn analyze_ssl_probing(
+    task_id: &str,
+    url: &str,
+    options: &ScanOptions,
+    report: &mut ScanReport,
+    https_port: u16,
+) -> ScannerResult<()> {
+    tracing::info!("🔍 開始 SSL/TLS 分析: {}", url);
+    control::enter_stage(ScanStage::Ssl, 15);
 
-    let hostname = url
-        .trim_start_matches("https://")
-        .trim_start_matches("http://")
-        .split('/')
-        .next()
-        .ok_or("無效的 URL")?;
+    let (target, host) = parse_target(url)?;
 
-    // 轉換錯誤為 String 以確保 Send
-    let scanner = SslScanner::new().map_err(|e| {
-        let error_msg = format!("SSL Scanner 初始化失敗: {}", e);
-        println!("❌ {}", error_msg);
-        error_msg.clone()
+    let scanner = SslScanner::new(options).map_err(|e| {
+        let e = e.context("SSL Scanner 初始化失敗");
+        tracing::error!("❌ {}", e);
+        e
     })?;
 
-    let analysis = scanner.scan_ssl(task_id, hostname).await.map_err(|e| {
-        let error_msg = format!("SSL 分析失敗: {}", e);
-        println!("❌ {}", error_msg);
-        error_msg.clone()
+    // http 目標不直接改用 https://同主機 分析 (可能是完全不同的 vhost)：先確認 HTTPS 埠有 HTTPS
+    let analyzed_url = if target.scheme() == "https" {
+        format!("https://{}", target.port().map_or(host.clone(), |port| format!("{}:{}", host, port)))
+    } else {
+        let https_url = if https_port == HTTPS_PORT {
+            format!("https://{}/", host)
+        } else {
+            format!("https://{}:{}/", host, https_port)
+        };
+        if !scanner.https_available(&https_url).await {
+            let reason = format!("目標僅提供 HTTP，{} 的 {} 埠未提供 HTTPS", host, https_port);
+            tracing::info!("⏭️  略過 SSL/TLS 分析: {}", reason);
+            control::skip_stage(reason.clone());
+            control::cover("ssl", CoverageStatus::Skipped, Some(reason.clone()));
+            report.coverage_notes.push(CoverageNote {
+                check: "ssl".to_string(),
+                message: reason,
+                details: None,
+                degraded: false,
+            });
+            return Ok(());
+        }
+
+        report.vulnerabilities.push(ScanResult {
+            id: Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            result_type: ResultType::Ssl,
+            severity: Some(Severity::Info),
+            title: "HTTPS 可用但未作為掃描目標".to_string(),
+            description: Some(format!(
+                "掃描目標為 {}，但 {} 也提供 HTTPS。SSL/TLS 分析針對 {}，該站台可能與 HTTP 目標是不同的 vhost；建議以 HTTPS 作為正式網址並將 HTTP 重新導向。",
+                url, host, https_url
+            )),
+            raw_data: None,
+            created_at: Utc::now(),
+            low_confidence: false,
+            cwe_ids: Vec::new(),
+            details: None,
+        });
+        https_url
+    };
+
+    let analysis = scanner.scan_ssl(task_id, &analyzed_url, None).await.map_err(|e| {
+        let e = e.context("SSL 分析失敗");
+        tracing::error!("❌ {}", e);
+        e
     })?;
 
-    println!("✅ SSL 分析完成，等級: {:?}", analysis.grade);
-    report.ssl_analysis = Some(analysis);
+    tracing::info!("✅ SSL 分析完成，等級: {:?}", analysis.grade);
+    report.vulnerabilities.extend(scanner.weak_cipher_findings(task_id, &analysis));
+    report.ssl_analysis.push(analysis);
+    control::cover("ssl", CoverageStatus::Completed, None);
     Ok(())
 }
 
-async fn scan_vulnerabilities_with_results(task_id: &str, url: &str, report: &mut ScanReport) -> Result<(), String> {
-    println!("🔍 開始漏洞掃描: {}", url);
+async fn scan_vulnerabilities_with_results(
+    task_id: &str,
+    url: &str,
+    options: &ScanOptions,
+    context: TechContext,
+    report: &mut ScanReport,
+) -> ScannerResult<()> {
+    tracing::info!("🔍 開始漏洞掃描: {}", url);
 
     // 使用增強的 OWASP Top 10 掃描器
-    let owasp_scanner = OwaspScanner::new();
-    let owasp_results = match owasp_scanner.scan_all(task_id, url).await {
+    let owasp_scanner = Arc::new(OwaspScanner::new(options)
+        .map_err(|e| e.context("OWASP Scanner 初始化失敗"))?
+        .with_tech_context(context.clone()));
+    let owasp_results = match owasp_scanner.scan_all(task_id, url, &options.owasp).await {
         Ok(results) => {
-            println!("✅ OWASP 掃描完成，發現 {} 個問題", results.len());
+            tracing::info!("✅ OWASP 掃描完成，發現 {} 個問題", results.len());
             results
         }
         Err(e) => {
             let error_msg = format!("OWASP 掃描失敗: {}", e);
-            println!("⚠️  {}", error_msg);
+            tracing::warn!("⚠️  {}", error_msg);
             Vec::new() // 繼續執行，但記錄錯誤
         }
     };
+    report.coverage_notes.extend(owasp_scanner.take_notes());
+    report.owasp_categories = Some(owasp_scanner.category_report());
+    report.resource_inventory = owasp_scanner.resource_inventory();
+    if let Some(crawl) = owasp_scanner.crawl_result() {
+        report.crawl = Some(crawl.summary());
+        report.crawl_skipped.extend(crawl.skipped.iter().cloned());
+    }
 
     // 也可以使用舊的掃描器作為補充
-    let legacy_scanner = VulnerabilityScanner::new();
-    let legacy_results = match legacy_scanner.scan(task_id, url).await {
-        Ok(results) => {
-            println!("✅ Legacy 掃描完成，發現 {} 個問題", results.len());
-            results
-        }
-        Err(e) => {
-            let error_msg = format!("Legacy 掃描失敗: {}", e);
-            println!("⚠️  {}", error_msg);
-            Vec::new() // 繼續執行，但記錄錯誤
+    // 舊掃描器會送出 SQL 注入與 XSS payload，只在 full 模式執行
+    if options.scan_mode < ScanMode::Full {
+        report.coverage_notes.push(CoverageNote {
+            check: "legacy".to_string(),
+            message: format!("{} 模式不執行送出注入 payload 的舊掃描器", options.scan_mode),
+            details: Some(serde_json::json!({ "scan_mode": options.scan_mode, "required_mode": ScanMode::Full })),
+            degraded: false,
+        });
+    }
+    let legacy_results = if options.scan_mode == ScanMode::Full && control::admit("legacy") {
+        let legacy_scanner = VulnerabilityScanner::new(options)
+            .map_err(|e| e.context("Legacy Scanner 初始化失敗"))?
+            .with_tech_context(context);
+        let results = match control::check("legacy", legacy_scanner.scan(task_id, url)).await {
+            Ok(results) => {
+                tracing::info!("✅ Legacy 掃描完成，發現 {} 個問題", results.len());
+                results
+            }
+            Err(e) => {
+                let error_msg = format!("Legacy 掃描失敗: {}", e);
+                tracing::warn!("⚠️  {}", error_msg);
+                Vec::new() // 繼續執行，但記錄錯誤
+            }
+        };
+        control::record_findings("legacy", results.len());
+        finding_caps::apply(task_id, "legacy", results)
+    } else {
+        Vec::new()
+    };
+
+    // 上傳功能：預設只偵測表單，啟用 upload_tests 且為 full 模式時才實際上傳；passive 模式不尋找上傳表單
+    let upload_tests = options.upload_tests && options.scan_mode == ScanMode::Full;
+    let upload_results = if options.scan_mode >= ScanMode::Safe && control::admit("upload") {
+        let outcome = match UploadScanner::new(options) {
+            Ok(scanner) => control::check("upload", scanner.scan(task_id, url, upload_tests)).await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(outcome) => {
+                if outcome.forms_found > 0 && !upload_tests {
+                    report.coverage_notes.push(CoverageNote {
+                        check: "upload".to_string(),
+                        message: if options.upload_tests {
+                            format!("發現 {} 個上傳表單，{} 模式不執行上傳測試", outcome.forms_found, options.scan_mode)
+                        } else {
+                            format!("發現 {} 個上傳表單，未啟用上傳測試 (upload_tests)", outcome.forms_found)
+                        },
+                        details: None,
+                        degraded: false,
+                    });
+                }
+                report.uploaded_files.extend(outcome.uploads);
+                control::record_findings("upload", outcome.findings.len());
+                outcome.findings
+            }
+            Err(e) => {
+                tracing::warn!("⚠️  上傳檢查失敗: {}", e);
+                Vec::new()
+            }
         }
+    } else {
+        Vec::new()
     };
 
     // 合併結果
     report.vulnerabilities.extend(owasp_results);
     report.vulnerabilities.extend(legacy_results);
+    report.vulnerabilities.extend(upload_results);
 
-    // 去重 (基於 title)
-    report.vulnerabilities.sort_by(|a, b| {
-        b.severity.as_ref().unwrap_or(&Severity::Info)
-            .cmp(a.severity.as_ref().unwrap_or(&Severity::Info))
-    });
-    report.vulnerabilities.dedup_by(|a, b| a.title == b.title);
+    // 依嚴重程度排序 (Critical 在前)，並以標題去重，保留嚴重程度最高的一筆
+    report.vulnerabilities = dedupe_by_title(std::mem::take(&mut report.vulnerabilities));
 
-    println!("✅ 漏洞掃描完成，共發現 {} 個潛在漏洞", report.vulnerabilities.len());
+    tracing::info!("✅ 漏洞掃描完成，共發現 {} 個潛在漏洞", report.vulnerabilities.len());
     Ok(())
 }
 
-async fn scan_full_with_results(task_id: &str, url: &str, report: &mut ScanReport) -> Result<(), String> {
-    println!("🔍 開始完整掃描: {}", url);
-    let mut errors = Vec::new();
+/// 將報告中的發現送出為 scan-finding 事件；已送出的發現由事件節流略過
+fn emit_findings(report: &ScanReport) {
+    for finding in &report.vulnerabilities {
+        control::finding(finding);
+    }
+}
 
-    // HTTP 標頭掃描
-    if let Err(e) = scan_headers_with_results(task_id, url, report).await {
-        errors.push(format!("標頭掃描: {}", e));
+/// 分層代理差異檢查；失敗只記錄在涵蓋範圍，不影響掃描結果
+async fn scan_proxy_disagreement_with_results(task_id: &str, url: &str, report: &mut ScanReport) {
+    tracing::info!("🔍 開始分層代理差異檢查: {}", url);
+    let result = match ProxyDisagreementScanner::new(url) {
+        Ok(scanner) => scanner.scan(task_id).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(Some(finding)) => {
+            tracing::info!("ℹ️  觀察到分層代理處理不一致，建議人工進行 smuggling 測試");
+            report.vulnerabilities.push(finding);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::warn!("⚠️  分層代理差異檢查失敗: {}", e);
+            report.coverage_notes.push(CoverageNote {
+                check: "proxy_disagreement".to_string(),
+                message: format!("分層代理差異檢查未完成: {}", e),
+                details: None,
+                degraded: false,
+            });
+        }
     }
+}
 
-    // SSL/TLS 分析
-    if url.starts_with("https://") {
-        if let Err(e) = scan_ssl_with_results(task_id, url, report).await {
-            errors.push(format!("SSL 分析: {}", e));
+/// 依嚴重程度由高到低排序 (同嚴重程度保留原順序)，同標題只保留第一筆，也就是嚴重程度最高的一筆
+fn dedupe_by_title(mut findings: Vec<ScanResult>) -> Vec<ScanResult> {
+    findings.sort_by_key(|f| std::cmp::Reverse(severity_rank(f.severity.as_ref())));
+    let mut seen = HashSet::new();
+    findings.retain(|f| seen.insert(f.title.clone()));
+    findings
+}
+
+async fn scan_ports_with_results(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    control::enter_stage(ScanStage::Ports, 25);
+    let (_, host) = parse_target(url)?;
+
+    match PortScanner::new(&options.port_scan).scan(task_id, &host).await {
+        Ok(ports) => {
+            report.open_ports = ports;
+            Ok(())
+        }
+        Err(e) => {
+            let e = e.context("連接埠掃描失敗");
+            tracing::error!("❌ {}", e);
+            Err(e)
         }
     }
+}
 
-    // 漏洞掃描 (永遠不會失敗，因為內部已處理錯誤)
-    if let Err(e) = scan_vulnerabilities_with_results(task_id, url, report).await {
-        errors.push(format!("漏洞掃描: {}", e));
+/// 解析目標 URL 與主機名稱
+fn parse_target(url: &str) -> ScannerResult<(reqwest::Url, String)> {
+    let target = reqwest::Url::parse(url).map_err(|e| ScannerError::InvalidTarget(format!("無效的 URL: {}", e)))?;
+    let host = target
+        .host_str()
+        .ok_or_else(|| ScannerError::InvalidTarget(format!("無效的 URL: {} 沒有主機名稱", url)))?
+        .to_string();
+    Ok((target, host))
+}
+
+/// 對連接埠掃描找到、可能是 TLS 的其他連接埠各做一次 SSL 分析
+///
+/// 無法完成 TLS 交握的連接埠視為明文服務略過，不算錯誤
+async fn scan_tls_ports_with_results(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    let (_, host) = parse_target(url)?;
+    let analyzed: HashSet<u16> = report.ssl_analysis.iter().filter_map(|ssl| ssl.analyzed_port).collect();
+    let candidates: Vec<u16> = report.open_ports
+        .iter()
+        .filter(|port| !analyzed.contains(&port.port) && port_scanner::may_speak_tls(port))
+        .map(|port| port.port)
+        .collect();
+    if candidates.is_empty() {
+        return Ok(());
     }
 
-    // 技術檢測
-    let detector = TechDetector::new();
-    match detector.detect(task_id, url).await {
+    let scanner = SslScanner::new(options).map_err(|e| e.context("SSL Scanner 初始化失敗"))?;
+    for port in candidates {
+        if control::stopped() {
+            break;
+        }
+        match scanner.scan_ssl(task_id, &host, Some(port)).await {
+            Ok(analysis) => {
+                tracing::info!("✅ 連接埠 {} SSL 分析完成，等級: {:?}", port, analysis.grade);
+                report.vulnerabilities.extend(scanner.weak_cipher_findings(task_id, &analysis));
+                report.ssl_analysis.push(analysis);
+            }
+            Err(e) => tracing::info!("⏭️  連接埠 {} 未提供 TLS，略過 SSL 分析: {}", port, e),
+        }
+    }
+    Ok(())
+}
+
+async fn scan_technologies_with_results(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    control::enter_stage(ScanStage::TechDetection, 35);
+    let detection = match TechDetector::new(options) {
+        Ok(detector) => control::check("tech_detection", detector.detect(task_id, url)).await,
+        Err(e) => Err(e),
+    };
+    match detection {
         Ok(technologies) => {
-            println!("✅ 檢測到 {} 個技術", technologies.len());
-            report.technologies = technologies;
+            tracing::info!("✅ 檢測到 {} 個技術", technologies.len());
+            report.technologies.extend(technologies);
+            control::cover("tech_detection", CoverageStatus::Completed, None);
+            Ok(())
         }
         Err(e) => {
-            let error_msg = format!("技術檢測失敗: {}", e);
-            println!("⚠️  {}", error_msg);
-            errors.push(error_msg);
+            let e = e.context("技術檢測失敗");
+            tracing::warn!("⚠️  {}", e);
+            control::cover("tech_detection", CoverageStatus::Error, Some(e.to_string()));
+            Err(e)
+        }
+    }
+}
+
+/// 快速掃描：只做 HTTP 標頭與技術檢測，不送出 OWASP 與其他主動探測的 payload
+///
+/// 發現只有標頭分析產生的被動發現；技術檢測失敗時仍以標頭結果完成
+async fn scan_quick_with_results(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    tracing::info!("🔍 開始快速掃描: {}", url);
+
+    if control::admit("headers") {
+        let before = report.vulnerabilities.len();
+        let result = control::check("headers", scan_headers_with_results(task_id, url, options, report)).await;
+        control::record_findings("headers", report.vulnerabilities.len() - before);
+        result?;
+    }
+    emit_findings(report);
+
+    if control::admit("tech_detection") {
+        let _ = scan_technologies_with_results(task_id, url, options, report).await;
+    }
+
+    tracing::info!("✅ 快速掃描完成");
+    Ok(())
+}
+
+async fn scan_full_with_results(task_id: &str, url: &str, options: &ScanOptions, report: &mut ScanReport) -> ScannerResult<()> {
+    tracing::info!("🔍 開始完整掃描: {}", url);
+    let mut errors: Vec<(&str, ScannerError)> = Vec::new();
+
+    // 各階段的順序與 time_box 的優先順序一致：標頭、TLS、技術檢測，接著是漏洞掃描
+    // HTTP 標頭掃描
+    if control::admit("headers") {
+        let before = report.vulnerabilities.len();
+        if let Err(e) = control::check("headers", scan_headers_with_results(task_id, url, options, report)).await {
+            errors.push(("標頭掃描", e));
+        }
+        control::record_findings("headers", report.vulnerabilities.len() - before);
+    }
+
+    // SSL/TLS 分析 (http 目標只在同主機提供 HTTPS 時分析，否則略過)
+    if control::admit("ssl") {
+        let before = report.vulnerabilities.len();
+        if let Err(e) = control::check("ssl", scan_ssl_with_results(task_id, url, options, report)).await {
+            errors.push(("SSL 分析", e));
+        }
+        control::record_findings("ssl", report.vulnerabilities.len() - before);
+    }
+    emit_findings(report);
+
+    // 連接埠掃描：開放的連接埠列在報告與匯出的資產中
+    if control::admit("ports") {
+        if let Err(e) = control::check("ports", scan_ports_with_results(task_id, url, options, report)).await {
+            errors.push(("連接埠掃描", e));
+        }
+    }
+
+    // 其他開放連接埠上的 TLS 服務 (例如 8443、9443、10250)
+    if !report.open_ports.is_empty() && control::admit("ssl") {
+        if let Err(e) = control::check("ssl", scan_tls_ports_with_results(task_id, url, options, report)).await {
+            errors.push(("連接埠 SSL 分析", e));
+        }
+    }
+
+    // 技術檢測：必須在漏洞掃描之前，條件式檢查依此結果決定是否執行
+    // 時間用盡而未執行時沒有技術資訊，與檢測失敗相同，所有檢查照常執行
+    let context = if control::admit("tech_detection") {
+        match scan_technologies_with_results(task_id, url, options, report).await {
+            Ok(()) => TechContext::from_detection(&report.technologies, &report.headers, options.ignore_preconditions),
+            Err(e) => {
+                errors.push(("技術檢測", e));
+                TechContext::undetected(options.ignore_preconditions)
+            }
         }
+    } else {
+        TechContext::undetected(options.ignore_preconditions)
+    };
+
+    // 漏洞掃描 (永遠不會失敗，因為內部已處理錯誤)
+    if let Err(e) = scan_vulnerabilities_with_results(task_id, url, options, context, report).await {
+        errors.push(("漏洞掃描", e));
+    }
+    emit_findings(report);
+
+    if options.proxy_disagreement_check && control::admit("proxy_disagreement") {
+        let before = report.vulnerabilities.len();
+        control::check("proxy_disagreement", scan_proxy_disagreement_with_results(task_id, url, report)).await;
+        control::record_findings("proxy_disagreement", report.vulnerabilities.len() - before);
     }
 
     if errors.is_empty() {
-        println!("✅ 完整掃描成功完成");
+        tracing::info!("✅ 完整掃描成功完成");
         Ok(())
     } else {
-        let error_summary = format!("部分掃描失敗: {}", errors.join("; "));
-        println!("⚠️  {}", error_summary);
+        let summary: Vec<String> = errors.iter().map(|(step, e)| format!("{}: {}", step, e)).collect();
+        tracing::warn!("⚠️  部分掃描失敗: {}", summary.join("; "));
         // 即使有部分失敗，只要有部分成功就返回 Ok
         // 因為我們已經收集到了一些有用的資料
-        if !report.headers.is_empty() || !report.vulnerabilities.is_empty() || !report.technologies.is_empty() {
+        if !report.headers.is_empty() || !report.vulnerabilities.is_empty() || !report.technologies.is_empty() || !report.open_ports.is_empty() {
             Ok(())
         } else {
-            Err(error_summary)
+            // 沒有任何結果時回傳第一個錯誤，保留其種類
+            Err(errors.remove(0).1)
         }
     }
 }
 
+/// 暫停執行中的掃描
+///
+/// 進行中的請求會完成，下一個請求在共用 client 的檢查點等待。暫停超過 MAX_PAUSE 後掃描自動失敗。
+#[tauri::command]
+pub async fn pause_scan(task_id: String, state: State<'_, ScanState>) -> Result<(), String> {
+    let control = find_control(&state, &task_id).await?;
+    let generation = control.pause().ok_or_else(|| "掃描已經暫停".to_string())?;
+
+    update_task_status(&state, &task_id, ScanStatus::Paused).await;
+    tracing::info!("⏸️  掃描已暫停: {}", task_id);
+    let (_, progress) = control.current();
+    control.emit(
+        ScanStage::Paused,
+        progress,
+        format!("掃描已暫停，{} 分鐘內未繼續將自動終止", MAX_PAUSE.as_secs() / 60),
+    );
+
+    // 逾時計時器只持有控制本身，不持有任何 state 的鎖
+    tokio::spawn(async move {
+        if control.expire_pause(generation).await {
+            tracing::info!("⏱️  掃描暫停逾時: {}", task_id);
+            let (_, progress) = control.current();
+            control.emit(ScanStage::Failed, progress, control.abort_reason().unwrap_or_default());
+        }
+    });
+
+    Ok(())
+}
+
+/// 繼續已暫停的掃描
+#[tauri::command]
+pub async fn resume_scan_inflight(task_id: String, state: State<'_, ScanState>) -> Result<(), String> {
+    let control = find_control(&state, &task_id).await?;
+    if !control.resume() {
+        return Err("掃描沒有暫停".to_string());
+    }
+
+    update_task_status(&state, &task_id, ScanStatus::Running).await;
+    tracing::info!("▶️  掃描已繼續: {}", task_id);
+    let (stage, progress) = control.current();
+    control.emit(stage, progress, "掃描已繼續".to_string());
+
+    Ok(())
+}
+
+/// 取消執行中 (或暫停中) 的掃描
+///
+/// 進行中的請求立即中斷，各掃描器在下一個檢查點停止。已收集的部分結果照常存入報告，
+/// 狀態為 Cancelled。掃描已結束時回傳錯誤。
+#[tauri::command]
+pub async fn cancel_scan(task_id: String, state: State<'_, ScanState>) -> Result<(), String> {
+    let control = match find_control(&state, &task_id).await {
+        Ok(control) => control,
+        Err(_) => {
+            return match status_of(&state, &task_id).await {
+                Some(status) if is_terminal(&Some(status.clone())) => Err(format!("掃描已結束 ({})，無法取消", status)),
+                Some(_) => Err("掃描尚未開始執行".to_string()),
+                None => Err(format!("找不到該任務: {}", task_id)),
+            };
+        }
+    };
+    if !control.cancel() {
+        return Err("掃描已經終止".to_string());
+    }
+
+    tracing::info!("🛑 取消掃描: {}", task_id);
+    let (_, progress) = control.current();
+    control.emit(ScanStage::Cancelled, progress, "正在取消掃描，保留已收集的結果".to_string());
+    Ok(())
+}
+
+// 取出控制後立即釋放鎖
+async fn find_control(state: &ScanState, task_id: &str) -> Result<ScanControl, String> {
+    state
+        .controls
+        .lock()
+        .await
+        .get(task_id)
+        .cloned()
+        .ok_or_else(|| "找不到執行中的掃描".to_string())
+}
+
+/// 階段與其顯示名稱，供前端建立進度介面
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StageInfo {
+    pub stage: ScanStage,
+    pub label: String,
+}
+
+/// 列出所有掃描階段 (依執行順序)，label 依 locale 產生，預設為繁體中文
+#[tauri::command]
+pub async fn list_stages(locale: Option<String>) -> Result<Vec<StageInfo>, String> {
+    let locale = locale.unwrap_or_else(|| messages::DEFAULT_LOCALE.to_string());
+    Ok(ScanStage::all()
+        .into_iter()
+        .map(|stage| StageInfo { label: messages::stage_label_for(&stage, &locale), stage })
+        .collect())
+}
+
 #[tauri::command]
 pub async fn get_scan_status(
     task_id: String,
     state: State<'_, ScanState>,
 ) -> Result<ScanTask, String> {
-    let tasks = state.current_tasks.lock().await;
-    tasks
+    let mut task = state
+        .current_tasks
+        .lock()
+        .await
         .iter()
         .find(|t| t.id == task_id)
         .cloned()
-        .ok_or_else(|| "找不到該任務".to_string())
+        .ok_or_else(|| "找不到該任務".to_string())?;
+    // 執行中的掃描即時回報請求預算，讓介面在預算快用完時提醒使用者
+    if let Some(control) = state.controls.lock().await.get(&task_id) {
+        task.request_budget = Some(control.request_budget());
+    }
+    Ok(task)
+}
+
+/// wait_for_scan 的結果
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ScanOutcome {
+    pub task: ScanTask,
+    /// 掃描完成時計算的統計摘要；逾時或沒有報告時為 None
+    pub summary: Option<ReportSummary>,
+    /// 等待逾時，task 為當時的狀態
+    pub timed_out: bool,
+}
+
+/// 等待掃描進入終止狀態 (完成、失敗或取消) 或逾時，供腳本與自動化流程使用，不需要輪詢 get_scan_status
+#[tauri::command]
+pub async fn wait_for_scan(
+    task_id: String,
+    timeout_secs: u64,
+    state: State<'_, ScanState>,
+) -> Result<ScanOutcome, String> {
+    wait_for_completion(&state, &task_id, timeout_secs).await
+}
+
+async fn wait_for_completion(state: &ScanState, task_id: &str, timeout_secs: u64) -> Result<ScanOutcome, String> {
+    let notify = state.completion(task_id).await;
+    let outcome = wait_for_terminal(state, task_id, &notify, timeout_secs).await;
+    // 每個結束路徑 (已終止、找不到、逾時、被喚醒) 都釋放，避免通知留在表中
+    state.release_completion(task_id, notify).await;
+    outcome
+}
+
+async fn wait_for_terminal(state: &ScanState, task_id: &str, notify: &Notify, timeout_secs: u64) -> Result<ScanOutcome, String> {
+    // 先登記等待再檢查狀態，檢查之後才完成的掃描也會喚醒這個等待者
+    let notified = notify.notified();
+    tokio::pin!(notified);
+    notified.as_mut().enable();
+
+    let status = status_of(state, task_id).await;
+    if status.is_none() {
+        return Err(format!("找不到該任務: {}", task_id));
+    }
+
+    let timed_out = !is_terminal(&status)
+        && tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), notified).await.is_err();
+
+    let task = state
+        .current_tasks
+        .lock()
+        .await
+        .iter()
+        .find(|t| t.id == task_id)
+        .cloned()
+        .ok_or_else(|| format!("找不到該任務: {}", task_id))?;
+    let summary = if timed_out {
+        None
+    } else {
+        state.scan_results.lock().await.get(task_id).and_then(|report| report.summary.clone())
+    };
+    Ok(ScanOutcome { task, summary, timed_out })
 }
 
 #[tauri::command]
@@ -310,6 +1599,65 @@ pub async fn list_scans(
     Ok(tasks.clone())
 }
 
+/// delete_scans 中單一掃描的刪除結果
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ScanDeletion {
+    pub task_id: String,
+    pub deleted: bool,
+    pub error: Option<String>,
+}
+
+/// 刪除掃描與其所有結果 (發現、註解、標頭、TLS 分析、技術)
+///
+/// 執行中或暫停中的掃描不會被刪除，需先以 cancel_scan 取消，掃描結束後再刪除
+#[tauri::command]
+pub async fn delete_scan(task_id: String, state: State<'_, ScanState>) -> Result<(), String> {
+    delete_one(&state, &task_id).await?;
+    invalidate_dashboard_cache(&state).await;
+    Ok(())
+}
+
+/// 批次刪除掃描，回傳每個掃描各自的結果；其中一個失敗不影響其他掃描
+#[tauri::command]
+pub async fn delete_scans(task_ids: Vec<String>, state: State<'_, ScanState>) -> Result<Vec<ScanDeletion>, String> {
+    let mut deletions = Vec::with_capacity(task_ids.len());
+    for task_id in task_ids {
+        let result = delete_one(&state, &task_id).await;
+        deletions.push(ScanDeletion { task_id, deleted: result.is_ok(), error: result.err() });
+    }
+    if deletions.iter().any(|d| d.deleted) {
+        invalidate_dashboard_cache(&state).await;
+    }
+    Ok(deletions)
+}
+
+async fn delete_one(state: &ScanState, task_id: &str) -> Result<(), String> {
+    if state.controls.lock().await.contains_key(task_id) {
+        return Err(format!("掃描 {} 仍在執行中，無法刪除；請先以 cancel_scan 取消，掃描結束後再刪除", task_id));
+    }
+
+    // 先刪除資料庫中的紀錄，失敗時記憶體中的掃描保持不變
+    let in_database = match state.store() {
+        Some(store) => store.delete_scan(task_id).await.map_err(|e| format!("無法刪除掃描 {}: {}", task_id, e))?,
+        None => false,
+    };
+
+    let mut tasks = state.current_tasks.lock().await;
+    let before = tasks.len();
+    tasks.retain(|t| t.id != task_id);
+    let in_memory = tasks.len() != before;
+    drop(tasks);
+    state.scan_results.lock().await.remove(task_id);
+    state.scan_options.lock().await.remove(task_id);
+    logging::discard(task_id);
+
+    if !in_memory && !in_database {
+        return Err(format!("找不到該任務: {}", task_id));
+    }
+    tracing::info!("🗑️  已刪除掃描: {}", task_id);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_scan_report(
     task_id: String,
@@ -321,3 +1669,259 @@ pub async fn get_scan_report(
         .cloned()
         .ok_or_else(|| "找不到掃描報告".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, status: &str) -> ScanTask {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "target_url": "https://example.com/",
+            "scan_type": "full",
+            "status": status,
+            "created_at": "2026-01-05T10:00:00Z",
+        }))
+        .expect("valid task")
+    }
+
+    fn finding(id: &str, title: &str, severity: Option<&str>) -> ScanResult {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "task_id": "scan-1",
+            "result_type": "vulnerability",
+            "severity": severity,
+            "title": title,
+            "description": null,
+            "raw_data": null,
+            "created_at": "2026-01-05T10:00:00Z",
+        }))
+        .expect("valid finding")
+    }
+
+    async fn state_with(tasks: Vec<ScanTask>) -> ScanState {
+        let state = ScanState::new(None, None, None);
+        *state.current_tasks.lock().await = tasks;
+        state
+    }
+
+    #[tokio::test]
+    async fn restore_upgrades_a_database_left_at_an_older_schema() {
+        use crate::commands::baseline::{baseline_key, snapshot_baseline};
+        use crate::database::store::Store;
+
+        // 升級前前端只執行到 005；task_state、report 與 baselines 都還不存在
+        let path = Store::create_at_version(5).await;
+        let store = Store::open_at(&path).await;
+        let state = ScanState::new(Some(store.clone()), None, None);
+        state.restore().await;
+        assert!(store.load_scans().await.is_ok());
+        assert!(store.load_baselines().await.is_ok());
+
+        let report: ScanReport = serde_json::from_value(serde_json::json!({
+            "task": task("scan-1", "completed"),
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": [finding("finding-1", "SQL Injection", Some("high"))],
+        }))
+        .expect("valid report");
+        store.save_report(&report).await.expect("save report");
+        store.save_baseline(&snapshot_baseline(&report)).await.expect("save baseline");
+
+        // 再次啟動時遷移已全部記錄，不會重複執行
+        let state = ScanState::new(Some(Store::open_at(&path).await), None, None);
+        state.restore().await;
+        assert_eq!(state.current_tasks.lock().await.len(), 1);
+        assert_eq!(state.scan_results.lock().await["scan-1"].vulnerabilities.len(), 1);
+        assert!(state.baselines.lock().await.contains_key(&baseline_key("https://example.com/")));
+    }
+
+    #[tokio::test]
+    async fn releases_the_notification_on_every_exit_path() {
+        let state = state_with(vec![task("done", "completed"), task("running", "running")]).await;
+
+        assert!(!wait_for_completion(&state, "done", 5).await.unwrap().timed_out);
+        assert!(wait_for_completion(&state, "missing", 5).await.is_err());
+        assert!(wait_for_completion(&state, "running", 0).await.unwrap().timed_out);
+
+        assert!(state.completions.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_timed_out_waiter_does_not_detach_the_others() {
+        let state = state_with(vec![task("running", "running")]).await;
+
+        let waiter = {
+            let state = state.clone();
+            tokio::spawn(async move { wait_for_completion(&state, "running", 30).await })
+        };
+        while !state.completions.lock().await.contains_key("running") {
+            tokio::task::yield_now().await;
+        }
+        assert!(wait_for_completion(&state, "running", 0).await.unwrap().timed_out);
+        assert!(state.completions.lock().await.contains_key("running"));
+
+        update_task_status(&state, "running", ScanStatus::Completed).await;
+        let outcome = tokio::time::timeout(std::time::Duration::from_secs(5), waiter).await.unwrap().unwrap().unwrap();
+        assert!(!outcome.timed_out);
+        assert_eq!(outcome.task.status, ScanStatus::Completed);
+        assert!(state.completions.lock().await.is_empty());
+    }
+
+    #[test]
+    fn keeps_the_highest_severity_duplicate_and_sorts_critical_first() {
+        let findings = vec![
+            finding("legacy-xss", "Reflected XSS", Some("low")),
+            finding("info", "Server banner", Some("info")),
+            finding("owasp-sqli", "SQL Injection", Some("high")),
+            finding("owasp-xss", "Reflected XSS", Some("high")),
+            finding("unrated", "Directory listing", None),
+            finding("legacy-sqli", "SQL Injection", Some("critical")),
+            finding("medium", "Missing CSP", Some("medium")),
+            finding("unrated-xss", "Reflected XSS", None),
+            finding("second-high-xss", "Reflected XSS", Some("high")),
+        ];
+
+        let ids: Vec<String> = dedupe_by_title(findings).into_iter().map(|f| f.id).collect();
+        // 同嚴重程度依原順序，未設定嚴重程度視為 Info
+        assert_eq!(ids, vec!["legacy-sqli", "owasp-xss", "medium", "info", "unrated"]);
+    }
+
+    #[test]
+    fn duplicates_far_apart_are_still_removed() {
+        let mut findings: Vec<ScanResult> = (0..20).map(|i| finding(&format!("f{}", i), &format!("Finding {}", i), Some("medium"))).collect();
+        findings.insert(0, finding("first", "Finding 7", Some("low")));
+        findings.push(finding("last", "Finding 7", Some("critical")));
+
+        let deduped = dedupe_by_title(findings);
+        assert_eq!(deduped.len(), 20);
+        assert_eq!(deduped[0].id, "last");
+        assert_eq!(deduped.iter().filter(|f| f.title == "Finding 7").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn snapshot_lists_secrets_and_rescan_requires_them() {
+        let options: ScanOptions = serde_json::from_value(serde_json::json!({
+            "auth": { "label": "member", "cookies": "session=abc" },
+            "proxy": { "url": "http://127.0.0.1:8080", "username": "tester", "password": "hunter2" },
+        }))
+        .unwrap();
+        let (config, hash) = config_snapshot(&ScanType::Full, &options);
+        assert_eq!(config["secrets_configured"], serde_json::json!(["auth.cookies", "proxy.password"]));
+        assert!(!config.to_string().contains("session=abc") && !config.to_string().contains("hunter2"));
+        // 有無登入的掃描雜湊不同
+        assert_ne!(hash, config_snapshot(&ScanType::Full, &ScanOptions::default()).1);
+
+        // 重新啟動後記憶體中沒有原始設定，只剩快照
+        let mut restored = task("scan-1", "completed");
+        restored.config = Some(config);
+        let state = state_with(vec![restored.clone()]).await;
+        let error = rescan_task("scan-1", &state).await.unwrap_err();
+        assert!(error.contains("auth.cookies") && error.contains("proxy.password"), "{}", error);
+        // 沒有以不帶憑證的設定建立新掃描
+        assert_eq!(state.current_tasks.lock().await.len(), 1);
+    }
+
+    /// 只提供 HTTP 的本機伺服器，每個連線都回應 200
+    async fn serve_http_only() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok").await;
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    /// 回應 nginx 標頭與一般頁面的本機網站；回傳網址與所有請求的路徑
+    async fn serve_site() -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requested = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = requested.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0u8; 8192];
+                let read = socket.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                log.lock().unwrap().push(request.split_whitespace().nth(1).unwrap_or("/").to_string());
+
+                let body = "<html><head><title>Shop</title></head><body><h1>Welcome</h1></body></html>";
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nServer: nginx/1.18.0\r\nX-Powered-By: Express\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{}/", addr), requested)
+    }
+
+    #[tokio::test]
+    async fn quick_scan_completes_with_headers_and_technologies() {
+        let (url, requested) = serve_site().await;
+        let mut quick = task("quick-1", "pending");
+        quick.target_url = url;
+        let state = Arc::new(state_with(vec![quick.clone()]).await);
+        // 預先放入的控制不寫入稽核與 canary 紀錄
+        state.controls.lock().await.insert("quick-1".to_string(), ScanControl::default());
+
+        let started = std::time::Instant::now();
+        execute_scan(quick, "quick".to_string(), ScanOptions::default(), state.clone()).await;
+        assert!(started.elapsed() < std::time::Duration::from_secs(15), "took {:?}", started.elapsed());
+
+        let report = state.scan_results.lock().await["quick-1"].clone();
+        assert_eq!(report.task.status, ScanStatus::Completed, "{:?}", report.task.error);
+        assert_eq!(state.current_tasks.lock().await[0].status, ScanStatus::Completed);
+        assert!(!report.headers.is_empty());
+        assert!(report.technologies.iter().any(|t| t.technology_name.to_lowercase().contains("nginx")));
+        // 只有標頭分析的被動發現，沒有送出任何 OWASP 探測
+        assert!(report.vulnerabilities.iter().all(|f| matches!(f.result_type, ResultType::Header)));
+        let requested = requested.lock().unwrap();
+        assert!(
+            requested.iter().all(|path| !path.contains("admin") && !path.contains(".env") && !path.contains('\'')),
+            "{:?}",
+            requested
+        );
+    }
+
+    #[tokio::test]
+    async fn http_only_target_skips_the_ssl_stage() {
+        let url = serve_http_only().await;
+        let mut report: ScanReport = serde_json::from_value(serde_json::json!({
+            "task": task("scan-1", "running"),
+            "headers": [],
+            "technologies": [],
+            "vulnerabilities": [],
+        }))
+        .unwrap();
+
+        // 不探測本機真正的 443 埠：改向同一個只回應 HTTP 的埠發起 TLS 交握
+        let port = reqwest::Url::parse(&url).unwrap().port().unwrap();
+        let control = ScanControl::default();
+        control::scope(control.clone(), analyze_ssl_probing("scan-1", &url, &ScanOptions::default(), &mut report, port))
+            .await
+            .unwrap();
+
+        // 沒有分析結果，也就沒有預設的 A 等級
+        assert!(report.ssl_analysis.is_empty());
+        assert!(report.vulnerabilities.is_empty());
+
+        let stages = control.finish_stages();
+        let ssl = stages.iter().find(|s| s.stage == ScanStage::Ssl).unwrap();
+        assert!(ssl.skipped.as_deref().is_some_and(|reason| reason.contains("僅提供 HTTP")));
+
+        let coverage = control.coverage();
+        let ssl = coverage.iter().find(|c| c.check == "ssl").unwrap();
+        assert_eq!(ssl.status, CoverageStatus::Skipped);
+        assert_eq!(report.coverage_notes.len(), 1);
+    }
+}
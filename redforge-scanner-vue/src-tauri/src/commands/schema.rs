@@ -0,0 +1,294 @@
+/**
+ * API Schema Commands
+ *
+ * 由 Rust 型別產生所有 command 輸入 / 輸出與事件的 JSON Schema，前端用來驗證資料並產生 TypeScript 型別。
+ * 快照存放在 schema/api-schema.json，由 `cargo run --bin api-schema` 產生；
+ * `cargo test` 會在 schema 與快照不同時失敗，schema 變動但 API_SCHEMA_VERSION 未調整時另外提示。
+ */
+
+use crate::attack_chains::ChainRule;
+use crate::canary::CanaryRecord;
+use crate::commands::baseline::Baseline;
+use crate::commands::changes::TargetChanges;
+use crate::commands::compare::{ScanComparison, ScanDiff};
+use crate::commands::workspace::{WorkspaceImportMode, WorkspaceImportResult};
+use crate::events::{FindingEvent, ScanLogBatch};
+use crate::executive_summary::ExecutiveSummary;
+use crate::logging::{LogLevel, ScanLogEntry};
+use crate::commands::collaboration::{DeduplicateResult, ExportData, ImportResult};
+use crate::commands::dashboard::{ActivitySummary, CheckEfficiencyReport, DashboardStats};
+use crate::commands::findings::CsvExportOptions;
+use crate::commands::scan::{ScanDeletion, ScanOutcome, ScanReport, StageInfo};
+use crate::commands::settings::ProxyTestResult;
+use crate::database::store::{FindingFilters, FindingPage, StorageStatus};
+use crate::models::*;
+use crate::output::WrittenFile;
+use crate::redaction::{PseudonymMapping, RedactionProfile};
+use crate::settings::AppSettings;
+use crate::summary::ReportSummary;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+/// API 版本；schema 有任何變動都必須調整此值並重新產生快照
+pub const API_SCHEMA_VERSION: &str = "2.26.0";
+
+/// 單一 command 的參數與回傳型別；參數名稱與前端 invoke 相同 (camelCase)
+struct CommandSchema<'a> {
+    generator: &'a mut SchemaGenerator,
+    properties: Map<String, Value>,
+    required: Vec<String>,
+}
+
+impl<'a> CommandSchema<'a> {
+    fn new(generator: &'a mut SchemaGenerator) -> Self {
+        Self { generator, properties: Map::new(), required: Vec::new() }
+    }
+
+    fn arg<T: JsonSchema>(mut self, name: &str) -> Self {
+        self.required.push(name.to_string());
+        self.optional::<T>(name)
+    }
+
+    fn optional<T: JsonSchema>(mut self, name: &str) -> Self {
+        let schema = self.generator.subschema_for::<T>();
+        self.properties.insert(name.to_string(), json!(schema));
+        self
+    }
+
+    /// 錯誤一律為字串訊息
+    fn returns<T: JsonSchema>(self) -> Value {
+        let output = self.generator.subschema_for::<T>();
+        json!({
+            "input": {
+                "type": "object",
+                "properties": self.properties,
+                "required": self.required,
+                "additionalProperties": false,
+            },
+            "output": output,
+            "error": { "type": "string" },
+        })
+    }
+}
+
+/// 產生完整的 API schema 文件
+pub fn api_schema() -> Value {
+    let mut generator = SchemaSettings::draft07().into_generator();
+    let mut commands = Map::new();
+    let mut add = |name: &str, schema: Value| {
+        commands.insert(name.to_string(), schema);
+    };
+
+    // scan
+    add("start_scan", CommandSchema::new(&mut generator)
+        .arg::<String>("url")
+        .arg::<String>("scanType")
+        .optional::<Option<ScanOptions>>("options")
+        .returns::<String>());
+    add("rescan", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<String>());
+    add("pause_scan", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<()>());
+    add("cancel_scan", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<()>());
+    add("resume_scan_inflight", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<()>());
+    add("list_stages", CommandSchema::new(&mut generator).optional::<Option<String>>("locale").returns::<Vec<StageInfo>>());
+    add("get_scan_status", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<ScanTask>());
+    add("wait_for_scan", CommandSchema::new(&mut generator)
+        .arg::<String>("taskId")
+        .arg::<u64>("timeoutSecs")
+        .returns::<ScanOutcome>());
+    add("list_scans", CommandSchema::new(&mut generator).returns::<Vec<ScanTask>>());
+    add("delete_scan", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<()>());
+    add("delete_scans", CommandSchema::new(&mut generator).arg::<Vec<String>>("taskIds").returns::<Vec<ScanDeletion>>());
+    add("get_scan_report", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<ScanReport>());
+    add("resolve_target_redirect", CommandSchema::new(&mut generator)
+        .arg::<String>("taskId")
+        .arg::<RedirectDecision>("decision")
+        .returns::<String>());
+
+    add("check_target_changes", CommandSchema::new(&mut generator).arg::<String>("target").returns::<TargetChanges>());
+    add("compare_scans", CommandSchema::new(&mut generator)
+        .arg::<String>("baseTaskId")
+        .arg::<String>("taskId")
+        .returns::<ScanComparison>());
+    add("diff_scans", CommandSchema::new(&mut generator)
+        .arg::<String>("taskIdA")
+        .arg::<String>("taskIdB")
+        .returns::<ScanDiff>());
+
+    // workspace settings
+    add("export_workspace_settings", CommandSchema::new(&mut generator)
+        .arg::<String>("path")
+        .optional::<Option<bool>>("overwrite")
+        .returns::<WrittenFile>());
+    add("import_workspace_settings", CommandSchema::new(&mut generator)
+        .arg::<String>("path")
+        .arg::<WorkspaceImportMode>("mode")
+        .returns::<WorkspaceImportResult>());
+
+    // findings
+    add("get_findings", CommandSchema::new(&mut generator).optional::<Option<FindingFilters>>("filters").returns::<FindingPage>());
+    add("update_finding_status", CommandSchema::new(&mut generator)
+        .arg::<String>("findingId")
+        .optional::<Option<String>>("status")
+        .optional::<Option<bool>>("suppressed")
+        .returns::<ReportSummary>());
+    add("export_findings_csv", CommandSchema::new(&mut generator)
+        .arg::<Vec<String>>("taskIds")
+        .arg::<String>("path")
+        .optional::<Option<CsvExportOptions>>("options")
+        .returns::<WrittenFile>());
+
+    // collaboration
+    add("export_scan_data", CommandSchema::new(&mut generator)
+        .optional::<Option<Vec<String>>>("scanIds")
+        .optional::<Option<bool>>("includeScans")
+        .optional::<Option<bool>>("includeFindings")
+        .optional::<Option<bool>>("includeFindingsOnly")
+        .arg::<bool>("includeAnnotations")
+        .arg::<bool>("includeAssets")
+        .optional::<Option<bool>>("includeBaselines")
+        .optional::<Option<String>>("since")
+        .optional::<Option<String>>("redactionProfile")
+        .returns::<ExportData>());
+    add("export_scan_data_to_file", CommandSchema::new(&mut generator)
+        .arg::<String>("content")
+        .arg::<String>("path")
+        .optional::<Option<bool>>("overwrite")
+        .optional::<Option<String>>("redactionProfile")
+        .optional::<Option<String>>("redactionMappingId")
+        .returns::<WrittenFile>());
+    add("deduplicate_import_data", CommandSchema::new(&mut generator).arg::<ExportData>("data").returns::<DeduplicateResult>());
+    add("import_scan_data", CommandSchema::new(&mut generator)
+        .arg::<ExportData>("data")
+        .arg::<bool>("skipDuplicates")
+        .arg::<String>("mergeStrategy")
+        .optional::<Option<bool>>("dryRun")
+        .optional::<Option<String>>("planToken")
+        .optional::<Option<bool>>("confirmLargeImport")
+        .optional::<Option<u32>>("maxOverwrites")
+        .optional::<Option<f64>>("maxOverwritePercent")
+        .returns::<ImportResult>());
+
+    // dashboard
+    add("get_dashboard_stats", CommandSchema::new(&mut generator)
+        .optional::<Option<String>>("since")
+        .optional::<Option<String>>("until")
+        .returns::<DashboardStats>());
+    add("generate_activity_summary", CommandSchema::new(&mut generator)
+        .optional::<Option<String>>("since")
+        .optional::<Option<String>>("until")
+        .returns::<ActivitySummary>());
+    add("get_check_efficiency", CommandSchema::new(&mut generator)
+        .optional::<Option<String>>("since")
+        .optional::<Option<String>>("until")
+        .returns::<CheckEfficiencyReport>());
+
+    // baseline
+    add("set_baseline", CommandSchema::new(&mut generator).arg::<String>("taskId").returns::<Baseline>());
+    add("clear_baseline", CommandSchema::new(&mut generator).arg::<String>("target").returns::<bool>());
+    add("get_baseline", CommandSchema::new(&mut generator).arg::<String>("target").returns::<Option<Baseline>>());
+
+    // audit
+    add("export_audit_log", CommandSchema::new(&mut generator)
+        .arg::<String>("scanId")
+        .arg::<String>("path")
+        .optional::<Option<bool>>("overwrite")
+        .returns::<WrittenFile>());
+
+    // redaction
+    add("list_redaction_profiles", CommandSchema::new(&mut generator).returns::<Vec<RedactionProfile>>());
+    add("save_redaction_profile", CommandSchema::new(&mut generator).arg::<RedactionProfile>("profile").returns::<()>());
+    add("get_redaction_mapping", CommandSchema::new(&mut generator).arg::<String>("mappingId").returns::<PseudonymMapping>());
+
+    // canary
+    add("lookup_canary", CommandSchema::new(&mut generator).arg::<String>("value").returns::<CanaryRecord>());
+
+    // attack chains
+    add("list_attack_chain_rules", CommandSchema::new(&mut generator).returns::<Vec<ChainRule>>());
+    add("save_attack_chain_rule", CommandSchema::new(&mut generator).arg::<ChainRule>("rule").returns::<()>());
+
+    // logs
+    add("get_scan_logs", CommandSchema::new(&mut generator)
+        .arg::<String>("taskId")
+        .optional::<Option<LogLevel>>("minLevel")
+        .optional::<Option<usize>>("limit")
+        .returns::<Vec<ScanLogEntry>>());
+    add("set_log_level", CommandSchema::new(&mut generator).arg::<LogLevel>("level").returns::<()>());
+
+    // settings
+    add("get_app_settings", CommandSchema::new(&mut generator).returns::<AppSettings>());
+    add("save_app_settings", CommandSchema::new(&mut generator).arg::<AppSettings>("settings").returns::<()>());
+    add("test_proxy_connection", CommandSchema::new(&mut generator)
+        .optional::<Option<ProxySettings>>("proxy")
+        .optional::<Option<String>>("url")
+        .returns::<ProxyTestResult>());
+
+    // report
+    add("generate_report", CommandSchema::new(&mut generator)
+        .arg::<String>("taskId")
+        .arg::<ReportType>("reportType")
+        .arg::<String>("outputPath")
+        .optional::<Option<bool>>("overwrite")
+        .optional::<Option<String>>("compareTaskId")
+        .returns::<Report>());
+    add("get_executive_summary", CommandSchema::new(&mut generator)
+        .arg::<String>("taskId")
+        .returns::<ExecutiveSummary>());
+
+    // storage / schema
+    add("get_storage_status", CommandSchema::new(&mut generator).returns::<StorageStatus>());
+    add("get_api_schema", json!({
+        "input": { "type": "object", "properties": {}, "required": [], "additionalProperties": false },
+        "output": { "type": "object" },
+        "error": { "type": "string" },
+    }));
+
+    let events = json!({
+        "scan-progress": generator.subschema_for::<ScanProgress>(),
+        "scan-log": generator.subschema_for::<ScanLogBatch>(),
+        "scan-finding": generator.subschema_for::<FindingEvent>(),
+        "storage-status": generator.subschema_for::<StorageStatus>(),
+    });
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "RedForge API",
+        "version": API_SCHEMA_VERSION,
+        "commands": commands,
+        "events": events,
+        "definitions": generator.definitions(),
+    })
+}
+
+/// 取得所有 command 與事件的 JSON Schema
+#[tauri::command]
+pub async fn get_api_schema() -> Result<Value, String> {
+    Ok(api_schema())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_committed_snapshot() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schema").join("api-schema.json");
+        let content = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("無法讀取快照 {}: {}", path.display(), e));
+        let committed: Value = serde_json::from_str(&content).unwrap_or_else(|e| panic!("快照格式錯誤 {}: {}", path.display(), e));
+        let current = api_schema();
+
+        if committed == current {
+            return;
+        }
+        if committed["version"] == current["version"] {
+            panic!(
+                "API schema 已變動但版本仍為 {}。請調整 API_SCHEMA_VERSION 後執行 `cargo run --bin api-schema` 並提交快照",
+                API_SCHEMA_VERSION
+            );
+        }
+        panic!(
+            "API schema 快照過期 (快照 {}，目前 {})。請執行 `cargo run --bin api-schema` 並提交快照",
+            committed["version"], API_SCHEMA_VERSION
+        );
+    }
+}
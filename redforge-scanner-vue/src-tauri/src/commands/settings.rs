@@ -0,0 +1,72 @@
+/**
+ * Settings Commands
+ *
+ * 全域設定 (目前為掃描流量的代理) 的讀取與儲存，以及儲存前確認代理可用
+ */
+
+use crate::models::{ProxySettings, ScanErrorKind};
+use crate::scanners::proxy;
+use crate::settings::{self, AppSettings};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 測試代理時預設請求的網址
+const DEFAULT_TEST_URL: &str = "https://example.com/";
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 代理測試結果
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProxyTestResult {
+    pub success: bool,
+    // 測試的代理網址 (不含帳密)
+    pub proxy: String,
+    pub url: String,
+    // 經由代理取得的 HTTP 狀態碼
+    #[serde(default)]
+    pub status: Option<u16>,
+    pub elapsed_ms: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub error_kind: Option<ScanErrorKind>,
+}
+
+/// 目前的全域設定；代理密碼不會回傳
+#[tauri::command]
+pub async fn get_app_settings() -> Result<AppSettings, String> {
+    settings::load()
+}
+
+/// 儲存全域設定，之後開始的掃描立即套用；代理密碼留空且代理與帳號沒有變動時保留原本的密碼
+#[tauri::command]
+pub async fn save_app_settings(settings: AppSettings) -> Result<(), String> {
+    let proxy = settings.proxy.as_ref().map(proxy::display_url);
+    settings::save(settings)?;
+    match proxy {
+        Some(proxy) => tracing::info!("⚙️ 已儲存設定，掃描流量經過代理 {}", proxy),
+        None => tracing::info!("⚙️ 已儲存設定，掃描流量直接連線"),
+    }
+    Ok(())
+}
+
+/// 經由代理請求 url (預設 https://example.com/)，確認代理位址與帳密可用
+///
+/// proxy 未指定時測試已儲存的代理；指定的代理沒有密碼時沿用已儲存的密碼
+#[tauri::command]
+pub async fn test_proxy_connection(proxy: Option<ProxySettings>, url: Option<String>) -> Result<ProxyTestResult, String> {
+    let settings = settings::resolve_proxy(proxy)?.ok_or_else(|| "尚未設定代理".to_string())?;
+    let url = url.unwrap_or_else(|| DEFAULT_TEST_URL.to_string());
+    let started = Instant::now();
+    let result = proxy::probe(&settings, &url, TEST_TIMEOUT).await;
+
+    Ok(ProxyTestResult {
+        success: result.is_ok(),
+        proxy: proxy::display_url(&settings),
+        url,
+        status: result.as_ref().ok().map(|(status, _)| *status),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        error: result.as_ref().err().map(|e| e.to_string()),
+        error_kind: result.as_ref().err().map(|e| e.kind()),
+    })
+}
@@ -0,0 +1,25 @@
+/**
+ * Storage Commands
+ *
+ * 回報資料庫目前的模式 (正常或唯讀記憶體模式) 以及啟動時採取的還原動作
+ */
+
+use crate::commands::scan::ScanState;
+use crate::database::store::{StorageMode, StorageProblem, StorageStatus};
+use tauri::State;
+
+/// 取得資料庫狀態
+#[tauri::command]
+pub async fn get_storage_status(state: State<'_, ScanState>) -> Result<StorageStatus, String> {
+    match &state.storage {
+        Some(monitor) => Ok(monitor.status()),
+        None => Ok(StorageStatus {
+            mode: StorageMode::ReadOnlyFallback,
+            path: None,
+            problem: Some(StorageProblem::Unavailable),
+            message: Some("資料庫未初始化".to_string()),
+            recovery_actions: Vec::new(),
+            restored_from: None,
+        }),
+    }
+}
@@ -0,0 +1,240 @@
+/**
+ * Workspace Settings Commands
+ *
+ * 將團隊共用的設定 (自訂遮蔽設定檔、攻擊鏈規則) 匯出為一個以協作金鑰簽章的 JSON 檔，
+ * 其他成員匯入後即使用相同的設定，不需要伺服器。
+ *
+ * 匯入時先驗證簽章，再逐項比對本機設定並回報結果 (新增、相同、衝突、取代、移除)。
+ * 檔案中無法辨識的區段 (例如其他版本或其他工具加入的 webhook、憑證) 一律不匯入，並列在結果中。
+ */
+
+use crate::attack_chains::{self, ChainRule};
+use crate::keys;
+use crate::output::{write_atomic, WrittenFile};
+use crate::redaction::{self, RedactionProfile};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::path::Path;
+
+const FORMAT: &str = "redforge-workspace-settings/1";
+const REDACTION_PROFILES: &str = "redaction_profiles";
+const ATTACK_CHAIN_RULES: &str = "attack_chain_rules";
+
+/// 工作區設定內容；不包含任何密鑰
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WorkspaceSettings {
+    #[serde(default)]
+    redaction_profiles: Vec<RedactionProfile>,
+    #[serde(default)]
+    attack_chain_rules: Vec<ChainRule>,
+}
+
+/// 簽章的工作區設定檔；signature 對 payload 的 UTF-8 位元組簽章
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedWorkspaceSettings {
+    format: String,
+    exported_at: String,
+    algorithm: String,
+    public_key: String,
+    signature: String,
+    payload: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceImportMode {
+    /// 只新增本機沒有的項目，內容不同的項目保留本機版本並回報衝突
+    Merge,
+    /// 以檔案內容取代本機的自訂項目，檔案中沒有的本機項目會被移除
+    Replace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceImportResult {
+    pub mode: WorkspaceImportMode,
+    /// 簽章者的公鑰 (base64)
+    pub signer: String,
+    /// 由本機的協作金鑰簽章
+    pub signed_by_this_machine: bool,
+    pub exported_at: String,
+    pub items: Vec<WorkspaceItem>,
+    /// 無法辨識而未匯入的區段
+    pub ignored_sections: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceItem {
+    pub section: String,
+    /// 設定檔名稱或規則 id
+    pub id: String,
+    pub outcome: WorkspaceItemOutcome,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceItemOutcome {
+    Added,
+    Unchanged,
+    /// 內容與本機不同，保留本機版本 (merge)
+    Conflict,
+    /// 內容與本機不同，已改用檔案版本 (replace)
+    Replaced,
+    /// 本機有、檔案中沒有，已移除 (replace)
+    Removed,
+    /// 檔案中的項目無效，未匯入
+    Invalid,
+}
+
+/// 匯出工作區設定到 path；目的地已存在時改用編號檔名，除非指定 overwrite
+#[tauri::command]
+pub async fn export_workspace_settings(path: String, overwrite: Option<bool>) -> Result<WrittenFile, String> {
+    let settings = WorkspaceSettings {
+        redaction_profiles: redaction::load_custom_profiles()?,
+        attack_chain_rules: attack_chains::load_custom_rules()?,
+    };
+    let payload = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let signature = keys::sign(payload.as_bytes())?;
+
+    let signed = SignedWorkspaceSettings {
+        format: FORMAT.to_string(),
+        exported_at: Utc::now().to_rfc3339(),
+        algorithm: signature.algorithm.to_string(),
+        public_key: signature.public_key,
+        signature: signature.signature,
+        payload,
+    };
+    let content = serde_json::to_string_pretty(&signed).map_err(|e| e.to_string())?;
+
+    let written = write_atomic(Path::new(&path), content.as_bytes(), overwrite.unwrap_or(false))?;
+    tracing::info!(
+        "🗂️  已匯出工作區設定 ({} 個遮蔽設定檔、{} 條攻擊鏈規則) 到 {}",
+        settings.redaction_profiles.len(),
+        settings.attack_chain_rules.len(),
+        written.path
+    );
+    Ok(written)
+}
+
+/// 匯入工作區設定；簽章無效或格式不符時不做任何變更
+#[tauri::command]
+pub async fn import_workspace_settings(path: String, mode: WorkspaceImportMode) -> Result<WorkspaceImportResult, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("無法讀取工作區設定檔 {}: {}", path, e))?;
+    let signed: SignedWorkspaceSettings =
+        serde_json::from_str(&content).map_err(|e| format!("工作區設定檔格式無效: {}", e))?;
+    if signed.format != FORMAT {
+        return Err(format!("不支援的工作區設定格式: {}", signed.format));
+    }
+    if signed.algorithm != "Ed25519" {
+        return Err(format!("不支援的簽章演算法: {}", signed.algorithm));
+    }
+    keys::verify(&signed.public_key, signed.payload.as_bytes(), &signed.signature)?;
+
+    let mut sections: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&signed.payload).map_err(|e| format!("工作區設定內容格式無效: {}", e))?;
+    let ignored_sections: Vec<String> = sections
+        .keys()
+        .filter(|key| *key != REDACTION_PROFILES && *key != ATTACK_CHAIN_RULES)
+        .cloned()
+        .collect();
+    for section in &ignored_sections {
+        sections.remove(section);
+        tracing::warn!("⚠️  工作區設定檔包含未支援的區段 {}，未匯入", section);
+    }
+    let settings: WorkspaceSettings = serde_json::from_value(serde_json::Value::Object(sections))
+        .map_err(|e| format!("工作區設定內容格式無效: {}", e))?;
+
+    let mut items = Vec::new();
+    let profiles = reconcile(
+        REDACTION_PROFILES,
+        mode,
+        redaction::load_custom_profiles()?,
+        settings.redaction_profiles,
+        |p| p.name.clone(),
+        redaction::validate_profile,
+        &mut items,
+    );
+    let rules = reconcile(
+        ATTACK_CHAIN_RULES,
+        mode,
+        attack_chains::load_custom_rules()?,
+        settings.attack_chain_rules,
+        |r| r.id.clone(),
+        attack_chains::validate_rule,
+        &mut items,
+    );
+    redaction::replace_custom_profiles(profiles)?;
+    attack_chains::replace_custom_rules(rules)?;
+
+    let signed_by_this_machine = keys::public_key().is_ok_and(|own| own == signed.public_key);
+    let conflicts = items.iter().filter(|i| i.outcome == WorkspaceItemOutcome::Conflict).count();
+    tracing::info!("🗂️  已匯入工作區設定 {} ({} 項，{} 項衝突)", path, items.len(), conflicts);
+
+    Ok(WorkspaceImportResult {
+        mode,
+        signer: signed.public_key,
+        signed_by_this_machine,
+        exported_at: signed.exported_at,
+        items,
+        ignored_sections,
+    })
+}
+
+/// 比對本機與檔案中的項目，回傳要寫回的本機項目並記錄每一項的結果
+fn reconcile<T: Serialize>(
+    section: &str,
+    mode: WorkspaceImportMode,
+    mut local: Vec<T>,
+    incoming: Vec<T>,
+    key: impl Fn(&T) -> String,
+    validate: impl Fn(&T) -> Result<(), String>,
+    items: &mut Vec<WorkspaceItem>,
+) -> Vec<T> {
+    let mut record = |id: String, outcome, detail: Option<String>| {
+        items.push(WorkspaceItem { section: section.to_string(), id, outcome, detail });
+    };
+    let mut imported_ids = Vec::new();
+
+    for item in incoming {
+        let id = key(&item);
+        // 無效的項目不取代也不移除同名的本機項目
+        imported_ids.push(id.clone());
+        if let Err(e) = validate(&item) {
+            record(id, WorkspaceItemOutcome::Invalid, Some(e));
+            continue;
+        }
+
+        match local.iter().position(|existing| key(existing) == id) {
+            None => {
+                local.push(item);
+                record(id, WorkspaceItemOutcome::Added, None);
+            }
+            Some(index) if same(&local[index], &item) => record(id, WorkspaceItemOutcome::Unchanged, None),
+            Some(_) if mode == WorkspaceImportMode::Merge => {
+                record(id, WorkspaceItemOutcome::Conflict, Some("內容與本機設定不同，保留本機版本".to_string()));
+            }
+            Some(index) => {
+                local[index] = item;
+                record(id, WorkspaceItemOutcome::Replaced, None);
+            }
+        }
+    }
+
+    if mode == WorkspaceImportMode::Replace {
+        local.retain(|existing| {
+            let id = key(existing);
+            let keep = imported_ids.contains(&id);
+            if !keep {
+                record(id, WorkspaceItemOutcome::Removed, None);
+            }
+            keep
+        });
+    }
+    local
+}
+
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
@@ -0,0 +1,156 @@
+/**
+ * CSV Export
+ *
+ * 產生給試算表 (Excel) 開啟的 CSV：RFC 4180 格式，以 CRLF 換行，開頭加上 UTF-8 BOM 讓 Excel 正確辨識中文。
+ *
+ * 含逗號、雙引號或換行的欄位以雙引號包住，內部的雙引號重複一次。發現的標題、說明與證據可能來自目標的回應，
+ * 以 = + - @ 等字元開頭的欄位會被 Excel 當成公式執行，因此這些自由文字欄位前面加上單引號 (CSV injection)。
+ * id、CVSS 等欄位由掃描器產生，維持原值 (例如負數 -1 不會變成 '-1)。
+ */
+
+/// Excel 依此判斷檔案為 UTF-8
+const BOM: &str = "\u{feff}";
+/// 欄位開頭為這些字元時會被試算表解讀為公式
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// 單一欄位：需要時以雙引號包住
+pub fn escape_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 自由文字欄位：公式開頭加上單引號，再依 escape_field 處理
+pub fn escape_text(value: &str) -> String {
+    if value.starts_with(FORMULA_PREFIXES) {
+        escape_field(&format!("'{}", value))
+    } else {
+        escape_field(value)
+    }
+}
+
+/// 標題列與資料列組成的完整檔案內容；text_columns 中的欄位 (依標題名稱) 以 escape_text 處理
+pub fn render(header: &[&str], rows: &[Vec<String>], text_columns: &[&str]) -> String {
+    let text: Vec<bool> = header.iter().map(|name| text_columns.contains(name)).collect();
+    let mut csv = String::from(BOM);
+    push_row(&mut csv, header.iter().map(|name| escape_field(name)));
+    for row in rows {
+        push_row(
+            &mut csv,
+            row.iter().enumerate().map(|(i, value)| {
+                if text.get(i).copied().unwrap_or(false) { escape_text(value) } else { escape_field(value) }
+            }),
+        );
+    }
+    csv
+}
+
+fn push_row(csv: &mut String, fields: impl Iterator<Item = String>) {
+    let fields: Vec<String> = fields.collect();
+    csv.push_str(&fields.join(","));
+    csv.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 依 RFC 4180 解析，驗證輸出可以還原
+    fn parse(csv: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = csv.trim_start_matches(BOM).chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => row.push(std::mem::take(&mut field)),
+                (false, '\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn round_trips_commas_quotes_and_newlines() {
+        let values = [
+            "plain",
+            "",
+            "a, b",
+            "say \"hi\"",
+            "\"",
+            "line 1\nline 2",
+            "windows\r\nline",
+            "mixed, \"quoted\"\r\nand\nnewlines,",
+            "反射型 XSS: <script>alert(\"1\")</script>",
+            "trailing comma,",
+        ];
+        let rows: Vec<Vec<String>> = values.iter().map(|value| vec![value.to_string(), "next".to_string()]).collect();
+        let parsed = parse(&render(&["value", "other"], &rows, &["value"]));
+
+        assert_eq!(parsed[0], vec!["value", "other"]);
+        for (value, row) in values.iter().zip(&parsed[1..]) {
+            assert_eq!(row, &vec![value.to_string(), "next".to_string()]);
+        }
+        assert_eq!(parsed.len(), values.len() + 1);
+    }
+
+    #[test]
+    fn quotes_only_when_needed() {
+        assert_eq!(escape_field("CWE-89"), "CWE-89");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("a\"b"), "\"a\"\"b\"");
+        assert_eq!(escape_field("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn neutralizes_formulas() {
+        assert_eq!(escape_text("=HYPERLINK(\"http://evil\")"), "\"'=HYPERLINK(\"\"http://evil\"\")\"");
+        assert_eq!(escape_text("+1"), "'+1");
+        assert_eq!(escape_text("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape_text("-2+3"), "'-2+3");
+
+        let parsed = parse(&render(&["value"], &[vec!["=1+1, x".to_string()]], &["value"]));
+        assert_eq!(parsed[1], vec!["'=1+1, x"]);
+    }
+
+    #[test]
+    fn guards_only_free_text_columns() {
+        let header = ["id", "title", "cvss"];
+        let rows = vec![
+            vec!["=cmd".to_string(), "=cmd".to_string(), "-1".to_string()],
+            vec!["-1".to_string(), "-1".to_string(), "@SUM".to_string()],
+            vec!["@SUM".to_string(), "@SUM".to_string(), "-- comment injection".to_string()],
+        ];
+        let csv = render(&header, &rows, &["title"]);
+        assert_eq!(
+            csv,
+            "\u{feff}id,title,cvss\r\n=cmd,'=cmd,-1\r\n-1,'-1,@SUM\r\n@SUM,'@SUM,-- comment injection\r\n"
+        );
+
+        let parsed = parse(&csv);
+        assert_eq!(parsed[1], vec!["=cmd", "'=cmd", "-1"]);
+        assert_eq!(parsed[2], vec!["-1", "'-1", "@SUM"]);
+        assert_eq!(parsed[3], vec!["@SUM", "'@SUM", "-- comment injection"]);
+    }
+
+    #[test]
+    fn starts_with_bom_and_uses_crlf() {
+        let csv = render(&["a", "b"], &[vec!["1".to_string(), "2".to_string()]], &[]);
+        assert_eq!(csv, "\u{feff}a,b\r\n1,2\r\n");
+    }
+}
@@ -0,0 +1,133 @@
+/**
+ * CWE Taxonomy
+ *
+ * 每個檢查宣告其主要的 CWE (例如 SQL 注入 → CWE-89、未強制 HTTPS → CWE-319)，
+ * 掃描完成時依發現的 check id 標記 cwe_ids，供匯出、發現查詢與報告的 CWE 統計使用。
+ *
+ * 沒有登錄的檢查不標記 CWE；匯入的發現只採用來源資料中明確寫出的 CWE 編號，不從標題猜測。
+ */
+
+use crate::attack_chains::finding_check_id;
+use crate::models::ScanResult;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 檢查 id → CWE；id 與 raw_data 中的 check_id (或由 type 轉換的 id) 相同
+const CHECK_CWES: &[(&str, &[&str])] = &[
+    ("sql_injection", &["CWE-89"]),
+    ("command_injection", &["CWE-78"]),
+    ("ldap_injection", &["CWE-90"]),
+    ("xss", &["CWE-79"]),
+    ("reflected_parameter", &["CWE-79"]),
+    ("path_traversal", &["CWE-22"]),
+    ("open_redirect", &["CWE-601"]),
+    ("ssrf", &["CWE-918"]),
+    ("idor", &["CWE-639"]),
+    ("user_enumeration", &["CWE-204"]),
+    ("no_rate_limiting", &["CWE-307"]),
+    ("weak_password_policy", &["CWE-521"]),
+    ("default_credentials_check", &["CWE-1392"]),
+    ("missing_csrf_token", &["CWE-352"]),
+    ("missing_sri", &["CWE-353"]),
+    ("insecure_resource_loading", &["CWE-319"]),
+    ("missing_hsts", &["CWE-319"]),
+    ("hsts_disabled", &["CWE-319"]),
+    ("hsts_invalid", &["CWE-319"]),
+    ("weak_cipher", &["CWE-327"]),
+    ("csp_unsafe_inline", &["CWE-79"]),
+    ("csp_unsafe_eval", &["CWE-95"]),
+    ("csp_data_script", &["CWE-79"]),
+    ("csp_wildcard_source", &["CWE-693"]),
+    ("csp_missing_script_src", &["CWE-693"]),
+    ("csp_missing_object_src", &["CWE-693"]),
+    ("csp_missing_base_uri", &["CWE-693"]),
+    ("csp_report_only", &["CWE-693"]),
+    ("information_disclosure", &["CWE-200"]),
+    ("directory_listing", &["CWE-548"]),
+    ("autocomplete", &["CWE-525"]),
+    ("logging_best_practices", &["CWE-778"]),
+    ("upload_exposure", &["CWE-434"]),
+    ("upload_html", &["CWE-434", "CWE-79"]),
+    ("http_trace_enabled", &["CWE-693"]),
+    ("http_put_writable", &["CWE-650"]),
+    ("http_delete_enabled", &["CWE-650"]),
+    ("http_patch_unauthenticated", &["CWE-306"]),
+    ("cors_reflected_origin", &["CWE-942"]),
+    ("cors_null_origin", &["CWE-942"]),
+    ("cors_wildcard_origin", &["CWE-942"]),
+    ("cors_allow_credentials", &["CWE-942"]),
+    ("cookie_missing_samesite", &["CWE-1275"]),
+    ("cookie_samesite_none_insecure", &["CWE-1275"]),
+    ("cookie_missing_secure", &["CWE-614"]),
+    ("cookie_missing_httponly", &["CWE-1004"]),
+    ("cookie_long_expiry", &["CWE-613"]),
+    ("proxy_disagreement", &["CWE-444"]),
+];
+
+/// CWE → 報告中的修補建議；同一個 CWE 的發現共用同一段建議
+const REMEDIATIONS: &[(&str, &str)] = &[
+    ("CWE-22", "以白名單限制可存取的檔案，正規化路徑後確認仍位於允許的目錄內，不直接以使用者輸入組成檔案路徑"),
+    ("CWE-78", "避免將使用者輸入傳給 shell；改用不經 shell 的 API 並以參數陣列傳遞，必要時以白名單驗證輸入"),
+    ("CWE-79", "依輸出語境 (HTML 內容、屬性、JavaScript、URL) 編碼使用者輸入，並以 Content-Security-Policy 限制腳本來源"),
+    ("CWE-89", "改用參數化查詢或 ORM 的繫結參數，不以字串串接 SQL；資料庫帳號只給予必要權限"),
+    ("CWE-90", "跳脫 LDAP 篩選條件中的特殊字元，或以白名單驗證輸入後再組成查詢"),
+    ("CWE-95", "移除 CSP 中的 'unsafe-eval'，避免使用 eval、new Function 與字串形式的 setTimeout"),
+    ("CWE-200", "移除回應中的版本、堆疊追蹤與內部路徑等資訊，正式環境關閉除錯輸出"),
+    ("CWE-204", "登入、註冊與重設密碼對存在與不存在的帳號回傳相同的訊息、狀態碼與回應時間"),
+    ("CWE-306", "修改資料的請求必須先驗證身分，並在伺服器端檢查權限"),
+    ("CWE-307", "對登入等驗證端點限制嘗試次數 (依帳號與來源 IP)，連續失敗時延遲回應或要求額外驗證"),
+    ("CWE-319", "全站使用 HTTPS，將 HTTP 重新導向到 HTTPS 並設置 Strict-Transport-Security，頁面不載入 HTTP 資源"),
+    ("CWE-327", "停用 RC4、3DES 等弱加密套件與 TLS 1.0/1.1，只啟用支援前向保密的 AEAD 套件"),
+    ("CWE-352", "狀態變更的表單與 API 驗證 CSRF token，並將 session cookie 設為 SameSite=Lax 或 Strict"),
+    ("CWE-353", "載入第三方 CDN 的腳本與樣式時加上 integrity 與 crossorigin 屬性"),
+    ("CWE-434", "以白名單限制上傳檔案類型，存放在網站根目錄外或以 Content-Disposition: attachment 提供下載，不直接以上傳的檔名存放"),
+    ("CWE-444", "前端代理與後端伺服器使用一致的請求解析方式，拒絕同時帶有 Content-Length 與 Transfer-Encoding 等模稜兩可的請求"),
+    ("CWE-521", "要求足夠長度的密碼並比對常見或外洩密碼清單，不只依賴字元種類規則"),
+    ("CWE-525", "在密碼等敏感欄位加上 autocomplete=\"off\" 或適當的 autocomplete 值，並以 Cache-Control: no-store 避免快取敏感頁面"),
+    ("CWE-548", "關閉網頁伺服器的目錄列表功能，並確認目錄中沒有不應公開的檔案"),
+    ("CWE-601", "重新導向的目的地只接受相對路徑或白名單中的網域，不直接使用參數中的 URL"),
+    ("CWE-613", "縮短 session 與驗證 cookie 的有效期限，並在伺服器端設定逾時與登出時撤銷"),
+    ("CWE-614", "為 session 與驗證 cookie 加上 Secure 屬性"),
+    ("CWE-639", "每個物件存取都在伺服器端檢查目前使用者是否有權限，不依賴不可猜測的 id"),
+    ("CWE-650", "停用不需要的 HTTP 方法 (PUT、DELETE)，或要求驗證並檢查權限"),
+    ("CWE-693", "依建議補齊安全標頭與 CSP 指令，並停用 TRACE 等不需要的功能"),
+    ("CWE-778", "記錄驗證失敗、權限拒絕等安全事件，並避免在記錄中寫入密碼或 token"),
+    ("CWE-918", "伺服器端請求的目的地以白名單限制，拒絕內部位址與非 HTTP(S) 協定，並在解析 DNS 後再次檢查"),
+    ("CWE-942", "CORS 只允許白名單中的來源，不反射 Origin 或允許 null；需要 credentials 時不可使用萬用字元"),
+    ("CWE-1004", "為 session 與驗證 cookie 加上 HttpOnly 屬性"),
+    ("CWE-1275", "明確設置 SameSite=Lax 或 Strict；需要跨站使用的 cookie 設為 SameSite=None 時必須同時加上 Secure"),
+    ("CWE-1392", "移除或變更預設帳號密碼，首次登入時強制更換密碼"),
+];
+
+/// CWE 的修補建議；沒有登錄時為 None
+pub fn remediation(cwe: &str) -> Option<&'static str> {
+    REMEDIATIONS.iter().find(|(id, _)| *id == cwe).map(|(_, text)| *text)
+}
+
+/// 檢查宣告的 CWE；未登錄時為空
+pub fn for_check(check_id: &str) -> Vec<String> {
+    CHECK_CWES
+        .iter()
+        .find(|(id, _)| *id == check_id)
+        .map(|(_, cwes)| cwes.iter().map(|cwe| cwe.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// 發現的 CWE，由其 check id 決定
+pub fn for_finding(finding: &ScanResult) -> Vec<String> {
+    finding_check_id(finding).map(|check_id| for_check(&check_id)).unwrap_or_default()
+}
+
+/// 從外部工具的分類文字 (例如 "CWE-89"、"cwe-79, cwe-80") 取出 CWE；沒有明確的編號時為空
+pub fn from_classification(text: &str) -> Vec<String> {
+    static CWE: OnceLock<Regex> = OnceLock::new();
+    let re = CWE.get_or_init(|| Regex::new(r"(?i)\bCWE[-_ :]?(\d{1,4})\b").unwrap());
+    let mut cwes: Vec<String> = Vec::new();
+    for capture in re.captures_iter(text) {
+        let cwe = format!("CWE-{}", capture[1].trim_start_matches('0'));
+        if !cwes.contains(&cwe) {
+            cwes.push(cwe);
+        }
+    }
+    cwes
+}
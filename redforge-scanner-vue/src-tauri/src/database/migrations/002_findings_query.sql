@@ -0,0 +1,64 @@
+-- RedForge Scanner Database Schema v1.1
+-- Cross-scan finding queries: triage status, check id, suppression, target hostname and project
+
+-- =============================================================================
+-- Scan Tasks: hostname and project
+-- =============================================================================
+ALTER TABLE scan_tasks ADD COLUMN hostname TEXT;
+ALTER TABLE scan_tasks ADD COLUMN project TEXT;
+
+-- Host part of target_url: strip the scheme, the path and a port (bracketed IPv6 hosts keep their brackets)
+UPDATE scan_tasks SET hostname = lower(
+    CASE
+        WHEN instr(rest, ':') > 0 AND substr(rest, 1, 1) != '[' THEN substr(rest, 1, instr(rest, ':') - 1)
+        ELSE rest
+    END
+)
+FROM (
+    SELECT id AS task_id,
+        CASE
+            WHEN instr(after_scheme, '/') > 0 THEN substr(after_scheme, 1, instr(after_scheme, '/') - 1)
+            ELSE after_scheme
+        END AS rest
+    FROM (SELECT id, substr(target_url, instr(target_url, '://') + 3) AS after_scheme FROM scan_tasks)
+) AS parsed
+WHERE scan_tasks.id = parsed.task_id;
+
+-- Tasks inserted by clients that do not set hostname get it derived the same way
+CREATE TRIGGER IF NOT EXISTS trg_scan_tasks_hostname
+AFTER INSERT ON scan_tasks
+WHEN NEW.hostname IS NULL
+BEGIN
+    UPDATE scan_tasks SET hostname = (
+        SELECT lower(
+            CASE
+                WHEN instr(rest, ':') > 0 AND substr(rest, 1, 1) != '[' THEN substr(rest, 1, instr(rest, ':') - 1)
+                ELSE rest
+            END
+        )
+        FROM (
+            SELECT CASE
+                WHEN instr(after_scheme, '/') > 0 THEN substr(after_scheme, 1, instr(after_scheme, '/') - 1)
+                ELSE after_scheme
+            END AS rest
+            FROM (SELECT substr(NEW.target_url, instr(NEW.target_url, '://') + 3) AS after_scheme)
+        )
+    )
+    WHERE id = NEW.id;
+END;
+
+CREATE INDEX IF NOT EXISTS idx_scan_tasks_hostname ON scan_tasks(hostname);
+CREATE INDEX IF NOT EXISTS idx_scan_tasks_project ON scan_tasks(project);
+
+-- =============================================================================
+-- Scan Results: triage status, check id and suppression
+-- =============================================================================
+ALTER TABLE scan_results ADD COLUMN status TEXT NOT NULL DEFAULT 'open'
+    CHECK(status IN ('open', 'confirmed', 'fixed', 'accepted_risk', 'false_positive'));
+ALTER TABLE scan_results ADD COLUMN check_id TEXT;
+ALTER TABLE scan_results ADD COLUMN suppressed INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_scan_results_severity_status ON scan_results(severity, status, suppressed);
+CREATE INDEX IF NOT EXISTS idx_scan_results_status ON scan_results(status);
+CREATE INDEX IF NOT EXISTS idx_scan_results_check_id ON scan_results(check_id);
+CREATE INDEX IF NOT EXISTS idx_scan_results_created_at ON scan_results(created_at DESC);
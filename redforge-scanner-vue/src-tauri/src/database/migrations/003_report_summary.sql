@@ -0,0 +1,5 @@
+-- RedForge Scanner Database Schema v1.2
+-- Canonical report summary (counts by severity, check, OWASP category, status and confidence)
+-- computed at scan completion and after triage changes; consumers read it instead of recounting
+
+ALTER TABLE scan_tasks ADD COLUMN summary TEXT;
@@ -0,0 +1,5 @@
+-- RedForge Scanner Database Schema v1.3
+-- Per-check request, timeout, finding and duration counts of each scan (JSON array),
+-- aggregated across scans to find checks that spend requests without producing findings
+
+ALTER TABLE scan_tasks ADD COLUMN check_metrics TEXT;
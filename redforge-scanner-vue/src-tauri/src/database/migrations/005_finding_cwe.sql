@@ -0,0 +1,4 @@
+-- RedForge Scanner Database Schema v1.4
+-- CWE ids of each finding (JSON array, e.g. ["CWE-89"]) from the check registry or the imported source
+
+ALTER TABLE scan_results ADD COLUMN cwe_ids TEXT NOT NULL DEFAULT '[]';
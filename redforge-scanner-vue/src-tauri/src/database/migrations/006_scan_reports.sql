@@ -0,0 +1,8 @@
+-- RedForge Scanner Database Schema v1.5
+-- Scans persisted by the backend: the exact task (status, error, redirect) and the full report as JSON,
+-- loaded back at startup so scan history and reports survive an app restart.
+-- The status column keeps its original CHECK: paused is stored as running and cancelled as failed,
+-- task_state holds the exact status
+
+ALTER TABLE scan_tasks ADD COLUMN task_state TEXT;
+ALTER TABLE scan_tasks ADD COLUMN report TEXT;
@@ -0,0 +1,12 @@
+-- RedForge Scanner Database Schema v1.6
+-- Affected URL of each finding, so persisted findings are fingerprinted by the same location as
+-- in-memory and imported ones. Existing rows are filled from the stored report; NULL means the scan target
+
+ALTER TABLE scan_results ADD COLUMN affected_url TEXT;
+
+UPDATE scan_results SET affected_url = (
+    SELECT json_extract(finding.value, '$.details.affected_url')
+    FROM scan_tasks t, json_each(t.report, '$.vulnerabilities') AS finding
+    WHERE t.id = scan_results.task_id
+      AND json_extract(finding.value, '$.id') = scan_results.id
+);
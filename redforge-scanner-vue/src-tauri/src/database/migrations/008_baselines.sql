@@ -0,0 +1,10 @@
+-- RedForge Scanner Database Schema v1.7
+-- Approved baseline of each target (findings, secure headers, TLS grade) as JSON, keyed by the
+-- normalized target URL, so "new since baseline" comparisons survive an app restart
+
+CREATE TABLE IF NOT EXISTS baselines (
+    target TEXT PRIMARY KEY,
+    source_task_id TEXT NOT NULL,
+    baseline TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
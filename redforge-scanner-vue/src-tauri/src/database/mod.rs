@@ -5,11 +5,14 @@
  * Database operations are performed from frontend using tauri-plugin-sql
  */
 
+pub mod store;
+
 use tauri_plugin_sql::{Migration, MigrationKind};
 
 /// Get database migrations
 ///
-/// Returns migrations to be executed on database initialization
+/// Returns migrations to be executed on database initialization. The backend applies them when it
+/// opens the database at startup; tauri-plugin-sql finds them recorded and skips them
 pub fn get_migrations() -> Vec<Migration> {
     vec![
         // Migration 1: Create initial tables
@@ -19,5 +22,54 @@ pub fn get_migrations() -> Vec<Migration> {
             sql: include_str!("migrations/001_create_initial_tables.sql"),
             kind: MigrationKind::Up,
         },
+        // Migration 2: Finding triage columns and indices for cross-scan queries
+        Migration {
+            version: 2,
+            description: "findings_query",
+            sql: include_str!("migrations/002_findings_query.sql"),
+            kind: MigrationKind::Up,
+        },
+        // Migration 3: Canonical report summary on scan tasks
+        Migration {
+            version: 3,
+            description: "report_summary",
+            sql: include_str!("migrations/003_report_summary.sql"),
+            kind: MigrationKind::Up,
+        },
+        // Migration 4: Per-check execution metrics on scan tasks
+        Migration {
+            version: 4,
+            description: "check_metrics",
+            sql: include_str!("migrations/004_check_metrics.sql"),
+            kind: MigrationKind::Up,
+        },
+        // Migration 5: CWE ids on findings
+        Migration {
+            version: 5,
+            description: "finding_cwe",
+            sql: include_str!("migrations/005_finding_cwe.sql"),
+            kind: MigrationKind::Up,
+        },
+        // Migration 6: Scan tasks and reports persisted by the backend
+        Migration {
+            version: 6,
+            description: "scan_reports",
+            sql: include_str!("migrations/006_scan_reports.sql"),
+            kind: MigrationKind::Up,
+        },
+        // Migration 7: Affected URL on findings
+        Migration {
+            version: 7,
+            description: "finding_location",
+            sql: include_str!("migrations/007_finding_location.sql"),
+            kind: MigrationKind::Up,
+        },
+        // Migration 8: Approved target baselines
+        Migration {
+            version: 8,
+            description: "baselines",
+            sql: include_str!("migrations/008_baselines.sql"),
+            kind: MigrationKind::Up,
+        },
     ]
 }
@@ -0,0 +1,1378 @@
+/**
+ * Persistent Store
+ *
+ * Backend access to redforge.db, the same SQLite file the frontend writes through tauri-plugin-sql.
+ * The backend applies the plugin migrations itself when it opens the database, before anything is loaded,
+ * and records them in `_sqlx_migrations` exactly as the plugin does; the plugin then finds them applied.
+ * Scans are written here as they run (task rows, then the full report when they finish) and loaded
+ * back into memory at startup.
+ *
+ * A locked or corrupted database does not stop the app: startup falls back to a read-only in-memory
+ * mode (scans work, history is unavailable), restoring the latest backup first when one exists.
+ */
+
+use crate::attack_chains::finding_check_id;
+use crate::commands::baseline::Baseline;
+use crate::commands::dashboard::{CheckCount, DashboardStats, ExpiringCertificate, TargetRisk, CERT_EXPIRY_WINDOW_DAYS};
+use crate::commands::scan::ScanReport;
+use crate::models::{ScanErrorKind, ScanStatus, ScanTask};
+use crate::scanners::check_metrics::CheckMetrics;
+use crate::summary::{owasp_category, ReportSummary, SummaryInput};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use sqlx::error::BoxDynError;
+use sqlx::migrate::{MigrateError, Migration, MigrationSource, Migrator};
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::{QueryBuilder, Row};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Emitter;
+
+pub const DATABASE_FILE: &str = "redforge.db";
+const BACKUP_DIR: &str = "backups";
+const BACKUPS_KEPT: usize = 5;
+const BACKUP_INTERVAL_HOURS: i64 = 24;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+const FINDINGS_DEFAULT_LIMIT: u32 = 50;
+const FINDINGS_MAX_LIMIT: u32 = 500;
+const SEVERITIES: [&str; 5] = ["critical", "high", "medium", "low", "info"];
+const FINDING_STATUSES: [&str; 5] = ["open", "confirmed", "fixed", "accepted_risk", "false_positive"];
+
+/// Scan tasks created in [?1, ?2); either bound may be NULL
+const IN_RANGE: &str = "(?1 IS NULL OR julianday(created_at) >= julianday(?1)) AND (?2 IS NULL OR julianday(created_at) < julianday(?2))";
+/// The latest completed scan of each target created in [?1, ?2); findings of older scans are superseded
+const LATEST_COMPLETED: &str = "WITH latest AS (
+    SELECT id, target_url, report FROM (
+        SELECT id, target_url, report,
+            ROW_NUMBER() OVER (PARTITION BY target_url ORDER BY julianday(created_at) DESC) AS position
+        FROM scan_tasks
+        WHERE COALESCE(json_extract(task_state, '$.status'), status) = 'completed'
+          AND (?1 IS NULL OR julianday(created_at) >= julianday(?1))
+          AND (?2 IS NULL OR julianday(created_at) < julianday(?2))
+    ) WHERE position = 1
+)";
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Classified storage failure
+#[derive(Debug)]
+pub enum StoreError {
+    Locked(String),
+    Corrupted(String),
+    DiskFull(String),
+    /// Storage was disabled earlier in the session
+    Unavailable(String),
+    Other(sqlx::Error),
+}
+
+impl std::fmt::Display for StoreError {
+    fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StoreError::Locked(msg) => write!(f, "資料庫被其他程式鎖定: {}", msg),
+            StoreError::Corrupted(msg) => write!(f, "資料庫已損毀: {}", msg),
+            StoreError::DiskFull(msg) => write!(f, "磁碟空間不足: {}", msg),
+            StoreError::Unavailable(msg) => write!(f, "資料庫無法使用: {}", msg),
+            StoreError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(error: sqlx::Error) -> Self {
+        let (code, message) = match &error {
+            sqlx::Error::Database(e) => (
+                e.code().and_then(|c| c.parse::<i32>().ok()).map(|c| c & 0xff),
+                e.message().to_string(),
+            ),
+            other => (None, other.to_string()),
+        };
+        let lower = message.to_lowercase();
+
+        // SQLite primary result codes: BUSY 5, LOCKED 6, CORRUPT 11, FULL 13, NOTADB 26
+        match code {
+            Some(5) | Some(6) => StoreError::Locked(message),
+            Some(11) | Some(26) => StoreError::Corrupted(message),
+            Some(13) => StoreError::DiskFull(message),
+            _ if lower.contains("database is locked") => StoreError::Locked(message),
+            _ if lower.contains("malformed") || lower.contains("not a database") => StoreError::Corrupted(message),
+            _ if lower.contains("disk is full") || lower.contains("no space left") => StoreError::DiskFull(message),
+            _ => StoreError::Other(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageMode {
+    Persistent,
+    /// Scans keep working in memory, history and persistence are unavailable
+    ReadOnlyFallback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageProblem {
+    Locked,
+    Corrupted,
+    DiskFull,
+    Unavailable,
+}
+
+/// Current storage mode, reported by `get_storage_status` and the `storage-status` event
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StorageStatus {
+    pub mode: StorageMode,
+    pub path: Option<String>,
+    pub problem: Option<StorageProblem>,
+    pub message: Option<String>,
+    /// Recovery steps taken so far, in order
+    pub recovery_actions: Vec<String>,
+    pub restored_from: Option<String>,
+}
+
+impl StorageStatus {
+    fn persistent(path: &Path) -> Self {
+        Self {
+            mode: StorageMode::Persistent,
+            path: Some(path.display().to_string()),
+            problem: None,
+            message: None,
+            recovery_actions: Vec::new(),
+            restored_from: None,
+        }
+    }
+}
+
+/// Shared view of the storage status; flips to fallback when a mid-session failure makes the database unusable
+#[derive(Clone)]
+pub struct StorageMonitor {
+    status: Arc<Mutex<StorageStatus>>,
+    app: Option<tauri::AppHandle>,
+}
+
+impl StorageMonitor {
+    fn new(status: StorageStatus, app: Option<tauri::AppHandle>) -> Self {
+        Self { status: Arc::new(Mutex::new(status)), app }
+    }
+
+    pub fn status(&self) -> StorageStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.status().mode == StorageMode::Persistent
+    }
+
+    /// Announce the current status to the frontend
+    pub fn announce(&self) {
+        if let Some(app) = &self.app {
+            if let Err(e) = app.emit("storage-status", self.status()) {
+                tracing::warn!("⚠️  無法發送資料庫狀態: {}", e);
+            }
+        }
+    }
+
+    fn degrade(&self, problem: StorageProblem, message: String) {
+        {
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            if status.mode == StorageMode::ReadOnlyFallback {
+                return;
+            }
+            tracing::warn!("⚠️  資料庫改為唯讀模式: {}", message);
+            status.mode = StorageMode::ReadOnlyFallback;
+            status.problem = Some(problem);
+            status.recovery_actions.push(format!("切換為唯讀記憶體模式: {}", message));
+            status.message = Some(message);
+        }
+        self.announce();
+    }
+
+    // Corruption and a full disk will not fix themselves mid-session; a lock is retried on the next call
+    fn observe(&self, error: &StoreError) {
+        match error {
+            StoreError::Corrupted(_) => self.degrade(StorageProblem::Corrupted, error.to_string()),
+            StoreError::DiskFull(_) => self.degrade(StorageProblem::DiskFull, error.to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// A finding row as needed for fingerprinting
+#[derive(Debug, Clone)]
+pub struct PersistedFinding {
+    pub result_type: String,
+    pub title: String,
+    pub target_url: String,
+    /// None for findings located at the scan target
+    pub affected_url: Option<String>,
+}
+
+/// A scan task row, as needed for activity statistics
+#[derive(Debug, Clone)]
+pub struct PersistedTask {
+    pub id: String,
+    pub scan_type: String,
+    /// Exact status from task_state when present, so paused and cancelled are not read as running and failed
+    pub status: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+    pub error: Option<String>,
+    pub error_kind: Option<ScanErrorKind>,
+}
+
+/// An entry of the persisted asset inventory
+#[derive(Debug, Clone)]
+pub struct PersistedAsset {
+    pub id: String,
+    pub hostname: String,
+    pub ip_address: Option<String>,
+    pub ports: Vec<u16>,
+    pub services: Vec<String>,
+    pub technologies: Vec<String>,
+    pub discovered_at: String,
+}
+
+/// Filters for `get_findings`; every field is optional and filters combine with AND
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct FindingFilters {
+    /// critical / high / medium / low / info
+    pub severities: Vec<String>,
+    /// open / confirmed / fixed / accepted_risk / false_positive
+    pub statuses: Vec<String>,
+    pub check_ids: Vec<String>,
+    /// Findings tagged with any of these CWE ids (e.g. "CWE-89")
+    pub cwe_ids: Vec<String>,
+    /// Matches the scan hostname exactly, or any part of the target URL
+    pub target: Option<String>,
+    pub project: Option<String>,
+    /// RFC 3339, inclusive
+    pub since: Option<String>,
+    /// RFC 3339, exclusive
+    pub until: Option<String>,
+    /// Case-insensitive match on title and description
+    pub text: Option<String>,
+    /// None returns both suppressed and active findings
+    pub suppressed: Option<bool>,
+    pub sort: FindingSort,
+    pub descending: Option<bool>,
+    pub offset: u32,
+    /// Defaults to 50, capped at 500
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingSort {
+    #[default]
+    Severity,
+    DiscoveredAt,
+    Title,
+    Target,
+}
+
+/// Parent scan of a finding
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FindingScanContext {
+    pub task_id: String,
+    pub target_url: String,
+    pub hostname: Option<String>,
+    pub project: Option<String>,
+    pub scan_type: String,
+    pub scanned_at: String,
+}
+
+/// A persisted finding with its scan context
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FindingRecord {
+    pub id: String,
+    pub result_type: String,
+    pub severity: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub evidence: Option<String>,
+    pub status: String,
+    pub check_id: Option<String>,
+    pub cwe_ids: Vec<String>,
+    pub suppressed: bool,
+    pub discovered_at: String,
+    pub scan: FindingScanContext,
+}
+
+/// One page of `get_findings` results; `total` counts all matches ignoring pagination
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FindingPage {
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+    pub findings: Vec<FindingRecord>,
+}
+
+/// One `query_findings` row; mapped by hand because it is wider than sqlx's tuple `FromRow` impls
+struct FindingRow(FindingRecord);
+
+impl<'r> sqlx::FromRow<'r, SqliteRow> for FindingRow {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let cwe_ids: String = row.try_get(10)?;
+        let suppressed: i64 = row.try_get(11)?;
+        Ok(FindingRow(FindingRecord {
+            id: row.try_get(0)?,
+            result_type: row.try_get(2)?,
+            severity: row.try_get(3)?,
+            title: row.try_get(4)?,
+            description: row.try_get(5)?,
+            evidence: row.try_get(6)?,
+            discovered_at: row.try_get(7)?,
+            status: row.try_get(8)?,
+            check_id: row.try_get(9)?,
+            cwe_ids: serde_json::from_str(&cwe_ids).unwrap_or_default(),
+            suppressed: suppressed != 0,
+            scan: FindingScanContext {
+                task_id: row.try_get(1)?,
+                target_url: row.try_get(12)?,
+                hostname: row.try_get(13)?,
+                project: row.try_get(14)?,
+                scan_type: row.try_get(15)?,
+                scanned_at: row.try_get(16)?,
+            },
+        }))
+    }
+}
+
+impl FindingFilters {
+    /// Reject unknown enum values and malformed dates instead of silently matching nothing
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(bad) = self.severities.iter().find(|s| !SEVERITIES.contains(&s.as_str())) {
+            return Err(format!("未知的嚴重程度: {}", bad));
+        }
+        if let Some(bad) = self.statuses.iter().find(|s| !FINDING_STATUSES.contains(&s.as_str())) {
+            return Err(format!("未知的發現狀態: {}", bad));
+        }
+        for date in [&self.since, &self.until].into_iter().flatten() {
+            DateTime::parse_from_rfc3339(date).map_err(|_| format!("無效的日期 (需為 RFC 3339): {}", date))?;
+        }
+        Ok(())
+    }
+
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(FINDINGS_DEFAULT_LIMIT).clamp(1, FINDINGS_MAX_LIMIT)
+    }
+
+    // Dates are normalised to UTC RFC 3339 so they compare correctly against stored timestamps
+    fn utc(date: &Option<String>) -> Option<String> {
+        date.as_deref()
+            .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+            .map(|d| d.with_timezone(&Utc).to_rfc3339())
+    }
+
+    /// Append the WHERE clause; shared by the page query and the count query
+    fn push_where(&self, query: &mut QueryBuilder<'_, Sqlite>) {
+        query.push(" WHERE 1 = 1");
+
+        let json = |values: &Vec<String>| serde_json::to_string(values).unwrap_or_else(|_| "[]".to_string());
+        if !self.severities.is_empty() {
+            query.push(" AND r.severity IN (SELECT value FROM json_each(").push_bind(json(&self.severities)).push("))");
+        }
+        if !self.statuses.is_empty() {
+            query.push(" AND r.status IN (SELECT value FROM json_each(").push_bind(json(&self.statuses)).push("))");
+        }
+        if !self.check_ids.is_empty() {
+            query.push(" AND r.check_id IN (SELECT value FROM json_each(").push_bind(json(&self.check_ids)).push("))");
+        }
+        if !self.cwe_ids.is_empty() {
+            let cwe_ids: Vec<String> = self.cwe_ids.iter().map(|c| c.to_uppercase()).collect();
+            query
+                .push(" AND EXISTS (SELECT 1 FROM json_each(r.cwe_ids) c WHERE c.value IN (SELECT value FROM json_each(")
+                .push_bind(json(&cwe_ids))
+                .push(")))");
+        }
+        if let Some(target) = self.target.as_deref().filter(|t| !t.is_empty()) {
+            query
+                .push(" AND (t.hostname = ")
+                .push_bind(target.to_lowercase())
+                .push(" OR t.target_url LIKE ")
+                .push_bind(like_pattern(target))
+                .push(" ESCAPE '\\')");
+        }
+        if let Some(project) = &self.project {
+            query.push(" AND t.project = ").push_bind(project.clone());
+        }
+        if let Some(since) = Self::utc(&self.since) {
+            query.push(" AND r.created_at >= ").push_bind(since);
+        }
+        if let Some(until) = Self::utc(&self.until) {
+            query.push(" AND r.created_at < ").push_bind(until);
+        }
+        if let Some(text) = self.text.as_deref().filter(|t| !t.is_empty()) {
+            let pattern = like_pattern(text);
+            query
+                .push(" AND (r.title LIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR r.description LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+        if let Some(suppressed) = self.suppressed {
+            query.push(" AND r.suppressed = ").push_bind(i64::from(suppressed));
+        }
+    }
+
+    fn push_order(&self, query: &mut QueryBuilder<'_, Sqlite>) {
+        // Severity sorts most severe first by default, everything else newest / A-Z first
+        let descending = self.descending.unwrap_or(self.sort == FindingSort::DiscoveredAt);
+        let direction = if descending { "DESC" } else { "ASC" };
+        let column = match self.sort {
+            FindingSort::Severity => {
+                let direction = if self.descending.unwrap_or(true) { "ASC" } else { "DESC" };
+                query.push(format!(
+                    " ORDER BY CASE r.severity WHEN 'critical' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2 \
+                     WHEN 'low' THEN 3 WHEN 'info' THEN 4 ELSE 5 END {}, r.created_at DESC, r.id",
+                    direction
+                ));
+                return;
+            }
+            FindingSort::DiscoveredAt => "r.created_at",
+            FindingSort::Title => "r.title COLLATE NOCASE",
+            FindingSort::Target => "t.target_url",
+        };
+        query.push(format!(" ORDER BY {} {}, r.id", column, direction));
+    }
+}
+
+/// LIKE pattern matching `value` anywhere, with wildcards in the value escaped
+fn like_pattern(value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+#[derive(Clone)]
+pub struct Store {
+    pool: SqlitePool,
+    monitor: StorageMonitor,
+}
+
+impl Store {
+    async fn connect(path: &Path) -> Result<SqlitePool, sqlx::Error> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .busy_timeout(BUSY_TIMEOUT);
+
+        SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await
+    }
+
+    /// Open the database, verify it is readable, writable and structurally sound, and bring its schema up to date
+    async fn open_checked(path: &Path) -> StoreResult<SqlitePool> {
+        let pool = Self::connect(path).await?;
+
+        let check = sqlx::query_scalar::<_, String>("PRAGMA quick_check").fetch_one(&pool).await?;
+        if check != "ok" {
+            pool.close().await;
+            return Err(StoreError::Corrupted(check));
+        }
+
+        // Take and release the write lock so a database held by another process is detected now
+        let mut conn = pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+        drop(conn);
+
+        migrate(&pool, crate::database::get_migrations()).await?;
+        Ok(pool)
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.monitor.is_available()
+    }
+
+    fn ensure_available(&self) -> StoreResult<()> {
+        if self.is_available() {
+            Ok(())
+        } else {
+            Err(StoreError::Unavailable(self.monitor.status().message.unwrap_or_default()))
+        }
+    }
+
+    // Convert and record a query failure so fatal ones switch the session to fallback mode
+    fn checked<T>(&self, result: Result<T, sqlx::Error>) -> StoreResult<T> {
+        result.map_err(|e| {
+            let error = StoreError::from(e);
+            self.monitor.observe(&error);
+            error
+        })
+    }
+
+    /// Returns which of the given scan ids already exist in the database
+    pub async fn existing_scan_ids(&self, ids: &[String]) -> StoreResult<HashSet<String>> {
+        self.existing_values("SELECT id FROM scan_tasks WHERE id IN (SELECT value FROM json_each(?))", ids).await
+    }
+
+    /// Returns which of the given annotation ids already exist in the database
+    pub async fn existing_annotation_ids(&self, ids: &[String]) -> StoreResult<HashSet<String>> {
+        self.existing_values("SELECT id FROM annotations WHERE id IN (SELECT value FROM json_each(?))", ids).await
+    }
+
+    /// Returns which of the given asset hostnames already exist in the database
+    pub async fn existing_asset_hostnames(&self, hostnames: &[String]) -> StoreResult<HashSet<String>> {
+        self.existing_values("SELECT hostname FROM assets WHERE hostname IN (SELECT value FROM json_each(?))", hostnames).await
+    }
+
+    /// All persisted findings with their affected URL and the target of their parent scan
+    pub async fn findings(&self) -> StoreResult<Vec<PersistedFinding>> {
+        self.ensure_available()?;
+        let rows = sqlx::query_as::<_, (String, String, String, Option<String>)>(
+            "SELECT r.result_type, r.title, t.target_url, r.affected_url
+             FROM scan_results r JOIN scan_tasks t ON t.id = r.task_id",
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        Ok(self.checked(rows)?
+            .into_iter()
+            .map(|(result_type, title, target_url, affected_url)| PersistedFinding { result_type, title, target_url, affected_url })
+            .collect())
+    }
+
+    /// All persisted scan tasks
+    pub async fn tasks(&self) -> StoreResult<Vec<PersistedTask>> {
+        self.ensure_available()?;
+        let rows = sqlx::query_as::<_, (String, String, String, Option<String>, Option<String>, String, Option<String>, Option<String>)>(
+            "SELECT id, scan_type, COALESCE(json_extract(task_state, '$.status'), status), started_at, completed_at, created_at,
+                 json_extract(task_state, '$.error'), json_extract(task_state, '$.error_kind')
+             FROM scan_tasks",
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        Ok(self.checked(rows)?
+            .into_iter()
+            .map(|(id, scan_type, status, started_at, completed_at, created_at, error, error_kind)| PersistedTask {
+                id,
+                scan_type,
+                status,
+                started_at,
+                completed_at,
+                created_at,
+                error,
+                error_kind: error_kind.and_then(|kind| serde_json::from_value(serde_json::Value::String(kind)).ok()),
+            })
+            .collect())
+    }
+
+    /// Change the triage status and/or suppression of a finding, returning its scan id
+    ///
+    /// None when the finding does not exist
+    pub async fn update_finding_triage(&self, finding_id: &str, status: Option<&str>, suppressed: Option<bool>) -> StoreResult<Option<String>> {
+        self.ensure_available()?;
+        if let Some(status) = status {
+            if !FINDING_STATUSES.contains(&status) {
+                return Err(StoreError::Other(sqlx::Error::Protocol(format!("unknown finding status: {}", status))));
+            }
+        }
+
+        let result = sqlx::query(
+            "UPDATE scan_results SET status = COALESCE(?, status), suppressed = COALESCE(?, suppressed) WHERE id = ?",
+        )
+        .bind(status)
+        .bind(suppressed.map(i64::from))
+        .bind(finding_id)
+        .execute(&self.pool)
+        .await;
+        if self.checked(result)?.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let task_id = sqlx::query_scalar::<_, String>("SELECT task_id FROM scan_results WHERE id = ?")
+            .bind(finding_id)
+            .fetch_optional(&self.pool)
+            .await;
+        self.checked(task_id)
+    }
+
+    /// Summary inputs for every persisted finding of a scan, keyed by finding id
+    ///
+    /// Confidence is not persisted, so every finding reads as normal confidence
+    pub async fn summary_inputs(&self, task_id: &str) -> StoreResult<Vec<(String, SummaryInput)>> {
+        self.ensure_available()?;
+        let rows = sqlx::query_as::<_, (String, Option<String>, Option<String>, Option<String>, String, String, i64)>(
+            "SELECT id, severity, check_id, raw_data, cwe_ids, status, suppressed FROM scan_results WHERE task_id = ?",
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await;
+
+        Ok(self.checked(rows)?
+            .into_iter()
+            .map(|(id, severity, check_id, raw_data, cwe_ids, status, suppressed)| {
+                (id, SummaryInput {
+                    severity,
+                    check_id,
+                    owasp: owasp_category(raw_data.as_deref()),
+                    cwe_ids: serde_json::from_str(&cwe_ids).unwrap_or_default(),
+                    status,
+                    suppressed: suppressed != 0,
+                    low_confidence: false,
+                })
+            })
+            .collect())
+    }
+
+    /// Store the canonical summary of a scan
+    pub async fn save_summary(&self, task_id: &str, summary: &ReportSummary) -> StoreResult<()> {
+        self.ensure_available()?;
+        let encoded = serde_json::to_string(summary).unwrap_or_default();
+        let result = sqlx::query("UPDATE scan_tasks SET summary = ? WHERE id = ?")
+            .bind(encoded)
+            .bind(task_id)
+            .execute(&self.pool)
+            .await;
+        self.checked(result).map(|_| ())
+    }
+
+    /// Insert or update a scan task row
+    pub async fn save_task(&self, task: &ScanTask) -> StoreResult<()> {
+        self.ensure_available()?;
+        let result = upsert_task(&self.pool, task).await;
+        self.checked(result)
+    }
+
+    /// Persist a finished scan: the task, the full report and its findings, headers, TLS analysis and technologies
+    ///
+    /// Findings that already exist keep their triage status and suppression; the other sections are replaced
+    pub async fn save_report(&self, report: &ScanReport) -> StoreResult<()> {
+        self.ensure_available()?;
+        let result = self.write_report(report).await;
+        self.checked(result)
+    }
+
+    async fn write_report(&self, report: &ScanReport) -> Result<(), sqlx::Error> {
+        let task_id = &report.task.id;
+        let mut tx = self.pool.begin().await?;
+        upsert_task(&mut *tx, &report.task).await?;
+        sqlx::query("UPDATE scan_tasks SET report = ?, summary = ?, check_metrics = ? WHERE id = ?")
+            .bind(serde_json::to_string(report).unwrap_or_default())
+            .bind(report.summary.as_ref().and_then(|s| serde_json::to_string(s).ok()))
+            .bind((!report.check_metrics.is_empty()).then(|| serde_json::to_string(&report.check_metrics).unwrap_or_default()))
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for finding in &report.vulnerabilities {
+            sqlx::query(
+                "INSERT INTO scan_results (id, task_id, result_type, severity, title, description, raw_data, created_at, check_id, cwe_ids, affected_url)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(id) DO UPDATE SET severity = excluded.severity, title = excluded.title,
+                     description = excluded.description, raw_data = excluded.raw_data,
+                     check_id = excluded.check_id, cwe_ids = excluded.cwe_ids, affected_url = excluded.affected_url",
+            )
+            .bind(&finding.id)
+            .bind(task_id)
+            .bind(format!("{:?}", finding.result_type).to_lowercase())
+            .bind(finding.severity.as_ref().map(|s| s.to_string()))
+            .bind(&finding.title)
+            .bind(&finding.description)
+            .bind(&finding.raw_data)
+            .bind(finding.created_at.to_rfc3339())
+            .bind(finding_check_id(finding))
+            .bind(serde_json::to_string(&finding.cwe_ids).unwrap_or_else(|_| "[]".to_string()))
+            .bind(finding.details.as_ref().and_then(|d| d.affected_url.as_deref()))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for table in ["security_headers", "ssl_analysis", "detected_technologies"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE task_id = ?", table)).bind(task_id).execute(&mut *tx).await?;
+        }
+        for header in &report.headers {
+            sqlx::query(
+                "INSERT INTO security_headers (id, task_id, header_name, header_value, is_present, is_secure, recommendation, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&header.id)
+            .bind(task_id)
+            .bind(&header.header_name)
+            .bind(&header.header_value)
+            .bind(header.is_present)
+            .bind(header.is_secure)
+            .bind(&header.recommendation)
+            .bind(header.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        for ssl in &report.ssl_analysis {
+            let json = |value: &Option<Vec<String>>| value.as_ref().and_then(|v| serde_json::to_string(v).ok());
+            sqlx::query(
+                "INSERT INTO ssl_analysis (id, task_id, certificate_issuer, certificate_subject, valid_from, valid_to,
+                     signature_algorithm, tls_versions, cipher_suites, vulnerabilities, grade, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&ssl.id)
+            .bind(task_id)
+            .bind(&ssl.certificate_issuer)
+            .bind(&ssl.certificate_subject)
+            .bind(ssl.valid_from.map(|d| d.to_rfc3339()))
+            .bind(ssl.valid_to.map(|d| d.to_rfc3339()))
+            .bind(&ssl.signature_algorithm)
+            .bind(json(&ssl.tls_versions))
+            .bind(json(&ssl.cipher_suites))
+            .bind(json(&ssl.vulnerabilities))
+            .bind(&ssl.grade)
+            .bind(ssl.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        for technology in &report.technologies {
+            sqlx::query(
+                "INSERT INTO detected_technologies (id, task_id, technology_name, technology_version, category, confidence, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&technology.id)
+            .bind(task_id)
+            .bind(&technology.technology_name)
+            .bind(&technology.technology_version)
+            .bind(format!("{:?}", technology.category).to_lowercase())
+            .bind(i64::from(technology.confidence))
+            .bind(technology.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// Delete a scan with its findings, their annotations, headers, TLS analysis and technologies in one transaction
+    ///
+    /// Returns false when the scan is not in the database
+    pub async fn delete_scan(&self, task_id: &str) -> StoreResult<bool> {
+        self.ensure_available()?;
+        let result = self.delete_scan_rows(task_id).await;
+        self.checked(result)
+    }
+
+    async fn delete_scan_rows(&self, task_id: &str) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM annotations WHERE finding_id IN (SELECT id FROM scan_results WHERE task_id = ?)")
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+        for table in ["scan_results", "security_headers", "ssl_analysis", "detected_technologies"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE task_id = ?", table)).bind(task_id).execute(&mut *tx).await?;
+        }
+        // Assets outlive the scan that first found them
+        sqlx::query("UPDATE assets SET first_scan_id = NULL WHERE first_scan_id = ?").bind(task_id).execute(&mut *tx).await?;
+        let deleted = sqlx::query("DELETE FROM scan_tasks WHERE id = ?").bind(task_id).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    /// Mark scans that were pending or running when the app last exited as failed
+    ///
+    /// Returns the ids of the recovered scans
+    pub async fn recover_interrupted(&self, message: &str) -> StoreResult<Vec<String>> {
+        self.ensure_available()?;
+        let rows = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT id, task_state FROM scan_tasks WHERE status IN ('pending', 'running') AND report IS NULL",
+        )
+        .fetch_all(&self.pool)
+        .await;
+        let rows = self.checked(rows)?;
+
+        let now = Utc::now();
+        let mut recovered = Vec::new();
+        for (id, task_state) in rows {
+            let mut task = task_state.and_then(|state| serde_json::from_str::<ScanTask>(&state).ok());
+            if let Some(task) = task.as_mut() {
+                task.status = ScanStatus::Failed;
+                task.completed_at = Some(now);
+                task.error = Some(message.to_string());
+            }
+            let result = sqlx::query("UPDATE scan_tasks SET status = 'failed', completed_at = ?, task_state = ? WHERE id = ?")
+                .bind(now.to_rfc3339())
+                .bind(task.and_then(|task| serde_json::to_string(&task).ok()))
+                .bind(&id)
+                .execute(&self.pool)
+                .await;
+            self.checked(result)?;
+            recovered.push(id);
+        }
+        Ok(recovered)
+    }
+
+    /// Every scan persisted by the backend: tasks in creation order and the reports of finished scans
+    pub async fn load_scans(&self) -> StoreResult<(Vec<ScanTask>, Vec<ScanReport>)> {
+        self.ensure_available()?;
+        let rows = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT task_state, report FROM scan_tasks WHERE task_state IS NOT NULL ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        let mut tasks = Vec::new();
+        let mut reports = Vec::new();
+        for (task_state, report) in self.checked(rows)? {
+            let Ok(mut task) = serde_json::from_str::<ScanTask>(&task_state) else {
+                continue;
+            };
+            if let Some(mut report) = report.and_then(|report| serde_json::from_str::<ScanReport>(&report).ok()) {
+                // The task row is updated after the report (triage, recovery); it is authoritative
+                report.task = task.clone();
+                reports.push(report);
+            } else if task.status == ScanStatus::Completed {
+                task.error.get_or_insert_with(|| "掃描報告無法讀取".to_string());
+            }
+            tasks.push(task);
+        }
+        Ok((tasks, reports))
+    }
+
+    /// Insert or replace the baseline of its target
+    pub async fn save_baseline(&self, baseline: &Baseline) -> StoreResult<()> {
+        self.ensure_available()?;
+        let result = sqlx::query(
+            "INSERT INTO baselines (target, source_task_id, baseline, created_at) VALUES (?, ?, ?, ?)
+             ON CONFLICT(target) DO UPDATE SET source_task_id = excluded.source_task_id,
+                 baseline = excluded.baseline, created_at = excluded.created_at",
+        )
+        .bind(&baseline.target)
+        .bind(&baseline.source_task_id)
+        .bind(serde_json::to_string(baseline).unwrap_or_default())
+        .bind(baseline.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await;
+        self.checked(result).map(|_| ())
+    }
+
+    /// Remove the baseline of a target (already normalized with `baseline_key`)
+    pub async fn delete_baseline(&self, target: &str) -> StoreResult<()> {
+        self.ensure_available()?;
+        let result = sqlx::query("DELETE FROM baselines WHERE target = ?").bind(target).execute(&self.pool).await;
+        self.checked(result).map(|_| ())
+    }
+
+    /// Every persisted baseline; rows that no longer deserialize are skipped
+    pub async fn load_baselines(&self) -> StoreResult<Vec<Baseline>> {
+        self.ensure_available()?;
+        let rows = sqlx::query_scalar::<_, String>("SELECT baseline FROM baselines")
+            .fetch_all(&self.pool)
+            .await;
+
+        Ok(self.checked(rows)?
+            .into_iter()
+            .filter_map(|encoded| serde_json::from_str(&encoded).ok())
+            .collect())
+    }
+
+    /// Dashboard statistics over persisted scans created in [since, until)
+    ///
+    /// Open findings are those of the latest completed scan of each target that are still open or
+    /// confirmed and not suppressed, grouped by target, severity and check id.
+    /// Targets and checks are returned unranked; every counted target and check is included.
+    pub async fn dashboard_stats(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> StoreResult<DashboardStats> {
+        self.ensure_available()?;
+        let result = self.read_dashboard_stats(since, until, now).await;
+        self.checked(result)
+    }
+
+    async fn read_dashboard_stats(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<DashboardStats, sqlx::Error> {
+        let since_bound = since.map(|d| d.to_rfc3339());
+        let until_bound = until.map(|d| d.to_rfc3339());
+        let mut stats = DashboardStats { range_since: since, range_until: until, generated_at: now, ..Default::default() };
+
+        let by_status = sqlx::query_as::<_, (String, i64)>(&format!(
+            "SELECT COALESCE(json_extract(task_state, '$.status'), status), COUNT(*) FROM scan_tasks WHERE {} GROUP BY 1",
+            IN_RANGE
+        ))
+        .bind(&since_bound)
+        .bind(&until_bound)
+        .fetch_all(&self.pool)
+        .await;
+        for (status, count) in by_status? {
+            stats.scans_by_status.insert(status, count as usize);
+        }
+
+        let recent = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT COUNT(CASE WHEN julianday(created_at) >= julianday(?1) THEN 1 END),
+                 COUNT(CASE WHEN julianday(created_at) >= julianday(?2) THEN 1 END)
+             FROM scan_tasks",
+        )
+        .bind((now - chrono::Duration::days(7)).to_rfc3339())
+        .bind((now - chrono::Duration::days(30)).to_rfc3339())
+        .fetch_all(&self.pool)
+        .await;
+        if let Some((last_7_days, last_30_days)) = recent?.first() {
+            stats.scans_last_7_days = *last_7_days as usize;
+            stats.scans_last_30_days = *last_30_days as usize;
+        }
+
+        let open = sqlx::query_as::<_, (String, String, String, i64)>(&format!(
+            "{}
+             SELECT l.target_url, COALESCE(r.severity, 'info'), COALESCE(r.check_id, 'unknown'), COUNT(*)
+             FROM latest l JOIN scan_results r ON r.task_id = l.id
+             WHERE r.suppressed = 0 AND r.status IN ('open', 'confirmed')
+             GROUP BY 1, 2, 3",
+            LATEST_COMPLETED
+        ))
+        .bind(&since_bound)
+        .bind(&until_bound)
+        .fetch_all(&self.pool)
+        .await;
+        let mut targets: HashMap<String, TargetRisk> = HashMap::new();
+        let mut checks: HashMap<String, usize> = HashMap::new();
+        for (target, severity, check_id, count) in open? {
+            let count = count as usize;
+            let risk = targets.entry(target.clone()).or_insert(TargetRisk { target, critical: 0, high: 0 });
+            match severity.as_str() {
+                "critical" => risk.critical += count,
+                "high" => risk.high += count,
+                _ => {}
+            }
+            *stats.open_findings_by_severity.entry(severity).or_insert(0) += count;
+            *checks.entry(check_id).or_insert(0) += count;
+        }
+        stats.top_targets = targets.into_values().filter(|t| t.critical + t.high > 0).collect();
+        stats.top_checks = checks.into_iter().map(|(check_id, count)| CheckCount { check_id, count }).collect();
+
+        // Reports written before per-port TLS analysis hold a single object instead of an array
+        let certificates = sqlx::query_as::<_, (String, i64, Option<i64>, Option<String>, Option<String>)>(&format!(
+            "{}
+             SELECT l.target_url, s.key, json_extract(s.value, '$.analyzed_port'),
+                 json_extract(s.value, '$.certificate_subject'), json_extract(s.value, '$.valid_to')
+             FROM latest l, json_each(l.report, '$.ssl_analysis') AS s
+             WHERE json_type(l.report, '$.ssl_analysis') = 'array'
+             UNION ALL
+             SELECT target_url, 0, NULL,
+                 json_extract(report, '$.ssl_analysis.certificate_subject'), json_extract(report, '$.ssl_analysis.valid_to')
+             FROM latest
+             WHERE json_type(report, '$.ssl_analysis') = 'object'",
+            LATEST_COMPLETED
+        ))
+        .bind(&since_bound)
+        .bind(&until_bound)
+        .fetch_all(&self.pool)
+        .await;
+        for (target, index, port, subject, valid_to) in certificates? {
+            let Some(valid_to) = valid_to.and_then(|v| DateTime::parse_from_rfc3339(&v).ok()).map(|v| v.with_timezone(&Utc)) else {
+                continue;
+            };
+            let days_remaining = (valid_to - now).num_days();
+            if valid_to >= now && days_remaining <= CERT_EXPIRY_WINDOW_DAYS {
+                stats.expiring_certificates.push(ExpiringCertificate {
+                    // The first analysis is the scan target itself; later ones are other TLS ports
+                    target: match port {
+                        Some(port) if index > 0 => format!("{}:{}", target, port),
+                        _ => target,
+                    },
+                    subject,
+                    valid_to,
+                    days_remaining,
+                });
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Per-check metrics of every persisted scan that recorded them, with the scan id and creation time
+    pub async fn check_metrics(&self) -> StoreResult<Vec<(String, String, Vec<CheckMetrics>)>> {
+        self.ensure_available()?;
+        let rows = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT id, created_at, check_metrics FROM scan_tasks WHERE check_metrics IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        Ok(self.checked(rows)?
+            .into_iter()
+            .filter_map(|(id, created_at, encoded)| {
+                serde_json::from_str(&encoded).ok().map(|metrics| (id, created_at, metrics))
+            })
+            .collect())
+    }
+
+    /// Inventory entries for the given hostnames
+    pub async fn assets_by_hostnames(&self, hostnames: &[String]) -> StoreResult<Vec<PersistedAsset>> {
+        if hostnames.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.ensure_available()?;
+
+        let encoded = serde_json::to_string(hostnames).unwrap_or_else(|_| "[]".to_string());
+        let rows = sqlx::query_as::<_, (String, String, Option<String>, Option<String>, Option<String>, Option<String>, String)>(
+            "SELECT id, hostname, ip_address, ports, services, technologies, discovered_at
+             FROM assets WHERE hostname IN (SELECT value FROM json_each(?))",
+        )
+        .bind(encoded)
+        .fetch_all(&self.pool)
+        .await;
+
+        // JSON array columns are written by the frontend, tolerate malformed values
+        fn json_list<T: serde::de::DeserializeOwned>(value: Option<String>) -> Vec<T> {
+            value.and_then(|v| serde_json::from_str(&v).ok()).unwrap_or_default()
+        }
+
+        Ok(self.checked(rows)?
+            .into_iter()
+            .map(|(id, hostname, ip_address, ports, services, technologies, discovered_at)| PersistedAsset {
+                id,
+                hostname,
+                ip_address,
+                ports: json_list(ports),
+                services: json_list(services),
+                technologies: json_list(technologies),
+                discovered_at,
+            })
+            .collect())
+    }
+
+    /// Insert inventory entries, replacing any entry with the same id
+    pub async fn save_assets(&self, assets: &[PersistedAsset]) -> StoreResult<()> {
+        self.ensure_available()?;
+        let result = self.write_assets(assets).await;
+        self.checked(result)
+    }
+
+    async fn write_assets(&self, assets: &[PersistedAsset]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for asset in assets {
+            sqlx::query(
+                "INSERT INTO assets (id, hostname, ip_address, ports, services, technologies, discovered_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(id) DO UPDATE SET hostname = excluded.hostname, ip_address = excluded.ip_address,
+                     ports = excluded.ports, services = excluded.services,
+                     technologies = excluded.technologies, discovered_at = excluded.discovered_at",
+            )
+            .bind(&asset.id)
+            .bind(&asset.hostname)
+            .bind(&asset.ip_address)
+            .bind(serde_json::to_string(&asset.ports).unwrap_or_else(|_| "[]".to_string()))
+            .bind(serde_json::to_string(&asset.services).unwrap_or_else(|_| "[]".to_string()))
+            .bind(serde_json::to_string(&asset.technologies).unwrap_or_else(|_| "[]".to_string()))
+            .bind(&asset.discovered_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Findings across all scans matching `filters`, filtered, sorted and paginated in SQL
+    pub async fn query_findings(&self, filters: &FindingFilters) -> StoreResult<FindingPage> {
+        self.ensure_available()?;
+        let limit = filters.limit();
+
+        let mut count = QueryBuilder::<Sqlite>::new(
+            "SELECT COUNT(*) FROM scan_results r JOIN scan_tasks t ON t.id = r.task_id",
+        );
+        filters.push_where(&mut count);
+        let total = count.build_query_scalar::<i64>().fetch_all(&self.pool).await;
+        let total = self.checked(total)?.first().copied().unwrap_or(0);
+
+        let mut select = QueryBuilder::<Sqlite>::new(
+            "SELECT r.id, r.task_id, r.result_type, r.severity, r.title, r.description, r.raw_data, r.created_at, \
+             r.status, r.check_id, r.cwe_ids, r.suppressed, t.target_url, t.hostname, t.project, t.scan_type, t.created_at \
+             FROM scan_results r JOIN scan_tasks t ON t.id = r.task_id",
+        );
+        filters.push_where(&mut select);
+        filters.push_order(&mut select);
+        select.push(" LIMIT ").push_bind(i64::from(limit)).push(" OFFSET ").push_bind(i64::from(filters.offset));
+        let rows = select.build_query_as::<FindingRow>().fetch_all(&self.pool).await;
+
+        let findings = self.checked(rows)?
+            .into_iter()
+            .map(|FindingRow(finding)| finding)
+            .collect();
+
+        Ok(FindingPage { total: total.max(0) as u64, offset: filters.offset, limit, findings })
+    }
+
+    async fn existing_values(&self, sql: &str, values: &[String]) -> StoreResult<HashSet<String>> {
+        if values.is_empty() {
+            return Ok(HashSet::new());
+        }
+        self.ensure_available()?;
+
+        let encoded = serde_json::to_string(values).unwrap_or_else(|_| "[]".to_string());
+        let rows = sqlx::query_scalar::<_, String>(sql)
+            .bind(encoded)
+            .fetch_all(&self.pool)
+            .await;
+
+        Ok(self.checked(rows)?.into_iter().collect())
+    }
+}
+
+/// Resolve the database path used by tauri-plugin-sql for `sqlite:redforge.db`
+pub fn default_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    use tauri::Manager;
+    app.path().app_config_dir().ok().map(|dir| dir.join(DATABASE_FILE))
+}
+
+/// Open the default database
+///
+/// Never fails: a locked, corrupted or unreachable database yields no store and a fallback status.
+/// Corruption is repaired from the most recent backup when one exists.
+pub fn open_default(app: &tauri::AppHandle) -> (Option<Store>, StorageMonitor) {
+    let (pool, status) = match default_path(app) {
+        Some(path) => tauri::async_runtime::block_on(open_with_recovery(&path)),
+        None => (None, fallback_status(None, StorageProblem::Unavailable, "無法取得資料目錄".to_string(), Vec::new())),
+    };
+
+    let monitor = StorageMonitor::new(status, Some(app.clone()));
+    monitor.announce();
+    (pool.map(|pool| Store { pool, monitor: monitor.clone() }), monitor)
+}
+
+async fn open_with_recovery(path: &Path) -> (Option<SqlitePool>, StorageStatus) {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+
+    match Store::open_checked(path).await {
+        Ok(pool) => {
+            if let Err(e) = backup(&pool, path).await {
+                tracing::warn!("⚠️  資料庫備份失敗: {}", e);
+            }
+            (Some(pool), StorageStatus::persistent(path))
+        }
+        Err(StoreError::Corrupted(message)) => {
+            tracing::error!("❌ 資料庫已損毀 {}: {}", path.display(), message);
+            restore_latest_backup(path, message).await
+        }
+        Err(StoreError::Locked(message)) => {
+            tracing::error!("❌ 資料庫被鎖定 {}: {}", path.display(), message);
+            (None, fallback_status(Some(path), StorageProblem::Locked, format!("資料庫被其他程式鎖定: {}", message), Vec::new()))
+        }
+        Err(StoreError::DiskFull(message)) => {
+            (None, fallback_status(Some(path), StorageProblem::DiskFull, format!("磁碟空間不足: {}", message), Vec::new()))
+        }
+        Err(e) => {
+            tracing::warn!("⚠️  無法開啟資料庫 {}: {}", path.display(), e);
+            (None, fallback_status(Some(path), StorageProblem::Unavailable, e.to_string(), Vec::new()))
+        }
+    }
+}
+
+/// Move the corrupted file aside and copy the most recent backup into place
+async fn restore_latest_backup(path: &Path, message: String) -> (Option<SqlitePool>, StorageStatus) {
+    let mut actions = vec![format!("偵測到資料庫損毀: {}", message)];
+
+    let Some(latest) = list_backups(path).pop() else {
+        actions.push("沒有可用的備份".to_string());
+        return (None, fallback_status(Some(path), StorageProblem::Corrupted, format!("資料庫已損毀且沒有備份: {}", message), actions));
+    };
+
+    let quarantine = path.with_extension(format!("db.corrupt-{}", Utc::now().format("%Y%m%d%H%M%S")));
+    let restored = std::fs::rename(path, &quarantine)
+        .and_then(|_| {
+            // Stale journal files belong to the corrupted database
+            for suffix in ["-wal", "-shm", "-journal"] {
+                let journal = PathBuf::from(format!("{}{}", path.display(), suffix));
+                if journal.exists() {
+                    std::fs::remove_file(journal)?;
+                }
+            }
+            std::fs::copy(&latest, path).map(|_| ())
+        });
+
+    if let Err(e) = restored {
+        actions.push(format!("還原備份失敗: {}", e));
+        return (None, fallback_status(Some(path), StorageProblem::Corrupted, format!("資料庫已損毀，還原失敗: {}", e), actions));
+    }
+    actions.push(format!("已將損毀的資料庫移至 {}", quarantine.display()));
+    actions.push(format!("已從備份還原: {}", latest.display()));
+
+    match Store::open_checked(path).await {
+        Ok(pool) => {
+            tracing::info!("✅ 已從備份還原資料庫: {}", latest.display());
+            let mut status = StorageStatus::persistent(path);
+            status.problem = Some(StorageProblem::Corrupted);
+            status.message = Some("資料庫曾損毀，已從最近的備份還原，備份之後的紀錄可能遺失".to_string());
+            status.recovery_actions = actions;
+            status.restored_from = Some(latest.display().to_string());
+            (Some(pool), status)
+        }
+        Err(e) => {
+            actions.push(format!("還原後的資料庫仍無法使用: {}", e));
+            (None, fallback_status(Some(path), StorageProblem::Corrupted, e.to_string(), actions))
+        }
+    }
+}
+
+fn fallback_status(path: Option<&Path>, problem: StorageProblem, message: String, mut actions: Vec<String>) -> StorageStatus {
+    actions.push("切換為唯讀記憶體模式，掃描可正常執行但無法讀取或保存歷史紀錄".to_string());
+    StorageStatus {
+        mode: StorageMode::ReadOnlyFallback,
+        path: path.map(|p| p.display().to_string()),
+        problem: Some(problem),
+        message: Some(message),
+        recovery_actions: actions,
+        restored_from: None,
+    }
+}
+
+fn backup_dir(path: &Path) -> PathBuf {
+    path.parent().unwrap_or_else(|| Path::new(".")).join(BACKUP_DIR)
+}
+
+/// Backups of the database, oldest first (names sort by timestamp)
+fn list_backups(path: &Path) -> Vec<PathBuf> {
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(backup_dir(path))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().map_or(false, |ext| ext == "db"))
+                .collect()
+        })
+        .unwrap_or_default();
+    backups.sort();
+    backups
+}
+
+/// Snapshot a healthy database at most once per interval, keeping the newest few
+async fn backup(pool: &SqlitePool, path: &Path) -> StoreResult<()> {
+    let backups = list_backups(path);
+    let cutoff = (Utc::now() - chrono::Duration::hours(BACKUP_INTERVAL_HOURS)).format("%Y%m%d%H%M%S").to_string();
+    let recent = backups.last().and_then(|p| p.file_stem()).map_or(false, |stem| {
+        stem.to_string_lossy().trim_start_matches("redforge-") > cutoff.as_str()
+    });
+    if recent {
+        return Ok(());
+    }
+
+    let dir = backup_dir(path);
+    std::fs::create_dir_all(&dir).map_err(|e| StoreError::Unavailable(e.to_string()))?;
+    let target = dir.join(format!("redforge-{}.db", Utc::now().format("%Y%m%d%H%M%S")));
+
+    // VACUUM INTO writes a consistent copy without blocking readers
+    sqlx::query("VACUUM INTO ?")
+        .bind(target.display().to_string())
+        .execute(pool)
+        .await?;
+
+    let backups = list_backups(path);
+    for old in backups.iter().take(backups.len().saturating_sub(BACKUPS_KEPT)) {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(())
+}
+
+/// The status column predates paused and cancelled; task_state keeps the exact status
+fn stored_status(status: &ScanStatus) -> &'static str {
+    match status {
+        ScanStatus::Pending => "pending",
+        ScanStatus::Running | ScanStatus::Paused => "running",
+        ScanStatus::Completed => "completed",
+        ScanStatus::Failed | ScanStatus::Cancelled => "failed",
+    }
+}
+
+async fn upsert_task<'e, E: sqlx::Executor<'e, Database = Sqlite>>(executor: E, task: &ScanTask) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO scan_tasks (id, target_url, scan_type, status, started_at, completed_at, created_at, task_state)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET status = excluded.status, started_at = excluded.started_at,
+             completed_at = excluded.completed_at, task_state = excluded.task_state",
+    )
+    .bind(&task.id)
+    .bind(&task.target_url)
+    .bind(task.scan_type.to_string())
+    .bind(stored_status(&task.status))
+    .bind(task.started_at.map(|d| d.to_rfc3339()))
+    .bind(task.completed_at.map(|d| d.to_rfc3339()))
+    .bind(task.created_at.to_rfc3339())
+    .bind(serde_json::to_string(task).unwrap_or_default())
+    .execute(executor)
+    .await
+    .map(|_| ())
+}
+
+/// The plugin migrations as tauri-plugin-sql resolves them, so both record the same checksums
+#[derive(Debug)]
+struct PluginMigrations(Vec<tauri_plugin_sql::Migration>);
+
+impl MigrationSource<'static> for PluginMigrations {
+    fn resolve(self) -> Pin<Box<dyn Future<Output = Result<Vec<Migration>, BoxDynError>> + Send>> {
+        let migrations = self
+            .0
+            .into_iter()
+            .filter(|m| matches!(m.kind, tauri_plugin_sql::MigrationKind::Up))
+            .map(|m| Migration::new(m.version, m.description.into(), m.kind.into(), m.sql.into(), false))
+            .collect();
+        Box::pin(std::future::ready(Ok(migrations)))
+    }
+}
+
+/// Apply the migrations that have not run yet; startup must not wait for the frontend to load the database
+async fn migrate(pool: &SqlitePool, migrations: Vec<tauri_plugin_sql::Migration>) -> StoreResult<()> {
+    let migrated = match Migrator::new(PluginMigrations(migrations)).await {
+        Ok(migrator) => migrator.run(pool).await,
+        Err(e) => Err(e),
+    };
+    migrated.map_err(|e| match e {
+        MigrateError::Execute(e) | MigrateError::ExecuteMigration(e, _) => StoreError::from(e),
+        other => StoreError::Unavailable(format!("資料庫結構升級失敗: {}", other)),
+    })
+}
+
+#[cfg(test)]
+impl Store {
+    /// A new database in the temp directory with every plugin migration applied, as after the first app start
+    pub(crate) async fn open_migrated() -> Store {
+        let path = std::env::temp_dir().join(format!("redforge-test-{}.db", uuid::Uuid::new_v4()));
+        let pool = Self::connect(&path).await.expect("open test database");
+        migrate(&pool, crate::database::get_migrations()).await.expect("apply migrations");
+        Store { pool, monitor: StorageMonitor::new(StorageStatus::persistent(&path), None) }
+    }
+
+    /// A new database with only the migrations up to `version` applied, as an older release left it
+    pub(crate) async fn create_at_version(version: i64) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("redforge-test-{}", uuid::Uuid::new_v4())).join(DATABASE_FILE);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("create test directory");
+        let pool = Self::connect(&path).await.expect("open test database");
+        let migrations = crate::database::get_migrations().into_iter().filter(|m| m.version <= version).collect();
+        migrate(&pool, migrations).await.expect("apply migrations");
+        pool.close().await;
+        path
+    }
+
+    /// Open a database file the way startup does
+    pub(crate) async fn open_at(path: &Path) -> Store {
+        let (pool, status) = open_with_recovery(path).await;
+        Store { pool: pool.expect("open database"), monitor: StorageMonitor::new(status, None) }
+    }
+
+    /// The database file backing this store
+    pub(crate) fn path(&self) -> PathBuf {
+        PathBuf::from(self.monitor.status().path.unwrap_or_default())
+    }
+
+    /// The summary column of a scan, as the history view reads it
+    pub(crate) async fn saved_summary(&self, task_id: &str) -> Option<ReportSummary> {
+        let encoded = sqlx::query_scalar::<_, Option<String>>("SELECT summary FROM scan_tasks WHERE id = ?")
+            .bind(task_id)
+            .fetch_one(&self.pool)
+            .await
+            .expect("scan row");
+        encoded.map(|encoded| serde_json::from_str(&encoded).expect("valid summary"))
+    }
+}
@@ -0,0 +1,314 @@
+/**
+ * Scan Event Coalescer
+ *
+ * 快速的掃描每分鐘可能產生上千個事件，逐一送到 webview 會讓 Vue 介面卡頓。所有掃描事件經過這裡：
+ *
+ * - scan-progress：每個掃描每 250ms 最多一次，期間只保留最新的值；完成 / 失敗 / 取消事件立即送出
+ * - scan-log：日誌行累積後每 250ms 以陣列批次送出；webview 停止接收時最多保留 1000 行，超過時捨棄最舊的並計數
+ * - scan-finding：一律立即送出，同一發現重複送出時略過
+ */
+
+use crate::models::{ScanProgress, ScanResult, ScanStage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 進度事件的最小間隔 (每秒約 4 次)
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// 日誌批次的送出間隔
+const LOG_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+/// 每個掃描等待送出的日誌行上限
+const MAX_PENDING_LOG_LINES: usize = 1000;
+
+/// 實際送出事件，例如 AppHandle::emit
+pub type EventEmitter = Arc<dyn Fn(&str, serde_json::Value) + Send + Sync>;
+
+/// 單一日誌行
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanLogLine {
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+}
+
+/// scan-log 事件：一個掃描在一個批次間隔內的日誌行
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanLogBatch {
+    pub task_id: String,
+    pub lines: Vec<ScanLogLine>,
+    /// 超過上限而捨棄的較舊日誌行數量
+    #[serde(default)]
+    pub dropped_lines: u64,
+}
+
+/// scan-finding 事件
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindingEvent {
+    pub task_id: String,
+    pub finding: ScanResult,
+}
+
+#[derive(Default)]
+struct TaskEvents {
+    last_progress: Option<Instant>,
+    pending_progress: Option<ScanProgress>,
+    logs: VecDeque<ScanLogLine>,
+    dropped_logs: u64,
+    findings_sent: HashSet<String>,
+}
+
+#[derive(Clone)]
+pub struct EventCoalescer {
+    emitter: EventEmitter,
+    tasks: Arc<Mutex<HashMap<String, TaskEvents>>>,
+}
+
+impl EventCoalescer {
+    pub fn new(emitter: EventEmitter) -> Self {
+        Self { emitter, tasks: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// 啟動背景計時器，定期送出累積的進度與日誌
+    pub fn spawn_flusher(&self) {
+        let coalescer = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(LOG_FLUSH_INTERVAL);
+            loop {
+                interval.tick().await;
+                coalescer.flush();
+            }
+        });
+    }
+
+    /// 進度事件：距離上次送出不足間隔時只保留最新值，等計時器送出
+    pub fn progress(&self, progress: ScanProgress) {
+        let terminal = matches!(progress.stage, ScanStage::Completed | ScanStage::Failed | ScanStage::Cancelled);
+        let mut outgoing = Vec::new();
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let task = tasks.entry(progress.task_id.clone()).or_default();
+            let due = task.last_progress.map_or(true, |last| last.elapsed() >= PROGRESS_INTERVAL);
+
+            if terminal {
+                // 結束前先送出剩餘日誌，確保日誌不會晚於完成事件到達
+                outgoing.extend(take_logs(&progress.task_id, task));
+                task.pending_progress = None;
+            }
+            if terminal || due {
+                task.last_progress = Some(Instant::now());
+                task.pending_progress = None;
+                outgoing.push(("scan-progress", serde_json::to_value(&progress).unwrap_or_default()));
+            } else {
+                task.pending_progress = Some(progress);
+            }
+        }
+        self.send(outgoing);
+    }
+
+    /// 日誌行：累積後由計時器批次送出；超過上限時捨棄最舊的一行
+    pub fn log(&self, task_id: &str, message: String) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let task = tasks.entry(task_id.to_string()).or_default();
+        if task.logs.len() >= MAX_PENDING_LOG_LINES {
+            task.logs.pop_front();
+            task.dropped_logs += 1;
+        }
+        task.logs.push_back(ScanLogLine { timestamp: Utc::now(), message });
+    }
+
+    /// 發現：立即送出；同一掃描中已送出過的發現 id 略過
+    pub fn finding(&self, task_id: &str, finding: &ScanResult) {
+        let first = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            tasks.entry(task_id.to_string()).or_default().findings_sent.insert(finding.id.clone())
+        };
+        if first {
+            let event = FindingEvent { task_id: task_id.to_string(), finding: finding.clone() };
+            self.send(vec![("scan-finding", serde_json::to_value(&event).unwrap_or_default())]);
+        }
+    }
+
+    /// 送出到期的進度與所有累積的日誌
+    pub fn flush(&self) {
+        let mut outgoing = Vec::new();
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            for (task_id, task) in tasks.iter_mut() {
+                outgoing.extend(take_logs(task_id, task));
+                let due = task.last_progress.map_or(true, |last| last.elapsed() >= PROGRESS_INTERVAL);
+                if due {
+                    if let Some(progress) = task.pending_progress.take() {
+                        task.last_progress = Some(Instant::now());
+                        outgoing.push(("scan-progress", serde_json::to_value(&progress).unwrap_or_default()));
+                    }
+                }
+            }
+        }
+        self.send(outgoing);
+    }
+
+    /// 掃描結束：送出剩餘的日誌並釋放此掃描的狀態
+    pub fn finish(&self, task_id: &str) {
+        let outgoing = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            match tasks.remove(task_id) {
+                Some(mut task) => take_logs(task_id, &mut task).into_iter().collect(),
+                None => Vec::new(),
+            }
+        };
+        self.send(outgoing);
+    }
+
+    // 在鎖外送出，避免事件處理時間拖住掃描
+    fn send(&self, events: Vec<(&'static str, serde_json::Value)>) {
+        for (name, payload) in events {
+            (self.emitter)(name, payload);
+        }
+    }
+}
+
+fn take_logs(task_id: &str, task: &mut TaskEvents) -> Option<(&'static str, serde_json::Value)> {
+    if task.logs.is_empty() {
+        return None;
+    }
+    let batch = ScanLogBatch {
+        task_id: task_id.to_string(),
+        lines: task.logs.drain(..).collect(),
+        dropped_lines: std::mem::take(&mut task.dropped_logs),
+    };
+    Some(("scan-log", serde_json::to_value(&batch).unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Sent = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+    fn coalescer() -> (EventCoalescer, Sent) {
+        let sent: Sent = Arc::default();
+        let sink = sent.clone();
+        let coalescer = EventCoalescer::new(Arc::new(move |name, payload| sink.lock().unwrap().push((name.to_string(), payload))));
+        (coalescer, sent)
+    }
+
+    fn names(sent: &Sent) -> Vec<String> {
+        sent.lock().unwrap().iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    fn progress(stage: ScanStage, value: u8) -> ScanProgress {
+        ScanProgress::new("scan-1", stage, value, String::new())
+    }
+
+    #[test]
+    fn throttles_progress_but_sends_terminal_immediately() {
+        let (coalescer, sent) = coalescer();
+        coalescer.progress(progress(ScanStage::Headers, 10));
+        coalescer.progress(progress(ScanStage::Headers, 20));
+        coalescer.progress(progress(ScanStage::Ssl, 30));
+        // Within the interval the flusher holds the latest value back
+        coalescer.flush();
+        assert_eq!(names(&sent), vec!["scan-progress"]);
+
+        coalescer.progress(progress(ScanStage::Completed, 100));
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].1["stage"]["id"], "completed");
+    }
+
+    #[test]
+    fn sends_latest_pending_progress_once_due() {
+        let (coalescer, sent) = coalescer();
+        coalescer.progress(progress(ScanStage::Headers, 10));
+        coalescer.progress(progress(ScanStage::Ssl, 30));
+        std::thread::sleep(PROGRESS_INTERVAL);
+        coalescer.flush();
+        coalescer.flush();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].1["progress"], 30);
+    }
+
+    #[test]
+    fn batches_logs_until_flush() {
+        let (coalescer, sent) = coalescer();
+        for i in 0..3 {
+            coalescer.log("scan-1", format!("line {}", i));
+        }
+        assert!(names(&sent).is_empty());
+
+        coalescer.flush();
+        coalescer.flush();
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1["lines"].as_array().unwrap().len(), 3);
+        assert_eq!(sent[0].1["dropped_lines"], 0);
+    }
+
+    #[test]
+    fn sends_remaining_logs_before_terminal_progress() {
+        let (coalescer, sent) = coalescer();
+        coalescer.log("scan-1", "last line".to_string());
+        coalescer.progress(progress(ScanStage::Failed, 100));
+        assert_eq!(names(&sent), vec!["scan-log", "scan-progress"]);
+    }
+
+    #[test]
+    fn sends_each_finding_once() {
+        let (coalescer, sent) = coalescer();
+        let finding: ScanResult = serde_json::from_value(serde_json::json!({
+            "id": "finding-1",
+            "task_id": "scan-1",
+            "result_type": "vulnerability",
+            "severity": "high",
+            "title": "SQL Injection",
+            "description": null,
+            "raw_data": null,
+            "created_at": "2026-01-05T10:05:00Z",
+        }))
+        .unwrap();
+        coalescer.finding("scan-1", &finding);
+        coalescer.finding("scan-1", &finding);
+        // Another scan reporting the same id is a separate finding
+        coalescer.finding("scan-2", &finding);
+        assert_eq!(names(&sent), vec!["scan-finding", "scan-finding"]);
+    }
+
+    #[test]
+    fn finish_sends_remaining_logs_and_releases_the_scan() {
+        let (coalescer, sent) = coalescer();
+        coalescer.log("scan-1", "done".to_string());
+        coalescer.finish("scan-1");
+        coalescer.finish("scan-1");
+        assert_eq!(names(&sent), vec!["scan-log"]);
+        assert!(coalescer.tasks.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn bounds_events_and_buffer_under_a_log_flood() {
+        let (coalescer, sent) = coalescer();
+        let started = Instant::now();
+        for i in 0..10_000 {
+            coalescer.log("scan-1", format!("line {}", i));
+            coalescer.progress(progress(ScanStage::Crawling, (i / 100) as u8));
+        }
+        assert_eq!(coalescer.tasks.lock().unwrap()["scan-1"].logs.len(), MAX_PENDING_LOG_LINES);
+
+        coalescer.flush();
+        // At most one progress event per interval, plus the first
+        let intervals = (started.elapsed().as_millis() / PROGRESS_INTERVAL.as_millis()) as usize;
+        let sent = sent.lock().unwrap();
+        let progress_events = sent.iter().filter(|(name, _)| name == "scan-progress").count();
+        assert!(progress_events <= intervals + 2, "{} progress events in {} intervals", progress_events, intervals);
+        let batches: Vec<&serde_json::Value> = sent.iter().filter(|(name, _)| name == "scan-log").map(|(_, batch)| batch).collect();
+        assert_eq!(batches.len(), 1);
+        let lines = batches[0]["lines"].as_array().unwrap();
+        assert_eq!(lines.len(), MAX_PENDING_LOG_LINES);
+        assert_eq!(lines.last().unwrap()["message"], "line 9999");
+        assert_eq!(lines[0]["message"], "line 9000");
+        assert_eq!(batches[0]["dropped_lines"], 9000);
+    }
+}
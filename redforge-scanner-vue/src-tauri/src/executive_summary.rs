@@ -0,0 +1,252 @@
+/**
+ * Executive Summary
+ *
+ * 由掃描報告產生給管理階層閱讀的幾段文字：掃描概況、各嚴重程度的數量、最嚴重的發現、
+ * 值得注意的憑證與技術觀察，以及由加權嚴重度分數決定的整體風險評級。
+ *
+ * 文字只由報告內容決定 (日期以掃描完成時間為基準，不使用目前時間)，同一份報告永遠產生相同的摘要。
+ * 句型集中在 messages::SUMMARY_TEMPLATES，此處只負責挑選句子與代入數值。
+ */
+
+use crate::commands::scan::ScanReport;
+use crate::messages::summary_text;
+use crate::models::{ScanResult, Severity};
+use crate::report::severity_label;
+use crate::summary::ReportSummary;
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 各嚴重程度的權重，加總為風險分數
+const SEVERITY_WEIGHTS: [(Severity, u32); 5] = [
+    (Severity::Critical, 40),
+    (Severity::High, 15),
+    (Severity::Medium, 5),
+    (Severity::Low, 1),
+    (Severity::Info, 0),
+];
+/// 列出的最嚴重發現數量
+const TOP_FINDINGS: usize = 3;
+/// 列出的技術數量
+const TOP_TECHNOLOGIES: usize = 5;
+/// 憑證在此天數內到期時列為觀察
+const CERT_EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// 整體風險評級
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskRating {
+    Critical,
+    High,
+    Medium,
+    Low,
+    None,
+}
+
+impl RiskRating {
+    /// 分數門檻與權重對應：任何 Critical 即為 Critical，任何 High 至少為 High
+    pub fn from_score(score: u32) -> Self {
+        match score {
+            40.. => RiskRating::Critical,
+            15.. => RiskRating::High,
+            5.. => RiskRating::Medium,
+            1.. => RiskRating::Low,
+            0 => RiskRating::None,
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            RiskRating::Critical => "critical",
+            RiskRating::High => "high",
+            RiskRating::Medium => "medium",
+            RiskRating::Low => "low",
+            RiskRating::None => "none",
+        }
+    }
+}
+
+/// 報告的執行摘要
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExecutiveSummary {
+    pub task_id: String,
+    /// 依序為概況、最嚴重的發現、憑證與技術觀察 (沒有時省略) 與整體風險
+    pub paragraphs: Vec<String>,
+    pub risk_rating: RiskRating,
+    /// 加權嚴重度分數 (Critical 40、High 15、Medium 5、Low 1)
+    pub risk_score: u32,
+}
+
+impl ExecutiveSummary {
+    /// 以空行分隔段落的純文字
+    pub fn text(&self) -> String {
+        self.paragraphs.join("\n\n")
+    }
+}
+
+/// 產生報告的執行摘要；summary 為已排除抑制與誤報的統計
+pub fn build(report: &ScanReport, summary: &ReportSummary) -> ExecutiveSummary {
+    let risk_score = SEVERITY_WEIGHTS
+        .iter()
+        .map(|(severity, weight)| summary.count(&severity.to_string()) * weight)
+        .sum();
+    let risk_rating = RiskRating::from_score(risk_score);
+
+    let mut paragraphs = vec![overview(report, summary)];
+    if let Some(top) = top_findings(&report.vulnerabilities) {
+        paragraphs.push(top);
+    }
+    let observations = observations(report);
+    if !observations.is_empty() {
+        paragraphs.push(observations.join(""));
+    }
+    paragraphs.push(format!(
+        "{}{}",
+        summary_text("risk", &[("rating", &rating_label(risk_rating)), ("score", &risk_score.to_string())]),
+        summary_text(&format!("risk_advice.{}", risk_rating.key()), &[])
+    ));
+
+    ExecutiveSummary { task_id: report.task.id.clone(), paragraphs, risk_rating, risk_score }
+}
+
+fn rating_label(rating: RiskRating) -> String {
+    summary_text(&format!("rating.{}", rating.key()), &[])
+}
+
+fn separator() -> String {
+    summary_text("list_separator", &[])
+}
+
+/// 目標、掃描類型、耗時與各嚴重程度的數量
+fn overview(report: &ScanReport, summary: &ReportSummary) -> String {
+    let task = &report.task;
+    let scan_type = summary_text(&format!("scan_type.{}", task.scan_type), &[]);
+    let mut text = summary_text("overview", &[("target", &task.target_url), ("scan_type", &scan_type)]);
+
+    if let (Some(started), Some(completed)) = (task.started_at, task.completed_at) {
+        text.push_str(&summary_text(
+            "duration",
+            &[
+                ("started_at", &started.format("%Y-%m-%d %H:%M UTC").to_string()),
+                ("duration", &duration(completed - started)),
+            ],
+        ));
+    }
+
+    if summary.total == 0 {
+        text.push_str(&summary_text("no_findings", &[]));
+        return text;
+    }
+    let counts: Vec<String> = SEVERITY_WEIGHTS
+        .iter()
+        .map(|(severity, _)| (severity_label(severity), summary.count(&severity.to_string())))
+        .filter(|(_, count)| *count > 0)
+        .map(|(label, count)| summary_text("count_item", &[("severity", label), ("count", &count.to_string())]))
+        .collect();
+    text.push_str(&summary_text(
+        "counts",
+        &[("total", &summary.total.to_string()), ("counts", &counts.join(&separator()))],
+    ));
+    text
+}
+
+fn duration(elapsed: Duration) -> String {
+    let seconds = elapsed.num_seconds().max(0);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        summary_text("duration.hours", &[("hours", &hours.to_string()), ("minutes", &minutes.to_string())])
+    } else if minutes > 0 {
+        summary_text("duration.minutes", &[("minutes", &minutes.to_string()), ("seconds", &seconds.to_string())])
+    } else {
+        summary_text("duration.seconds", &[("seconds", &seconds.to_string())])
+    }
+}
+
+/// 依嚴重程度排序後的前 TOP_FINDINGS 個發現 (不含 Info)；同等級時保留報告中的順序
+fn top_findings(findings: &[ScanResult]) -> Option<String> {
+    let mut ranked: Vec<(&Severity, &ScanResult)> = findings
+        .iter()
+        .filter_map(|finding| finding.severity.as_ref().map(|severity| (severity, finding)))
+        .filter(|(severity, _)| **severity != Severity::Info)
+        .collect();
+    ranked.sort_by_key(|(severity, _)| severity_rank(severity));
+
+    let titles: Vec<String> = ranked
+        .iter()
+        .take(TOP_FINDINGS)
+        .map(|(severity, finding)| {
+            let title = finding.title.replace(['\r', '\n'], " ");
+            summary_text("top_finding_item", &[("title", title.trim()), ("severity", severity_label(severity))])
+        })
+        .collect();
+    (!titles.is_empty()).then(|| summary_text("top_findings", &[("titles", &titles.join(&separator()))]))
+}
+
+/// 憑證到期、SSL/TLS 評級與弱點、缺少 HSTS 與偵測到的技術
+fn observations(report: &ScanReport) -> Vec<String> {
+    let task = &report.task;
+    // 以掃描時間判斷憑證是否過期，同一份報告的摘要不隨產生時間改變
+    let scanned_at: DateTime<Utc> = task.completed_at.or(task.started_at).unwrap_or(task.created_at);
+    let mut sentences = Vec::new();
+
+    for ssl in &report.ssl_analysis {
+        let endpoint = ssl.analyzed_url.clone().unwrap_or_else(|| task.target_url.clone());
+        if let Some(valid_to) = ssl.valid_to {
+            let date = valid_to.format("%Y-%m-%d").to_string();
+            let days = (valid_to - scanned_at).num_days();
+            if valid_to < scanned_at {
+                sentences.push(summary_text("expired_cert", &[("endpoint", &endpoint), ("date", &date)]));
+            } else if days <= CERT_EXPIRY_WARNING_DAYS {
+                sentences.push(summary_text(
+                    "expiring_cert",
+                    &[("endpoint", &endpoint), ("date", &date), ("days", &days.to_string())],
+                ));
+            }
+        }
+        if let Some(grade) = ssl.grade.as_deref().filter(|grade| !grade.starts_with('A')) {
+            sentences.push(summary_text("ssl_grade", &[("endpoint", &endpoint), ("grade", grade)]));
+        }
+        // 只列出有 CVE 的已知弱點，憑證與協定設定問題由其他句子說明
+        let names: Vec<&str> = ssl
+            .tls_vulnerabilities
+            .iter()
+            .flatten()
+            .filter(|v| !v.cve_ids.is_empty())
+            .map(|v| v.name.as_str())
+            .collect();
+        if !names.is_empty() {
+            sentences.push(summary_text(
+                "tls_vulnerabilities",
+                &[("endpoint", &endpoint), ("names", &names.join(&separator()))],
+            ));
+        }
+    }
+
+    let missing_hsts = report
+        .headers
+        .iter()
+        .any(|header| header.header_name.eq_ignore_ascii_case("strict-transport-security") && !header.is_present);
+    if missing_hsts {
+        sentences.push(summary_text("missing_hsts", &[]));
+    }
+
+    let mut technologies: Vec<_> = report.technologies.iter().collect();
+    technologies.sort_by_key(|technology| std::cmp::Reverse(technology.confidence));
+    let technologies: Vec<String> = technologies
+        .iter()
+        .take(TOP_TECHNOLOGIES)
+        .map(|technology| match &technology.technology_version {
+            Some(version) => format!("{} {}", technology.technology_name, version),
+            None => technology.technology_name.clone(),
+        })
+        .collect();
+    if !technologies.is_empty() {
+        sentences.push(summary_text("technologies", &[("technologies", &technologies.join(&separator()))]));
+    }
+
+    sentences
+}
+
+fn severity_rank(severity: &Severity) -> usize {
+    SEVERITY_WEIGHTS.iter().position(|(s, _)| s == severity).unwrap_or(SEVERITY_WEIGHTS.len())
+}
@@ -0,0 +1,51 @@
+/**
+ * Favicon Cache
+ *
+ * 掃描目標的圖示存放在 favicons/ 目錄，檔名取自目標主機的雜湊，同一主機只保留最新的一份
+ */
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const FAVICON_DIR: &str = "favicons";
+
+static FAVICON_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// 啟動時呼叫：設定圖示快取目錄
+pub fn init(config_dir: &Path) {
+    let dir = config_dir.join(FAVICON_DIR);
+    let _ = std::fs::create_dir_all(&dir);
+    let _ = FAVICON_ROOT.set(dir);
+}
+
+/// 儲存目標的圖示並回傳檔案路徑；目錄未初始化或寫入失敗時為 None
+pub fn save(target: &str, bytes: &[u8], content_type: &str) -> Option<String> {
+    let root = FAVICON_ROOT.get()?;
+    let host = reqwest::Url::parse(target)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase))
+        .unwrap_or_else(|| target.to_string());
+    let hash = format!("{:x}", Sha256::digest(host.as_bytes()));
+    let path = root.join(format!("{}.{}", &hash[..16], extension(content_type, bytes)));
+
+    match crate::output::write_atomic(&path, bytes, true) {
+        Ok(written) => Some(written.path),
+        Err(e) => {
+            tracing::warn!("⚠️  無法儲存圖示 {}: {}", host, e);
+            None
+        }
+    }
+}
+
+fn extension(content_type: &str, bytes: &[u8]) -> &'static str {
+    match content_type.split(';').next().unwrap_or_default().trim() {
+        "image/png" => "png",
+        "image/svg+xml" => "svg",
+        "image/gif" => "gif",
+        "image/jpeg" => "jpg",
+        "image/webp" => "webp",
+        _ if bytes.starts_with(b"\x89PNG") => "png",
+        _ => "ico",
+    }
+}
@@ -0,0 +1,89 @@
+/**
+ * Collaboration Keys
+ *
+ * 離線協作使用的 Ed25519 簽章金鑰，第一次使用時產生並以 PKCS#8 存放在設定目錄。
+ * 稽核紀錄等交付給客戶的檔案以此金鑰簽章，接收方用附帶的公鑰驗證。
+ * 工作區設定檔在匯入時以 `verify` 驗證。
+ */
+
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+const KEY_FILE: &str = "collaboration-ed25519.pk8";
+
+static KEY_PATH: OnceLock<PathBuf> = OnceLock::new();
+// 產生金鑰時避免兩個請求同時建立不同的金鑰
+static KEY_LOCK: Mutex<()> = Mutex::new(());
+
+/// 啟動時呼叫：設定金鑰存放目錄
+pub fn init(config_dir: &std::path::Path) {
+    let _ = KEY_PATH.set(config_dir.join("keys").join(KEY_FILE));
+}
+
+/// 簽章結果
+pub struct Signature {
+    pub algorithm: &'static str,
+    pub public_key: String,
+    pub signature: String,
+}
+
+/// 以協作金鑰簽章，公鑰與簽章皆為 base64
+pub fn sign(message: &[u8]) -> Result<Signature, String> {
+    let key_pair = collaboration_key_pair()?;
+    let engine = base64::engine::general_purpose::STANDARD;
+
+    Ok(Signature {
+        algorithm: "Ed25519",
+        public_key: engine.encode(key_pair.public_key().as_ref()),
+        signature: engine.encode(key_pair.sign(message).as_ref()),
+    })
+}
+
+/// 驗證 base64 公鑰對 message 的 Ed25519 簽章
+pub fn verify(public_key: &str, message: &[u8], signature: &str) -> Result<(), String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let public_key = engine.decode(public_key).map_err(|_| "公鑰不是有效的 base64".to_string())?;
+    let signature = engine.decode(signature).map_err(|_| "簽章不是有效的 base64".to_string())?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(message, &signature)
+        .map_err(|_| "簽章驗證失敗，檔案可能已被修改".to_string())
+}
+
+/// 本機協作金鑰的公鑰 (base64)
+pub fn public_key() -> Result<String, String> {
+    let key_pair = collaboration_key_pair()?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(key_pair.public_key().as_ref()))
+}
+
+fn collaboration_key_pair() -> Result<Ed25519KeyPair, String> {
+    let path = KEY_PATH.get().ok_or("協作金鑰目錄未初始化")?;
+    let _guard = KEY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    if path.exists() {
+        let pkcs8 = std::fs::read(path).map_err(|e| format!("無法讀取協作金鑰 {}: {}", path.display(), e))?;
+        return Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|e| format!("協作金鑰格式無效: {}", e));
+    }
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).map_err(|_| "無法產生協作金鑰".to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("無法建立金鑰目錄: {}", e))?;
+    }
+    crate::output::write_atomic(path, pkcs8.as_ref(), false)?;
+    restrict_permissions(path);
+    tracing::info!("🔑 已產生協作簽章金鑰: {}", path.display());
+
+    Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|e| format!("協作金鑰格式無效: {}", e))
+}
+
+/// 限制檔案只有使用者可讀寫 (金鑰與包含密碼的設定檔)
+#[cfg(unix)]
+pub fn restrict_permissions(path: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+}
+
+#[cfg(not(unix))]
+pub fn restrict_permissions(_path: &std::path::Path) {}
@@ -2,19 +2,65 @@ mod commands;
 mod models;
 mod scanners;
 mod database;
+mod messages;
+mod output;
+mod audit;
+mod keys;
+mod redaction;
+mod canary;
+mod attack_chains;
+mod events;
+mod favicons;
+mod summary;
+mod cwe;
+mod logging;
+mod settings;
+mod report;
+mod report_pdf;
+mod executive_summary;
+mod csv_export;
 
-use commands::scan::{ScanState, start_scan, get_scan_status, list_scans, get_scan_report};
-use commands::collaboration::{export_scan_data, deduplicate_import_data, import_scan_data};
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use std::collections::HashMap;
+use commands::scan::{ScanState, start_scan, rescan, pause_scan, resume_scan_inflight, cancel_scan, list_stages, get_scan_status, wait_for_scan, list_scans, delete_scan, delete_scans, get_scan_report, resolve_target_redirect};
+use commands::collaboration::{export_scan_data, export_scan_data_to_file, deduplicate_import_data, import_scan_data};
+use commands::dashboard::{get_dashboard_stats, generate_activity_summary, get_check_efficiency};
+use commands::baseline::{set_baseline, clear_baseline, get_baseline};
+use commands::storage::get_storage_status;
+use commands::schema::get_api_schema;
+use commands::audit::export_audit_log;
+use commands::redaction::{list_redaction_profiles, save_redaction_profile, get_redaction_mapping};
+use commands::findings::{export_findings_csv, get_findings, update_finding_status};
+use commands::canary::lookup_canary;
+use commands::changes::check_target_changes;
+use commands::compare::{compare_scans, diff_scans};
+use commands::workspace::{export_workspace_settings, import_workspace_settings};
+use commands::attack_chains::{list_attack_chain_rules, save_attack_chain_rule};
+use commands::logs::{get_scan_logs, set_log_level};
+use commands::settings::{get_app_settings, save_app_settings, test_proxy_connection};
+use commands::report::{generate_report, get_executive_summary};
+use tauri::Manager;
+
+pub use commands::schema::{api_schema, API_SCHEMA_VERSION};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .manage(ScanState {
-            current_tasks: Arc::new(Mutex::new(Vec::new())),
-            scan_results: Arc::new(Mutex::new(HashMap::new())),
+        .setup(|app| {
+            logging::init(app.path().app_data_dir().ok().as_deref());
+            if let Ok(dir) = app.path().app_config_dir() {
+                output::init(&dir);
+                audit::init(&dir);
+                keys::init(&dir);
+                redaction::init(&dir);
+                canary::init(&dir);
+                attack_chains::init(&dir);
+                favicons::init(&dir);
+                settings::init(&dir);
+            }
+            let (store, storage) = database::store::open_default(app.handle());
+            let state = ScanState::new(store, Some(storage), Some(app.handle().clone()));
+            tauri::async_runtime::block_on(state.restore());
+            app.manage(state);
+            Ok(())
         })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
@@ -26,12 +72,52 @@ pub fn run() {
         )
         .invoke_handler(tauri::generate_handler![
             start_scan,
+            rescan,
+            pause_scan,
+            cancel_scan,
+            resume_scan_inflight,
+            list_stages,
             get_scan_status,
+            wait_for_scan,
             list_scans,
+            delete_scan,
+            delete_scans,
             get_scan_report,
+            resolve_target_redirect,
             export_scan_data,
+            export_scan_data_to_file,
             deduplicate_import_data,
             import_scan_data,
+            get_dashboard_stats,
+            generate_activity_summary,
+            get_check_efficiency,
+            set_baseline,
+            clear_baseline,
+            get_baseline,
+            get_storage_status,
+            get_api_schema,
+            export_audit_log,
+            list_redaction_profiles,
+            save_redaction_profile,
+            get_redaction_mapping,
+            get_findings,
+            update_finding_status,
+            export_findings_csv,
+            lookup_canary,
+            check_target_changes,
+            compare_scans,
+            diff_scans,
+            export_workspace_settings,
+            import_workspace_settings,
+            list_attack_chain_rules,
+            save_attack_chain_rule,
+            get_scan_logs,
+            set_log_level,
+            get_app_settings,
+            save_app_settings,
+            test_proxy_connection,
+            generate_report,
+            get_executive_summary,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,287 @@
+/**
+ * Structured Logging
+ *
+ * 後端日誌以 tracing 記錄：掃描在 `scan` span (task_id) 內執行，各檢查在 `check` span 內執行，
+ * 事件依所在 span 的 task_id 歸屬到掃描。每個事件同時送到：
+ *
+ * - 主控台 (開發時)
+ * - app 資料目錄的 logs/redforge.log，每日輪替；打包後的 app 沒有主控台，回報問題時附上此檔
+ * - 每個掃描的記憶體 ring buffer (最近 MAX_SCAN_LINES 行)，由 get_scan_logs 查詢
+ *
+ * 送出的請求為 debug，發現為 info，掃描器錯誤為 warn / error。其他 crate 只記錄 warn 以上；
+ * 本程式的層級預設為 info，可以在執行中以 set_log_level 調整，不需重新啟動。
+ */
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+const LOG_DIR: &str = "logs";
+const LOG_FILE: &str = "redforge.log";
+/// 每個掃描保留的日誌行數，超過時捨棄最舊的
+const MAX_SCAN_LINES: usize = 5000;
+/// 記憶體中保留日誌的掃描數，超過時捨棄最早開始記錄的掃描
+const MAX_SCANS: usize = 20;
+/// get_scan_logs 未指定數量時回傳的行數
+pub const DEFAULT_LIMIT: usize = 500;
+/// 本程式事件的 tracing target 前綴 (crate 名稱)
+const APP_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+/// 日誌層級，由低到高排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Trace => LevelFilter::TRACE,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Error => LevelFilter::ERROR,
+        }
+    }
+}
+
+impl From<&tracing::Level> for LogLevel {
+    fn from(level: &tracing::Level) -> Self {
+        match *level {
+            tracing::Level::TRACE => LogLevel::Trace,
+            tracing::Level::DEBUG => LogLevel::Debug,
+            tracing::Level::INFO => LogLevel::Info,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::ERROR => LogLevel::Error,
+        }
+    }
+}
+
+/// 掃描的一行日誌
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    // 產生日誌的模組，例如 redforge_scanner_vue_lib::scanners::control
+    pub target: String,
+    // 事件所在的檢查；不在檢查內時為 None
+    #[serde(default)]
+    pub check: Option<String>,
+    pub message: String,
+    // 事件的其他欄位
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+struct LoggingState {
+    level: reload::Handle<Targets, Registry>,
+    // 保留到程式結束，確保檔案的緩衝寫出
+    _file: Option<WorkerGuard>,
+}
+
+static LOGGING: OnceLock<LoggingState> = OnceLock::new();
+
+/// 啟動時呼叫：安裝全域的 tracing subscriber；data_dir 為 None 時不寫入檔案
+pub fn init(data_dir: Option<&Path>) {
+    let (filter, handle) = reload::Layer::new(targets(LogLevel::Info));
+    let (file_layer, guard) = match data_dir.and_then(file_appender) {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(fmt::layer().with_ansi(false).with_writer(writer)), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .with(file_layer)
+        .with(ScanLogLayer)
+        .try_init();
+    if installed.is_ok() {
+        let _ = LOGGING.set(LoggingState { level: handle, _file: guard });
+    }
+}
+
+fn file_appender(data_dir: &Path) -> Option<RollingFileAppender> {
+    let dir = data_dir.join(LOG_DIR);
+    std::fs::create_dir_all(&dir).ok()?;
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE)
+        .build(dir)
+        .ok()
+}
+
+/// 本程式使用 level，其他 crate (reqwest、hyper、rustls) 只記錄 warn 以上
+fn targets(level: LogLevel) -> Targets {
+    Targets::new()
+        .with_default(LevelFilter::WARN)
+        .with_target(APP_TARGET, level.filter())
+}
+
+/// 調整本程式的日誌層級，立即套用到進行中的掃描
+pub fn set_level(level: LogLevel) -> Result<(), String> {
+    let state = LOGGING.get().ok_or_else(|| "日誌尚未初始化".to_string())?;
+    state
+        .level
+        .reload(targets(level))
+        .map_err(|e| format!("無法調整日誌層級: {}", e))
+}
+
+/// 掃描最近的 limit 行中層級不低於 min_level 的日誌，依時間排序
+pub fn scan_logs(task_id: &str, min_level: LogLevel, limit: usize) -> Vec<ScanLogEntry> {
+    let logs = buffers();
+    let Some(lines) = logs.scans.get(task_id) else {
+        return Vec::new();
+    };
+    let mut matching: Vec<ScanLogEntry> = lines
+        .iter()
+        .rev()
+        .filter(|entry| entry.level >= min_level)
+        .take(limit)
+        .cloned()
+        .collect();
+    matching.reverse();
+    matching
+}
+
+/// 刪除掃描時一併捨棄其日誌
+pub fn discard(task_id: &str) {
+    let mut logs = buffers();
+    logs.scans.remove(task_id);
+    logs.order.retain(|id| id != task_id);
+}
+
+#[derive(Default)]
+struct ScanLogs {
+    scans: HashMap<String, VecDeque<ScanLogEntry>>,
+    // 開始記錄的順序，超過 MAX_SCANS 時捨棄最早的
+    order: VecDeque<String>,
+}
+
+impl ScanLogs {
+    fn push(&mut self, task_id: &str, entry: ScanLogEntry) {
+        if !self.scans.contains_key(task_id) {
+            if self.order.len() >= MAX_SCANS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.scans.remove(&oldest);
+                }
+            }
+            self.order.push_back(task_id.to_string());
+        }
+        let lines = self.scans.entry(task_id.to_string()).or_default();
+        if lines.len() >= MAX_SCAN_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(entry);
+    }
+}
+
+fn buffers() -> MutexGuard<'static, ScanLogs> {
+    static SCAN_LOGS: OnceLock<Mutex<ScanLogs>> = OnceLock::new();
+    SCAN_LOGS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// span 建立時記錄的 task_id 與 check
+struct SpanScope {
+    task_id: Option<String>,
+    check: Option<String>,
+}
+
+/// 將屬於掃描的事件寫入該掃描的 ring buffer
+struct ScanLogLayer;
+
+impl<S> Layer<S> for ScanLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = FieldVisitor::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanScope {
+                task_id: fields.values.remove("task_id"),
+                check: fields.values.remove("check"),
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = FieldVisitor::default();
+        event.record(&mut fields);
+        let mut task_id = fields.values.remove("task_id");
+        let mut check = fields.values.remove("check");
+
+        // 事件本身的欄位優先，其次是由內而外的 span
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope {
+                if let Some(recorded) = span.extensions().get::<SpanScope>() {
+                    task_id = task_id.or_else(|| recorded.task_id.clone());
+                    check = check.or_else(|| recorded.check.clone());
+                }
+            }
+        }
+        let Some(task_id) = task_id else {
+            return;
+        };
+
+        let metadata = event.metadata();
+        buffers().push(
+            &task_id,
+            ScanLogEntry {
+                timestamp: Utc::now(),
+                level: metadata.level().into(),
+                target: metadata.target().to_string(),
+                check,
+                message: fields.message.unwrap_or_default(),
+                fields: fields.values,
+            },
+        );
+    }
+}
+
+/// 事件與 span 的欄位；message 另外取出
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    values: BTreeMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{:?}", value));
+    }
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = Some(value);
+        } else {
+            self.values.insert(field.name().to_string(), value);
+        }
+    }
+}
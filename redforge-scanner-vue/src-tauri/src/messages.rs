@@ -0,0 +1,116 @@
+/**
+ * Message Catalog
+ *
+ * 使用者可見的文字集中在此，依語系查詢；找不到的語系退回預設的繁體中文
+ */
+
+use crate::models::ScanStage;
+
+pub const DEFAULT_LOCALE: &str = "zh-TW";
+
+// (鍵, 繁體中文, English)
+const STAGE_LABELS: &[(&str, &str, &str)] = &[
+    ("queued", "等待中", "Queued"),
+    ("resolving", "解析目標", "Resolving target"),
+    ("headers", "HTTP 標頭", "HTTP headers"),
+    ("ssl", "SSL/TLS 分析", "SSL/TLS analysis"),
+    ("ports", "連接埠掃描", "Port scan"),
+    ("crawling", "爬取頁面", "Crawling"),
+    ("owasp.a01", "A01: 存取控制缺陷", "A01: Broken Access Control"),
+    ("owasp.a02", "A02: 加密機制失效", "A02: Cryptographic Failures"),
+    ("owasp.a03", "A03: 注入攻擊", "A03: Injection"),
+    ("owasp.a04", "A04: 不安全的設計", "A04: Insecure Design"),
+    ("owasp.a05", "A05: 安全設定錯誤", "A05: Security Misconfiguration"),
+    ("owasp.a06", "A06: 易受攻擊和過時的元件", "A06: Vulnerable and Outdated Components"),
+    ("owasp.a07", "A07: 認證及驗證機制失效", "A07: Identification and Authentication Failures"),
+    ("owasp.a08", "A08: 軟體及資料完整性失效", "A08: Software and Data Integrity Failures"),
+    ("owasp.a09", "A09: 資安記錄及監控失效", "A09: Security Logging and Monitoring Failures"),
+    ("owasp.a10", "A10: 伺服器端請求偽造", "A10: Server-Side Request Forgery"),
+    ("tech_detection", "技術檢測", "Technology detection"),
+    ("reporting", "產生報告", "Reporting"),
+    ("finalizing", "完成收尾", "Finalizing"),
+    ("paused", "已暫停", "Paused"),
+    ("completed", "已完成", "Completed"),
+    ("failed", "失敗", "Failed"),
+    ("cancelled", "已取消", "Cancelled"),
+];
+
+/// 預設語系的階段名稱
+pub fn stage_label(stage: &ScanStage) -> String {
+    stage_label_for(stage, DEFAULT_LOCALE)
+}
+
+/// 指定語系的階段名稱，目錄中沒有的鍵直接回傳鍵本身
+pub fn stage_label_for(stage: &ScanStage, locale: &str) -> String {
+    let key = stage.message_key();
+    let english = locale.to_lowercase().starts_with("en");
+
+    STAGE_LABELS
+        .iter()
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, zh, en)| if english { *en } else { *zh }.to_string())
+        .unwrap_or(key)
+}
+
+// 執行摘要的句型 (鍵, 繁體中文)；{name} 為代入的值。翻譯時在此表加入其他語系
+const SUMMARY_TEMPLATES: &[(&str, &str)] = &[
+    ("overview", "本報告涵蓋對 {target} 的{scan_type}掃描。"),
+    ("duration", "掃描於 {started_at} 開始，耗時 {duration}。"),
+    ("duration.hours", "{hours} 小時 {minutes} 分"),
+    ("duration.minutes", "{minutes} 分 {seconds} 秒"),
+    ("duration.seconds", "{seconds} 秒"),
+    ("counts", "共發現 {total} 個需要處理的問題：{counts}。"),
+    ("count_item", "{severity} {count} 個"),
+    ("no_findings", "本次掃描沒有發現需要處理的問題。"),
+    ("top_findings", "最嚴重的發現為{titles}。"),
+    ("top_finding_item", "「{title}」({severity})"),
+    ("expired_cert", "{endpoint} 的憑證已於 {date} 過期。"),
+    ("expiring_cert", "{endpoint} 的憑證將於 {date} 到期，剩餘 {days} 天。"),
+    ("ssl_grade", "{endpoint} 的 SSL/TLS 評級為 {grade}。"),
+    ("tls_vulnerabilities", "{endpoint} 存在 TLS 弱點：{names}。"),
+    ("missing_hsts", "網站未設定 HSTS (Strict-Transport-Security)，使用者仍可能經由未加密的連線存取。"),
+    ("technologies", "偵測到的技術包括 {technologies}。"),
+    ("risk", "整體風險評級為 {rating} (加權嚴重度分數 {score})。"),
+    ("risk_advice.critical", "建議立即處理 Critical 等級的發現，修正前評估是否暫停受影響的服務。"),
+    ("risk_advice.high", "建議優先於近期內修正 High 等級的發現。"),
+    ("risk_advice.medium", "建議將發現排入一般的修正排程。"),
+    ("risk_advice.low", "發現的風險較低，可於例行維護時一併處理。"),
+    ("risk_advice.none", "目前沒有需要處理的風險，建議定期重新掃描。"),
+    ("rating.critical", "Critical"),
+    ("rating.high", "High"),
+    ("rating.medium", "Medium"),
+    ("rating.low", "Low"),
+    ("rating.none", "無"),
+    ("scan_type.full", "完整"),
+    ("scan_type.quick", "快速"),
+    ("scan_type.vulnerability", "漏洞"),
+    ("scan_type.port", "連接埠"),
+    ("scan_type.ssl", "SSL/TLS"),
+    ("scan_type.headers", "安全標頭"),
+    ("list_separator", "、"),
+];
+
+/// 執行摘要的句型代入 args 後的文字；目錄中沒有的鍵直接回傳鍵本身，args 中沒有的 {name} 保留原樣
+pub fn summary_text(key: &str, args: &[(&str, &str)]) -> String {
+    let Some((_, template)) = SUMMARY_TEMPLATES.iter().find(|(k, _)| *k == key) else {
+        return key.to_string();
+    };
+
+    // 逐段代入，值中的 {name} 不會再被替換
+    let mut text = String::with_capacity(template.len());
+    let mut rest = *template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        text.push_str(&rest[..start]);
+        let name = &rest[start + 1..start + end];
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => text.push_str(value),
+            None => text.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    text
+}
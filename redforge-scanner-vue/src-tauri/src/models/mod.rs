@@ -1,7 +1,10 @@
+use crate::messages;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScanTask {
     pub id: String,
     pub target_url: String,
@@ -10,9 +13,686 @@ pub struct ScanTask {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    // 開始掃描時解析完成的設定快照 (不含敏感值) 與其雜湊，用於重現結果
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
+    #[serde(default)]
+    pub config_hash: Option<String>,
+    // 使用者原始輸入，與 target_url 不同時才有值 (例如輸入 example.com，實際掃描 https://example.com/)
+    #[serde(default)]
+    pub requested_target: Option<String>,
+    // 掃描失敗時的錯誤訊息
+    #[serde(default)]
+    pub error: Option<String>,
+    // 錯誤的種類，決定介面是否建議重試
+    #[serde(default)]
+    pub error_kind: Option<ScanErrorKind>,
+    // 目標永久重定向到其他網域時的證據與處理方式
+    #[serde(default)]
+    pub redirect: Option<TargetRedirect>,
+    // 目標自此掃描後沒有變動，本次掃描已略過
+    #[serde(default)]
+    pub unchanged_since: Option<String>,
+    // 基礎頁面的標題與快取的圖示檔案，供掃描列表顯示
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub favicon_path: Option<String>,
+    // 請求預算的使用情況；掃描進行中由 get_scan_status 即時填入，完成後保留最終狀態
+    #[serde(default)]
+    pub request_budget: Option<RequestBudgetStatus>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 掃描失敗的原因種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanErrorKind {
+    Network,
+    Timeout,
+    Dns,
+    Tls,
+    Parse,
+    InvalidTarget,
+    Config,
+    Blocked,
+    Interrupted,
+    Internal,
+    Proxy,
+}
+
+impl ScanErrorKind {
+    /// 暫時性的網路問題，重新掃描可能成功；無效的目標或設定重試也會失敗
+    pub fn retryable(&self) -> bool {
+        matches!(self, ScanErrorKind::Network | ScanErrorKind::Timeout)
+    }
+}
+
+/// 單次掃描請求預算的使用情況
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RequestBudgetStatus {
+    // 整個掃描的請求數上限，未設定時為 None
+    pub max_requests: Option<u64>,
+    pub used: u64,
+    pub remaining: Option<u64>,
+    // 預算已用完，之後的檢查不會執行
+    pub exhausted: bool,
+    // 預算用完而未執行的檢查 id
+    pub skipped: Vec<String>,
+    // 達到檢查或整體預算而提前結束的檢查 id
+    pub truncated: Vec<String>,
+}
+
+/// 基礎 URL 永久重定向到其他網域
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TargetRedirect {
+    pub from: String,
+    pub to: String,
+    // 依序經過的重定向
+    pub hops: Vec<RedirectHop>,
+    pub decision: RedirectDecision,
+    // 依決定啟動的後續掃描
+    #[serde(default)]
+    pub follow_up_task_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status: u16,
+    pub location: String,
+}
+
+/// 跨網域重定向的處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectDecision {
+    // 掃描已停止，等待使用者決定
+    Stopped,
+    // 改掃描重定向後的目標
+    RescanNew,
+    // 仍掃描原目標，所有請求固定在原主機
+    ForceOriginal,
+}
+
+/// 單次掃描的設定，所有欄位皆有預設值，前端可省略
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub client_identity: Option<ClientIdentity>,
+    // 以已登入的身分掃描：請求目標來源時帶上 cookie、bearer token 或 basic 認證
+    pub auth: Option<ScanAuth>,
+    // 與目標的核可基準比較，只突顯新的偏差
+    pub compare_to_baseline: bool,
+    // 忽略技術前置條件，執行所有檢查
+    pub ignore_preconditions: bool,
+    // 送出少見請求型式比較代理層的處理差異 (不允許 smuggling 探測時的替代檢查)
+    pub proxy_disagreement_check: bool,
+    // 暖身探測判定目標封鎖掃描流量時仍繼續掃描 (所有發現標記為低信心度)
+    pub proceed_when_blocked: bool,
+    // 同意頁 (OneTrust / Cookiebot / Didomi) 繞過時使用的 cookie，同名時取代預設的最小同意值
+    pub consent_cookies: HashMap<String, String>,
+    // 同意頁繞過成功後由掃描流程設定，不屬於使用者設定
+    #[serde(skip)]
+    pub consent_cookie_header: Option<String>,
+    // 跟隨頁面連結時的安全規則與預算
+    pub crawl: CrawlSettings,
+    // 限時掃描：超過秒數後不再啟動新的檢查，檢查依優先順序執行
+    pub max_duration_secs: Option<u64>,
+    // 目標永久重定向到其他網域時仍掃描原目標 (不跟隨到其他主機)
+    pub force_original_host: bool,
+    // 強制掃描原目標時由掃描流程設定，重定向只能停留在此主機
+    #[serde(skip)]
+    pub pinned_host: Option<String>,
+    // 目標自上次掃描後內容沒有變動時略過完整掃描 (排程掃描使用)
+    pub skip_if_unchanged: bool,
+    // 對上傳表單實際上傳 canary 檔案 (文字檔與 HTML)，預設只偵測表單不上傳
+    pub upload_tests: bool,
+    // 純 SSL 掃描不取得基礎頁面；啟用時多一次請求取得頁面標題與圖示
+    pub capture_identity_without_warmup: bool,
+    // 連接埠掃描 (port 與 full 掃描) 的連接埠清單、逾時與並行上限
+    pub port_scan: PortScanSettings,
+    // 所有掃描器共用的 HTTP 請求設定：逾時、重定向、憑證驗證、User-Agent 與請求節流
+    pub http: HttpSettings,
+    // OWASP Top 10 掃描要執行的類別
+    pub owasp: OwaspConfig,
+    // passive 只分析原本就會取得的回應，safe 加上不具破壞性的探測，full 包含注入 payload 與重複請求
+    pub scan_mode: ScanMode,
+    // 盲 SSRF 偵測使用的 OOB 回呼伺服器；未設定時 SSRF 只偵測回應中出現的內部內容
+    pub collaborator: Option<CollaboratorSettings>,
+    // 備份與暫存檔探測的探測數上限與額外的副檔名變化
+    pub backup_files: BackupFileSettings,
+    // 以內建的弱金鑰清單比對 JWT 的 HMAC 簽章 (只在本機計算，不送出請求)
+    pub jwt_weak_keys: bool,
+    // SSL 分析時送出主動的 TLS 弱點探測 (例如 Heartbleed 的畸形 heartbeat)，預設關閉
+    pub tls_active_probes: bool,
+    // 認證端點速率限制測試的突發請求數與請求間隔的抖動
+    pub rate_limit_probe: RateLimitProbeSettings,
+    // 所有掃描流量經過的代理；未設定時使用全域設定的代理
+    pub proxy: Option<ProxySettings>,
+}
+
+/// 掃描的侵入程度；每個檢查標記需要的最低模式，低於該模式時略過
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanMode {
+    // 只分析掃描本來就會取得的回應 (標頭、cookie、HTML、技術)
+    Passive,
+    // 加上不具破壞性的探測 (管理路徑、敏感檔案、重定向)，不送注入 payload 也不重複大量請求
+    Safe,
+    #[default]
+    Full,
+}
+
+impl std::fmt::Display for ScanMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanMode::Passive => write!(f, "passive"),
+            ScanMode::Safe => write!(f, "safe"),
+            ScanMode::Full => write!(f, "full"),
+        }
+    }
+}
+
+/// OWASP Top 10 掃描設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct OwaspConfig {
+    // 要執行的類別 ("A01" - "A10")，預設全部；未啟用的類別完全不送出請求
+    // 依名稱排序序列化，設定快照的雜湊才會穩定
+    #[serde(serialize_with = "sorted")]
+    pub enabled_categories: HashSet<String>,
+    // 同時執行的類別數
+    pub parallelism: usize,
+}
+
+fn sorted<S: serde::Serializer>(set: &HashSet<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut values: Vec<&String> = set.iter().collect();
+    values.sort();
+    serializer.collect_seq(values)
+}
+
+impl Default for OwaspConfig {
+    fn default() -> Self {
+        Self {
+            enabled_categories: OWASP_CATEGORIES.iter().map(|c| c.to_string()).collect(),
+            parallelism: 3,
+        }
+    }
+}
+
+impl OwaspConfig {
+    /// 類別名稱不分大小寫；未知的類別或沒有啟用任何類別時回傳錯誤
+    pub fn validate(&self) -> Result<(), String> {
+        let mut unknown: Vec<&str> = self.enabled_categories
+            .iter()
+            .map(String::as_str)
+            .filter(|c| !OWASP_CATEGORIES.contains(&c.trim().to_uppercase().as_str()))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(format!("未知的 OWASP 類別: {} (可用: {})", unknown.join(", "), OWASP_CATEGORIES.join(", ")));
+        }
+        if self.enabled_categories.is_empty() {
+            return Err("至少需要啟用一個 OWASP 類別".to_string());
+        }
+        if self.parallelism == 0 {
+            return Err("同時執行的 OWASP 類別數 (parallelism) 必須大於 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, category: &str) -> bool {
+        self.enabled_categories.iter().any(|c| c.trim().eq_ignore_ascii_case(category))
+    }
+}
+
+/// 本次掃描實際執行與未執行的 OWASP 類別，避免部分掃描被誤讀為全部通過
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OwaspCategoryReport {
+    pub ran: Vec<String>,
+    // 所有未執行的類別 (設定停用、限時掃描時間用完或掃描取消)
+    pub skipped: Vec<String>,
+    // skipped 中因設定停用的類別
+    pub disabled: Vec<String>,
+    // 執行失敗的類別與錯誤，不影響其他類別的結果
+    #[serde(default)]
+    pub errors: Vec<OwaspCategoryError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OwaspCategoryError {
+    pub category: String,
+    pub message: String,
+}
+
+/// 爬取安全規則；樣式為正規表示式，比對 URL 的路徑與查詢字串 (不分大小寫)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CrawlSettings {
+    // 額外允許的樣式；非空時只爬符合的 URL
+    pub allow_patterns: Vec<String>,
+    // 額外拒絕的樣式，與預設拒絕清單 (logout、delete…) 合併，拒絕永遠優先
+    pub deny_patterns: Vec<String>,
+    // 目錄深度上限 (/a/b/page 為 2)
+    pub max_depth: usize,
+    pub max_pages_per_directory: usize,
+    pub max_pages: usize,
+    // 注入檢查前爬取頁面的連結深度 (基礎頁面為 0) 與頁數預算
+    pub discovery_depth: usize,
+    pub discovery_max_pages: usize,
+}
+
+impl Default for CrawlSettings {
+    fn default() -> Self {
+        Self {
+            allow_patterns: Vec::new(),
+            deny_patterns: Vec::new(),
+            max_depth: 5,
+            max_pages_per_directory: 50,
+            max_pages: 500,
+            discovery_depth: 2,
+            discovery_max_pages: 50,
+        }
+    }
+}
+
+/// 反序列化單一物件或陣列 (欄位由單一值改為清單時，舊資料仍可讀取)；null 視為空清單
+pub fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+
+    Ok(match Option::<OneOrMany<T>>::deserialize(deserializer)? {
+        Some(OneOrMany::Many(values)) => values,
+        Some(OneOrMany::One(value)) => vec![value],
+        None => Vec::new(),
+    })
+}
+
+/// HTTP 請求設定；未設定的項目沿用各掃描器原本的預設 (逾時 10-15 秒、重定向策略依檢查而定)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct HttpSettings {
+    // 單一請求的逾時 (秒)
+    pub timeout_secs: Option<u64>,
+    // 是否跟隨重定向；OWASP 檢查預設不跟隨，其他掃描器預設跟隨
+    pub follow_redirects: Option<bool>,
+    // 驗證目標憑證；預設不驗證 (憑證問題由 SSL 分析回報)
+    pub verify_tls: bool,
+    pub user_agent: Option<String>,
+    // 單次掃描的請求數上限 (預設 1000，設為 null 不限制)，用完後其餘檢查略過並記錄在涵蓋範圍
+    pub max_requests: Option<u64>,
+    // 各檢查的軟性請求預算 (檢查 id，例如 "owasp.a03")，超過時只停止該檢查
+    pub check_budgets: BTreeMap<String, u64>,
+    // 沒有個別設定的檢查最多使用請求數上限的百分比
+    pub check_budget_percent: u8,
+    // 每個請求送出前的固定延遲 (毫秒)
+    pub request_delay_ms: u64,
+    // 整個掃描對目標的每秒請求數上限 (所有掃描器與連線共用)，未設定時不限制
+    pub requests_per_second: Option<f64>,
+    // 路徑探測 (管理後台、敏感檔案、目錄列表) 同時送出的請求數；設定延遲時一律逐一送出
+    pub concurrency: usize,
+    // 發現附帶的回應內容保留的位元組數，超過的部分截斷
+    pub evidence_body_bytes: usize,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: None,
+            follow_redirects: None,
+            verify_tls: false,
+            user_agent: None,
+            max_requests: Some(1000),
+            check_budgets: BTreeMap::new(),
+            check_budget_percent: 40,
+            request_delay_ms: 0,
+            requests_per_second: None,
+            concurrency: 5,
+            evidence_body_bytes: 4096,
+        }
+    }
+}
+
+impl HttpSettings {
+    /// 路徑探測實際使用的並行數：設定請求延遲時逐一送出，避免延遲被並行抵銷
+    pub fn effective_concurrency(&self) -> usize {
+        if self.request_delay_ms > 0 { 1 } else { self.concurrency.max(1) }
+    }
+
+    /// 檢查設定組合，錯誤訊息說明哪個欄位無效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_secs == Some(0) {
+            return Err("請求逾時 (timeout_secs) 必須大於 0 秒".to_string());
+        }
+        if self.concurrency == 0 {
+            return Err("並行請求數 (concurrency) 必須大於 0".to_string());
+        }
+        if self.max_requests == Some(0) {
+            return Err("請求數上限 (max_requests) 必須大於 0".to_string());
+        }
+        if !(1..=100).contains(&self.check_budget_percent) {
+            return Err("檢查的請求預算比例 (check_budget_percent) 必須介於 1 到 100".to_string());
+        }
+        if let Some((check, _)) = self.check_budgets.iter().find(|(_, budget)| **budget == 0) {
+            return Err(format!("檢查 {} 的請求預算 (check_budgets) 必須大於 0", check));
+        }
+        if self.requests_per_second.is_some_and(|rps| !rps.is_finite() || rps <= 0.0) {
+            return Err("每秒請求數上限 (requests_per_second) 必須大於 0".to_string());
+        }
+        if let Some(user_agent) = &self.user_agent {
+            if user_agent.trim().is_empty() || reqwest::header::HeaderValue::from_str(user_agent).is_err() {
+                return Err("User-Agent 不可為空且不可包含控制字元".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 備份與暫存檔探測設定
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct BackupFileSettings {
+    // 單次掃描最多送出的探測數
+    pub max_probes: usize,
+    // 內建清單 (.bak、.old、.orig、~、.swp、.save、.zip、.tar.gz) 以外附加在檔名後的變化，例如 ".backup"、"_old"
+    pub mutations: Vec<String>,
+}
+
+impl Default for BackupFileSettings {
+    fn default() -> Self {
+        Self { max_probes: 200, mutations: Vec::new() }
+    }
+}
+
+impl BackupFileSettings {
+    /// 檢查設定組合，錯誤訊息說明哪個欄位無效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_probes == 0 {
+            return Err("備份檔探測數上限 (backup_files.max_probes) 必須大於 0".to_string());
+        }
+        if let Some(mutation) = self
+            .mutations
+            .iter()
+            .find(|m| m.trim().is_empty() || m.contains(['/', '\\', '?', '#']) || m.chars().any(char::is_whitespace))
+        {
+            return Err(format!("備份檔副檔名變化 (backup_files.mutations) 無效: \"{}\"", mutation));
+        }
+        Ok(())
+    }
+}
+
+/// 認證端點速率限制測試設定
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RateLimitProbeSettings {
+    // 每個端點連續送出的請求數
+    pub burst: usize,
+    // 請求之間隨機等待 0 到此毫秒數
+    pub jitter_ms: u64,
+}
+
+impl Default for RateLimitProbeSettings {
+    fn default() -> Self {
+        Self { burst: 15, jitter_ms: 200 }
+    }
+}
+
+impl RateLimitProbeSettings {
+    /// 檢查設定組合，錯誤訊息說明哪個欄位無效
+    pub fn validate(&self) -> Result<(), String> {
+        if !(5..=100).contains(&self.burst) {
+            return Err("速率限制測試的突發請求數 (rate_limit_probe.burst) 必須介於 5 到 100".to_string());
+        }
+        if self.jitter_ms > 5000 {
+            return Err("速率限制測試的抖動 (rate_limit_probe.jitter_ms) 不能超過 5000 毫秒".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 連接埠掃描 (TCP connect) 設定
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PortScanSettings {
+    // 要掃描的連接埠；空時使用內建的常見連接埠清單
+    pub ports: Vec<u16>,
+    // 單一連接埠的連線逾時 (毫秒)
+    pub timeout_ms: u64,
+    // 同時建立的連線數上限
+    pub concurrency: usize,
+}
+
+impl Default for PortScanSettings {
+    fn default() -> Self {
+        Self { ports: Vec::new(), timeout_ms: 1500, concurrency: 50 }
+    }
+}
+
+/// mTLS 用戶端憑證，PKCS#12 (搭配 passphrase) 或 PEM 憑證/私鑰擇一
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ClientIdentity {
+    pub pkcs12_path: Option<String>,
+    #[serde(skip_serializing)]
+    pub passphrase: Option<String>,
+    pub cert_path: Option<String>,
+    pub key_path: Option<String>,
+}
+
+// passphrase 不可出現在任何日誌中
+impl std::fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ClientIdentity")
+            .field("pkcs12_path", &self.pkcs12_path)
+            .field("passphrase", &self.passphrase.as_ref().map(|_| "<redacted>"))
+            .field("cert_path", &self.cert_path)
+            .field("key_path", &self.key_path)
+            .finish()
+    }
+}
+
+/// 掃描流量經過的代理 (Burp 等攔截代理、出口代理或 SOCKS5 / Tor)
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ProxySettings {
+    // http://、https://、socks5:// 或 socks5h:// (由代理解析主機名稱，經 Tor 時使用)
+    pub url: String,
+    pub username: Option<String>,
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+    // 攔截代理以自己的 CA 重新簽發憑證；啟用時即使設定 http.verify_tls 也不驗證憑證
+    pub accept_invalid_certs: bool,
+}
+
+/// 代理支援的通訊協定
+pub const PROXY_SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+
+impl ProxySettings {
+    /// 檢查設定，錯誤訊息說明哪個欄位無效 (不包含密碼)
+    pub fn validate(&self) -> Result<(), String> {
+        let url = reqwest::Url::parse(self.url.trim()).map_err(|e| format!("代理網址 (proxy.url) 無效: {}", e))?;
+        if !PROXY_SCHEMES.contains(&url.scheme()) {
+            return Err(format!("不支援的代理通訊協定 {}，必須是 http、https、socks5 或 socks5h", url.scheme()));
+        }
+        if url.host_str().is_none() {
+            return Err("代理網址 (proxy.url) 沒有主機".to_string());
+        }
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err("代理帳密請填在 proxy.username 與 proxy.password，不要放在網址中".to_string());
+        }
+        if self.password.is_some() && self.username.is_none() {
+            return Err("設定代理密碼時必須設定帳號 (proxy.username)".to_string());
+        }
+        Ok(())
+    }
+}
+
+// password 不可出現在任何日誌中
+impl std::fmt::Debug for ProxySettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ProxySettings")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("accept_invalid_certs", &self.accept_invalid_certs)
+            .finish()
+    }
+}
+
+/// OOB 回呼伺服器 (interactsh 或自架的監聽服務)
+///
+/// SSRF 檢查把 `http://<canary>.<callback_url 的主機>/` 注入候選參數，探測結束後輪詢 poll_url，
+/// 回應中出現 canary 即表示目標伺服器曾連線到回呼網址。
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CollaboratorSettings {
+    // 回呼網址，例如 http://collab.example 或 collab.example (未指定通訊協定時為 http)
+    pub callback_url: String,
+    // 輪詢 API，GET 回應 (JSON 或文字) 為收到的互動紀錄
+    pub poll_url: String,
+    // 輪詢 API 的 Bearer token
+    #[serde(skip_serializing)]
+    pub poll_token: Option<String>,
+    // 探測結束後等待回呼的秒數
+    pub wait_secs: u64,
+}
+
+impl Default for CollaboratorSettings {
+    fn default() -> Self {
+        Self { callback_url: String::new(), poll_url: String::new(), poll_token: None, wait_secs: 10 }
+    }
+}
+
+impl CollaboratorSettings {
+    /// 回呼網址，未指定通訊協定時補上 http://
+    pub fn callback_base(&self) -> Option<reqwest::Url> {
+        let url = self.callback_url.trim();
+        let url = if url.contains("://") { url.to_string() } else { format!("http://{}", url) };
+        reqwest::Url::parse(&url).ok().filter(|u| u.host_str().is_some())
+    }
+
+    /// 檢查設定組合，錯誤訊息說明哪個欄位無效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.callback_base().is_none() {
+            return Err(format!("OOB 回呼網址 (collaborator.callback_url) 無效: {}", self.callback_url));
+        }
+        if reqwest::Url::parse(&self.poll_url).is_err() {
+            return Err(format!("OOB 輪詢 API (collaborator.poll_url) 不是有效的 URL: {}", self.poll_url));
+        }
+        if self.wait_secs > 300 {
+            return Err("OOB 回呼等待時間 (collaborator.wait_secs) 不可超過 300 秒".to_string());
+        }
+        Ok(())
+    }
+}
+
+// 輪詢 token 不可出現在任何日誌中
+impl std::fmt::Debug for CollaboratorSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CollaboratorSettings")
+            .field("callback_url", &self.callback_url)
+            .field("poll_url", &self.poll_url)
+            .field("poll_token", &self.poll_token.as_ref().map(|_| "<redacted>"))
+            .field("wait_secs", &self.wait_secs)
+            .finish()
+    }
+}
+
+/// 已登入狀態的掃描設定，cookies、bearer_token 與 username/password 擇一
+///
+/// 只套用到與目標同來源 (scheme、主機、連接埠) 的請求，爬取時遇到的第三方主機不會收到。
+/// 機密值不會序列化，報告只記錄 label。
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ScanAuth {
+    // 報告中標示的身分名稱，例如 "一般會員"
+    pub label: String,
+    // Cookie 標頭格式：name=value; name2=value2
+    #[serde(skip_serializing)]
+    pub cookies: Option<String>,
+    #[serde(skip_serializing)]
+    pub bearer_token: Option<String>,
+    pub username: Option<String>,
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+    // 登入後才能存取的頁面，掃描前與掃描期間確認頁面包含 indicator_marker
+    pub indicator_url: Option<String>,
+    pub indicator_marker: Option<String>,
+}
+
+impl ScanAuth {
+    /// 使用的認證方式：cookies、bearer 或 basic
+    pub fn method(&self) -> &'static str {
+        if self.cookies.is_some() {
+            "cookies"
+        } else if self.bearer_token.is_some() {
+            "bearer"
+        } else {
+            "basic"
+        }
+    }
+
+    /// 檢查設定組合，錯誤訊息說明哪個欄位無效 (不包含機密值)
+    pub fn validate(&self) -> Result<(), String> {
+        let methods = [self.cookies.is_some(), self.bearer_token.is_some(), self.username.is_some()];
+        match methods.iter().filter(|set| **set).count() {
+            0 => return Err("登入設定 (auth) 必須指定 cookies、bearer_token 或 username/password 其中之一".to_string()),
+            1 => {}
+            _ => return Err("登入設定 (auth) 的 cookies、bearer_token 與 username/password 只能擇一".to_string()),
+        }
+        if self.password.is_some() && self.username.is_none() {
+            return Err("登入設定 (auth) 指定 password 時必須同時指定 username".to_string());
+        }
+        let invalid = |value: &Option<String>| {
+            value.as_ref().is_some_and(|v| v.trim().is_empty() || reqwest::header::HeaderValue::from_str(v).is_err())
+        };
+        if invalid(&self.cookies) {
+            return Err("登入 cookie (auth.cookies) 不可為空且不可包含控制字元".to_string());
+        }
+        if invalid(&self.bearer_token) {
+            return Err("Bearer token (auth.bearer_token) 不可為空且不可包含控制字元".to_string());
+        }
+        match (&self.indicator_url, &self.indicator_marker) {
+            (Some(url), Some(marker)) => {
+                if reqwest::Url::parse(url).is_err() {
+                    return Err(format!("登入狀態確認頁面 (auth.indicator_url) 不是有效的 URL: {}", url));
+                }
+                if marker.is_empty() {
+                    return Err("登入狀態標記 (auth.indicator_marker) 不可為空".to_string());
+                }
+            }
+            (None, None) => {}
+            _ => return Err("登入狀態確認頁面 (auth.indicator_url) 與標記 (auth.indicator_marker) 必須同時指定".to_string()),
+        }
+        Ok(())
+    }
+}
+
+// cookie、token 與密碼不可出現在任何日誌中
+impl std::fmt::Debug for ScanAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let redacted = |value: &Option<String>| value.as_ref().map(|_| "<redacted>");
+        f.debug_struct("ScanAuth")
+            .field("label", &self.label)
+            .field("cookies", &redacted(&self.cookies))
+            .field("bearer_token", &redacted(&self.bearer_token))
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("indicator_url", &self.indicator_url)
+            .field("indicator_marker", &self.indicator_marker)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ScanType {
     Full,
@@ -36,13 +716,16 @@ impl std::fmt::Display for ScanType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ScanStatus {
     Pending,
     Running,
+    Paused,
     Completed,
     Failed,
+    /// 使用者取消；已收集的部分結果仍保留在報告中
+    Cancelled,
 }
 
 impl std::fmt::Display for ScanStatus {
@@ -50,12 +733,32 @@ impl std::fmt::Display for ScanStatus {
         match self {
             ScanStatus::Pending => write!(f, "pending"),
             ScanStatus::Running => write!(f, "running"),
+            ScanStatus::Paused => write!(f, "paused"),
             ScanStatus::Completed => write!(f, "completed"),
             ScanStatus::Failed => write!(f, "failed"),
+            ScanStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl Severity {
+    /// 嚴重程度排名，Critical 最高；derive 的 Ord 依宣告順序，Critical 反而最小，排序時請使用此值
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::Critical => 4,
+            Severity::High => 3,
+            Severity::Medium => 2,
+            Severity::Low => 1,
+            Severity::Info => 0,
         }
     }
 }
 
+/// 發現的嚴重程度排名；未設定時視為 Info
+pub fn severity_rank(severity: Option<&Severity>) -> u8 {
+    severity.map_or(0, Severity::rank)
+}
+
 impl std::fmt::Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -68,7 +771,7 @@ impl std::fmt::Display for Severity {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScanResult {
     pub id: String,
     pub task_id: String,
@@ -78,9 +781,108 @@ pub struct ScanResult {
     pub description: Option<String>,
     pub raw_data: Option<String>,
     pub created_at: DateTime<Utc>,
+    // 目標封鎖掃描流量但仍繼續掃描時為 true，結果可能來自封鎖頁
+    #[serde(default)]
+    pub low_confidence: bool,
+    // 對應的 CWE (例如 CWE-89)，掃描完成時依 check id 標記
+    #[serde(default)]
+    pub cwe_ids: Vec<String>,
+    // 結構化的中繼資料 (OWASP 類別、受影響 URL、payload)，舊資料沒有此欄位
+    #[serde(default)]
+    pub details: Option<FindingDetails>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 發現的結構化中繼資料；完整的證據仍保留在 raw_data
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct FindingDetails {
+    /// 例如 "A03:2021"
+    pub owasp_category: Option<String>,
+    /// 主要的 CWE，與 cwe_ids 的第一個相同
+    pub cwe_id: Option<String>,
+    pub affected_url: Option<String>,
+    pub payload: Option<String>,
+    /// 0-100
+    pub confidence: Option<u8>,
+    /// XSS 反射的語境，例如 "script"、"unquoted_attribute"
+    pub reflection_context: Option<String>,
+    /// 回應中 payload 周圍的內容
+    pub evidence: Option<String>,
+    /// 產生此發現的 HTTP 請求與回應
+    pub exchange: Option<HttpExchange>,
+}
+
+/// 產生發現的 HTTP 請求與回應；Authorization、Cookie 等請求標頭已遮蔽，內容只保留開頭
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct HttpExchange {
+    /// 例如 "GET https://example.com/?id=1 HTTP/1.1"
+    pub request_line: String,
+    pub request_headers: Vec<EvidenceHeader>,
+    pub request_body: Option<String>,
+    /// 沒有收到回應 (逾時、連線失敗) 時為 None
+    pub response_status: Option<u16>,
+    pub response_headers: Vec<EvidenceHeader>,
+    /// 回應內容的開頭，長度上限為 HttpSettings.evidence_body_bytes
+    pub response_body_excerpt: Option<String>,
+    /// 回應內容的完整大小 (位元組)
+    pub response_body_bytes: Option<u64>,
+    pub response_body_truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EvidenceHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// 掃描涵蓋範圍說明：略過的測試、請求數統計等，避免「沒測」被誤讀為「沒問題」
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CoverageNote {
+    pub check: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: Option<serde_json::Value>,
+    // 檢查有執行，但分析的內容不可信 (例如只取得同意頁)
+    #[serde(default)]
+    pub degraded: bool,
+}
+
+/// 單一檢查或類別的執行結果，報告依此列出哪些部分實際測試過
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CheckCoverage {
+    // 與 check_metrics 相同的檢查 id (例如 headers、ssl、owasp.a03)
+    pub check: String,
+    pub status: CoverageStatus,
+    pub requests: u32,
+    // 略過的原因、錯誤訊息或未完成的部分；完整執行時為 None
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageStatus {
+    Completed,
+    // 沒有執行 (掃描模式、設定、時間限制或請求預算)
+    Skipped,
+    // 有執行，但部分檢查略過或中途停止
+    Partial,
+    Error,
+}
+
+impl CoverageStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CoverageStatus::Completed => "完成",
+            CoverageStatus::Skipped => "略過",
+            CoverageStatus::Partial => "部分完成",
+            CoverageStatus::Error => "錯誤",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ResultType {
     Port,
@@ -90,7 +892,7 @@ pub enum ResultType {
     Technology,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     Critical,
@@ -113,7 +915,7 @@ pub struct Vulnerability {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OpenPort {
     pub id: String,
     pub task_id: String,
@@ -125,14 +927,14 @@ pub struct OpenPort {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Protocol {
     Tcp,
     Udp,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SslAnalysis {
     pub id: String,
     pub task_id: String,
@@ -143,12 +945,38 @@ pub struct SslAnalysis {
     pub signature_algorithm: Option<String>,
     pub tls_versions: Option<Vec<String>>,
     pub cipher_suites: Option<Vec<String>>,
+    // 舊版報告的文字問題描述；新的掃描只記錄在 tls_vulnerabilities
+    #[serde(default)]
     pub vulnerabilities: Option<Vec<String>>,
     pub grade: Option<String>,
+    pub grade_factors: Option<Vec<String>>, // 影響評級的因素，供報告說明
+    pub chain_valid: Option<bool>,
+    pub key_size: Option<u32>,
+    pub hsts_enabled: Option<bool>,
+    pub tls_vulnerabilities: Option<Vec<TlsVulnerability>>,
     pub created_at: DateTime<Utc>,
+    // 實際分析的 URL 與連接埠 (http 目標會改為分析同主機的 HTTPS)
+    #[serde(default)]
+    pub analyzed_url: Option<String>,
+    #[serde(default)]
+    pub analyzed_port: Option<u16>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TlsVulnerability {
+    pub id: String, // 例如 "heartbleed"、"poodle"
+    pub name: String,
+    pub severity: Severity,
+    pub cve_ids: Vec<String>,
+    pub references: Vec<String>,
+    pub description: String,
+    pub active_probe: bool, // true 表示經實際探測確認，false 為依協定/密碼套件推論
+    // 判斷的依據，例如接受的協定版本或密碼套件；不包含伺服器回傳的內容
+    #[serde(default)]
+    pub evidence: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SecurityHeader {
     pub id: String,
     pub task_id: String,
@@ -158,9 +986,12 @@ pub struct SecurityHeader {
     pub is_secure: bool,
     pub recommendation: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// 標頭重複出現時的所有值；header_value 為合併後的顯示字串
+    #[serde(default)]
+    pub header_values: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DetectedTechnology {
     pub id: String,
     pub task_id: String,
@@ -169,9 +1000,29 @@ pub struct DetectedTechnology {
     pub category: TechnologyCategory,
     pub confidence: u8,
     pub created_at: DateTime<Utc>,
+    /// 判定依據 (符合的標頭、HTML 特徵或腳本網址)
+    #[serde(default)]
+    pub evidence: Vec<TechnologyEvidence>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TechnologyEvidence {
+    pub kind: EvidenceKind,
+    /// 符合的內容，例如 "server: nginx/1.24"、"data-v-" 或腳本 URL
+    pub matched: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    Header,
+    HtmlPattern,
+    ScriptUrl,
+    Cookie,
+    UrlPattern,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TechnologyCategory {
     Framework,
@@ -183,12 +1034,15 @@ pub enum TechnologyCategory {
     Database,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// generate_report 產生的報告檔案與其統計
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Report {
     pub id: String,
     pub task_id: String,
     pub report_type: ReportType,
     pub file_path: Option<String>,
+    // 寫入的檔案大小 (bytes)
+    pub file_size: Option<u64>,
     pub executive_summary: Option<String>,
     pub total_vulnerabilities: i32,
     pub critical_count: i32,
@@ -199,7 +1053,7 @@ pub struct Report {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ReportType {
     Pdf,
@@ -208,10 +1062,95 @@ pub enum ReportType {
     Markdown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScanProgress {
     pub task_id: String,
-    pub stage: String,
+    pub stage: ScanStage,
+    // 依訊息目錄產生的顯示文字
+    pub label: String,
     pub progress: u8, // 0-100
     pub message: String,
 }
+
+impl ScanProgress {
+    pub fn new(task_id: &str, stage: ScanStage, progress: u8, message: String) -> Self {
+        Self {
+            task_id: task_id.to_string(),
+            label: messages::stage_label(&stage),
+            stage,
+            progress: progress.min(100),
+            message,
+        }
+    }
+}
+
+/// 掃描階段
+///
+/// 以穩定的 snake_case id 序列化，例如 {"id": "headers"}、{"id": "owasp", "category": "A05"}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "id", content = "category", rename_all = "snake_case")]
+pub enum ScanStage {
+    Queued,
+    Resolving,
+    Headers,
+    Ssl,
+    Ports,
+    Crawling,
+    Owasp(String),
+    TechDetection,
+    Reporting,
+    Finalizing,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+pub const OWASP_CATEGORIES: [&str; 10] = ["A01", "A02", "A03", "A04", "A05", "A06", "A07", "A08", "A09", "A10"];
+
+impl ScanStage {
+    /// 所有階段，依執行順序排列 (OWASP 每個類別各一個階段)
+    pub fn all() -> Vec<ScanStage> {
+        let mut stages = vec![
+            ScanStage::Queued,
+            ScanStage::Resolving,
+            ScanStage::Headers,
+            ScanStage::Ssl,
+            ScanStage::Ports,
+            ScanStage::Crawling,
+            ScanStage::TechDetection,
+        ];
+        stages.extend(OWASP_CATEGORIES.iter().map(|c| ScanStage::Owasp(c.to_string())));
+        stages.extend([
+            ScanStage::Reporting,
+            ScanStage::Finalizing,
+            ScanStage::Paused,
+            ScanStage::Completed,
+            ScanStage::Failed,
+            ScanStage::Cancelled,
+        ]);
+        stages
+    }
+
+    /// 訊息目錄中的鍵，例如 "headers"、"owasp.a05"
+    pub fn message_key(&self) -> String {
+        match self {
+            ScanStage::Owasp(category) => format!("owasp.{}", category.to_lowercase()),
+            other => serde_json::to_value(other)
+                .ok()
+                .and_then(|v| v["id"].as_str().map(str::to_string))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// 單一階段的耗時，掃描結束時附在報告上
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StageTiming {
+    pub stage: ScanStage,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    // 階段被略過時的原因
+    #[serde(default)]
+    pub skipped: Option<String>,
+}
@@ -0,0 +1,199 @@
+/**
+ * Output Files
+ *
+ * 報告與匯出檔案的寫入：先寫到同目錄的暫存檔，驗證大小與 SHA-256 後再以 rename 原子取代，
+ * 中途當機不會留下寫到一半的檔案。進行中的暫存檔記錄在 journal，下次啟動時清除。
+ */
+
+use serde::Serialize;
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const TEMP_SUFFIX: &str = ".redforge-tmp";
+const JOURNAL_FILE: &str = "pending-writes";
+// 自動編號的上限，超過時視為無法決定檔名
+const MAX_SUFFIX: u32 = 999;
+
+static JOURNAL: OnceLock<Mutex<PathBuf>> = OnceLock::new();
+
+/// 寫入完成的檔案
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WrittenFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    // 目的地已存在而改用編號檔名時為 true
+    pub renamed: bool,
+}
+
+/// 啟動時呼叫：設定 journal 位置並刪除上次未完成寫入遺留的暫存檔
+pub fn init(data_dir: &Path) {
+    let _ = fs::create_dir_all(data_dir);
+    let journal = data_dir.join(JOURNAL_FILE);
+
+    if let Ok(content) = fs::read_to_string(&journal) {
+        for line in content.lines().filter(|l| l.ends_with(TEMP_SUFFIX)) {
+            let temp = Path::new(line);
+            if temp.exists() {
+                match fs::remove_file(temp) {
+                    Ok(_) => tracing::info!("🧹 已清除未完成的暫存檔: {}", temp.display()),
+                    Err(e) => tracing::warn!("⚠️  無法清除暫存檔 {}: {}", temp.display(), e),
+                }
+            }
+        }
+    }
+    let _ = fs::write(&journal, "");
+
+    let _ = JOURNAL.set(Mutex::new(journal));
+}
+
+/// 原子寫入檔案並回傳寫入結果
+///
+/// overwrite 為 false 時，目的地已存在會改用 `name (1).ext` 形式的編號檔名。
+pub fn write_atomic(destination: &Path, bytes: &[u8], overwrite: bool) -> Result<WrittenFile, String> {
+    let dir = destination_dir(destination)?;
+
+    let target = if overwrite {
+        destination.to_path_buf()
+    } else {
+        available_path(destination)?
+    };
+    let renamed = target != destination;
+
+    let file_name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("無效的檔案路徑: {}", destination.display()))?;
+    let temp = dir.join(format!(".{}.{}{}", file_name, uuid::Uuid::new_v4().simple(), TEMP_SUFFIX));
+
+    journal_add(&temp);
+    let result = write_verified(&temp, bytes).and_then(|written| {
+        if !overwrite && target.exists() {
+            return Err(format!("目的地已被其他程式建立: {}", target.display()));
+        }
+        fs::rename(&temp, &target).map_err(|e| format!("無法移動暫存檔到 {}: {}", target.display(), e))?;
+        Ok(written)
+    });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    journal_remove(&temp);
+
+    let (size, sha256) = result?;
+    Ok(WrittenFile {
+        path: target.to_string_lossy().to_string(),
+        size,
+        sha256,
+        renamed,
+    })
+}
+
+/// 在產生耗時的內容 (例如 PDF) 之前確認目的地目錄存在且可以寫入
+pub fn check_writable(destination: &Path) -> Result<(), String> {
+    let dir = destination_dir(destination)?;
+    let probe = dir.join(format!(".{}{}", uuid::Uuid::new_v4().simple(), TEMP_SUFFIX));
+    journal_add(&probe);
+    let result = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map(drop)
+        .map_err(|e| format!("無法寫入目錄 {}: {}", dir.display(), e));
+    let _ = fs::remove_file(&probe);
+    journal_remove(&probe);
+    result
+}
+
+fn destination_dir(destination: &Path) -> Result<PathBuf, String> {
+    let dir = match destination.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    if !dir.is_dir() {
+        return Err(format!("目錄不存在: {}", dir.display()));
+    }
+    Ok(dir)
+}
+
+/// 寫入暫存檔並重新讀回比對大小與雜湊
+fn write_verified(temp: &Path, bytes: &[u8]) -> Result<(u64, String), String> {
+    let expected = hex_digest(bytes);
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp)
+        .map_err(|e| format!("無法建立暫存檔 {}: {}", temp.display(), e))?;
+    file.write_all(bytes).map_err(|e| format!("寫入失敗: {}", e))?;
+    file.sync_all().map_err(|e| format!("寫入失敗: {}", e))?;
+    drop(file);
+
+    let written = fs::read(temp).map_err(|e| format!("無法讀回暫存檔: {}", e))?;
+    if written.len() != bytes.len() || hex_digest(&written) != expected {
+        return Err(format!(
+            "寫入驗證失敗: 預期 {} bytes，實際 {} bytes",
+            bytes.len(),
+            written.len()
+        ));
+    }
+
+    Ok((written.len() as u64, expected))
+}
+
+/// 目的地不存在時原樣回傳，否則找第一個未使用的編號檔名
+fn available_path(destination: &Path) -> Result<PathBuf, String> {
+    if !destination.exists() {
+        return Ok(destination.to_path_buf());
+    }
+
+    let file_name = destination
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // 以第一個 '.' 分割，保留 .md.enc 這類多段副檔名
+    // (開頭的 '.' 屬於隱藏檔名稱，不視為副檔名)
+    let (stem, extension) = match file_name.char_indices().skip(1).find(|(_, c)| *c == '.') {
+        Some((i, _)) => file_name.split_at(i),
+        None => (file_name.as_str(), ""),
+    };
+
+    (1..=MAX_SUFFIX)
+        .map(|n| destination.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .ok_or_else(|| format!("無法為 {} 找到可用的檔名", destination.display()))
+}
+
+fn hex_digest(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn journal_add(temp: &Path) {
+    if let Some(journal) = JOURNAL.get() {
+        let journal = journal.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&*journal) {
+            let _ = writeln!(file, "{}", temp.display());
+            let _ = file.sync_all();
+        }
+    }
+}
+
+fn journal_remove(temp: &Path) {
+    if let Some(journal) = JOURNAL.get() {
+        let journal = journal.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = temp.display().to_string();
+        let remaining: Vec<String> = fs::read_to_string(&*journal)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| *line != entry)
+            .map(|line| format!("{}\n", line))
+            .collect();
+        if let Ok(mut file) = File::create(&*journal) {
+            let _ = file.write_all(remaining.concat().as_bytes());
+        }
+    }
+}
@@ -0,0 +1,516 @@
+/**
+ * Report Rendering
+ *
+ * 將掃描報告轉成交付給客戶的檔案。Markdown 由程式組成，HTML 以 askama 樣板 (templates/report.html) 產生，
+ * 兩者都讀取同一份 ReportView，章節與數字一致：執行摘要、統計、安全標頭、SSL/TLS、技術與依嚴重程度分組的發現。
+ *
+ * 發現的標題、說明與證據來自目標的回應，可能包含惡意內容 (例如儲存型 XSS 的 payload)。
+ * HTML 由 askama 自動跳脫；Markdown 以反斜線跳脫會被解讀為 HTML、連結或表格的字元，
+ * HTTP 證據放在 fenced code block 中 (`HttpExchange::markdown`)。
+ */
+
+use crate::commands::compare::{ChangeRecord, DiffFinding, MatchedFinding, ScanDiff};
+use crate::commands::scan::ScanReport;
+use crate::cwe;
+use crate::executive_summary;
+use crate::models::{HttpExchange, ScanResult, Severity};
+use crate::summary::{summarize, ReportSummary, SummaryInput};
+use askama::Template;
+use chrono::{DateTime, Utc};
+
+/// 報告中依序列出的嚴重程度
+const SEVERITIES: [Severity; 5] = [Severity::Critical, Severity::High, Severity::Medium, Severity::Low, Severity::Info];
+/// 沒有值的欄位
+const EMPTY: &str = "-";
+
+/// 報告的統計：掃描完成時計算的摘要，舊報告沒有摘要時由發現重新計算
+pub fn report_summary(report: &ScanReport) -> ReportSummary {
+    report
+        .summary
+        .clone()
+        .unwrap_or_else(|| summarize(report.vulnerabilities.iter().map(SummaryInput::from_result)))
+}
+
+/// 兩種格式共用的報告內容，所有欄位都已轉成顯示用的字串
+pub struct ReportView {
+    pub target: String,
+    pub scan_type: String,
+    pub status: String,
+    pub started_at: String,
+    pub completed_at: String,
+    pub generated_at: String,
+    // 執行摘要的段落
+    pub executive_summary: Vec<String>,
+    pub total: u32,
+    pub counts: Vec<SeverityCount>,
+    pub headers: Vec<HeaderView>,
+    pub ssl: Vec<SslView>,
+    pub technologies: Vec<TechnologyView>,
+    // 只包含有發現的嚴重程度
+    pub groups: Vec<SeverityGroup>,
+}
+
+pub struct SeverityCount {
+    pub label: &'static str,
+    pub count: u32,
+}
+
+pub struct HeaderView {
+    pub name: String,
+    pub state: &'static str,
+    pub value: String,
+    pub recommendation: String,
+}
+
+pub struct SslView {
+    pub endpoint: String,
+    pub grade: String,
+    pub issuer: String,
+    pub subject: String,
+    pub validity: String,
+    pub versions: String,
+    pub issues: Vec<String>,
+}
+
+pub struct TechnologyView {
+    pub name: String,
+    pub version: String,
+    pub category: String,
+    pub confidence: u8,
+}
+
+pub struct SeverityGroup {
+    pub label: &'static str,
+    pub findings: Vec<FindingView>,
+}
+
+pub struct FindingView {
+    pub title: String,
+    pub description: String,
+    pub affected_url: String,
+    pub cwe: String,
+    pub payload: String,
+    pub low_confidence: bool,
+    // 依 CWE 對應的修補建議，沒有登錄的 CWE 時為空
+    pub remediation: String,
+    // 回應中 payload 周圍的內容
+    pub evidence: String,
+    pub exchange: Option<HttpExchange>,
+}
+
+impl FindingView {
+    /// HTML 報告中的原始 HTTP 請求與回應
+    pub fn transcript(&self) -> String {
+        self.exchange.as_ref().map(HttpExchange::transcript).unwrap_or_default()
+    }
+}
+
+impl ReportView {
+    pub fn new(report: &ScanReport, summary: &ReportSummary, generated_at: DateTime<Utc>) -> Self {
+        let task = &report.task;
+        let groups = SEVERITIES
+            .iter()
+            .filter_map(|severity| {
+                let findings: Vec<FindingView> = report
+                    .vulnerabilities
+                    .iter()
+                    .filter(|finding| finding.severity.as_ref().unwrap_or(&Severity::Info) == severity)
+                    .map(finding_view)
+                    .collect();
+                (!findings.is_empty()).then_some(SeverityGroup { label: severity_label(severity), findings })
+            })
+            .collect();
+
+        Self {
+            target: task.target_url.clone(),
+            scan_type: task.scan_type.to_string(),
+            status: task.status.to_string(),
+            started_at: timestamp(task.started_at.as_ref()),
+            completed_at: timestamp(task.completed_at.as_ref()),
+            generated_at: timestamp(Some(&generated_at)),
+            executive_summary: executive_summary::build(report, summary).paragraphs,
+            total: summary.total,
+            counts: SEVERITIES
+                .iter()
+                .map(|severity| SeverityCount { label: severity_label(severity), count: summary.count(&severity.to_string()) })
+                .collect(),
+            headers: report
+                .headers
+                .iter()
+                .map(|header| HeaderView {
+                    name: header.header_name.clone(),
+                    state: match (header.is_present, header.is_secure) {
+                        (false, _) => "缺少",
+                        (true, false) => "不安全",
+                        (true, true) => "安全",
+                    },
+                    value: header.header_value.clone().unwrap_or_else(|| EMPTY.to_string()),
+                    recommendation: header.recommendation.clone().unwrap_or_else(|| EMPTY.to_string()),
+                })
+                .collect(),
+            ssl: report
+                .ssl_analysis
+                .iter()
+                .map(|ssl| {
+                    // 舊版報告只有文字描述
+                    let mut issues = ssl.vulnerabilities.clone().unwrap_or_default();
+                    for vulnerability in ssl.tls_vulnerabilities.iter().flatten() {
+                        issues.push(format!(
+                            "{} ({}): {}",
+                            vulnerability.name,
+                            severity_label(&vulnerability.severity),
+                            vulnerability.description
+                        ));
+                    }
+                    SslView {
+                        endpoint: ssl.analyzed_url.clone().unwrap_or_else(|| task.target_url.clone()),
+                        grade: ssl.grade.clone().unwrap_or_else(|| EMPTY.to_string()),
+                        issuer: ssl.certificate_issuer.clone().unwrap_or_else(|| EMPTY.to_string()),
+                        subject: ssl.certificate_subject.clone().unwrap_or_else(|| EMPTY.to_string()),
+                        validity: format!("{} ~ {}", date(ssl.valid_from.as_ref()), date(ssl.valid_to.as_ref())),
+                        versions: ssl
+                            .tls_versions
+                            .as_ref()
+                            .filter(|versions| !versions.is_empty())
+                            .map(|versions| versions.join(", "))
+                            .unwrap_or_else(|| EMPTY.to_string()),
+                        issues,
+                    }
+                })
+                .collect(),
+            technologies: report
+                .technologies
+                .iter()
+                .map(|technology| TechnologyView {
+                    name: technology.technology_name.clone(),
+                    version: technology.technology_version.clone().unwrap_or_else(|| EMPTY.to_string()),
+                    category: serde_json::to_value(&technology.category)
+                        .ok()
+                        .and_then(|value| value.as_str().map(str::to_string))
+                        .unwrap_or_default(),
+                    confidence: technology.confidence,
+                })
+                .collect(),
+            groups,
+        }
+    }
+}
+
+fn finding_view(finding: &ScanResult) -> FindingView {
+    let details = finding.details.clone().unwrap_or_default();
+    FindingView {
+        title: finding.title.clone(),
+        description: finding.description.clone().unwrap_or_default(),
+        affected_url: details.affected_url.unwrap_or_default(),
+        cwe: finding.cwe_ids.join(", "),
+        payload: details.payload.unwrap_or_default(),
+        low_confidence: finding.low_confidence,
+        remediation: finding.cwe_ids.iter().find_map(|cwe| cwe::remediation(cwe)).unwrap_or_default().to_string(),
+        evidence: details.evidence.unwrap_or_default(),
+        exchange: details.exchange,
+    }
+}
+
+pub fn severity_label(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "Critical",
+        Severity::High => "High",
+        Severity::Medium => "Medium",
+        Severity::Low => "Low",
+        Severity::Info => "Info",
+    }
+}
+
+fn timestamp(value: Option<&DateTime<Utc>>) -> String {
+    value.map(|value| value.format("%Y-%m-%d %H:%M:%S UTC").to_string()).unwrap_or_else(|| EMPTY.to_string())
+}
+
+fn date(value: Option<&DateTime<Utc>>) -> String {
+    value.map(|value| value.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "?".to_string())
+}
+
+/// 反斜線跳脫 Markdown 中會被解讀為 HTML、實體、連結、強調或表格的字元；換行保留
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '~' | '[' | ']' | '<' | '>' | '&' | '|' | '#' | '!') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 單行內容 (表格儲存格、清單項目)：換行改為空白後跳脫
+fn inline(text: &str) -> String {
+    escape_markdown(&text.replace("\r\n", " ").replace(['\r', '\n'], " "))
+}
+
+pub fn render_markdown(view: &ReportView) -> String {
+    let mut md = String::new();
+    md.push_str("# RedForge 掃描報告\n\n");
+    md.push_str(&format!("- 目標: {}\n", inline(&view.target)));
+    md.push_str(&format!("- 掃描類型: {}\n", view.scan_type));
+    md.push_str(&format!("- 狀態: {}\n", view.status));
+    md.push_str(&format!("- 開始時間: {}\n", view.started_at));
+    md.push_str(&format!("- 完成時間: {}\n", view.completed_at));
+    md.push_str(&format!("- 報告產生時間: {}\n\n", view.generated_at));
+
+    md.push_str("## 執行摘要\n\n");
+    for paragraph in &view.executive_summary {
+        md.push_str(&format!("{}\n\n", inline(paragraph)));
+    }
+
+    md.push_str("## 摘要\n\n| 嚴重程度 | 數量 |\n|------|------|\n");
+    for count in &view.counts {
+        md.push_str(&format!("| {} | {} |\n", count.label, count.count));
+    }
+    md.push_str(&format!("| 合計 | {} |\n\n", view.total));
+
+    md.push_str("## 安全標頭\n\n");
+    if view.headers.is_empty() {
+        md.push_str("沒有檢查安全標頭。\n\n");
+    } else {
+        md.push_str("| 標頭 | 狀態 | 值 | 建議 |\n|------|------|------|------|\n");
+        for header in &view.headers {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                inline(&header.name),
+                header.state,
+                inline(&header.value),
+                inline(&header.recommendation)
+            ));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## SSL/TLS\n\n");
+    if view.ssl.is_empty() {
+        md.push_str("沒有執行 SSL/TLS 分析。\n\n");
+    }
+    for ssl in &view.ssl {
+        md.push_str(&format!("### {} (評級 {})\n\n", inline(&ssl.endpoint), inline(&ssl.grade)));
+        md.push_str(&format!("- 簽發者: {}\n", inline(&ssl.issuer)));
+        md.push_str(&format!("- 主體: {}\n", inline(&ssl.subject)));
+        md.push_str(&format!("- 有效期間: {}\n", ssl.validity));
+        md.push_str(&format!("- 協定版本: {}\n", inline(&ssl.versions)));
+        if !ssl.issues.is_empty() {
+            md.push_str("- 問題:\n");
+            for issue in &ssl.issues {
+                md.push_str(&format!("  - {}\n", inline(issue)));
+            }
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## 技術\n\n");
+    if view.technologies.is_empty() {
+        md.push_str("沒有偵測到技術。\n\n");
+    } else {
+        md.push_str("| 技術 | 版本 | 類別 | 信心度 |\n|------|------|------|------|\n");
+        for technology in &view.technologies {
+            md.push_str(&format!(
+                "| {} | {} | {} | {}% |\n",
+                inline(&technology.name),
+                inline(&technology.version),
+                technology.category,
+                technology.confidence
+            ));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## 發現\n\n");
+    if view.groups.is_empty() {
+        md.push_str("沒有發現。\n");
+    }
+    for group in &view.groups {
+        md.push_str(&format!("### {} ({})\n\n", group.label, group.findings.len()));
+        for (index, finding) in group.findings.iter().enumerate() {
+            md.push_str(&format!("#### {}. {}\n\n", index + 1, inline(&finding.title)));
+            if finding.low_confidence {
+                md.push_str("> 低信心度：目標封鎖掃描流量時取得，結果可能來自封鎖頁\n\n");
+            }
+            let fields = [("受影響 URL", &finding.affected_url), ("CWE", &finding.cwe), ("Payload", &finding.payload)];
+            let mut listed = false;
+            for (label, value) in fields.iter().filter(|(_, value)| !value.is_empty()) {
+                md.push_str(&format!("- {}: {}\n", label, inline(value)));
+                listed = true;
+            }
+            if listed {
+                md.push('\n');
+            }
+            if !finding.description.is_empty() {
+                md.push_str(&format!("{}\n\n", escape_markdown(finding.description.trim())));
+            }
+            if !finding.remediation.is_empty() {
+                md.push_str(&format!("修補建議: {}\n\n", finding.remediation));
+            }
+            if !finding.evidence.is_empty() {
+                md.push_str(&format!("證據: {}\n\n", inline(&finding.evidence)));
+            }
+            if let Some(exchange) = &finding.exchange {
+                md.push_str("請求與回應:\n\n");
+                md.push_str(&exchange.markdown());
+                md.push('\n');
+            }
+        }
+    }
+
+    md
+}
+
+/// 與前次掃描的差異章節 (diff_scans 的結果)，附加在 Markdown 報告之後
+pub fn render_diff_markdown(diff: &ScanDiff) -> String {
+    let mut md = String::new();
+    md.push_str("## 與前次掃描的差異\n\n");
+    md.push_str(&format!("- 基準掃描: {}\n- 本次掃描: {}\n\n", inline(&diff.task_id_a), inline(&diff.task_id_b)));
+
+    md.push_str("| 變化 | 數量 |\n|------|------|\n");
+    md.push_str(&format!("| 已修正 | {} |\n", diff.fixed.len()));
+    md.push_str(&format!("| 新發現 | {} |\n", diff.new.len()));
+    md.push_str(&format!("| 嚴重度改變 | {} |\n", diff.severity_changed.len()));
+    md.push_str(&format!("| 持續存在 | {} |\n\n", diff.persisting.len()));
+
+    if !diff.severity_changed.is_empty() {
+        md.push_str("### 嚴重度改變\n\n| 發現 | 受影響 URL | 前次 | 本次 |\n|------|------|------|------|\n");
+        for matched in &diff.severity_changed {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                inline(&matched.after.title),
+                inline(matched.after.affected_url.as_deref().unwrap_or(EMPTY)),
+                optional_severity(&matched.before),
+                optional_severity(&matched.after)
+            ));
+        }
+        md.push('\n');
+    }
+    for (heading, findings) in [("新發現", &diff.new), ("已修正", &diff.fixed)] {
+        if findings.is_empty() {
+            continue;
+        }
+        md.push_str(&format!("### {}\n\n| 發現 | 嚴重程度 | 受影響 URL | OWASP |\n|------|------|------|------|\n", heading));
+        for finding in findings {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                inline(&finding.title),
+                optional_severity(finding),
+                inline(finding.affected_url.as_deref().unwrap_or(EMPTY)),
+                finding.owasp_category.as_deref().unwrap_or(EMPTY)
+            ));
+        }
+        md.push('\n');
+    }
+    if !diff.persisting.is_empty() {
+        md.push_str("### 持續存在\n\n| 發現 | 嚴重程度 | 受影響 URL | 比對方式 |\n|------|------|------|------|\n");
+        for matched in &diff.persisting {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                inline(&matched.after.title),
+                optional_severity(&matched.after),
+                inline(matched.after.affected_url.as_deref().unwrap_or(EMPTY)),
+                match_method(matched)
+            ));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("### 安全標頭、技術與 SSL/TLS 的變化\n\n");
+    let mut changed = false;
+    for change in diff.header_changes.iter().chain(&diff.technology_changes).chain(&diff.ssl_grade) {
+        let line = match change {
+            ChangeRecord::Header { name, before, after, regressed, .. } => format!(
+                "標頭 {}: {} → {}{}",
+                inline(name),
+                inline(before.as_deref().unwrap_or("(未設定)")),
+                inline(after.as_deref().unwrap_or("(未設定)")),
+                if *regressed { " (退步)" } else { "" }
+            ),
+            ChangeRecord::Technology { name, before_version, after_version } => format!(
+                "技術 {}: {} → {}",
+                inline(name),
+                inline(before_version.as_deref().unwrap_or("(未偵測到)")),
+                inline(after_version.as_deref().unwrap_or("(未偵測到)"))
+            ),
+            ChangeRecord::TlsGrade { before, after, regressed, .. } => format!(
+                "SSL/TLS 評級: {} → {}{}",
+                inline(before.as_deref().unwrap_or(EMPTY)),
+                inline(after.as_deref().unwrap_or(EMPTY)),
+                if *regressed { " (退步)" } else { "" }
+            ),
+            ChangeRecord::Finding { .. } => continue,
+        };
+        md.push_str(&format!("- {}\n", line));
+        changed = true;
+    }
+    if !changed {
+        md.push_str("沒有變化。\n");
+    }
+    md.push('\n');
+
+    md
+}
+
+fn optional_severity(finding: &DiffFinding) -> &'static str {
+    finding.severity.as_ref().map(severity_label).unwrap_or(EMPTY)
+}
+
+fn match_method(matched: &MatchedFinding) -> String {
+    match matched.similarity {
+        Some(similarity) => format!("相似度 {:.0}%", similarity * 100.0),
+        None => "完全相符".to_string(),
+    }
+}
+
+#[derive(Template)]
+#[template(path = "report.html")]
+struct HtmlReport<'a> {
+    view: &'a ReportView,
+}
+
+/// 以 askama 樣板產生 HTML；所有內容都經過 HTML 跳脫
+pub fn render_html(view: &ReportView) -> Result<String, String> {
+    HtmlReport { view }.render().map_err(|e| format!("無法產生 HTML 報告: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/report/scan-report.json");
+    const GOLDEN_MARKDOWN: &str = "tests/fixtures/report/scan-report.md";
+
+    fn fixture_view() -> ReportView {
+        let report: ScanReport = serde_json::from_str(FIXTURE).expect("fixture is a valid ScanReport");
+        let generated_at = Utc.with_ymd_and_hms(2024, 5, 2, 8, 0, 0).unwrap();
+        ReportView::new(&report, &report_summary(&report), generated_at)
+    }
+
+    /// 以 REDFORGE_UPDATE_GOLDEN=1 執行會以目前的輸出更新 golden 檔
+    #[test]
+    fn markdown_matches_golden_file() {
+        let rendered = render_markdown(&fixture_view());
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_MARKDOWN);
+        if std::env::var_os("REDFORGE_UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, &rendered).unwrap();
+        }
+        let golden = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(rendered, golden);
+    }
+
+    #[test]
+    fn html_escapes_finding_content() {
+        let html = render_html(&fixture_view()).unwrap();
+        assert!(!html.contains("<script>alert(1)</script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<img src=x onerror=alert(2)>"));
+    }
+
+    #[test]
+    fn markdown_escapes_html_and_links() {
+        assert_eq!(escape_markdown("<script>alert(1)</script>"), "\\<script\\>alert(1)\\</script\\>");
+        assert_eq!(escape_markdown("[x](javascript:alert(1))"), "\\[x\\](javascript:alert(1))");
+        assert_eq!(inline("a|b\nc"), "a\\|b c");
+    }
+}
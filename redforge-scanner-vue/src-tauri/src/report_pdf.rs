@@ -0,0 +1,582 @@
+/**
+ * PDF Report
+ *
+ * 以 pdf-writer 直接產生 PDF，內容取自與 HTML/Markdown 報告相同的 ReportView：
+ * 封面 (目標、掃描日期、嚴重程度統計表與執行摘要)，接著依嚴重程度列出發現，每個發現一個區塊，
+ * 包含嚴重程度標籤、說明與修補建議；每頁頁尾有頁碼。
+ *
+ * 報告以中文為主，PDF 內建的標準字型無法顯示，因此從系統字型中找出涵蓋繁體中文的字型嵌入，
+ * 並以 subsetter 只保留用到的字形 (字形編號不變，內容串流直接寫入字形編號)。
+ * 找不到可用的字型時回傳錯誤，不產生缺字的檔案。
+ *
+ * 證據與請求/回應可能長達數千行，每個區塊最多保留 EXCERPT_MAX_LINES 行、EXCERPT_MAX_CHARS 個字元，
+ * 超過時截斷並註明，完整內容請參考 HTML 或 JSON 報告。
+ */
+
+use crate::report::{FindingView, ReportView};
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// A4，單位為 pt
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+// 頁尾頁碼的基線，內文不會低於 MARGIN
+const FOOTER_Y: f32 = 28.0;
+const LINE_SPACING: f32 = 1.45;
+
+/// 證據與請求/回應最多保留的行數與字元數
+const EXCERPT_MAX_LINES: usize = 30;
+const EXCERPT_MAX_CHARS: usize = 2000;
+
+/// 依序偏好的字型家族；都沒有時使用任何涵蓋 COVERAGE_PROBE 的字型
+const PREFERRED_FAMILIES: &[&str] = &[
+    "Noto Sans TC",
+    "Noto Sans CJK TC",
+    "Source Han Sans TC",
+    "Microsoft JhengHei",
+    "PingFang TC",
+    "Heiti TC",
+    "Noto Sans CJK SC",
+    "Microsoft YaHei",
+    "PingFang SC",
+    "WenQuanYi Micro Hei",
+    "Droid Sans Fallback",
+    "Arial Unicode MS",
+];
+/// 字型必須涵蓋的字元，用來判斷是否支援繁體中文
+const COVERAGE_PROBE: &str = "報告發現嚴重程度修補建議證據頁";
+
+const FONT_RESOURCE: Name<'static> = Name(b"F1");
+const SYSTEM_INFO: SystemInfo<'static> = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+/// 嵌入 PDF 的系統字型 (字型集合時以 index 指定其中一個)
+struct FontSource {
+    path: PathBuf,
+    index: u32,
+    post_script_name: String,
+}
+
+static SYSTEM_FONT: OnceLock<Option<FontSource>> = OnceLock::new();
+
+/// 產生 PDF 報告；第一次呼叫時掃描系統字型
+pub fn render_pdf(view: &ReportView) -> Result<Vec<u8>, String> {
+    let source = SYSTEM_FONT.get_or_init(find_system_font).as_ref().ok_or_else(|| {
+        "找不到支援中文的系統字型，無法產生 PDF 報告；請安裝 Noto Sans TC 等中文字型，或改用 HTML 報告".to_string()
+    })?;
+    let data = std::fs::read(&source.path).map_err(|e| format!("無法讀取字型 {}: {}", source.path.display(), e))?;
+    let face = ttf_parser::Face::parse(&data, source.index)
+        .map_err(|e| format!("無法解析字型 {}: {}", source.path.display(), e))?;
+
+    let font = Font::new(face);
+    let mut layout = Layout::new(&font);
+    layout.title_page(view);
+    layout.findings(view);
+    layout.page_numbers();
+    assemble(view, &font, layout, &data, source)
+}
+
+/// 找出涵蓋繁體中文且能嵌入 (TrueType 或 CFF 輪廓) 的一般樣式字型，偏好 PREFERRED_FAMILIES 與標準字重
+fn find_system_font() -> Option<FontSource> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+
+    let font = db
+        .faces()
+        .filter(|face| face.style == fontdb::Style::Normal)
+        .filter_map(|face| {
+            let path = match &face.source {
+                fontdb::Source::File(path) | fontdb::Source::SharedFile(path, _) => path.clone(),
+                fontdb::Source::Binary(_) => return None,
+            };
+            let usable = db
+                .with_face_data(face.id, |data, index| {
+                    ttf_parser::Face::parse(data, index).map(|parsed| embeddable(&parsed)).unwrap_or(false)
+                })
+                .unwrap_or(false);
+            if !usable {
+                return None;
+            }
+            let preference = face
+                .families
+                .iter()
+                .filter_map(|(family, _)| PREFERRED_FAMILIES.iter().position(|preferred| preferred == family))
+                .min()
+                .unwrap_or(PREFERRED_FAMILIES.len());
+            let weight_distance = (i32::from(face.weight.0) - 400).abs();
+            Some((
+                (preference, weight_distance),
+                FontSource { path, index: face.index, post_script_name: face.post_script_name.clone() },
+            ))
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, source)| source);
+
+    match &font {
+        Some(source) => tracing::info!("🔤 PDF 報告使用字型 {} ({})", source.post_script_name, source.path.display()),
+        None => tracing::warn!("⚠️  找不到支援中文的系統字型，無法產生 PDF 報告"),
+    }
+    font
+}
+
+fn embeddable(face: &ttf_parser::Face) -> bool {
+    let tables = face.tables();
+    (tables.glyf.is_some() || tables.cff.is_some()) && COVERAGE_PROBE.chars().all(|c| face.glyph_index(c).is_some())
+}
+
+/// 字型的字形對應與寬度；寬度以 PDF 的千分之一 em 為單位
+struct Font<'a> {
+    face: ttf_parser::Face<'a>,
+    scale: f32,
+}
+
+impl<'a> Font<'a> {
+    fn new(face: ttf_parser::Face<'a>) -> Self {
+        let scale = 1000.0 / f32::from(face.units_per_em());
+        Self { face, scale }
+    }
+
+    /// 字元的字形編號；字型沒有的字元為 0 (.notdef)
+    fn glyph(&self, c: char) -> u16 {
+        self.face.glyph_index(c).map(|glyph| glyph.0).unwrap_or(0)
+    }
+
+    fn advance(&self, glyph: u16) -> f32 {
+        self.face.glyph_hor_advance(ttf_parser::GlyphId(glyph)).map(f32::from).unwrap_or(0.0) * self.scale
+    }
+
+    fn width(&self, text: &str, size: f32) -> f32 {
+        text.chars().map(|c| self.advance(self.glyph(c))).sum::<f32>() * size / 1000.0
+    }
+
+    fn units(&self, value: i16) -> f32 {
+        f32::from(value) * self.scale
+    }
+}
+
+/// 報告中的嚴重程度顏色 (RGB)，與 HTML 報告一致
+fn severity_color(label: &str) -> [f32; 3] {
+    match label {
+        "Critical" => rgb(0x9b, 0x2c, 0x2c),
+        "High" => rgb(0xdd, 0x6b, 0x20),
+        "Medium" => rgb(0xd6, 0x9e, 0x2e),
+        "Low" => rgb(0x31, 0x82, 0xce),
+        _ => rgb(0x71, 0x80, 0x96),
+    }
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> [f32; 3] {
+    [f32::from(r) / 255.0, f32::from(g) / 255.0, f32::from(b) / 255.0]
+}
+
+const TEXT: [f32; 3] = [0.12, 0.16, 0.2];
+const MUTED: [f32; 3] = [0.42, 0.45, 0.5];
+const ACCENT: [f32; 3] = [0.77, 0.19, 0.19];
+const NOTE: [f32; 3] = [0.72, 0.47, 0.12];
+const REMEDIATION: [f32; 3] = [0.13, 0.45, 0.27];
+const RULE: [f32; 3] = [0.82, 0.84, 0.86];
+
+/// 依序排版到各頁的內容串流；y 為目前位置 (由上往下遞減)
+struct Layout<'a> {
+    font: &'a Font<'a>,
+    pages: Vec<Content>,
+    y: f32,
+    // 用到的字形與對應的文字，產生寬度表、ToUnicode 與子集字型
+    glyphs: BTreeMap<u16, String>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(font: &'a Font<'a>) -> Self {
+        let mut layout = Self { font, pages: Vec::new(), y: 0.0, glyphs: BTreeMap::new() };
+        layout.new_page();
+        layout
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Content::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    /// 剩餘空間不足 height 時換頁
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// 在目前頁面的 (x, y) 以基線寫入單行文字
+    fn text(&mut self, x: f32, y: f32, size: f32, color: [f32; 3], text: &str) {
+        self.text_on(self.pages.len() - 1, x, y, size, color, text);
+    }
+
+    fn text_on(&mut self, page: usize, x: f32, y: f32, size: f32, color: [f32; 3], text: &str) {
+        let mut encoded = Vec::with_capacity(text.len() * 2);
+        for c in text.chars() {
+            let glyph = self.font.glyph(c);
+            if glyph != 0 {
+                self.glyphs.entry(glyph).or_insert_with(|| c.to_string());
+            }
+            encoded.extend_from_slice(&glyph.to_be_bytes());
+        }
+        let [r, g, b] = color;
+        self.pages[page]
+            .begin_text()
+            .set_fill_rgb(r, g, b)
+            .set_font(FONT_RESOURCE, size)
+            .next_line(x, y)
+            .show(Str(&encoded))
+            .end_text();
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: [f32; 3]) {
+        let [r, g, b] = color;
+        self.page().set_fill_rgb(r, g, b).rect(x, y, width, height).fill_nonzero();
+    }
+
+    fn rule(&mut self) {
+        let y = self.y;
+        self.fill_rect(MARGIN, y, CONTENT_WIDTH, 0.6, RULE);
+    }
+
+    /// 自動換行的段落；indent 為左側縮排
+    fn paragraph(&mut self, text: &str, size: f32, color: [f32; 3], indent: f32) {
+        let leading = size * LINE_SPACING;
+        for line in wrap(self.font, text, size, CONTENT_WIDTH - indent) {
+            self.ensure(leading);
+            self.y -= leading;
+            let baseline = self.y + (leading - size) / 2.0 + size * 0.15;
+            self.text(MARGIN + indent, baseline, size, color, &line);
+        }
+    }
+
+    fn heading(&mut self, text: &str, size: f32) {
+        self.ensure(size * 3.0);
+        self.space(size * 0.6);
+        self.paragraph(text, size, TEXT, 0.0);
+        self.space(size * 0.4);
+    }
+
+    /// 填色的嚴重程度標籤，左下角位於 (x, y)；回傳寬度
+    fn badge(&mut self, x: f32, y: f32, label: &str) -> f32 {
+        let size = 8.0;
+        let width = self.font.width(label, size) + 10.0;
+        self.fill_rect(x, y, width, size + 6.0, severity_color(label));
+        self.text(x + 5.0, y + 4.0, size, [1.0, 1.0, 1.0], label);
+        width
+    }
+
+    fn title_page(&mut self, view: &ReportView) {
+        self.fill_rect(MARGIN, self.y, CONTENT_WIDTH, 3.0, ACCENT);
+        self.space(48.0);
+        self.text(MARGIN, self.y, 26.0, TEXT, "RedForge 掃描報告");
+        self.space(28.0);
+
+        let rows = [
+            ("目標", &view.target),
+            ("掃描日期", &view.started_at),
+            ("完成時間", &view.completed_at),
+            ("掃描類型", &view.scan_type),
+            ("狀態", &view.status),
+            ("報告產生時間", &view.generated_at),
+        ];
+        for (label, value) in rows {
+            // 標籤與值的第一行基線對齊
+            let top = self.y;
+            self.text(MARGIN, top - 11.8, 10.0, MUTED, label);
+            self.paragraph(value, 11.0, TEXT, 90.0);
+            self.y = self.y.min(top - 16.0) - 4.0;
+        }
+
+        self.heading("嚴重程度統計", 15.0);
+        let row_height = 24.0;
+        for count in &view.counts {
+            self.y -= row_height;
+            let y = self.y;
+            self.badge(MARGIN, y + 6.0, count.label);
+            let value = count.count.to_string();
+            let x = MARGIN + 200.0 - self.font.width(&value, 11.0);
+            self.text(x, y + 10.0, 11.0, TEXT, &value);
+            self.rule();
+        }
+        self.y -= row_height;
+        let y = self.y;
+        self.text(MARGIN, y + 10.0, 11.0, TEXT, "合計");
+        let total = view.total.to_string();
+        let x = MARGIN + 200.0 - self.font.width(&total, 11.0);
+        self.text(x, y + 10.0, 11.0, TEXT, &total);
+
+        self.heading("執行摘要", 15.0);
+        for paragraph in &view.executive_summary {
+            self.paragraph(paragraph, 10.0, TEXT, 0.0);
+            self.space(6.0);
+        }
+
+        self.space(18.0);
+        self.paragraph("已抑制或標記為誤報的發現不列入報告。安全標頭、SSL/TLS 與技術的完整結果請參考 HTML 報告。", 9.0, MUTED, 0.0);
+    }
+
+    fn findings(&mut self, view: &ReportView) {
+        self.new_page();
+        self.heading("發現", 20.0);
+        if view.groups.is_empty() {
+            self.paragraph("沒有發現。", 11.0, TEXT, 0.0);
+        }
+        for group in &view.groups {
+            self.heading(&format!("{} ({})", group.label, group.findings.len()), 14.0);
+            for (index, finding) in group.findings.iter().enumerate() {
+                self.finding(group.label, index + 1, finding);
+            }
+        }
+    }
+
+    fn finding(&mut self, label: &str, number: usize, finding: &FindingView) {
+        // 標籤、標題與至少兩行內容放在同一頁
+        self.ensure(64.0);
+        self.space(6.0);
+        let top = self.y;
+        let badge_width = self.badge(MARGIN, top - 15.0, label);
+        self.y = top + 1.0;
+        self.paragraph(&format!("{}. {}", number, single_line(&finding.title)), 12.0, TEXT, badge_width + 8.0);
+        self.space(4.0);
+
+        if finding.low_confidence {
+            self.paragraph("低信心度：目標封鎖掃描流量時取得，結果可能來自封鎖頁", 9.0, NOTE, 0.0);
+        }
+        let fields = [("受影響 URL", &finding.affected_url), ("CWE", &finding.cwe), ("Payload", &finding.payload)];
+        for (name, value) in fields.iter().filter(|(_, value)| !value.is_empty()) {
+            self.paragraph(&format!("{}: {}", name, single_line(value)), 9.5, TEXT, 0.0);
+        }
+        if !finding.description.is_empty() {
+            self.space(4.0);
+            self.paragraph(finding.description.trim(), 10.0, TEXT, 0.0);
+        }
+        self.space(4.0);
+        self.paragraph("修補建議", 10.0, REMEDIATION, 0.0);
+        let remediation = if finding.remediation.is_empty() {
+            "請依上述說明修正，並在修正後重新掃描確認。"
+        } else {
+            finding.remediation.as_str()
+        };
+        self.paragraph(remediation, 10.0, TEXT, 8.0);
+
+        if !finding.evidence.is_empty() {
+            self.space(4.0);
+            self.paragraph("證據", 9.0, MUTED, 0.0);
+            self.excerpt(&finding.evidence);
+        }
+        if finding.exchange.is_some() {
+            self.space(4.0);
+            self.paragraph("請求與回應", 9.0, MUTED, 0.0);
+            self.excerpt(&finding.transcript());
+        }
+        self.space(10.0);
+        self.rule();
+        self.space(4.0);
+    }
+
+    /// 原始證據：超過上限時截斷並註明
+    fn excerpt(&mut self, text: &str) {
+        let (kept, omitted) = truncate(text);
+        self.paragraph(&kept, 8.0, MUTED, 8.0);
+        if let Some(note) = omitted {
+            self.paragraph(&note, 8.0, NOTE, 8.0);
+        }
+    }
+
+    fn page_numbers(&mut self) {
+        let total = self.pages.len();
+        for page in 0..total {
+            let text = format!("第 {} / {} 頁", page + 1, total);
+            let x = (PAGE_WIDTH - self.font.width(&text, 8.0)) / 2.0;
+            self.text_on(page, x, FOOTER_Y, 8.0, MUTED, &text);
+        }
+    }
+}
+
+/// 依寬度換行：優先在空白或中文字之後斷行，沒有斷點的長字串 (URL、base64) 直接切斷
+fn wrap(font: &Font, text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for raw in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0.0;
+        // 目前行中最後一個可斷行的位置 (byte offset)
+        let mut break_at = None;
+        for c in raw.replace('\t', "    ").chars().filter(|c| !c.is_control()) {
+            let advance = font.width(c.encode_utf8(&mut [0; 4]), size);
+            if line_width + advance > width && !line.is_empty() {
+                match break_at.take() {
+                    Some(offset) => {
+                        let rest = line.split_off(offset);
+                        lines.push(line.trim_end().to_string());
+                        line = rest;
+                    }
+                    None => lines.push(std::mem::take(&mut line)),
+                }
+                line_width = font.width(&line, size);
+            }
+            line.push(c);
+            line_width += advance;
+            if c.is_whitespace() || !c.is_ascii() {
+                break_at = Some(line.len());
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// 標題與欄位值以單行呈現 (仍會自動換行)
+fn single_line(text: &str) -> String {
+    text.replace("\r\n", " ").replace(['\r', '\n'], " ")
+}
+
+/// 保留前 EXCERPT_MAX_LINES 行、EXCERPT_MAX_CHARS 個字元；有截斷時回傳說明
+fn truncate(text: &str) -> (String, Option<String>) {
+    let total_lines = text.lines().count();
+    let total_chars = text.chars().count();
+    let mut kept = String::new();
+    let mut chars = 0;
+    for line in text.lines().take(EXCERPT_MAX_LINES) {
+        let remaining = EXCERPT_MAX_CHARS - chars;
+        let count = line.chars().count();
+        if count >= remaining {
+            kept.extend(line.chars().take(remaining));
+            chars = EXCERPT_MAX_CHARS;
+            break;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+        chars += count + 1;
+    }
+    let kept = kept.trim_end_matches('\n').to_string();
+    if chars < EXCERPT_MAX_CHARS && total_lines <= EXCERPT_MAX_LINES {
+        return (kept, None);
+    }
+    let note = format!(
+        "(內容過長，已截斷：原始內容共 {} 行、{} 個字元，完整內容請參考 HTML 或 JSON 報告)",
+        total_lines, total_chars
+    );
+    (kept, Some(note))
+}
+
+/// 組成 PDF：頁面、Type0 字型 (Identity-H，字形編號即 CID) 與子集字型
+fn assemble(view: &ReportView, font: &Font, layout: Layout, data: &[u8], source: &FontSource) -> Result<Vec<u8>, String> {
+    let Layout { pages, glyphs, .. } = layout;
+    let used: Vec<u16> = std::iter::once(0).chain(glyphs.keys().copied()).collect();
+    let subset = subsetter::subset(data, source.index, subsetter::Profile::pdf(&used))
+        .map_err(|e| format!("無法建立子集字型: {}", e))?;
+    let cff = font.face.tables().cff.is_some();
+
+    let mut next = Ref::new(1);
+    let catalog_id = next.bump();
+    let tree_id = next.bump();
+    let info_id = next.bump();
+    let font_id = next.bump();
+    let cid_font_id = next.bump();
+    let descriptor_id = next.bump();
+    let cmap_id = next.bump();
+    let file_id = next.bump();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.document_info(info_id)
+        .title(TextStr(&format!("RedForge 掃描報告 - {}", view.target)))
+        .producer(TextStr("RedForge"));
+
+    let page_ids: Vec<(Ref, Ref)> = pages.iter().map(|_| (next.bump(), next.bump())).collect();
+    pdf.pages(tree_id).kids(page_ids.iter().map(|(page_id, _)| *page_id)).count(page_ids.len() as i32);
+    for (content, (page_id, content_id)) in pages.into_iter().zip(&page_ids) {
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT)).parent(tree_id).contents(*content_id);
+        page.resources().fonts().pair(FONT_RESOURCE, font_id);
+        page.finish();
+        pdf.stream(*content_id, &deflate(&content.finish())).filter(Filter::FlateDecode);
+    }
+
+    // 子集字型名稱需要六個大寫字母的前綴
+    let name: String = source.post_script_name.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    let base_font = format!("RFPDFA+{}", if name.is_empty() { "RedForgeFont" } else { &name });
+
+    pdf.type0_font(font_id)
+        .base_font(Name(base_font.as_bytes()))
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_font_id)
+        .to_unicode(cmap_id);
+
+    let mut cid_font = pdf.cid_font(cid_font_id);
+    cid_font
+        .subtype(if cff { CidFontType::Type0 } else { CidFontType::Type2 })
+        .base_font(Name(base_font.as_bytes()))
+        .system_info(SYSTEM_INFO)
+        .font_descriptor(descriptor_id)
+        .default_width(0.0);
+    if !cff {
+        cid_font.cid_to_gid_map_predefined(Name(b"Identity"));
+    }
+    let mut widths = cid_font.widths();
+    for glyph in glyphs.keys() {
+        widths.consecutive(*glyph, [font.advance(*glyph)]);
+    }
+    widths.finish();
+    cid_font.finish();
+
+    let face = &font.face;
+    let bbox = face.global_bounding_box();
+    let mut descriptor = pdf.font_descriptor(descriptor_id);
+    descriptor
+        .name(Name(base_font.as_bytes()))
+        .flags(FontFlags::SYMBOLIC)
+        .bbox(Rect::new(
+            font.units(bbox.x_min),
+            font.units(bbox.y_min),
+            font.units(bbox.x_max),
+            font.units(bbox.y_max),
+        ))
+        .italic_angle(0.0)
+        .ascent(font.units(face.ascender()))
+        .descent(font.units(face.descender()))
+        .cap_height(font.units(face.capital_height().unwrap_or(face.ascender())))
+        .stem_v(80.0);
+    if cff {
+        descriptor.font_file3(file_id);
+    } else {
+        descriptor.font_file2(file_id);
+    }
+    descriptor.finish();
+
+    let mut cmap = UnicodeCmap::new(Name(b"RedForge-UTF16"), SYSTEM_INFO);
+    for (glyph, text) in &glyphs {
+        cmap.pair_with_multiple(*glyph, text.chars());
+    }
+    pdf.cmap(cmap_id, &cmap.finish());
+
+    let compressed = deflate(&subset);
+    let mut file = pdf.stream(file_id, &compressed);
+    file.filter(Filter::FlateDecode);
+    if cff {
+        file.pair(Name(b"Subtype"), Name(b"OpenType"));
+    }
+    file.finish();
+
+    Ok(pdf.finish())
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, 6)
+}
@@ -0,0 +1,119 @@
+/**
+ * CPU-bound Response Analysis
+ *
+ * 大型頁面的正規表示式分析 (密鑰掃描、錯誤追蹤偵測、技術指紋) 在 async executor 上執行時會卡住
+ * reactor，拖慢同時進行的其他掃描。分析改交給 tokio 的 blocking 執行緒池，頁面內容整個移交過去，
+ * async 端只負責網路 I/O。
+ *
+ * 樣式只編譯一次 (OnceLock)，多個樣式的比對使用 RegexSet 一次掃過內容。
+ */
+
+use regex::{Regex, RegexSet};
+use std::sync::OnceLock;
+
+/// 在 blocking 執行緒池執行 CPU 密集的分析；分析中的 panic 原樣傳回呼叫端
+pub async fn offload<T, F>(analyze: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    match tokio::task::spawn_blocking(analyze).await {
+        Ok(result) => result,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// 預先編譯的一組具名樣式
+pub struct PatternSet {
+    set: RegexSet,
+    regexes: Vec<Regex>,
+    patterns: Vec<(&'static str, &'static str)>,
+}
+
+/// 樣式在內容中的一次符合
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternMatch {
+    pub name: &'static str,
+    pub pattern: &'static str,
+    /// 符合值在內容中的位元組位置
+    pub offset: usize,
+    /// 第一個擷取群組 (沒有群組時為整個符合的字串)
+    pub value: String,
+}
+
+impl PatternSet {
+    /// patterns 為 (樣式, 名稱)；樣式必須是合法的正規表示式
+    pub fn new(patterns: &[(&'static str, &'static str)]) -> Self {
+        Self {
+            set: RegexSet::new(patterns.iter().map(|(pattern, _)| *pattern)).unwrap(),
+            regexes: patterns.iter().map(|(pattern, _)| Regex::new(pattern).unwrap()).collect(),
+            patterns: patterns.to_vec(),
+        }
+    }
+
+    /// 符合的 (樣式, 名稱)，依定義順序
+    pub fn matches(&self, text: &str) -> Vec<(&'static str, &'static str)> {
+        self.set.matches(text).into_iter().map(|index| self.patterns[index]).collect()
+    }
+
+    /// 每個符合的位置與值，依樣式定義順序、再依位置；RegexSet 先篩出有符合的樣式，其餘不逐一掃描
+    pub fn locate(&self, text: &str) -> Vec<PatternMatch> {
+        self.set
+            .matches(text)
+            .into_iter()
+            .flat_map(|index| {
+                let (pattern, name) = self.patterns[index];
+                self.regexes[index].captures_iter(text).filter_map(move |captures| {
+                    let value = captures.get(1).or_else(|| captures.get(0))?;
+                    Some(PatternMatch { name, pattern, offset: value.start(), value: value.as_str().to_string() })
+                })
+            })
+            .collect()
+    }
+}
+
+/// 硬編碼密鑰的樣式，HTML 與 JavaScript 共用
+const SECRET_PATTERNS: &[(&str, &str)] = &[
+    (r#"(?i)api[_-]?key['"]?\s*[:=]\s*['"]([a-zA-Z0-9_\-]{20,})"#, "API Key"),
+    (r#"(?i)secret[_-]?key['"]?\s*[:=]\s*['"]([a-zA-Z0-9_\-]{20,})"#, "Secret Key"),
+    (r#"(?i)access[_-]?token['"]?\s*[:=]\s*['"]([a-zA-Z0-9_\-]{20,})"#, "Access Token"),
+    (r#"(?i)password['"]?\s*[:=]\s*['"]([^'"]{3,})"#, "Password"),
+    (r#"(?i)aws[_-]?access[_-]?key['"]?\s*[:=]\s*['"]([A-Z0-9]{20})"#, "AWS Access Key"),
+    (r#"(?i)private[_-]?key['"]?\s*[:=]"#, "Private Key"),
+    (r#"-----BEGIN (RSA |DSA )?PRIVATE KEY-----"#, "PEM Private Key"),
+];
+
+/// 打包後的 JavaScript 常見的服務金鑰格式 (不依賴變數名稱)
+const JS_SECRET_PATTERNS: &[(&str, &str)] = &[
+    (r#"\b(AIza[0-9A-Za-z_\-]{35})"#, "Google API Key"),
+    (r#"\b((?:sk|rk)_live_[0-9A-Za-z]{24,})"#, "Stripe Secret Key"),
+    (r#"\b(xox[abposr]-[0-9A-Za-z\-]{10,})"#, "Slack Token"),
+    (r#"(https://hooks\.slack\.com/services/[A-Za-z0-9/]{20,})"#, "Slack Webhook"),
+    (
+        r#"\b(10\.\d{1,3}\.\d{1,3}\.\d{1,3}|172\.(?:1[6-9]|2\d|3[01])\.\d{1,3}\.\d{1,3}|192\.168\.\d{1,3}\.\d{1,3})\b"#,
+        PRIVATE_IP,
+    ),
+];
+
+/// 私有 IP 位址的樣式名稱；洩露內部網路配置，但不是憑證
+pub const PRIVATE_IP: &str = "Private IP Address";
+
+/// HTML 原始碼中的硬編碼密鑰 (OWASP A02)
+pub fn secret_patterns() -> &'static PatternSet {
+    static PATTERNS: OnceLock<PatternSet> = OnceLock::new();
+    PATTERNS.get_or_init(|| PatternSet::new(SECRET_PATTERNS))
+}
+
+/// JavaScript 檔案中的密鑰：HTML 的樣式加上服務金鑰格式與私有 IP 位址
+pub fn js_secret_patterns() -> &'static PatternSet {
+    static PATTERNS: OnceLock<PatternSet> = OnceLock::new();
+    PATTERNS.get_or_init(|| PatternSet::new(&[SECRET_PATTERNS, JS_SECRET_PATTERNS].concat()))
+}
+
+/// 錯誤頁面中的堆疊追蹤與技術細節 (OWASP A09)
+pub fn leaks_error_details(body: &str) -> bool {
+    static DETAILS: OnceLock<Regex> = OnceLock::new();
+    DETAILS
+        .get_or_init(|| Regex::new(r"(?i)stack trace|traceback|exception|line |file:|at |sql|query|database").unwrap())
+        .is_match(body)
+}
@@ -0,0 +1,247 @@
+/**
+ * API Documentation Exposure
+ *
+ * 現代目標多半是 API：GraphQL 開啟 introspection 時任何人都能取得完整的 schema，
+ * 公開的 OpenAPI / Swagger 文件則列出所有端點與參數，包括不打算公開的管理端點。
+ *
+ * 回應必須通過結構驗證才算命中：GraphQL 要有 data.__schema.types，OpenAPI 要有 openapi / swagger 版本與
+ * paths 物件。回應 200 的 HTML 錯誤頁面與 catch-all 頁面不是 JSON，不會被誤判為文件。
+ * 文件中的路徑轉成 URL 交給爬取，讓之後的注入檢查也測試這些端點；接受 JSON 內容的操作另外記錄
+ * 字串欄位，供 NoSQL 注入檢查替換。
+ */
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 嘗試 introspection 的 GraphQL 端點
+pub const GRAPHQL_PATHS: &[&str] = &["/graphql", "/api/graphql", "/v1/graphql"];
+/// OpenAPI / Swagger 文件與 Swagger UI 的常見位置
+pub const SPEC_PATHS: &[&str] = &["/swagger.json", "/openapi.json", "/v2/api-docs", "/v3/api-docs"];
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui/";
+
+/// 只取得類型與 mutation 名稱的精簡 introspection 查詢
+pub const INTROSPECTION_QUERY: &str =
+    r#"{"query":"query IntrospectionQuery { __schema { queryType { name } mutationType { name fields { name } } types { name kind } } }"}"#;
+
+/// 看起來是管理功能的路徑片段
+const ADMIN_HINTS: &[&str] = &["admin", "internal", "debug", "manage", "actuator", "private", "sudo", "impersonate"];
+/// 轉成爬取 URL 的文件路徑上限
+const MAX_SPEC_URLS: usize = 100;
+/// 每個 JSON 操作記錄的字串欄位上限
+const MAX_JSON_FIELDS: usize = 10;
+const HTTP_METHODS: &[&str] = &["get", "put", "post", "delete", "patch", "head", "options"];
+
+/// introspection 回應中的 schema 摘要
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphqlSchema {
+    pub types: usize,
+    pub mutations: Vec<String>,
+}
+
+/// 解析 introspection 回應；沒有 data.__schema.types 陣列時回傳 None
+pub fn parse_introspection(body: &[u8]) -> Option<GraphqlSchema> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let schema = value.get("data")?.get("__schema")?;
+    let types = schema.get("types")?.as_array()?;
+    let mutations = schema
+        .get("mutationType")
+        .and_then(|mutation| mutation.get("fields"))
+        .and_then(|fields| fields.as_array())
+        .map(|fields| fields.iter().filter_map(|field| field.get("name")?.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    Some(GraphqlSchema { types: types.len(), mutations })
+}
+
+/// 文件中的一個操作
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    pub method: String,
+    pub path: String,
+    /// query 參數名稱
+    pub query: Vec<String>,
+    /// JSON 內容的頂層字串欄位；不接受 JSON 內容時為空
+    pub json_fields: Vec<String>,
+}
+
+/// 接受 JSON 內容的操作
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonOperation {
+    pub method: String,
+    pub url: String,
+    pub fields: Vec<String>,
+}
+
+/// 解析後的 OpenAPI / Swagger 文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiSpec {
+    /// 例如 "OpenAPI 3.0.1"、"Swagger 2.0"
+    pub version: String,
+    pub title: Option<String>,
+    /// 路徑的前綴 (Swagger 2 的 basePath 或 OpenAPI 3 第一個相對的 servers url)
+    pub base_path: String,
+    pub operations: Vec<Operation>,
+}
+
+impl ApiSpec {
+    /// 看起來是管理功能的路徑，依出現順序且不重複
+    pub fn admin_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        for operation in &self.operations {
+            let lower = operation.path.to_lowercase();
+            let admin = lower.split(|c: char| !c.is_ascii_alphanumeric()).any(|segment| ADMIN_HINTS.contains(&segment));
+            if admin && !paths.contains(&operation.path) {
+                paths.push(operation.path.clone());
+            }
+        }
+        paths
+    }
+
+    /// 可以爬取的 URL：GET 操作，路徑參數以 1 取代，query 參數帶上 1 讓注入檢查有參數可測
+    pub fn urls(&self, origin: &str) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        for operation in self.operations.iter().filter(|operation| operation.method == "GET") {
+            let url = self.url(origin, operation);
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+            if urls.len() >= MAX_SPEC_URLS {
+                break;
+            }
+        }
+        urls
+    }
+
+    /// 接受 JSON 內容且有字串欄位的操作
+    pub fn json_operations(&self, origin: &str) -> Vec<JsonOperation> {
+        self.operations
+            .iter()
+            .filter(|operation| !operation.json_fields.is_empty())
+            .map(|operation| JsonOperation {
+                method: operation.method.clone(),
+                url: self.url(origin, operation),
+                fields: operation.json_fields.clone(),
+            })
+            .collect()
+    }
+
+    fn url(&self, origin: &str, operation: &Operation) -> String {
+        static PARAM: OnceLock<Regex> = OnceLock::new();
+        let param = PARAM.get_or_init(|| Regex::new(r"\{[^}/]+\}").unwrap());
+        let path = param.replace_all(&operation.path, "1");
+        let url = format!("{}{}{}", origin.trim_end_matches('/'), self.base_path.trim_end_matches('/'), path);
+        if operation.query.is_empty() {
+            return url;
+        }
+        let query: Vec<String> = operation.query.iter().map(|name| format!("{}=1", urlencoding::encode(name))).collect();
+        format!("{}?{}", url, query.join("&"))
+    }
+}
+
+/// 解析 OpenAPI 3 / Swagger 2 文件；不是 JSON、缺少版本或 paths 物件時回傳 None
+pub fn parse_spec(body: &[u8]) -> Option<ApiSpec> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let version = match (value.get("openapi").and_then(|v| v.as_str()), value.get("swagger").and_then(|v| v.as_str())) {
+        (Some(version), _) => format!("OpenAPI {}", version),
+        (None, Some(version)) => format!("Swagger {}", version),
+        (None, None) => return None,
+    };
+    let paths = value.get("paths")?.as_object()?;
+
+    let base_path = value
+        .get("basePath")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            value
+                .get("servers")?
+                .as_array()?
+                .iter()
+                .filter_map(|server| server.get("url")?.as_str())
+                .find(|url| url.starts_with('/'))
+        })
+        .unwrap_or_default()
+        .to_string();
+
+    let mut operations = Vec::new();
+    for (path, item) in paths {
+        let Some(item) = item.as_object() else { continue };
+        let shared = query_params(item.get("parameters"));
+        for (method, operation) in item.iter().filter(|(method, _)| HTTP_METHODS.contains(&method.as_str())) {
+            let mut query = shared.clone();
+            for name in query_params(operation.get("parameters")) {
+                if !query.contains(&name) {
+                    query.push(name);
+                }
+            }
+            let json_fields = json_fields(&value, operation);
+            operations.push(Operation { method: method.to_uppercase(), path: path.clone(), query, json_fields });
+        }
+    }
+
+    Some(ApiSpec {
+        version,
+        title: value.get("info").and_then(|info| info.get("title")).and_then(|v| v.as_str()).map(str::to_string),
+        base_path,
+        operations,
+    })
+}
+
+fn query_params(parameters: Option<&serde_json::Value>) -> Vec<String> {
+    parameters
+        .and_then(|parameters| parameters.as_array())
+        .map(|parameters| {
+            parameters
+                .iter()
+                .filter(|parameter| parameter.get("in").and_then(|v| v.as_str()) == Some("query"))
+                .filter_map(|parameter| parameter.get("name")?.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 操作的 JSON 內容 (OpenAPI 3 的 requestBody 或 Swagger 2 的 body 參數) 的頂層字串欄位
+fn json_fields(root: &serde_json::Value, operation: &serde_json::Value) -> Vec<String> {
+    let schema = operation
+        .get("requestBody")
+        .and_then(|body| body.get("content"))
+        .and_then(|content| content.get("application/json"))
+        .and_then(|json| json.get("schema"))
+        .or_else(|| {
+            operation
+                .get("parameters")?
+                .as_array()?
+                .iter()
+                .find(|parameter| parameter.get("in").and_then(|v| v.as_str()) == Some("body"))?
+                .get("schema")
+        });
+    let Some(schema) = schema.map(|schema| resolve(root, schema)) else {
+        return Vec::new();
+    };
+    schema
+        .get("properties")
+        .and_then(|properties| properties.as_object())
+        .map(|properties| {
+            properties
+                .iter()
+                .filter(|(_, property)| resolve(root, property).get("type").and_then(|v| v.as_str()) == Some("string"))
+                .map(|(name, _)| name.clone())
+                .take(MAX_JSON_FIELDS)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 解析文件內的 $ref ("#/components/schemas/User")；無法解析時回傳原本的值
+fn resolve<'a>(root: &'a serde_json::Value, schema: &'a serde_json::Value) -> &'a serde_json::Value {
+    schema
+        .get("$ref")
+        .and_then(|reference| reference.as_str())
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+        .unwrap_or(schema)
+}
+
+/// 頁面是否為 Swagger UI
+pub fn is_swagger_ui(body: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(body);
+    text.contains("SwaggerUIBundle") || text.contains("swagger-ui-bundle.js") || text.contains("id=\"swagger-ui\"")
+}
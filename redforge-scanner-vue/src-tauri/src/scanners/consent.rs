@@ -0,0 +1,186 @@
+/**
+ * Cookie Consent Interstitial Detection
+ *
+ * 許多歐盟網站在使用者同意前只回傳同意頁 (OneTrust、Cookiebot、Didomi)，掃描器看到的是同意管理平台而不是實際應用，
+ * 技術檢測與內容檢查的「未發現」因此沒有意義。
+ *
+ * 偵測到同意頁時，設定各平台文件中記錄的同意 cookie (最小同意狀態：只允許必要 cookie) 後重新取得頁面；
+ * 繞過成功時後續請求都附加這些 cookie，失敗時由呼叫端將受影響的檢查標記為涵蓋範圍降級。
+ */
+
+use crate::models::*;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::ScannerResult;
+use base64::Engine;
+use chrono::Utc;
+use regex::Regex;
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// 去除 script / style 與標籤後的文字少於此長度時，視為只有同意頁
+const INTERSTITIAL_TEXT_LENGTH: usize = 1500;
+
+/// 同意管理平台
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentManager {
+    OneTrust,
+    Cookiebot,
+    Didomi,
+}
+
+impl ConsentManager {
+    const ALL: [ConsentManager; 3] = [ConsentManager::OneTrust, ConsentManager::Cookiebot, ConsentManager::Didomi];
+
+    /// 頁面中的平台特徵 (小寫比對)
+    fn signatures(&self) -> &'static [&'static str] {
+        match self {
+            ConsentManager::OneTrust => &["cdn.cookielaw.org", "onetrust-banner-sdk", "optanon-alert-box", "otsdkstub"],
+            ConsentManager::Cookiebot => &["consent.cookiebot.com", "cybotcookiebotdialog", "cookieconsent.js"],
+            ConsentManager::Didomi => &["sdk.privacy-center.org", "didomi-host", "window.didomiconfig"],
+        }
+    }
+
+    /// 最小同意狀態的 cookie：已回應同意視窗，只允許必要 cookie
+    fn default_cookies(&self) -> Vec<(String, String)> {
+        let now = Utc::now();
+        match self {
+            ConsentManager::OneTrust => vec![
+                ("OptanonAlertBoxClosed".to_string(), now.to_rfc3339()),
+                (
+                    "OptanonConsent".to_string(),
+                    "isGpcEnabled=0&interactionCount=1&groups=C0001%3A1%2CC0002%3A0%2CC0003%3A0%2CC0004%3A0".to_string(),
+                ),
+            ],
+            ConsentManager::Cookiebot => vec![(
+                "CookieConsent".to_string(),
+                "{stamp:%27-1%27%2Cnecessary:true%2Cpreferences:false%2Cstatistics:false%2Cmarketing:false%2Cmethod:%27explicit%27%2Cver:1}".to_string(),
+            )],
+            ConsentManager::Didomi => {
+                let token = serde_json::json!({
+                    "user_id": "redforge-scanner",
+                    "created": now.to_rfc3339(),
+                    "updated": now.to_rfc3339(),
+                    "vendors": { "enabled": [] },
+                    "purposes": { "enabled": [] },
+                    "version": 2,
+                });
+                let encoded = base64::engine::general_purpose::STANDARD.encode(token.to_string());
+                vec![("didomi_token".to_string(), encoded)]
+            }
+        }
+    }
+
+    fn detect(html_lower: &str) -> Option<ConsentManager> {
+        Self::ALL
+            .into_iter()
+            .find(|manager| manager.signatures().iter().any(|s| html_lower.contains(s)))
+    }
+}
+
+/// 分析實際使用的頁面
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyzedPage {
+    /// 實際應用頁面 (沒有同意頁，或繞過成功)
+    RealPage,
+    /// 只取得同意頁，內容檢查的結果不可信
+    Interstitial,
+}
+
+/// 同意頁偵測結果，附在報告上
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConsentCheck {
+    pub manager: ConsentManager,
+    /// 基礎頁面只有同意頁，沒有實際內容
+    pub interstitial: bool,
+    pub bypass_attempted: bool,
+    /// 繞過時設定的 cookie 名稱
+    pub cookies: Vec<String>,
+    pub analyzed: AnalyzedPage,
+    /// 繞過成功時後續請求附加的 Cookie 標頭值；不寫入報告
+    #[serde(skip)]
+    pub cookie_header: Option<String>,
+}
+
+pub struct ConsentChecker {
+    client: ScanClient,
+}
+
+impl ConsentChecker {
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 10))
+                .build()?
+                .into(),
+        })
+    }
+
+    /// 偵測基礎頁面的同意頁並嘗試繞過；沒有同意管理平台時回傳 None
+    ///
+    /// overrides 為使用者設定的同意 cookie，同名時取代平台預設值
+    pub async fn check(&self, url: &str, overrides: &std::collections::HashMap<String, String>) -> ScannerResult<Option<ConsentCheck>> {
+        let body = self.client.get(url).send().await?.text().await?;
+        let Some(manager) = ConsentManager::detect(&body.to_lowercase()) else {
+            return Ok(None);
+        };
+
+        // 只是覆蓋在實際內容上的橫幅，不影響分析
+        if !is_interstitial(&body) {
+            return Ok(Some(ConsentCheck {
+                manager,
+                interstitial: false,
+                bypass_attempted: false,
+                cookies: Vec::new(),
+                analyzed: AnalyzedPage::RealPage,
+                cookie_header: None,
+            }));
+        }
+
+        let mut cookies = manager.default_cookies();
+        for (name, value) in overrides {
+            match cookies.iter_mut().find(|(existing, _)| existing == name) {
+                Some(cookie) => cookie.1 = value.clone(),
+                None => cookies.push((name.clone(), value.clone())),
+            }
+        }
+        let cookie_header = cookies
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        let bypassed = self.client.get(url).header("cookie", &cookie_header).send().await?.text().await?;
+        let analyzed = if is_interstitial(&bypassed) { AnalyzedPage::Interstitial } else { AnalyzedPage::RealPage };
+
+        Ok(Some(ConsentCheck {
+            manager,
+            interstitial: true,
+            bypass_attempted: true,
+            cookies: cookies.into_iter().map(|(name, _)| name).collect(),
+            analyzed,
+            cookie_header: (analyzed == AnalyzedPage::RealPage).then_some(cookie_header),
+        }))
+    }
+}
+
+/// 同意管理平台特徵存在，且去除腳本與標籤後幾乎沒有文字內容
+fn is_interstitial(html: &str) -> bool {
+    let lower = html.to_lowercase();
+    if ConsentManager::detect(&lower).is_none() {
+        return false;
+    }
+
+    static SCRIPTS: OnceLock<Regex> = OnceLock::new();
+
+    let scripts = SCRIPTS.get_or_init(|| Regex::new(r"(?is)<(script|style|noscript)\b.*?</(script|style|noscript)>").unwrap());
+    static TAGS: OnceLock<Regex> = OnceLock::new();
+    let tags = TAGS.get_or_init(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+    let without_scripts = scripts.replace_all(html, " ");
+    let text = tags.replace_all(&without_scripts, " ");
+    let visible: usize = text.split_whitespace().map(|word| word.len() + 1).sum();
+
+    visible < INTERSTITIAL_TEXT_LENGTH
+}
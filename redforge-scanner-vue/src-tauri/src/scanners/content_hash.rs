@@ -0,0 +1,98 @@
+/**
+ * Page Content Hashing
+ *
+ * 每次掃描記錄基礎頁面與主要路徑的正規化內容雜湊，之後只要重新取得這幾個 URL 比較雜湊，
+ * 就能判斷目標自上次掃描後是否有變動，決定是否值得執行完整的重新掃描。
+ */
+
+use crate::models::*;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::response_diff;
+use crate::scanners::ScannerResult;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// 除了基礎頁面外一併記錄的路徑 (不存在時記錄狀態碼，之後出現也算變動)
+const KEY_PATHS: &[&str] = &["/robots.txt", "/sitemap.xml"];
+
+/// 單一 URL 的內容雜湊
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PageHash {
+    pub url: String,
+    pub status: Option<u16>,
+    /// 正規化後內容的 SHA-256；請求失敗時為 None
+    pub hash: Option<String>,
+}
+
+/// 與上次掃描比較的結果
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PageChange {
+    pub url: String,
+    pub previous: Option<PageHash>,
+    pub current: PageHash,
+    pub changed: bool,
+}
+
+pub struct ContentHasher {
+    client: ScanClient,
+}
+
+impl ContentHasher {
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 10))
+                .build()?
+                .into(),
+        })
+    }
+
+    /// 基礎頁面、主要路徑與額外的 URL (例如暖身時請求的靜態資源)
+    pub fn key_urls(base_url: &str, extra: impl IntoIterator<Item = String>) -> Vec<String> {
+        let mut urls = vec![base_url.to_string()];
+        if let Ok(base) = reqwest::Url::parse(base_url) {
+            urls.extend(KEY_PATHS.iter().filter_map(|path| base.join(path).ok()).map(|url| url.to_string()));
+        }
+        for url in extra {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+
+    pub async fn hash_all(&self, urls: &[String]) -> Vec<PageHash> {
+        let mut hashes = Vec::with_capacity(urls.len());
+        for url in urls {
+            hashes.push(self.hash(url).await);
+        }
+        hashes
+    }
+
+    async fn hash(&self, url: &str) -> PageHash {
+        let mut page = PageHash { url: url.to_string(), status: None, hash: None };
+        if let Ok(response) = self.client.get(url).send().await {
+            page.status = Some(response.status().as_u16());
+            if let Ok(body) = response.text().await {
+                page.hash = Some(response_diff::content_hash(&body));
+            }
+        }
+        page
+    }
+}
+
+/// 依 URL 比較兩組雜湊；狀態碼或雜湊不同即視為變動，本次請求失敗時不算變動
+pub fn compare(previous: &[PageHash], current: Vec<PageHash>) -> Vec<PageChange> {
+    current
+        .into_iter()
+        .map(|page| {
+            let before = previous.iter().find(|p| p.url == page.url).cloned();
+            let changed = match &before {
+                _ if page.hash.is_none() => false,
+                Some(before) => before.status != page.status || before.hash != page.hash,
+                None => true,
+            };
+            PageChange { url: page.url.clone(), previous: before, current: page, changed }
+        })
+        .collect()
+}
@@ -0,0 +1,694 @@
+/**
+ * Scan Control
+ *
+ * 執行中掃描的暫停 / 繼續控制。掃描在 `scope` 內執行，共用的 ScanClient 在每個請求送出前呼叫
+ * `checkpoint`：暫停時在此等待，直到繼續或掃描被終止。
+ *
+ * 各檢查在 `check` 內執行，目前的檢查 id 放在 task-local 中，請求、逾時與稽核紀錄都歸屬到該檢查。
+ */
+
+use crate::audit::AuditLog;
+use crate::canary::CanaryRegistry;
+use crate::scanners::auth::{AuthSession, AuthenticationReport};
+use crate::scanners::check_metrics::{CheckLedger, CheckMetrics};
+use crate::scanners::evidence::{self, ExchangeLog};
+use crate::scanners::finding_caps::DiscoveredContent;
+use crate::scanners::proxy::ProxyRoute;
+use crate::scanners::raw_http::TlsIdentity;
+use crate::events::EventCoalescer;
+use crate::scanners::rate_limit::{RequestRateReport, TokenBucket};
+use crate::scanners::request_budget::RequestBudget;
+use crate::scanners::time_box::{self, TimeBox, TimeBoxReport};
+use crate::scanners::{ScannerError, ScannerResult};
+use crate::models::{CheckCoverage, CoverageStatus, HttpExchange, HttpSettings, RequestBudgetStatus, ScanProgress, ScanResult, ScanStage, StageTiming};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+/// 暫停超過此時間後掃描自動失敗
+pub const MAX_PAUSE: Duration = Duration::from_secs(30 * 60);
+
+tokio::task_local! {
+    static CURRENT: ScanControl;
+    static CHECK: String;
+}
+
+#[derive(Clone, Default)]
+pub struct ScanControl {
+    inner: Arc<ControlInner>,
+}
+
+#[derive(Default)]
+struct ControlInner {
+    task_id: String,
+    paused: AtomicBool,
+    // 每次暫停遞增，用來判斷逾時計時器是否仍對應同一次暫停
+    pause_generation: Mutex<u64>,
+    aborted: Mutex<Option<String>>,
+    cancelled: AtomicBool,
+    changed: Notify,
+    stages: Mutex<StageTracker>,
+    // 進度、日誌與發現事件經過節流後送到前端
+    events: Option<EventCoalescer>,
+    audit: Option<AuditLog>,
+    canaries: Option<CanaryRegistry>,
+    time_box: Mutex<Option<TimeBox>>,
+    checks: CheckLedger,
+    // 各檢查自行記錄的執行結果
+    coverage: Mutex<Vec<CheckCoverage>>,
+    discovered: Mutex<Vec<DiscoveredContent>>,
+    limits: Mutex<RequestLimits>,
+    budget: RequestBudget,
+    // 第一個與最後一個請求的時間，計算實際速率
+    request_span: Mutex<Option<(Instant, Instant)>>,
+    // 最近的請求與回應，產生發現時附上作為證據
+    exchanges: ExchangeLog,
+    // 已寫入日誌的發現 id，同一發現多次送出時只記錄一次
+    logged_findings: Mutex<HashSet<String>>,
+    // 已登入狀態的掃描：送往目標來源的請求附上認證標頭
+    session: Mutex<Option<Arc<AuthSession>>>,
+    // 原始 socket (TLS 探測、連接埠掃描、原始請求) 經過的代理
+    proxy: Mutex<Option<Arc<ProxyRoute>>>,
+    // 原始 TLS 交握 (憑證分析、TLS 1.3 探測、原始請求) 出示的用戶端憑證
+    tls_identity: Mutex<Option<Arc<TlsIdentity>>>,
+}
+
+/// 掃描設定的每秒請求數上限與每個請求前的固定延遲
+#[derive(Default, Clone)]
+struct RequestLimits {
+    requests_per_second: Option<f64>,
+    bucket: Option<Arc<TokenBucket>>,
+    delay: Duration,
+}
+
+#[derive(Default)]
+struct StageTracker {
+    current: Option<(ScanStage, DateTime<Utc>, Instant)>,
+    progress: u8,
+    timings: Vec<StageTiming>,
+    skipped: Option<String>,
+}
+
+impl StageTracker {
+    fn close_current(&mut self) {
+        if let Some((stage, started_at, started)) = self.current.take() {
+            self.timings.push(StageTiming {
+                stage,
+                started_at,
+                duration_ms: started.elapsed().as_millis() as u64,
+                skipped: self.skipped.take(),
+            });
+        }
+    }
+}
+
+impl ScanControl {
+    pub fn new(task_id: &str, events: Option<EventCoalescer>) -> Self {
+        Self {
+            inner: Arc::new(ControlInner {
+                task_id: task_id.to_string(),
+                events,
+                audit: AuditLog::open(task_id),
+                canaries: Some(CanaryRegistry::open(task_id)),
+                ..ControlInner::default()
+            }),
+        }
+    }
+
+    /// 進入新階段：結束上一個階段的計時並發送進度事件
+    pub fn enter_stage(&self, stage: ScanStage, progress: u8, message: String) {
+        {
+            let mut tracker = self.inner.stages.lock().unwrap_or_else(|e| e.into_inner());
+            tracker.close_current();
+            tracker.current = Some((stage.clone(), Utc::now(), Instant::now()));
+            tracker.progress = progress.min(100);
+        }
+        self.flush_audit();
+        self.emit(stage, progress, message);
+    }
+
+    /// 將目前階段標記為略過，原因記錄在階段耗時上
+    pub fn skip_stage(&self, reason: String) {
+        let (stage, progress) = self.current();
+        self.inner.stages.lock().unwrap_or_else(|e| e.into_inner()).skipped = Some(reason.clone());
+        self.emit(stage, progress, format!("略過: {}", reason));
+    }
+
+    /// 目前的階段與進度 (0-100)，暫停與繼續事件沿用
+    pub fn current(&self) -> (ScanStage, u8) {
+        let tracker = self.inner.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let stage = tracker.current.as_ref().map(|(s, _, _)| s.clone()).unwrap_or(ScanStage::Queued);
+        (stage, tracker.progress)
+    }
+
+    /// 結束計時並取出各階段耗時
+    pub fn finish_stages(&self) -> Vec<StageTiming> {
+        let mut tracker = self.inner.stages.lock().unwrap_or_else(|e| e.into_inner());
+        tracker.close_current();
+        self.flush_audit();
+        std::mem::take(&mut tracker.timings)
+    }
+
+    /// 目前的檢查 id；不在 `check` 內時為目前階段 (例如 warmup)
+    fn current_check(&self) -> String {
+        CHECK.try_with(|check| check.clone()).unwrap_or_else(|_| self.current().0.message_key())
+    }
+
+    /// 記錄送出的請求，歸屬到目前的檢查
+    pub fn record_request(&self, method: &str, url: &str, body: Option<&[u8]>) {
+        let check_id = self.current_check();
+        tracing::debug!(task_id = %self.inner.task_id, check = %check_id, "➡️  {} {}", method, url);
+        self.inner.checks.request(&check_id);
+        if let Some(audit) = &self.inner.audit {
+            audit.record(&check_id, method, url, body);
+        }
+    }
+
+    /// 記錄以逾時失敗的請求
+    pub fn record_timeout(&self) {
+        self.inner.checks.timeout(&self.current_check());
+    }
+
+    /// 記錄檢查產生的發現數量
+    pub fn record_findings(&self, check_id: &str, count: usize) {
+        self.inner.checks.findings(check_id, count);
+    }
+
+    /// 記錄檢查的執行結果；同一檢查記錄多次時 (例如主要目標與其他連接埠的 TLS 分析) 保留較差的狀態
+    pub fn cover(&self, check_id: &str, status: CoverageStatus, message: Option<String>) {
+        let mut coverage = self.inner.coverage.lock().unwrap_or_else(|e| e.into_inner());
+        match coverage.iter_mut().find(|entry| entry.check == check_id) {
+            Some(entry) if status > entry.status => {
+                entry.status = status;
+                entry.message = message;
+            }
+            Some(_) => {}
+            None => coverage.push(CheckCoverage { check: check_id.to_string(), status, requests: 0, message }),
+        }
+    }
+
+    /// 各檢查的涵蓋範圍：檢查記錄的結果加上因時間限制或請求預算未執行、提前結束的檢查，
+    /// 請求數取自檢查統計，依 time_box 的優先順序排列
+    pub fn coverage(&self) -> Vec<CheckCoverage> {
+        if let Some(time_box) = self.time_box_report() {
+            for check in &time_box.skipped {
+                let message = format!("限時掃描 ({} 秒) 的時間已用盡，未執行", time_box.max_duration_secs);
+                self.cover(check, CoverageStatus::Skipped, Some(message));
+            }
+        }
+        let budget = self.request_budget();
+        for check in &budget.skipped {
+            self.cover(check, CoverageStatus::Skipped, Some("請求預算已用盡，未執行".to_string()));
+        }
+        for check in &budget.truncated {
+            self.cover(check, CoverageStatus::Partial, Some("達到請求預算，檢查提前結束".to_string()));
+        }
+
+        let metrics = self.check_metrics();
+        let mut coverage = self.inner.coverage.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        for entry in &mut coverage {
+            entry.requests = metrics.iter().find(|m| m.check_id == entry.check).map_or(0, |m| m.requests);
+        }
+        coverage.sort_by_key(|entry| time_box::priority(&entry.check));
+        coverage
+    }
+
+    /// 記錄超過上限而合併的發現清單
+    pub fn discovered(&self, content: DiscoveredContent) {
+        self.inner.discovered.lock().unwrap_or_else(|e| e.into_inner()).push(content);
+    }
+
+    /// 取出合併的發現清單
+    pub fn take_discovered(&self) -> Vec<DiscoveredContent> {
+        std::mem::take(&mut *self.inner.discovered.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// 各檢查的請求、逾時、發現與耗時
+    pub fn check_metrics(&self) -> Vec<CheckMetrics> {
+        self.inner.checks.snapshot()
+    }
+
+    /// 套用掃描設定的請求預算、每秒請求數上限與請求延遲
+    pub fn limit_requests(&self, http: &HttpSettings) {
+        self.inner.budget.configure(http);
+        *self.inner.limits.lock().unwrap_or_else(|e| e.into_inner()) = RequestLimits {
+            requests_per_second: http.requests_per_second,
+            bucket: http.requests_per_second.map(|rps| Arc::new(TokenBucket::new(rps))),
+            delay: Duration::from_millis(http.request_delay_ms),
+        };
+    }
+
+    /// 原始 socket 的連線經過 route；None 時直接連線
+    pub fn route_through(&self, route: Option<ProxyRoute>) {
+        *self.inner.proxy.lock().unwrap_or_else(|e| e.into_inner()) = route.map(Arc::new);
+    }
+
+    fn proxy_route(&self) -> Option<Arc<ProxyRoute>> {
+        self.inner.proxy.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 原始 TLS 交握出示 identity；None 時不出示用戶端憑證
+    pub fn present_identity(&self, identity: Option<TlsIdentity>) {
+        *self.inner.tls_identity.lock().unwrap_or_else(|e| e.into_inner()) = identity.map(Arc::new);
+    }
+
+    fn tls_identity(&self) -> Option<Arc<TlsIdentity>> {
+        self.inner.tls_identity.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 請求送出前呼叫：目前檢查或整個掃描的請求預算用完時回傳錯誤，否則等待速率限制的 token 與設定的延遲
+    async fn pace(&self) -> Result<(), String> {
+        self.inner.budget.spend(&self.current_check())?;
+        let limits = self.inner.limits.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(bucket) = &limits.bucket {
+            bucket.acquire().await;
+        }
+        if !limits.delay.is_zero() {
+            tokio::time::sleep(limits.delay).await;
+        }
+        let now = Instant::now();
+        let mut span = self.inner.request_span.lock().unwrap_or_else(|e| e.into_inner());
+        *span = Some(span.map_or((now, now), |(first, _)| (first, now)));
+        Ok(())
+    }
+
+    /// 目前檢查在預算內還能送出的請求數 (整體與檢查預算中較小者)；沒有上限時為 None
+    pub fn remaining_requests(&self) -> Option<u64> {
+        self.inner.budget.remaining(&self.current_check())
+    }
+
+    /// 請求預算的使用情況與因預算未執行或提前結束的檢查
+    pub fn request_budget(&self) -> RequestBudgetStatus {
+        self.inner.budget.status()
+    }
+
+    /// 發現附帶的回應內容保留的位元組數
+    pub fn limit_evidence(&self, body_bytes: usize) {
+        self.inner.exchanges.set_body_limit(body_bytes);
+    }
+
+    /// 最近一次對 url 的請求與回應
+    pub fn exchange(&self, url: &str) -> Option<HttpExchange> {
+        self.inner.exchanges.find(url)
+    }
+
+    /// 之後的請求以此登入狀態送出
+    pub fn authenticate(&self, session: Arc<AuthSession>) {
+        *self.inner.session.lock().unwrap_or_else(|e| e.into_inner()) = Some(session);
+    }
+
+    fn session(&self) -> Option<Arc<AuthSession>> {
+        self.inner.session.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 報告中記錄的登入身分；未設定登入時為 None
+    pub fn authentication_report(&self) -> Option<AuthenticationReport> {
+        self.session().map(|session| session.report())
+    }
+
+    /// 目標回應 401 或距離上次確認太久時重新確認登入狀態，失效時終止掃描
+    async fn watch_session(&self, url: &reqwest::Url, status: u16) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        if !session.due(url, status) {
+            return;
+        }
+        let result = session.verify().await;
+        session.finish_check(&result);
+        if let Err(e) = result {
+            self.log(format!("🔒 身分 {} 的登入狀態已失效: {}", session.label(), e));
+            self.abort(format!("登入狀態已失效，掃描終止: {}", e));
+        }
+    }
+
+    /// 送出的請求總數與實際速率 (超過請求數上限而未送出的不計)
+    pub fn request_rate_report(&self) -> RequestRateReport {
+        let limits = self.inner.limits.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let total = self.inner.budget.sent();
+        let span = *self.inner.request_span.lock().unwrap_or_else(|e| e.into_inner());
+        RequestRateReport::new(total, span, limits.requests_per_second, limits.delay)
+    }
+
+    /// 開始計算限時掃描的時間
+    pub fn start_time_box(&self, max_duration_secs: u64) {
+        *self.inner.time_box.lock().unwrap_or_else(|e| e.into_inner()) = Some(TimeBox::new(max_duration_secs));
+    }
+
+    /// 是否可以啟動檢查；限時掃描的時間或請求預算用完時記錄為略過並回傳 false
+    pub fn admit(&self, check_id: &str) -> bool {
+        let in_time = match self.inner.time_box.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            Some(time_box) => time_box.admit(check_id),
+            None => true,
+        };
+        in_time && self.inner.budget.admit(check_id)
+    }
+
+    /// 限時掃描的摘要；沒有設定時間限制時為 None
+    pub fn time_box_report(&self) -> Option<TimeBoxReport> {
+        self.inner.time_box.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(TimeBox::report)
+    }
+
+    /// 產生此掃描的 canary，歸屬到目前的檢查
+    pub fn canary(&self, purpose: &str) -> String {
+        let registry = self.inner.canaries.clone().unwrap_or_else(detached_canaries);
+        registry.issue(&self.current_check(), purpose)
+    }
+
+    pub fn flush_audit(&self) {
+        if let Some(audit) = &self.inner.audit {
+            audit.flush();
+        }
+    }
+
+    /// 發送不改變目前階段的事件 (例如暫停、終止)
+    pub fn emit(&self, stage: ScanStage, progress: u8, message: String) {
+        if let Some(events) = &self.inner.events {
+            events.progress(ScanProgress::new(&self.inner.task_id, stage, progress, message));
+        }
+    }
+
+    /// 寫入掃描日誌並轉發為 scan-log 事件
+    pub fn log(&self, message: String) {
+        tracing::info!(task_id = %self.inner.task_id, "{}", message);
+        if let Some(events) = &self.inner.events {
+            events.log(&self.inner.task_id, message);
+        }
+    }
+
+    /// 送出發現事件；同一發現重複送出時由事件節流略過
+    pub fn finding(&self, finding: &ScanResult) {
+        if self.inner.logged_findings.lock().unwrap_or_else(|e| e.into_inner()).insert(finding.id.clone()) {
+            tracing::info!(
+                task_id = %self.inner.task_id,
+                finding_id = %finding.id,
+                "🚩 發現 [{:?}] {}",
+                finding.severity,
+                finding.title
+            );
+        }
+        if let Some(events) = &self.inner.events {
+            events.finding(&self.inner.task_id, finding);
+        }
+    }
+
+    /// 掃描結束：送出剩餘的日誌並釋放事件狀態
+    pub fn finish_events(&self) {
+        if let Some(events) = &self.inner.events {
+            events.finish(&self.inner.task_id);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::SeqCst)
+    }
+
+    /// 暫停掃描，回傳此次暫停的序號；已暫停或已終止時回傳 None
+    pub fn pause(&self) -> Option<u64> {
+        if self.abort_reason().is_some() || self.inner.paused.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        let mut generation = self.inner.pause_generation.lock().unwrap();
+        *generation += 1;
+        Some(*generation)
+    }
+
+    /// 繼續掃描；原本不是暫停狀態時回傳 false
+    pub fn resume(&self) -> bool {
+        let was_paused = self.inner.paused.swap(false, Ordering::SeqCst);
+        self.inner.changed.notify_waiters();
+        was_paused
+    }
+
+    /// 終止掃描，之後的請求都會以 reason 失敗
+    pub fn abort(&self, reason: String) {
+        self.inner.aborted.lock().unwrap().get_or_insert(reason);
+        self.inner.paused.store(false, Ordering::SeqCst);
+        self.inner.changed.notify_waiters();
+    }
+
+    pub fn abort_reason(&self) -> Option<String> {
+        self.inner.aborted.lock().unwrap().clone()
+    }
+
+    /// 使用者取消掃描；已終止 (取消或暫停逾時) 時回傳 false
+    pub fn cancel(&self) -> bool {
+        if self.abort_reason().is_some() {
+            return false;
+        }
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.abort("掃描已由使用者取消".to_string());
+        true
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 等到掃描被終止，回傳終止原因
+    async fn aborted(&self) -> String {
+        loop {
+            let notified = self.inner.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(reason) = self.abort_reason() {
+                return reason;
+            }
+            notified.await;
+        }
+    }
+
+    /// 第 generation 次暫停持續超過 MAX_PAUSE 時終止掃描
+    pub async fn expire_pause(&self, generation: u64) -> bool {
+        tokio::time::sleep(MAX_PAUSE).await;
+        let still_same_pause = self.is_paused() && *self.inner.pause_generation.lock().unwrap() == generation;
+        if still_same_pause {
+            self.abort(format!("掃描暫停超過 {} 分鐘，已自動終止", MAX_PAUSE.as_secs() / 60));
+        }
+        still_same_pause
+    }
+
+    /// 暫停時等待繼續；掃描已終止時回傳終止原因
+    pub async fn wait_if_paused(&self) -> Result<(), String> {
+        loop {
+            // 先註冊通知再檢查狀態，避免檢查與等待之間錯過 resume
+            let notified = self.inner.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(reason) = self.abort_reason() {
+                return Err(reason);
+            }
+            if !self.is_paused() {
+                return Ok(());
+            }
+            notified.await;
+        }
+    }
+}
+
+/// 在指定的控制下執行掃描
+pub async fn scope<F: Future>(control: ScanControl, future: F) -> F::Output {
+    CURRENT.scope(control, future).await
+}
+
+/// 在 JoinSet 中執行 future，沿用目前的掃描控制、檢查 id 與 tracing span (task-local 與 span 都不會跨 spawn 傳遞)
+pub fn spawn<T, F>(set: &mut JoinSet<T>, future: F)
+where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+{
+    let control = CURRENT.try_with(|control| control.clone()).ok();
+    let check = CHECK.try_with(|check| check.clone()).ok();
+    let span = tracing::Span::current();
+    set.spawn(
+        async move {
+            match (control, check) {
+                (Some(control), Some(check)) => CURRENT.scope(control, CHECK.scope(check, future)).await,
+                (Some(control), None) => CURRENT.scope(control, future).await,
+                (None, Some(check)) => CHECK.scope(check, future).await,
+                (None, None) => future.await,
+            }
+        }
+        .instrument(span),
+    );
+}
+
+/// 目前掃描的檢查點；不在掃描 scope 內 (例如目標正規化) 時直接通過
+pub async fn checkpoint() -> ScannerResult<()> {
+    match CURRENT.try_with(|control| control.clone()) {
+        Ok(control) => control.wait_if_paused().await.map_err(ScannerError::Interrupted),
+        Err(_) => Ok(()),
+    }
+}
+
+/// 目前掃描的請求節流；不在掃描 scope 內時直接通過
+pub async fn pace() -> ScannerResult<()> {
+    match CURRENT.try_with(|control| control.clone()) {
+        Ok(control) => control.pace().await.map_err(ScannerError::Interrupted),
+        Err(_) => Ok(()),
+    }
+}
+
+/// 目前檢查還能送出的請求數；沒有上限或不在掃描 scope 內時為 None
+pub fn remaining_requests() -> Option<u64> {
+    CURRENT.try_with(|control| control.remaining_requests()).ok().flatten()
+}
+
+/// 目前掃描的原始 socket 代理；沒有設定或不在掃描 scope 內時為 None
+pub fn proxy_route() -> Option<Arc<ProxyRoute>> {
+    CURRENT.try_with(|control| control.proxy_route()).ok().flatten()
+}
+
+/// 目前掃描的用戶端憑證；沒有設定或不在掃描 scope 內時為 None
+pub fn tls_identity() -> Option<Arc<TlsIdentity>> {
+    CURRENT.try_with(|control| control.tls_identity()).ok().flatten()
+}
+
+/// 執行 future，掃描被終止時立即放棄 (例如取消時中斷進行中的請求)；不在掃描 scope 內時照常執行
+pub async fn interruptible<F: Future>(future: F) -> ScannerResult<F::Output> {
+    let Ok(control) = CURRENT.try_with(|control| control.clone()) else {
+        return Ok(future.await);
+    };
+    tokio::select! {
+        output = future => Ok(output),
+        reason = control.aborted() => Err(ScannerError::Interrupted(reason)),
+    }
+}
+
+/// 目前的掃描是否已終止；迴圈在啟動下一個檢查前呼叫，終止後保留已收集的結果並停止
+pub fn stopped() -> bool {
+    CURRENT.try_with(|control| control.abort_reason().is_some()).unwrap_or(false)
+}
+
+/// 以 check_id 執行檢查：期間送出的請求與日誌都歸屬到此檢查，並累計檢查耗時
+pub async fn check<F: Future>(check_id: &str, future: F) -> F::Output {
+    let started = Instant::now();
+    let span = tracing::info_span!("check", check = check_id);
+    let output = CHECK.scope(check_id.to_string(), future).instrument(span).await;
+    let _ = CURRENT.try_with(|control| control.inner.checks.elapsed(check_id, started.elapsed()));
+    output
+}
+
+/// 記錄檢查產生的發現數量；不在掃描 scope 內時忽略
+pub fn record_findings(check_id: &str, count: usize) {
+    let _ = CURRENT.try_with(|control| control.record_findings(check_id, count));
+}
+
+/// 記錄目前掃描中檢查的執行結果；不在掃描 scope 內時忽略
+pub fn cover(check_id: &str, status: CoverageStatus, message: Option<String>) {
+    let _ = CURRENT.try_with(|control| control.cover(check_id, status, message));
+}
+
+/// 記錄目前掃描合併的發現清單；不在掃描 scope 內時忽略
+pub fn discovered(content: DiscoveredContent) {
+    let _ = CURRENT.try_with(|control| control.discovered(content));
+}
+
+/// 記錄目前掃描送出的請求；不在掃描 scope 內時忽略
+pub fn record_request(method: &str, url: &str, body: Option<&[u8]>) {
+    let _ = CURRENT.try_with(|control| control.record_request(method, url, body));
+}
+
+/// 依目前掃描的登入狀態附上認證標頭 (只限目標來源)；不在掃描 scope 內時不變
+pub fn authorize(request: &mut reqwest::Request) {
+    if let Ok(Some(session)) = CURRENT.try_with(|control| control.session()) {
+        session.apply(request);
+    }
+}
+
+/// 目前掃描收到目標回應後確認登入狀態是否仍有效；不在掃描 scope 內時忽略
+pub async fn watch_session(url: &reqwest::Url, status: u16) {
+    if let Ok(control) = CURRENT.try_with(|control| control.clone()) {
+        control.watch_session(url, status).await;
+    }
+}
+
+/// 與登入 cookie 一起送出的 cookie (例如同意頁的 cookie)；未設定登入或不在掃描 scope 內時忽略
+pub fn extend_session_cookies(header: &str) {
+    if let Ok(Some(session)) = CURRENT.try_with(|control| control.session()) {
+        session.extend_cookies(header);
+    }
+}
+
+/// 由即將送出的請求建立證據，回應內容依目前掃描的設定截斷
+pub fn exchange_for(request: &reqwest::Request) -> HttpExchange {
+    let limit = CURRENT
+        .try_with(|control| control.inner.exchanges.body_limit())
+        .unwrap_or(evidence::DEFAULT_BODY_BYTES);
+    evidence::request(request, limit)
+}
+
+/// 記錄目前掃描的請求與回應；不在掃描 scope 內時忽略
+pub fn record_exchange(url: &str, exchange: HttpExchange) {
+    let _ = CURRENT.try_with(|control| control.inner.exchanges.record(url, exchange));
+}
+
+/// 補上目前掃描最近一次對 url 的回應內容；不在掃描 scope 內時忽略
+pub fn record_body(url: &str, body: &[u8]) {
+    let _ = CURRENT.try_with(|control| control.inner.exchanges.record_body(url, body));
+}
+
+/// 目前掃描最近一次對 url 的請求與回應；不在掃描 scope 內時為 None
+pub fn exchange(url: &str) -> Option<HttpExchange> {
+    CURRENT.try_with(|control| control.exchange(url)).ok().flatten()
+}
+
+/// 記錄目前檢查以逾時失敗的請求；不在掃描 scope 內時忽略
+pub fn record_timeout() {
+    let _ = CURRENT.try_with(|control| control.record_timeout());
+}
+
+/// 目前掃描進入新階段；不在掃描 scope 內時忽略
+pub fn enter_stage(stage: ScanStage, progress: u8) {
+    let message = crate::messages::stage_label(&stage);
+    let _ = CURRENT.try_with(|control| control.enter_stage(stage, progress, message));
+}
+
+/// 送出不改變目前階段的進度事件 (例如並行的類別完成)；不在掃描 scope 內時忽略
+pub fn progress(stage: ScanStage, progress: u8, message: String) {
+    let _ = CURRENT.try_with(|control| control.emit(stage, progress, message));
+}
+
+/// 將目前掃描的目前階段標記為略過；不在掃描 scope 內時忽略
+pub fn skip_stage(reason: String) {
+    let _ = CURRENT.try_with(|control| control.skip_stage(reason));
+}
+
+/// 寫入目前掃描的日誌；不在掃描 scope 內時只寫入 tracing
+pub fn log(message: String) {
+    match CURRENT.try_with(|control| control.clone()) {
+        Ok(control) => control.log(message),
+        Err(_) => tracing::info!("{}", message),
+    }
+}
+
+/// 送出目前掃描的發現事件；不在掃描 scope 內時忽略
+pub fn finding(finding: &ScanResult) {
+    let _ = CURRENT.try_with(|control| control.finding(finding));
+}
+
+/// 目前掃描是否可以啟動檢查；不在掃描 scope 內時一律允許
+pub fn admit(check_id: &str) -> bool {
+    CURRENT.try_with(|control| control.admit(check_id)).unwrap_or(true)
+}
+
+/// 產生目前掃描的 canary；不在掃描 scope 內時產生不記錄的 canary
+pub fn canary(purpose: &str) -> String {
+    CURRENT
+        .try_with(|control| control.canary(purpose))
+        .unwrap_or_else(|_| detached_canaries().issue("", purpose))
+}
+
+/// 不屬於任何掃描的 canary 產生器 (不寫入紀錄)
+fn detached_canaries() -> CanaryRegistry {
+    static DETACHED: std::sync::OnceLock<CanaryRegistry> = std::sync::OnceLock::new();
+    DETACHED.get_or_init(|| CanaryRegistry::open("")).clone()
+}
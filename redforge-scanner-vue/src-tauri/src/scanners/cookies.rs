@@ -0,0 +1,164 @@
+/**
+ * Cookie Security Audit
+ *
+ * 依 RFC 6265 解析 Set-Cookie：第一段為 name=value，其後的屬性以分號分隔、名稱不分大小寫。
+ * 稽核 Secure、HttpOnly、SameSite、Domain 範圍與有效期限；cookie 的值只在解析時略過，不會出現在任何輸出中。
+ */
+
+use crate::models::Severity;
+use chrono::{DateTime, Duration, Utc};
+
+/// 看起來像身分驗證用途的 cookie 名稱片段；CSRF token cookie 需要讓 JavaScript 讀取，不列入
+const AUTH_COOKIE_PATTERNS: [&str; 8] = ["sess", "sid", "auth", "token", "jwt", "login", "remember", "identity"];
+
+/// 身分驗證 cookie 超過此天數視為有效期過長
+const MAX_AUTH_COOKIE_DAYS: i64 = 365;
+
+/// 解析後的 Set-Cookie；不保留 cookie 的值
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetCookie {
+    pub name: String,
+    /// 值以外的原始屬性字串，例如 "Path=/; HttpOnly"，作為發現的證據
+    pub attributes: String,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<String>,
+    pub domain: Option<String>,
+    pub max_age: Option<i64>,
+    pub expires: Option<DateTime<Utc>>,
+}
+
+/// 單一 cookie 的一項問題與針對性的修正建議
+#[derive(Debug, Clone, PartialEq)]
+pub struct CookieIssue {
+    pub check_id: &'static str,
+    pub severity: Severity,
+    pub title: String,
+    pub remediation: String,
+}
+
+/// 解析 Set-Cookie 標頭；沒有 cookie 名稱時回傳 None
+pub fn parse(header: &str) -> Option<SetCookie> {
+    let mut parts = header.split(';');
+    let (name, _value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let attributes: Vec<&str> = parts.map(str::trim).filter(|a| !a.is_empty()).collect();
+    let mut cookie = SetCookie {
+        name: name.to_string(),
+        attributes: attributes.join("; "),
+        ..Default::default()
+    };
+
+    for attribute in attributes {
+        let (key, value) = match attribute.split_once('=') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+            None => (attribute.to_lowercase(), ""),
+        };
+        match key.as_str() {
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            "samesite" => cookie.same_site = Some(value.to_string()),
+            "domain" if !value.is_empty() => cookie.domain = Some(value.trim_start_matches('.').to_lowercase()),
+            "max-age" => cookie.max_age = value.parse().ok(),
+            "expires" => cookie.expires = parse_expires(value),
+            _ => {}
+        }
+    }
+
+    Some(cookie)
+}
+
+/// Expires 常見的格式：RFC 1123 (Wed, 21 Oct 2026 07:28:00 GMT) 與舊式的 21-Oct-2026
+fn parse_expires(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc2822(&value.replace('-', " ")))
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+impl SetCookie {
+    /// 名稱看起來像 session 或身分驗證 cookie
+    pub fn is_auth_like(&self) -> bool {
+        let name = self.name.to_lowercase();
+        !name.contains("csrf") && !name.contains("xsrf") && AUTH_COOKIE_PATTERNS.iter().any(|pattern| name.contains(pattern))
+    }
+
+    /// cookie 的有效期限；Max-Age 優先於 Expires，兩者皆無時為 session cookie (None)
+    pub fn lifetime(&self, now: DateTime<Utc>) -> Option<Duration> {
+        match self.max_age {
+            Some(seconds) => Some(Duration::seconds(seconds)),
+            None => self.expires.map(|expires| expires - now),
+        }
+    }
+
+    /// 稽核 cookie；https 為目標是否使用 HTTPS，host 為回應的主機名稱
+    pub fn audit(&self, https: bool, host: &str, now: DateTime<Utc>) -> Vec<CookieIssue> {
+        let mut issues = Vec::new();
+        let auth = self.is_auth_like();
+        let same_site = self.same_site.as_deref().map(str::to_lowercase);
+
+        if https && !self.secure {
+            issues.push(CookieIssue {
+                check_id: "cookie_missing_secure",
+                severity: if auth { Severity::High } else { Severity::Medium },
+                title: format!("Cookie {} 未設置 Secure 標誌", self.name),
+                remediation: "加上 Secure 屬性，避免 cookie 在 HTTP 連線中以明文傳送".to_string(),
+            });
+        }
+
+        if auth && !self.http_only {
+            issues.push(CookieIssue {
+                check_id: "cookie_missing_httponly",
+                severity: Severity::High,
+                title: format!("身分驗證 Cookie {} 未設置 HttpOnly 標誌", self.name),
+                remediation: "加上 HttpOnly 屬性，避免 XSS 透過 document.cookie 竊取 session".to_string(),
+            });
+        }
+
+        match same_site.as_deref() {
+            Some("none") if !self.secure => issues.push(CookieIssue {
+                check_id: "cookie_samesite_none_insecure",
+                severity: Severity::Medium,
+                title: format!("Cookie {} 設置 SameSite=None 但未設置 Secure", self.name),
+                remediation: "SameSite=None 必須搭配 Secure，新版瀏覽器會拒絕此 cookie，舊版瀏覽器則會在跨站請求中送出。需要跨站使用時加上 Secure，否則改為 SameSite=Lax".to_string(),
+            }),
+            Some("strict") | Some("lax") | Some("none") => {}
+            _ => issues.push(CookieIssue {
+                check_id: "cookie_missing_samesite",
+                severity: if auth { Severity::Medium } else { Severity::Low },
+                title: format!("Cookie {} 未設置有效的 SameSite 屬性", self.name),
+                remediation: "明確設置 SameSite=Lax (或 Strict)，降低跨站請求偽造 (CSRF) 的風險，不依賴瀏覽器的預設行為".to_string(),
+            }),
+        }
+
+        // Domain 設為上層網域時，所有子網域 (包含可能被接管的子網域) 都能讀取此 cookie
+        if let Some(domain) = &self.domain {
+            let host = host.to_lowercase();
+            if *domain != host && host.ends_with(&format!(".{}", domain)) {
+                issues.push(CookieIssue {
+                    check_id: "cookie_parent_domain",
+                    severity: if auth { Severity::Medium } else { Severity::Low },
+                    title: format!("Cookie {} 的 Domain 範圍過大 ({})", self.name, domain),
+                    remediation: format!("移除 Domain 屬性讓 cookie 只送往 {}，或限縮到實際需要的子網域", host),
+                });
+            }
+        }
+
+        if auth {
+            if let Some(lifetime) = self.lifetime(now).filter(|l| l.num_days() > MAX_AUTH_COOKIE_DAYS) {
+                issues.push(CookieIssue {
+                    check_id: "cookie_long_expiry",
+                    severity: Severity::Low,
+                    title: format!("身分驗證 Cookie {} 的有效期過長 ({} 天)", self.name, lifetime.num_days()),
+                    remediation: format!("縮短有效期限至 {} 天以內，並在伺服器端設定 session 逾時與撤銷機制", MAX_AUTH_COOKIE_DAYS),
+                });
+            }
+        }
+
+        issues
+    }
+}
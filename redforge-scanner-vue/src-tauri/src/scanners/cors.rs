@@ -0,0 +1,137 @@
+/**
+ * CORS Misconfiguration
+ *
+ * 以攻擊者的 Origin (任意網域、null、目標網域加上前綴 / 後綴的變形) 送出一般請求與 OPTIONS 預檢，
+ * 依回應的 Access-Control-Allow-Origin 與 Access-Control-Allow-Credentials 判斷是否信任不該信任的來源。
+ */
+
+use crate::models::Severity;
+
+/// 代表攻擊者網站的來源
+pub const ATTACKER_ORIGIN: &str = "https://evil.example";
+
+/// 一個測試用的 Origin 與其變形方式
+#[derive(Debug, Clone, PartialEq)]
+pub struct OriginProbe {
+    pub origin: String,
+    /// arbitrary / null / prefix / suffix
+    pub kind: &'static str,
+}
+
+/// CORS 回應標頭
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorsHeaders {
+    pub allow_origin: Option<String>,
+    pub allow_credentials: Option<String>,
+    pub allow_methods: Option<String>,
+    pub vary: Option<String>,
+}
+
+/// 不安全的 CORS 設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsIssue {
+    pub check_id: &'static str,
+    pub severity: Severity,
+    pub title: String,
+    pub description: String,
+}
+
+/// 針對目標產生測試的 Origin；host 為目標主機名稱
+///
+/// 前綴變形 (evil + 主機) 用來發現只檢查結尾的白名單，後綴變形 (主機 + 攻擊者網域) 用來發現只檢查開頭的白名單
+pub fn probe_origins(scheme: &str, host: &str) -> Vec<OriginProbe> {
+    vec![
+        OriginProbe { origin: ATTACKER_ORIGIN.to_string(), kind: "arbitrary" },
+        OriginProbe { origin: "null".to_string(), kind: "null" },
+        OriginProbe { origin: format!("{}://evil{}", scheme, host), kind: "prefix" },
+        OriginProbe { origin: format!("{}://{}.evil.example", scheme, host), kind: "suffix" },
+    ]
+}
+
+impl CorsHeaders {
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let get = |name: &str| headers.get(name).map(crate::scanners::http_scanner::header_value_lossy);
+        Self {
+            allow_origin: get("access-control-allow-origin"),
+            allow_credentials: get("access-control-allow-credentials"),
+            allow_methods: get("access-control-allow-methods"),
+            vary: get("vary"),
+        }
+    }
+
+    fn credentials(&self) -> bool {
+        self.allow_credentials.as_deref().is_some_and(|v| v.trim().eq_ignore_ascii_case("true"))
+    }
+
+    /// 回應標頭的 JSON 表示，作為發現的證據
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "access-control-allow-origin": self.allow_origin,
+            "access-control-allow-credentials": self.allow_credentials,
+            "access-control-allow-methods": self.allow_methods,
+            "vary": self.vary,
+        })
+    }
+
+    /// 依送出的 Origin 判斷回應是否信任了不該信任的來源
+    pub fn classify(&self, probe: &OriginProbe) -> Option<CorsIssue> {
+        let allow_origin = self.allow_origin.as_deref()?.trim();
+        let credentials = self.credentials();
+
+        if allow_origin == "*" {
+            // 規範禁止 * 搭配憑證，瀏覽器會拒絕回應，但代表伺服器想開放帶憑證的跨網域存取
+            return credentials.then(|| CorsIssue {
+                check_id: "cors_wildcard_origin",
+                severity: Severity::Low,
+                title: "CORS 設定 Access-Control-Allow-Origin: * 並允許憑證".to_string(),
+                description: "Access-Control-Allow-Origin 為 * 且 Access-Control-Allow-Credentials 為 true。瀏覽器會拒絕此組合，目前無法利用，但代表伺服器意圖開放帶憑證的跨網域存取，調整設定時容易變成反射任意來源。建議: 改用明確的來源白名單".to_string(),
+            });
+        }
+
+        if allow_origin != probe.origin {
+            return None;
+        }
+
+        if probe.kind == "null" {
+            return Some(CorsIssue {
+                check_id: "cors_null_origin",
+                severity: if credentials { Severity::High } else { Severity::Medium },
+                title: "CORS 信任 null 來源".to_string(),
+                description: format!(
+                    "伺服器對 Origin: null 回傳 Access-Control-Allow-Origin: null{}。沙箱 iframe 與 data: URL 的請求來源都是 null，任何網站都能取得此來源。建議: 從白名單中移除 null",
+                    if credentials { " 並允許憑證" } else { "" }
+                ),
+            });
+        }
+
+        let origin_kind = match probe.kind {
+            "prefix" => "在目標網域前加上字首的網域",
+            "suffix" => "以目標網域開頭的攻擊者網域",
+            _ => "任意網域",
+        };
+        Some(CorsIssue {
+            check_id: "cors_reflected_origin",
+            severity: if credentials { Severity::High } else { Severity::Low },
+            title: if credentials {
+                "CORS 反射任意來源並允許憑證".to_string()
+            } else {
+                "CORS 反射任意來源".to_string()
+            },
+            description: format!(
+                "伺服器將 {} ({}) 反射到 Access-Control-Allow-Origin{}。建議: 以完整比對的來源白名單驗證 Origin，不要直接反射或以字首 / 字尾比對",
+                origin_kind,
+                probe.origin,
+                if credentials { "，且 Access-Control-Allow-Credentials 為 true，攻擊者的網頁可用受害者的 session 讀取回應" } else { "" }
+            ),
+        })
+    }
+}
+
+/// 重現請求的 curl 指令
+pub fn curl_command(method: &str, url: &str, origin: &str) -> String {
+    if method == "OPTIONS" {
+        format!("curl -i -X OPTIONS -H 'Origin: {}' -H 'Access-Control-Request-Method: GET' '{}'", origin, url)
+    } else {
+        format!("curl -i -H 'Origin: {}' '{}'", origin, url)
+    }
+}
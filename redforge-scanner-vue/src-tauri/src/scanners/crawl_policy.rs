@@ -0,0 +1,281 @@
+/**
+ * Crawl Safety Policy
+ *
+ * 帶著登入 session 爬取時，點到「登出」或「刪除」連結會讓 session 失效甚至改動資料。
+ * 所有跟隨頁面連結的請求都先經過這裡判斷：
+ *
+ * - 預設拒絕清單 (logout、signout、delete、remove、disable…) 的 URL 永遠不請求
+ * - 設定中的允許 / 拒絕樣式；拒絕永遠優先，允許清單非空時只爬符合的 URL
+ * - 只用 GET / HEAD 遍歷，其他會改變狀態的 method 一律拒絕
+ * - 目錄深度、每個目錄的頁數與總頁數上限
+ *
+ * 每個略過的 URL 與原因都會記錄並輸出到掃描日誌。
+ */
+
+use crate::models::CrawlSettings;
+use crate::scanners::control;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::{HashMap, HashSet};
+
+/// 預設拒絕的 URL 樣式 (比對路徑與查詢字串，不分大小寫)
+const DEFAULT_DENY_PATTERNS: &[&str] = &[
+    r"log[-_]?out",
+    r"log[-_]?off",
+    r"sign[-_]?out",
+    r"sign[-_]?off",
+    r"delete",
+    r"remove",
+    r"disable",
+    r"destroy",
+    r"deactivate",
+    r"unsubscribe",
+    r"revoke",
+];
+
+/// 略過 URL 的原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "reason", content = "detail", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// 符合拒絕樣式 (預設或設定)
+    DeniedPattern(String),
+    /// 允許清單非空且不符合任何允許樣式
+    NotAllowed,
+    /// 會改變狀態的 method
+    StateChangingMethod(String),
+    /// 與目標不同的主機
+    OffScope(String),
+    /// 目錄深度超過上限
+    DepthExceeded(usize),
+    /// 同一目錄的頁數已達上限
+    DirectoryBudget(String),
+    /// 總頁數已達上限
+    PageBudget,
+    /// 已請求過
+    AlreadyVisited,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SkipReason::DeniedPattern(pattern) => write!(f, "符合拒絕樣式 {}", pattern),
+            SkipReason::NotAllowed => write!(f, "不在允許清單中"),
+            SkipReason::StateChangingMethod(method) => write!(f, "{} 會改變狀態，只允許 GET / HEAD", method),
+            SkipReason::OffScope(host) => write!(f, "主機 {} 不在掃描範圍", host),
+            SkipReason::DepthExceeded(depth) => write!(f, "目錄深度 {} 超過上限", depth),
+            SkipReason::DirectoryBudget(dir) => write!(f, "目錄 {} 的頁數已達上限", dir),
+            SkipReason::PageBudget => write!(f, "總頁數已達上限"),
+            SkipReason::AlreadyVisited => write!(f, "已請求過"),
+        }
+    }
+}
+
+/// 一筆略過決定，附在報告上供檢視
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlDecision {
+    pub url: String,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+pub struct CrawlPolicy {
+    host: Option<String>,
+    deny: Vec<(String, Regex)>,
+    allow: Vec<Regex>,
+    max_depth: usize,
+    max_pages_per_directory: usize,
+    max_pages: usize,
+    visited: HashSet<String>,
+    pages_per_directory: HashMap<String, usize>,
+    skipped: Vec<CrawlDecision>,
+}
+
+impl CrawlPolicy {
+    /// 以掃描目標的主機為範圍建立；設定中的無效樣式略過並輸出警告
+    pub fn new(target: &str, settings: &CrawlSettings) -> Self {
+        let compile = |pattern: &str| match Regex::new(&format!("(?i){}", pattern)) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                tracing::warn!("⚠️  略過無效的爬取樣式 {}: {}", pattern, e);
+                None
+            }
+        };
+
+        let deny = DEFAULT_DENY_PATTERNS
+            .iter()
+            .copied()
+            .chain(settings.deny_patterns.iter().map(String::as_str))
+            .filter_map(|pattern| compile(pattern).map(|re| (pattern.to_string(), re)))
+            .collect();
+        let allow = settings.allow_patterns.iter().filter_map(|pattern| compile(pattern)).collect();
+
+        Self {
+            host: reqwest::Url::parse(target).ok().and_then(|url| url.host_str().map(str::to_lowercase)),
+            deny,
+            allow,
+            max_depth: settings.max_depth,
+            max_pages_per_directory: settings.max_pages_per_directory,
+            max_pages: settings.max_pages,
+            visited: HashSet::new(),
+            pages_per_directory: HashMap::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// 判斷是否可以請求；允許時計入頁數預算，拒絕時記錄原因並輸出到掃描日誌
+    pub fn permit(&mut self, method: &str, url: &str) -> bool {
+        match self.evaluate(method, url) {
+            Ok(directory) => {
+                self.visited.insert(normalize(url));
+                *self.pages_per_directory.entry(directory).or_insert(0) += 1;
+                true
+            }
+            Err(reason) => {
+                control::log(format!("🚫 爬取略過 {} {}: {}", method, url, reason));
+                self.skipped.push(CrawlDecision { url: url.to_string(), reason });
+                false
+            }
+        }
+    }
+
+    /// 略過的 URL 與原因
+    pub fn skipped(&self) -> &[CrawlDecision] {
+        &self.skipped
+    }
+
+    fn evaluate(&self, method: &str, url: &str) -> Result<String, SkipReason> {
+        if !matches!(method.to_ascii_uppercase().as_str(), "GET" | "HEAD") {
+            return Err(SkipReason::StateChangingMethod(method.to_ascii_uppercase()));
+        }
+
+        let parsed = reqwest::Url::parse(url).map_err(|_| SkipReason::OffScope(url.to_string()))?;
+        let host = parsed.host_str().unwrap_or_default().to_lowercase();
+        if self.host.as_deref().is_some_and(|target| target != host) {
+            return Err(SkipReason::OffScope(host));
+        }
+
+        // 拒絕樣式比對路徑與查詢字串，主機名稱 (例如 remove.example.com) 不影響
+        let location = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        if let Some((pattern, _)) = self.deny.iter().find(|(_, re)| re.is_match(&location)) {
+            return Err(SkipReason::DeniedPattern(pattern.clone()));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|re| re.is_match(&location)) {
+            return Err(SkipReason::NotAllowed);
+        }
+
+        if self.visited.contains(&normalize(url)) {
+            return Err(SkipReason::AlreadyVisited);
+        }
+
+        let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+        // 最後一段是頁面本身，目錄深度不含它
+        let depth = segments.len().saturating_sub(1);
+        if depth > self.max_depth {
+            return Err(SkipReason::DepthExceeded(depth));
+        }
+
+        if self.visited.len() >= self.max_pages {
+            return Err(SkipReason::PageBudget);
+        }
+        let directory = format!("/{}", segments[..depth].join("/"));
+        if self.pages_per_directory.get(&directory).copied().unwrap_or(0) >= self.max_pages_per_directory {
+            return Err(SkipReason::DirectoryBudget(directory));
+        }
+
+        Ok(directory)
+    }
+}
+
+/// 去除 fragment，同一頁面不同錨點視為同一 URL
+fn normalize(url: &str) -> String {
+    url.split('#').next().unwrap_or(url).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "https://example.com/";
+
+    fn policy(settings: CrawlSettings) -> CrawlPolicy {
+        CrawlPolicy::new(TARGET, &settings)
+    }
+
+    #[test]
+    fn never_requests_default_denied_urls() {
+        let mut policy = policy(CrawlSettings::default());
+        let cases = [
+            ("https://example.com/logout", false),
+            ("https://example.com/account/Log-Out", false),
+            ("https://example.com/auth/sign_out?next=/", false),
+            ("https://example.com/users/1/delete", false),
+            ("https://example.com/profile?action=remove", false),
+            ("https://example.com/settings/disable-2fa", false),
+            ("https://example.com/newsletter/unsubscribe", false),
+            // 主機名稱不參與比對
+            ("https://example.com/about", true),
+            ("https://example.com/blog/logo.png", true),
+        ];
+        for (url, permitted) in cases {
+            assert_eq!(policy.permit("GET", url), permitted, "{}", url);
+        }
+        assert!(policy.skipped().iter().all(|d| matches!(d.reason, SkipReason::DeniedPattern(_))));
+        assert_eq!(policy.skipped().len(), 7);
+    }
+
+    #[test]
+    fn only_get_and_head_traverse() {
+        let mut policy = policy(CrawlSettings::default());
+        assert!(policy.permit("get", "https://example.com/a"));
+        assert!(policy.permit("HEAD", "https://example.com/b"));
+        for method in ["POST", "PUT", "PATCH", "DELETE"] {
+            assert!(!policy.permit(method, "https://example.com/c"));
+        }
+        assert_eq!(policy.skipped()[0].reason, SkipReason::StateChangingMethod("POST".to_string()));
+    }
+
+    #[test]
+    fn configured_patterns_deny_first_then_allow() {
+        let mut policy = policy(CrawlSettings {
+            allow_patterns: vec!["^/app/".to_string()],
+            deny_patterns: vec!["/app/admin".to_string(), "([".to_string()],
+            ..CrawlSettings::default()
+        });
+        assert!(policy.permit("GET", "https://example.com/app/home"));
+        assert_eq!(policy.evaluate("GET", "https://example.com/app/admin/users"), Err(SkipReason::DeniedPattern("/app/admin".to_string())));
+        assert_eq!(policy.evaluate("GET", "https://example.com/blog"), Err(SkipReason::NotAllowed));
+        // 允許清單無法放行預設拒絕的 URL
+        assert!(matches!(policy.evaluate("GET", "https://example.com/app/logout"), Err(SkipReason::DeniedPattern(_))));
+    }
+
+    #[test]
+    fn stays_on_the_target_host_and_skips_revisits() {
+        let mut policy = policy(CrawlSettings::default());
+        assert_eq!(policy.evaluate("GET", "https://other.example/page"), Err(SkipReason::OffScope("other.example".to_string())));
+        assert!(policy.permit("GET", "https://example.com/page#top"));
+        assert_eq!(policy.evaluate("GET", "https://example.com/page#bottom"), Err(SkipReason::AlreadyVisited));
+    }
+
+    #[test]
+    fn enforces_depth_and_page_budgets() {
+        let mut policy = policy(CrawlSettings {
+            max_depth: 2,
+            max_pages_per_directory: 2,
+            max_pages: 4,
+            ..CrawlSettings::default()
+        });
+        assert!(policy.permit("GET", "https://example.com/a/b/page"));
+        assert_eq!(policy.evaluate("GET", "https://example.com/a/b/c/page"), Err(SkipReason::DepthExceeded(3)));
+
+        assert!(policy.permit("GET", "https://example.com/docs/1"));
+        assert!(policy.permit("GET", "https://example.com/docs/2"));
+        assert_eq!(policy.evaluate("GET", "https://example.com/docs/3"), Err(SkipReason::DirectoryBudget("/docs".to_string())));
+
+        assert!(policy.permit("GET", "https://example.com/"));
+        assert_eq!(policy.evaluate("GET", "https://example.com/contact"), Err(SkipReason::PageBudget));
+    }
+}
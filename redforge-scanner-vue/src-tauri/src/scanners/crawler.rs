@@ -0,0 +1,315 @@
+/**
+ * Page Crawler
+ *
+ * 從基礎頁面開始，依連結深度與頁數預算爬取同源頁面，找出注入檢查要測試的參數：
+ *
+ * - `<a href>` 連結與行內腳本中 fetch() / XHR / axios / jQuery 請求的 URL
+ * - `<form>` 的 action、method、enctype 與欄位 (名稱、類型、預設值)
+ *
+ * 頁面只以 GET 取得並經過爬取安全規則 (CrawlPolicy)，表單只記錄不送出；
+ * 請求由呼叫端送出，因此同樣受掃描的節流與請求上限限制。
+ */
+
+use crate::scanners::crawl_policy::CrawlDecision;
+use crate::scanners::path_traversal;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// 注入檢查最多測試的參數數量
+pub const MAX_INJECTION_POINTS: usize = 10;
+
+/// 帶有查詢參數的同源 URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub url: String,
+    pub params: Vec<String>,
+}
+
+/// 頁面中的表單
+#[derive(Debug, Clone, PartialEq)]
+pub struct Form {
+    /// 表單所在的頁面
+    pub page: String,
+    /// 解析後的絕對 action URL；未設定時為頁面本身
+    pub action: String,
+    /// 大寫的 method，未設定時為 GET
+    pub method: String,
+    /// 小寫的 enctype，未設定時為 application/x-www-form-urlencoded
+    pub enctype: String,
+    pub fields: Vec<FormField>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    pub name: String,
+    /// input 的 type (小寫，預設 text)，或 select / textarea
+    pub kind: String,
+    pub value: String,
+}
+
+impl FormField {
+    /// 可自由輸入文字的欄位，注入 payload 只放在這些欄位
+    pub fn is_text_like(&self) -> bool {
+        matches!(self.kind.as_str(), "text" | "search" | "email" | "url" | "tel" | "password" | "textarea")
+    }
+}
+
+impl Form {
+    /// 看起來像 CSRF token 的隱藏欄位
+    pub fn csrf_field(&self) -> Option<&FormField> {
+        self.fields.iter().find(|field| {
+            let name = field.name.to_lowercase();
+            field.kind == "hidden" && ["csrf", "xsrf", "token", "nonce", "authenticity"].iter().any(|hint| name.contains(hint))
+        })
+    }
+
+    /// 欄位預設值，其中 field 的值換成 value
+    pub fn values_with(&self, field: &str, value: &str) -> Vec<(String, String)> {
+        self.fields
+            .iter()
+            .map(|f| (f.name.clone(), if f.name == field { value.to_string() } else { f.value.clone() }))
+            .collect()
+    }
+
+    /// 依表單宣告的 enctype 編碼欄位，回傳 (Content-Type, 內容)
+    pub fn encode(&self, values: &[(String, String)]) -> (String, Vec<u8>) {
+        if self.enctype.starts_with("multipart/form-data") {
+            let boundary = format!("----RedForge{}", uuid::Uuid::new_v4().simple());
+            let mut body = Vec::new();
+            for (name, value) in values {
+                body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value).as_bytes());
+            }
+            body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+            (format!("multipart/form-data; boundary={}", boundary), body)
+        } else {
+            let body = values
+                .iter()
+                .map(|(name, value)| format!("{}={}", urlencoding::encode(name), urlencoding::encode(value)))
+                .collect::<Vec<_>>()
+                .join("&");
+            ("application/x-www-form-urlencoded".to_string(), body.into_bytes())
+        }
+    }
+
+    /// 所有欄位都使用預設值的 URL (GET 表單送出時的網址)
+    pub fn default_url(&self) -> String {
+        let mut url = self.action.clone();
+        for field in &self.fields {
+            url = path_traversal::with_raw_param(&url, &field.name, &urlencoding::encode(&field.value));
+        }
+        url
+    }
+}
+
+/// 單一頁面擷取到的內容
+#[derive(Debug, Clone, Default)]
+pub struct PageLinks {
+    /// 可繼續爬取的同源 URL (已去除 fragment)
+    pub links: Vec<String>,
+    pub forms: Vec<Form>,
+}
+
+/// 爬取結果，注入檢查共用
+#[derive(Debug, Clone, Default)]
+pub struct CrawlResult {
+    pub pages: Vec<String>,
+    pub endpoints: Vec<Endpoint>,
+    pub forms: Vec<Form>,
+    /// 爬取規則略過的 URL
+    pub skipped: Vec<CrawlDecision>,
+    /// 實際到達的連結深度 (基礎頁面為 0)
+    pub depth: usize,
+}
+
+/// 報告中的爬取摘要
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlSummary {
+    pub pages: usize,
+    pub endpoints: usize,
+    pub forms: usize,
+    /// 不重複的 (路徑, 參數) 數量，包含 GET 連結與表單欄位
+    pub parameters: usize,
+    pub depth: usize,
+}
+
+impl CrawlResult {
+    /// 記錄 URL：有查詢參數時加入 endpoint (同一路徑與參數組合只記一次)
+    pub fn add_endpoint(&mut self, url: &str) {
+        let Ok(parsed) = reqwest::Url::parse(url) else { return };
+        let mut params: Vec<String> = parsed.query_pairs().map(|(name, _)| name.to_string()).collect();
+        params.dedup();
+        if params.is_empty() {
+            return;
+        }
+        let key = endpoint_key(url);
+        let known = self.endpoints.iter().any(|e| endpoint_key(&e.url) == key && e.params == params);
+        if !known {
+            self.endpoints.push(Endpoint { url: url.to_string(), params });
+        }
+    }
+
+    /// 記錄表單 (相同 method、action 與欄位的表單只記一次)
+    pub fn add_form(&mut self, form: Form) {
+        let names = |f: &Form| f.fields.iter().map(|field| field.name.clone()).collect::<Vec<_>>();
+        let known = self.forms.iter().any(|f| f.method == form.method && f.action == form.action && names(f) == names(&form));
+        if !known {
+            self.forms.push(form);
+        }
+    }
+
+    /// 注入檢查要測試的 (URL, 參數)，最多 MAX_INJECTION_POINTS 個
+    pub fn injection_points(&self) -> Vec<(String, String)> {
+        let mut points = self.parameters();
+        points.truncate(MAX_INJECTION_POINTS);
+        points
+    }
+
+    /// 所有 (URL, 參數)：GET 連結的查詢參數與 GET 表單的文字欄位，同一路徑的參數只列一次
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        let mut points: Vec<(String, String)> = Vec::new();
+        let mut seen: Vec<(String, String)> = Vec::new();
+        let mut push = |url: String, param: String| {
+            let key = (endpoint_key(&url), param.clone());
+            if !seen.contains(&key) {
+                seen.push(key);
+                points.push((url, param));
+            }
+        };
+
+        for endpoint in &self.endpoints {
+            for param in &endpoint.params {
+                push(endpoint.url.clone(), param.clone());
+            }
+        }
+        for form in self.forms.iter().filter(|f| f.method == "GET") {
+            let url = form.default_url();
+            for field in form.fields.iter().filter(|f| f.is_text_like()) {
+                push(url.clone(), field.name.clone());
+            }
+        }
+
+        points
+    }
+
+    pub fn summary(&self) -> CrawlSummary {
+        let mut parameters: Vec<(String, String)> = self
+            .endpoints
+            .iter()
+            .flat_map(|e| e.params.iter().map(move |p| (endpoint_key(&e.url), p.clone())))
+            .chain(self.forms.iter().flat_map(|f| f.fields.iter().map(move |field| (endpoint_key(&f.action), field.name.clone()))))
+            .collect();
+        parameters.sort();
+        parameters.dedup();
+
+        CrawlSummary {
+            pages: self.pages.len(),
+            endpoints: self.endpoints.len(),
+            forms: self.forms.len(),
+            parameters: parameters.len(),
+            depth: self.depth,
+        }
+    }
+}
+
+/// 比對用的 URL：去除查詢字串與 fragment
+fn endpoint_key(url: &str) -> String {
+    url.split(['?', '#']).next().unwrap_or(url).to_string()
+}
+
+/// 同源 (scheme、主機與連接埠相同)
+pub fn same_origin(a: &reqwest::Url, b: &reqwest::Url) -> bool {
+    a.scheme() == b.scheme() && a.host_str() == b.host_str() && a.port_or_known_default() == b.port_or_known_default()
+}
+
+/// 擷取頁面中的連結、腳本請求 URL 與表單；只保留與頁面同源的 URL
+pub fn extract(page_url: &str, html: &str) -> PageLinks {
+    static ANCHOR: OnceLock<Regex> = OnceLock::new();
+    static SCRIPT: OnceLock<Regex> = OnceLock::new();
+    static SCRIPT_URL: OnceLock<Regex> = OnceLock::new();
+    static FORM: OnceLock<Regex> = OnceLock::new();
+    static FIELD: OnceLock<Regex> = OnceLock::new();
+    let anchor = ANCHOR.get_or_init(|| Regex::new(r#"(?i)<a\s[^>]*?href\s*=\s*["']([^"']+)["']"#).unwrap());
+    let script = SCRIPT.get_or_init(|| Regex::new(r"(?is)<script\b([^>]*)>(.*?)</script>").unwrap());
+    let script_url = SCRIPT_URL.get_or_init(|| {
+        Regex::new(r#"(?:\bfetch|\baxios(?:\.(?:get|post|put|patch|delete))?|\$\.(?:get|post|getJSON|ajax)|\.open\s*\(\s*["'][A-Za-z]+["']\s*,)\s*\(?\s*["'`]([^"'`$\s]+)["'`]"#).unwrap()
+    });
+    let form_re = FORM.get_or_init(|| Regex::new(r"(?is)<form\b([^>]*)>(.*?)</form>").unwrap());
+    let field_re = FIELD.get_or_init(|| Regex::new(r"(?is)<(input|select|textarea)\b([^>]*)>").unwrap());
+
+    let Ok(base) = reqwest::Url::parse(page_url) else {
+        return PageLinks::default();
+    };
+    let resolve = |href: &str| -> Option<String> {
+        let href = href.trim().replace("&amp;", "&");
+        let lower = href.to_lowercase();
+        if lower.starts_with("javascript:") || lower.starts_with("mailto:") || lower.starts_with("tel:") || lower.starts_with("data:") {
+            return None;
+        }
+        let mut url = base.join(&href).ok()?;
+        url.set_fragment(None);
+        same_origin(&url, &base).then(|| url.to_string())
+    };
+
+    let mut links: Vec<String> = anchor.captures_iter(html).filter_map(|c| resolve(&c[1])).collect();
+    for caps in script.captures_iter(html) {
+        // 外部腳本 (有 src) 的內容不在頁面中
+        if attr(&caps[1], "src").is_some() {
+            continue;
+        }
+        links.extend(script_url.captures_iter(&caps[2]).filter_map(|c| resolve(&c[1])));
+    }
+    links.sort();
+    links.dedup();
+
+    let forms = form_re
+        .captures_iter(html)
+        .filter_map(|caps| {
+            let action = match attr(&caps[1], "action").filter(|a| !a.trim().is_empty()) {
+                Some(action) => resolve(&action)?,
+                None => {
+                    let mut url = base.clone();
+                    url.set_fragment(None);
+                    url.to_string()
+                }
+            };
+            let fields = field_re
+                .captures_iter(&caps[2])
+                .filter_map(|field| {
+                    let tag = field[1].to_lowercase();
+                    let name = attr(&field[2], "name")?;
+                    let kind = match tag.as_str() {
+                        "input" => attr(&field[2], "type").map(|t| t.to_lowercase()).unwrap_or_else(|| "text".to_string()),
+                        other => other.to_string(),
+                    };
+                    // 按鈕與檔案欄位不是注入目標，也不影響送出
+                    if matches!(kind.as_str(), "submit" | "button" | "reset" | "image" | "file") {
+                        return None;
+                    }
+                    Some(FormField { name, kind, value: attr(&field[2], "value").unwrap_or_default() })
+                })
+                .collect();
+            Some(Form {
+                page: page_url.to_string(),
+                action,
+                method: attr(&caps[1], "method").map(|m| m.trim().to_uppercase()).unwrap_or_else(|| "GET".to_string()),
+                enctype: attr(&caps[1], "enctype")
+                    .map(|e| e.trim().to_lowercase())
+                    .unwrap_or_else(|| "application/x-www-form-urlencoded".to_string()),
+                fields,
+            })
+        })
+        .collect();
+
+    PageLinks { links, forms }
+}
+
+/// 取得 HTML 屬性的值 (不分大小寫，單引號或雙引號)
+pub fn attr(attrs: &str, name: &str) -> Option<String> {
+    Regex::new(&format!(r#"(?i)(?:^|[\s"']){}\s*=\s*(?:"([^"]*)"|'([^']*)')"#, regex::escape(name)))
+        .ok()?
+        .captures(attrs)
+        .and_then(|c| c.get(1).or_else(|| c.get(2)))
+        .map(|m| m.as_str().to_string())
+}
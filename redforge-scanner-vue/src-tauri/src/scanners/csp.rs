@@ -0,0 +1,164 @@
+/**
+ * Content-Security-Policy Analysis
+ *
+ * 依 CSP Level 3 解析政策：指令以分號分隔、名稱不分大小寫、來源以空白分隔，重複的指令只採用第一個。
+ * 分析具體的弱點 (script-src 允許 unsafe-inline / unsafe-eval / data:、萬用字元來源、缺少 object-src 或 base-uri)，
+ * 標頭與頁面中 <meta http-equiv> 宣告的政策使用同一套規則。
+ */
+
+use crate::models::Severity;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 解析後的 CSP：(指令名稱, 來源清單)，依出現順序
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CspPolicy {
+    pub directives: Vec<(String, Vec<String>)>,
+}
+
+/// 政策中的一項弱點；directive 為引發問題的原始指令，供描述引用
+#[derive(Debug, Clone, PartialEq)]
+pub struct CspWeakness {
+    pub check_id: &'static str,
+    pub severity: Severity,
+    pub title: String,
+    pub directive: Option<String>,
+}
+
+pub fn parse(value: &str) -> CspPolicy {
+    let mut policy = CspPolicy::default();
+
+    for directive in value.split(';').map(str::trim).filter(|d| !d.is_empty()) {
+        let mut tokens = directive.split_ascii_whitespace();
+        let Some(name) = tokens.next().map(str::to_lowercase) else { continue };
+        // 重複的指令依規範忽略
+        if policy.get(&name).is_some() {
+            continue;
+        }
+        policy.directives.push((name, tokens.map(str::to_string).collect()));
+    }
+
+    policy
+}
+
+/// 從 HTML 中取出 <meta http-equiv="Content-Security-Policy"> 的政策
+pub fn meta_policies(html: &str) -> Vec<String> {
+    static META: OnceLock<Regex> = OnceLock::new();
+    let meta = META.get_or_init(|| Regex::new(r"(?is)<meta\s[^>]*>").unwrap());
+
+    meta.find_iter(html)
+        .map(|tag| tag.as_str())
+        .filter(|tag| attr(tag, "http-equiv").is_some_and(|v| v.trim().eq_ignore_ascii_case("content-security-policy")))
+        .filter_map(|tag| attr(tag, "content"))
+        .collect()
+}
+
+fn attr(tag: &str, name: &str) -> Option<String> {
+    Regex::new(&format!(r#"(?i)[\s"']{}\s*=\s*(?:"([^"]*)"|'([^']*)')"#, regex::escape(name)))
+        .ok()?
+        .captures(tag)
+        .and_then(|c| c.get(1).or_else(|| c.get(2)))
+        .map(|m| m.as_str().to_string())
+}
+
+impl CspPolicy {
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.directives.iter().find(|(n, _)| n == name).map(|(_, sources)| sources.as_slice())
+    }
+
+    /// 指令的原始文字，例如 "script-src 'self' 'unsafe-inline'"
+    fn quote(&self, name: &str) -> Option<String> {
+        self.get(name).map(|sources| {
+            if sources.is_empty() {
+                name.to_string()
+            } else {
+                format!("{} {}", name, sources.join(" "))
+            }
+        })
+    }
+
+    /// 實際限制腳本的指令：script-src，未設定時退回 default-src
+    fn script_directive(&self) -> Option<&'static str> {
+        ["script-src", "default-src"].into_iter().find(|name| self.get(name).is_some())
+    }
+
+    /// 分析政策的弱點，由最嚴重的開始
+    pub fn weaknesses(&self) -> Vec<CspWeakness> {
+        let mut weaknesses = Vec::new();
+        let has = |sources: &[String], keyword: &str| sources.iter().any(|s| s.eq_ignore_ascii_case(keyword));
+
+        match self.script_directive() {
+            Some(name) => {
+                let sources = self.get(name).unwrap_or_default();
+                // 有 nonce 或 hash 時，支援 CSP2 以上的瀏覽器會忽略 unsafe-inline
+                let has_nonce_or_hash = sources.iter().any(|s| {
+                    let s = s.to_lowercase();
+                    s.starts_with("'nonce-") || s.starts_with("'sha256-") || s.starts_with("'sha384-") || s.starts_with("'sha512-")
+                });
+                if has(sources, "'unsafe-inline'") && !has_nonce_or_hash {
+                    weaknesses.push(self.weakness("csp_unsafe_inline", Severity::High, format!("CSP 的 {} 允許 'unsafe-inline'，無法阻擋注入的行內腳本", name), name));
+                }
+                if has(sources, "'unsafe-eval'") {
+                    weaknesses.push(self.weakness("csp_unsafe_eval", Severity::Medium, format!("CSP 的 {} 允許 'unsafe-eval'", name), name));
+                }
+                if has(sources, "data:") {
+                    weaknesses.push(self.weakness("csp_data_script", Severity::High, format!("CSP 的 {} 允許 data: 來源的腳本", name), name));
+                }
+                if sources.iter().any(|s| is_wildcard(s)) {
+                    weaknesses.push(self.weakness("csp_wildcard_source", Severity::High, format!("CSP 的 {} 允許任意來源的腳本", name), name));
+                }
+            }
+            None => weaknesses.push(CspWeakness {
+                check_id: "csp_missing_script_src",
+                severity: Severity::Medium,
+                title: "CSP 未設定 script-src 或 default-src，腳本不受限制".to_string(),
+                directive: None,
+            }),
+        }
+
+        // 腳本以外的指令使用萬用字元
+        for (name, sources) in &self.directives {
+            if Some(name.as_str()) != self.script_directive() && name.ends_with("-src") && sources.iter().any(|s| is_wildcard(s)) {
+                weaknesses.push(self.weakness("csp_wildcard_source", Severity::Medium, format!("CSP 的 {} 允許任意來源", name), name));
+            }
+        }
+
+        // object-src 未設定時退回 default-src，只有 default-src 'none' 才能擋下外掛內容
+        let object_restricted = self.get("default-src").is_some_and(|sources| has(sources, "'none'"));
+        if self.get("object-src").is_none() && !object_restricted {
+            weaknesses.push(CspWeakness {
+                check_id: "csp_missing_object_src",
+                severity: Severity::Medium,
+                title: "CSP 缺少 object-src，可透過外掛內容 (<object>/<embed>) 繞過腳本限制".to_string(),
+                directive: None,
+            });
+        }
+
+        // base-uri 不會退回 default-src
+        if self.get("base-uri").is_none() {
+            weaknesses.push(CspWeakness {
+                check_id: "csp_missing_base_uri",
+                severity: Severity::Low,
+                title: "CSP 缺少 base-uri，注入的 <base> 標籤可改寫相對路徑腳本的來源".to_string(),
+                directive: None,
+            });
+        }
+
+        weaknesses.sort_by_key(|w| std::cmp::Reverse(w.severity.rank()));
+        weaknesses
+    }
+
+    /// 沒有 Medium 以上的弱點
+    pub fn is_secure(&self) -> bool {
+        self.weaknesses().iter().all(|w| w.severity.rank() < Severity::Medium.rank())
+    }
+
+    fn weakness(&self, check_id: &'static str, severity: Severity, title: String, directive: &str) -> CspWeakness {
+        CspWeakness { check_id, severity, title, directive: self.quote(directive) }
+    }
+}
+
+/// 允許任意主機的來源：*、http: / https: 等只有 scheme 的來源
+fn is_wildcard(source: &str) -> bool {
+    matches!(source.to_lowercase().as_str(), "*" | "http:" | "https:" | "http://*" | "https://*")
+}
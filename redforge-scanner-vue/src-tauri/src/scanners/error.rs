@@ -0,0 +1,140 @@
+/**
+ * Scanner Errors
+ *
+ * 掃描器共用的錯誤型別：依失敗原因分類，而不是只保留訊息，讓失敗的任務可以記錄錯誤種類
+ * (網路中斷與逾時可以重試，無效的目標或設定重試也不會成功)。
+ * 型別為 Send + Sync，可以直接跨越 tokio::spawn 與 spawn_blocking；只在 Tauri 命令的邊界轉成字串。
+ */
+
+use crate::models::ScanErrorKind;
+use std::time::Duration;
+
+pub type ScannerResult<T> = Result<T, ScannerError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScannerError {
+    /// 連線被拒、中斷或 HTTP 層的錯誤
+    #[error("{url} 請求失敗: {source}")]
+    Network {
+        source: Box<dyn std::error::Error + Send + Sync>,
+        url: String,
+    },
+    /// 連線或回應逾時；after 為實際等待的時間 (不確定時為 None)
+    #[error("{url} 逾時{}", elapsed_label(.after))]
+    Timeout { url: String, after: Option<Duration> },
+    /// 無法解析主機名稱
+    #[error("{0}")]
+    Dns(String),
+    /// TLS 交握、憑證或 TLS 設定錯誤
+    #[error("{0}")]
+    Tls(String),
+    /// 無法解析回應或憑證內容
+    #[error("{0}")]
+    Parse(String),
+    /// 目標 URL 無效或不支援
+    #[error("{0}")]
+    InvalidTarget(String),
+    /// 掃描設定無效 (用戶端憑證、登入、同意 cookie、OOB 設定)
+    #[error("{0}")]
+    Config(String),
+    /// 目標封鎖掃描流量
+    #[error("{0}")]
+    Blocked(String),
+    /// 掃描被取消、暫停逾時、登入失效或請求預算用盡
+    #[error("{0}")]
+    Interrupted(String),
+    #[error("{0}")]
+    Internal(String),
+    /// 代理設定錯誤、無法連線到代理或代理拒絕認證
+    #[error("{0}")]
+    Proxy(String),
+}
+
+fn elapsed_label(after: &Option<Duration>) -> String {
+    after.map(|after| format!(" ({:.1} 秒)", after.as_secs_f64())).unwrap_or_default()
+}
+
+impl ScannerError {
+    /// 分類 reqwest 的錯誤；elapsed 為請求送出到失敗的時間
+    pub fn request(error: reqwest::Error, elapsed: Option<Duration>) -> Self {
+        let url = error.url().map(|url| url.to_string()).unwrap_or_default();
+        if error.is_timeout() {
+            return ScannerError::Timeout { url, after: elapsed };
+        }
+        if error.is_builder() {
+            return if url.is_empty() {
+                ScannerError::Config(format!("無法建立 HTTP 用戶端: {}", error))
+            } else {
+                ScannerError::InvalidTarget(format!("無效的請求 {}: {}", url, error))
+            };
+        }
+        if error.is_connect() {
+            // reqwest 不區分連線失敗的原因，只能從錯誤鏈的訊息判斷
+            let chain = source_chain(&error).to_lowercase();
+            let host = error.url().and_then(|url| url.host_str()).unwrap_or_default().to_string();
+            if ["dns error", "failed to lookup address", "name or service not known", "no such host"].iter().any(|m| chain.contains(m)) {
+                return ScannerError::Dns(format!("無法解析主機 {}: {}", host, error));
+            }
+            if ["certificate", "tls", "ssl", "handshake", "alert"].iter().any(|m| chain.contains(m)) {
+                return ScannerError::Tls(format!("與 {} 的 TLS 交握失敗: {}", host, error));
+            }
+        }
+        ScannerError::Network { source: Box::new(error), url }
+    }
+
+    /// 同步 socket 的連線或讀寫錯誤；逾時的錯誤另外分類
+    pub fn io(error: std::io::Error, url: String, after: Duration) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => ScannerError::Timeout { url, after: Some(after) },
+            _ => ScannerError::Network { source: Box::new(error), url },
+        }
+    }
+
+    pub fn kind(&self) -> ScanErrorKind {
+        match self {
+            ScannerError::Network { .. } => ScanErrorKind::Network,
+            ScannerError::Timeout { .. } => ScanErrorKind::Timeout,
+            ScannerError::Dns(_) => ScanErrorKind::Dns,
+            ScannerError::Tls(_) => ScanErrorKind::Tls,
+            ScannerError::Parse(_) => ScanErrorKind::Parse,
+            ScannerError::InvalidTarget(_) => ScanErrorKind::InvalidTarget,
+            ScannerError::Config(_) => ScanErrorKind::Config,
+            ScannerError::Blocked(_) => ScanErrorKind::Blocked,
+            ScannerError::Interrupted(_) => ScanErrorKind::Interrupted,
+            ScannerError::Internal(_) => ScanErrorKind::Internal,
+            ScannerError::Proxy(_) => ScanErrorKind::Proxy,
+        }
+    }
+
+    /// 在訊息前加上失敗的步驟，保留錯誤種類 (Network 與 Timeout 的訊息已包含 URL，不另外加)
+    pub fn context(self, step: &str) -> Self {
+        let prefix = |message: String| format!("{}: {}", step, message);
+        match self {
+            ScannerError::Dns(message) => ScannerError::Dns(prefix(message)),
+            ScannerError::Tls(message) => ScannerError::Tls(prefix(message)),
+            ScannerError::Parse(message) => ScannerError::Parse(prefix(message)),
+            ScannerError::InvalidTarget(message) => ScannerError::InvalidTarget(prefix(message)),
+            ScannerError::Config(message) => ScannerError::Config(prefix(message)),
+            ScannerError::Internal(message) => ScannerError::Internal(prefix(message)),
+            ScannerError::Proxy(message) => ScannerError::Proxy(prefix(message)),
+            other => other,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ScannerError {
+    fn from(error: reqwest::Error) -> Self {
+        ScannerError::request(error, None)
+    }
+}
+
+fn source_chain(error: &dyn std::error::Error) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        chain.push_str(": ");
+        chain.push_str(&error.to_string());
+        source = error.source();
+    }
+    chain
+}
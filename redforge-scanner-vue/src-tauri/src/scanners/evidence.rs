@@ -0,0 +1,188 @@
+/**
+ * Request / Response Evidence
+ *
+ * 記錄掃描送出的請求與收到的回應，產生發現時附上對應的交換內容，讓開發人員可以直接重現。
+ * 由 ScanControl 持有：ScanClient 送出請求時記錄請求列與標頭，收到回應時補上狀態碼與標頭，
+ * 檢查讀取內容時 (`client::text` / `client::bytes`) 再補上截斷的內容。
+ *
+ * 只保留最近的 MAX_EXCHANGES 筆，同一 URL 只保留最新的一筆；憑證類的請求標頭一律遮蔽，
+ * 其他位置出現的 JWT 移除簽章。
+ */
+
+use crate::models::{EvidenceHeader, HttpExchange};
+use crate::scanners::jwt;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 預設保留的回應內容位元組數
+pub const DEFAULT_BODY_BYTES: usize = 4096;
+/// 保留的交換筆數上限，超過時捨棄最舊的
+const MAX_EXCHANGES: usize = 500;
+/// 值一律以 "<redacted>" 取代的請求標頭 (小寫)
+const SENSITIVE_REQUEST_HEADERS: &[&str] = &[
+    "authorization", "proxy-authorization", "cookie", "x-api-key", "x-auth-token", "x-csrf-token", "x-xsrf-token",
+];
+const REDACTED: &str = "<redacted>";
+
+/// 掃描的交換紀錄，依 URL 查詢
+pub struct ExchangeLog {
+    body_limit: Mutex<usize>,
+    entries: Mutex<VecDeque<(String, HttpExchange)>>,
+}
+
+impl Default for ExchangeLog {
+    fn default() -> Self {
+        Self { body_limit: Mutex::new(DEFAULT_BODY_BYTES), entries: Mutex::new(VecDeque::new()) }
+    }
+}
+
+impl ExchangeLog {
+    /// 設定保留的回應內容位元組數
+    pub fn set_body_limit(&self, bytes: usize) {
+        *self.body_limit.lock().unwrap_or_else(|e| e.into_inner()) = bytes;
+    }
+
+    pub fn body_limit(&self) -> usize {
+        *self.body_limit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 記錄完成的交換 (尚未讀取內容)，取代同一 URL 先前的紀錄
+    pub fn record(&self, url: &str, exchange: HttpExchange) {
+        let key = normalize(url);
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|(existing, _)| *existing != key);
+        if entries.len() >= MAX_EXCHANGES {
+            entries.pop_front();
+        }
+        entries.push_back((key, exchange));
+    }
+
+    /// 補上最近一次對 url 的交換的回應內容
+    pub fn record_body(&self, url: &str, body: &[u8]) {
+        let key = normalize(url);
+        let limit = self.body_limit();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, exchange)) = entries.iter_mut().rev().find(|(existing, _)| *existing == key) {
+            let (excerpt, truncated) = excerpt(body, limit);
+            exchange.response_body_excerpt = Some(jwt::strip_signatures(&excerpt).into_owned());
+            exchange.response_body_bytes = Some(body.len() as u64);
+            exchange.response_body_truncated = truncated;
+        }
+    }
+
+    /// 最近一次對 url 的交換
+    pub fn find(&self, url: &str) -> Option<HttpExchange> {
+        let key = normalize(url);
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().rev().find(|(existing, _)| *existing == key).map(|(_, exchange)| exchange.clone())
+    }
+}
+
+/// 由即將送出的請求建立交換紀錄；請求內容也依 body_limit 截斷
+pub fn request(request: &reqwest::Request, body_limit: usize) -> HttpExchange {
+    let request_body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .filter(|body| !body.is_empty())
+        .map(|body| jwt::strip_signatures(&excerpt(body, body_limit).0).into_owned());
+    HttpExchange {
+        request_line: jwt::strip_signatures(&format!("{} {} {}", request.method(), request.url(), version(request.version()))).into_owned(),
+        request_headers: headers(request.headers(), true),
+        request_body,
+        ..HttpExchange::default()
+    }
+}
+
+/// 補上回應的狀態碼與標頭
+pub fn respond(mut exchange: HttpExchange, response: &reqwest::Response) -> HttpExchange {
+    exchange.response_status = Some(response.status().as_u16());
+    exchange.response_headers = headers(response.headers(), false);
+    exchange
+}
+
+/// 以 UTF-8 解碼內容的前 limit 個位元組 (不切斷多位元組字元)，回傳 (內容, 是否截斷)
+pub fn excerpt(body: &[u8], limit: usize) -> (String, bool) {
+    if body.len() <= limit {
+        return (String::from_utf8_lossy(body).into_owned(), false);
+    }
+    let text = String::from_utf8_lossy(&body[..limit]);
+    // 截斷處若在多位元組字元中間，lossy 解碼會在結尾產生替換字元
+    let text = text.trim_end_matches('\u{FFFD}');
+    (text.to_string(), true)
+}
+
+fn headers(map: &reqwest::header::HeaderMap, redact: bool) -> Vec<EvidenceHeader> {
+    map.iter()
+        .map(|(name, value)| {
+            let sensitive = redact && SENSITIVE_REQUEST_HEADERS.contains(&name.as_str());
+            EvidenceHeader {
+                name: name.as_str().to_string(),
+                value: if sensitive {
+                    REDACTED.to_string()
+                } else {
+                    jwt::strip_signatures(&String::from_utf8_lossy(value.as_bytes())).into_owned()
+                },
+            }
+        })
+        .collect()
+}
+
+fn version(version: reqwest::Version) -> &'static str {
+    match version {
+        reqwest::Version::HTTP_09 => "HTTP/0.9",
+        reqwest::Version::HTTP_10 => "HTTP/1.0",
+        reqwest::Version::HTTP_2 => "HTTP/2",
+        reqwest::Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP/1.1",
+    }
+}
+
+/// URL 經過解析後的字串，同一 URL 的不同寫法 (例如未編碼的字元) 視為相同
+fn normalize(url: &str) -> String {
+    reqwest::Url::parse(url).map(|u| u.to_string()).unwrap_or_else(|_| url.to_string())
+}
+
+impl HttpExchange {
+    /// 原始 HTTP 格式的請求與回應，供報告與重現使用
+    pub fn transcript(&self) -> String {
+        let mut text = format!("{}\n", self.request_line);
+        for header in &self.request_headers {
+            text.push_str(&format!("{}: {}\n", header.name, header.value));
+        }
+        if let Some(body) = &self.request_body {
+            text.push_str(&format!("\n{}\n", body));
+        }
+
+        let Some(status) = self.response_status else {
+            text.push_str("\n(沒有收到回應)\n");
+            return text;
+        };
+        let reason = reqwest::StatusCode::from_u16(status).ok().and_then(|s| s.canonical_reason()).unwrap_or("");
+        text.push_str(&format!("\nHTTP {} {}\n", status, reason).replace(" \n", "\n"));
+        for header in &self.response_headers {
+            text.push_str(&format!("{}: {}\n", header.name, header.value));
+        }
+        if let Some(body) = &self.response_body_excerpt {
+            text.push_str(&format!("\n{}\n", body));
+            if self.response_body_truncated {
+                text.push_str(&format!(
+                    "... (內容已截斷，共 {} 位元組)\n",
+                    self.response_body_bytes.unwrap_or_default()
+                ));
+            }
+        }
+        text
+    }
+
+    /// Markdown 的 fenced code block；內容中的反引號長度超過圍欄時自動加長圍欄
+    pub fn markdown(&self) -> String {
+        let transcript = self.transcript();
+        let longest = transcript
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        let fence = "`".repeat(longest.max(2) + 1);
+        format!("{}http\n{}{}\n", fence, transcript, fence)
+    }
+}
@@ -0,0 +1,123 @@
+/**
+ * Per-check Finding Caps
+ *
+ * 對所有路徑都回應 200 的目標 (catch-all)，探測型檢查會產生數百個幾乎相同的發現，
+ * 讓報告與 IPC 傳輸變得龐大。單一檢查的發現超過上限時，只保留前 N 個 (依嚴重度)，
+ * 其餘合併為一個摘要發現，完整清單移到報告的探索內容 (discovered_content)。
+ */
+
+use crate::models::*;
+use crate::scanners::control;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+
+/// 未登錄的檢查使用的上限
+const DEFAULT_CAP: usize = 25;
+
+/// 各檢查的發現上限；id 與 time_box 的檢查 id 相同
+const CHECK_CAPS: &[(&str, usize)] = &[
+    // 管理後台與敏感檔案探測最容易受 catch-all 影響
+    ("owasp.a01", 15),
+    ("owasp.a05", 25),
+    // 每個參數各自是獨立的注入點，保留較多
+    ("owasp.a03", 50),
+    ("legacy", 25),
+];
+
+pub fn cap(check_id: &str) -> usize {
+    CHECK_CAPS
+        .iter()
+        .find(|(id, _)| *id == check_id)
+        .map(|(_, cap)| *cap)
+        .unwrap_or(DEFAULT_CAP)
+}
+
+/// 被合併的發現的完整清單
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiscoveredContent {
+    pub check_id: String,
+    /// 檢查產生的發現總數
+    pub total: usize,
+    /// 保留為個別發現的數量
+    pub kept: usize,
+    pub items: Vec<DiscoveredItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiscoveredItem {
+    pub title: String,
+    pub severity: Option<Severity>,
+    /// raw_data 中的 url 或 path
+    pub location: Option<String>,
+}
+
+/// 超過上限時合併發現；合併的清單記錄在目前掃描的探索內容中
+pub fn apply(task_id: &str, check_id: &str, findings: Vec<ScanResult>) -> Vec<ScanResult> {
+    collapse(task_id, check_id, cap(check_id), findings)
+}
+
+/// 以指定的上限合併發現
+pub(crate) fn collapse(task_id: &str, check_id: &str, limit: usize, mut findings: Vec<ScanResult>) -> Vec<ScanResult> {
+    if findings.len() <= limit {
+        return findings;
+    }
+
+    let total = findings.len();
+    // 依嚴重度保留，同嚴重度保留原順序
+    findings.sort_by_key(|f| std::cmp::Reverse(severity_rank(f.severity.as_ref())));
+    let collapsed = findings.split_off(limit);
+    control::log(format!("🧺 {} 產生 {} 個發現，超過上限 {}，其餘 {} 個合併為摘要", check_id, total, limit, collapsed.len()));
+
+    let items: Vec<DiscoveredItem> = findings.iter().chain(&collapsed).map(discovered_item).collect();
+    findings.push(summary_finding(task_id, check_id, total, &collapsed));
+    control::discovered(DiscoveredContent { check_id: check_id.to_string(), total, kept: limit, items });
+    findings
+}
+
+fn discovered_item(finding: &ScanResult) -> DiscoveredItem {
+    let raw: Option<serde_json::Value> = finding.raw_data.as_deref().and_then(|raw| serde_json::from_str(raw).ok());
+    DiscoveredItem {
+        title: finding.title.clone(),
+        severity: finding.severity.clone(),
+        location: raw
+            .as_ref()
+            .and_then(|raw| raw.get("url").or_else(|| raw.get("path")))
+            .and_then(|value| value.as_str())
+            .map(str::to_string),
+    }
+}
+
+fn summary_finding(task_id: &str, check_id: &str, total: usize, collapsed: &[ScanResult]) -> ScanResult {
+    let severity = collapsed
+        .iter()
+        .filter_map(|f| f.severity.clone())
+        .max_by_key(Severity::rank)
+        .unwrap_or(Severity::Info);
+    ScanResult {
+        id: Uuid::new_v4().to_string(),
+        task_id: task_id.to_string(),
+        result_type: ResultType::Vulnerability,
+        severity: Some(severity),
+        title: format!("{} 符合 {} 個項目，可能為 catch-all 回應", check_id, total),
+        description: Some(format!(
+            "{} 產生的發現數量 ({}) 超過上限，其餘 {} 個相似的發現已合併。大量相同的結果通常代表目標對任何路徑都回應成功 (catch-all)，請在探索內容中檢視完整清單並人工確認",
+            check_id, total, collapsed.len()
+        )),
+        raw_data: Some(
+            serde_json::json!({
+                "type": "Collapsed Findings",
+                "check_id": "finding_cap",
+                "source_check": check_id,
+                "total": total,
+                "collapsed": collapsed.len(),
+            })
+            .to_string(),
+        ),
+        created_at: Utc::now(),
+        low_confidence: true,
+        cwe_ids: Vec::new(),
+        details: None,
+    }
+}
@@ -0,0 +1,145 @@
+/**
+ * Host Header Injection
+ *
+ * 以偽造的 Host 或 X-Forwarded-Host 重新請求基礎頁面：應用程式用請求的主機名稱產生絕對網址時，
+ * 重定向、頁面連結與密碼重設信件中的連結都會指向攻擊者的主機；回應被快取時，其他使用者也會拿到被污染的頁面。
+ *
+ * 偽造的主機名稱帶有此掃描的 canary，只有回應中出現該名稱才算反射。以虛擬主機區分網站的伺服器
+ * 對未知主機回應 4xx 是正常行為，不是漏洞。
+ */
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 反射片段前後保留的字元數
+const SNIPPET_CONTEXT: usize = 80;
+
+/// 一組覆寫主機的請求標頭
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub headers: Vec<(&'static str, String)>,
+}
+
+impl Override {
+    /// 報告中顯示的送出標頭，例如 "X-Forwarded-Host: rfg-host-1.evil.example"
+    pub fn describe(&self) -> String {
+        self.headers.iter().map(|(name, value)| format!("{}: {}", name, value)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// 要嘗試的主機覆寫：直接偽造 Host，以及代理層常見的轉送標頭
+pub fn overrides(forged: &str) -> Vec<Override> {
+    vec![
+        Override { headers: vec![("Host", forged.to_string())] },
+        Override { headers: vec![("X-Forwarded-Host", forged.to_string())] },
+        Override { headers: vec![("X-Forwarded-Host", forged.to_string()), ("X-Forwarded-Proto", "http".to_string())] },
+        Override { headers: vec![("X-Host", forged.to_string())] },
+        Override { headers: vec![("Forwarded", format!("host={}", forged))] },
+    ]
+}
+
+/// 反射的位置，決定嚴重度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReflectionKind {
+    /// Location 標頭
+    Redirect,
+    /// 頁面中的連結或資源網址 (href、src、action、密碼重設連結)
+    Link,
+    /// 其他位置的文字
+    Body,
+    /// 只出現在分析服務的參數中 (例如 Google Analytics 的 dl 參數)
+    Analytics,
+}
+
+impl ReflectionKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReflectionKind::Redirect => "重定向的 Location 標頭",
+            ReflectionKind::Link => "頁面產生的連結",
+            ReflectionKind::Body => "頁面內容",
+            ReflectionKind::Analytics => "分析服務的參數",
+        }
+    }
+}
+
+/// 回應中偽造主機名稱的反射
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reflection {
+    pub kind: ReflectionKind,
+    pub snippet: String,
+}
+
+/// 找出最嚴重的反射：Location 標頭優先，其次是連結，分析服務參數最後
+pub fn reflection(forged: &str, location: Option<&str>, body: &str) -> Option<Reflection> {
+    let forged = forged.to_lowercase();
+    if let Some(location) = location.filter(|location| location.to_lowercase().contains(&forged)) {
+        return Some(Reflection { kind: ReflectionKind::Redirect, snippet: location.to_string() });
+    }
+
+    let lower = body.to_lowercase();
+    let mut best: Option<Reflection> = None;
+    for (offset, _) in lower.match_indices(&forged) {
+        let snippet = snippet(body, offset, forged.len());
+        let kind = classify(&snippet.to_lowercase(), &forged);
+        let rank = |kind: ReflectionKind| match kind {
+            ReflectionKind::Redirect => 0,
+            ReflectionKind::Link => 1,
+            ReflectionKind::Body => 2,
+            ReflectionKind::Analytics => 3,
+        };
+        if best.as_ref().map_or(true, |best| rank(kind) < rank(best.kind)) {
+            best = Some(Reflection { kind, snippet });
+        }
+    }
+    best
+}
+
+fn classify(snippet: &str, forged: &str) -> ReflectionKind {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r#"(?:href|src|action|content|url)\s*=\s*["']?(?:https?:)?//$"#).unwrap());
+    let Some(position) = snippet.find(forged) else {
+        return ReflectionKind::Body;
+    };
+    let before = &snippet[..position];
+
+    if ["google-analytics", "googletagmanager", "gtag(", "analytics", "utm_", "&dl=", "?dl="]
+        .iter()
+        .any(|hint| before.contains(hint))
+    {
+        return ReflectionKind::Analytics;
+    }
+    if link.is_match(before) || snippet.contains("reset") || snippet.contains("token=") {
+        return ReflectionKind::Link;
+    }
+    ReflectionKind::Body
+}
+
+/// 反射位置前後的片段 (不切斷多位元組字元)
+fn snippet(body: &str, offset: usize, len: usize) -> String {
+    let mut start = offset.saturating_sub(SNIPPET_CONTEXT);
+    while !body.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (offset + len + SNIPPET_CONTEXT).min(body.len());
+    while !body.is_char_boundary(end) {
+        end += 1;
+    }
+    body[start..end].trim().to_string()
+}
+
+/// 回應是否可能被共用快取儲存 (反射被快取時成為 cache poisoning)
+pub fn cache_indicators(headers: &reqwest::header::HeaderMap) -> Vec<String> {
+    let mut indicators = Vec::new();
+    for name in ["x-cache", "cf-cache-status", "age", "x-varnish", "x-served-by"] {
+        if let Some(value) = headers.get(name).and_then(|v| v.to_str().ok()) {
+            indicators.push(format!("{}: {}", name, value));
+        }
+    }
+    if let Some(cache_control) = headers.get("cache-control").and_then(|v| v.to_str().ok()) {
+        let lower = cache_control.to_lowercase();
+        if lower.contains("public") || lower.contains("s-maxage") {
+            indicators.push(format!("cache-control: {}", cache_control));
+        }
+    }
+    indicators
+}
@@ -0,0 +1,165 @@
+/**
+ * HSTS Policy
+ *
+ * 依 RFC 6797 解析 Strict-Transport-Security：指令以分號分隔、名稱不分大小寫，max-age 值可加引號。
+ * 指令重複或 max-age 不是數字時整個標頭無效，瀏覽器會忽略它，等同沒有設定 HSTS。
+ */
+
+/// 視為安全的最短 max-age (180 天)
+pub const MIN_MAX_AGE: u64 = 15_552_000;
+/// 建議的 max-age (一年)，preload 清單的要求
+pub const RECOMMENDED_MAX_AGE: u64 = 31_536_000;
+
+/// 解析後的 HSTS 設定
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HstsPolicy {
+    pub max_age: Option<u64>,
+    pub include_subdomains: bool,
+    pub preload: bool,
+    // 讓標頭無效的問題 (重複指令、max-age 格式錯誤)
+    pub errors: Vec<String>,
+}
+
+pub fn parse(value: &str) -> HstsPolicy {
+    let mut policy = HstsPolicy::default();
+    let mut seen: Vec<String> = Vec::new();
+
+    for directive in value.split(';').map(str::trim).filter(|d| !d.is_empty()) {
+        let (name, directive_value) = match directive.split_once('=') {
+            Some((name, value)) => (name.trim().to_lowercase(), Some(value.trim().trim_matches('"'))),
+            None => (directive.to_lowercase(), None),
+        };
+        if seen.contains(&name) {
+            policy.errors.push(format!("指令 {} 重複出現", name));
+            continue;
+        }
+        seen.push(name.clone());
+
+        match name.as_str() {
+            "max-age" => match directive_value.map(|v| v.parse::<u64>()) {
+                Some(Ok(seconds)) => policy.max_age = Some(seconds),
+                _ => policy.errors.push(format!("max-age 的值無效: {}", directive_value.unwrap_or(""))),
+            },
+            "includesubdomains" => policy.include_subdomains = true,
+            "preload" => policy.preload = true,
+            // 未知的指令依規範忽略
+            _ => {}
+        }
+    }
+
+    if policy.max_age.is_none() && policy.errors.is_empty() {
+        policy.errors.push("缺少必要的 max-age 指令".to_string());
+    }
+    policy
+}
+
+impl HstsPolicy {
+    /// 瀏覽器會採用此標頭 (格式正確且有 max-age)
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty() && self.max_age.is_some()
+    }
+
+    /// 標頭有效且實際啟用 HSTS (max-age=0 代表要求瀏覽器移除 HSTS)
+    pub fn is_enabled(&self) -> bool {
+        self.is_valid() && self.max_age.unwrap_or(0) > 0
+    }
+
+    /// max-age=0：明確停用 HSTS
+    pub fn disables(&self) -> bool {
+        self.is_valid() && self.max_age == Some(0)
+    }
+
+    /// 有效且 max-age 至少 180 天
+    pub fn is_secure(&self) -> bool {
+        self.is_valid() && self.max_age.unwrap_or(0) >= MIN_MAX_AGE
+    }
+
+    /// 依實際的問題產生建議
+    pub fn recommendation(&self) -> String {
+        let recommended = format!("建議值: max-age={}; includeSubDomains", RECOMMENDED_MAX_AGE);
+        if !self.errors.is_empty() {
+            return format!("HSTS 標頭無效 ({})，瀏覽器會忽略此標頭。{}", self.errors.join("、"), recommended);
+        }
+        let max_age = self.max_age.unwrap_or(0);
+        if max_age == 0 {
+            return format!("max-age=0 會讓瀏覽器移除 HSTS 設定，等同停用。{}", recommended);
+        }
+
+        let mut issues = Vec::new();
+        if max_age < MIN_MAX_AGE {
+            issues.push(format!("max-age 過短 ({} 秒，至少應為 {} 秒 / 180 天)", max_age, MIN_MAX_AGE));
+        }
+        if !self.include_subdomains {
+            issues.push("缺少 includeSubDomains，子網域仍可能被降級為 HTTP".to_string());
+        }
+        if self.preload && (!self.include_subdomains || max_age < RECOMMENDED_MAX_AGE) {
+            issues.push(format!("preload 需要 includeSubDomains 且 max-age 至少 {} 秒", RECOMMENDED_MAX_AGE));
+        }
+        if issues.is_empty() {
+            "HSTS 設定正確".to_string()
+        } else {
+            format!("{}。{}", issues.join("；"), recommended)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_directives_case_insensitively() {
+        let policy = parse("max-age=31536000; includeSubDomains; preload");
+        assert_eq!(policy, HstsPolicy { max_age: Some(31_536_000), include_subdomains: true, preload: true, errors: Vec::new() });
+        assert!(policy.is_secure());
+        assert_eq!(policy.recommendation(), "HSTS 設定正確");
+
+        let policy = parse(r#"  MAX-AGE="15552000" ;INCLUDESUBDOMAINS;  ; unknown-directive=1"#);
+        assert_eq!(policy.max_age, Some(MIN_MAX_AGE));
+        assert!(policy.include_subdomains && !policy.preload);
+        assert!(policy.is_secure());
+    }
+
+    #[test]
+    fn malformed_values_make_the_header_invalid() {
+        for value in ["max-age=abc", "max-age=", "max-age=-1", "max-age=1.5", "includeSubDomains", ""] {
+            let policy = parse(value);
+            assert!(!policy.is_valid(), "{:?}", value);
+            assert!(!policy.is_enabled() && !policy.is_secure() && !policy.disables(), "{:?}", value);
+            assert!(policy.recommendation().starts_with("HSTS 標頭無效"), "{:?}", value);
+        }
+        assert!(parse("max-age=abc").errors[0].contains("abc"));
+        assert!(parse("includeSubDomains").errors[0].contains("缺少必要的 max-age"));
+    }
+
+    #[test]
+    fn duplicate_directives_make_the_header_invalid() {
+        for value in ["max-age=31536000; max-age=60", "max-age=31536000; includeSubDomains; includesubdomains", "max-age=31536000; preload; PRELOAD"] {
+            let policy = parse(value);
+            assert!(!policy.is_valid(), "{:?}", value);
+            assert!(policy.errors.iter().any(|e| e.contains("重複")), "{:?}", policy.errors);
+        }
+        // 第一個 max-age 仍被解析，但重複讓整個標頭無效
+        assert_eq!(parse("max-age=31536000; max-age=60").max_age, Some(31_536_000));
+    }
+
+    #[test]
+    fn recommendation_names_the_actual_problem() {
+        let short = parse("max-age=60; includeSubDomains");
+        assert!(short.is_enabled() && !short.is_secure());
+        assert!(short.recommendation().contains("max-age 過短 (60 秒"), "{}", short.recommendation());
+        assert!(!short.recommendation().contains("includeSubDomains，"));
+
+        let no_subdomains = parse("max-age=31536000");
+        assert!(no_subdomains.is_secure());
+        assert!(no_subdomains.recommendation().contains("缺少 includeSubDomains"));
+        assert!(!no_subdomains.recommendation().contains("過短"));
+
+        let weak_preload = parse("max-age=15552000; includeSubDomains; preload");
+        assert!(weak_preload.recommendation().contains("preload 需要"));
+
+        let disabled = parse("max-age=0");
+        assert!(disabled.disables() && !disabled.is_enabled());
+        assert!(disabled.recommendation().starts_with("max-age=0"));
+    }
+}
@@ -0,0 +1,61 @@
+/**
+ * HTTP Method Enumeration
+ *
+ * OPTIONS 回應的 Allow / Access-Control-Allow-Methods 只是伺服器宣告的方法，不代表實際可用；
+ * 危險的方法 (TRACE、PUT、DELETE、PATCH) 需以實際請求確認，宣告清單作為證據一併記錄。
+ */
+
+/// 需要實際驗證的危險方法
+pub const DANGEROUS_METHODS: [&str; 4] = ["TRACE", "PUT", "DELETE", "PATCH"];
+
+/// 除了首頁外，最多對幾個頁面中的路徑送出 OPTIONS 與 PATCH
+pub const MAX_DISCOVERED_PATHS: usize = 2;
+
+/// 追蹤 TRACE 是否回顯請求時使用的標頭
+pub const TRACE_MARKER_HEADER: &str = "X-RedForge-Trace";
+
+/// 解析 Allow 類型的標頭 ("GET, POST, OPTIONS")，方法名稱轉為大寫並去除重複
+pub fn parse_allow(value: &str) -> Vec<String> {
+    let mut methods: Vec<String> = Vec::new();
+    for method in value.split(',').map(|m| m.trim().to_uppercase()).filter(|m| !m.is_empty()) {
+        if !methods.contains(&method) {
+            methods.push(method);
+        }
+    }
+    methods
+}
+
+/// OPTIONS 回應宣告的方法 (Allow 與 Access-Control-Allow-Methods 的聯集)
+pub fn advertised_methods(headers: &reqwest::header::HeaderMap) -> Vec<String> {
+    let mut methods: Vec<String> = Vec::new();
+    for name in ["allow", "access-control-allow-methods"] {
+        for value in crate::scanners::http_scanner::header_values(headers, name) {
+            for method in parse_allow(&value) {
+                if !methods.contains(&method) {
+                    methods.push(method);
+                }
+            }
+        }
+    }
+    methods
+}
+
+/// 宣告清單中的危險方法
+pub fn dangerous(methods: &[String]) -> Vec<String> {
+    methods.iter().filter(|m| DANGEROUS_METHODS.contains(&m.as_str())).cloned().collect()
+}
+
+/// 頁面中最多 MAX_DISCOVERED_PATHS 個同主機路徑，不含首頁本身與靜態資源
+pub fn discovered_paths(html: &str, base_url: &str) -> Vec<String> {
+    const STATIC_EXTENSIONS: [&str; 10] = [".css", ".js", ".png", ".jpg", ".jpeg", ".gif", ".svg", ".ico", ".woff", ".woff2"];
+    let base = base_url.trim_end_matches('/');
+    crate::scanners::language_inference::page_urls(html, base_url)
+        .into_iter()
+        .filter(|url| url.trim_end_matches('/') != base)
+        .filter(|url| {
+            let path = reqwest::Url::parse(url).map(|u| u.path().to_lowercase()).unwrap_or_default();
+            !STATIC_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+        })
+        .take(MAX_DISCOVERED_PATHS)
+        .collect()
+}
@@ -1,54 +1,266 @@
 use crate::models::*;
+use crate::scanners::tech_detector::{header_evidence, html_evidence};
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::{cookies, csp, hsts};
 use crate::scanners::ScannerResult;
-use reqwest::header::HeaderMap;
+use reqwest::header::{HeaderMap, HeaderValue};
 use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::Utc;
 
+/// 除了主頁面外，也稽核登入頁面設定的 cookie (session cookie 通常在此發出)
+const LOGIN_PATHS: [&str; 4] = ["/login", "/signin", "/auth", "/user/login"];
+
+/// 多個值合併顯示時使用的分隔符號
+pub const HEADER_VALUE_SEPARATOR: &str = " | ";
+
+/// 只應出現一次的安全標頭，重複且值不同時瀏覽器行為未定義
+const SINGLE_VALUE_SECURITY_HEADERS: [&str; 4] = [
+    "x-frame-options",
+    "x-content-type-options",
+    "strict-transport-security",
+    "x-xss-protection",
+];
+
+/// 標頭掃描結果：標頭清單，以及由標頭本身產生的發現 (例如互相衝突的重複標頭)
+#[derive(Debug, Clone)]
+pub struct HeaderScan {
+    pub headers: Vec<SecurityHeader>,
+    pub findings: Vec<ScanResult>,
+}
+
 pub struct HttpScanner {
-    client: reqwest::Client,
+    client: ScanClient,
 }
 
 impl HttpScanner {
-    pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::builder()
-                .danger_accept_invalid_certs(true) // 為了測試目的
-                .timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap(),
-        }
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 10))
+                .build()?.into(),
+        })
     }
 
-    pub async fn scan_headers(&self, task_id: &str, url: &str) -> ScannerResult<Vec<SecurityHeader>> {
+    pub async fn scan_headers(&self, task_id: &str, url: &str) -> ScannerResult<HeaderScan> {
         let response = self.client.get(url).send().await?;
-        let headers = response.headers();
+        let final_url = response.url().clone();
+        let headers = &response.headers().clone();
+        // 頁面內容只用來找 <meta> 宣告的 CSP，讀取失敗時只分析標頭
+        let body = response.text().await.unwrap_or_default();
 
         let mut results = Vec::new();
 
         // 定義應該檢查的安全標頭
         let security_headers = self.get_security_headers_checklist();
 
-        for (header_name, (expected, recommendation)) in security_headers {
-            let header_value = headers.get(&header_name).map(|v| v.to_str().unwrap_or("").to_string());
-            let is_present = header_value.is_some();
+        for (header_name, (_expected, recommendation)) in security_headers {
+            // 重複出現的標頭保留所有值
+            let values = header_values(headers, &header_name);
+            let is_present = !values.is_empty();
+            let is_secure = is_present
+                && distinct_values(&values).len() == 1
+                && values.iter().all(|v| self.validate_header(&header_name, &Some(v.clone())));
+
+            // HSTS 依實際的問題給建議 (max-age 過短、缺少 includeSubDomains…)
+            let recommendation = match values.first() {
+                Some(value) if header_name == "strict-transport-security" && is_present => hsts::parse(value).recommendation(),
+                _ => recommendation,
+            };
 
             results.push(SecurityHeader {
                 id: Uuid::new_v4().to_string(),
                 task_id: task_id.to_string(),
                 header_name: header_name.clone(),
-                header_value: header_value.clone(),
+                header_value: join_header_values(&values),
                 is_present,
-                is_secure: is_present && self.validate_header(&header_name, &header_value),
+                is_secure,
                 recommendation: Some(recommendation),
                 created_at: Utc::now(),
+                header_values: values,
             });
         }
 
         // 檢查額外的不安全標頭
         results.extend(self.check_unsafe_headers(task_id, headers));
 
-        Ok(results)
+        let mut findings = self.check_conflicting_headers(task_id, headers);
+        findings.extend(self.check_hsts(task_id, headers));
+        findings.extend(self.check_csp(task_id, headers, &body));
+        findings.extend(self.audit_cookies(task_id, &final_url, headers).await);
+
+        Ok(HeaderScan { headers: results, findings })
+    }
+
+    /// 同一個安全標頭出現多次且值不同時，瀏覽器採用哪一個並無定義
+    fn check_conflicting_headers(&self, task_id: &str, headers: &HeaderMap) -> Vec<ScanResult> {
+        let mut findings = Vec::new();
+
+        for header_name in SINGLE_VALUE_SECURITY_HEADERS {
+            let values = header_values(headers, header_name);
+            let distinct = distinct_values(&values);
+            if distinct.len() < 2 {
+                continue;
+            }
+
+            findings.push(ScanResult {
+                id: Uuid::new_v4().to_string(),
+                task_id: task_id.to_string(),
+                result_type: ResultType::Header,
+                severity: Some(Severity::Low),
+                title: format!("{} 標頭重複且值互相衝突", header_name),
+                description: Some(format!(
+                    "回應包含 {} 個 {} 標頭 ({})，瀏覽器對衝突值的處理方式不一致，防護可能失效。請只保留一個值",
+                    values.len(),
+                    header_name,
+                    distinct.join(" / ")
+                )),
+                raw_data: Some(serde_json::to_string(&serde_json::json!({
+                    "header": header_name,
+                    "values": values
+                })).unwrap()),
+                created_at: Utc::now(),
+                low_confidence: false,
+                cwe_ids: Vec::new(),
+                details: None,
+            });
+        }
+
+        findings
+    }
+
+    /// HSTS 被 max-age=0 停用或格式無效 (瀏覽器忽略) 時產生發現；重複標頭由衝突檢查處理
+    fn check_hsts(&self, task_id: &str, headers: &HeaderMap) -> Vec<ScanResult> {
+        let values = header_values(headers, "strict-transport-security");
+        let [value] = values.as_slice() else {
+            return Vec::new();
+        };
+        let policy = hsts::parse(value);
+
+        let (check_id, severity, title) = if policy.disables() {
+            ("hsts_disabled", Severity::Medium, "HSTS 已被停用 (max-age=0)")
+        } else if !policy.is_valid() {
+            ("hsts_invalid", Severity::Low, "Strict-Transport-Security 標頭格式無效")
+        } else {
+            return Vec::new();
+        };
+
+        vec![ScanResult {
+            id: Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            result_type: ResultType::Header,
+            severity: Some(severity),
+            title: title.to_string(),
+            description: Some(policy.recommendation()),
+            raw_data: Some(serde_json::to_string(&serde_json::json!({
+                "check_id": check_id,
+                "header": "strict-transport-security",
+                "value": value,
+                "errors": policy.errors,
+            })).unwrap()),
+            created_at: Utc::now(),
+            low_confidence: false,
+            cwe_ids: Vec::new(),
+            details: None,
+        }]
+    }
+
+    /// 分析標頭與 <meta http-equiv> 宣告的 CSP，每個弱點各自成為一個發現；只有 report-only 政策時不會阻擋任何內容
+    fn check_csp(&self, task_id: &str, headers: &HeaderMap, body: &str) -> Vec<ScanResult> {
+        let policies: Vec<(&str, String)> = header_values(headers, "content-security-policy")
+            .into_iter()
+            .map(|value| ("header", value))
+            .chain(csp::meta_policies(body).into_iter().map(|value| ("meta", value)))
+            .collect();
+
+        let mut findings = Vec::new();
+        for (source, value) in &policies {
+            for weakness in csp::parse(value).weaknesses() {
+                let location = if *source == "meta" { "<meta http-equiv> 宣告的 CSP" } else { "Content-Security-Policy 標頭" };
+                let description = match &weakness.directive {
+                    Some(directive) => format!("{}中的指令 `{}` 有弱點: {}", location, directive, weakness.title),
+                    None => format!("{} (`{}`) 有弱點: {}", location, value, weakness.title),
+                };
+                findings.push(csp_finding(task_id, weakness.severity, weakness.title, description, serde_json::json!({
+                    "check_id": weakness.check_id,
+                    "source": source,
+                    "directive": weakness.directive,
+                    "policy": value,
+                })));
+            }
+        }
+
+        let report_only = header_values(headers, "content-security-policy-report-only");
+        if policies.is_empty() && !report_only.is_empty() {
+            findings.push(csp_finding(
+                task_id,
+                Severity::Medium,
+                "CSP 只以 report-only 模式部署".to_string(),
+                format!(
+                    "回應只有 Content-Security-Policy-Report-Only (`{}`)，瀏覽器只回報違規而不會阻擋任何內容。確認政策無誤後請改用 Content-Security-Policy 標頭",
+                    report_only.join(HEADER_VALUE_SEPARATOR)
+                ),
+                serde_json::json!({
+                    "check_id": "csp_report_only",
+                    "source": "header",
+                    "policy": report_only,
+                }),
+            ));
+        }
+
+        findings
+    }
+
+    /// 稽核主頁面與登入頁面的每個 Set-Cookie，同一個 cookie (名稱與屬性相同) 只回報一次
+    ///
+    /// 發現只包含 cookie 名稱與屬性，cookie 的值不會寫入描述或 raw_data
+    async fn audit_cookies(&self, task_id: &str, url: &reqwest::Url, headers: &HeaderMap) -> Vec<ScanResult> {
+        let mut responses = vec![(url.clone(), header_values(headers, "set-cookie"))];
+        for path in LOGIN_PATHS {
+            let Ok(login_url) = url.join(path) else { continue };
+            if let Ok(response) = self.client.get(login_url.as_str()).send().await {
+                responses.push((response.url().clone(), header_values(response.headers(), "set-cookie")));
+            }
+        }
+
+        let now = Utc::now();
+        let mut seen: Vec<(String, String)> = Vec::new();
+        let mut findings = Vec::new();
+        for (page, set_cookies) in responses {
+            let https = page.scheme() == "https";
+            let host = page.host_str().unwrap_or_default();
+            for cookie in set_cookies.iter().filter_map(|header| cookies::parse(header)) {
+                let key = (cookie.name.clone(), cookie.attributes.clone());
+                if seen.contains(&key) {
+                    continue;
+                }
+                seen.push(key);
+
+                for issue in cookie.audit(https, host, now) {
+                    let attributes = if cookie.attributes.is_empty() { "(無屬性)" } else { cookie.attributes.as_str() };
+                    findings.push(ScanResult {
+                        id: Uuid::new_v4().to_string(),
+                        task_id: task_id.to_string(),
+                        result_type: ResultType::Header,
+                        severity: Some(issue.severity),
+                        title: issue.title,
+                        description: Some(format!("{} 設定的 cookie {} 屬性為: {}。建議: {}", page, cookie.name, attributes, issue.remediation)),
+                        raw_data: Some(serde_json::to_string(&serde_json::json!({
+                            "check_id": issue.check_id,
+                            "cookie_name": cookie.name,
+                            "attributes": cookie.attributes,
+                            "url": page.as_str(),
+                        })).unwrap()),
+                        created_at: now,
+                        low_confidence: false,
+                        cwe_ids: Vec::new(),
+                        details: None,
+                    });
+                }
+            }
+        }
+
+        findings
     }
 
     fn get_security_headers_checklist(&self) -> HashMap<String, (bool, String)> {
@@ -95,8 +307,8 @@ impl HttpScanner {
     fn validate_header(&self, header_name: &str, header_value: &Option<String>) -> bool {
         if let Some(value) = header_value {
             match header_name {
-                "strict-transport-security" => value.contains("max-age=") && value.len() > 20,
-                "content-security-policy" => value.len() > 10,
+                "strict-transport-security" => hsts::parse(value).is_secure(),
+                "content-security-policy" => csp::parse(value).is_secure(),
                 "x-frame-options" => value.to_uppercase().contains("DENY") || value.to_uppercase().contains("SAMEORIGIN"),
                 "x-content-type-options" => value.to_lowercase().contains("nosniff"),
                 "referrer-policy" => !value.is_empty(),
@@ -113,32 +325,34 @@ impl HttpScanner {
         let mut results = Vec::new();
 
         // 檢查是否洩露服務器版本資訊
-        if let Some(server) = headers.get("server") {
-            let server_value = server.to_str().unwrap_or("");
+        let server_values = header_values(headers, "server");
+        if !server_values.is_empty() {
             results.push(SecurityHeader {
                 id: Uuid::new_v4().to_string(),
                 task_id: task_id.to_string(),
                 header_name: "server".to_string(),
-                header_value: Some(server_value.to_string()),
+                header_value: join_header_values(&server_values),
                 is_present: true,
                 is_secure: false,
                 recommendation: Some("建議隱藏或移除服務器版本資訊以減少攻擊面".to_string()),
                 created_at: Utc::now(),
+                header_values: server_values,
             });
         }
 
         // 檢查是否洩露 X-Powered-By
-        if let Some(powered_by) = headers.get("x-powered-by") {
-            let value = powered_by.to_str().unwrap_or("");
+        let powered_by_values = header_values(headers, "x-powered-by");
+        if !powered_by_values.is_empty() {
             results.push(SecurityHeader {
                 id: Uuid::new_v4().to_string(),
                 task_id: task_id.to_string(),
                 header_name: "x-powered-by".to_string(),
-                header_value: Some(value.to_string()),
+                header_value: join_header_values(&powered_by_values),
                 is_present: true,
                 is_secure: false,
                 recommendation: Some("建議移除 X-Powered-By 標頭以避免洩露技術堆疊資訊".to_string()),
                 created_at: Utc::now(),
+                header_values: powered_by_values,
             });
         }
 
@@ -154,7 +368,8 @@ impl HttpScanner {
 
         // 從標頭檢測
         if let Some(server) = headers.get("server") {
-            let server_str = server.to_str().unwrap_or("");
+            let server_value = header_value_lossy(server);
+            let server_str = server_value.as_str();
             if server_str.to_lowercase().contains("nginx") {
                 technologies.push(DetectedTechnology {
                     id: Uuid::new_v4().to_string(),
@@ -164,6 +379,7 @@ impl HttpScanner {
                     category: TechnologyCategory::Server,
                     confidence: 95,
                     created_at: Utc::now(),
+                    evidence: vec![header_evidence("server", server_str)],
                 });
             }
             if server_str.to_lowercase().contains("apache") {
@@ -175,12 +391,26 @@ impl HttpScanner {
                     category: TechnologyCategory::Server,
                     confidence: 95,
                     created_at: Utc::now(),
+                    evidence: vec![header_evidence("server", server_str)],
+                });
+            }
+            if server_str.to_lowercase().contains("microsoft-iis") {
+                technologies.push(DetectedTechnology {
+                    id: Uuid::new_v4().to_string(),
+                    task_id: task_id.to_string(),
+                    technology_name: "IIS".to_string(),
+                    technology_version: self.extract_version(server_str, "Microsoft-IIS"),
+                    category: TechnologyCategory::Server,
+                    confidence: 95,
+                    created_at: Utc::now(),
+                    evidence: vec![header_evidence("server", server_str)],
                 });
             }
         }
 
         if let Some(powered_by) = headers.get("x-powered-by") {
-            let powered_str = powered_by.to_str().unwrap_or("");
+            let powered_value = header_value_lossy(powered_by);
+            let powered_str = powered_value.as_str();
             if powered_str.to_lowercase().contains("php") {
                 technologies.push(DetectedTechnology {
                     id: Uuid::new_v4().to_string(),
@@ -190,17 +420,37 @@ impl HttpScanner {
                     category: TechnologyCategory::Language,
                     confidence: 95,
                     created_at: Utc::now(),
+                    evidence: vec![header_evidence("x-powered-by", powered_str)],
                 });
             }
         }
 
+        let aspnet_evidence: Vec<TechnologyEvidence> = ["x-powered-by", "x-aspnet-version"]
+            .iter()
+            .flat_map(|name| headers.get_all(*name).iter().map(move |v| (name, header_value_lossy(v))))
+            .filter(|(name, value)| **name == "x-aspnet-version" || value.to_lowercase().contains("asp.net"))
+            .map(|(name, value)| header_evidence(name, &value))
+            .collect();
+        if !aspnet_evidence.is_empty() {
+            technologies.push(DetectedTechnology {
+                id: Uuid::new_v4().to_string(),
+                task_id: task_id.to_string(),
+                technology_name: "ASP.NET".to_string(),
+                technology_version: headers.get("x-aspnet-version").map(header_value_lossy),
+                category: TechnologyCategory::Framework,
+                confidence: 95,
+                created_at: Utc::now(),
+                evidence: aspnet_evidence,
+            });
+        }
+
         // 從 HTML 內容檢測
         technologies.extend(self.detect_from_html(task_id, &body));
 
         Ok(technologies)
     }
 
-    fn detect_from_html(&self, task_id: &str, html: &str) -> Vec<DetectedTechnology> {
+    pub(crate) fn detect_from_html(&self, task_id: &str, html: &str) -> Vec<DetectedTechnology> {
         let mut technologies = Vec::new();
         let html_lower = html.to_lowercase();
 
@@ -214,6 +464,7 @@ impl HttpScanner {
                 category: TechnologyCategory::Framework,
                 confidence: 85,
                 created_at: Utc::now(),
+                evidence: html_evidence(html, &html_lower, &["__next", "next/script"]),
             });
         }
 
@@ -227,6 +478,7 @@ impl HttpScanner {
                 category: TechnologyCategory::Framework,
                 confidence: 80,
                 created_at: Utc::now(),
+                evidence: html_evidence(html, &html_lower, &["react", "_reactroot"]),
             });
         }
 
@@ -240,6 +492,7 @@ impl HttpScanner {
                 category: TechnologyCategory::Analytics,
                 confidence: 95,
                 created_at: Utc::now(),
+                evidence: html_evidence(html, &html_lower, &["google-analytics.com", "gtag"]),
             });
         }
 
@@ -253,6 +506,10 @@ impl HttpScanner {
                 category: TechnologyCategory::Framework,
                 confidence: 75,
                 created_at: Utc::now(),
+                evidence: vec![TechnologyEvidence {
+                    kind: EvidenceKind::HtmlPattern,
+                    matched: if html_lower.contains("tailwind") { "tailwind" } else { "tailwind utility classes" }.to_string(),
+                }],
             });
         }
 
@@ -271,3 +528,169 @@ impl HttpScanner {
             .map(|m| m.as_str().to_string())
     }
 }
+
+fn csp_finding(task_id: &str, severity: Severity, title: String, description: String, raw: serde_json::Value) -> ScanResult {
+    ScanResult {
+        id: Uuid::new_v4().to_string(),
+        task_id: task_id.to_string(),
+        result_type: ResultType::Header,
+        severity: Some(severity),
+        title,
+        description: Some(description),
+        raw_data: Some(serde_json::to_string(&raw).unwrap()),
+        created_at: Utc::now(),
+        low_confidence: false,
+        cwe_ids: Vec::new(),
+        details: None,
+    }
+}
+
+/// 將標頭值轉為可顯示的字串；非 UTF-8 的位元組以 Latin-1 解讀，不會變成空字串
+pub fn header_value_lossy(value: &HeaderValue) -> String {
+    let bytes = value.as_bytes();
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// 取得標頭的所有值 (依回應中的順序)
+pub fn header_values(headers: &HeaderMap, name: &str) -> Vec<String> {
+    headers.get_all(name).iter().map(header_value_lossy).collect()
+}
+
+fn join_header_values(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(HEADER_VALUE_SEPARATOR))
+    }
+}
+
+// 比較時忽略大小寫與前後空白，"DENY" 與 "deny " 不算衝突
+fn distinct_values(values: &[String]) -> Vec<String> {
+    let mut distinct: Vec<String> = Vec::new();
+    for value in values {
+        let normalized = value.trim().to_lowercase();
+        if !distinct.iter().any(|d| d.trim().to_lowercase() == normalized) {
+            distinct.push(value.trim().to_string());
+        }
+    }
+    distinct
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 回應一次固定內容的本機 HTTP 伺服器，可送出重複與非 UTF-8 的標頭
+    async fn serve_once(head: &'static [u8]) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await;
+            socket.write_all(head).await.unwrap();
+            socket.write_all(b"Content-Length: 0\r\nConnection: close\r\n\r\n").await.unwrap();
+        });
+        format!("http://{}/", addr)
+    }
+
+    fn header<'a>(scan: &'a HeaderScan, name: &str) -> &'a SecurityHeader {
+        scan.headers.iter().find(|h| h.header_name == name).unwrap()
+    }
+
+    fn conflicts(scan: &HeaderScan) -> Vec<&ScanResult> {
+        scan.findings.iter().filter(|f| f.title.contains("標頭重複")).collect()
+    }
+
+    #[tokio::test]
+    async fn keeps_every_value_of_repeated_and_latin1_headers() {
+        let url = serve_once(
+            b"HTTP/1.1 200 OK\r\n\
+              X-Frame-Options: DENY\r\n\
+              X-Frame-Options: SAMEORIGIN\r\n\
+              X-Content-Type-Options: nosniff\r\n\
+              X-Content-Type-Options: NOSNIFF\r\n\
+              Server: caf\xe9-server\r\n\
+              X-Powered-By: PHP/8.2\r\n\
+              X-Powered-By: Express\r\n",
+        )
+        .await;
+        let scan = HttpScanner::new(&ScanOptions::default()).unwrap().scan_headers("scan-1", &url).await.unwrap();
+
+        let frame_options = header(&scan, "x-frame-options");
+        assert_eq!(frame_options.header_values, vec!["DENY", "SAMEORIGIN"]);
+        assert_eq!(frame_options.header_value.as_deref(), Some("DENY | SAMEORIGIN"));
+        assert!(!frame_options.is_secure);
+
+        // 只差大小寫的重複值不算衝突
+        let content_type_options = header(&scan, "x-content-type-options");
+        assert_eq!(content_type_options.header_values.len(), 2);
+        assert!(content_type_options.is_secure);
+
+        assert_eq!(header(&scan, "server").header_value.as_deref(), Some("café-server"));
+        assert_eq!(header(&scan, "x-powered-by").header_values, vec!["PHP/8.2", "Express"]);
+
+        let conflicts = conflicts(&scan);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].title.starts_with("x-frame-options"));
+        assert_eq!(conflicts[0].severity, Some(Severity::Low));
+    }
+
+    #[tokio::test]
+    async fn single_headers_are_not_flagged() {
+        let url = serve_once(b"HTTP/1.1 200 OK\r\nX-Frame-Options: DENY\r\nX-Content-Type-Options: nosniff\r\n").await;
+        let scan = HttpScanner::new(&ScanOptions::default()).unwrap().scan_headers("scan-1", &url).await.unwrap();
+
+        assert_eq!(header(&scan, "x-frame-options").header_values, vec!["DENY"]);
+        assert!(header(&scan, "x-frame-options").is_secure);
+        assert!(conflicts(&scan).is_empty());
+    }
+
+    #[test]
+    fn decodes_non_utf8_values_as_latin1() {
+        assert_eq!(header_value_lossy(&HeaderValue::from_bytes(b"caf\xe9").unwrap()), "café");
+        assert_eq!(header_value_lossy(&HeaderValue::from_str("plain").unwrap()), "plain");
+        assert_eq!(distinct_values(&["DENY".to_string(), " deny".to_string(), "SAMEORIGIN".to_string()]), vec!["DENY", "SAMEORIGIN"]);
+    }
+
+    #[test]
+    fn hsts_is_secure_only_with_a_long_enough_max_age() {
+        let scanner = HttpScanner::new(&ScanOptions::default()).unwrap();
+        let hsts = |value: &str| scanner.validate_header("strict-transport-security", &Some(value.to_string()));
+
+        // 舊的檢查只看長度與 max-age= 字串
+        assert!(!hsts("max-age=60; includeSubDomains"));
+        assert!(!hsts("max-age=abc; includeSubDomains; preload"));
+        assert!(!hsts("max-age=31536000; max-age=31536000"));
+        assert!(hsts("max-age=15552000"));
+        assert!(hsts("max-age=31536000; includeSubDomains; preload"));
+    }
+
+    #[test]
+    fn disabled_and_invalid_hsts_are_findings() {
+        let scanner = HttpScanner::new(&ScanOptions::default()).unwrap();
+        let findings = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("strict-transport-security", HeaderValue::from_static(value));
+            scanner.check_hsts("task-1", &headers)
+        };
+
+        let disabled = findings("max-age=0");
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].severity, Some(Severity::Medium));
+        assert!(disabled[0].raw_data.as_deref().unwrap().contains("hsts_disabled"));
+
+        let invalid = findings("max-age=abc");
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].severity, Some(Severity::Low));
+        assert!(invalid[0].raw_data.as_deref().unwrap().contains("hsts_invalid"));
+
+        // 過短的 max-age 只影響標頭評分與建議，不另外產生發現
+        assert!(findings("max-age=60").is_empty());
+        assert!(scanner.check_hsts("task-1", &HeaderMap::new()).is_empty());
+    }
+}
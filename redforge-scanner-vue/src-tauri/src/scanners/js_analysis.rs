@@ -0,0 +1,94 @@
+/**
+ * JavaScript File Analysis
+ *
+ * 前端打包後的 JS 檔案常含有 API 金鑰、內部位址與頁面上看不到的 API 路徑。基礎頁面的 `<script src>`
+ * 中與目標同源、或與目標同一網域的子網域 (例如 static.example.com、cdn.example.com) 的檔案會被下載，
+ * 以密鑰樣式比對內容，並擷取相對 API 路徑 ("/api/v1/...") 交給爬取與注入檢查。
+ *
+ * 發現中的密鑰只保留前 4 與後 4 個字元。
+ */
+
+use crate::scanners::crawler;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 最多下載的 JS 檔案數與單一檔案的大小上限
+pub const MAX_SCRIPTS: usize = 20;
+pub const MAX_SCRIPT_BYTES: usize = 2 * 1024 * 1024;
+/// 每個檔案最多擷取的 API 路徑數
+const MAX_ENDPOINTS_PER_SCRIPT: usize = 50;
+
+/// 下載的 JS 檔案
+#[derive(Debug, Clone)]
+pub struct ScriptFile {
+    pub url: String,
+    pub body: String,
+}
+
+/// 頁面中要分析的外部腳本 URL：與頁面同源或同一網域的子網域，依出現順序且不重複
+pub fn script_urls(page_url: &str, html: &str) -> Vec<String> {
+    static SCRIPT_TAG: OnceLock<Regex> = OnceLock::new();
+    let script_tag = SCRIPT_TAG.get_or_init(|| Regex::new(r"(?is)<script\b([^>]*)>").unwrap());
+    let Ok(base) = reqwest::Url::parse(page_url) else {
+        return Vec::new();
+    };
+
+    let mut urls: Vec<String> = Vec::new();
+    for caps in script_tag.captures_iter(html) {
+        let Some(src) = crawler::attr(&caps[1], "src") else { continue };
+        let Ok(mut url) = base.join(src.trim()) else { continue };
+        url.set_fragment(None);
+        if !matches!(url.scheme(), "http" | "https") || !(crawler::same_origin(&url, &base) || same_site(&url, &base)) {
+            continue;
+        }
+        let url = url.to_string();
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// 兩個主機是否屬於同一網域 (最後兩個標籤相同)；IP 位址不算 (只有同源時才分析)
+fn same_site(url: &reqwest::Url, base: &reqwest::Url) -> bool {
+    let site = |url: &reqwest::Url| {
+        let host = url.host_str()?.trim_end_matches('.').to_lowercase();
+        if host.starts_with('[') || host.parse::<std::net::IpAddr>().is_ok() {
+            return None;
+        }
+        let labels: Vec<&str> = host.rsplitn(3, '.').take(2).collect();
+        (labels.len() == 2).then(|| format!("{}.{}", labels[1], labels[0]))
+    };
+    site(url).is_some_and(|site_url| site(base).is_some_and(|site_base| site_url == site_base))
+}
+
+/// JS 內容中的相對 API 路徑 (例如 "/api/v1/users"、"/v2/orders?id=1")，依出現順序且不重複
+pub fn endpoints(script: &str) -> Vec<String> {
+    static ENDPOINT: OnceLock<Regex> = OnceLock::new();
+    let endpoint = ENDPOINT.get_or_init(|| {
+        Regex::new(r#"["'`](/(?:api|rest|graphql|v\d+)(?:/[A-Za-z0-9_\-.]*)*(?:\?[A-Za-z0-9_\-=&]*)?)["'`]"#).unwrap()
+    });
+
+    let mut found: Vec<String> = Vec::new();
+    for caps in endpoint.captures_iter(script) {
+        let path = caps[1].to_string();
+        if !found.contains(&path) {
+            found.push(path);
+        }
+        if found.len() >= MAX_ENDPOINTS_PER_SCRIPT {
+            break;
+        }
+    }
+    found
+}
+
+/// 只保留前 4 與後 4 個字元；8 個字元以內的值全部遮蔽
+pub fn redact(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
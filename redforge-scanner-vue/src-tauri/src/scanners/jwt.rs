@@ -0,0 +1,299 @@
+/**
+ * JWT Analysis
+ *
+ * 目標在 cookie、回應標頭或頁面腳本 (localStorage / sessionStorage) 交給瀏覽器的 JWT 只要解碼就能檢查，
+ * 不需要驗證簽章：標頭的演算法、內容的效期與內容中不該出現的敏感資料。
+ * 啟用弱金鑰檢查時，以內建的常見金鑰清單在本機計算 HMAC 比對 HS256 / HS384 / HS512 的簽章，不送出請求。
+ *
+ * 簽章只用於本機比對，不寫入紀錄、發現或匯出：顯示的 token 以 "<signature>" 取代簽章，
+ * 證據記錄的回應內容與標頭也會先移除 JWT 的簽章 (`strip_signatures`)。
+ */
+
+use base64::Engine;
+use regex::Regex;
+use ring::hmac;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// 取代簽章的文字
+const SIGNATURE_PLACEHOLDER: &str = "<signature>";
+const REDACTED: &str = "<redacted>";
+/// 效期超過此秒數視為過長 (一年)
+const MAX_LIFETIME_SECS: i64 = 365 * 24 * 60 * 60;
+
+/// 常見的弱 HMAC 金鑰：框架範例、教學文件與預設值
+const WEAK_SECRETS: &[&str] = &[
+    "secret", "secretkey", "secret-key", "secret_key", "secret123", "mysecret", "my_secret", "supersecret",
+    "s3cr3t", "password", "changeme", "changeit", "123456", "12345678", "qwerty", "letmein", "admin", "test",
+    "key", "private", "default", "token", "auth", "jwt", "jwtsecret", "jwt_secret", "jwt-secret", "jwtkey",
+    "your-256-bit-secret", "your_jwt_secret", "your-secret-key", "shhhhh", "development", "example",
+];
+
+/// 內容中不該出現的欄位名稱 (小寫，部分比對)
+const SENSITIVE_CLAIMS: &[&str] = &[
+    "password", "passwd", "pwd", "secret", "ssn", "social_security", "credit_card", "creditcard", "card_number",
+    "cardnumber", "pan", "cvv", "cvc", "pin",
+];
+
+/// 解碼後的 JWT；簽章不會出現在 Debug 或任何輸出中
+pub struct Jwt {
+    /// 取得 token 的位置，例如 "Cookie session"、"Header authorization"、"localStorage"
+    pub source: String,
+    pub header: serde_json::Value,
+    pub payload: serde_json::Value,
+    signing_input: String,
+    signature: Vec<u8>,
+}
+
+impl std::fmt::Debug for Jwt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jwt")
+            .field("source", &self.source)
+            .field("header", &self.header)
+            .field("payload", &self.redacted_payload())
+            .finish_non_exhaustive()
+    }
+}
+
+/// JWT 的設定問題
+#[derive(Debug, Clone, PartialEq)]
+pub enum JwtIssue {
+    /// 未簽章的 token (alg: none)
+    AlgNone,
+    /// HMAC 金鑰在弱金鑰清單中
+    WeakKey { key: &'static str },
+    MissingExpiry,
+    /// 效期超過一年
+    LongExpiry { days: i64 },
+    /// 內容包含敏感欄位或完整的卡號
+    SensitiveClaims { claims: Vec<String> },
+}
+
+impl Jwt {
+    /// 解碼 token；標頭或內容不是 JSON 物件時回傳 None
+    pub fn parse(source: &str, token: &str) -> Option<Self> {
+        let mut parts = token.trim().split('.');
+        let (header, payload, signature) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        let decode = |part: &str| base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(part.trim_end_matches('='));
+        let header_json: serde_json::Value = serde_json::from_slice(&decode(header).ok()?).ok()?;
+        let payload_json: serde_json::Value = serde_json::from_slice(&decode(payload).ok()?).ok()?;
+        if !header_json.is_object() || !payload_json.is_object() {
+            return None;
+        }
+        Some(Self {
+            source: source.to_string(),
+            header: header_json,
+            payload: payload_json,
+            signing_input: format!("{}.{}", header, payload),
+            signature: decode(signature).ok()?,
+        })
+    }
+
+    pub fn alg(&self) -> String {
+        self.header.get("alg").and_then(|v| v.as_str()).unwrap_or_default().to_string()
+    }
+
+    /// 可以顯示的 token：簽章以 "<signature>" 取代
+    pub fn display(&self) -> String {
+        format!("{}.{}", self.signing_input, SIGNATURE_PLACEHOLDER)
+    }
+
+    /// 敏感欄位的值與完整卡號以 "<redacted>" 取代的內容
+    pub fn redacted_payload(&self) -> serde_json::Value {
+        let mut payload = self.payload.clone();
+        redact_claims(&mut payload);
+        payload
+    }
+
+    /// 檢查 token 的設定問題；crack_weak_keys 為 true 時以弱金鑰清單比對 HMAC 簽章
+    pub fn issues(&self, crack_weak_keys: bool, now: i64) -> Vec<JwtIssue> {
+        let mut issues = Vec::new();
+        if self.alg().eq_ignore_ascii_case("none") {
+            issues.push(JwtIssue::AlgNone);
+        } else if crack_weak_keys {
+            if let Some(key) = self.weak_key() {
+                issues.push(JwtIssue::WeakKey { key });
+            }
+        }
+
+        match self.payload.get("exp").and_then(|v| v.as_f64()) {
+            None => issues.push(JwtIssue::MissingExpiry),
+            Some(exp) => {
+                // 有簽發時間時以簽發時間計算效期，否則以現在時間計算
+                let issued = self.payload.get("iat").and_then(|v| v.as_f64()).map_or(now, |iat| iat as i64);
+                let lifetime = exp as i64 - issued.min(now);
+                if lifetime > MAX_LIFETIME_SECS {
+                    issues.push(JwtIssue::LongExpiry { days: lifetime / 86_400 });
+                }
+            }
+        }
+
+        let mut claims = Vec::new();
+        sensitive_claims(&self.payload, "", &mut claims);
+        if !claims.is_empty() {
+            issues.push(JwtIssue::SensitiveClaims { claims });
+        }
+        issues
+    }
+
+    /// 以弱金鑰清單比對 HMAC 簽章
+    fn weak_key(&self) -> Option<&'static str> {
+        let algorithm = match self.alg().as_str() {
+            "HS256" => hmac::HMAC_SHA256,
+            "HS384" => hmac::HMAC_SHA384,
+            "HS512" => hmac::HMAC_SHA512,
+            _ => return None,
+        };
+        WEAK_SECRETS.iter().copied().find(|secret| {
+            let key = hmac::Key::new(algorithm, secret.as_bytes());
+            hmac::verify(&key, self.signing_input.as_bytes(), &self.signature).is_ok()
+        })
+    }
+}
+
+fn token_pattern() -> &'static Regex {
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    // alg none 的 token 簽章為空
+    TOKEN.get_or_init(|| Regex::new(r"\beyJ[A-Za-z0-9_-]{4,}\.eyJ[A-Za-z0-9_-]{4,}\.([A-Za-z0-9_-]*)").unwrap())
+}
+
+/// 文字中形狀像 JWT 的值，依出現順序且不重複
+pub fn find(text: &str) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    for token in token_pattern().find_iter(text) {
+        let token = token.as_str().to_string();
+        if !found.contains(&token) {
+            found.push(token);
+        }
+    }
+    found
+}
+
+/// 移除文字中 JWT 的簽章 (證據記錄前使用)
+pub fn strip_signatures(text: &str) -> Cow<'_, str> {
+    if !text.contains("eyJ") {
+        return Cow::Borrowed(text);
+    }
+    token_pattern().replace_all(text, |caps: &regex::Captures| {
+        let token = &caps[0];
+        let signature = &caps[1];
+        if signature.is_empty() {
+            token.to_string()
+        } else {
+            format!("{}{}", &token[..token.len() - signature.len()], SIGNATURE_PLACEHOLDER)
+        }
+    })
+}
+
+/// 回應中的 JWT：Set-Cookie、其他回應標頭、寫入 localStorage / sessionStorage 的內嵌腳本與 JSON 回應
+pub fn collect(headers: &reqwest::header::HeaderMap, body: &str) -> Vec<Jwt> {
+    let mut tokens: Vec<(String, String)> = Vec::new();
+    for (name, value) in headers {
+        let Ok(value) = value.to_str() else { continue };
+        let source = if name == reqwest::header::SET_COOKIE {
+            format!("Cookie {}", value.split('=').next().unwrap_or_default().trim())
+        } else {
+            format!("Header {}", name.as_str())
+        };
+        tokens.extend(find(value).into_iter().map(|token| (source.clone(), token)));
+    }
+
+    static SCRIPT: OnceLock<Regex> = OnceLock::new();
+    let script = SCRIPT.get_or_init(|| Regex::new(r"(?is)<script\b[^>]*>(.*?)</script>").unwrap());
+    for caps in script.captures_iter(body) {
+        let content = &caps[1];
+        for storage in ["localStorage", "sessionStorage"] {
+            if content.contains(storage) {
+                tokens.extend(find(content).into_iter().map(|token| (storage.to_string(), token)));
+            }
+        }
+    }
+    if body.trim_start().starts_with('{') {
+        tokens.extend(find(body).into_iter().map(|token| ("Response body".to_string(), token)));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    tokens
+        .into_iter()
+        .filter(|(_, token)| seen.insert(token.clone()))
+        .filter_map(|(source, token)| Jwt::parse(&source, &token))
+        .collect()
+}
+
+fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_CLAIMS.iter().any(|claim| match *claim {
+        // 短名稱只比對完整欄位，避免 "spin"、"pinned"、"company" 之類的誤判
+        "pan" | "pin" | "pwd" | "ssn" | "cvv" | "cvc" => name == *claim,
+        _ => name.contains(claim),
+    })
+}
+
+/// 欄位名稱敏感 (且有值)，或值為完整的卡號
+fn is_sensitive(name: &str, value: &serde_json::Value) -> bool {
+    let card = match value {
+        serde_json::Value::String(text) => is_card_number(text),
+        serde_json::Value::Number(number) => is_card_number(&number.to_string()),
+        _ => false,
+    };
+    card || (is_sensitive_name(name) && !value.is_null())
+}
+
+/// 13-19 位數且通過 Luhn 檢查的數字 (允許空白與連字號)
+fn is_card_number(value: &str) -> bool {
+    let digits: Option<Vec<u32>> = value.chars().filter(|c| !matches!(c, ' ' | '-')).map(|c| c.to_digit(10)).collect();
+    let Some(digits) = digits.filter(|digits| (13..=19).contains(&digits.len())) else {
+        return false;
+    };
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (1, doubled) if doubled > 9 => doubled - 9,
+            (1, doubled) => doubled,
+            _ => d,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+fn sensitive_claims(value: &serde_json::Value, path: &str, found: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (name, value) in object {
+                let path = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+                if is_sensitive(name, value) {
+                    found.push(path);
+                } else {
+                    sensitive_claims(value, &path, found);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                sensitive_claims(item, &format!("{}[{}]", path, index), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn redact_claims(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (name, value) in object.iter_mut() {
+                if is_sensitive(name, value) {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_claims(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_claims),
+        _ => {}
+    }
+}
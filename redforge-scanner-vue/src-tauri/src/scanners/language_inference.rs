@@ -0,0 +1,305 @@
+/**
+ * Backend Language Inference
+ *
+ * Server 標頭被隱藏時，從其他被動訊號推測後端語言：X-Runtime (Rails)、各框架的 session cookie 名稱、
+ * Express 的 ETag 格式、Python WSGI/ASGI 伺服器標頭，以及頁面連結中的副檔名 (.do、.jsp、.aspx…)。
+ *
+ * 每個訊號有各自的權重，多個訊號同時指向同一語言時以 1 - Π(1 - w) 合併信心度。
+ * 結果為 Language 分類的 DetectedTechnology，名稱與技術前置條件使用的名稱一致 (例如 PHP、ASP.NET)。
+ */
+
+use crate::models::*;
+use crate::scanners::tech_detector::header_evidence;
+use chrono::Utc;
+use regex::Regex;
+use std::sync::OnceLock;
+use reqwest::header::HeaderMap;
+use uuid::Uuid;
+
+/// 合併後的信心度上限；推測結果不會是 100%
+const MAX_CONFIDENCE: f64 = 0.95;
+
+/// 單一訊號：指向的語言、權重 (0-100) 與判定依據
+struct Signal {
+    language: &'static str,
+    weight: u8,
+    evidence: TechnologyEvidence,
+}
+
+/// session cookie 名稱 (不分大小寫) 與對應的語言
+const SESSION_COOKIES: &[(&str, &str, u8)] = &[
+    ("phpsessid", "PHP", 70),
+    ("jsessionid", "Java", 70),
+    ("asp.net_sessionid", "ASP.NET", 75),
+    (".aspxauth", "ASP.NET", 60),
+    (".aspnetcore.session", "ASP.NET", 75),
+    ("connect.sid", "Node.js", 70),
+    ("laravel_session", "PHP", 70),
+    ("ci_session", "PHP", 60),
+    ("cfid", "ColdFusion", 60),
+    ("sessionid", "Python", 25),
+    ("csrftoken", "Python", 35),
+];
+
+/// URL 副檔名與對應的語言
+const URL_SUFFIXES: &[(&str, &str, u8)] = &[
+    (".php", "PHP", 50),
+    (".jsp", "Java", 55),
+    (".jspx", "Java", 55),
+    (".do", "Java", 45),
+    (".action", "Java", 45),
+    (".jsf", "Java", 50),
+    (".aspx", "ASP.NET", 55),
+    (".ashx", "ASP.NET", 50),
+    (".asmx", "ASP.NET", 50),
+    (".asp", "ASP.NET", 40),
+    (".cfm", "ColdFusion", 55),
+    (".py", "Python", 40),
+    (".rb", "Ruby", 40),
+];
+
+/// Server 標頭中的 Python 伺服器
+const PYTHON_SERVERS: &[&str] = &["wsgiserver", "gunicorn", "uvicorn", "werkzeug", "tornadoserver", "cherrypy", "waitress", "hypercorn", "daphne"];
+
+/// 由回應標頭與頁面中的 URL 推測後端語言
+pub fn infer_languages(task_id: &str, headers: &HeaderMap, urls: &[String]) -> Vec<DetectedTechnology> {
+    let mut signals = header_signals(headers);
+    signals.extend(cookie_signals(headers));
+    signals.extend(url_signals(urls));
+
+    let mut languages: Vec<(&'static str, Vec<Signal>)> = Vec::new();
+    for signal in signals {
+        match languages.iter_mut().find(|(language, _)| *language == signal.language) {
+            Some((_, group)) => group.push(signal),
+            None => languages.push((signal.language, vec![signal])),
+        }
+    }
+
+    languages
+        .into_iter()
+        .map(|(language, group)| DetectedTechnology {
+            id: Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            technology_name: language.to_string(),
+            technology_version: None,
+            category: TechnologyCategory::Language,
+            confidence: combined_confidence(&group),
+            created_at: Utc::now(),
+            evidence: group.into_iter().map(|s| s.evidence).collect(),
+        })
+        .collect()
+}
+
+/// 1 - Π(1 - w)：各訊號獨立時至少一個正確的機率
+fn combined_confidence(signals: &[Signal]) -> u8 {
+    let miss: f64 = signals.iter().map(|s| 1.0 - f64::from(s.weight) / 100.0).product();
+    ((1.0 - miss).min(MAX_CONFIDENCE) * 100.0).round() as u8
+}
+
+fn header_signals(headers: &HeaderMap) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    let value_of = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+
+    if let Some(runtime) = value_of("x-runtime") {
+        // Rails 的 X-Runtime 為秒數 (例如 0.012345)
+        if runtime.trim().parse::<f64>().is_ok() {
+            signals.push(Signal { language: "Ruby", weight: 60, evidence: header_evidence("x-runtime", &runtime) });
+        }
+    }
+
+    for name in ["x-aspnet-version", "x-aspnetmvc-version"] {
+        if let Some(value) = value_of(name) {
+            signals.push(Signal { language: "ASP.NET", weight: 80, evidence: header_evidence(name, &value) });
+        }
+    }
+
+    if let Some(powered_by) = value_of("x-powered-by") {
+        let lower = powered_by.to_lowercase();
+        let language = if lower.contains("express") {
+            Some("Node.js")
+        } else if lower.contains("php") {
+            Some("PHP")
+        } else if lower.contains("servlet") || lower.contains("jsp") {
+            Some("Java")
+        } else if lower.contains("phusion passenger") {
+            Some("Ruby")
+        } else {
+            None
+        };
+        if let Some(language) = language {
+            signals.push(Signal { language, weight: 80, evidence: header_evidence("x-powered-by", &powered_by) });
+        }
+    }
+
+    if let Some(server) = value_of("server") {
+        let lower = server.to_lowercase();
+        if PYTHON_SERVERS.iter().any(|s| lower.contains(s)) {
+            signals.push(Signal { language: "Python", weight: 70, evidence: header_evidence("server", &server) });
+        }
+    }
+
+    // Express (etag 套件) 的弱 ETag：W/"<長度 hex>-<base64 雜湊 27 字元>"
+    if let Some(etag) = value_of("etag") {
+        static EXPRESS_ETAG: OnceLock<Regex> = OnceLock::new();
+        let express_etag = EXPRESS_ETAG.get_or_init(|| Regex::new(r#"^W/"[0-9a-f]+-[A-Za-z0-9+/_-]{27}"$"#).unwrap());
+        if express_etag.is_match(etag.trim()) {
+            signals.push(Signal { language: "Node.js", weight: 40, evidence: header_evidence("etag", &etag) });
+        }
+    }
+
+    signals
+}
+
+fn cookie_signals(headers: &HeaderMap) -> Vec<Signal> {
+    static RAILS_SESSION: OnceLock<Regex> = OnceLock::new();
+    let rails_session = RAILS_SESSION.get_or_init(|| Regex::new(r"^_[a-z0-9_]+_session$").unwrap());
+    let mut signals = Vec::new();
+
+    for value in headers.get_all("set-cookie").iter().filter_map(|v| v.to_str().ok()) {
+        let name = value.split(['=', ';']).next().unwrap_or_default().trim();
+        let lower = name.to_lowercase();
+
+        let matched = SESSION_COOKIES
+            .iter()
+            .find(|(cookie, _, _)| *cookie == lower)
+            .map(|(_, language, weight)| (*language, *weight))
+            .or_else(|| rails_session.is_match(&lower).then_some(("Ruby", 45)));
+
+        if let Some((language, weight)) = matched {
+            signals.push(Signal {
+                language,
+                weight,
+                evidence: TechnologyEvidence { kind: EvidenceKind::Cookie, matched: name.to_string() },
+            });
+        }
+    }
+
+    signals
+}
+
+fn url_signals(urls: &[String]) -> Vec<Signal> {
+    let mut signals: Vec<Signal> = Vec::new();
+    let mut seen_suffixes: Vec<&str> = Vec::new();
+
+    for url in urls {
+        let path = url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let Some((suffix, language, weight)) = URL_SUFFIXES.iter().find(|(suffix, _, _)| path.ends_with(suffix)) else {
+            continue;
+        };
+        // 同一副檔名只計一次，避免大量連結灌高信心度
+        if seen_suffixes.contains(suffix) {
+            continue;
+        }
+        seen_suffixes.push(suffix);
+        signals.push(Signal {
+            language,
+            weight: *weight,
+            evidence: TechnologyEvidence { kind: EvidenceKind::UrlPattern, matched: url.clone() },
+        });
+    }
+
+    signals
+}
+
+/// 頁面中指向同一主機的連結、表單與腳本網址；外部網站的副檔名與目標無關
+pub fn page_urls(html: &str, page_url: &str) -> Vec<String> {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r#"(?i)(?:href|src|action)\s*=\s*["']([^"'#]+)["']"#).unwrap());
+    let base = reqwest::Url::parse(page_url).ok();
+    let mut urls: Vec<String> = link
+        .captures_iter(html)
+        .filter_map(|caps| caps.get(1).map(|m| m.as_str()))
+        .filter_map(|href| {
+            let base = base.as_ref()?;
+            let resolved = base.join(href).ok()?;
+            (resolved.host_str() == base.host_str()).then(|| resolved.to_string())
+        })
+        .collect();
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(reqwest::header::HeaderName::from_bytes(name.as_bytes()).unwrap(), value.parse().unwrap());
+        }
+        headers
+    }
+
+    fn inferred(pairs: &[(&str, &str)], urls: &[&str]) -> Vec<(String, u8)> {
+        let urls: Vec<String> = urls.iter().map(|u| u.to_string()).collect();
+        infer_languages("scan-1", &headers(pairs), &urls)
+            .into_iter()
+            .map(|t| (t.technology_name, t.confidence))
+            .collect()
+    }
+
+    #[test]
+    fn infers_each_language_from_synthetic_signals() {
+        let cases: Vec<(&[(&str, &str)], &[&str], Vec<(&str, u8)>)> = vec![
+            // Ruby：X-Runtime 秒數與 Rails session cookie
+            (&[("x-runtime", "0.012345")], &[], vec![("Ruby", 60)]),
+            (&[("x-runtime", "0.012345"), ("set-cookie", "_shop_session=abc; path=/; HttpOnly")], &[], vec![("Ruby", 78)]),
+            (&[("x-runtime", "fast")], &[], vec![]),
+            // PHP
+            (&[("set-cookie", "PHPSESSID=abc123; path=/")], &[], vec![("PHP", 70)]),
+            (&[("x-powered-by", "PHP/8.2.1")], &[], vec![("PHP", 80)]),
+            // Java
+            (&[("set-cookie", "JSESSIONID=ABC; Path=/app")], &[], vec![("Java", 70)]),
+            (&[], &["https://example.com/login.do", "https://example.com/a.do?x=1", "https://example.com/b.jsp"], vec![("Java", 75)]),
+            // ASP.NET：兩個訊號合併後受上限限制
+            (&[("set-cookie", "ASP.NET_SessionId=xyz; path=/"), ("x-aspnet-version", "4.0.30319")], &[], vec![("ASP.NET", 95)]),
+            (&[], &["https://example.com/Default.aspx"], vec![("ASP.NET", 55)]),
+            // Node.js：connect.sid 與 Express 的弱 ETag
+            (&[("set-cookie", "connect.sid=s%3Aabc; Path=/"), ("etag", "W/\"5f-3IOIDq2mbg8JqxZbUxDy3PwYAuQ\"")], &[], vec![("Node.js", 82)]),
+            (&[("etag", "\"5f-3IOIDq2mbg8JqxZbUxDy3PwYAuQ\"")], &[], vec![]),
+            // Python：WSGI 伺服器標頭與 Django cookie
+            (&[("server", "gunicorn/21.2.0")], &[], vec![("Python", 70)]),
+            (&[("set-cookie", "csrftoken=abc; Path=/"), ("set-cookie", "sessionid=xyz; HttpOnly")], &[], vec![("Python", 51)]),
+            // ColdFusion
+            (&[("set-cookie", "CFID=123; path=/")], &[], vec![("ColdFusion", 60)]),
+            // 沒有任何訊號
+            (&[("server", "nginx"), ("set-cookie", "theme=dark")], &["https://example.com/index.html"], vec![]),
+        ];
+
+        for (pairs, urls, expected) in cases {
+            let expected: Vec<(String, u8)> = expected.into_iter().map(|(name, confidence)| (name.to_string(), confidence)).collect();
+            assert_eq!(inferred(pairs, urls), expected, "{:?} {:?}", pairs, urls);
+        }
+    }
+
+    #[test]
+    fn conflicting_signals_produce_one_entry_per_language() {
+        let technologies = infer_languages(
+            "scan-1",
+            &headers(&[("set-cookie", "PHPSESSID=1"), ("set-cookie", "JSESSIONID=2")]),
+            &["https://example.com/index.php".to_string()],
+        );
+        let names: Vec<&str> = technologies.iter().map(|t| t.technology_name.as_str()).collect();
+        assert_eq!(names, vec!["PHP", "Java"]);
+        assert!(technologies.iter().all(|t| matches!(t.category, TechnologyCategory::Language)));
+        // PHP：cookie 與副檔名兩個依據
+        assert_eq!(technologies[0].evidence.len(), 2);
+        assert_eq!(technologies[0].confidence, 85);
+    }
+
+    #[test]
+    fn collects_same_host_page_urls() {
+        let html = r#"<a href="/login.do">login</a><form action="search.jsp"></form>
+            <script src="https://cdn.example.net/lib.php"></script><a href="/login.do">again</a>"#;
+        assert_eq!(
+            page_urls(html, "https://example.com/app/"),
+            vec!["https://example.com/app/search.jsp", "https://example.com/login.do"]
+        );
+    }
+}
@@ -3,21 +3,54 @@ pub mod ssl_scanner;
 pub mod tech_detector;
 pub mod vulnerability_scanner;
 pub mod owasp_scanner;
+pub mod tls_probe;
+pub mod client;
+pub mod control;
+pub mod target;
+pub mod sensitive_files;
+pub mod preconditions;
+pub mod raw_http;
+pub mod proxy_disagreement;
+pub mod language_inference;
+pub mod waf;
+pub mod warmup;
+pub mod consent;
+pub mod crawl_policy;
+pub mod time_box;
+pub mod redirect;
+pub mod response_diff;
+pub mod content_hash;
+pub mod path_traversal;
+pub mod analysis;
+pub mod page_identity;
+pub mod upload;
+pub mod check_metrics;
+pub mod finding_caps;
+pub mod port_scanner;
+pub mod soft_404;
+pub mod certificate;
+pub mod hsts;
+pub mod csp;
+pub mod cookies;
+pub mod cors;
+pub mod http_methods;
+pub mod crawler;
+pub mod blind_sqli;
+pub mod xss_context;
+pub mod rate_limit;
+pub mod evidence;
+pub mod auth;
+pub mod oob;
+pub mod backup_files;
+pub mod js_analysis;
+pub mod jwt;
+pub mod api_docs;
+pub mod sri;
+pub mod host_header;
+pub mod nosql;
+pub mod rate_limit_probe;
+pub mod request_budget;
+pub mod error;
+pub mod proxy;
 
-use crate::models::*;
-use std::error::Error;
-
-#[derive(Debug)]
-pub struct ScannerError {
-    pub message: String,
-}
-
-impl std::fmt::Display for ScannerError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Scanner Error: {}", self.message)
-    }
-}
-
-impl Error for ScannerError {}
-
-pub type ScannerResult<T> = Result<T, Box<dyn Error>>;
+pub use error::{ScannerError, ScannerResult};
@@ -0,0 +1,117 @@
+/**
+ * NoSQL Operator Injection
+ *
+ * MongoDB 類的資料庫接受查詢運算子：Express / PHP 把 `param[$ne]=1` 解析成物件 `{ "$ne": "1" }`，
+ * JSON API 則直接收到 `{"$ne": null}`，直接放進查詢時條件會變成「不等於」而繞過比對 (例如登入)。
+ *
+ * 沒有錯誤訊息可比對，只能比較回應：以乾淨請求為基準，運算子 payload 的回應明顯不同 (狀態碼改變、
+ * 內容相似度大幅下降或出現登入後的字樣) 才算可疑。對任何格式錯誤都回應 500 或錯誤頁的端點也會不同，
+ * 因此另外送出結構相同但沒有運算子的控制請求 (`param[rfg]=1`、`{"rfg": null}`)：
+ * 運算子的回應必須同時與控制請求不同才回報。
+ */
+
+use crate::scanners::response_diff;
+
+/// 與基準的相似度低於此值視為明顯不同
+const SIMILARITY_THRESHOLD: f64 = 0.8;
+/// 出現在 payload 回應、但不在基準回應中時視為登入繞過的字樣
+const AUTH_INDICATORS: &[&str] = &["welcome", "dashboard", "logout", "sign out", "my account"];
+/// 發現中保留的回應開頭長度
+const EXCERPT_CHARS: usize = 200;
+
+/// GET 參數的運算子 payload：(顯示用的 payload, 查詢字串片段)
+pub const QUERY_OPERATORS: &[(&str, &str)] = &[("[$ne]=1", "%5B%24ne%5D=1"), ("[$gt]=", "%5B%24gt%5D=")];
+/// GET 參數的控制請求：同樣是物件，但沒有運算子
+const QUERY_CONTROL: &str = "%5Brfg%5D=1";
+
+/// JSON 內容的運算子與控制值
+pub fn json_operator() -> serde_json::Value {
+    serde_json::json!({ "$ne": null })
+}
+
+pub fn json_control() -> serde_json::Value {
+    serde_json::json!({ "rfg": null })
+}
+
+/// endpoint 中的 param 換成 `param<suffix>` (suffix 已編碼)，其餘參數不變
+pub fn with_operator(endpoint: &str, param: &str, suffix: &str) -> String {
+    let (path, query) = endpoint.split_once('?').unwrap_or((endpoint, ""));
+    let mut pairs: Vec<String> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split('=').next().unwrap_or_default();
+            urlencoding::decode(key).map(|k| k != param).unwrap_or(true)
+        })
+        .map(str::to_string)
+        .collect();
+    pairs.push(format!("{}{}", urlencoding::encode(param), suffix));
+    format!("{}?{}", path, pairs.join("&"))
+}
+
+/// 控制請求的 URL
+pub fn control_url(endpoint: &str, param: &str) -> String {
+    with_operator(endpoint, param, QUERY_CONTROL)
+}
+
+/// 比較用的回應摘要
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub status: u16,
+    pub bytes: usize,
+    body: String,
+    normalized: String,
+}
+
+impl Snapshot {
+    pub fn new(status: u16, body: String) -> Self {
+        let normalized = response_diff::normalize(&body);
+        Self { status, bytes: body.len(), body, normalized }
+    }
+
+    pub fn similarity(&self, other: &Snapshot) -> f64 {
+        response_diff::calculate_similarity(&self.normalized, &other.normalized)
+    }
+
+    /// 發現中的證據：狀態碼、大小與內容開頭
+    pub fn summary(&self) -> serde_json::Value {
+        serde_json::json!({
+            "status": self.status,
+            "bytes": self.bytes,
+            "excerpt": self.body.chars().take(EXCERPT_CHARS).collect::<String>()
+        })
+    }
+
+    fn indicators(&self) -> Vec<&'static str> {
+        let lower = self.body.to_lowercase();
+        AUTH_INDICATORS.iter().copied().filter(|indicator| lower.contains(indicator)).collect()
+    }
+}
+
+/// 運算子 payload 的回應與基準明顯不同、且不是格式錯誤造成時，回傳判斷依據
+pub fn verdict(baseline: &Snapshot, mutated: &Snapshot, control: &Snapshot) -> Option<String> {
+    // 伺服器錯誤多半只是無法處理物件參數
+    if mutated.status >= 500 {
+        return None;
+    }
+    let reason = differs(baseline, mutated)?;
+    // 控制請求得到相同的回應：差異來自格式錯誤的輸入，不是運算子
+    differs(control, mutated)?;
+    Some(reason)
+}
+
+fn differs(reference: &Snapshot, mutated: &Snapshot) -> Option<String> {
+    if reference.status / 100 != mutated.status / 100 {
+        return Some(format!("狀態碼由 {} 變為 {}", reference.status, mutated.status));
+    }
+    let new_indicators: Vec<&str> = mutated
+        .indicators()
+        .into_iter()
+        .filter(|indicator| !reference.indicators().contains(indicator))
+        .collect();
+    if !new_indicators.is_empty() {
+        return Some(format!("回應出現登入後的字樣: {}", new_indicators.join(", ")));
+    }
+    let similarity = reference.similarity(mutated);
+    (similarity < SIMILARITY_THRESHOLD).then(|| format!("內容相似度降為 {:.0}%", similarity * 100.0))
+}
@@ -0,0 +1,131 @@
+/**
+ * Out-of-band Callbacks
+ *
+ * 盲 SSRF 的回應不會包含內部內容，只能從目標伺服器是否連線到外部位址判斷。每個參數注入帶有獨特
+ * canary 子網域的回呼網址，探測結束後輪詢回呼伺服器的 API，回應中出現的 canary 即為命中。
+ *
+ * 輪詢 API 的格式不限：JSON 陣列或含 data / interactions 陣列的物件時，取出提到 canary 的互動紀錄；
+ * 其他格式只記錄包含 canary 的文字行。DNS 名稱不分大小寫，比對時一律轉為小寫。
+ */
+
+use crate::models::CollaboratorSettings;
+use crate::scanners::{control, ScannerError, ScannerResult};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 等待結束後的輪詢次數與間隔，全部命中時提前結束
+const POLL_ATTEMPTS: usize = 3;
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+const POLL_TIMEOUT: Duration = Duration::from_secs(10);
+/// 每個 canary 保留的互動紀錄上限
+const MAX_INTERACTIONS: usize = 5;
+
+pub struct Collaborator {
+    base: reqwest::Url,
+    poll_url: String,
+    poll_token: Option<String>,
+    wait: Duration,
+    // 輪詢不屬於對目標的流量，不經過 ScanClient 的節流與登入標頭
+    client: reqwest::Client,
+}
+
+impl Collaborator {
+    pub fn new(settings: &CollaboratorSettings) -> ScannerResult<Self> {
+        settings.validate().map_err(ScannerError::Config)?;
+        let base = settings
+            .callback_base()
+            .ok_or_else(|| ScannerError::Config("OOB 回呼網址無效".to_string()))?;
+        Ok(Self {
+            base,
+            poll_url: settings.poll_url.clone(),
+            poll_token: settings.poll_token.clone(),
+            wait: Duration::from_secs(settings.wait_secs),
+            client: reqwest::Client::builder().timeout(POLL_TIMEOUT).build()?,
+        })
+    }
+
+    /// 帶有 canary 子網域的回呼網址，例如 http://rfg-1a2b3c-7.collab.example/
+    pub fn payload(&self, canary: &str) -> String {
+        let host = self.base.host_str().unwrap_or_default();
+        let port = self.base.port().map(|port| format!(":{}", port)).unwrap_or_default();
+        format!("{}://{}.{}{}/", self.base.scheme(), canary.to_lowercase(), host, port)
+    }
+
+    /// 等待回呼後輪詢，回傳各 canary 的互動紀錄 (沒有命中的 canary 不在結果中)
+    ///
+    /// 掃描被終止時放棄等待並回傳已收集的結果
+    pub async fn poll(&self, canaries: &[String]) -> ScannerResult<HashMap<String, Vec<serde_json::Value>>> {
+        let mut hits: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
+        if canaries.is_empty() {
+            return Ok(hits);
+        }
+        if control::interruptible(tokio::time::sleep(self.wait)).await.is_err() {
+            return Ok(hits);
+        }
+
+        let mut last_error = None;
+        for attempt in 0..POLL_ATTEMPTS {
+            if attempt > 0 && control::interruptible(tokio::time::sleep(POLL_INTERVAL)).await.is_err() {
+                break;
+            }
+            match self.fetch().await {
+                Ok(body) => {
+                    last_error = None;
+                    for canary in canaries {
+                        let found = interactions(&body, canary);
+                        if !found.is_empty() {
+                            hits.insert(canary.clone(), found);
+                        }
+                    }
+                }
+                Err(e) => last_error = Some(e),
+            }
+            if hits.len() == canaries.len() {
+                break;
+            }
+        }
+
+        match last_error {
+            Some(e) if hits.is_empty() => Err(ScannerError::from(e).context("無法輪詢 OOB 回呼伺服器")),
+            _ => Ok(hits),
+        }
+    }
+
+    async fn fetch(&self) -> Result<String, reqwest::Error> {
+        let mut request = self.client.get(&self.poll_url);
+        if let Some(token) = &self.poll_token {
+            request = request.bearer_auth(token);
+        }
+        request.send().await?.error_for_status()?.text().await
+    }
+}
+
+/// 輪詢回應中提到 canary 的互動紀錄
+fn interactions(body: &str, canary: &str) -> Vec<serde_json::Value> {
+    let canary = canary.to_lowercase();
+    if !body.to_lowercase().contains(&canary) {
+        return Vec::new();
+    }
+
+    let mentions = |value: &serde_json::Value| value.to_string().to_lowercase().contains(&canary);
+    let parsed: Option<serde_json::Value> = serde_json::from_str(body).ok();
+    let entries = parsed.as_ref().and_then(|value| match value {
+        serde_json::Value::Array(entries) => Some(entries),
+        serde_json::Value::Object(object) => ["data", "interactions"]
+            .iter()
+            .find_map(|key| object.get(*key).and_then(|v| v.as_array())),
+        _ => None,
+    });
+
+    let found: Vec<serde_json::Value> = entries
+        .map(|entries| entries.iter().filter(|entry| mentions(entry)).take(MAX_INTERACTIONS).cloned().collect())
+        .unwrap_or_default();
+    if !found.is_empty() {
+        return found;
+    }
+    body.lines()
+        .filter(|line| line.to_lowercase().contains(&canary))
+        .take(MAX_INTERACTIONS)
+        .map(|line| serde_json::Value::String(line.trim().to_string()))
+        .collect()
+}
@@ -17,64 +17,595 @@
  */
 
 use crate::models::*;
-use crate::scanners::ScannerResult;
-use reqwest::Client;
+use crate::scanners::control;
+use crate::scanners::sensitive_files::{self, FileValidation};
+use crate::scanners::client::{self, ScanClient, ScanRequest};
+use crate::scanners::preconditions::{path_precondition, TechContext};
+use crate::scanners::time_box;
+use crate::scanners::soft_404::{self, Soft404};
+use crate::scanners::crawl_policy::CrawlPolicy;
+use crate::scanners::crawler::{self, CrawlResult};
+use crate::scanners::api_docs::{self, ApiSpec};
+use crate::scanners::backup_files::{self, BackupKind};
+use crate::scanners::host_header::{self, ReflectionKind};
+use crate::scanners::sri::{self, ExternalResource};
+use crate::scanners::js_analysis::{self, ScriptFile};
+use crate::scanners::jwt::{self, Jwt, JwtIssue};
+use crate::scanners::nosql::{self, Snapshot};
+use crate::scanners::rate_limit_probe::{self, Sample, Target};
+use crate::scanners::{analysis, blind_sqli, cors, finding_caps, http_methods, oob, path_traversal, response_diff, xss_context};
+use crate::scanners::{ScannerError, ScannerResult};
 use uuid::Uuid;
 use chrono::Utc;
+use std::sync::Arc;
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::task::JoinSet;
+
+/// 完整 SSRF 掃描的候選參數
+const SSRF_CANDIDATE_PARAMS: &[&str] = &["url", "uri", "path", "dest", "redirect", "fetch", "file", "document"];
+/// 完全沒有參數證據時才測試的短清單
+const SSRF_PRIORITY_PARAMS: &[&str] = &["url", "dest", "redirect"];
+
+/// 以錯誤訊息判斷的 SQL 注入 payload 與說明；時間盲注由 blind_sqli 以回應時間判斷
+const SQL_PAYLOADS: &[(&str, &str)] = &[
+    ("' OR '1'='1", "Basic OR injection"),
+    ("' OR '1'='1' --", "OR injection with comment"),
+    ("1' OR '1' = '1", "Numeric OR injection"),
+    ("admin'--", "Admin bypass"),
+    ("' UNION SELECT NULL--", "UNION injection"),
+    ("' AND 1=0 UNION ALL SELECT 'admin', '81dc9bdb52d04dc20036dbd8313ed055'", "UNION hash injection"),
+];
+
+/// SQL 錯誤訊息特徵 (小寫)
+const SQL_ERROR_SIGNATURES: &[&str] = &[
+    "sql syntax", "mysql", "postgresql", "sqlite", "syntax error",
+    "odbc", "jdbc", "oracle", "warning: mysql", "unclosed quotation",
+    "quoted string not properly terminated", "sqlexception",
+];
+
+/// 各檢查需要的最低掃描模式 (類別, 檢查, 模式)；不在表中的檢查視為 full
+///
+/// passive 只分析本來就會取得的基礎頁面 (永遠執行)，safe 加上不具破壞性的 GET 探測，
+/// 注入 payload、寫入類方法與重複大量請求需要 full。類別中沒有可執行的檢查時整個類別不啟動
+const CHECK_MODES: &[(&str, &str, ScanMode)] = &[
+    ("A01", "admin_paths", ScanMode::Safe),
+    ("A01", "idor", ScanMode::Safe),
+    ("A01", "path_traversal", ScanMode::Full),
+    ("A02", "https", ScanMode::Passive),
+    ("A02", "https_redirect", ScanMode::Safe),
+    ("A02", "page_source", ScanMode::Passive),
+    ("A02", "js_analysis", ScanMode::Safe),
+    ("A03", "sql_injection", ScanMode::Full),
+    ("A03", "time_based_sql_injection", ScanMode::Full),
+    ("A03", "xss", ScanMode::Full),
+    ("A03", "command_injection", ScanMode::Full),
+    ("A03", "ldap_injection", ScanMode::Full),
+    ("A03", "nosql_injection", ScanMode::Full),
+    ("A03", "form_injection", ScanMode::Full),
+    ("A04", "user_enumeration", ScanMode::Passive),
+    ("A04", "rate_limit", ScanMode::Full),
+    ("A05", "sensitive_files", ScanMode::Safe),
+    ("A05", "directory_listing", ScanMode::Safe),
+    ("A05", "backup_files", ScanMode::Safe),
+    ("A05", "api_docs", ScanMode::Safe),
+    ("A05", "graphql", ScanMode::Safe),
+    ("A05", "host_header", ScanMode::Safe),
+    ("A05", "security_headers", ScanMode::Passive),
+    ("A05", "cors", ScanMode::Safe),
+    ("A05", "http_methods", ScanMode::Full),
+    ("A06", "components", ScanMode::Passive),
+    ("A07", "login_pages", ScanMode::Safe),
+    ("A07", "default_credentials", ScanMode::Passive),
+    ("A07", "jwt", ScanMode::Passive),
+    ("A08", "integrity", ScanMode::Passive),
+    ("A09", "error_disclosure", ScanMode::Safe),
+    ("A09", "logging_advice", ScanMode::Passive),
+    ("A10", "ssrf", ScanMode::Full),
+    ("A10", "open_redirect", ScanMode::Safe),
+];
+
+/// NoSQL 注入最多測試的 JSON 操作數量，以及每個操作最多替換的欄位數量
+const MAX_JSON_OPERATIONS: usize = 5;
+const MAX_JSON_FIELDS: usize = 3;
+
+/// 每個表單最多測試的文字欄位數量，以及最多測試的表單數量
+const MAX_FORM_FIELDS: usize = 3;
+const MAX_FORMS: usize = 5;
 
 pub struct OwaspScanner {
-    client: Client,
+    client: ScanClient,
+    // 送出表單用的 client，保留 cookie 讓重新取得的 CSRF token 與 session 相符
+    form_client: ScanClient,
+    notes: std::sync::Mutex<Vec<CoverageNote>>,
+    context: TechContext,
+    // 路徑探測同時送出的請求數
+    concurrency: usize,
+    // 不存在路徑的回應指紋，A01 與 A05 共用
+    soft_404: OnceCell<Soft404>,
+    crawl_settings: CrawlSettings,
+    // 爬取找到的頁面、參數與表單，注入與 SSRF 檢查共用
+    crawl: OnceCell<CrawlResult>,
+    // 基礎頁面的回應；請求失敗時為 None
+    base: OnceCell<Option<BaseResponse>>,
+    // 基礎頁面引用的 JS 檔案，A02 的密鑰分析與爬取共用
+    scripts: OnceCell<Vec<ScriptFile>>,
+    // 公開的 OpenAPI / Swagger 文件，A05 的檢查與爬取共用
+    api_docs: OnceCell<ApiDocs>,
+    // 基礎頁面載入的外部腳本與樣式表，A08 建立後附在報告中
+    resources: std::sync::Mutex<Vec<ExternalResource>>,
+    // scan_all 實際執行與略過的類別
+    categories: std::sync::Mutex<OwaspCategoryReport>,
+    scan_mode: ScanMode,
+    // 因掃描模式略過的檢查
+    mode_skipped: std::sync::Mutex<Vec<String>>,
+    // 盲 SSRF 使用的 OOB 回呼伺服器
+    collaborator: Option<CollaboratorSettings>,
+    backup_settings: BackupFileSettings,
+    // 以弱金鑰清單比對 JWT 的 HMAC 簽章
+    jwt_weak_keys: bool,
+    rate_limit_settings: RateLimitProbeSettings,
+}
+
+/// scan_all 開始時取得一次的基礎頁面，各類別共用：不重複下載同一頁，不穩定的目標也不會讓各類別看到不同的回應
+#[derive(Debug, Clone)]
+pub struct BaseResponse {
+    pub status: u16,
+    pub headers: reqwest::header::HeaderMap,
+    pub cookies: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// 路徑探測取得的回應；不需要內容時 body 為空
+#[derive(Debug, Clone)]
+struct FetchedPage {
+    status: u16,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// 探測到的 API 文件
+#[derive(Debug, Default)]
+struct ApiDocs {
+    specs: Vec<(String, ApiSpec)>,
+    // 只有 Swagger UI 頁面、沒有取得文件時的 URL
+    swagger_ui: Option<String>,
+}
+
+/// 參數探索結果，SSRF 與開放重定向檢查共用
+#[derive(Debug, Default)]
+struct ParameterMap {
+    existing: Vec<String>,
+    redirect_observed: std::collections::HashSet<String>,
+    baseline_status: u16,
+    baseline_body: String,
+    requests_sent: usize,
 }
 
 impl OwaspScanner {
-    pub fn new() -> Self {
-        Self {
-            client: Client::builder()
-                .danger_accept_invalid_certs(true)
-                .timeout(std::time::Duration::from_secs(15))
-                .redirect(reqwest::redirect::Policy::none()) // 不自動跟隨重定向
-                .build()
-                .unwrap(),
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 15))
+                .redirect(client::redirects(options, None)) // 預設不自動跟隨重定向
+                .build()?.into(),
+            form_client: client::client_builder(options)?
+                .timeout(client::timeout(options, 15))
+                .redirect(client::redirects(options, None))
+                .cookie_store(true)
+                .build()?.into(),
+            notes: std::sync::Mutex::new(Vec::new()),
+            context: TechContext::default(),
+            concurrency: options.http.effective_concurrency(),
+            soft_404: OnceCell::new(),
+            crawl_settings: options.crawl.clone(),
+            crawl: OnceCell::new(),
+            base: OnceCell::new(),
+            scripts: OnceCell::new(),
+            api_docs: OnceCell::new(),
+            resources: std::sync::Mutex::new(Vec::new()),
+            categories: std::sync::Mutex::new(OwaspCategoryReport::default()),
+            scan_mode: options.scan_mode,
+            mode_skipped: std::sync::Mutex::new(Vec::new()),
+            collaborator: options.collaborator.clone(),
+            backup_settings: options.backup_files.clone(),
+            jwt_weak_keys: options.jwt_weak_keys,
+            rate_limit_settings: options.rate_limit_probe.clone(),
+        })
+    }
+
+    /// 基礎頁面，第一次使用時取得，之後的檢查共用同一個回應
+    async fn base_response(&self, base_url: &str) -> Option<&BaseResponse> {
+        self.base
+            .get_or_init(|| async {
+                let response = self.client.get(base_url).send().await.ok()?;
+                let status = response.status().as_u16();
+                let headers = response.headers().clone();
+                let cookies = response.cookies().map(|c| (c.name().to_string(), c.value().to_string())).collect();
+                let body = client::text(response).await;
+                Some(BaseResponse { status, headers, cookies, body })
+            })
+            .await
+            .as_ref()
+    }
+
+    /// 爬取結果，第一次使用時從基礎頁面開始依連結深度逐層爬取
+    ///
+    /// 每層的頁面以有限並行取得；所有 URL 先經過爬取安全規則，已排入的 URL 不重複請求
+    async fn crawl(&self, base_url: &str) -> &CrawlResult {
+        self.crawl
+            .get_or_init(|| async {
+                let settings = &self.crawl_settings;
+                let mut policy = CrawlPolicy::new(base_url, settings);
+                let mut result = CrawlResult::default();
+                let mut queued = std::collections::HashSet::from([base_url.to_string()]);
+                let mut frontier = vec![base_url.to_string()];
+                result.add_endpoint(base_url);
+
+                for depth in 0..=settings.discovery_depth {
+                    let remaining = settings.discovery_max_pages.saturating_sub(result.pages.len());
+                    let urls: Vec<String> = std::mem::take(&mut frontier)
+                        .into_iter()
+                        .filter(|url| policy.permit("GET", url))
+                        .take(remaining)
+                        .collect();
+                    if urls.is_empty() || control::stopped() {
+                        break;
+                    }
+                    result.depth = depth;
+
+                    // 第一層只有基礎頁面，沿用已取得的回應
+                    let pages = match self.base_response(base_url).await {
+                        Some(base) if depth == 0 => vec![Some(FetchedPage {
+                            status: base.status,
+                            content_type: base.headers.get("content-type").and_then(|v| v.to_str().ok()).map(str::to_string),
+                            body: base.body.clone().into_bytes(),
+                        })],
+                        _ => self.fetch_all(&urls, true).await,
+                    };
+                    for (url, page) in urls.into_iter().zip(pages) {
+                        let Some(page) = page else { continue };
+                        let links = crawler::extract(&url, &String::from_utf8_lossy(&page.body));
+                        result.pages.push(url);
+                        for form in links.forms {
+                            result.add_form(form);
+                        }
+                        for link in links.links {
+                            result.add_endpoint(&link);
+                            if queued.insert(link.clone()) {
+                                frontier.push(link);
+                            }
+                        }
+                    }
+
+                    // 基礎頁面引用的 JS 與公開的 API 文件中的路徑，與頁面連結一起爬取
+                    if depth == 0 {
+                        let discovered = [self.script_endpoints(base_url).await, self.spec_endpoints(base_url).await].concat();
+                        for endpoint in discovered {
+                            result.add_endpoint(&endpoint);
+                            if queued.insert(endpoint.clone()) {
+                                frontier.push(endpoint);
+                            }
+                        }
+                    }
+                }
+
+                result.skipped = policy.skipped().to_vec();
+                let summary = result.summary();
+                tracing::info!(
+                    "🕸️  爬取 {} 個頁面 (深度 {})，發現 {} 個參數與 {} 個表單",
+                    summary.pages, summary.depth, summary.parameters, summary.forms
+                );
+                result
+            })
+            .await
+    }
+
+    /// 基礎頁面引用的 JS 檔案，第一次使用時下載；超過大小上限的檔案不分析
+    async fn scripts(&self, base_url: &str) -> &[ScriptFile] {
+        self.scripts
+            .get_or_init(|| async {
+                let Some(base) = self.base_response(base_url).await else {
+                    return Vec::new();
+                };
+                let mut urls = js_analysis::script_urls(base_url, &base.body);
+                urls.truncate(js_analysis::MAX_SCRIPTS);
+                let pages = self.fetch_all(&urls, true).await;
+
+                let mut scripts = Vec::new();
+                for (url, page) in urls.into_iter().zip(pages) {
+                    let Some(page) = page.filter(|page| (200..300).contains(&page.status)) else { continue };
+                    if page.body.len() > js_analysis::MAX_SCRIPT_BYTES {
+                        tracing::info!("ℹ️  {} 超過 {} 位元組，不分析", url, js_analysis::MAX_SCRIPT_BYTES);
+                        continue;
+                    }
+                    scripts.push(ScriptFile { url, body: String::from_utf8_lossy(&page.body).into_owned() });
+                }
+                scripts
+            })
+            .await
+    }
+
+    /// JS 檔案中與目標同源的 API 路徑 (完整 URL)
+    async fn script_endpoints(&self, base_url: &str) -> Vec<String> {
+        let Ok(base) = reqwest::Url::parse(base_url) else {
+            return Vec::new();
+        };
+        let mut endpoints: Vec<String> = Vec::new();
+        for script in self.scripts(base_url).await {
+            for path in js_analysis::endpoints(&script.body) {
+                let Ok(url) = base.join(&path) else { continue };
+                let url = url.to_string();
+                if !endpoints.contains(&url) {
+                    endpoints.push(url);
+                }
+            }
         }
+        endpoints
     }
 
-    /// 執行完整的 OWASP Top 10 掃描
-    pub async fn scan_all(&self, task_id: &str, url: &str) -> ScannerResult<Vec<ScanResult>> {
-        let mut results = Vec::new();
+    /// 公開的 OpenAPI / Swagger 文件，第一次使用時探測；只有通過結構驗證的 JSON 才算文件
+    async fn api_docs(&self, base_url: &str) -> &ApiDocs {
+        self.api_docs
+            .get_or_init(|| async {
+                let paths: Vec<&str> = api_docs::SPEC_PATHS
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(api_docs::SWAGGER_UI_PATH))
+                    .filter(|path| self.context.allows_path(path))
+                    .collect();
+                let urls: Vec<String> = paths.iter().map(|path| format!("{}{}", base_url.trim_end_matches('/'), path)).collect();
+                let pages = self.fetch_all(&urls, true).await;
+
+                let mut docs = ApiDocs::default();
+                for (url, page) in urls.into_iter().zip(pages) {
+                    let Some(page) = page.filter(|page| (200..300).contains(&page.status)) else { continue };
+                    match api_docs::parse_spec(&page.body) {
+                        Some(spec) => docs.specs.push((url, spec)),
+                        None if api_docs::is_swagger_ui(&page.body) => docs.swagger_ui = Some(url),
+                        None => {}
+                    }
+                }
+                docs
+            })
+            .await
+    }
+
+    /// API 文件中可以爬取的 URL
+    async fn spec_endpoints(&self, base_url: &str) -> Vec<String> {
+        let Ok(base) = reqwest::Url::parse(base_url) else {
+            return Vec::new();
+        };
+        let origin = base.origin().ascii_serialization();
+        let mut endpoints: Vec<String> = Vec::new();
+        for (_, spec) in &self.api_docs(base_url).await.specs {
+            for url in spec.urls(&origin) {
+                if !endpoints.contains(&url) {
+                    endpoints.push(url);
+                }
+            }
+        }
+        endpoints
+    }
+
+    /// 爬取結果；此次掃描沒有執行需要爬取的檢查時為 None
+    pub fn crawl_result(&self) -> Option<&CrawlResult> {
+        self.crawl.get()
+    }
 
-        println!("🔍 開始 OWASP Top 10 掃描: {}", url);
+    /// 目標的 soft-404 指紋，第一次使用時請求兩個不存在的路徑建立
+    async fn soft_404(&self, base_url: &str) -> &Soft404 {
+        self.soft_404
+            .get_or_init(|| async {
+                let pages = self.fetch_all(&soft_404::probe_urls(base_url), true).await;
+                let baseline = Soft404::from_responses(pages.iter().flatten().map(|page| (page.status, page.body.as_slice())));
+                if baseline.is_active() {
+                    tracing::info!("ℹ️  {} 對不存在的路徑回傳成功 (soft-404)，路徑探測將比對此回應", base_url);
+                }
+                baseline
+            })
+            .await
+    }
 
-        // A01: Broken Access Control
-        results.extend(self.a01_broken_access_control(task_id, url).await?);
+    /// 記錄因回應與不存在路徑相同而不回報的路徑
+    fn note_soft_404(&self, check: &str, paths: Vec<&str>) {
+        if paths.is_empty() {
+            return;
+        }
+        self.add_note(
+            check,
+            format!("{} 個路徑的回應與不存在的頁面相同 (soft-404)，不視為存在", paths.len()),
+            serde_json::json!({ "paths": paths, "reason": "soft_404" }),
+        );
+    }
 
-        // A02: Cryptographic Failures
-        results.extend(self.a02_cryptographic_failures(task_id, url).await?);
+    /// 以有限並行對多個 URL 送出 GET，結果依輸入順序回傳；請求失敗為 None
+    ///
+    /// 每個請求受 client 逾時限制，單一請求卡住不會拖住其他請求
+    async fn fetch_all(&self, urls: &[String], read_body: bool) -> Vec<Option<FetchedPage>> {
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let mut requests = JoinSet::new();
+        for (index, url) in urls.iter().enumerate() {
+            let client = self.client.clone();
+            let semaphore = semaphore.clone();
+            let url = url.clone();
+            control::spawn(&mut requests, async move {
+                let _permit = semaphore.acquire_owned().await;
+                let page = match client.get(&url).send().await {
+                    Ok(response) => {
+                        let status = response.status().as_u16();
+                        let content_type = response.headers().get("content-type").and_then(|v| v.to_str().ok()).map(str::to_string);
+                        let body = if read_body {
+                            client::bytes(response).await
+                        } else {
+                            Vec::new()
+                        };
+                        Some(FetchedPage { status, content_type, body })
+                    }
+                    Err(_) => None,
+                };
+                (index, page)
+            });
+        }
 
-        // A03: Injection
-        results.extend(self.a03_injection(task_id, url).await?);
+        let mut pages = vec![None; urls.len()];
+        while let Some(joined) = requests.join_next().await {
+            if let Ok((index, page)) = joined {
+                pages[index] = page;
+            }
+        }
+        pages
+    }
 
-        // A04: Insecure Design (靜態分析)
-        results.extend(self.a04_insecure_design(task_id, url).await?);
+    /// 設定技術檢測結果，用於略過前置條件不符的探測
+    pub fn with_tech_context(mut self, context: TechContext) -> Self {
+        self.context = context;
+        self
+    }
 
-        // A05: Security Misconfiguration
-        results.extend(self.a05_security_misconfiguration(task_id, url).await?);
+    /// 執行完整的 OWASP Top 10 掃描
+    /// 執行設定中啟用的類別；停用的類別不送出任何請求，並記錄為略過
+    pub async fn scan_all(self: &Arc<Self>, task_id: &str, url: &str, config: &OwaspConfig) -> ScannerResult<Vec<ScanResult>> {
+        let mut ran: Vec<&str> = Vec::new();
+        let mut errors: Vec<OwaspCategoryError> = Vec::new();
+
+        tracing::info!("🔍 開始 OWASP Top 10 掃描: {}", url);
+
+        // 基礎頁面只取得一次，標頭、cookie 與內容由各類別共用
+        self.base_response(url).await;
+
+        let (enabled, disabled): (Vec<&str>, Vec<&str>) = OWASP_CATEGORIES.iter().partition(|c| config.is_enabled(c));
+        // 掃描模式不允許任何檢查的類別不啟動，也不送出請求
+        let (enabled, out_of_mode): (Vec<&str>, Vec<&str>) = enabled.into_iter().partition(|c| self.category_allowed(c));
+        for category in &out_of_mode {
+            self.skip_for_mode(CHECK_MODES.iter().filter(|(c, _, _)| c == category).map(|(_, check, _)| *check));
+            let message = format!("{} 模式不執行此類別的任何檢查", self.scan_mode);
+            control::cover(&category_check_id(category), CoverageStatus::Skipped, Some(message));
+        }
+        for category in &disabled {
+            control::cover(&category_check_id(category), CoverageStatus::Skipped, Some("設定停用此類別".to_string()));
+        }
+        if !disabled.is_empty() {
+            self.add_note(
+                "owasp",
+                format!("設定停用了 OWASP 類別 {}，這些類別沒有測試", disabled.join(", ")),
+                serde_json::json!({ "disabled_categories": disabled }),
+            );
+        }
 
-        // A06: Vulnerable and Outdated Components
-        results.extend(self.a06_vulnerable_components(task_id, url).await?);
+        // 類別依優先順序取得執行名額，最多同時執行 parallelism 個；請求速率由掃描共用的速率限制控制。
+        // 限時掃描在取得名額後才判斷，時間用完後不再啟動新的類別
+        let categories = time_box::ordered(enabled, |c| ScanStage::Owasp(c.to_string()).message_key());
+        let total = categories.len().max(1);
+        let semaphore = Arc::new(Semaphore::new(config.parallelism.max(1)));
+        let mut running = JoinSet::new();
+        // 尚未回報的類別；JoinError 不帶類別名稱，結束時仍留在這裡的類別就是執行中斷的類別
+        let mut pending = categories.clone();
+        for (index, category) in categories.into_iter().enumerate() {
+            let scanner = self.clone();
+            let semaphore = semaphore.clone();
+            let (task_id, url) = (task_id.to_string(), url.to_string());
+            control::spawn(&mut running, async move {
+                let _permit = semaphore.acquire_owned().await;
+                let stage = ScanStage::Owasp(category.to_string());
+                let check_id = stage.message_key();
+                if control::stopped() {
+                    control::cover(&check_id, CoverageStatus::Skipped, Some("掃描已終止，未執行".to_string()));
+                    return (category, None);
+                }
+                if !control::admit(&check_id) {
+                    return (category, None);
+                }
+                // OWASP 類別佔 40-90%
+                control::enter_stage(stage, 40 + (index * 50 / total) as u8);
+                let findings = control::check(&check_id, scanner.run_category(category, &task_id, &url)).await;
+                (category, Some(findings))
+            });
+        }
 
-        // A07: Identification and Authentication Failures
-        results.extend(self.a07_authentication_failures(task_id, url).await?);
+        let mut by_category: Vec<(&str, Vec<ScanResult>)> = Vec::new();
+        let mut completed = 0;
+        let mut join_errors: Vec<String> = Vec::new();
+        while let Some(joined) = running.join_next().await {
+            let (category, outcome) = match joined {
+                Ok(joined) => joined,
+                Err(join_err) => {
+                    tracing::warn!("⚠️  OWASP 類別執行中斷: {}", join_err);
+                    join_errors.push(join_err.to_string());
+                    continue;
+                }
+            };
+            pending.retain(|c| *c != category);
+            let Some(outcome) = outcome else { continue };
+            let stage = ScanStage::Owasp(category.to_string());
+            let check_id = stage.message_key();
+            match outcome {
+                Ok(findings) => {
+                    control::record_findings(&check_id, findings.len());
+                    by_category.push((category, finding_caps::apply(task_id, &check_id, findings)));
+                    ran.push(category);
+                    match self.out_of_mode_checks(category) {
+                        skipped if skipped.is_empty() => control::cover(&check_id, CoverageStatus::Completed, None),
+                        skipped => {
+                            let message = format!("{} 模式略過: {}", self.scan_mode, skipped.join(", "));
+                            control::cover(&check_id, CoverageStatus::Partial, Some(message));
+                        }
+                    }
+                }
+                // 取消時保留已完成類別的結果
+                Err(_) if control::stopped() => {
+                    control::cover(&check_id, CoverageStatus::Partial, Some("掃描終止時中斷".to_string()));
+                    continue;
+                }
+                // 單一類別失敗不影響其他類別，錯誤記錄在報告中
+                Err(e) => {
+                    let message = e.to_string();
+                    tracing::warn!("⚠️  OWASP {} 檢查失敗: {}", category, message);
+                    control::cover(&check_id, CoverageStatus::Error, Some(message.clone()));
+                    errors.push(OwaspCategoryError { category: category.to_string(), message });
+                }
+            }
+            completed += 1;
+            control::progress(stage, 40 + (completed * 50 / total) as u8, format!("OWASP {} 完成 ({}/{})", category, completed, total));
+        }
 
-        // A08: Software and Data Integrity Failures
-        results.extend(self.a08_integrity_failures(task_id, url).await?);
+        // panic 的類別沒有結果，與失敗的類別一樣記錄為錯誤，不當作已完成
+        for category in pending {
+            let message = format!("類別執行中斷: {}", join_errors.join("; "));
+            tracing::warn!("⚠️  OWASP {} 檢查失敗: {}", category, message);
+            control::cover(&ScanStage::Owasp(category.to_string()).message_key(), CoverageStatus::Error, Some(message.clone()));
+            errors.push(OwaspCategoryError { category: category.to_string(), message });
+        }
 
-        // A09: Security Logging and Monitoring Failures
-        results.extend(self.a09_logging_failures(task_id, url).await?);
+        // 結果依類別、再依嚴重度排序，同一輸入的報告輸出固定
+        by_category.sort_by_key(|(category, _)| OWASP_CATEGORIES.iter().position(|c| c == category));
+        let results: Vec<ScanResult> = by_category
+            .into_iter()
+            .flat_map(|(_, mut findings)| {
+                findings.sort_by_key(|f| std::cmp::Reverse(severity_rank(f.severity.as_ref())));
+                findings
+            })
+            .collect();
+        ran.sort_by_key(|category| OWASP_CATEGORIES.iter().position(|c| c == category));
+
+        let mode_skipped = self.mode_skipped.lock().map(|skipped| skipped.clone()).unwrap_or_default();
+        if !mode_skipped.is_empty() {
+            self.add_note(
+                "owasp",
+                format!("{} 模式略過了 {} 個需要更高模式的檢查: {}", self.scan_mode, mode_skipped.len(), mode_skipped.join(", ")),
+                serde_json::json!({ "scan_mode": self.scan_mode, "skipped_checks": mode_skipped }),
+            );
+        }
 
-        // A10: Server-Side Request Forgery
-        results.extend(self.a10_ssrf(task_id, url).await?);
+        if let Ok(mut report) = self.categories.lock() {
+            *report = OwaspCategoryReport {
+                ran: ran.iter().map(|c| c.to_string()).collect(),
+                skipped: OWASP_CATEGORIES.iter().filter(|c| !ran.contains(c)).map(|c| c.to_string()).collect(),
+                disabled: disabled.iter().map(|c| c.to_string()).collect(),
+                errors,
+            };
+        }
 
-        println!("✅ OWASP Top 10 掃描完成，發現 {} 個潛在問題", results.len());
+        tracing::info!("✅ OWASP Top 10 掃描完成，發現 {} 個潛在問題", results.len());
 
         Ok(results)
     }
@@ -86,46 +617,58 @@ impl OwaspScanner {
         let mut results = Vec::new();
 
         // 檢查常見的管理後台路徑
-        let admin_paths = vec![
-            "/admin", "/administrator", "/admin.php", "/admin/",
-            "/wp-admin", "/adminpanel", "/cpanel", "/controlpanel",
-            "/dashboard", "/manage", "/manager", "/backend",
-        ];
-
-        for path in admin_paths {
-            let test_url = format!("{}{}", base_url.trim_end_matches('/'), path);
-
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let status = response.status().as_u16();
+        if self.allows("admin_paths") {
+            let admin_paths = vec![
+                "/admin", "/administrator", "/admin.php", "/admin/",
+                "/wp-admin", "/adminpanel", "/cpanel", "/controlpanel",
+                "/dashboard", "/manage", "/manager", "/backend",
+            ];
+
+            let admin_paths = self.skip_unmet_preconditions("A01:2021", admin_paths);
+            let urls: Vec<String> = admin_paths.iter().map(|path| format!("{}{}", base_url.trim_end_matches('/'), path)).collect();
+            let soft_404 = self.soft_404(base_url).await;
+            let pages = self.fetch_all(&urls, true).await;
+            let mut soft_404_paths = Vec::new();
+
+            for ((path, test_url), page) in admin_paths.into_iter().zip(urls).zip(pages) {
+                match page {
+                    Some(page) => {
+                        let status = page.status;
+                        if soft_404.matches(status, &page.body) {
+                            soft_404_paths.push(path);
+                            continue;
+                        }
 
-                    // 200 OK 或 403 Forbidden 都代表路徑存在
-                    if status == 200 || status == 403 {
-                        let severity = if status == 200 {
-                            Severity::High
-                        } else {
-                            Severity::Medium
-                        };
+                        // 200 OK 或 403 Forbidden 都代表路徑存在
+                        if status == 200 || status == 403 {
+                            let severity = if status == 200 {
+                                Severity::High
+                            } else {
+                                Severity::Medium
+                            };
 
-                        results.push(self.create_result(
-                            task_id,
-                            severity,
-                            format!("發現管理後台路徑: {}", path),
-                            format!(
-                                "管理後台可訪問 (HTTP {})，可能存在未授權訪問風險。建議: 1) 使用強認證 2) IP 白名單 3) 隱藏管理路徑",
-                                status
-                            ),
-                            serde_json::json!({
-                                "owasp": "A01:2021",
-                                "path": path,
-                                "status": status,
-                                "url": test_url
-                            })
-                        ));
-                    }
-                },
-                Err(_) => continue,
+                            results.push(self.create_result(
+                                task_id,
+                                severity,
+                                format!("發現管理後台路徑: {}", path),
+                                format!(
+                                    "管理後台可訪問 (HTTP {})，可能存在未授權訪問風險。建議: 1) 使用強認證 2) IP 白名單 3) 隱藏管理路徑",
+                                    status
+                                ),
+                                serde_json::json!({
+                                    "owasp": "A01:2021",
+                                    "path": path,
+                                    "status": status,
+                                    "url": test_url
+                                })
+                            ));
+                        }
+                    },
+                    None => continue,
+                }
             }
+
+            self.note_soft_404("A01:2021", soft_404_paths);
         }
 
         // 檢查 IDOR (Insecure Direct Object Reference)
@@ -133,80 +676,143 @@ impl OwaspScanner {
             "?id=1", "?user_id=1", "?doc_id=1", "?file_id=1",
         ];
 
-        for pattern in idor_patterns {
-            let test_url = format!("{}{}", base_url, pattern);
+        if self.allows("idor") {
+            for pattern in idor_patterns {
+                let test_url = format!("{}{}", base_url, pattern);
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        let body = response.text().await.unwrap_or_default();
+                match self.client.get(&test_url).send().await {
+                    Ok(response) => {
+                        if response.status().is_success() {
+                            let body = client::text(response).await;
 
-                        // 檢查是否返回了用戶數據
-                        if body.contains("email") || body.contains("username") || body.contains("user") {
-                            results.push(self.create_result(
-                                task_id,
-                                Severity::High,
-                                format!("潛在的 IDOR 漏洞: {}", pattern),
-                                "URL 參數可能存在不安全的直接對象引用 (IDOR)，攻擊者可能通過修改 ID 訪問其他用戶資料".to_string(),
-                                serde_json::json!({
-                                    "owasp": "A01:2021",
-                                    "type": "IDOR",
-                                    "pattern": pattern,
-                                    "url": test_url
-                                })
-                            ));
-                            break;
+                            // 檢查是否返回了用戶數據
+                            if body.contains("email") || body.contains("username") || body.contains("user") {
+                                results.push(self.create_result(
+                                    task_id,
+                                    Severity::High,
+                                    format!("潛在的 IDOR 漏洞: {}", pattern),
+                                    "URL 參數可能存在不安全的直接對象引用 (IDOR)，攻擊者可能通過修改 ID 訪問其他用戶資料".to_string(),
+                                    serde_json::json!({
+                                        "owasp": "A01:2021",
+                                        "type": "IDOR",
+                                        "pattern": pattern,
+                                        "url": test_url
+                                    })
+                                ));
+                                break;
+                            }
                         }
-                    }
-                },
-                Err(_) => continue,
+                    },
+                    Err(_) => continue,
+                }
             }
         }
 
         // 檢查 Path Traversal
-        let path_traversal_payloads = vec![
-            "../../../etc/passwd",
-            "..\\..\\..\\windows\\system32\\config\\sam",
-            "....//....//....//etc/passwd",
-        ];
+        if self.allows("path_traversal") {
+            results.extend(self.check_path_traversal(task_id, base_url).await?);
+        }
 
-        for payload in path_traversal_payloads {
-            let test_url = format!("{}?file={}", base_url, urlencoding::encode(payload));
+        Ok(results)
+    }
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let body = response.text().await.unwrap_or_default();
+    /// 對頁面中處理檔案的參數測試路徑遍歷，以 passwd / win.ini 的格式標記確認
+    async fn check_path_traversal(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+        let mut results = Vec::new();
 
-                    if body.contains("root:") || body.contains("[boot loader]") {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::Critical,
-                            "路徑遍歷漏洞 (Path Traversal)".to_string(),
-                            format!(
-                                "使用 payload '{}' 成功讀取系統文件，攻擊者可能讀取任意文件",
-                                payload
-                            ),
-                            serde_json::json!({
-                                "owasp": "A01:2021",
-                                "type": "Path Traversal",
-                                "payload": payload,
-                                "url": test_url
-                            })
-                        ));
-                        break;
+        let page = self.base_response(base_url).await.map(|base| base.body.clone()).unwrap_or_default();
+        let mut targets = path_traversal::discover(base_url, &page);
+        let basis = if targets.is_empty() {
+            targets = path_traversal::FALLBACK_PARAMS.iter().map(|p| (base_url.to_string(), p.to_string())).collect();
+            "no_evidence_priority_list"
+        } else {
+            "discovered"
+        };
+        // 基礎頁面沿用 scan_all 取得的回應，不計入
+        let mut requests_sent = 0;
+
+        for (endpoint, param) in &targets {
+            // 端點原本的回應作為基準；基準中已有標記的檔案不測試 (例如說明 passwd 格式的頁面)
+            let baseline = if endpoint == base_url {
+                page.clone()
+            } else {
+                requests_sent += 1;
+                match self.client.get(endpoint).send().await {
+                    Ok(response) => client::text(response).await,
+                    Err(_) => continue,
+                }
+            };
+            let baseline_normalized = response_diff::normalize(&baseline);
+
+            'files: for file in path_traversal::TargetFile::ALL {
+                if !file.matched_lines(&baseline).is_empty() {
+                    continue;
+                }
+                for payload in path_traversal::payloads(file) {
+                    let test_url = path_traversal::with_raw_param(endpoint, param, &payload.value);
+                    requests_sent += 1;
+
+                    let body = match self.client.get(&test_url).send().await {
+                        Ok(response) => client::text(response).await,
+                        Err(_) => continue,
+                    };
+                    let matched_lines = file.matched_lines(&body);
+                    if matched_lines.is_empty() || response_diff::normalize(&body) == baseline_normalized {
+                        continue;
                     }
-                },
-                Err(_) => continue,
+
+                    results.push(self.create_result(
+                        task_id,
+                        Severity::Critical,
+                        format!("路徑遍歷漏洞 (Path Traversal): 參數 {}", param),
+                        format!(
+                            "參數 '{}' 以深度 {} 的遍歷 payload 讀取到 {} 的內容，攻擊者可讀取伺服器上的任意檔案。建議: 1) 不要以使用者輸入組成檔案路徑 2) 使用檔案 ID 白名單 3) 正規化路徑後確認位於允許的目錄內",
+                            param,
+                            payload.depth,
+                            match file {
+                                path_traversal::TargetFile::EtcPasswd => "/etc/passwd",
+                                path_traversal::TargetFile::WinIni => "win.ini",
+                            }
+                        ),
+                        serde_json::json!({
+                            "owasp": "A01:2021",
+                            "type": "Path Traversal",
+                            "parameter": param,
+                            "endpoint": endpoint,
+                            "depth": payload.depth,
+                            "encoding": payload.encoding,
+                            "null_byte": payload.null_byte,
+                            "target_file": payload.file,
+                            "payload": payload.value,
+                            "matched_lines": matched_lines,
+                            "url": test_url
+                        })
+                    ));
+                    // 每個參數確認一次即可
+                    break 'files;
+                }
             }
         }
 
+        tracing::info!("📉 路徑遍歷測試 {} 個參數，送出 {} 個請求", targets.len(), requests_sent);
+        self.add_note(
+            "A01:2021",
+            format!("路徑遍歷測試 {} 個處理檔案的參數，共送出 {} 個請求", targets.len(), requests_sent),
+            serde_json::json!({
+                "check": "path_traversal",
+                "basis": basis,
+                "requests_sent": requests_sent,
+                "tested": targets.iter().map(|(endpoint, param)| serde_json::json!({ "endpoint": endpoint, "parameter": param })).collect::<Vec<_>>(),
+            }),
+        );
+
         Ok(results)
     }
 
     // ========================================================================
     // A02: Cryptographic Failures
     // ========================================================================
-    async fn a02_cryptographic_failures(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn a02_cryptographic_failures(&self, task_id: &str, base_url: &str, base: Option<&BaseResponse>) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
         // 檢查是否使用 HTTPS
@@ -225,7 +831,7 @@ impl OwaspScanner {
         }
 
         // 檢查 HTTP 是否會自動重定向到 HTTPS
-        if base_url.starts_with("https://") {
+        if base_url.starts_with("https://") && self.allows("https_redirect") {
             let http_url = base_url.replace("https://", "http://");
 
             match self.client.get(&http_url).send().await {
@@ -253,60 +859,139 @@ impl OwaspScanner {
         }
 
         // 檢查原始碼中的敏感資訊洩露
-        match self.client.get(base_url).send().await {
-            Ok(response) => {
-                let body = response.text().await.unwrap_or_default();
-
-                // 檢查各種密鑰和令牌
-                let sensitive_patterns = vec![
-                    (r#"(?i)api[_-]?key['\"]?\s*[:=]\s*['\"]([a-zA-Z0-9_\-]{20,})"#, "API Key"),
-                    (r#"(?i)secret[_-]?key['\"]?\s*[:=]\s*['\"]([a-zA-Z0-9_\-]{20,})"#, "Secret Key"),
-                    (r#"(?i)access[_-]?token['\"]?\s*[:=]\s*['\"]([a-zA-Z0-9_\-]{20,})"#, "Access Token"),
-                    (r#"(?i)password['\"]?\s*[:=]\s*['\"]([^'\"]{3,})"#, "Password"),
-                    (r#"(?i)aws[_-]?access[_-]?key['\"]?\s*[:=]\s*['\"]([A-Z0-9]{20})"#, "AWS Access Key"),
-                    (r#"(?i)private[_-]?key['\"]?\s*[:=]"#, "Private Key"),
-                    (r#"-----BEGIN (RSA |DSA )?PRIVATE KEY-----"#, "PEM Private Key"),
-                ];
-
-                for (pattern, name) in sensitive_patterns {
-                    if let Ok(re) = regex::Regex::new(pattern) {
-                        if re.is_match(&body) {
-                            results.push(self.create_result(
-                                task_id,
-                                Severity::Critical,
-                                format!("HTML 原始碼中發現 {}", name),
-                                format!(
-                                    "網頁原始碼中包含 {}，這可能導致嚴重的資訊洩露。建議: 1) 移除硬編碼密鑰 2) 使用環境變數 3) 使用密鑰管理服務",
-                                    name
-                                ),
-                                serde_json::json!({
-                                    "owasp": "A02:2021",
-                                    "type": name,
-                                    "pattern": pattern
-                                })
-                            ));
-                        }
-                    }
+        if let Some(base) = base {
+            let body = base.body.clone();
+
+            // 檢查各種密鑰和令牌 (在 blocking 執行緒池比對，大型頁面不卡住 executor)
+            let (body, secrets) = analysis::offload(move || {
+                let secrets = analysis::secret_patterns().matches(&body);
+                (body, secrets)
+            }).await;
+
+            for (pattern, name) in secrets {
+                results.push(self.create_result(
+                    task_id,
+                    Severity::Critical,
+                    format!("HTML 原始碼中發現 {}", name),
+                    format!(
+                        "網頁原始碼中包含 {}，這可能導致嚴重的資訊洩露。建議: 1) 移除硬編碼密鑰 2) 使用環境變數 3) 使用密鑰管理服務",
+                        name
+                    ),
+                    serde_json::json!({
+                        "owasp": "A02:2021",
+                        "type": name,
+                        "pattern": pattern
+                    })
+                ));
+            }
+
+            // 檢查是否有明文密碼輸入框
+            if body.contains(r#"type="password""#) && !body.contains("autocomplete=\"off\"") {
+                results.push(self.create_result(
+                    task_id,
+                    Severity::Low,
+                    "密碼輸入框未禁用自動完成".to_string(),
+                    "密碼輸入框未設置 autocomplete=\"off\"，可能導致密碼被瀏覽器緩存".to_string(),
+                    serde_json::json!({
+                        "owasp": "A02:2021",
+                        "type": "Autocomplete"
+                    })
+                ));
+            }
+        }
+
+        if self.allows("js_analysis") {
+            results.extend(self.analyze_scripts(task_id, base_url).await);
+        }
+
+        Ok(results)
+    }
+
+    /// 基礎頁面引用的 JS 檔案中的密鑰與內部 IP 位址
+    ///
+    /// 同一密鑰出現在多個檔案時只回報第一次；發現與附帶的回應內容中的密鑰只保留前 4 與後 4 個字元
+    async fn analyze_scripts(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let scripts = self.scripts(base_url).await.to_vec();
+        if scripts.is_empty() {
+            return results;
+        }
+        let script_urls: Vec<String> = scripts.iter().map(|script| script.url.clone()).collect();
+        let matches = analysis::offload(move || {
+            scripts
+                .iter()
+                .map(|script| (script.url.clone(), analysis::js_secret_patterns().locate(&script.body)))
+                .collect::<Vec<_>>()
+        })
+        .await;
+
+        let mut reported: std::collections::HashSet<(&str, String)> = std::collections::HashSet::new();
+        for (script_url, found) in &matches {
+            let (addresses, secrets): (Vec<_>, Vec<_>) = found.iter().partition(|m| m.name == analysis::PRIVATE_IP);
+            let values: Vec<&str> = secrets.iter().map(|m| m.value.as_str()).collect();
+
+            for secret in secrets {
+                if !reported.insert((secret.name, secret.value.clone())) {
+                    continue;
                 }
+                let redacted = js_analysis::redact(&secret.value);
+                let mut result = self.create_result(
+                    task_id,
+                    Severity::Critical,
+                    format!("JavaScript 檔案中發現 {}", secret.name),
+                    format!(
+                        "{} 的第 {} 位元組包含 {} ({})，任何訪客都能下載此檔案取得。建議: 1) 從前端程式碼移除密鑰 2) 撤銷並重新產生已外洩的密鑰 3) 改由後端代為呼叫需要密鑰的服務",
+                        script_url, secret.offset, secret.name, redacted
+                    ),
+                    serde_json::json!({
+                        "owasp": "A02:2021",
+                        "type": secret.name,
+                        "pattern": secret.pattern,
+                        "url": script_url,
+                        "offset": secret.offset,
+                        "value": redacted
+                    }),
+                );
+                redact_exchange(&mut result, &values);
+                results.push(result);
+            }
 
-                // 檢查是否有明文密碼輸入框
-                if body.contains(r#"type="password""#) && !body.contains("autocomplete=\"off\"") {
-                    results.push(self.create_result(
-                        task_id,
-                        Severity::Low,
-                        "密碼輸入框未禁用自動完成".to_string(),
-                        "密碼輸入框未設置 autocomplete=\"off\"，可能導致密碼被瀏覽器緩存".to_string(),
-                        serde_json::json!({
-                            "owasp": "A02:2021",
-                            "type": "Autocomplete"
-                        })
-                    ));
+            if !addresses.is_empty() {
+                let mut unique: Vec<serde_json::Value> = Vec::new();
+                let mut seen = std::collections::HashSet::new();
+                for address in addresses.iter().filter(|m| seen.insert(m.value.clone())) {
+                    unique.push(serde_json::json!({ "address": address.value, "offset": address.offset }));
                 }
-            },
-            Err(_) => {},
+                let mut result = self.create_result(
+                    task_id,
+                    Severity::Low,
+                    "JavaScript 檔案中發現內部 IP 位址".to_string(),
+                    format!(
+                        "{} 包含 {} 個私有 IP 位址，洩露內部網路配置。建議: 移除前端程式碼中的內部位址，改用對外的網域名稱",
+                        script_url,
+                        unique.len()
+                    ),
+                    serde_json::json!({
+                        "owasp": "A02:2021",
+                        "type": analysis::PRIVATE_IP,
+                        "url": script_url,
+                        "offset": addresses[0].offset,
+                        "addresses": unique
+                    }),
+                );
+                redact_exchange(&mut result, &values);
+                results.push(result);
+            }
         }
 
-        Ok(results)
+        let endpoints = self.script_endpoints(base_url).await;
+        self.add_note(
+            "A02:2021",
+            format!("分析 {} 個 JS 檔案，擷取 {} 個 API 路徑加入爬取", script_urls.len(), endpoints.len()),
+            serde_json::json!({ "check": "js_analysis", "scripts": script_urls, "endpoints": endpoints }),
+        );
+
+        results
     }
 
     // ========================================================================
@@ -315,122 +1000,455 @@ impl OwaspScanner {
     async fn a03_injection(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
+        // 測試爬取找到的參數；沒有任何參數時才退回在基礎 URL 加上常見參數名稱
+        let points = self.crawl(base_url).await.injection_points();
+        if points.is_empty() {
+            self.add_note(
+                "A03:2021",
+                "爬取未發現任何參數，注入檢查改為在基礎 URL 加上常見參數名稱測試".to_string(),
+                serde_json::json!({ "basis": "no_evidence_priority_list" }),
+            );
+        }
+        let points_or = |param: &str| {
+            if points.is_empty() {
+                vec![(base_url.to_string(), param.to_string())]
+            } else {
+                points.clone()
+            }
+        };
+
         // SQL Injection
-        results.extend(self.check_sql_injection(task_id, base_url).await?);
+        if self.allows("sql_injection") {
+            results.extend(self.check_sql_injection(task_id, &points_or("id")).await?);
+        }
+        if self.allows("time_based_sql_injection") {
+            results.extend(self.check_time_based_sql_injection(task_id, &points_or("id")).await);
+        }
 
         // XSS (Cross-Site Scripting)
-        results.extend(self.check_xss(task_id, base_url).await?);
+        if self.allows("xss") {
+            results.extend(self.check_xss(task_id, &points_or("q")).await?);
+        }
 
         // Command Injection
-        results.extend(self.check_command_injection(task_id, base_url).await?);
+        if self.allows("command_injection") {
+            results.extend(self.check_command_injection(task_id, &points_or("cmd")).await?);
+        }
 
         // LDAP Injection
-        results.extend(self.check_ldap_injection(task_id, base_url).await?);
-
-        Ok(results)
-    }
-
-    async fn check_sql_injection(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
-        let mut results = Vec::new();
-
-        let sql_payloads = vec![
-            ("' OR '1'='1", "Basic OR injection"),
-            ("' OR '1'='1' --", "OR injection with comment"),
-            ("1' OR '1' = '1", "Numeric OR injection"),
-            ("admin'--", "Admin bypass"),
-            ("' UNION SELECT NULL--", "UNION injection"),
-            ("' AND 1=0 UNION ALL SELECT 'admin', '81dc9bdb52d04dc20036dbd8313ed055'", "UNION hash injection"),
-            ("1' AND SLEEP(5)--", "Time-based blind injection"),
-        ];
-
-        for (payload, description) in sql_payloads {
-            let test_url = format!("{}?id={}", base_url, urlencoding::encode(payload));
-
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let body = response.text().await.unwrap_or_default().to_lowercase();
+        if self.allows("ldap_injection") {
+            results.extend(self.check_ldap_injection(task_id, &points_or("user")).await?);
+        }
 
-                    // SQL 錯誤訊息特徵
-                    let sql_errors = vec![
-                        "sql syntax", "mysql", "postgresql", "sqlite", "syntax error",
-                        "odbc", "jdbc", "oracle", "warning: mysql", "unclosed quotation",
-                        "quoted string not properly terminated", "sqlexception",
-                    ];
+        // NoSQL 運算子注入：GET 參數與 API 文件中接受 JSON 的操作
+        if self.allows("nosql_injection") {
+            results.extend(self.check_nosql_injection(task_id, &points_or("username")).await);
+            results.extend(self.check_nosql_json_injection(task_id, base_url).await);
+        }
 
-                    if sql_errors.iter().any(|err| body.contains(err)) {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::Critical,
-                            format!("SQL Injection 漏洞: {}", description),
-                            format!(
-                                "使用 payload '{}' 觸發了資料庫錯誤訊息，確認存在 SQL 注入漏洞。建議: 1) 使用參數化查詢 2) 使用 ORM 3) 輸入驗證",
-                                payload
-                            ),
-                            serde_json::json!({
-                                "owasp": "A03:2021",
-                                "type": "SQL Injection",
-                                "payload": payload,
-                                "description": description,
-                                "url": test_url
-                            })
-                        ));
-                        break;
-                    }
-                },
-                Err(_) => continue,
-            }
+        // POST 表單 (GET 表單的欄位已包含在上面的參數中)
+        if self.allows("form_injection") {
+            results.extend(self.check_form_injection(task_id, base_url).await);
         }
 
         Ok(results)
     }
 
-    async fn check_xss(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn check_sql_injection(&self, task_id: &str, points: &[(String, String)]) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
-        let xss_payloads = vec![
-            ("<script>alert('XSS')</script>", "Basic XSS"),
-            ("<img src=x onerror=alert('XSS')>", "Image XSS"),
-            ("javascript:alert('XSS')", "JavaScript protocol"),
-            ("<svg onload=alert('XSS')>", "SVG XSS"),
-            ("<iframe src=javascript:alert('XSS')>", "Iframe XSS"),
-            ("'><script>alert(String.fromCharCode(88,83,83))</script>", "Encoded XSS"),
-        ];
-
-        for (payload, description) in xss_payloads {
-            let test_url = format!("{}?q={}", base_url, urlencoding::encode(payload));
+        for (endpoint, param) in points {
+            for (payload, description) in SQL_PAYLOADS {
+                let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(payload));
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let body = response.text().await.unwrap_or_default();
+                match self.client.get(&test_url).send().await {
+                    Ok(response) => {
+                        let body = client::text(response).await;
 
-                    // 檢查 payload 是否未經編碼直接出現在響應中
-                    if body.contains(payload) || body.contains(&payload.replace("'", "\"")) {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::High,
-                            format!("XSS (跨站腳本) 漏洞: {}", description),
-                            format!(
-                                "輸入內容未正確編碼就輸出到 HTML 中，可能存在 XSS 漏洞。建議: 1) 輸出編碼 2) Content Security Policy 3) HttpOnly Cookie"
-                            ),
-                            serde_json::json!({
-                                "owasp": "A03:2021",
-                                "type": "XSS",
-                                "payload": payload,
-                                "description": description,
-                                "url": test_url
-                            })
-                        ));
+                        if has_sql_error(&body) {
+                            results.push(self.create_result(
+                                task_id,
+                                Severity::Critical,
+                                format!("SQL Injection 漏洞: {}", description),
+                                format!(
+                                    "使用 payload '{}' 觸發了資料庫錯誤訊息，確認存在 SQL 注入漏洞。建議: 1) 使用參數化查詢 2) 使用 ORM 3) 輸入驗證",
+                                    payload
+                                ),
+                                serde_json::json!({
+                                    "owasp": "A03:2021",
+                                    "type": "SQL Injection",
+                                    "parameter": param,
+                                    "payload": payload,
+                                    "description": description,
+                                    "url": test_url
+                                })
+                            ));
+                            break;
+                        }
+                    },
+                    Err(_) => continue,
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// 送出請求並保留比較用的摘要；請求失敗時為 None
+    async fn snapshot(&self, request: ScanRequest) -> Option<Snapshot> {
+        let response = request.send().await.ok()?;
+        let status = response.status().as_u16();
+        Some(Snapshot::new(status, client::text(response).await))
+    }
+
+    /// GET 參數的 NoSQL 運算子注入：param[$ne]=1 與 param[$gt]= 的回應與基準、控制請求比較
+    async fn check_nosql_injection(&self, task_id: &str, points: &[(String, String)]) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+
+        for (endpoint, param) in points {
+            let Some(baseline) = self.snapshot(self.client.get(endpoint)).await else { continue };
+            let Some(control) = self.snapshot(self.client.get(nosql::control_url(endpoint, param))).await else { continue };
+            for (payload, suffix) in nosql::QUERY_OPERATORS {
+                let test_url = nosql::with_operator(endpoint, param, suffix);
+                let Some(mutated) = self.snapshot(self.client.get(&test_url)).await else { continue };
+                let Some(reason) = nosql::verdict(&baseline, &mutated, &control) else { continue };
+                results.push(self.nosql_result(task_id, &test_url, param, &format!("{}{}", param, payload), &reason, [&baseline, &mutated, &control]));
+                break;
+            }
+        }
+
+        results
+    }
+
+    /// API 文件中接受 JSON 的操作：字串欄位換成 {"$ne": null}，控制請求換成沒有運算子的物件
+    async fn check_nosql_json_injection(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let Ok(origin) = reqwest::Url::parse(base_url).map(|url| url.origin().ascii_serialization()) else {
+            return results;
+        };
+        let operations: Vec<api_docs::JsonOperation> = self
+            .api_docs(base_url)
+            .await
+            .specs
+            .iter()
+            .flat_map(|(_, spec)| spec.json_operations(&origin))
+            .take(MAX_JSON_OPERATIONS)
+            .collect();
+
+        for operation in operations {
+            let Ok(method) = reqwest::Method::from_bytes(operation.method.as_bytes()) else { continue };
+            let clean: serde_json::Map<String, serde_json::Value> =
+                operation.fields.iter().map(|field| (field.clone(), serde_json::json!("redforge"))).collect();
+            let send = |body: &serde_json::Map<String, serde_json::Value>| {
+                self.client
+                    .request(method.clone(), &operation.url)
+                    .header("Content-Type", "application/json")
+                    .body(serde_json::to_vec(body).unwrap_or_default())
+            };
+            let Some(baseline) = self.snapshot(send(&clean)).await else { continue };
+
+            for field in operation.fields.iter().take(MAX_JSON_FIELDS) {
+                let with = |value: serde_json::Value| {
+                    let mut body = clean.clone();
+                    body.insert(field.clone(), value);
+                    body
+                };
+                // 控制請求先送出，證據記錄的是最後一次 (運算子) 的請求
+                let Some(control) = self.snapshot(send(&with(nosql::json_control()))).await else { continue };
+                let mutated_body = with(nosql::json_operator());
+                let Some(mutated) = self.snapshot(send(&mutated_body)).await else { continue };
+                let Some(reason) = nosql::verdict(&baseline, &mutated, &control) else { continue };
+                let payload = serde_json::Value::Object(mutated_body).to_string();
+                results.push(self.nosql_result(task_id, &operation.url, field, &payload, &reason, [&baseline, &mutated, &control]));
+                break;
+            }
+        }
+
+        results
+    }
+
+    /// NoSQL 注入發現；snapshots 依序為基準、運算子與控制請求的回應
+    fn nosql_result(&self, task_id: &str, url: &str, param: &str, payload: &str, reason: &str, snapshots: [&Snapshot; 3]) -> ScanResult {
+        let [baseline, mutated, control] = snapshots;
+        self.create_result(
+            task_id,
+            Severity::High,
+            format!("NoSQL 注入: {}", param),
+            format!(
+                "參數 {} 以查詢運算子 {} 送出時回應明顯不同 ({})，且與沒有運算子的控制請求不同，查詢可能直接使用了使用者輸入的物件。建議: 1) 將輸入轉為字串或以 schema 驗證型別 2) 過濾以 $ 開頭的鍵 3) 啟用 MongoDB 驅動的 sanitizeFilter",
+                param, payload, reason
+            ),
+            serde_json::json!({
+                "owasp": "A03:2021",
+                "type": "NoSQL Injection",
+                "parameter": param,
+                "payload": payload,
+                "url": url,
+                "reason": reason,
+                "baseline": baseline.summary(),
+                "mutated": mutated.summary(),
+                "control": control.summary()
+            }),
+        )
+    }
+
+    /// 反射型 XSS：每個請求使用獨立的 canary 標記，依標記在回應中的語境判斷，只有可執行的語境才回報
+    async fn check_xss(&self, task_id: &str, points: &[(String, String)]) -> ScannerResult<Vec<ScanResult>> {
+        let mut results = Vec::new();
+
+        for (endpoint, param) in points {
+            for template in xss_context::PROBES {
+                let marker = xss_context::marker();
+                let payload = xss_context::payload(template, &marker);
+                let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(&payload));
+
+                let Ok(response) = self.client.get(&test_url).send().await else { continue };
+                let is_html = xss_context::is_html(response.headers());
+                let body = client::text(response).await;
+
+                let reflections = xss_context::analyze(&body, &marker, is_html);
+                let Some(reflection) = xss_context::executable(&reflections) else { continue };
+                results.push(self.xss_result(task_id, &test_url, param, &payload, &marker, reflection, None));
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// XSS 發現；form 為表單送出時的額外證據
+    #[allow(clippy::too_many_arguments)]
+    fn xss_result(
+        &self,
+        task_id: &str,
+        url: &str,
+        param: &str,
+        payload: &str,
+        marker: &str,
+        reflection: &xss_context::Reflection,
+        form: Option<(&crawler::Form, u16)>,
+    ) -> ScanResult {
+        let mut raw_data = serde_json::json!({
+            "owasp": "A03:2021",
+            "type": "XSS",
+            "parameter": param,
+            "payload": payload,
+            "canary": marker,
+            "reflection_context": reflection.context.as_str(),
+            "evidence": reflection.snippet,
+            "url": url,
+        });
+        if let Some((form, status)) = form {
+            raw_data["form_page"] = serde_json::json!(form.page);
+            raw_data["form_method"] = serde_json::json!(form.method);
+            raw_data["form_enctype"] = serde_json::json!(form.enctype);
+            raw_data["csrf_token_field"] = serde_json::json!(form.csrf_field().map(|f| f.name.clone()));
+            raw_data["status"] = serde_json::json!(status);
+        }
+        self.create_result(
+            task_id,
+            Severity::High,
+            format!(
+                "{}XSS (跨站腳本) 漏洞: 參數 {} 反射於 {}",
+                if form.is_some() { "表單 " } else { "" },
+                param,
+                reflection.context.description()
+            ),
+            format!(
+                "參數 {} 的 payload '{}' 未經編碼反射到{}，攻擊者可在頁面中執行腳本。反射內容: {}。建議: 1) 依輸出語境編碼 (HTML / 屬性 / JavaScript) 2) Content Security Policy 3) HttpOnly Cookie",
+                param,
+                payload,
+                reflection.context.description(),
+                reflection.snippet
+            ),
+            raw_data,
+        )
+    }
+
+    /// 時間盲注：以乾淨請求的中位數為基準，3 秒與 6 秒的延遲都成立才回報
+    ///
+    /// 整個檢查受 blind_sqli::TIME_BUDGET 限制，用完後保留已確認的發現並記錄未完成
+    async fn check_time_based_sql_injection(&self, task_id: &str, points: &[(String, String)]) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let points = &points[..points.len().min(blind_sqli::MAX_POINTS)];
+
+        let sweep = async {
+            for (endpoint, param) in points {
+                let mut samples = Vec::new();
+                for _ in 0..blind_sqli::BASELINE_SAMPLES {
+                    if let Some(elapsed) = self.timed_get(endpoint).await {
+                        samples.push(elapsed);
+                    }
+                }
+                let Some(baseline) = blind_sqli::median(&samples) else { continue };
+
+                for (template, dbms) in blind_sqli::PAYLOADS {
+                    let mut timings = Vec::new();
+                    for delay in blind_sqli::DELAYS_SECS {
+                        let payload = blind_sqli::payload(template, delay);
+                        let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(&payload));
+                        let Some(elapsed) = self.timed_get(&test_url).await else { break };
+                        timings.push((delay, elapsed));
+                        // 第一個延遲不成立就不必送出較長的延遲
+                        if !blind_sqli::is_delayed(baseline, elapsed, delay) {
+                            break;
+                        }
+                    }
+                    if !blind_sqli::confirms(baseline, &timings) {
+                        continue;
+                    }
+
+                    let payload = blind_sqli::payload(template, blind_sqli::DELAYS_SECS[1]);
+                    let ms = |d: std::time::Duration| d.as_millis() as u64;
+                    let finding = self.create_result(
+                        task_id,
+                        Severity::Critical,
+                        format!("SQL Injection 漏洞: 時間盲注 ({})", dbms),
+                        format!(
+                            "參數 {} 的 {} 延遲 payload 讓回應時間隨延遲秒數增加 (基準 {} ms，{} 秒延遲 {} ms，{} 秒延遲 {} ms)，資料庫執行了注入的語句。建議: 1) 使用參數化查詢 2) 使用 ORM 3) 輸入驗證",
+                            param, dbms, ms(baseline), timings[0].0, ms(timings[0].1), timings[1].0, ms(timings[1].1)
+                        ),
+                        serde_json::json!({
+                            "owasp": "A03:2021",
+                            "type": "SQL Injection",
+                            "technique": "time_based",
+                            "dbms": dbms,
+                            "parameter": param,
+                            "payload": payload,
+                            "url": path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(&payload)),
+                            "baseline_ms": ms(baseline),
+                            "baseline_samples_ms": samples.iter().map(|d| ms(*d)).collect::<Vec<_>>(),
+                            "timings": timings.iter().map(|(delay, elapsed)| serde_json::json!({ "delay_secs": delay, "elapsed_ms": ms(*elapsed) })).collect::<Vec<_>>(),
+                        }),
+                    );
+                    results.push(finding);
+                    break;
+                }
+            }
+        };
+
+        if tokio::time::timeout(blind_sqli::TIME_BUDGET, sweep).await.is_err() {
+            self.add_note(
+                "A03:2021",
+                format!("時間盲注檢查超過 {} 秒的時間預算，未測試完所有參數", blind_sqli::TIME_BUDGET.as_secs()),
+                serde_json::json!({ "check": "time_based_sql_injection", "budget_secs": blind_sqli::TIME_BUDGET.as_secs() }),
+            );
+        }
+
+        results
+    }
+
+    /// 送出 GET 並讀完內容，回傳耗時；逾時也算完成 (耗時即逾時秒數)，其他錯誤回傳 None
+    async fn timed_get(&self, url: &str) -> Option<std::time::Duration> {
+        let started = std::time::Instant::now();
+        match self.client.get(url).send().await {
+            Ok(response) => {
+                let _ = response.bytes().await;
+                Some(started.elapsed())
+            }
+            Err(ScannerError::Timeout { .. }) => Some(started.elapsed()),
+            Err(_) => None,
+        }
+    }
+
+    /// 對爬取到的非 GET 表單送出 SQL 注入與 XSS payload：payload 只放在文字欄位，其他欄位保留預設值
+    ///
+    /// 有 CSRF token 的表單每次送出前重新取得頁面，使用頁面上最新的 token (與同一 session 的 cookie)
+    async fn check_form_injection(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let forms: Vec<crawler::Form> = self.crawl(base_url).await
+            .forms
+            .iter()
+            .filter(|form| form.method != "GET" && form.fields.iter().any(|f| f.is_text_like()))
+            .take(MAX_FORMS)
+            .cloned()
+            .collect();
+
+        for form in &forms {
+            let fields: Vec<String> = form.fields.iter().filter(|f| f.is_text_like()).take(MAX_FORM_FIELDS).map(|f| f.name.clone()).collect();
+            for field in &fields {
+                for (payload, description) in SQL_PAYLOADS {
+                    let Some((status, _, body)) = self.submit_form(form, field, payload).await else { continue };
+                    if !has_sql_error(&body) {
+                        continue;
+                    }
+                    results.push(self.create_result(
+                        task_id,
+                        Severity::Critical,
+                        format!("表單 SQL Injection 漏洞: {}", description),
+                        format!(
+                            "以 {} 送出 {} 的表單 (action: {})，欄位 {} 使用 payload '{}' 後回應 HTTP {} 包含資料庫錯誤訊息。建議: 使用參數化查詢或 ORM，並驗證輸入",
+                            form.method,
+                            form.page,
+                            form.action,
+                            field,
+                            payload,
+                            status
+                        ),
+                        serde_json::json!({
+                            "owasp": "A03:2021",
+                            "type": "SQL Injection",
+                            "parameter": field,
+                            "payload": payload,
+                            "description": description,
+                            "url": form.action,
+                            "form_page": form.page,
+                            "form_method": form.method,
+                            "form_enctype": form.enctype,
+                            "csrf_token_field": form.csrf_field().map(|f| f.name.clone()),
+                            "status": status,
+                        }),
+                    ));
+                    break;
+                }
+
+                for template in xss_context::PROBES {
+                    let marker = xss_context::marker();
+                    let payload = xss_context::payload(template, &marker);
+                    let Some((status, is_html, body)) = self.submit_form(form, field, &payload).await else { continue };
+                    let reflections = xss_context::analyze(&body, &marker, is_html);
+                    if let Some(reflection) = xss_context::executable(&reflections) {
+                        results.push(self.xss_result(task_id, &form.action, field, &payload, &marker, reflection, Some((form, status))));
                         break;
                     }
-                },
-                Err(_) => continue,
+                }
             }
         }
 
-        Ok(results)
+        results
     }
 
-    async fn check_command_injection(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    /// 送出表單並回傳 (狀態碼, 回應是否為 HTML, 內容)；有 CSRF token 時先重新取得頁面中同一表單的最新欄位值
+    async fn submit_form(&self, form: &crawler::Form, field: &str, payload: &str) -> Option<(u16, bool, String)> {
+        let mut current = form.clone();
+        if form.csrf_field().is_some() {
+            if let Ok(response) = self.form_client.get(&form.page).send().await {
+                let html = client::text(response).await;
+                if let Some(fresh) = crawler::extract(&form.page, &html)
+                    .forms
+                    .into_iter()
+                    .find(|f| f.action == form.action && f.method == form.method)
+                {
+                    current = fresh;
+                }
+            }
+        }
+
+        let (content_type, body) = current.encode(&current.values_with(field, payload));
+        let method = reqwest::Method::from_bytes(current.method.as_bytes()).unwrap_or(reqwest::Method::POST);
+        let response = self.form_client
+            .request(method, current.action.as_str())
+            .header("Content-Type", &content_type)
+            .body(body)
+            .send()
+            .await
+            .ok()?;
+        let status = response.status().as_u16();
+        let is_html = xss_context::is_html(response.headers());
+        Some((status, is_html, client::text(response).await))
+    }
+
+    async fn check_command_injection(&self, task_id: &str, points: &[(String, String)]) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
         let command_payloads = vec![
@@ -442,42 +1460,45 @@ impl OwaspScanner {
             ("|| ls", "OR operator"),
         ];
 
-        for (payload, description) in command_payloads {
-            let test_url = format!("{}?cmd={}", base_url, urlencoding::encode(payload));
+        for (endpoint, param) in points {
+            for (payload, description) in &command_payloads {
+                let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(payload));
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let body = response.text().await.unwrap_or_default();
+                match self.client.get(&test_url).send().await {
+                    Ok(response) => {
+                        let body = client::text(response).await;
 
-                    // 檢查命令執行的特徵
-                    if body.contains("bin") || body.contains("usr") || body.contains("etc") {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::Critical,
-                            format!("命令注入漏洞: {}", description),
-                            format!(
-                                "使用 payload '{}' 可能觸發了命令執行，存在 OS 命令注入漏洞。建議: 1) 避免調用系統命令 2) 使用白名單驗證 3) 使用安全的 API",
-                                payload
-                            ),
-                            serde_json::json!({
-                                "owasp": "A03:2021",
-                                "type": "Command Injection",
-                                "payload": payload,
-                                "description": description,
-                                "url": test_url
-                            })
-                        ));
-                        break;
-                    }
-                },
-                Err(_) => continue,
+                        // 檢查命令執行的特徵
+                        if body.contains("bin") || body.contains("usr") || body.contains("etc") {
+                            results.push(self.create_result(
+                                task_id,
+                                Severity::Critical,
+                                format!("命令注入漏洞: {}", description),
+                                format!(
+                                    "使用 payload '{}' 可能觸發了命令執行，存在 OS 命令注入漏洞。建議: 1) 避免調用系統命令 2) 使用白名單驗證 3) 使用安全的 API",
+                                    payload
+                                ),
+                                serde_json::json!({
+                                    "owasp": "A03:2021",
+                                    "type": "Command Injection",
+                                    "parameter": param,
+                                    "payload": payload,
+                                    "description": description,
+                                    "url": test_url
+                                })
+                            ));
+                            break;
+                        }
+                    },
+                    Err(_) => continue,
+                }
             }
         }
 
         Ok(results)
     }
 
-    async fn check_ldap_injection(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn check_ldap_injection(&self, task_id: &str, points: &[(String, String)]) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
         let ldap_payloads = vec![
@@ -486,33 +1507,36 @@ impl OwaspScanner {
             ("*)(uid=*))(|(uid=*", "Complex LDAP injection"),
         ];
 
-        for (payload, description) in ldap_payloads {
-            let test_url = format!("{}?user={}", base_url, urlencoding::encode(payload));
+        for (endpoint, param) in points {
+            for (payload, description) in &ldap_payloads {
+                let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(payload));
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
+                match self.client.get(&test_url).send().await {
+                    Ok(response) => {
+                        let status = response.status();
+                        let body = client::text(response).await;
 
-                    // 檢查 LDAP 錯誤或異常行為
-                    if body.to_lowercase().contains("ldap") || status.as_u16() == 500 {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::High,
-                            format!("潛在的 LDAP 注入: {}", description),
-                            "應用程序可能存在 LDAP 注入漏洞，攻擊者可能繞過身份驗證或提取敏感資訊".to_string(),
-                            serde_json::json!({
-                                "owasp": "A03:2021",
-                                "type": "LDAP Injection",
-                                "payload": payload,
-                                "description": description,
-                                "url": test_url
-                            })
-                        ));
-                        break;
-                    }
-                },
-                Err(_) => continue,
+                        // 檢查 LDAP 錯誤或異常行為
+                        if body.to_lowercase().contains("ldap") || status.as_u16() == 500 {
+                            results.push(self.create_result(
+                                task_id,
+                                Severity::High,
+                                format!("潛在的 LDAP 注入: {}", description),
+                                "應用程序可能存在 LDAP 注入漏洞，攻擊者可能繞過身份驗證或提取敏感資訊".to_string(),
+                                serde_json::json!({
+                                    "owasp": "A03:2021",
+                                    "type": "LDAP Injection",
+                                    "parameter": param,
+                                    "payload": payload,
+                                    "description": description,
+                                    "url": test_url
+                                })
+                            ));
+                            break;
+                        }
+                    },
+                    Err(_) => continue,
+                }
             }
         }
 
@@ -522,266 +1546,912 @@ impl OwaspScanner {
     // ========================================================================
     // A04: Insecure Design
     // ========================================================================
-    async fn a04_insecure_design(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn a04_insecure_design(&self, task_id: &str, base_url: &str, base: Option<&BaseResponse>) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
-        match self.client.get(base_url).send().await {
-            Ok(response) => {
-                let body = response.text().await.unwrap_or_default();
-
-                // 檢查是否缺少速率限制 (通過多次請求測試)
-                let mut success_count = 0;
-                for _ in 0..10 {
-                    if let Ok(r) = self.client.get(base_url).send().await {
-                        if r.status().is_success() {
-                            success_count += 1;
-                        }
-                    }
-                }
+        // 檢查認證端點是否缺少速率限制 (突發請求測試)
+        if self.allows("rate_limit") {
+            results.extend(self.rate_limit_burst(task_id, base_url).await);
+        }
 
-                if success_count == 10 {
-                    results.push(self.create_result(
-                        task_id,
-                        Severity::Medium,
-                        "缺少速率限制 (Rate Limiting)".to_string(),
-                        "應用程序未實施速率限制，可能遭受暴力破解、DDoS 攻擊。建議: 實施請求速率限制和 IP 黑名單".to_string(),
-                        serde_json::json!({
-                            "owasp": "A04:2021",
-                            "type": "No Rate Limiting",
-                            "test_requests": 10
-                        })
-                    ));
-                }
+        if let Some(base) = base {
+            let body = &base.body;
+
+            // 檢查是否有明顯的用戶枚舉問題
+            if body.contains("User not found") || body.contains("Invalid username") {
+                results.push(self.create_result(
+                    task_id,
+                    Severity::Medium,
+                    "用戶枚舉漏洞".to_string(),
+                    "登錄失敗時區分用戶名不存在和密碼錯誤，攻擊者可枚舉有效用戶名。建議: 使用統一的錯誤訊息".to_string(),
+                    serde_json::json!({
+                        "owasp": "A04:2021",
+                        "type": "User Enumeration"
+                    })
+                ));
+            }
+        }
 
-                // 檢查是否有明顯的用戶枚舉問題
-                if body.contains("User not found") || body.contains("Invalid username") {
-                    results.push(self.create_result(
-                        task_id,
-                        Severity::Medium,
-                        "用戶枚舉漏洞".to_string(),
-                        "登錄失敗時區分用戶名不存在和密碼錯誤，攻擊者可枚舉有效用戶名。建議: 使用統一的錯誤訊息".to_string(),
-                        serde_json::json!({
-                            "owasp": "A04:2021",
-                            "type": "User Enumeration"
-                        })
-                    ));
+        Ok(results)
+    }
+
+    /// 對爬取找到的認證端點送出突發請求；沒有認證端點時改測首頁並降為 Low
+    ///
+    /// 突發大小不超過掃描剩餘的請求數，不足 MIN_BURST 時不測試
+    async fn rate_limit_burst(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let mut targets = rate_limit_probe::targets(self.crawl(base_url).await);
+        let homepage_only = targets.is_empty();
+        if homepage_only {
+            targets.push(Target { url: base_url.to_string(), method: "GET".to_string(), body: None, label: "首頁" });
+        }
+
+        for target in targets {
+            let burst = control::remaining_requests().map_or(self.rate_limit_settings.burst, |remaining| {
+                self.rate_limit_settings.burst.min(remaining as usize)
+            });
+            if burst < rate_limit_probe::MIN_BURST {
+                self.add_note(
+                    "A04:2021",
+                    format!("掃描剩餘的請求數不足 {} 個，未測試 {} 的速率限制", rate_limit_probe::MIN_BURST, target.url),
+                    serde_json::json!({ "check": "rate_limit", "url": target.url, "reason": "request_budget" }),
+                );
+                break;
+            }
+            let Ok(method) = reqwest::Method::from_bytes(target.method.as_bytes()) else { continue };
+
+            let mut samples = Vec::new();
+            for index in 0..burst {
+                if index > 0 && control::interruptible(tokio::time::sleep(rate_limit_probe::jitter(self.rate_limit_settings.jitter_ms))).await.is_err() {
+                    break;
                 }
-            },
-            Err(_) => {},
+                let mut request = self.client.request(method.clone(), &target.url);
+                if let Some((content_type, body)) = &target.body {
+                    request = request.header("Content-Type", content_type).body(body.clone());
+                }
+                let started = std::time::Instant::now();
+                let Ok(response) = request.send().await else { continue };
+                let status = response.status().as_u16();
+                let body = client::text(response).await;
+                samples.push(Sample::new(status, started.elapsed(), &body));
+            }
+            if samples.len() < rate_limit_probe::MIN_BURST {
+                continue;
+            }
+
+            let outcome = rate_limit_probe::analyze(&samples);
+            if let Some(reason) = &outcome.limited_by {
+                self.add_note(
+                    "A04:2021",
+                    format!("{} ({}) 在 {} 個連續請求內開始限制: {}", target.label, target.url, samples.len(), reason),
+                    serde_json::json!({ "check": "rate_limit", "url": target.url, "statuses": outcome.status_distribution() }),
+                );
+                continue;
+            }
+            results.push(self.create_result(
+                task_id,
+                if homepage_only { Severity::Low } else { Severity::Medium },
+                "缺少速率限制 (Rate Limiting)".to_string(),
+                if homepage_only {
+                    format!(
+                        "爬取未找到登入或密碼重設端點，改以首頁測試：{} 個連續請求都沒有被限制 (沒有 429/403、驗證碼或變慢)。首頁通常不需要速率限制，請手動確認認證端點。建議: 對登入、密碼重設與 token 端點實施速率限制",
+                        samples.len()
+                    )
+                } else {
+                    format!(
+                        "{} ({}) 在 {} 個連續請求內都沒有被限制 (沒有 429/403、驗證碼或變慢)，可能遭受暴力破解。建議: 依帳號與來源 IP 實施速率限制，並在多次失敗後要求驗證碼",
+                        target.label,
+                        target.url,
+                        samples.len()
+                    )
+                },
+                serde_json::json!({
+                    "owasp": "A04:2021",
+                    "type": "No Rate Limiting",
+                    "url": target.url,
+                    "method": target.method,
+                    "endpoint": target.label,
+                    "test_requests": samples.len(),
+                    "status_distribution": outcome.status_distribution(),
+                    "first_median_ms": outcome.first_median_ms,
+                    "last_median_ms": outcome.last_median_ms,
+                    "homepage_only": homepage_only
+                }),
+            ));
         }
 
-        Ok(results)
+        results
     }
 
     // ========================================================================
     // A05: Security Misconfiguration
     // ========================================================================
-    async fn a05_security_misconfiguration(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn a05_security_misconfiguration(&self, task_id: &str, base_url: &str, base: Option<&BaseResponse>) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
         // 檢查敏感文件
-        let sensitive_files = vec![
-            ("/.git/config", Severity::Critical, "Git 配置文件"),
-            ("/.env", Severity::Critical, "環境變數文件"),
-            ("/config.php", Severity::High, "PHP 配置文件"),
-            ("/wp-config.php", Severity::High, "WordPress 配置"),
-            ("/.htaccess", Severity::Medium, "Apache 配置"),
-            ("/phpinfo.php", Severity::High, "PHP 資訊頁面"),
-            ("/web.config", Severity::High, "IIS 配置"),
-            ("/backup.sql", Severity::Critical, "資料庫備份"),
-            ("/database.sql", Severity::Critical, "資料庫備份"),
-            ("/.DS_Store", Severity::Low, "macOS 系統文件"),
-            ("/robots.txt", Severity::Info, "Robots 文件"),
-            ("/sitemap.xml", Severity::Info, "網站地圖"),
-        ];
-
-        for (path, severity, description) in sensitive_files {
-            let test_url = format!("{}{}", base_url.trim_end_matches('/'), path);
+        if self.allows("sensitive_files") {
+            let sensitive_files = vec![
+                ("/.git/config", Severity::Critical, "Git 配置文件"),
+                ("/.env", Severity::Critical, "環境變數文件"),
+                ("/config.php", Severity::High, "PHP 配置文件"),
+                ("/wp-config.php", Severity::High, "WordPress 配置"),
+                ("/.htaccess", Severity::Medium, "Apache 配置"),
+                ("/phpinfo.php", Severity::High, "PHP 資訊頁面"),
+                ("/web.config", Severity::High, "IIS 配置"),
+                ("/backup.sql", Severity::Critical, "資料庫備份"),
+                ("/database.sql", Severity::Critical, "資料庫備份"),
+                ("/.DS_Store", Severity::Low, "macOS 系統文件"),
+                ("/robots.txt", Severity::Info, "Robots 文件"),
+                ("/sitemap.xml", Severity::Info, "網站地圖"),
+            ];
+
+            let allowed_paths = self.skip_unmet_preconditions("A05:2021", sensitive_files.iter().map(|(path, _, _)| *path).collect());
+            let sensitive_files: Vec<_> = sensitive_files.into_iter().filter(|(path, _, _)| allowed_paths.contains(path)).collect();
+            let urls: Vec<String> = sensitive_files.iter().map(|(path, _, _)| format!("{}{}", base_url.trim_end_matches('/'), path)).collect();
+            let soft_404 = self.soft_404(base_url).await;
+            let pages = self.fetch_all(&urls, true).await;
+            let mut soft_404_paths = Vec::new();
+
+            for ((path, severity, description), (test_url, page)) in sensitive_files.into_iter().zip(urls.into_iter().zip(pages)) {
+                match page {
+                    Some(page) => {
+                        if !(200..300).contains(&page.status) {
+                            continue;
+                        }
+                        if soft_404.matches(page.status, &page.body) {
+                            soft_404_paths.push(path);
+                            continue;
+                        }
+                        let status = page.status;
+                        let body = page.body;
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        results.push(self.create_result(
-                            task_id,
-                            severity,
-                            format!("發現可訪問的敏感文件: {}", description),
-                            format!(
-                                "{} ({}) 可以被公開訪問，這可能洩露重要的配置資訊或原始碼",
-                                description, path
-                            ),
-                            serde_json::json!({
-                                "owasp": "A05:2021",
-                                "path": path,
-                                "url": test_url,
-                                "status": response.status().as_u16()
-                            })
-                        ));
-                    }
-                },
-                Err(_) => continue,
+                        // 依檔案類型驗證內容，200 但內容不符的多半是 catch-all 頁面
+                        match sensitive_files::validate(path, &body) {
+                            FileValidation::Confirmed { validator, excerpt } => {
+                                results.push(self.create_result(
+                                    task_id,
+                                    severity,
+                                    format!("發現可訪問的敏感文件: {}", description),
+                                    format!(
+                                        "{} ({}) 可以被公開訪問，這可能洩露重要的配置資訊或原始碼",
+                                        description, path
+                                    ),
+                                    serde_json::json!({
+                                        "owasp": "A05:2021",
+                                        "path": path,
+                                        "url": test_url,
+                                        "status": status,
+                                        "validator": validator,
+                                        "excerpt": excerpt
+                                    })
+                                ));
+                            }
+                            FileValidation::Rejected { validator, reason } => {
+                                tracing::info!("ℹ️  {} 回傳 {} 但內容驗證失敗 ({}: {})，視為誤判", test_url, status, validator, reason);
+                                self.add_note(
+                                    "A05:2021",
+                                    format!("{} 回傳 {} 但未通過內容驗證，可能為誤判", path, status),
+                                    serde_json::json!({ "path": path, "validator": validator, "reason": reason }),
+                                );
+                            }
+                            FileValidation::Unverified => {
+                                results.push(self.create_result(
+                                    task_id,
+                                    severity,
+                                    format!("發現可訪問的敏感文件: {}", description),
+                                    format!(
+                                        "{} ({}) 可以被公開訪問，這可能洩露重要的配置資訊或原始碼",
+                                        description, path
+                                    ),
+                                    serde_json::json!({
+                                        "owasp": "A05:2021",
+                                        "path": path,
+                                        "url": test_url,
+                                        "status": status
+                                    })
+                                ));
+                            }
+                        }
+                    },
+                    None => continue,
+                }
             }
+
+            self.note_soft_404("A05:2021", soft_404_paths);
+        }
+
+        if self.allows("backup_files") {
+            results.extend(self.backup_files(task_id, base_url).await);
+        }
+
+        if self.allows("api_docs") {
+            results.extend(self.api_documentation(task_id, base_url).await);
+        }
+
+        if self.allows("graphql") {
+            results.extend(self.graphql_introspection(task_id, base_url).await);
+        }
+
+        if self.allows("host_header") {
+            results.extend(self.host_header_injection(task_id, base_url).await);
         }
 
         // 檢查目錄列表
-        let directories = vec!["/uploads", "/images", "/static", "/assets", "/backup", "/tmp"];
-        for dir in directories {
-            let test_url = format!("{}{}", base_url.trim_end_matches('/'), dir);
+        if self.allows("directory_listing") {
+            let directories = vec!["/uploads", "/images", "/static", "/assets", "/backup", "/tmp"];
+            let urls: Vec<String> = directories.iter().map(|dir| format!("{}{}", base_url.trim_end_matches('/'), dir)).collect();
+            let pages = self.fetch_all(&urls, true).await;
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let body = response.text().await.unwrap_or_default();
+            for ((dir, test_url), page) in directories.into_iter().zip(urls).zip(pages) {
+                match page {
+                    Some(page) => {
+                        let body = String::from_utf8_lossy(&page.body);
 
-                    if body.contains("Index of") || body.contains("Directory listing") || body.contains("Parent Directory") {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::Medium,
-                            format!("發現目錄列表: {}", dir),
-                            format!(
-                                "目錄 {} 啟用了目錄列表功能，可能洩露文件結構和敏感文件名稱",
-                                dir
-                            ),
-                            serde_json::json!({
-                                "owasp": "A05:2021",
-                                "type": "Directory Listing",
-                                "path": dir,
-                                "url": test_url
-                            })
-                        ));
-                    }
-                },
-                Err(_) => continue,
+                        if body.contains("Index of") || body.contains("Directory listing") || body.contains("Parent Directory") {
+                            results.push(self.create_result(
+                                task_id,
+                                Severity::Medium,
+                                format!("發現目錄列表: {}", dir),
+                                format!(
+                                    "目錄 {} 啟用了目錄列表功能，可能洩露文件結構和敏感文件名稱",
+                                    dir
+                                ),
+                                serde_json::json!({
+                                    "owasp": "A05:2021",
+                                    "type": "Directory Listing",
+                                    "path": dir,
+                                    "url": test_url
+                                })
+                            ));
+                        }
+                    },
+                    None => continue,
+                }
             }
         }
 
         // 檢查 HTTP 安全標頭
-        match self.client.get(base_url).send().await {
-            Ok(response) => {
-                let headers = response.headers();
+        if let Some(base) = base {
+            let headers = &base.headers;
+
+            // 檢查關鍵安全標頭
+            if !headers.contains_key("strict-transport-security") {
+                results.push(self.create_result(
+                    task_id,
+                    Severity::Medium,
+                    "缺少 Strict-Transport-Security 標頭".to_string(),
+                    "未設置 HSTS，瀏覽器可能使用不安全的 HTTP 連接。建議: 添加 Strict-Transport-Security 標頭".to_string(),
+                    serde_json::json!({
+                        "owasp": "A05:2021",
+                        "header": "Strict-Transport-Security"
+                    })
+                ));
+            }
 
-                // 檢查關鍵安全標頭
-                if !headers.contains_key("strict-transport-security") {
-                    results.push(self.create_result(
-                        task_id,
-                        Severity::Medium,
-                        "缺少 Strict-Transport-Security 標頭".to_string(),
-                        "未設置 HSTS，瀏覽器可能使用不安全的 HTTP 連接。建議: 添加 Strict-Transport-Security 標頭".to_string(),
-                        serde_json::json!({
-                            "owasp": "A05:2021",
-                            "header": "Strict-Transport-Security"
-                        })
-                    ));
+            if !headers.contains_key("x-frame-options") && !headers.contains_key("content-security-policy") {
+                results.push(self.create_result(
+                    task_id,
+                    Severity::Medium,
+                    "缺少 Clickjacking 防護".to_string(),
+                    "未設置 X-Frame-Options 或 CSP frame-ancestors，可能遭受 Clickjacking 攻擊".to_string(),
+                    serde_json::json!({
+                        "owasp": "A05:2021",
+                        "header": "X-Frame-Options / CSP"
+                    })
+                ));
+            }
+
+            if !headers.contains_key("content-security-policy") {
+                results.push(self.create_result(
+                    task_id,
+                    Severity::Low,
+                    "缺少 Content-Security-Policy 標頭".to_string(),
+                    "未設置 CSP，無法防禦 XSS 和資料注入攻擊。建議: 實施嚴格的 CSP 策略".to_string(),
+                    serde_json::json!({
+                        "owasp": "A05:2021",
+                        "header": "Content-Security-Policy"
+                    })
+                ));
+            }
+        }
+
+        if self.allows("cors") {
+            results.extend(self.check_cors(task_id, base_url).await);
+        }
+        if self.allows("http_methods") {
+            results.extend(self.check_http_methods(task_id, base_url).await);
+        }
+
+        Ok(results)
+    }
+
+    /// 列舉 OPTIONS 宣告的方法，並實際驗證 TRACE 回顯、PUT / DELETE 可寫入與未驗證的 PATCH
+    ///
+    /// PUT 只寫入隨機檔名的測試檔並在確認後刪除，DELETE 只對該測試檔或不存在的隨機路徑送出；
+    /// 伺服器拒絕 OPTIONS 或請求失敗時略過該步驟，不會讓整個檢查失敗
+    async fn check_http_methods(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let base = base_url.trim_end_matches('/');
+        let method_result = |severity: Severity, title: String, description: String, check_id: &str, method: &str, url: &str, status: u16, advertised: &[String]| {
+            self.create_result(task_id, severity, title, description, serde_json::json!({
+                "owasp": "A05:2021",
+                "check_id": check_id,
+                "method": method,
+                "url": url,
+                "status": status,
+                "advertised_methods": advertised,
+            }))
+        };
+
+        let (page, page_status) = match self.base_response(base_url).await {
+            Some(base) => (base.body.clone(), base.status),
+            None => (String::new(), 0),
+        };
+        let mut paths = vec![base_url.to_string()];
+        paths.extend(http_methods::discovered_paths(&page, base_url));
+
+        // OPTIONS 宣告的方法，依路徑記錄
+        let mut advertised: Vec<(String, Vec<String>)> = Vec::new();
+        for path in &paths {
+            if let Ok(response) = self.client.request(reqwest::Method::OPTIONS, path.as_str()).send().await {
+                let methods = http_methods::advertised_methods(response.headers());
+                if !methods.is_empty() {
+                    advertised.push((path.clone(), methods));
                 }
+            }
+        }
+        let advertised_for = |url: &str| advertised.iter().find(|(path, _)| path == url).map(|(_, m)| m.clone()).unwrap_or_default();
+        let announced: Vec<String> = advertised.iter().flat_map(|(_, m)| http_methods::dangerous(m)).collect();
+        if !announced.is_empty() {
+            self.add_note(
+                "A05:2021",
+                "OPTIONS 宣告了危險的方法，已逐一實際驗證".to_string(),
+                serde_json::json!({ "advertised": advertised.iter().map(|(path, m)| serde_json::json!({ "url": path, "methods": m })).collect::<Vec<_>>() }),
+            );
+        }
 
-                if !headers.contains_key("x-frame-options") && !headers.contains_key("content-security-policy") {
-                    results.push(self.create_result(
-                        task_id,
-                        Severity::Medium,
-                        "缺少 Clickjacking 防護".to_string(),
-                        "未設置 X-Frame-Options 或 CSP frame-ancestors，可能遭受 Clickjacking 攻擊".to_string(),
-                        serde_json::json!({
-                            "owasp": "A05:2021",
-                            "header": "X-Frame-Options / CSP"
-                        })
-                    ));
+        // TRACE：回應內容包含請求中的標頭代表會回顯 (Cross-Site Tracing)
+        let marker = Uuid::new_v4().to_string();
+        if let Ok(response) = self.client.request(reqwest::Method::TRACE, base_url).header(http_methods::TRACE_MARKER_HEADER, &marker).send().await {
+            let status = response.status().as_u16();
+            let body = client::text(response).await;
+            if (200..300).contains(&status) && body.contains(&marker) {
+                results.push(method_result(
+                    Severity::Low,
+                    "伺服器啟用 TRACE 方法".to_string(),
+                    format!("TRACE {} 回傳 HTTP {} 並回顯請求標頭，可被用於跨站追蹤 (XST) 讀取 HttpOnly cookie 或代理加入的標頭。建議: 在伺服器或反向代理停用 TRACE", base_url, status),
+                    "http_trace_enabled",
+                    "TRACE",
+                    base_url,
+                    status,
+                    &advertised_for(base_url),
+                ));
+            }
+        }
+
+        // PUT：寫入隨機檔名的測試檔，再以 GET 讀回確認
+        let soft_404 = self.soft_404(base_url).await;
+        let put_url = format!("{}/redforge-put-{}.txt", base, Uuid::new_v4());
+        let put_body = format!("redforge-method-probe-{}", marker);
+        let mut writable = false;
+        if let Ok(response) = self.client.request(reqwest::Method::PUT, put_url.as_str()).body(put_body.clone().into_bytes()).send().await {
+            let status = response.status().as_u16();
+            if (200..300).contains(&status) {
+                let stored = match self.client.get(put_url.as_str()).send().await {
+                    Ok(readback) => client::text(readback).await.contains(&put_body),
+                    Err(_) => false,
+                };
+                writable = stored;
+                let mut result = method_result(
+                    Severity::High,
+                    "伺服器允許以 PUT 寫入任意路徑".to_string(),
+                    format!(
+                        "PUT {} 回傳 HTTP {}{}。攻擊者可上傳網頁或腳本檔案。建議: 停用 PUT 或要求身分驗證與授權",
+                        put_url,
+                        status,
+                        if stored { "，且以 GET 讀回了寫入的內容" } else { "，但無法讀回寫入的內容" }
+                    ),
+                    "http_put_writable",
+                    "PUT",
+                    &put_url,
+                    status,
+                    &advertised_for(base_url),
+                );
+                result.low_confidence = !stored;
+                results.push(result);
+            }
+        }
+
+        // DELETE：有寫入測試檔時刪除它 (同時清理)，否則對不存在的隨機路徑送出
+        let delete_url = if writable { put_url.clone() } else { format!("{}/redforge-delete-{}", base, Uuid::new_v4()) };
+        if let Ok(response) = self.client.request(reqwest::Method::DELETE, delete_url.as_str()).send().await {
+            let status = response.status().as_u16();
+            let body = client::bytes(response).await;
+            let removed = writable
+                && matches!(self.client.get(delete_url.as_str()).send().await, Ok(r) if r.status().as_u16() == 404 || r.status().as_u16() == 410);
+            if (200..300).contains(&status) && !soft_404.matches(status, &body) {
+                let mut result = method_result(
+                    Severity::High,
+                    "伺服器允許以 DELETE 刪除任意路徑".to_string(),
+                    format!(
+                        "DELETE {} 回傳 HTTP {}{}。攻擊者可刪除網站上的檔案或資源。建議: 停用 DELETE 或要求身分驗證與授權",
+                        delete_url,
+                        status,
+                        if removed { "，測試檔已確認被刪除" } else { "" }
+                    ),
+                    "http_delete_enabled",
+                    "DELETE",
+                    &delete_url,
+                    status,
+                    &advertised_for(base_url),
+                );
+                result.low_confidence = !removed;
+                results.push(result);
+            }
+        }
+
+        // PATCH：未帶任何憑證送出空的 JSON；與 GET 回應相同代表伺服器忽略方法，不算接受
+        for path in &paths {
+            let baseline = if path == base_url {
+                (page_status, page.clone())
+            } else {
+                match self.client.get(path.as_str()).send().await {
+                    Ok(response) => {
+                        let status = response.status().as_u16();
+                        (status, client::text(response).await)
+                    }
+                    Err(_) => continue,
                 }
+            };
+            let Ok(response) = self.client
+                .request(reqwest::Method::PATCH, path.as_str())
+                .header("Content-Type", "application/json")
+                .body(b"{}".to_vec())
+                .send()
+                .await
+            else {
+                continue;
+            };
+            let status = response.status().as_u16();
+            let body = client::text(response).await;
+            let ignored = status == baseline.0 && response_diff::calculate_similarity(&body, &baseline.1) >= 0.9;
+            if (200..300).contains(&status) && !ignored && !soft_404.matches(status, body.as_bytes()) {
+                results.push(method_result(
+                    Severity::Medium,
+                    format!("未驗證的 PATCH 請求被接受: {}", path),
+                    format!("未帶任何憑證的 PATCH {} 回傳 HTTP {}，且回應與 GET 不同，資源可能可在未驗證的情況下被修改。建議: 確認 PATCH 需要身分驗證與授權", path, status),
+                    "http_patch_unauthenticated",
+                    "PATCH",
+                    path,
+                    status,
+                    &advertised_for(path),
+                ));
+                break;
+            }
+        }
 
-                if !headers.contains_key("content-security-policy") {
-                    results.push(self.create_result(
-                        task_id,
-                        Severity::Low,
-                        "缺少 Content-Security-Policy 標頭".to_string(),
-                        "未設置 CSP，無法防禦 XSS 和資料注入攻擊。建議: 實施嚴格的 CSP 策略".to_string(),
-                        serde_json::json!({
-                            "owasp": "A05:2021",
-                            "header": "Content-Security-Policy"
-                        })
-                    ));
+        results
+    }
+
+    /// 以攻擊者的 Origin 送出 GET 與 OPTIONS 預檢，每種問題只回報一次 (優先回報允許憑證的回應)
+    async fn check_cors(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let Ok(target) = reqwest::Url::parse(base_url) else {
+            return Vec::new();
+        };
+        let host = target.host_str().unwrap_or_default();
+
+        let mut issues: Vec<(cors::CorsIssue, serde_json::Value)> = Vec::new();
+        for probe in cors::probe_origins(target.scheme(), host) {
+            for method in [reqwest::Method::GET, reqwest::Method::OPTIONS] {
+                let mut request = self.client.request(method.clone(), base_url).header("Origin", &probe.origin);
+                if method == reqwest::Method::OPTIONS {
+                    request = request.header("Access-Control-Request-Method", "GET");
+                }
+                let Ok(response) = request.send().await else { continue };
+                let headers = cors::CorsHeaders::from_headers(response.headers());
+                let Some(issue) = headers.classify(&probe) else { continue };
+
+                let evidence = serde_json::json!({
+                    "owasp": "A05:2021",
+                    "check_id": issue.check_id,
+                    "url": base_url,
+                    "method": method.as_str(),
+                    "request_origin": probe.origin,
+                    "payload": probe.origin,
+                    "status": response.status().as_u16(),
+                    "response_headers": headers.to_json(),
+                    "curl": cors::curl_command(method.as_str(), base_url, &probe.origin),
+                });
+                match issues.iter_mut().find(|(existing, _)| existing.check_id == issue.check_id) {
+                    Some(existing) if issue.severity.rank() > existing.0.severity.rank() => *existing = (issue, evidence),
+                    Some(_) => {}
+                    None => issues.push((issue, evidence)),
                 }
+            }
+        }
+
+        issues
+            .into_iter()
+            .map(|(issue, evidence)| {
+                let description = format!(
+                    "{}。重現: {}",
+                    issue.description,
+                    evidence["curl"].as_str().unwrap_or_default()
+                );
+                self.create_result(task_id, issue.severity, issue.title, description, evidence)
+            })
+            .collect()
+    }
+
+    /// 備份與暫存檔探測：根目錄、常見設定檔與爬取到的頁面加上或取代備份副檔名
+    ///
+    /// 回應須為 2xx、不是 soft-404，且與原始頁面不同 (伺服器忽略副檔名時會回傳原頁面)；
+    /// 壓縮檔須符合檔頭，已知類型的檔案 (例如 .env) 依原始檔名驗證內容
+    async fn backup_files(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let seeds: Vec<&str> = backup_files::SEED_FILES.iter().copied().filter(|path| self.context.allows_path(path)).collect();
+        let pages = self.crawl(base_url).await.pages.clone();
+        let max_probes = self.backup_settings.max_probes;
+        let candidates = backup_files::candidates(base_url, &pages, &self.backup_settings.mutations, &seeds, max_probes);
+        if candidates.is_empty() {
+            return results;
+        }
+
+        let soft_404 = self.soft_404(base_url).await;
+        let urls: Vec<String> = candidates.iter().map(|candidate| candidate.url.clone()).collect();
+        let probes = self.fetch_all(&urls, true).await;
+        let hits: Vec<(&backup_files::Candidate, FetchedPage)> = candidates
+            .iter()
+            .zip(probes)
+            .filter_map(|(candidate, page)| page.filter(|page| (200..300).contains(&page.status)).map(|page| (candidate, page)))
+            .collect();
+
+        // 只有命中的路徑才取得原始頁面比較
+        let mut originals: Vec<String> = Vec::new();
+        for original in hits.iter().filter_map(|(candidate, _)| candidate.original.clone()) {
+            if !originals.contains(&original) {
+                originals.push(original);
+            }
+        }
+        let original_pages = self.fetch_all(&originals, true).await;
+        let original_page = |url: &str| {
+            originals
+                .iter()
+                .position(|original| original == url)
+                .and_then(|index| original_pages[index].as_ref())
+                .filter(|page| (200..300).contains(&page.status))
+        };
+
+        let mut soft_404_paths = Vec::new();
+        let mut same_as_original = Vec::new();
+        for (candidate, page) in &hits {
+            let path = reqwest::Url::parse(&candidate.url).map(|u| u.path().to_string()).unwrap_or_default();
+            if soft_404.matches(page.status, &page.body) {
+                soft_404_paths.push(path);
+                continue;
+            }
+            if candidate.kind == BackupKind::Archive && !backup_files::is_archive_content(&page.body) {
+                same_as_original.push(path);
+                continue;
+            }
+            let original = candidate.original.as_deref().and_then(original_page);
+            if !backup_files::differs_from_original(original.map(|p| (&p.content_type, p.body.as_slice())), &page.content_type, &page.body) {
+                same_as_original.push(path);
+                continue;
+            }
+
+            // 已知類型的檔案依原始檔名驗證內容 (例如 .env.old 須為 KEY=value 格式)
+            let original_path = candidate
+                .original
+                .as_deref()
+                .and_then(|url| reqwest::Url::parse(url).ok())
+                .map(|url| url.path().to_string());
+            let excerpt = match (candidate.kind, original_path) {
+                (BackupKind::Archive, _) | (_, None) => None,
+                (_, Some(original_path)) => match sensitive_files::validate(&original_path, &page.body) {
+                    FileValidation::Confirmed { excerpt, .. } => Some(excerpt),
+                    FileValidation::Rejected { validator, reason } => {
+                        tracing::info!("ℹ️  {} 回傳 {} 但內容驗證失敗 ({}: {})，視為誤判", candidate.url, page.status, validator, reason);
+                        continue;
+                    }
+                    FileValidation::Unverified => None,
+                },
+            };
+
+            results.push(self.create_result(
+                task_id,
+                candidate.kind.severity(),
+                format!("發現可下載的{}: {}", candidate.kind.label(), path),
+                format!(
+                    "{} 可以被公開下載 ({} 位元組)，伺服器不會執行備份副檔名，原始碼、設定值或整站內容可能外洩。建議: 從網站目錄移除備份與暫存檔，並在伺服器設定中拒絕這類副檔名",
+                    path,
+                    page.body.len()
+                ),
+                serde_json::json!({
+                    "owasp": "A05:2021",
+                    "type": "Backup File",
+                    "path": path,
+                    "url": candidate.url,
+                    "original_url": candidate.original,
+                    "mutation": candidate.mutation,
+                    "status": page.status,
+                    "content_type": page.content_type,
+                    "size": page.body.len(),
+                    "excerpt": excerpt
+                }),
+            ));
+        }
+
+        self.note_soft_404("A05:2021", soft_404_paths.iter().map(String::as_str).collect());
+        self.add_note(
+            "A05:2021",
+            if candidates.len() >= max_probes {
+                format!("備份檔探測送出 {} 個請求，已達上限 (backup_files.max_probes)，其餘路徑未探測", candidates.len())
+            } else {
+                format!("備份檔探測送出 {} 個請求", candidates.len())
             },
-            Err(_) => {},
+            serde_json::json!({
+                "check": "backup_files",
+                "probes": candidates.len(),
+                "max_probes": max_probes,
+                "original_pages": originals.len(),
+                "same_as_original": same_as_original,
+            }),
+        );
+
+        results
+    }
+
+    /// 公開的 OpenAPI / Swagger 文件與 Swagger UI；同一份文件出現在多個路徑時只回報一次
+    async fn api_documentation(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let docs = self.api_docs(base_url).await;
+        let origin = reqwest::Url::parse(base_url).map(|url| url.origin().ascii_serialization()).unwrap_or_default();
+        let mut reported: Vec<&ApiSpec> = Vec::new();
+
+        for (url, spec) in &docs.specs {
+            if reported.contains(&spec) {
+                continue;
+            }
+            reported.push(spec);
+            let admin_paths = spec.admin_paths();
+            let crawl_urls = spec.urls(&origin).len();
+            results.push(self.create_result(
+                task_id,
+                if admin_paths.is_empty() { Severity::Medium } else { Severity::High },
+                format!("公開的 API 文件 ({})", spec.version),
+                if admin_paths.is_empty() {
+                    format!(
+                        "{} 公開了 {} 個 API 操作的文件，攻擊者可以直接得知所有端點與參數。建議: 在正式環境關閉 API 文件或加上存取控制",
+                        url,
+                        spec.operations.len()
+                    )
+                } else {
+                    format!(
+                        "{} 公開了 {} 個 API 操作的文件，其中包含看起來是管理功能的端點: {}。建議: 在正式環境關閉 API 文件或加上存取控制，並確認管理端點需要授權",
+                        url,
+                        spec.operations.len(),
+                        admin_paths.join(", ")
+                    )
+                },
+                serde_json::json!({
+                    "owasp": "A05:2021",
+                    "type": "Exposed API Documentation",
+                    "url": url,
+                    "spec_version": spec.version,
+                    "title": spec.title,
+                    "operations": spec.operations.len(),
+                    "admin_paths": admin_paths,
+                    "crawl_urls": crawl_urls
+                }),
+            ));
         }
 
-        Ok(results)
+        if let (Some(url), true) = (&docs.swagger_ui, docs.specs.is_empty()) {
+            results.push(self.create_result(
+                task_id,
+                Severity::Low,
+                "公開的 Swagger UI".to_string(),
+                format!("{} 可以公開訪問 Swagger UI，但無法從常見路徑取得 API 文件。建議: 在正式環境關閉 Swagger UI 或加上存取控制", url),
+                serde_json::json!({
+                    "owasp": "A05:2021",
+                    "type": "Exposed Swagger UI",
+                    "url": url
+                }),
+            ));
+        }
+
+        results
+    }
+
+    /// GraphQL introspection：回應包含 schema 時回報，有 mutation 時提高嚴重度；找到一個端點即停止
+    async fn graphql_introspection(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        for path in api_docs::GRAPHQL_PATHS.iter().filter(|path| self.context.allows_path(path)) {
+            let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+            let response = self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .body(api_docs::INTROSPECTION_QUERY.as_bytes().to_vec())
+                .send()
+                .await;
+            let Ok(response) = response else { continue };
+            let body = client::bytes(response).await;
+            let Some(schema) = api_docs::parse_introspection(&body) else { continue };
+
+            let mutations: Vec<&String> = schema.mutations.iter().take(20).collect();
+            results.push(self.create_result(
+                task_id,
+                if schema.mutations.is_empty() { Severity::Medium } else { Severity::High },
+                "GraphQL introspection 已開啟".to_string(),
+                if schema.mutations.is_empty() {
+                    format!(
+                        "{} 允許 introspection 查詢，回傳了 {} 個類型的完整 schema。建議: 在正式環境關閉 introspection",
+                        url, schema.types
+                    )
+                } else {
+                    format!(
+                        "{} 允許 introspection 查詢，回傳了 {} 個類型的完整 schema，包含 {} 個可以修改資料的 mutation。建議: 在正式環境關閉 introspection，並確認每個 mutation 都需要授權",
+                        url,
+                        schema.types,
+                        schema.mutations.len()
+                    )
+                },
+                serde_json::json!({
+                    "owasp": "A05:2021",
+                    "type": "GraphQL Introspection",
+                    "url": url,
+                    "types": schema.types,
+                    "mutation_count": schema.mutations.len(),
+                    "mutations": mutations
+                }),
+            ));
+            break;
+        }
+        results
+    }
+
+    /// 以偽造的主機標頭重新請求基礎頁面，回應中出現偽造的主機名稱即為反射
+    ///
+    /// 同一個標頭只回報一次；偽造 Host 時回應 4xx 是虛擬主機的正常行為，只記錄說明
+    async fn host_header_injection(&self, task_id: &str, base_url: &str) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let forged = format!("{}.evil.example", control::canary("host_header"));
+        let mut reported: Vec<&str> = Vec::new();
+
+        for forgery in host_header::overrides(&forged) {
+            let header = forgery.headers[0].0;
+            if reported.contains(&header) {
+                continue;
+            }
+            let mut request = self.client.get(base_url);
+            for (name, value) in &forgery.headers {
+                request = request.header(name, value);
+            }
+            let Ok(response) = request.send().await else { continue };
+            let status = response.status().as_u16();
+            if header == "Host" && (400..500).contains(&status) {
+                self.add_note(
+                    "A05:2021",
+                    format!("偽造 Host 標頭時伺服器回應 {}，以虛擬主機區分網站，不視為漏洞", status),
+                    serde_json::json!({ "check": "host_header", "status": status }),
+                );
+                continue;
+            }
+            let location = response.headers().get(reqwest::header::LOCATION).and_then(|v| v.to_str().ok()).map(str::to_string);
+            let cache = host_header::cache_indicators(response.headers());
+            let body = client::text(response).await;
+            let Some(reflection) = host_header::reflection(&forged, location.as_deref(), &body) else { continue };
+            reported.push(header);
+
+            let severity = match reflection.kind {
+                ReflectionKind::Redirect | ReflectionKind::Link => Severity::High,
+                ReflectionKind::Body => Severity::Medium,
+                ReflectionKind::Analytics => Severity::Low,
+            };
+            let cached = !cache.is_empty() && reflection.kind != ReflectionKind::Analytics;
+            results.push(self.create_result(
+                task_id,
+                severity,
+                format!("Host 標頭注入 ({})", header),
+                format!(
+                    "以 {} 送出偽造的主機名稱後，{}中出現該名稱，攻擊者可以讓密碼重設信件或重定向指向自己的主機。{}建議: 以設定檔中的網域產生絕對網址，不信任請求的 Host 與轉送標頭",
+                    header,
+                    reflection.kind.label(),
+                    if cached { "回應帶有快取標頭，被快取時其他使用者也會拿到被污染的頁面 (cache poisoning)。" } else { "" }
+                ),
+                serde_json::json!({
+                    "owasp": "A05:2021",
+                    "type": "Host Header Injection",
+                    "url": base_url,
+                    "payload": forgery.describe(),
+                    "status": status,
+                    "reflection": reflection.kind.label(),
+                    "evidence": reflection.snippet,
+                    "cache_indicators": cache
+                }),
+            ));
+        }
+
+        results
     }
 
     // ========================================================================
     // A06: Vulnerable and Outdated Components
     // ========================================================================
-    async fn a06_vulnerable_components(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn a06_vulnerable_components(&self, task_id: &str, base: Option<&BaseResponse>) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
-        match self.client.get(base_url).send().await {
-            Ok(response) => {
-                // 先提取 headers (因為 text() 會移動所有權)
-                let server_header = response.headers().get("server")
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
+        if let Some(base) = base {
+            let server_header = base.headers.get("server")
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string());
 
-                let body = response.text().await.unwrap_or_default();
+            let body = &base.body;
 
-                // 檢查已知的過時庫和框架
-                let vulnerable_libs = vec![
-                    // jQuery
-                    ("jquery-1.", "jQuery 1.x", Severity::High, "已知多個 XSS 漏洞，CVE-2015-9251"),
-                    ("jquery-2.", "jQuery 2.x", Severity::Medium, "已知安全問題"),
+            // 檢查已知的過時庫和框架
+            let vulnerable_libs = vec![
+                // jQuery
+                ("jquery-1.", "jQuery 1.x", Severity::High, "已知多個 XSS 漏洞，CVE-2015-9251"),
+                ("jquery-2.", "jQuery 2.x", Severity::Medium, "已知安全問題"),
 
-                    // AngularJS
-                    ("angular.js/1.0", "AngularJS 1.0", Severity::High, "已停止支援"),
-                    ("angular.js/1.2", "AngularJS 1.2", Severity::High, "已停止支援"),
+                // AngularJS
+                ("angular.js/1.0", "AngularJS 1.0", Severity::High, "已停止支援"),
+                ("angular.js/1.2", "AngularJS 1.2", Severity::High, "已停止支援"),
 
-                    // Bootstrap
-                    ("bootstrap/3.", "Bootstrap 3", Severity::Medium, "已停止安全更新"),
+                // Bootstrap
+                ("bootstrap/3.", "Bootstrap 3", Severity::Medium, "已停止安全更新"),
 
-                    // WordPress
-                    ("wp-content/plugins/", "WordPress Plugins", Severity::Medium, "需檢查插件版本"),
+                // WordPress
+                ("wp-content/plugins/", "WordPress Plugins", Severity::Medium, "需檢查插件版本"),
 
-                    // Other frameworks
-                    ("lodash@4.17.1", "Lodash 4.17.1", Severity::High, "已知原型污染漏洞"),
-                    ("moment.js/2.19.", "Moment.js 2.19.x", Severity::Low, "已停止維護，建議遷移到 Day.js"),
-                ];
+                // Other frameworks
+                ("lodash@4.17.1", "Lodash 4.17.1", Severity::High, "已知原型污染漏洞"),
+                ("moment.js/2.19.", "Moment.js 2.19.x", Severity::Low, "已停止維護，建議遷移到 Day.js"),
+            ];
 
-                for (pattern, lib_name, severity, issue) in vulnerable_libs {
-                    if body.to_lowercase().contains(pattern) {
-                        results.push(self.create_result(
-                            task_id,
-                            severity,
-                            format!("使用過時的組件: {}", lib_name),
-                            format!(
-                                "檢測到使用 {}，{}。建議: 升級到最新版本或使用替代方案",
-                                lib_name, issue
-                            ),
-                            serde_json::json!({
-                                "owasp": "A06:2021",
-                                "library": lib_name,
-                                "pattern": pattern,
-                                "issue": issue
-                            })
-                        ));
-                    }
+            for (pattern, lib_name, severity, issue) in vulnerable_libs {
+                if body.to_lowercase().contains(pattern) {
+                    results.push(self.create_result(
+                        task_id,
+                        severity,
+                        format!("使用過時的組件: {}", lib_name),
+                        format!(
+                            "檢測到使用 {}，{}。建議: 升級到最新版本或使用替代方案",
+                            lib_name, issue
+                        ),
+                        serde_json::json!({
+                            "owasp": "A06:2021",
+                            "library": lib_name,
+                            "pattern": pattern,
+                            "issue": issue
+                        })
+                    ));
                 }
+            }
 
-                // 檢查 Server 版本
-                if let Some(server_str) = server_header {
-                    // 檢查是否洩露版本資訊
-                    if server_str.contains('/') {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::Low,
-                            "Server 標頭洩露版本資訊".to_string(),
-                            format!(
-                                "Server 標頭包含版本資訊 '{}'，可能幫助攻擊者識別已知漏洞。建議: 隱藏版本資訊",
-                                server_str
-                            ),
-                            serde_json::json!({
-                                "owasp": "A06:2021",
-                                "header": "Server",
-                                "value": server_str
-                            })
-                        ));
-                    }
+            // 檢查 Server 版本
+            if let Some(server_str) = server_header {
+                // 檢查是否洩露版本資訊
+                if server_str.contains('/') {
+                    results.push(self.create_result(
+                        task_id,
+                        Severity::Low,
+                        "Server 標頭洩露版本資訊".to_string(),
+                        format!(
+                            "Server 標頭包含版本資訊 '{}'，可能幫助攻擊者識別已知漏洞。建議: 隱藏版本資訊",
+                            server_str
+                        ),
+                        serde_json::json!({
+                            "owasp": "A06:2021",
+                            "header": "Server",
+                            "value": server_str
+                        })
+                    ));
                 }
-            },
-            Err(_) => {},
+            }
         }
 
         Ok(results)
@@ -790,99 +2460,69 @@ impl OwaspScanner {
     // ========================================================================
     // A07: Identification and Authentication Failures
     // ========================================================================
-    async fn a07_authentication_failures(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn a07_authentication_failures(&self, task_id: &str, base_url: &str, base: Option<&BaseResponse>) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
+        // 基礎頁面與登入頁面的回應中的 JWT
+        let mut tokens: Vec<(String, Jwt)> = Vec::new();
+        if let Some(base) = base {
+            tokens.extend(jwt::collect(&base.headers, &base.body).into_iter().map(|token| (base_url.to_string(), token)));
+        }
 
         // 檢查登錄頁面
         let login_paths = vec!["/login", "/signin", "/auth", "/user/login"];
 
-        for path in login_paths {
-            let test_url = format!("{}{}", base_url.trim_end_matches('/'), path);
+        if self.allows("login_pages") {
+            for path in login_paths {
+                let test_url = format!("{}{}", base_url.trim_end_matches('/'), path);
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        let body = response.text().await.unwrap_or_default();
-
-                        // 檢查是否缺少 CSRF token
-                        if body.to_lowercase().contains("password") {
-                            if !body.contains("csrf") && !body.contains("token") && !body.contains("_token") {
-                                results.push(self.create_result(
-                                    task_id,
-                                    Severity::High,
-                                    "登錄表單缺少 CSRF 保護".to_string(),
-                                    "登錄表單未包含 CSRF token，可能遭受跨站請求偽造攻擊。建議: 實施 CSRF token 驗證".to_string(),
-                                    serde_json::json!({
-                                        "owasp": "A07:2021",
-                                        "type": "Missing CSRF Token",
-                                        "path": path,
-                                        "url": test_url
-                                    })
-                                ));
-                            }
-
-                            // 檢查是否支持弱密碼
-                            // 這裡只能做靜態檢查，無法真正測試
-                            if !body.to_lowercase().contains("password") || !body.contains("minimum") {
-                                results.push(self.create_result(
-                                    task_id,
-                                    Severity::Medium,
-                                    "未顯示密碼強度要求".to_string(),
-                                    "登錄/註冊頁面未顯示密碼強度要求，用戶可能設置弱密碼。建議: 實施密碼策略並顯示要求".to_string(),
-                                    serde_json::json!({
-                                        "owasp": "A07:2021",
-                                        "type": "Weak Password Policy",
-                                        "path": path
-                                    })
-                                ));
+                match self.client.get(&test_url).send().await {
+                    Ok(response) => {
+                        if response.status().is_success() {
+                            let headers = response.headers().clone();
+                            let body = client::text(response).await;
+                            tokens.extend(jwt::collect(&headers, &body).into_iter().map(|token| (test_url.clone(), token)));
+
+                            // 檢查是否缺少 CSRF token
+                            if body.to_lowercase().contains("password") {
+                                if !body.contains("csrf") && !body.contains("token") && !body.contains("_token") {
+                                    results.push(self.create_result(
+                                        task_id,
+                                        Severity::High,
+                                        "登錄表單缺少 CSRF 保護".to_string(),
+                                        "登錄表單未包含 CSRF token，可能遭受跨站請求偽造攻擊。建議: 實施 CSRF token 驗證".to_string(),
+                                        serde_json::json!({
+                                            "owasp": "A07:2021",
+                                            "type": "Missing CSRF Token",
+                                            "path": path,
+                                            "url": test_url
+                                        })
+                                    ));
+                                }
+
+                                // 檢查是否支持弱密碼
+                                // 這裡只能做靜態檢查，無法真正測試
+                                if !body.to_lowercase().contains("password") || !body.contains("minimum") {
+                                    results.push(self.create_result(
+                                        task_id,
+                                        Severity::Medium,
+                                        "未顯示密碼強度要求".to_string(),
+                                        "登錄/註冊頁面未顯示密碼強度要求，用戶可能設置弱密碼。建議: 實施密碼策略並顯示要求".to_string(),
+                                        serde_json::json!({
+                                            "owasp": "A07:2021",
+                                            "type": "Weak Password Policy",
+                                            "path": path
+                                        })
+                                    ));
+                                }
                             }
                         }
-                    }
-                },
-                Err(_) => continue,
+                    },
+                    Err(_) => continue,
+                }
             }
         }
 
-        // 檢查 Session Cookie 安全性
-        match self.client.get(base_url).send().await {
-            Ok(response) => {
-                for cookie in response.cookies() {
-                    let name = cookie.name().to_lowercase();
-
-                    // 檢查是否為 session cookie
-                    if name.contains("session") || name.contains("sess") || name == "phpsessid" {
-                        if !cookie.secure() {
-                            results.push(self.create_result(
-                                task_id,
-                                Severity::High,
-                                format!("Session Cookie 未設置 Secure 標誌: {}", cookie.name()),
-                                "Session cookie 未設置 Secure 標誌，可能在 HTTP 連接中被竊取。建議: 設置 Secure 和 HttpOnly 標誌".to_string(),
-                                serde_json::json!({
-                                    "owasp": "A07:2021",
-                                    "cookie_name": cookie.name(),
-                                    "missing_flags": vec!["Secure"]
-                                })
-                            ));
-                        }
-
-                        if !cookie.http_only() {
-                            results.push(self.create_result(
-                                task_id,
-                                Severity::High,
-                                format!("Session Cookie 未設置 HttpOnly 標誌: {}", cookie.name()),
-                                "Session cookie 未設置 HttpOnly 標誌，可能被 JavaScript 竊取 (XSS)。建議: 設置 HttpOnly 標誌".to_string(),
-                                serde_json::json!({
-                                    "owasp": "A07:2021",
-                                    "cookie_name": cookie.name(),
-                                    "missing_flags": vec!["HttpOnly"]
-                                })
-                            ));
-                        }
-                    }
-                }
-            },
-            Err(_) => {},
-        }
+        // Session cookie 的 Secure / HttpOnly / SameSite 由標頭掃描的 cookie 稽核檢查 (scanners::cookies)
 
         // 檢查預設憑證 (常見的用戶名密碼組合)
         let default_creds = vec![
@@ -911,83 +2551,194 @@ impl OwaspScanner {
             break; // 只提示一次
         }
 
+        if self.allows("jwt") {
+            results.extend(self.analyze_tokens(task_id, tokens));
+        }
+
         Ok(results)
     }
 
+    /// 解碼回應中的 JWT，每個問題一個發現；同一 token 出現在多個回應時只分析第一次
+    ///
+    /// 發現附上解碼的標頭與遮蔽敏感欄位的內容，token 的簽章不輸出
+    fn analyze_tokens(&self, task_id: &str, tokens: Vec<(String, Jwt)>) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        let now = Utc::now().timestamp();
+        let mut seen = std::collections::HashSet::new();
+
+        for (url, token) in tokens.iter().filter(|(_, token)| seen.insert(token.display())) {
+            for issue in token.issues(self.jwt_weak_keys, now) {
+                let (severity, title, description, kind) = match &issue {
+                    JwtIssue::AlgNone => (
+                        Severity::Critical,
+                        "JWT 未簽章 (alg: none)".to_string(),
+                        format!("{} 中的 JWT 使用 alg: none，沒有簽章，任何人都能竄改內容。建議: 伺服器固定使用的簽章演算法並拒絕 none", token.source),
+                        "JWT Algorithm None",
+                    ),
+                    JwtIssue::WeakKey { key } => (
+                        Severity::Critical,
+                        "JWT 使用弱簽章金鑰".to_string(),
+                        format!(
+                            "{} 中的 JWT ({}) 的簽章金鑰為常見的弱金鑰 \"{}\"，攻擊者可以偽造任意內容的 token。建議: 改用至少 256 位元的隨機金鑰並撤銷既有的 token",
+                            token.source,
+                            token.alg(),
+                            key
+                        ),
+                        "JWT Weak Signing Key",
+                    ),
+                    JwtIssue::MissingExpiry => (
+                        Severity::Medium,
+                        "JWT 沒有到期時間".to_string(),
+                        format!("{} 中的 JWT 沒有 exp 欄位，外洩的 token 永久有效。建議: 設定短效期並搭配更新機制", token.source),
+                        "JWT Missing Expiry",
+                    ),
+                    JwtIssue::LongExpiry { days } => (
+                        Severity::Medium,
+                        "JWT 效期過長".to_string(),
+                        format!("{} 中的 JWT 效期為 {} 天，外洩的 token 長時間有效。建議: 縮短效期並搭配更新機制", token.source, days),
+                        "JWT Long Expiry",
+                    ),
+                    JwtIssue::SensitiveClaims { claims } => (
+                        Severity::High,
+                        "JWT 內容包含敏感資料".to_string(),
+                        format!(
+                            "{} 中的 JWT 內容包含 {}。JWT 只經過編碼而非加密，任何取得 token 的人都能讀取。建議: 從 token 移除敏感資料",
+                            token.source,
+                            claims.join(", ")
+                        ),
+                        "JWT Sensitive Claims",
+                    ),
+                };
+                let mut raw_data = serde_json::json!({
+                    "owasp": "A07:2021",
+                    "type": kind,
+                    "url": url,
+                    "source": token.source,
+                    "header": token.header,
+                    "payload": token.redacted_payload()
+                });
+                if let JwtIssue::SensitiveClaims { claims } = &issue {
+                    raw_data["claims"] = serde_json::json!(claims);
+                }
+                results.push(self.create_result(task_id, severity, title, description, raw_data));
+            }
+        }
+
+        if !tokens.is_empty() && !self.jwt_weak_keys {
+            self.add_note(
+                "A07:2021",
+                format!("發現 {} 個 JWT，未啟用弱金鑰檢查 (jwt_weak_keys)，沒有檢查簽章金鑰", seen.len()),
+                serde_json::json!({ "check": "jwt", "tokens": seen.len(), "weak_key_check": false }),
+            );
+        }
+
+        results
+    }
+
     // ========================================================================
     // A08: Software and Data Integrity Failures
     // ========================================================================
-    async fn a08_integrity_failures(&self, task_id: &str, base_url: &str) -> ScannerResult<Vec<ScanResult>> {
+    async fn a08_integrity_failures(&self, task_id: &str, base_url: &str, base: Option<&BaseResponse>) -> ScannerResult<Vec<ScanResult>> {
         let mut results = Vec::new();
 
-        match self.client.get(base_url).send().await {
-            Ok(response) => {
-                // 先提取 cookies 資料為 owned values (因為 text() 會移動所有權)
-                let cookie_data: Vec<(String, String)> = response.cookies()
-                    .map(|c| (c.name().to_string(), c.value().to_string()))
-                    .collect();
-
-                let body = response.text().await.unwrap_or_default();
-
-                // 檢查是否從不安全的 CDN 加載資源
-                if body.contains("http://") && (body.contains(".js") || body.contains(".css")) {
+        if let Some(base) = base {
+            let cookie_data = &base.cookies;
+            let (page_url, html) = (base_url.to_string(), base.body.clone());
+            let resources = analysis::offload(move || sri::inventory(&page_url, &html)).await;
+            results.extend(self.integrity_findings(task_id, &resources));
+            *self.resources.lock().unwrap_or_else(|e| e.into_inner()) = resources;
+
+            // 檢查 Cookies 中是否包含序列化資料
+            for (cookie_name, value) in cookie_data {
+
+                // 檢查是否為 Java、PHP、Python 序列化資料
+                if value.len() > 50 && (
+                    value.starts_with("O:") ||        // PHP serialize
+                    value.starts_with("rO0") ||       // Java serialize (base64)
+                    value.contains("__pickle") ||      // Python pickle
+                    value.contains("__reduce")
+                ) {
                     results.push(self.create_result(
                         task_id,
                         Severity::High,
-                        "從不安全的 HTTP 加載外部資源".to_string(),
-                        "網頁從 HTTP 協議加載 JavaScript 或 CSS 資源，可能被中間人攻擊篡改。建議: 使用 HTTPS 和 SRI (Subresource Integrity)".to_string(),
+                        format!("Cookie 中發現序列化資料: {}", cookie_name),
+                        "Cookie 中包含序列化物件，如果未正確驗證可能導致遠程代碼執行 (反序列化漏洞)。建議: 使用 JSON 或 JWT，並驗證簽名".to_string(),
                         serde_json::json!({
                             "owasp": "A08:2021",
-                            "type": "Insecure Resource Loading"
+                            "cookie_name": cookie_name,
+                            "value_prefix": &value[..20.min(value.len())]
                         })
                     ));
                 }
+            }
+        }
 
-                // 檢查是否使用 SRI (Subresource Integrity)
-                let has_external_scripts = body.contains("<script src=\"http") || body.contains("<link");
-                let has_sri = body.contains("integrity=\"") && body.contains("sha");
+        Ok(results)
+    }
 
-                if has_external_scripts && !has_sri {
-                    results.push(self.create_result(
-                        task_id,
-                        Severity::Medium,
-                        "外部資源未使用 SRI 驗證".to_string(),
-                        "從 CDN 加載的資源未使用 Subresource Integrity，無法驗證完整性。建議: 為所有外部資源添加 integrity 屬性".to_string(),
-                        serde_json::json!({
-                            "owasp": "A08:2021",
-                            "type": "Missing SRI"
-                        })
-                    ));
-                }
+    /// 以 HTTP 載入的資源與跨來源資源的 SRI，各合併為一個發現
+    ///
+    /// SRI 的嚴重度依未保護資源涉及的第三方來源數調整
+    fn integrity_findings(&self, task_id: &str, resources: &[ExternalResource]) -> Vec<ScanResult> {
+        let mut results = Vec::new();
 
-                // 檢查 Cookies 中是否包含序列化資料
-                for (cookie_name, value) in cookie_data {
+        let insecure: Vec<&ExternalResource> = resources.iter().filter(|resource| resource.insecure).collect();
+        if !insecure.is_empty() {
+            results.push(self.create_result(
+                task_id,
+                Severity::High,
+                "從不安全的 HTTP 加載外部資源".to_string(),
+                format!(
+                    "網頁以 HTTP 協議加載 {} 個 JavaScript 或 CSS 資源，可能被中間人攻擊篡改。建議: 使用 HTTPS 和 SRI (Subresource Integrity)",
+                    insecure.len()
+                ),
+                serde_json::json!({
+                    "owasp": "A08:2021",
+                    "type": "Insecure Resource Loading",
+                    "resources": insecure.iter().map(|resource| &resource.url).collect::<Vec<_>>()
+                }),
+            ));
+        }
 
-                    // 檢查是否為 Java、PHP、Python 序列化資料
-                    if value.len() > 50 && (
-                        value.starts_with("O:") ||        // PHP serialize
-                        value.starts_with("rO0") ||       // Java serialize (base64)
-                        value.contains("__pickle") ||      // Python pickle
-                        value.contains("__reduce")
-                    ) {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::High,
-                            format!("Cookie 中發現序列化資料: {}", cookie_name),
-                            "Cookie 中包含序列化物件，如果未正確驗證可能導致遠程代碼執行 (反序列化漏洞)。建議: 使用 JSON 或 JWT，並驗證簽名".to_string(),
-                            serde_json::json!({
-                                "owasp": "A08:2021",
-                                "cookie_name": cookie_name,
-                                "value_prefix": &value[..20.min(value.len())]
-                            })
-                        ));
-                    }
-                }
-            },
-            Err(_) => {},
+        let unprotected: Vec<&ExternalResource> = resources.iter().filter(|resource| resource.unprotected()).collect();
+        if unprotected.is_empty() {
+            return results;
         }
+        let mut origins: Vec<String> = unprotected.iter().map(|resource| resource.origin()).collect();
+        origins.sort();
+        origins.dedup();
+        let cross_origin = resources.iter().filter(|resource| resource.cross_origin).count();
+        let severity = match origins.len() {
+            1 => Severity::Low,
+            2..=4 => Severity::Medium,
+            _ => Severity::High,
+        };
+        results.push(self.create_result(
+            task_id,
+            severity,
+            "外部資源未使用 SRI 驗證".to_string(),
+            format!(
+                "{} 個跨來源資源中有 {} 個 (來自 {} 個第三方來源: {}) 沒有有效的 integrity 屬性，第三方被入侵時會直接執行竄改後的內容。建議: 為所有跨來源的腳本與樣式表添加 integrity 屬性",
+                cross_origin,
+                unprotected.len(),
+                origins.len(),
+                origins.join(", ")
+            ),
+            serde_json::json!({
+                "owasp": "A08:2021",
+                "type": "Missing SRI",
+                "cross_origin_resources": cross_origin,
+                "unprotected_count": unprotected.len(),
+                "third_party_origins": origins,
+                "unprotected": unprotected.iter().map(|resource| &resource.url).collect::<Vec<_>>()
+            }),
+        ));
+        results
+    }
 
-        Ok(results)
+    /// A08 建立的外部資源清單；沒有執行 A08 時為空
+    pub fn resource_inventory(&self) -> Vec<ExternalResource> {
+        self.resources.lock().map(|resources| resources.clone()).unwrap_or_default()
     }
 
     // ========================================================================
@@ -1003,36 +2754,32 @@ impl OwaspScanner {
             "/<script>alert(1)</script>",
         ];
 
-        for path in error_paths {
-            let test_url = format!("{}{}", base_url.trim_end_matches('/'), path);
-
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    let body = response.text().await.unwrap_or_default();
+        if self.allows("error_disclosure") {
+            for path in error_paths {
+                let test_url = format!("{}{}", base_url.trim_end_matches('/'), path);
 
-                    // 檢查是否洩露堆棧追踪或敏感資訊
-                    let sensitive_info = vec![
-                        "stack trace", "traceback", "exception",
-                        "line ", "file:", "at ",
-                        "sql", "query", "database",
-                    ];
+                match self.client.get(&test_url).send().await {
+                    Ok(response) => {
+                        let body = client::text(response).await;
 
-                    if sensitive_info.iter().any(|info| body.to_lowercase().contains(info)) {
-                        results.push(self.create_result(
-                            task_id,
-                            Severity::Medium,
-                            "錯誤頁面洩露詳細資訊".to_string(),
-                            "錯誤頁面顯示堆棧追踪或技術細節，可能幫助攻擊者了解系統架構。建議: 1) 使用自定義錯誤頁面 2) 記錄到日誌而非顯示給用戶".to_string(),
-                            serde_json::json!({
-                                "owasp": "A09:2021",
-                                "type": "Information Disclosure",
-                                "url": test_url
-                            })
-                        ));
-                        break;
-                    }
-                },
-                Err(_) => continue,
+                        // 檢查是否洩露堆棧追踪或敏感資訊
+                        if analysis::offload(move || analysis::leaks_error_details(&body)).await {
+                            results.push(self.create_result(
+                                task_id,
+                                Severity::Medium,
+                                "錯誤頁面洩露詳細資訊".to_string(),
+                                "錯誤頁面顯示堆棧追踪或技術細節，可能幫助攻擊者了解系統架構。建議: 1) 使用自定義錯誤頁面 2) 記錄到日誌而非顯示給用戶".to_string(),
+                                serde_json::json!({
+                                    "owasp": "A09:2021",
+                                    "type": "Information Disclosure",
+                                    "url": test_url
+                                })
+                            ));
+                            break;
+                        }
+                    },
+                    Err(_) => continue,
+                }
             }
         }
 
@@ -1059,107 +2806,394 @@ impl OwaspScanner {
         let mut results = Vec::new();
 
         // SSRF 測試 payload
+        // HTTP payload 的查詢字串帶 canary，內部服務的存取日誌可追溯到此掃描
+        let ssrf_canary = control::canary("ssrf");
         let ssrf_payloads = vec![
-            ("http://localhost", "Localhost"),
-            ("http://127.0.0.1", "Loopback IP"),
-            ("http://169.254.169.254", "AWS Metadata"),
-            ("http://metadata.google.internal", "GCP Metadata"),
-            ("http://[::1]", "IPv6 Loopback"),
-            ("file:///etc/passwd", "File Protocol"),
+            (format!("http://localhost/?rfg={}", ssrf_canary), "Localhost"),
+            (format!("http://127.0.0.1/?rfg={}", ssrf_canary), "Loopback IP"),
+            (format!("http://169.254.169.254/?rfg={}", ssrf_canary), "AWS Metadata"),
+            (format!("http://metadata.google.internal/?rfg={}", ssrf_canary), "GCP Metadata"),
+            (format!("http://[::1]/?rfg={}", ssrf_canary), "IPv6 Loopback"),
+            ("file:///etc/passwd".to_string(), "File Protocol"),
+        ];
+        // 重定向目標使用 canary 子網域 (example.com 為保留網域)，避免與其他掃描或正常流量混淆
+        let redirect_canary = control::canary("open_redirect");
+        let redirect_host = format!("{}.example.com", redirect_canary);
+        let redirect_payloads = vec![
+            format!("https://{}", redirect_host),
+            format!("//{}", redirect_host),
+            format!("/\\{}", redirect_host),
         ];
 
-        // 測試常見的 SSRF 參數
-        let params = vec!["url", "uri", "path", "dest", "redirect", "fetch", "file", "document"];
+        // 先確認哪些參數確實存在，只對有證據的參數做完整 payload 測試
+        let mut params = self.map_parameters(base_url).await;
+        let mut requests_sent = params.requests_sent;
+
+        // 爬取到的內頁參數中看起來接受 URL 的也一併測試
+        let crawled = self.crawl(base_url).await.parameters();
+        let crawled_ssrf: Vec<(String, String)> = crawled.iter().filter(|(_, p)| is_url_like(&p.to_lowercase())).cloned().collect();
+        let on_base = |names: Vec<String>| names.into_iter().map(|p| (base_url.to_string(), p)).collect::<Vec<_>>();
+
+        let (mut ssrf_targets, ssrf_basis) = if !params.existing.is_empty() {
+            (on_base(params.existing.clone()), "evidence")
+        } else if !crawled_ssrf.is_empty() {
+            (Vec::new(), "crawled")
+        } else {
+            (on_base(SSRF_PRIORITY_PARAMS.iter().map(|p| p.to_string()).collect()), "no_evidence_priority_list")
+        };
+        ssrf_targets.extend(crawled_ssrf);
+
+        // 檢查是否成功訪問內部資源 (基準頁面本身已包含的字串不算)
+        let ssrf_indicators: Vec<&str> = vec![
+            "root:", "localhost", "127.0.0.1",
+            "ami-id", "instance-id", // AWS metadata
+            "kube-env", // GCP metadata
+        ]
+        .into_iter()
+        .filter(|indicator| !params.baseline_body.contains(indicator))
+        .collect();
+
+        if self.allows("ssrf") {
+            for (endpoint, param) in &ssrf_targets {
+                for (index, (payload, description)) in ssrf_payloads.iter().enumerate() {
+                    let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(payload));
+                    requests_sent += 1;
+
+                    let response = match self.client.get(&test_url).send().await {
+                        Ok(response) => response,
+                        Err(_) => continue,
+                    };
+                    let status = response.status().as_u16();
+                    if response.headers().contains_key("location") {
+                        params.redirect_observed.insert(param.clone());
+                    }
+                    let body = client::text(response).await.to_lowercase();
+
+                    if ssrf_indicators.iter().any(|indicator| body.contains(indicator)) {
+                        results.push(self.create_result(
+                            task_id,
+                            Severity::Critical,
+                            format!("SSRF (服務器端請求偽造) 漏洞: {}", description),
+                            format!(
+                                "應用程序可能存在 SSRF 漏洞，攻擊者可訪問內部資源。Payload: {}。建議: 1) 驗證和白名單 URL 2) 禁用不必要的協議 3) 使用網絡隔離",
+                                payload
+                            ),
+                            serde_json::json!({
+                                "owasp": "A10:2021",
+                                "payload": payload,
+                                "canary": ssrf_canary,
+                                "description": description,
+                                "parameter": param,
+                                "url": test_url
+                            })
+                        ));
+                        break;
+                    }
+
+                    // 第一個 payload 的回應與基準完全相同，表示參數被忽略，不必再試其他 payload (基準只有基礎 URL)
+                    if index == 0 && endpoint == base_url && status == params.baseline_status && body.len() == params.baseline_body.len() {
+                        break;
+                    }
+                }
+            }
 
-        for param in params {
-            for (payload, description) in &ssrf_payloads {
-                let test_url = format!("{}?{}={}", base_url, param, urlencoding::encode(payload));
+            // 回應內容看不出結果的盲 SSRF 由 OOB 回呼判斷
+            requests_sent += self.ssrf_out_of_band(task_id, &ssrf_targets, &mut results).await;
+        }
 
-                match self.client.get(&test_url).send().await {
-                    Ok(response) => {
-                        let body = response.text().await.unwrap_or_default();
+        // 檢查 Open Redirect (開放重定向)，沿用上面的參數探索結果
+        let mut redirect_targets: Vec<(String, String)> = on_base(params.existing
+            .iter()
+            .filter(|p| params.redirect_observed.contains(*p) || is_redirect_like(p))
+            .cloned()
+            .collect());
+        redirect_targets.extend(crawled.iter().filter(|(_, p)| is_redirect_like(&p.to_lowercase())).cloned());
+        if redirect_targets.is_empty() {
+            redirect_targets.push((base_url.to_string(), "redirect".to_string()));
+        }
+
+        if self.allows("open_redirect") {
+            for (endpoint, param) in &redirect_targets {
+                for payload in &redirect_payloads {
+                    let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(payload));
+                    requests_sent += 1;
+
+                    let response = match self.client.get(&test_url).send().await {
+                        Ok(response) => response,
+                        Err(_) => continue,
+                    };
 
-                        // 檢查是否成功訪問內部資源
-                        let ssrf_indicators = vec![
-                            "root:", "localhost", "127.0.0.1",
-                            "ami-id", "instance-id", // AWS metadata
-                            "kube-env", // GCP metadata
-                        ];
+                    let location = response.headers()
+                        .get("location")
+                        .and_then(|l| l.to_str().ok())
+                        .map(|l| l.to_string());
 
-                        if ssrf_indicators.iter().any(|indicator| body.to_lowercase().contains(indicator)) {
+                    match location {
+                        Some(location_str) if location_str.to_lowercase().contains(&redirect_host) => {
                             results.push(self.create_result(
                                 task_id,
-                                Severity::Critical,
-                                format!("SSRF (服務器端請求偽造) 漏洞: {}", description),
+                                Severity::Medium,
+                                "開放重定向 (Open Redirect) 漏洞".to_string(),
                                 format!(
-                                    "應用程序可能存在 SSRF 漏洞，攻擊者可訪問內部資源。Payload: {}。建議: 1) 驗證和白名單 URL 2) 禁用不必要的協議 3) 使用網絡隔離",
+                                    "應用程序存在開放重定向漏洞，可能被用於釣魚攻擊。Payload: {}。建議: 驗證重定向 URL 並使用白名單",
                                     payload
                                 ),
                                 serde_json::json!({
                                     "owasp": "A10:2021",
-                                    "payload": payload,
-                                    "description": description,
+                                    "type": "Open Redirect",
                                     "parameter": param,
+                                    "payload": payload,
+                                    "canary": redirect_canary,
+                                    "redirect_to": location_str,
                                     "url": test_url
                                 })
                             ));
                             break;
                         }
-
-                        // 檢查響應時間 (time-based SSRF detection)
-                        // 如果響應時間明顯變長，可能正在訪問內部網絡
-                    },
-                    Err(_) => continue,
+                        // 重定向到其他位置代表參數有作用但已驗證目標，結果已明確
+                        Some(_) => break,
+                        None => continue,
+                    }
                 }
             }
         }
 
-        // 檢查 Open Redirect (開放重定向)
-        let redirect_payloads = vec![
-            "https://evil.com",
-            "//evil.com",
-            "/\\evil.com",
-        ];
+        let full_sweep = SSRF_CANDIDATE_PARAMS.len() * ssrf_payloads.len() + redirect_payloads.len();
+        let skipped: Vec<&str> = SSRF_CANDIDATE_PARAMS
+            .iter()
+            .copied()
+            .filter(|p| !ssrf_targets.iter().any(|(_, t)| t == p))
+            .collect();
+        tracing::info!("📉 SSRF 參數掃描送出 {} 個請求 (完整掃描需 {} 個)", requests_sent, full_sweep);
+
+        self.add_note(
+            "A10:2021",
+            if skipped.is_empty() {
+                format!("SSRF 參數掃描共送出 {} 個請求", requests_sent)
+            } else {
+                format!("SSRF 參數掃描共送出 {} 個請求，因缺乏參數存在證據略過: {}", requests_sent, skipped.join(", "))
+            },
+            serde_json::json!({
+                "requests_sent": requests_sent,
+                "full_sweep_requests": full_sweep,
+                "requests_saved": full_sweep.saturating_sub(requests_sent),
+                "basis": ssrf_basis,
+                "tested_parameters": ssrf_targets.iter().map(|(url, param)| serde_json::json!({ "url": url, "parameter": param })).collect::<Vec<_>>(),
+                "skipped_parameters": skipped,
+                "redirect_parameters": redirect_targets.iter().map(|(url, param)| serde_json::json!({ "url": url, "parameter": param })).collect::<Vec<_>>(),
+            }),
+        );
 
-        for payload in redirect_payloads {
-            let test_url = format!("{}?redirect={}", base_url, urlencoding::encode(payload));
+        Ok(results)
+    }
 
-            match self.client.get(&test_url).send().await {
-                Ok(response) => {
-                    if let Some(location) = response.headers().get("location") {
-                        if let Ok(location_str) = location.to_str() {
-                            if location_str.contains("evil.com") {
-                                results.push(self.create_result(
-                                    task_id,
-                                    Severity::Medium,
-                                    "開放重定向 (Open Redirect) 漏洞".to_string(),
-                                    format!(
-                                        "應用程序存在開放重定向漏洞，可能被用於釣魚攻擊。Payload: {}。建議: 驗證重定向 URL 並使用白名單",
-                                        payload
-                                    ),
-                                    serde_json::json!({
-                                        "owasp": "A10:2021",
-                                        "type": "Open Redirect",
-                                        "payload": payload,
-                                        "redirect_to": location_str,
-                                        "url": test_url
-                                    })
-                                ));
-                                break;
-                            }
-                        }
-                    }
-                },
-                Err(_) => continue,
+    /// 盲 SSRF：每個參數注入帶有獨特 canary 的回呼網址，探測結束後輪詢 OOB 回呼伺服器，回傳送出的請求數
+    ///
+    /// 沒有設定回呼伺服器或輪詢失敗時，SSRF 只有回應內容的偵測，涵蓋範圍記錄為部分
+    async fn ssrf_out_of_band(&self, task_id: &str, targets: &[(String, String)], results: &mut Vec<ScanResult>) -> usize {
+        let collaborator = match self.collaborator.as_ref().map(oob::Collaborator::new) {
+            Some(Ok(collaborator)) => collaborator,
+            setup => {
+                let reason = match setup {
+                    Some(Err(e)) => format!("OOB 回呼伺服器設定無效: {}", e),
+                    _ => "未設定 OOB 回呼伺服器".to_string(),
+                };
+                self.add_note(
+                    "A10:2021",
+                    format!("{}，SSRF 只偵測回應中出現的內部內容，無法偵測盲 SSRF (部分涵蓋)", reason),
+                    serde_json::json!({ "coverage": "partial", "reason": "no_collaborator" }),
+                );
+                return 0;
             }
+        };
+
+        let mut probes = Vec::new();
+        for (endpoint, param) in targets {
+            let canary = control::canary("ssrf_oob");
+            let payload = collaborator.payload(&canary);
+            let test_url = path_traversal::with_raw_param(endpoint, param, &urlencoding::encode(&payload));
+            // 回應內容不影響判斷，只需要目標伺服器處理過請求
+            let _ = self.client.get(&test_url).send().await;
+            probes.push((canary, param.clone(), payload, test_url));
         }
 
-        Ok(results)
+        let canaries: Vec<String> = probes.iter().map(|(canary, ..)| canary.clone()).collect();
+        let hits = match collaborator.poll(&canaries).await {
+            Ok(hits) => hits,
+            Err(e) => {
+                self.add_note(
+                    "A10:2021",
+                    format!("{}，盲 SSRF 結果未知 (部分涵蓋)", e),
+                    serde_json::json!({ "coverage": "partial", "reason": "collaborator_poll_failed" }),
+                );
+                return probes.len();
+            }
+        };
+
+        for (canary, param, payload, test_url) in &probes {
+            let Some(interactions) = hits.get(canary) else {
+                continue;
+            };
+            results.push(self.create_result(
+                task_id,
+                Severity::Critical,
+                "盲 SSRF (服務器端請求偽造): 伺服器連線到外部回呼網址".to_string(),
+                format!(
+                    "參數 {} 注入的回呼網址被目標伺服器存取，攻擊者可讓伺服器對任意位址發出請求 (包含內部網路與雲端 metadata)。Payload: {}。建議: 1) 驗證和白名單 URL 2) 禁止伺服器對外連線到非必要的位址 3) 使用網絡隔離",
+                    param, payload
+                ),
+                serde_json::json!({
+                    "owasp": "A10:2021",
+                    "type": "Blind SSRF",
+                    "parameter": param,
+                    "payload": payload,
+                    "canary": canary,
+                    "interactions": interactions,
+                    "url": test_url
+                }),
+            ));
+        }
+        control::log(format!("📡 盲 SSRF: {} 個參數中 {} 個觸發回呼", probes.len(), hits.len()));
+        probes.len()
+    }
+
+    /// 參數映射：以一次帶有所有候選參數 (各自使用唯一標記值) 的請求找出會被反射的參數，
+    /// 再加上目標 URL 與頁面表單/連結中出現的 URL 類參數
+    async fn map_parameters(&self, base_url: &str) -> ParameterMap {
+        let mut map = ParameterMap::default();
+
+        if let Some(base) = self.base_response(base_url).await {
+            map.baseline_status = base.status;
+            map.baseline_body = base.body.to_lowercase();
+        }
+
+        // 頁面證據：目標 URL 本身的 query、表單欄位與連結中的參數
+        let mut page_params: Vec<String> = reqwest::Url::parse(base_url)
+            .map(|url| url.query_pairs().map(|(k, _)| k.to_lowercase()).collect())
+            .unwrap_or_default();
+        static PARAM: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        let param = PARAM.get_or_init(|| regex::Regex::new(r#"(?:name=["']|[?&])([a-z0-9_\-]{1,40})(?:["']|=)"#).unwrap());
+        page_params.extend(param.captures_iter(&map.baseline_body).map(|c| c[1].to_string()));
+
+        // 反射映射：一次請求測試所有候選參數
+        let marker = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let mut mapping_url = base_url.to_string();
+        for param in SSRF_CANDIDATE_PARAMS {
+            mapping_url = with_query_param(&mapping_url, param, &format!("rf{}{}", param, marker));
+        }
+        let reflected = match self.client.get(&mapping_url).send().await {
+            Ok(response) => client::text(response).await.to_lowercase(),
+            Err(_) => String::new(),
+        };
+        map.requests_sent += 1;
+
+        for param in SSRF_CANDIDATE_PARAMS {
+            let reflected_here = reflected.contains(&format!("rf{}{}", param, marker));
+            let on_page = page_params.iter().any(|p| p == param);
+            if reflected_here || on_page {
+                map.existing.push(param.to_string());
+            }
+        }
+        // 頁面上其他看起來接受 URL 的參數也一併測試
+        for param in page_params {
+            if is_url_like(&param) && !map.existing.contains(&param) {
+                map.existing.push(param);
+            }
+        }
+
+        map
     }
 
     // ========================================================================
     // Helper Methods
     // ========================================================================
+    fn add_note(&self, check: &str, message: String, details: serde_json::Value) {
+        if let Ok(mut notes) = self.notes.lock() {
+            notes.push(CoverageNote {
+                check: check.to_string(),
+                message,
+                details: Some(details),
+                degraded: false,
+            });
+        }
+    }
+
+    /// 過濾掉前置條件不符的路徑，並記錄為略過的檢查
+    fn skip_unmet_preconditions<'a>(&self, check: &str, paths: Vec<&'a str>) -> Vec<&'a str> {
+        let (allowed, skipped): (Vec<&str>, Vec<&str>) = paths.into_iter().partition(|path| self.context.allows_path(path));
+
+        if !skipped.is_empty() {
+            let details: Vec<serde_json::Value> = skipped
+                .iter()
+                .filter_map(|path| path_precondition(path).map(|p| serde_json::json!({ "path": path, "requires": p.description })))
+                .collect();
+            tracing::info!("⏭️  {} 略過 {} 個前置條件不符的探測", check, skipped.len());
+            self.add_note(
+                check,
+                format!("前置條件不符 (precondition not met)，略過 {} 個探測: {}", skipped.len(), skipped.join(", ")),
+                serde_json::json!({ "reason": "precondition_not_met", "skipped": details }),
+            );
+        }
+
+        allowed
+    }
+
+    /// 取出掃描過程中記錄的涵蓋範圍說明
+    async fn run_category(&self, category: &str, task_id: &str, url: &str) -> ScannerResult<Vec<ScanResult>> {
+        let base = self.base_response(url).await;
+        match category {
+            "A01" => self.a01_broken_access_control(task_id, url).await,
+            "A02" => self.a02_cryptographic_failures(task_id, url, base).await,
+            "A03" => self.a03_injection(task_id, url).await,
+            "A04" => self.a04_insecure_design(task_id, url, base).await,
+            "A05" => self.a05_security_misconfiguration(task_id, url, base).await,
+            "A06" => self.a06_vulnerable_components(task_id, base).await,
+            "A07" => self.a07_authentication_failures(task_id, url, base).await,
+            "A08" => self.a08_integrity_failures(task_id, url, base).await,
+            "A09" => self.a09_logging_failures(task_id, url).await,
+            "A10" => self.a10_ssrf(task_id, url).await,
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 目前的掃描模式是否允許此檢查；不允許時記錄為略過
+    fn allows(&self, check: &str) -> bool {
+        if self.scan_mode >= required_mode(check) {
+            return true;
+        }
+        self.skip_for_mode([check]);
+        false
+    }
+
+    /// 類別中目前的掃描模式不允許的檢查，例如 "path_traversal (full)"
+    fn out_of_mode_checks(&self, category: &str) -> Vec<String> {
+        CHECK_MODES
+            .iter()
+            .filter(|(c, _, mode)| *c == category && self.scan_mode < *mode)
+            .map(|(_, check, mode)| format!("{} ({})", check, mode))
+            .collect()
+    }
+
+    /// 類別中至少有一個檢查在目前的掃描模式下可以執行
+    fn category_allowed(&self, category: &str) -> bool {
+        CHECK_MODES.iter().any(|(c, _, mode)| *c == category && self.scan_mode >= *mode)
+    }
+
+    fn skip_for_mode<'a>(&self, checks: impl IntoIterator<Item = &'a str>) {
+        if let Ok(mut skipped) = self.mode_skipped.lock() {
+            skipped.extend(checks.into_iter().map(|check| format!("{} ({})", check, required_mode(check))));
+        }
+    }
+
+    /// 最近一次 scan_all 執行與略過的類別
+    pub fn category_report(&self) -> OwaspCategoryReport {
+        self.categories.lock().map(|report| report.clone()).unwrap_or_default()
+    }
+
+    pub fn take_notes(&self) -> Vec<CoverageNote> {
+        self.notes.lock().map(|mut notes| std::mem::take(&mut *notes)).unwrap_or_default()
+    }
+
     fn create_result(
         &self,
         task_id: &str,
@@ -1168,6 +3202,19 @@ impl OwaspScanner {
         description: String,
         raw_data: serde_json::Value,
     ) -> ScanResult {
+        let text = |keys: &[&str]| keys.iter().find_map(|key| raw_data.get(*key).and_then(|v| v.as_str()).map(str::to_string));
+        let affected_url = text(&["url", "endpoint", "http_url"]);
+        let details = FindingDetails {
+            owasp_category: text(&["owasp"]),
+            cwe_id: None,
+            // 產生發現的請求：受影響的 URL 最近一次的請求與回應
+            exchange: affected_url.as_deref().and_then(control::exchange),
+            affected_url,
+            payload: text(&["payload"]),
+            confidence: raw_data.get("confidence").and_then(|v| v.as_u64()).map(|c| c.min(100) as u8),
+            reflection_context: text(&["reflection_context"]),
+            evidence: text(&["evidence"]),
+        };
         ScanResult {
             id: Uuid::new_v4().to_string(),
             task_id: task_id.to_string(),
@@ -1177,6 +3224,434 @@ impl OwaspScanner {
             description: Some(description),
             raw_data: Some(serde_json::to_string(&raw_data).unwrap()),
             created_at: Utc::now(),
+            low_confidence: false,
+            cwe_ids: Vec::new(),
+            details: Some(details),
+        }
+    }
+}
+
+fn required_mode(check: &str) -> ScanMode {
+    CHECK_MODES.iter().find(|(_, c, _)| *c == check).map_or(ScanMode::Full, |(_, _, mode)| *mode)
+}
+
+/// 類別的檢查 id，與 time_box 的優先順序及檢查統計相同 (例如 "owasp.a03")
+fn category_check_id(category: &str) -> String {
+    ScanStage::Owasp(category.to_string()).message_key()
+}
+
+/// 遮蔽發現附帶的回應內容中的密鑰，與發現本身的遮蔽一致
+fn redact_exchange(result: &mut ScanResult, secrets: &[&str]) {
+    let Some(excerpt) = result
+        .details
+        .as_mut()
+        .and_then(|details| details.exchange.as_mut())
+        .and_then(|exchange| exchange.response_body_excerpt.as_mut())
+    else {
+        return;
+    };
+    for secret in secrets {
+        *excerpt = excerpt.replace(secret, &js_analysis::redact(secret));
+    }
+}
+
+fn has_sql_error(body: &str) -> bool {
+    let body = body.to_lowercase();
+    SQL_ERROR_SIGNATURES.iter().any(|err| body.contains(err))
+}
+
+fn with_query_param(url: &str, name: &str, value: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}{}={}", url, separator, name, urlencoding::encode(value))
+}
+
+fn is_url_like(param: &str) -> bool {
+    ["url", "uri", "link", "src", "dest", "target", "redirect", "return", "next", "callback", "fetch", "file", "path", "document", "feed", "host"]
+        .iter()
+        .any(|hint| param.contains(hint))
+}
+
+fn is_redirect_like(param: &str) -> bool {
+    ["redirect", "return", "next", "url", "dest", "goto", "continue", "target"]
+        .iter()
+        .any(|hint| param.contains(hint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const PATHS: [&str; 4] = ["/.env", "/wp-config.php", "/web.config", "/backup.zip"];
+
+    fn scanner(context: TechContext) -> OwaspScanner {
+        OwaspScanner::new(&ScanOptions::default()).unwrap().with_tech_context(context)
+    }
+
+    #[test]
+    fn unmet_preconditions_are_skipped_and_noted() {
+        // 技術檢測完成但沒有 WordPress 或 IIS
+        let scanner = scanner(TechContext::from_detection(&[], &[], false));
+
+        assert_eq!(scanner.skip_unmet_preconditions("A05:2021", PATHS.to_vec()), vec!["/.env", "/backup.zip"]);
+
+        let notes = scanner.take_notes();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].check, "A05:2021");
+        let details = notes[0].details.as_ref().unwrap();
+        assert_eq!(details["reason"], "precondition_not_met");
+        assert_eq!(
+            details["skipped"],
+            serde_json::json!([
+                { "path": "/wp-config.php", "requires": "CMS=WordPress" },
+                { "path": "/web.config", "requires": "Server contains IIS" },
+            ])
+        );
+    }
+
+    #[test]
+    fn ignore_preconditions_runs_every_probe() {
+        let scanner = scanner(TechContext::from_detection(&[], &[], true));
+
+        assert_eq!(scanner.skip_unmet_preconditions("A05:2021", PATHS.to_vec()), PATHS.to_vec());
+        assert!(scanner.take_notes().is_empty());
+    }
+
+    /// 帶登入 session 的本機應用：請求 /logout 會讓 session 失效；回傳網址與所有請求的路徑
+    async fn serve_app(session_valid: Arc<std::sync::atomic::AtomicBool>) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::sync::atomic::Ordering;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requested = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = requested.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0u8; 8192];
+                let read = socket.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                log.lock().unwrap().push(path.clone());
+
+                let body = match path.as_str() {
+                    "/" => r#"<a href="/profile">Profile</a> <a href="/logout">Log out</a>"#,
+                    "/profile" => r#"<a href="/orders?page=2">Orders</a> <a href="/account/delete">Delete account</a> <a href="/signout?next=/">Sign out</a>"#,
+                    "/orders?page=2" => "<p>orders</p>",
+                    _ => "",
+                };
+                if path.starts_with("/logout") || path.starts_with("/signout") || path.starts_with("/account/delete") {
+                    session_valid.store(false, Ordering::SeqCst);
+                }
+                let status = if body.is_empty() { "404 Not Found" } else { "200 OK" };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{}/", addr), requested)
+    }
+
+    #[tokio::test]
+    async fn crawl_never_follows_logout_links() {
+        let session_valid = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let (url, requested) = serve_app(session_valid.clone()).await;
+
+        let scanner = scanner(TechContext::default());
+        let crawl = scanner.crawl(&url).await;
+
+        assert!(session_valid.load(std::sync::atomic::Ordering::SeqCst), "requested: {:?}", requested.lock().unwrap());
+        assert!(crawl.pages.iter().any(|page| page.ends_with("/orders?page=2")));
+
+        let skipped: Vec<&str> = crawl.skipped.iter().map(|d| d.url.as_str()).collect();
+        for path in ["/logout", "/account/delete", "/signout?next=/"] {
+            assert!(skipped.iter().any(|url| url.ends_with(path)), "{} not skipped: {:?}", path, skipped);
+        }
+        assert!(crawl.skipped.iter().all(|d| matches!(d.reason, crate::scanners::crawl_policy::SkipReason::DeniedPattern(_))));
+    }
+
+    /// 對任何路徑都回應 200 的本機伺服器，內容帶有請求的路徑，因此不會被 soft-404 指紋過濾
+    async fn serve_catch_all() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0u8; 8192];
+                let read = socket.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let body = format!("<html><h1>Welcome</h1><p>{}</p></html>", path);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn catch_all_admin_probe_collapses_into_one_summary_finding() {
+        let url = serve_catch_all().await;
+        let scanner = scanner(TechContext::default());
+        let control = control::ScanControl::default();
+
+        let (findings, collapsed) = control::scope(control.clone(), async {
+            let findings = scanner.a01_broken_access_control("task-1", &url).await.unwrap();
+            let collapsed = finding_caps::collapse("task-1", "owasp.a01", 5, findings.clone());
+            (findings, collapsed)
+        })
+        .await;
+
+        // 每個管理後台路徑都「存在」
+        assert_eq!(findings.len(), 12);
+        assert_eq!(collapsed.len(), 6);
+        let summary = collapsed.last().unwrap();
+        assert!(summary.title.contains("符合 12 個項目"), "{}", summary.title);
+        assert!(summary.low_confidence);
+        assert_eq!(summary.severity, Some(Severity::High));
+
+        let discovered = control.take_discovered();
+        assert_eq!(discovered.len(), 1);
+        assert_eq!((discovered[0].check_id.as_str(), discovered[0].total, discovered[0].kept), ("owasp.a01", 12, 5));
+        assert_eq!(discovered[0].items.len(), 12);
+        assert!(discovered[0].items.iter().any(|item| item.location.as_deref().is_some_and(|l| l.ends_with("/wp-admin"))));
+
+        // 未超過上限的檢查原樣保留
+        let kept = control::scope(control.clone(), async { finding_caps::apply("task-1", "owasp.a01", findings) }).await;
+        assert_eq!(kept.len(), 12);
+        assert!(control.take_discovered().is_empty());
+    }
+
+    /// 每個請求延遲 latency 才回應的本機伺服器，內容為請求的路徑；/hang 永遠不回應
+    /// 每個請求延遲 latency 後回應路徑本身的本機伺服器，/hang 不回應；一併回傳同時處理中請求數的最高值
+    async fn serve_slow(latency: std::time::Duration) -> (String, Arc<AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let server_peak = peak.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (in_flight, peak) = (in_flight.clone(), server_peak.clone());
+                tokio::spawn(async move {
+                    let mut request = vec![0u8; 8192];
+                    let read = socket.read(&mut request).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&request[..read]).to_string();
+                    let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                    peak.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    if path == "/hang" {
+                        tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        return;
+                    }
+                    tokio::time::sleep(latency).await;
+                    // 回應前先減少，用戶端收到回應後送出的下一個請求不會與這個重疊
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        path.len(),
+                        path
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        (format!("http://{}", addr), peak)
+    }
+
+    fn scanner_with(http: HttpSettings) -> OwaspScanner {
+        OwaspScanner::new(&ScanOptions { http, ..ScanOptions::default() }).unwrap()
+    }
+
+    #[tokio::test]
+    async fn path_probes_run_concurrently_and_keep_input_order() {
+        let urls = |base: &str| -> Vec<String> { (0..12).map(|i| format!("{}/admin-{}", base, i)).collect() };
+
+        let (base, serial_peak) = serve_slow(std::time::Duration::from_millis(200)).await;
+        let serial = scanner_with(HttpSettings { concurrency: 1, ..HttpSettings::default() });
+        let serial_pages = serial.fetch_all(&urls(&base), true).await;
+        assert_eq!(serial_peak.load(Ordering::SeqCst), 1);
+
+        // 預設並行 5 個：伺服器同時處理的請求多於 1 個但不超過 5 個
+        let (base, peak) = serve_slow(std::time::Duration::from_millis(200)).await;
+        let concurrent = scanner_with(HttpSettings::default());
+        let pages = concurrent.fetch_all(&urls(&base), true).await;
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 5, "peak in-flight requests: {}", peak);
+
+        for (index, page) in pages.iter().chain(&serial_pages).enumerate() {
+            let page = page.as_ref().unwrap();
+            assert_eq!(String::from_utf8_lossy(&page.body), format!("/admin-{}", index % 12));
         }
     }
+
+    #[tokio::test]
+    async fn hung_probe_only_costs_its_own_timeout() {
+        let (base, _) = serve_slow(std::time::Duration::from_millis(50)).await;
+        let urls: Vec<String> = ["/hang", "/admin", "/backend"].iter().map(|path| format!("{}{}", base, path)).collect();
+        let scanner = scanner_with(HttpSettings { timeout_secs: Some(1), ..HttpSettings::default() });
+
+        let started = std::time::Instant::now();
+        let pages = scanner.fetch_all(&urls, true).await;
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(3), "took {:?}", started.elapsed());
+        assert!(pages[0].is_none());
+        assert_eq!(pages[1].as_ref().unwrap().body, b"/admin");
+        assert_eq!(pages[2].as_ref().unwrap().body, b"/backend");
+    }
+
+    #[test]
+    fn request_delay_forces_serial_probes() {
+        assert_eq!(HttpSettings::default().effective_concurrency(), 5);
+        assert_eq!(HttpSettings { concurrency: 8, ..HttpSettings::default() }.effective_concurrency(), 8);
+        assert_eq!(HttpSettings { request_delay_ms: 250, ..HttpSettings::default() }.effective_concurrency(), 1);
+        assert!(HttpSettings { concurrency: 0, ..HttpSettings::default() }.validate().is_err());
+    }
+
+    /// 對任何路徑都回應 200 與「找不到頁面」範本的本機伺服器；/.git/config 是真的設定檔，/.env 是與範本不同的首頁
+    async fn serve_soft_404() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0u8; 8192];
+                let read = socket.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+                let (content_type, body) = match path.as_str() {
+                    "/.git/config" => ("text/plain", "[core]\n\trepositoryformatversion = 0\n\tbare = false\n".to_string()),
+                    "/.env" => ("text/html", "<html><body><h1>Welcome to the shop</h1><p>Featured products</p></body></html>".to_string()),
+                    _ => (
+                        "text/html",
+                        format!(
+                            "<html><body><h1>Page not found</h1><p>Sorry, we could not find {} on this site. Please check the address, \
+                             use the search box above or return to the home page to keep browsing our catalogue of products, \
+                             guides and support articles.</p></body></html>",
+                            path
+                        ),
+                    ),
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    content_type,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    fn soft_404_paths(notes: &[CoverageNote], check: &str) -> Vec<String> {
+        notes
+            .iter()
+            .filter(|note| note.check == check)
+            .filter_map(|note| note.details.as_ref())
+            .filter(|details| details["reason"] == "soft_404")
+            .flat_map(|details| details["paths"].as_array().cloned().unwrap_or_default())
+            .filter_map(|path| path.as_str().map(str::to_string))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn soft_404_site_reports_no_admin_paths() {
+        let url = serve_soft_404().await;
+        let scanner = scanner(TechContext::default());
+
+        let findings = scanner.a01_broken_access_control("task-1", &url).await.unwrap();
+
+        assert!(findings.iter().all(|f| !f.title.contains("管理後台")), "{:?}", findings.iter().map(|f| &f.title).collect::<Vec<_>>());
+        let paths = soft_404_paths(&scanner.take_notes(), "A01:2021");
+        assert_eq!(paths.len(), 12);
+        assert!(paths.contains(&"/wp-admin".to_string()));
+    }
+
+    #[tokio::test]
+    async fn soft_404_site_reports_only_verified_sensitive_files() {
+        let url = serve_soft_404().await;
+        let scanner = scanner(TechContext::default());
+
+        let findings = scanner.a05_security_misconfiguration("task-1", &url, None).await.unwrap();
+
+        // 只有內容確實是 git 設定檔的 /.git/config；/.env 回傳的首頁未通過 KEY=VALUE 驗證
+        let exposed: Vec<&ScanResult> = findings.iter().filter(|f| f.title.contains("敏感文件")).collect();
+        assert_eq!(exposed.len(), 1, "{:?}", exposed.iter().map(|f| &f.title).collect::<Vec<_>>());
+        assert_eq!(exposed[0].severity, Some(Severity::Critical));
+        let raw: serde_json::Value = serde_json::from_str(exposed[0].raw_data.as_deref().unwrap()).unwrap();
+        assert_eq!(raw["path"], "/.git/config");
+        assert_eq!(raw["validator"], "git_config_core");
+
+        let notes = scanner.take_notes();
+        let paths = soft_404_paths(&notes, "A05:2021");
+        assert!(paths.contains(&"/wp-config.php".to_string()) && paths.contains(&"/backup.sql".to_string()), "{:?}", paths);
+        assert!(!paths.contains(&"/.env".to_string()) && !paths.contains(&"/.git/config".to_string()));
+        assert!(notes
+            .iter()
+            .any(|note| note.details.as_ref().is_some_and(|d| d["path"] == "/.env" && d["validator"] == "env_key_value")));
+    }
+
+    /// 計算基礎頁面請求次數的本機網站：只計算不帶探測標頭 (Origin、偽造的 Host) 的 GET /
+    async fn serve_counting() -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let base_fetches = Arc::new(AtomicUsize::new(0));
+        let counter = base_fetches.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let mut request = vec![0u8; 8192];
+                    let read = socket.read(&mut request).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                    let probe = request.contains("\r\norigin:") || request.contains("evil.example");
+                    if request.starts_with("get / ") && !probe {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                    }
+
+                    let body = "<html><head><title>Shop</title><script src=\"https://cdn.example.com/lib.js\"></script></head>\
+                                <body><h1>Welcome</h1><a href=\"/about\">About</a></body></html>";
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nSet-Cookie: session=abc; Path=/\r\nServer: nginx/1.18.0\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        (format!("http://{}/", addr), base_fetches)
+    }
+
+    #[tokio::test]
+    async fn scan_all_fetches_the_base_page_at_most_twice() {
+        let (url, base_fetches) = serve_counting().await;
+        let options = ScanOptions { scan_mode: ScanMode::Safe, ..ScanOptions::default() };
+        let scanner = Arc::new(OwaspScanner::new(&options).unwrap());
+
+        scanner.scan_all("task-1", &url, &OwaspConfig::default()).await.unwrap();
+
+        // 各檢查共用同一個基礎回應，不再各自下載首頁
+        let fetches = base_fetches.load(std::sync::atomic::Ordering::SeqCst);
+        assert!((1..=2).contains(&fetches), "base page fetched {} times", fetches);
+        assert!(scanner.base.get().is_some_and(|base| base.is_some()));
+    }
 }
@@ -0,0 +1,110 @@
+/**
+ * Target Page Identity
+ *
+ * 從基礎頁面取出 <title> 與 favicon 位置，供掃描列表在網址旁顯示頁面標題與圖示。
+ * 暖身時已取得基礎頁面，完整掃描不需要額外請求；圖示下載有大小上限，失敗時不影響掃描。
+ */
+
+use crate::favicons;
+use crate::models::ScanOptions;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::ScannerResult;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::OnceLock;
+
+/// 圖示大小上限 (位元組)，超過時不儲存
+const MAX_ICON_BYTES: usize = 64 * 1024;
+/// 標題的最大字元數
+const MAX_TITLE_CHARS: usize = 200;
+
+/// 頁面標題與圖示位置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PageIdentity {
+    pub title: Option<String>,
+    /// link rel="icon" 指向的網址，沒有時為 /favicon.ico
+    pub favicon_url: Option<String>,
+}
+
+/// 從 HTML 取出標題與圖示網址
+pub fn extract(page_url: &str, html: &str) -> PageIdentity {
+    static TITLE: OnceLock<Regex> = OnceLock::new();
+    static ICON: OnceLock<Regex> = OnceLock::new();
+    let title = TITLE.get_or_init(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap());
+    let icon = ICON.get_or_init(|| {
+        Regex::new(r#"(?is)<link\b[^>]*\brel\s*=\s*["'][^"']*\bicon\b[^"']*["'][^>]*>"#).unwrap()
+    });
+    static HREF: OnceLock<Regex> = OnceLock::new();
+    let href = HREF.get_or_init(|| Regex::new(r#"(?i)\bhref\s*=\s*["']([^"']+)["']"#).unwrap());
+
+    let title = title
+        .captures(html)
+        .map(|c| decode_entities(&c[1].split_whitespace().collect::<Vec<_>>().join(" ")))
+        .filter(|t| !t.is_empty())
+        .map(|t| t.chars().take(MAX_TITLE_CHARS).collect());
+
+    let base = reqwest::Url::parse(page_url).ok();
+    let favicon_url = base.and_then(|base| {
+        icon.find_iter(html)
+            .filter_map(|tag| href.captures(tag.as_str()))
+            .filter_map(|c| base.join(&c[1]).ok())
+            .find(|url| matches!(url.scheme(), "http" | "https"))
+            .or_else(|| base.join("/favicon.ico").ok())
+            .map(|url| url.to_string())
+    });
+
+    PageIdentity { title, favicon_url }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+}
+
+pub struct IdentityFetcher {
+    client: ScanClient,
+}
+
+impl IdentityFetcher {
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 10))
+                .build()?
+                .into(),
+        })
+    }
+
+    /// 沒有暖身的掃描 (純 SSL) 額外取得一次基礎頁面
+    pub async fn fetch_page(&self, url: &str) -> Option<PageIdentity> {
+        let body = self.client.get(url).send().await.ok()?.text().await.ok()?;
+        Some(extract(url, &body))
+    }
+
+    /// 下載圖示並存入快取目錄，回傳檔案路徑；超過大小上限或不是圖片時為 None
+    pub async fn cache_icon(&self, target: &str, icon_url: &str) -> Option<String> {
+        let response = self.client.get(icon_url).send().await.ok()?;
+        if !response.status().is_success() || response.content_length().is_some_and(|len| len as usize > MAX_ICON_BYTES) {
+            return None;
+        }
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_lowercase();
+        if !content_type.is_empty() && !content_type.starts_with("image/") && !content_type.starts_with("application/octet-stream") {
+            return None;
+        }
+        let bytes = response.bytes().await.ok()?;
+        if bytes.is_empty() || bytes.len() > MAX_ICON_BYTES {
+            return None;
+        }
+        favicons::save(target, &bytes, &content_type)
+    }
+}
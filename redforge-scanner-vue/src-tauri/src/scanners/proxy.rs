@@ -0,0 +1,342 @@
+/**
+ * Outbound Proxy
+ *
+ * 掃描流量經過掃描設定 (或全域設定) 的代理：reqwest 的請求由 `client_builder` 套用代理，
+ * 不經過 reqwest 的原始 socket (raw HTTP、TLS 交握與版本探測、憑證讀取、連接埠探測) 由 `Connector`
+ * 以 HTTP CONNECT 或 SOCKS5 建立通道。HTTPS 代理無法用於原始 socket，這些探測直接失敗而不是繞過代理。
+ *
+ * 掃描開始前以 `probe` 經由代理送出一個請求，代理設定錯誤時整個掃描立即以代理錯誤失敗，
+ * 而不是每個請求各自逾時。
+ */
+
+use crate::models::ProxySettings;
+use crate::scanners::{control, raw_http, ScannerError, ScannerResult};
+use base64::Engine;
+use std::io::{Read, Write};
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// CONNECT 回應標頭的長度上限
+const MAX_CONNECT_RESPONSE: usize = 8 * 1024;
+
+/// reqwest 使用的代理；帳密以 basic 認證 (HTTP) 或 SOCKS5 帳密認證送出
+pub fn reqwest_proxy(settings: &ProxySettings) -> ScannerResult<reqwest::Proxy> {
+    settings.validate().map_err(ScannerError::Proxy)?;
+    let mut url = reqwest::Url::parse(settings.url.trim()).map_err(|e| ScannerError::Proxy(format!("代理網址無效: {}", e)))?;
+    let socks = url.scheme().starts_with("socks");
+    // reqwest 的 basic_auth 不支援 SOCKS 代理，SOCKS5 的帳密只能放在網址中
+    if let (true, Some(username)) = (socks, &settings.username) {
+        let _ = url.set_username(username);
+        let _ = url.set_password(settings.password.as_deref());
+    }
+    let mut proxy = reqwest::Proxy::all(url.as_str()).map_err(|e| ScannerError::Proxy(format!("代理網址無效: {}", e)))?;
+    if let (false, Some(username)) = (socks, &settings.username) {
+        proxy = proxy.basic_auth(username, settings.password.as_deref().unwrap_or_default());
+    }
+    Ok(proxy)
+}
+
+/// 經由代理送出一個 GET 請求，回傳狀態碼與耗時；任何失敗都歸類為代理錯誤
+///
+/// 只確認代理的路徑是否可用，不驗證目標憑證 (憑證問題由 SSL 分析回報)
+pub async fn probe(settings: &ProxySettings, url: &str, wait: Duration) -> ScannerResult<(u16, Duration)> {
+    let label = display_url(settings);
+    let client = reqwest::Client::builder()
+        .proxy(reqwest_proxy(settings)?)
+        .danger_accept_invalid_certs(true)
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(wait)
+        .build()
+        .map_err(|e| ScannerError::Proxy(format!("無法套用代理 {}: {}", label, e)))?;
+
+    let started = Instant::now();
+    match client.get(url).send().await {
+        Ok(response) if response.status().as_u16() == 407 => {
+            Err(ScannerError::Proxy(format!("代理 {} 要求認證 (HTTP 407)，請確認帳號與密碼", label)))
+        }
+        Ok(response) => Ok((response.status().as_u16(), started.elapsed())),
+        Err(e) if e.is_timeout() => Err(ScannerError::Proxy(format!(
+            "經由代理 {} 連線到 {} 逾時 ({:.1} 秒)，請確認代理位址與是否允許此目標",
+            label,
+            url,
+            started.elapsed().as_secs_f64()
+        ))),
+        Err(e) => Err(ScannerError::Proxy(format!("無法經由代理 {} 連線到 {}: {}", label, url, error_chain(&e)))),
+    }
+}
+
+/// 不含帳密的代理網址，用於日誌、報告與錯誤訊息
+pub fn display_url(settings: &ProxySettings) -> String {
+    match reqwest::Url::parse(settings.url.trim()) {
+        Ok(mut url) => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.as_str().trim_end_matches('/').to_string()
+        }
+        Err(_) => settings.url.trim().to_string(),
+    }
+}
+
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        chain.push_str(": ");
+        chain.push_str(&error.to_string());
+        source = error.source();
+    }
+    chain
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RouteKind {
+    Http,
+    Https,
+    // remote_dns 為 socks5h：主機名稱由代理解析
+    Socks5 { remote_dns: bool },
+}
+
+/// 原始 socket 使用的代理
+#[derive(Debug, Clone)]
+pub struct ProxyRoute {
+    kind: RouteKind,
+    host: String,
+    port: u16,
+    credentials: Option<(String, String)>,
+    label: String,
+}
+
+impl ProxyRoute {
+    pub fn parse(settings: &ProxySettings) -> ScannerResult<Self> {
+        settings.validate().map_err(ScannerError::Proxy)?;
+        let url = reqwest::Url::parse(settings.url.trim()).map_err(|e| ScannerError::Proxy(format!("代理網址無效: {}", e)))?;
+        let (kind, default_port) = match url.scheme() {
+            "http" => (RouteKind::Http, 80),
+            "https" => (RouteKind::Https, 443),
+            "socks5" => (RouteKind::Socks5 { remote_dns: false }, 1080),
+            _ => (RouteKind::Socks5 { remote_dns: true }, 1080),
+        };
+        Ok(Self {
+            kind,
+            host: url.host_str().unwrap_or_default().trim_matches(['[', ']']).to_string(),
+            port: url.port().unwrap_or(default_port),
+            credentials: settings
+                .username
+                .clone()
+                .map(|username| (username, settings.password.clone().unwrap_or_default())),
+            label: display_url(settings),
+        })
+    }
+
+    /// 連線到代理並建立到 host:port 的通道；讀寫逾時都設為 wait
+    pub fn tunnel(&self, host: &str, port: u16, wait: Duration) -> ScannerResult<TcpStream> {
+        if self.kind == RouteKind::Https {
+            return Err(ScannerError::Proxy(format!(
+                "HTTPS 代理 {} 無法用於原始 socket 探測 (TLS 交握、連接埠掃描)，請改用 http:// 或 socks5:// 代理",
+                self.label
+            )));
+        }
+        let mut tcp = raw_http::connect_blocking(&self.host, self.port, wait)
+            .map_err(|e| ScannerError::Proxy(format!("無法連線到代理 {}: {}", self.label, e)))?;
+        match self.kind {
+            RouteKind::Socks5 { remote_dns } => self.socks5_connect(&mut tcp, host, port, remote_dns, wait)?,
+            _ => self.http_connect(&mut tcp, host, port, wait)?,
+        }
+        Ok(tcp)
+    }
+
+    fn http_connect(&self, tcp: &mut TcpStream, host: &str, port: u16, wait: Duration) -> ScannerResult<()> {
+        let authority = if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) };
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+        if let Some((username, password)) = &self.credentials {
+            let token = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+        }
+        request.push_str("\r\n");
+        tcp.write_all(request.as_bytes()).map_err(|e| self.io_error(e, wait))?;
+
+        // 逐位元組讀到標頭結束；之後的位元組屬於通道內的連線，不能多讀
+        let mut head = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            if head.len() >= MAX_CONNECT_RESPONSE {
+                return Err(ScannerError::Proxy(format!("代理 {} 的 CONNECT 回應過長", self.label)));
+            }
+            match tcp.read(&mut byte) {
+                Ok(0) => return Err(ScannerError::Proxy(format!("代理 {} 在建立通道前關閉連線", self.label))),
+                Ok(_) => head.push(byte[0]),
+                Err(e) => return Err(self.io_error(e, wait)),
+            }
+        }
+
+        let head = String::from_utf8_lossy(&head);
+        let status = head.split_whitespace().nth(1).and_then(|status| status.parse::<u16>().ok());
+        match status {
+            Some(200..=299) => Ok(()),
+            Some(407) => Err(ScannerError::Proxy(format!("代理 {} 要求認證 (HTTP 407)，請確認帳號與密碼", self.label))),
+            // 代理連不上目標 (例如連接埠關閉)，不是代理本身的問題
+            Some(status) => Err(ScannerError::Network {
+                source: format!("代理回應 HTTP {}", status).into(),
+                url: format!("tcp://{}", authority),
+            }),
+            None => Err(ScannerError::Proxy(format!("無法解析代理 {} 的 CONNECT 回應", self.label))),
+        }
+    }
+
+    /// RFC 1928 CONNECT；設定帳號時提供 RFC 1929 帳密認證
+    fn socks5_connect(&self, tcp: &mut TcpStream, host: &str, port: u16, remote_dns: bool, wait: Duration) -> ScannerResult<()> {
+        let methods: &[u8] = if self.credentials.is_some() { &[0x00, 0x02] } else { &[0x00] };
+        let mut greeting = vec![0x05, methods.len() as u8];
+        greeting.extend_from_slice(methods);
+        tcp.write_all(&greeting).map_err(|e| self.io_error(e, wait))?;
+
+        let mut choice = [0u8; 2];
+        tcp.read_exact(&mut choice).map_err(|e| self.io_error(e, wait))?;
+        if choice[0] != 0x05 {
+            return Err(ScannerError::Proxy(format!("{} 不是 SOCKS5 代理", self.label)));
+        }
+        match (choice[1], &self.credentials) {
+            (0x00, _) => {}
+            (0x02, Some((username, password))) => {
+                if username.len() > 255 || password.len() > 255 {
+                    return Err(ScannerError::Proxy("SOCKS5 帳號與密碼不可超過 255 位元組".to_string()));
+                }
+                let mut auth = vec![0x01, username.len() as u8];
+                auth.extend_from_slice(username.as_bytes());
+                auth.push(password.len() as u8);
+                auth.extend_from_slice(password.as_bytes());
+                tcp.write_all(&auth).map_err(|e| self.io_error(e, wait))?;
+
+                let mut status = [0u8; 2];
+                tcp.read_exact(&mut status).map_err(|e| self.io_error(e, wait))?;
+                if status[1] != 0x00 {
+                    return Err(ScannerError::Proxy(format!("SOCKS5 代理 {} 拒絕帳號或密碼", self.label)));
+                }
+            }
+            _ => return Err(ScannerError::Proxy(format!("SOCKS5 代理 {} 不接受提供的認證方式", self.label))),
+        }
+
+        let mut request = vec![0x05, 0x01, 0x00];
+        match host.parse::<IpAddr>() {
+            Ok(address) => push_address(&mut request, address),
+            Err(_) if remote_dns => {
+                if host.len() > 255 {
+                    return Err(ScannerError::InvalidTarget(format!("主機名稱過長: {}", host)));
+                }
+                request.extend_from_slice(&[0x03, host.len() as u8]);
+                request.extend_from_slice(host.as_bytes());
+            }
+            // socks5:// 由本機解析主機名稱，與 reqwest 的行為一致
+            Err(_) => {
+                let address = (host, port)
+                    .to_socket_addrs()
+                    .ok()
+                    .and_then(|mut addresses| addresses.next())
+                    .ok_or_else(|| ScannerError::Dns(format!("無法解析主機 {}", host)))?;
+                push_address(&mut request, address.ip());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        tcp.write_all(&request).map_err(|e| self.io_error(e, wait))?;
+
+        let mut reply = [0u8; 4];
+        tcp.read_exact(&mut reply).map_err(|e| self.io_error(e, wait))?;
+        if reply[1] != 0x00 {
+            let reason = match reply[1] {
+                0x02 => "代理的規則不允許此連線",
+                0x03 => "網路無法到達",
+                0x04 => "主機無法到達",
+                0x05 => "連線被拒",
+                0x06 => "TTL 過期",
+                _ => "一般錯誤",
+            };
+            let source = format!("SOCKS5 代理回應: {} (0x{:02x})", reason, reply[1]);
+            return Err(ScannerError::Network { source: source.into(), url: format!("tcp://{}:{}", host, port) });
+        }
+
+        // 略過代理綁定的位址與埠號
+        let remaining = match reply[3] {
+            0x01 => 4 + 2,
+            0x04 => 16 + 2,
+            0x03 => {
+                let mut length = [0u8; 1];
+                tcp.read_exact(&mut length).map_err(|e| self.io_error(e, wait))?;
+                length[0] as usize + 2
+            }
+            _ => return Err(ScannerError::Proxy(format!("無法解析 SOCKS5 代理 {} 的回應", self.label))),
+        };
+        let mut bound = vec![0u8; remaining];
+        tcp.read_exact(&mut bound).map_err(|e| self.io_error(e, wait))?;
+        Ok(())
+    }
+
+    fn io_error(&self, error: std::io::Error, wait: Duration) -> ScannerError {
+        match ScannerError::io(error, self.label.clone(), wait) {
+            ScannerError::Timeout { .. } => ScannerError::Proxy(format!("代理 {} 在 {} 秒內沒有回應", self.label, wait.as_secs())),
+            other => ScannerError::Proxy(format!("與代理 {} 的連線中斷: {}", self.label, other)),
+        }
+    }
+}
+
+fn push_address(request: &mut Vec<u8>, address: IpAddr) {
+    match address {
+        IpAddr::V4(address) => {
+            request.push(0x01);
+            request.extend_from_slice(&address.octets());
+        }
+        IpAddr::V6(address) => {
+            request.push(0x04);
+            request.extend_from_slice(&address.octets());
+        }
+    }
+}
+
+/// 原始 socket 的連線方式：直接連線或經過目前掃描的代理
+///
+/// 在 async 環境以 `current` 取得 (掃描控制是 task-local)，再帶進 blocking 執行緒
+#[derive(Clone, Default)]
+pub struct Connector {
+    route: Option<Arc<ProxyRoute>>,
+}
+
+impl Connector {
+    pub fn current() -> Self {
+        Self { route: control::proxy_route() }
+    }
+
+    pub fn is_proxied(&self) -> bool {
+        self.route.is_some()
+    }
+
+    /// 同步連線 (blocking 執行緒上的交握與原始請求)，讀寫逾時都設為 wait
+    pub fn connect_blocking(&self, host: &str, port: u16, wait: Duration) -> ScannerResult<TcpStream> {
+        match &self.route {
+            Some(route) => route.tunnel(host, port, wait),
+            None => raw_http::connect_blocking(host, port, wait),
+        }
+    }
+
+    /// 非同步連線；經過代理時在 blocking 執行緒上建立通道，再交給 tokio
+    pub async fn connect(&self, host: &str, port: u16, wait: Duration) -> ScannerResult<tokio::net::TcpStream> {
+        let endpoint = format!("{}:{}", host, port);
+        let Some(route) = self.route.clone() else {
+            return match tokio::time::timeout(wait, tokio::net::TcpStream::connect((host, port))).await {
+                Ok(Ok(stream)) => Ok(stream),
+                Ok(Err(e)) => Err(ScannerError::io(e, endpoint, wait)),
+                Err(_) => Err(ScannerError::Timeout { url: endpoint, after: Some(wait) }),
+            };
+        };
+
+        let target = host.to_string();
+        let tcp = tokio::task::spawn_blocking(move || route.tunnel(&target, port, wait))
+            .await
+            .map_err(|e| ScannerError::Internal(format!("代理通道建立中斷: {}", e)))??;
+        // 之後的讀寫逾時由呼叫端以 tokio 計時
+        tcp.set_read_timeout(None)
+            .and_then(|_| tcp.set_write_timeout(None))
+            .and_then(|_| tcp.set_nonblocking(true))
+            .map_err(|e| ScannerError::io(e, endpoint.clone(), wait))?;
+        tokio::net::TcpStream::from_std(tcp).map_err(|e| ScannerError::io(e, endpoint, wait))
+    }
+}
@@ -0,0 +1,268 @@
+/**
+ * Layered Proxy Disagreement
+ *
+ * 不允許送出 request smuggling 探測時的替代檢查：送出幾種合法但少見的請求型式
+ * (重複 Host + X-Forwarded-For、absolute-URI、非標準大小寫的標頭名稱)，
+ * 比較 Via / X-Cache / Server 等標頭與反射值，判斷前端與後端是否以不同方式處理。
+ *
+ * 結果只是 Info 等級的提示，標記目標值得人工進行 smuggling 測試，不代表存在漏洞。
+ */
+
+use crate::models::*;
+use crate::scanners::raw_http::{self, RawResponse, RawTarget};
+use crate::scanners::{ScannerError, ScannerResult};
+use chrono::Utc;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// 反射檢查用的值：TEST-NET-3 位址與保留的 .invalid 網域，不會指向真實主機
+const XFF_CANARY: &str = "203.0.113.77";
+const HOST_CANARY: &str = "redforge-canary.invalid";
+
+/// 顯示哪一層處理了請求的標頭
+const LAYER_HEADERS: &[&str] = &[
+    "server",
+    "via",
+    "x-cache",
+    "x-cache-status",
+    "cf-cache-status",
+    "x-served-by",
+    "x-amz-cf-id",
+    "x-varnish",
+];
+
+struct RequestVariant {
+    name: &'static str,
+    description: &'static str,
+    request: String,
+}
+
+/// 單一請求型式的觀察結果，直接寫入證據
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantObservation {
+    pub name: String,
+    pub description: String,
+    pub request: String,
+    pub status: Option<u16>,
+    pub status_line: Option<String>,
+    pub layer_headers: Vec<(String, String)>,
+    pub reflected: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl VariantObservation {
+    /// 用來比較的處理層特徵：狀態碼類別與處理層標頭名稱 / Server、Via 的值
+    fn fingerprint(&self) -> Option<(u16, Vec<String>)> {
+        let status_class = self.status? / 100;
+        let mut layers: Vec<String> = self
+            .layer_headers
+            .iter()
+            .map(|(name, value)| match name.as_str() {
+                "server" | "via" => format!("{}={}", name, value.to_lowercase()),
+                _ => name.clone(),
+            })
+            .collect();
+        layers.sort();
+        layers.dedup();
+        Some((status_class, layers))
+    }
+}
+
+pub struct ProxyDisagreementScanner {
+    target: RawTarget,
+    path: String,
+}
+
+impl ProxyDisagreementScanner {
+    pub fn new(url: &str) -> ScannerResult<Self> {
+        let target = RawTarget::from_url(url)?;
+        let after_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+        let path = match after_scheme.find(['/', '?']) {
+            Some(i) => after_scheme[i..].split('#').next().unwrap_or("/").to_string(),
+            None => "/".to_string(),
+        };
+        let path = if path.starts_with('?') { format!("/{}", path) } else { path };
+
+        Ok(Self { target, path })
+    }
+
+    /// 送出各請求型式並比較結果；發現處理不一致時回傳一個 Info 提示
+    pub async fn scan(&self, task_id: &str) -> ScannerResult<Option<ScanResult>> {
+        let mut observations = Vec::new();
+        for variant in self.variants() {
+            let (observation, error) = self.observe(variant).await;
+            // 基準請求失敗時沒有比較的對象，其餘型式不再送出
+            if let Some(e) = error.filter(|_| observations.is_empty()) {
+                return Err(e.context("基準請求失敗"));
+            }
+            observations.push(observation);
+        }
+
+        let Some(baseline) = observations.first().cloned() else {
+            return Ok(None);
+        };
+
+        let differences = compare(&baseline, &observations[1..]);
+        if differences.is_empty() {
+            tracing::info!("✅ 各請求型式處理一致，未觀察到分層代理差異");
+            return Ok(None);
+        }
+
+        Ok(Some(ScanResult {
+            id: Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            result_type: ResultType::Vulnerability,
+            severity: Some(Severity::Info),
+            title: "Layered proxy disagreement (分層代理處理不一致)".to_string(),
+            description: Some(format!(
+                "前端與後端對少見但合法的請求型式處理方式不同: {}。這不代表存在漏洞，但目標值得進行人工 HTTP request smuggling 測試",
+                differences.join("; ")
+            )),
+            raw_data: Some(serde_json::to_string(&serde_json::json!({
+                "check": "proxy_disagreement",
+                "target": self.target.origin(),
+                "differences": differences,
+                "variants": observations,
+            }))
+            .map_err(|e| ScannerError::Internal(format!("無法序列化觀察結果: {}", e)))?),
+            created_at: Utc::now(),
+            low_confidence: false,
+            cwe_ids: Vec::new(),
+            details: None,
+        }))
+    }
+
+    fn variants(&self) -> Vec<RequestVariant> {
+        let authority = self.target.authority();
+        let absolute = format!("{}{}", self.target.origin(), self.path);
+        let common = "Accept: */*\r\nUser-Agent: RedForge-Scanner\r\nConnection: close\r\n";
+
+        vec![
+            RequestVariant {
+                name: "baseline",
+                description: "一般請求",
+                request: format!("GET {} HTTP/1.1\r\nHost: {}\r\n{}\r\n", self.path, authority, common),
+            },
+            RequestVariant {
+                name: "duplicate_host_xff",
+                description: "重複 Host 標頭 (第二個為 canary) 並帶 X-Forwarded-For",
+                request: format!(
+                    "GET {} HTTP/1.1\r\nHost: {}\r\nHost: {}\r\nX-Forwarded-For: {}\r\n{}\r\n",
+                    self.path, authority, HOST_CANARY, XFF_CANARY, common
+                ),
+            },
+            RequestVariant {
+                name: "absolute_uri",
+                description: "absolute-URI request target",
+                request: format!("GET {} HTTP/1.1\r\nHost: {}\r\n{}\r\n", absolute, authority, common),
+            },
+            RequestVariant {
+                name: "absolute_uri_host_mismatch",
+                description: "absolute-URI 與不同的 Host 標頭 (canary)",
+                request: format!("GET {} HTTP/1.1\r\nHost: {}\r\n{}\r\n", absolute, HOST_CANARY, common),
+            },
+            RequestVariant {
+                name: "header_casing",
+                description: "非標準大小寫的標頭名稱",
+                request: format!(
+                    "GET {} HTTP/1.1\r\nhOsT: {}\r\nx-FORWARDED-for: {}\r\naCCEPT: */*\r\nUser-Agent: RedForge-Scanner\r\ncOnNeCtIoN: close\r\n\r\n",
+                    self.path, authority, XFF_CANARY
+                ),
+            },
+        ]
+    }
+
+    /// 送出一個請求型式；失敗時錯誤同時記錄在觀察結果中
+    async fn observe(&self, variant: RequestVariant) -> (VariantObservation, Option<ScannerError>) {
+        let response = raw_http::send(&self.target, &variant.request).await;
+        let mut observation = VariantObservation {
+            name: variant.name.to_string(),
+            description: variant.description.to_string(),
+            request: variant.request,
+            status: None,
+            status_line: None,
+            layer_headers: Vec::new(),
+            reflected: Vec::new(),
+            error: None,
+        };
+
+        match response {
+            Ok(response) => {
+                observation.status = Some(response.status);
+                observation.status_line = Some(response.status_line.clone());
+                observation.layer_headers = layer_headers(&response);
+                observation.reflected = reflected_canaries(&response);
+                (observation, None)
+            }
+            Err(e) => {
+                observation.error = Some(e.to_string());
+                (observation, Some(e))
+            }
+        }
+    }
+}
+
+fn layer_headers(response: &RawResponse) -> Vec<(String, String)> {
+    LAYER_HEADERS
+        .iter()
+        .flat_map(|name| {
+            response
+                .header_values(name)
+                .into_iter()
+                .map(move |value| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// 回應中出現的 canary 值與出現位置
+fn reflected_canaries(response: &RawResponse) -> Vec<String> {
+    let mut reflected = Vec::new();
+    for canary in [XFF_CANARY, HOST_CANARY] {
+        if let Some((name, _)) = response.headers.iter().find(|(_, value)| value.contains(canary)) {
+            reflected.push(format!("{} (標頭 {})", canary, name));
+        } else if response.body.contains(canary) {
+            reflected.push(format!("{} (內容)", canary));
+        }
+    }
+    reflected
+}
+
+/// 與基準請求比較，回傳可讀的差異描述
+///
+/// 只有在看得出多層架構 (處理層標頭或各回應的特徵不同) 時才視為差異，
+/// 單一伺服器直接拒絕重複 Host 之類的情況不回報。
+fn compare(baseline: &VariantObservation, variants: &[VariantObservation]) -> Vec<String> {
+    let Some((baseline_status, baseline_layers)) = baseline.fingerprint() else {
+        return Vec::new();
+    };
+    let baseline_layered = baseline_layers.iter().any(|l| !l.starts_with("server="));
+    let mut differences = Vec::new();
+
+    for variant in variants {
+        if !variant.reflected.is_empty() {
+            differences.push(format!("{}: 回應反射了 {}", variant.name, variant.reflected.join(", ")));
+            continue;
+        }
+
+        let Some((status, layers)) = variant.fingerprint() else {
+            continue;
+        };
+        if layers != baseline_layers {
+            differences.push(format!(
+                "{}: 回應來自不同的處理層 (基準 [{}]，此請求 [{}])",
+                variant.name,
+                baseline_layers.join(", "),
+                layers.join(", ")
+            ));
+        } else if status != baseline_status && baseline_layered {
+            differences.push(format!(
+                "{}: 經過代理層後狀態不同 (基準 {}，此請求 {})",
+                variant.name,
+                baseline.status_line.as_deref().unwrap_or_default(),
+                variant.status_line.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+
+    differences
+}
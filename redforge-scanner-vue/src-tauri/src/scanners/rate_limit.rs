@@ -0,0 +1,69 @@
+/**
+ * Request Rate Limiting
+ *
+ * 每次掃描一個 token bucket，由 ScanControl 持有：所有掃描器的 HTTP 請求、TLS 交握與 TCP 連線
+ * 送出前都在 `control::pace` 取得 token，各類別同時執行時對目標的總請求速率仍不超過設定的上限。
+ * 容量為 1，不允許突發，任何一秒內送出的請求都不超過 requests_per_second。
+ */
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// 令牌桶；容量 1，每 1 / rate 秒補充一個 token
+pub struct TokenBucket {
+    interval: Duration,
+    // 下一個 token 可用的時間
+    next: Mutex<Option<Instant>>,
+}
+
+impl TokenBucket {
+    /// requests_per_second 必須大於 0 (由 HttpSettings::validate 檢查)
+    pub fn new(requests_per_second: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / requests_per_second),
+            next: Mutex::new(None),
+        }
+    }
+
+    /// 等待直到取得 token；多個請求同時等待時依呼叫順序排隊
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next = self.next.lock().await;
+            let now = Instant::now();
+            let slot = next.map_or(now, |next| next.max(now));
+            *next = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+}
+
+/// 掃描送出的請求總數與實際速率，附在報告上供稽核
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RequestRateReport {
+    // HTTP 請求、TLS 交握與 TCP 連線的總數
+    pub total_requests: u64,
+    // 第一個到最後一個請求之間的時間
+    pub duration_ms: u64,
+    // 實際的平均每秒請求數；少於兩個請求時為 None
+    pub effective_rps: Option<f64>,
+    // 設定的每秒請求數上限，未設定時為 None
+    pub requests_per_second: Option<f64>,
+    pub request_delay_ms: u64,
+}
+
+impl RequestRateReport {
+    pub fn new(total_requests: u64, span: Option<(Instant, Instant)>, requests_per_second: Option<f64>, delay: Duration) -> Self {
+        let duration = span.map(|(first, last)| last.duration_since(first)).unwrap_or_default();
+        Self {
+            total_requests,
+            duration_ms: duration.as_millis() as u64,
+            effective_rps: (total_requests > 1 && !duration.is_zero())
+                .then(|| ((total_requests - 1) as f64 / duration.as_secs_f64() * 100.0).round() / 100.0),
+            requests_per_second,
+            request_delay_ms: delay.as_millis() as u64,
+        }
+    }
+}
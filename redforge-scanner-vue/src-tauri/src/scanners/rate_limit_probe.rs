@@ -0,0 +1,164 @@
+/**
+ * Authentication Rate Limiting
+ *
+ * 暴力破解的目標是登入、密碼重設與 token 端點，不是首頁：從爬取結果中找出這些端點，連續送出一小段突發請求
+ * (預設 15 個，間隔加上隨機抖動)，觀察伺服器是否開始限制。
+ *
+ * 任何一個回應是 429、403 或驗證碼挑戰，或回應時間明顯變慢 (伺服器以延遲節流)，都視為有限制。
+ * 突發請求同樣經過掃描的節流與請求數上限；剩餘的請求數不足時縮小突發的大小。
+ */
+
+use crate::scanners::crawler::{CrawlResult, Form};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// 最多測試的端點數
+pub const MAX_TARGETS: usize = 2;
+/// 突發至少要有的請求數，低於此數無法判斷
+pub const MIN_BURST: usize = 5;
+/// 路徑中代表認證功能的片段
+const AUTH_HINTS: &[&str] = &["login", "signin", "sign-in", "logon", "auth", "password", "reset", "forgot", "token", "oauth", "session"];
+/// 驗證碼與挑戰頁面的特徵 (小寫)
+const CHALLENGE_MARKERS: &[&str] = &["captcha", "cf-challenge", "challenge-platform", "too many requests", "rate limit"];
+/// 後段回應時間的中位數超過前段的倍數 (且至少多出 SLOWDOWN_MIN_MS) 時視為節流
+const SLOWDOWN_FACTOR: f64 = 2.0;
+const SLOWDOWN_MIN_MS: u64 = 300;
+/// 送出登入表單時使用的假憑證
+const PROBE_VALUE: &str = "redforge-rate-limit";
+
+/// 要測試的端點
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub url: String,
+    pub method: String,
+    /// (Content-Type, 內容)；GET 時為 None
+    pub body: Option<(String, Vec<u8>)>,
+    /// 端點的類型，例如 "登入表單"、"密碼重設"
+    pub label: &'static str,
+}
+
+/// 爬取結果中的認證端點：有密碼欄位的表單優先，其次是路徑看起來像認證功能的表單與頁面
+pub fn targets(crawl: &CrawlResult) -> Vec<Target> {
+    let mut targets: Vec<Target> = Vec::new();
+    let mut push = |target: Target| {
+        if targets.len() < MAX_TARGETS && !targets.iter().any(|t| t.url == target.url && t.method == target.method) {
+            targets.push(target);
+        }
+    };
+
+    let (login_forms, other_forms): (Vec<&Form>, Vec<&Form>) =
+        crawl.forms.iter().partition(|form| form.fields.iter().any(|field| field.kind == "password"));
+    for form in login_forms {
+        push(form_target(form, "登入表單"));
+    }
+    for form in other_forms.into_iter().filter(|form| is_auth_path(&form.action)) {
+        push(form_target(form, label(&form.action)));
+    }
+    for page in crawl.pages.iter().filter(|page| is_auth_path(page)) {
+        push(Target { url: page.clone(), method: "GET".to_string(), body: None, label: label(page) });
+    }
+    targets
+}
+
+fn form_target(form: &Form, label: &'static str) -> Target {
+    let values: Vec<(String, String)> = form
+        .fields
+        .iter()
+        .map(|field| {
+            let value = if field.is_text_like() { PROBE_VALUE.to_string() } else { field.value.clone() };
+            (field.name.clone(), value)
+        })
+        .collect();
+    if form.method == "GET" {
+        let mut url = form.action.clone();
+        for (name, value) in &values {
+            url = crate::scanners::path_traversal::with_raw_param(&url, name, &urlencoding::encode(value));
+        }
+        return Target { url, method: "GET".to_string(), body: None, label };
+    }
+    Target { url: form.action.clone(), method: form.method.clone(), body: Some(form.encode(&values)), label }
+}
+
+fn is_auth_path(url: &str) -> bool {
+    let path = reqwest::Url::parse(url).map(|url| url.path().to_lowercase()).unwrap_or_default();
+    AUTH_HINTS.iter().any(|hint| path.contains(hint))
+}
+
+fn label(url: &str) -> &'static str {
+    let url = url.to_lowercase();
+    if url.contains("reset") || url.contains("forgot") || url.contains("password") {
+        "密碼重設"
+    } else if url.contains("token") || url.contains("oauth") {
+        "API token 端點"
+    } else {
+        "登入端點"
+    }
+}
+
+/// 突發中的一個回應
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub status: u16,
+    pub elapsed: Duration,
+    pub challenge: bool,
+}
+
+impl Sample {
+    pub fn new(status: u16, elapsed: Duration, body: &str) -> Self {
+        let lower = body.to_lowercase();
+        Self { status, elapsed, challenge: CHALLENGE_MARKERS.iter().any(|marker| lower.contains(marker)) }
+    }
+}
+
+/// 突發的結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurstOutcome {
+    /// 狀態碼與次數 (請求失敗的不計)
+    pub statuses: BTreeMap<u16, usize>,
+    /// 限制的依據；沒有限制時為 None
+    pub limited_by: Option<String>,
+    pub first_median_ms: u64,
+    pub last_median_ms: u64,
+}
+
+impl BurstOutcome {
+    pub fn status_distribution(&self) -> serde_json::Value {
+        self.statuses.iter().map(|(status, count)| (status.to_string(), serde_json::json!(count))).collect()
+    }
+}
+
+/// 判斷突發是否遇到限制
+pub fn analyze(samples: &[Sample]) -> BurstOutcome {
+    let mut statuses = BTreeMap::new();
+    for sample in samples {
+        *statuses.entry(sample.status).or_insert(0) += 1;
+    }
+    let third = (samples.len() / 3).max(1);
+    let first_median_ms = median_ms(samples.iter().take(third));
+    let last_median_ms = median_ms(samples.iter().rev().take(third));
+
+    let limited_by = if let Some(sample) = samples.iter().find(|sample| matches!(sample.status, 429 | 403)) {
+        Some(format!("回應 HTTP {}", sample.status))
+    } else if samples.iter().any(|sample| sample.challenge) {
+        Some("回應包含驗證碼或挑戰頁面".to_string())
+    } else if last_median_ms as f64 > first_median_ms as f64 * SLOWDOWN_FACTOR && last_median_ms >= first_median_ms + SLOWDOWN_MIN_MS {
+        Some(format!("回應時間由 {} ms 增加到 {} ms", first_median_ms, last_median_ms))
+    } else {
+        None
+    };
+    BurstOutcome { statuses, limited_by, first_median_ms, last_median_ms }
+}
+
+fn median_ms<'a>(samples: impl Iterator<Item = &'a Sample>) -> u64 {
+    let mut durations: Vec<u64> = samples.map(|sample| sample.elapsed.as_millis() as u64).collect();
+    durations.sort_unstable();
+    durations.get(durations.len() / 2).copied().unwrap_or_default()
+}
+
+/// 請求之間的隨機抖動 (0 - jitter_ms)
+pub fn jitter(jitter_ms: u64) -> Duration {
+    if jitter_ms == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_millis((uuid::Uuid::new_v4().as_u128() % (jitter_ms as u128 + 1)) as u64)
+}
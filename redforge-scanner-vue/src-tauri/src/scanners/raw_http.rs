@@ -0,0 +1,253 @@
+/**
+ * Raw HTTP/1.1 Requests
+ *
+ * 送出逐位元組組裝的 HTTP/1.1 請求，用於 reqwest 無法表達的請求型式
+ * (absolute-URI request target、重複 Host、非標準大小寫的標頭名稱)。
+ * 每個請求使用獨立連線並帶 Connection: close，回應讀到連線關閉或達到上限為止。
+ */
+
+use crate::scanners::control;
+use crate::scanners::proxy::Connector;
+use crate::scanners::tls_probe::split_host_port;
+use crate::scanners::{ScannerError, ScannerResult};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// 只需要狀態列、標頭與少量內容，超過的部分不讀取
+const MAX_RESPONSE_BYTES: usize = 64 * 1024;
+
+/// 請求目標的連線資訊
+#[derive(Debug, Clone)]
+pub struct RawTarget {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+}
+
+impl RawTarget {
+    pub fn from_url(url: &str) -> ScannerResult<Self> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(ScannerError::InvalidTarget(format!("不支援的 URL: {}", url)));
+        };
+
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let authority = authority.rsplit('@').next().unwrap_or(authority);
+        let (host, port) = split_host_port(authority);
+        // split_host_port 預設 443，明文 HTTP 沒有指定埠號時改為 80
+        let port = if !tls && !authority.ends_with(&format!(":{}", port)) { 80 } else { port };
+
+        Ok(Self { host, port, tls })
+    }
+
+    /// Host 標頭的值，預設埠號省略
+    pub fn authority(&self) -> String {
+        let default_port = if self.tls { 443 } else { 80 };
+        let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+        if self.port == default_port { host } else { format!("{}:{}", host, self.port) }
+    }
+
+    pub fn origin(&self) -> String {
+        format!("{}://{}", if self.tls { "https" } else { "http" }, self.authority())
+    }
+}
+
+/// 解析後的回應；標頭名稱保留原樣，值為原始字串
+#[derive(Debug, Clone, Default)]
+pub struct RawResponse {
+    pub status: u16,
+    pub status_line: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RawResponse {
+    /// 指定標頭的所有值 (名稱不分大小寫)
+    pub fn header_values(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+}
+
+/// 送出原始請求並讀取回應
+pub async fn send(target: &RawTarget, request: &str) -> ScannerResult<RawResponse> {
+    control::checkpoint().await?;
+    control::pace().await?;
+
+    let mut request_line = request.split("\r\n").next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or("GET");
+    let request_target = request_line.next().unwrap_or("/");
+    let url = if request_target.starts_with('/') {
+        format!("{}{}", target.origin(), request_target)
+    } else {
+        request_target.to_string()
+    };
+    control::record_request(method, &url, request.split_once("\r\n\r\n").map(|(_, body)| body.as_bytes()));
+
+    let target = target.clone();
+    let request = request.to_string();
+    let connector = Connector::current();
+    let identity = control::tls_identity();
+    // 同步 socket 與 rustls 在 blocking 執行緒上執行
+    let bytes = tokio::task::spawn_blocking(move || exchange(&connector, identity.as_deref(), &target, request.as_bytes()))
+        .await
+        .map_err(|e| ScannerError::Internal(format!("原始請求中斷: {}", e)))??;
+    parse_response(&bytes).ok_or_else(|| ScannerError::Parse(format!("無法解析 {} 的 HTTP 回應", url)))
+}
+
+fn exchange(connector: &Connector, identity: Option<&TlsIdentity>, target: &RawTarget, request: &[u8]) -> ScannerResult<Vec<u8>> {
+    let tcp = connector.connect_blocking(&target.host, target.port, TIMEOUT)?;
+    let url = target.origin();
+
+    if target.tls {
+        let connection = rustls::ClientConnection::new(tls_config(identity)?, server_name(&target.host)?)
+            .map_err(|e| ScannerError::Tls(format!("TLS 初始化失敗: {}", e)))?;
+        let mut stream = rustls::StreamOwned::new(connection, tcp);
+        stream.write_all(request).map_err(|e| ScannerError::io(e, url.clone(), TIMEOUT))?;
+        read_capped(&mut stream, &url)
+    } else {
+        let mut stream = tcp;
+        stream.write_all(request).map_err(|e| ScannerError::io(e, url.clone(), TIMEOUT))?;
+        read_capped(&mut stream, &url)
+    }
+}
+
+/// 解析主機並以同步 socket 連線，讀寫逾時都設為 wait (blocking 執行緒上的交握與原始請求使用)
+pub fn connect_blocking(host: &str, port: u16, wait: Duration) -> ScannerResult<TcpStream> {
+    let address = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| ScannerError::Dns(format!("無法解析主機 {}: {}", host, e)))?
+        .next()
+        .ok_or_else(|| ScannerError::Dns(format!("無法解析主機 {}", host)))?;
+
+    let endpoint = format!("{}:{}", host, port);
+    let tcp = TcpStream::connect_timeout(&address, wait).map_err(|e| ScannerError::io(e, endpoint.clone(), wait))?;
+    tcp.set_read_timeout(Some(wait)).map_err(|e| ScannerError::io(e, endpoint.clone(), wait))?;
+    tcp.set_write_timeout(Some(wait)).map_err(|e| ScannerError::io(e, endpoint, wait))?;
+    Ok(tcp)
+}
+
+/// 與其他掃描器的 danger_accept_invalid_certs 一致，不驗證目標憑證 (憑證問題由 SSL 分析回報)
+struct AcceptAnyCertificate;
+
+impl rustls::client::ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+/// 用戶端憑證鏈與私鑰 (DER)；目標要求 mTLS 時，原始交握也要出示與 reqwest client 相同的憑證
+#[derive(Clone)]
+pub struct TlsIdentity {
+    pub chain: Vec<rustls::Certificate>,
+    pub key: rustls::PrivateKey,
+}
+
+/// 不驗證憑證的 rustls 設定，憑證分析另外處理；identity 為掃描設定的用戶端憑證
+pub fn tls_config(identity: Option<&TlsIdentity>) -> ScannerResult<Arc<rustls::ClientConfig>> {
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate));
+    Ok(Arc::new(with_client_auth(builder, identity)?))
+}
+
+/// 只啟用指定協定版本、不驗證憑證的 rustls 設定 (協定版本列舉使用)
+pub fn tls_config_with_versions(
+    versions: &[&'static rustls::SupportedProtocolVersion],
+    identity: Option<&TlsIdentity>,
+) -> ScannerResult<Arc<rustls::ClientConfig>> {
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(versions)
+        .map_err(|e| ScannerError::Tls(format!("TLS 設定失敗: {}", e)))?
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate));
+    Ok(Arc::new(with_client_auth(builder, identity)?))
+}
+
+fn with_client_auth(
+    builder: rustls::ConfigBuilder<rustls::ClientConfig, rustls::client::WantsClientCert>,
+    identity: Option<&TlsIdentity>,
+) -> ScannerResult<rustls::ClientConfig> {
+    match identity {
+        Some(identity) => builder
+            .with_client_auth_cert(identity.chain.clone(), identity.key.clone())
+            .map_err(|e| ScannerError::Config(format!("用戶端憑證無法用於 TLS 交握: {}", e))),
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+pub fn server_name(host: &str) -> ScannerResult<rustls::ServerName> {
+    match host.parse::<std::net::IpAddr>() {
+        Ok(ip) => Ok(rustls::ServerName::IpAddress(ip)),
+        Err(_) => rustls::ServerName::try_from(host)
+            .map_err(|e| ScannerError::InvalidTarget(format!("無效的主機名稱 {}: {}", host, e))),
+    }
+}
+
+/// 讀到連線關閉、逾時或達到上限；已讀到資料時逾時與未送 close_notify 都視為結束
+fn read_capped(stream: &mut impl Read, url: &str) -> ScannerResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+
+    while buffer.len() < MAX_RESPONSE_BYTES {
+        match stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+            Err(e) if matches!(e.kind(), ErrorKind::Interrupted) => continue,
+            Err(e) if !buffer.is_empty()
+                && matches!(e.kind(), ErrorKind::UnexpectedEof | ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ConnectionReset) =>
+            {
+                break
+            }
+            Err(e) => return Err(ScannerError::io(e, url.to_string(), TIMEOUT)),
+        }
+    }
+
+    buffer.truncate(MAX_RESPONSE_BYTES);
+    Ok(buffer)
+}
+
+fn parse_response(bytes: &[u8]) -> Option<RawResponse> {
+    let text = String::from_utf8_lossy(bytes);
+    let (head, body) = match text.split_once("\r\n\r\n") {
+        Some((head, body)) => (head, body),
+        None => (text.as_ref(), ""),
+    };
+
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next()?.to_string();
+    if !status_line.starts_with("HTTP/") {
+        return None;
+    }
+    let status = status_line.split_whitespace().nth(1)?.parse().ok()?;
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    Some(RawResponse {
+        status,
+        status_line,
+        headers,
+        body: body.to_string(),
+    })
+}
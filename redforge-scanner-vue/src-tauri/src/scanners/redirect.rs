@@ -0,0 +1,76 @@
+/**
+ * Cross-domain Redirect Detection
+ *
+ * 掃描 client-old.com 而它 301 到 client-new.com 時，有些檢查跟隨重定向、有些不跟隨，
+ * 發現會被歸到錯誤的主機。掃描開始時不跟隨重定向地追蹤基礎 URL，
+ * 鏈中有永久重定向 (301 / 308) 且最後落在其他網域時回報，由呼叫端停止掃描或固定在原主機。
+ */
+
+use crate::models::*;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::{ScannerError, ScannerResult};
+
+/// 追蹤的重定向次數上限
+const MAX_HOPS: usize = 5;
+
+pub struct RedirectDetector {
+    client: ScanClient,
+}
+
+impl RedirectDetector {
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 10))
+                .redirect(reqwest::redirect::Policy::none())
+                .build()?
+                .into(),
+        })
+    }
+
+    /// 基礎 URL 永久重定向到其他網域時回傳重定向鏈；只是同網域 (含 www. 差異) 或暫時重定向時回傳 None
+    pub async fn detect(&self, url: &str) -> ScannerResult<Option<TargetRedirect>> {
+        let origin = reqwest::Url::parse(url).map_err(|e| ScannerError::InvalidTarget(format!("無效的 URL {}: {}", url, e)))?;
+        let mut current = origin.clone();
+        let mut hops = Vec::new();
+
+        while hops.len() < MAX_HOPS {
+            let response = self.client.get(current.as_str()).send().await?;
+            let status = response.status().as_u16();
+            let Some(location) = response
+                .headers()
+                .get("location")
+                .and_then(|l| l.to_str().ok())
+                .filter(|_| response.status().is_redirection())
+            else {
+                break;
+            };
+            let Ok(next) = current.join(location) else { break };
+
+            hops.push(RedirectHop { url: current.to_string(), status, location: location.to_string() });
+            current = next;
+        }
+
+        let permanent = hops.iter().any(|hop| matches!(hop.status, 301 | 308));
+        if !permanent || same_site(origin.host_str(), current.host_str()) {
+            return Ok(None);
+        }
+
+        Ok(Some(TargetRedirect {
+            from: url.to_string(),
+            to: current.to_string(),
+            hops,
+            decision: RedirectDecision::Stopped,
+            follow_up_task_id: None,
+        }))
+    }
+}
+
+/// 忽略大小寫與開頭的 www. 後主機相同
+fn same_site(a: Option<&str>, b: Option<&str>) -> bool {
+    let normalize = |host: Option<&str>| {
+        let host = host.unwrap_or_default().to_lowercase();
+        host.strip_prefix("www.").map(str::to_string).unwrap_or(host)
+    };
+    normalize(a) == normalize(b)
+}
@@ -0,0 +1,140 @@
+/**
+ * Request Budget
+ *
+ * 每次掃描一個請求預算，由 ScanControl 持有：所有掃描器的 HTTP 請求、TLS 交握與 TCP 連線送出前都在
+ * `control::pace` 扣除。整個掃描的上限 (max_requests，預設 1000) 用完後不再啟動新的檢查，
+ * 未執行的檢查記錄在涵蓋範圍中；進行中的檢查剩餘的請求直接失敗，保留已收集的結果。
+ *
+ * 各檢查另有軟性預算 (預設為整體上限的 40%，可依檢查 id 個別設定)：超過時只停止該檢查，
+ * 其他檢查照常執行，避免大量送出 payload 的檢查用光整個預算。
+ */
+
+use crate::models::{HttpSettings, RequestBudgetStatus};
+use crate::scanners::control;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// 掃描設定的整體上限與各檢查的預算
+#[derive(Default, Clone)]
+struct BudgetLimits {
+    max_requests: Option<u64>,
+    check_budgets: BTreeMap<String, u64>,
+    check_share_percent: u8,
+}
+
+impl BudgetLimits {
+    /// 檢查的軟性預算：個別設定優先，其次是整體上限的比例；沒有整體上限也沒有個別設定時不限制
+    fn check_budget(&self, check_id: &str) -> Option<u64> {
+        if let Some(budget) = self.check_budgets.get(check_id) {
+            return Some(*budget);
+        }
+        self.max_requests
+            .map(|max| (max * self.check_share_percent as u64 / 100).max(1))
+    }
+}
+
+#[derive(Default)]
+pub struct RequestBudget {
+    limits: Mutex<BudgetLimits>,
+    sent: AtomicU64,
+    per_check: Mutex<BTreeMap<String, u64>>,
+    // 預算用完而未啟動的檢查，依原本的執行順序
+    skipped: Mutex<Vec<String>>,
+    // 執行中因預算用完而提前結束的檢查
+    truncated: Mutex<Vec<String>>,
+}
+
+impl RequestBudget {
+    /// 套用掃描設定的請求數上限與各檢查的預算
+    pub fn configure(&self, http: &HttpSettings) {
+        *self.limits.lock().unwrap_or_else(|e| e.into_inner()) = BudgetLimits {
+            max_requests: http.max_requests,
+            check_budgets: http.check_budgets.clone(),
+            check_share_percent: http.check_budget_percent,
+        };
+    }
+
+    fn limits(&self) -> BudgetLimits {
+        self.limits.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 扣除 check_id 的一個請求；檢查或整個掃描的預算已用完時回傳錯誤
+    pub fn spend(&self, check_id: &str) -> Result<(), String> {
+        let limits = self.limits();
+        if let Some(budget) = limits.check_budget(check_id) {
+            let mut per_check = self.per_check.lock().unwrap_or_else(|e| e.into_inner());
+            let used = per_check.entry(check_id.to_string()).or_insert(0);
+            if *used >= budget {
+                drop(per_check);
+                self.truncate(check_id, format!("檢查 {} 已達請求預算 ({})，其餘請求未送出", check_id, budget));
+                return Err(format!("檢查 {} 已達請求預算 ({})", check_id, budget));
+            }
+            *used += 1;
+        }
+        let sent = self.sent.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(max) = limits.max_requests.filter(|max| sent > *max) {
+            self.truncate(check_id, format!("單次掃描的請求預算 ({}) 已用盡，檢查 {} 提前結束", max, check_id));
+            return Err(format!("已達單次掃描的請求數上限 ({})", max));
+        }
+        Ok(())
+    }
+
+    fn truncate(&self, check_id: &str, message: String) {
+        let mut truncated = self.truncated.lock().unwrap_or_else(|e| e.into_inner());
+        if !truncated.iter().any(|id| id == check_id) {
+            control::log(format!("🧮 {}", message));
+            truncated.push(check_id.to_string());
+        }
+    }
+
+    /// 整個掃描的預算仍有剩餘時回傳 true；已用完時記錄為略過的檢查
+    pub fn admit(&self, check_id: &str) -> bool {
+        if self.remaining_total() != Some(0) {
+            return true;
+        }
+        let mut skipped = self.skipped.lock().unwrap_or_else(|e| e.into_inner());
+        if !skipped.iter().any(|id| id == check_id) {
+            control::log(format!("🧮 請求預算已用盡，略過檢查 {}", check_id));
+            skipped.push(check_id.to_string());
+        }
+        false
+    }
+
+    /// 實際送出的請求數 (超過上限而未送出的不計)
+    pub fn sent(&self) -> u64 {
+        let sent = self.sent.load(Ordering::SeqCst);
+        self.limits().max_requests.map_or(sent, |max| sent.min(max))
+    }
+
+    fn remaining_total(&self) -> Option<u64> {
+        let max = self.limits().max_requests?;
+        Some(max.saturating_sub(self.sent.load(Ordering::SeqCst)))
+    }
+
+    /// check_id 還能送出的請求數 (整體與檢查預算中較小者)；都沒有上限時為 None
+    pub fn remaining(&self, check_id: &str) -> Option<u64> {
+        let check = self.limits().check_budget(check_id).map(|budget| {
+            let used = self.per_check.lock().unwrap_or_else(|e| e.into_inner()).get(check_id).copied().unwrap_or(0);
+            budget.saturating_sub(used)
+        });
+        match (self.remaining_total(), check) {
+            (Some(total), Some(check)) => Some(total.min(check)),
+            (total, check) => total.or(check),
+        }
+    }
+
+    /// 預算的使用情況，供掃描進行中的狀態查詢與報告
+    pub fn status(&self) -> RequestBudgetStatus {
+        let max_requests = self.limits().max_requests;
+        let remaining = self.remaining_total();
+        RequestBudgetStatus {
+            max_requests,
+            used: self.sent(),
+            remaining,
+            exhausted: remaining == Some(0),
+            skipped: self.skipped.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+            truncated: self.truncated.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+}
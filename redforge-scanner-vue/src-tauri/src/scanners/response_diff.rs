@@ -0,0 +1,75 @@
+/**
+ * Response Normalization
+ *
+ * 比較兩次取得的同一頁面時，CSRF token、nonce、時間戳記、快取參數等每次請求都不同的內容會造成誤判。
+ * 先移除這些動態片段再比較或計算雜湊，只有實際內容改變時結果才不同。
+ */
+
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// 每次請求都可能不同的片段與替換值
+fn dynamic_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // CSP nonce 與 SRI 以外的 nonce 屬性
+            (r#"(?i)\bnonce\s*=\s*["'][^"']*["']"#, r#"nonce="""#),
+            // 名稱看起來是 token / csrf 的隱藏欄位與 meta 值
+            (
+                r#"(?i)(<(?:input|meta)[^>]*(?:name|id)\s*=\s*["'][^"']*(?:csrf|xsrf|token|nonce|authenticity)[^"']*["'][^>]*(?:value|content)\s*=\s*["'])[^"']*(["'])"#,
+                "$1$2",
+            ),
+            (
+                r#"(?i)(<(?:input|meta)[^>]*(?:value|content)\s*=\s*["'])[^"']*(["'][^>]*(?:name|id)\s*=\s*["'][^"']*(?:csrf|xsrf|token|nonce|authenticity)[^"']*["'])"#,
+                "$1$2",
+            ),
+            // ISO 8601 與常見日期時間
+            (r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2})?", "<time>"),
+            // 10 或 13 位數的 Unix 時間戳記
+            (r"\b1\d{9}(?:\d{3})?\b", "<timestamp>"),
+            // 靜態資源的快取參數 (?v=…、?_=…、?t=…)
+            (r#"(?i)([?&](?:v|ver|_|t|ts|cb|cache)=)[^&"'\s>]*"#, "$1"),
+            // UUID 與 32 字元以上的十六進位 (請求 id、session id)
+            (r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b", "<uuid>"),
+            (r"(?i)\b[0-9a-f]{32,}\b", "<hex>"),
+            // HTML 註解 (常見的建置時間、伺服器節點資訊)
+            (r"(?s)<!--.*?-->", ""),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).unwrap(), replacement))
+        .collect()
+    })
+}
+
+/// 移除動態片段並壓縮空白
+pub fn normalize(body: &str) -> String {
+    let mut normalized = body.to_string();
+    for (pattern, replacement) in dynamic_patterns() {
+        normalized = pattern.replace_all(&normalized, *replacement).into_owned();
+    }
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 正規化後內容的 SHA-256 (十六進位)
+pub fn content_hash(body: &str) -> String {
+    format!("{:x}", Sha256::digest(normalize(body).as_bytes()))
+}
+
+/// 兩段文字以空白分詞後的 Jaccard 相似度 (0.0 - 1.0)
+pub fn calculate_similarity(s1: &str, s2: &str) -> f64 {
+    let set1: HashSet<&str> = s1.split_whitespace().collect();
+    let set2: HashSet<&str> = s2.split_whitespace().collect();
+
+    let intersection = set1.intersection(&set2).count();
+    let union = set1.union(&set2).count();
+
+    if union == 0 {
+        // 兩者皆為空白時視為相同
+        1.0
+    } else {
+        intersection as f64 / union as f64
+    }
+}
@@ -0,0 +1,203 @@
+/**
+ * Sensitive File Validators
+ *
+ * 敏感檔案探測只看 200 不夠：catch-all 伺服器或 SPA 會在任何路徑回傳首頁。
+ * 這裡依檔案類型檢查內容，只有內容符合預期格式才視為真的外洩。
+ */
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 內容驗證結果
+#[derive(Debug, Clone)]
+pub enum FileValidation {
+    /// 內容符合檔案類型，附上符合的驗證器名稱與遮蔽過的節錄
+    Confirmed { validator: &'static str, excerpt: String },
+    /// 回傳 200 但內容不符，可能是誤判
+    Rejected { validator: &'static str, reason: String },
+    /// 此路徑沒有對應的驗證器
+    Unverified,
+}
+
+const EXCERPT_LINES: usize = 3;
+const EXCERPT_LINE_LEN: usize = 120;
+
+/// 依路徑選擇驗證器檢查回應內容
+pub fn validate(path: &str, body: &[u8]) -> FileValidation {
+    let text = String::from_utf8_lossy(body);
+    let lower_path = path.to_lowercase();
+
+    if lower_path.ends_with("/.env") {
+        validate_env(&text)
+    } else if lower_path.ends_with("/.git/config") {
+        validate_git_config(&text)
+    } else if lower_path.ends_with(".sql") {
+        validate_sql(&text)
+    } else if lower_path.ends_with("/web.config") {
+        validate_web_config(&text)
+    } else if lower_path.ends_with(".php") && lower_path.contains("phpinfo") {
+        validate_phpinfo(&text)
+    } else if lower_path.ends_with(".php") {
+        validate_php_source(&text)
+    } else if lower_path.ends_with("/.htaccess") {
+        validate_htaccess(&text)
+    } else if lower_path.ends_with("/.ds_store") {
+        validate_ds_store(body)
+    } else if lower_path.ends_with("/robots.txt") {
+        validate_robots(&text)
+    } else if lower_path.ends_with("/sitemap.xml") {
+        validate_sitemap(&text)
+    } else {
+        FileValidation::Unverified
+    }
+}
+
+fn validate_env(text: &str) -> FileValidation {
+    if looks_like_html(text) {
+        return rejected("env_key_value", "回應為 HTML 頁面");
+    }
+    static ENV_LINE: OnceLock<Regex> = OnceLock::new();
+    let re = ENV_LINE.get_or_init(|| Regex::new(r"^\s*(?:export\s+)?[A-Za-z_][A-Za-z0-9_]*\s*=").unwrap());
+    let lines: Vec<&str> = text.lines().filter(|l| re.is_match(l)).collect();
+    if lines.is_empty() {
+        return rejected("env_key_value", "沒有 KEY=VALUE 格式的設定行");
+    }
+    confirmed("env_key_value", &lines)
+}
+
+fn validate_git_config(text: &str) -> FileValidation {
+    if !text.contains("[core]") {
+        return rejected("git_config_core", "缺少 [core] 區段");
+    }
+    let lines: Vec<&str> = text.lines().filter(|l| l.trim_start().starts_with('[')).collect();
+    confirmed("git_config_core", &lines)
+}
+
+fn validate_sql(text: &str) -> FileValidation {
+    if looks_like_html(text) {
+        return rejected("sql_statements", "回應為 HTML 頁面");
+    }
+    static SQL_STATEMENT: OnceLock<Regex> = OnceLock::new();
+    let re = SQL_STATEMENT.get_or_init(|| Regex::new(r"(?i)^\s*(CREATE\s+TABLE|INSERT\s+INTO)\b").unwrap());
+    let lines: Vec<&str> = text.lines().filter(|l| re.is_match(l)).collect();
+    if lines.is_empty() {
+        return rejected("sql_statements", "沒有 CREATE/INSERT 陳述式");
+    }
+    confirmed("sql_statements", &lines)
+}
+
+fn validate_web_config(text: &str) -> FileValidation {
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+    let is_xml = trimmed.starts_with("<?xml") || trimmed.starts_with("<configuration");
+    if !is_xml || !text.contains("<configuration") {
+        return rejected("web_config_xml", "不是以 <configuration> 為根的 XML");
+    }
+    let lines: Vec<&str> = text.lines().filter(|l| l.contains("<configuration") || l.contains("<system.web")).collect();
+    confirmed("web_config_xml", &lines)
+}
+
+/// PHP 設定檔應被伺服器執行而非輸出；回傳 PHP 原始碼才是問題，執行後的空白頁不算
+fn validate_php_source(text: &str) -> FileValidation {
+    if !text.contains("<?php") {
+        return rejected("php_source", "回應不是 PHP 原始碼 (檔案已被執行)");
+    }
+    let lines: Vec<&str> = text.lines().filter(|l| l.contains("define(") || l.contains('$')).collect();
+    confirmed("php_source", &lines)
+}
+
+fn validate_phpinfo(text: &str) -> FileValidation {
+    if !text.contains("PHP Version") || !text.to_lowercase().contains("phpinfo") {
+        return rejected("phpinfo_page", "不是 phpinfo() 輸出");
+    }
+    let lines: Vec<&str> = text.lines().filter(|l| l.contains("PHP Version")).collect();
+    confirmed("phpinfo_page", &lines)
+}
+
+fn validate_htaccess(text: &str) -> FileValidation {
+    if looks_like_html(text) {
+        return rejected("htaccess_directives", "回應為 HTML 頁面");
+    }
+    static HTACCESS_DIRECTIVE: OnceLock<Regex> = OnceLock::new();
+    let re = HTACCESS_DIRECTIVE.get_or_init(|| Regex::new(r"(?i)^\s*(RewriteEngine|RewriteRule|RewriteCond|Options|Deny|Allow|Require|AuthType|<IfModule|Header|ErrorDocument)\b").unwrap());
+    let lines: Vec<&str> = text.lines().filter(|l| re.is_match(l)).collect();
+    if lines.is_empty() {
+        return rejected("htaccess_directives", "沒有 Apache 設定指令");
+    }
+    confirmed("htaccess_directives", &lines)
+}
+
+fn validate_ds_store(body: &[u8]) -> FileValidation {
+    // .DS_Store 以 0x00000001 "Bud1" 開頭
+    if body.len() >= 8 && body[..4] == [0, 0, 0, 1] && &body[4..8] == b"Bud1" {
+        FileValidation::Confirmed { validator: "ds_store_magic", excerpt: "Bud1 header".to_string() }
+    } else {
+        rejected("ds_store_magic", "缺少 .DS_Store 檔頭")
+    }
+}
+
+fn validate_robots(text: &str) -> FileValidation {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|l| {
+            let l = l.trim_start().to_lowercase();
+            l.starts_with("user-agent:") || l.starts_with("disallow:") || l.starts_with("allow:") || l.starts_with("sitemap:")
+        })
+        .collect();
+    if looks_like_html(text) || lines.is_empty() {
+        return rejected("robots_directives", "沒有 robots.txt 指令");
+    }
+    confirmed("robots_directives", &lines)
+}
+
+fn validate_sitemap(text: &str) -> FileValidation {
+    if !text.contains("<urlset") && !text.contains("<sitemapindex") {
+        return rejected("sitemap_xml", "不是 sitemap XML");
+    }
+    let lines: Vec<&str> = text.lines().filter(|l| l.contains("<urlset") || l.contains("<sitemapindex")).collect();
+    confirmed("sitemap_xml", &lines)
+}
+
+fn looks_like_html(text: &str) -> bool {
+    let head = text.trim_start().chars().take(512).collect::<String>().to_lowercase();
+    head.starts_with("<!doctype html") || head.starts_with("<html") || head.contains("<head") || head.contains("<body")
+}
+
+fn confirmed(validator: &'static str, lines: &[&str]) -> FileValidation {
+    let excerpt = lines
+        .iter()
+        .take(EXCERPT_LINES)
+        .map(|line| redact_line(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    FileValidation::Confirmed { validator, excerpt }
+}
+
+fn rejected(validator: &'static str, reason: &str) -> FileValidation {
+    FileValidation::Rejected { validator, reason: reason.to_string() }
+}
+
+/// 遮蔽節錄中的值：KEY=VALUE 只保留 KEY，引號內的字串改為 ***
+pub fn redact_line(line: &str) -> String {
+    let line = line.trim();
+    static QUOTED: OnceLock<Regex> = OnceLock::new();
+    let quoted = QUOTED.get_or_init(|| Regex::new(r#"'[^']*'|"[^"]*""#).unwrap());
+
+    let redacted = if let Some((key, _)) = line.split_once('=').filter(|(key, _)| !key.contains('<') && !key.contains('(')) {
+        format!("{}=***", key.trim_end())
+    } else {
+        // 保留第一個引號字串 (通常是設定名稱，例如 define('DB_PASSWORD', ...))，其餘遮蔽
+        let mut seen = 0;
+        quoted
+            .replace_all(line, |caps: &regex::Captures| {
+                seen += 1;
+                if seen == 1 && line.contains("define(") {
+                    caps[0].to_string()
+                } else {
+                    "'***'".to_string()
+                }
+            })
+            .into_owned()
+    };
+
+    redacted.chars().take(EXCERPT_LINE_LEN).collect()
+}
@@ -0,0 +1,127 @@
+/**
+ * Soft-404 Baseline
+ *
+ * 有些網站對任何路徑都回傳 200 加上「找不到頁面」的內容，路徑探測會把每個管理後台與敏感檔案都當成存在。
+ * 探測前先請求兩個一定不存在的路徑，記錄狀態碼、長度與正規化內容的雜湊作為 soft-404 指紋；
+ * 探測到的回應與指紋相同 (狀態碼一致且內容相似度達門檻) 時不視為路徑存在。
+ */
+
+use crate::scanners::response_diff;
+use uuid::Uuid;
+
+/// 內容相似度達此門檻視為同一個 soft-404 頁面
+pub const SIMILARITY_THRESHOLD: f64 = 0.9;
+
+/// 不存在路徑的回應樣本
+#[derive(Debug, Clone)]
+struct Sample {
+    status: u16,
+    length: usize,
+    hash: String,
+    normalized: String,
+}
+
+/// 目標對不存在路徑的回應指紋；取不到任何樣本時不過濾
+#[derive(Debug, Clone, Default)]
+pub struct Soft404 {
+    samples: Vec<Sample>,
+}
+
+/// 兩個隨機的不存在路徑：一般路徑與點開頭的檔案 (伺服器常對點檔案另有規則，例如一律 403)
+pub fn probe_urls(base_url: &str) -> Vec<String> {
+    let base = base_url.trim_end_matches('/');
+    vec![
+        format!("{}/redforge-probe-{}", base, Uuid::new_v4().simple()),
+        format!("{}/.redforge-probe-{}", base, Uuid::new_v4().simple()),
+    ]
+}
+
+impl Soft404 {
+    /// 由不存在路徑的回應 (狀態碼、內容) 建立指紋
+    pub fn from_responses<'a>(responses: impl IntoIterator<Item = (u16, &'a [u8])>) -> Self {
+        let samples = responses
+            .into_iter()
+            .map(|(status, body)| {
+                let text = String::from_utf8_lossy(body);
+                Sample {
+                    status,
+                    length: body.len(),
+                    hash: response_diff::content_hash(&text),
+                    normalized: response_diff::normalize(&text),
+                }
+            })
+            .collect();
+        Self { samples }
+    }
+
+    /// 指紋中是否有成功 (2xx) 的回應，也就是目標會對不存在的路徑回傳成功
+    pub fn is_active(&self) -> bool {
+        self.samples.iter().any(|sample| (200..300).contains(&sample.status))
+    }
+
+    /// 回應是否與不存在路徑的回應相同；狀態碼不同一律視為不同
+    pub fn matches(&self, status: u16, body: &[u8]) -> bool {
+        if self.samples.is_empty() {
+            return false;
+        }
+        let text = String::from_utf8_lossy(body);
+        let hash = response_diff::content_hash(&text);
+        let normalized = response_diff::normalize(&text);
+
+        self.samples.iter().filter(|sample| sample.status == status).any(|sample| {
+            if sample.hash == hash {
+                return true;
+            }
+            // 長度差異超過一倍的頁面不可能是同一個範本
+            let (shorter, longer) = (sample.length.min(body.len()), sample.length.max(body.len()));
+            if longer > 0 && shorter * 2 < longer {
+                return false;
+            }
+            response_diff::calculate_similarity(&sample.normalized, &normalized) >= SIMILARITY_THRESHOLD
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOT_FOUND: &str = "<html><body><h1>Page not found</h1><p>Sorry, we could not find /redforge-probe-1 on this site. \
+        Please check the address, use the search box above or return to the home page to keep browsing our catalogue \
+        of products, guides and support articles.</p></body></html>";
+
+    fn baseline(status: u16) -> Soft404 {
+        Soft404::from_responses([(status, NOT_FOUND.as_bytes()), (status, NOT_FOUND.replace("redforge-probe-1", "redforge-probe-2").as_bytes())])
+    }
+
+    #[test]
+    fn friendly_not_found_pages_match_the_baseline() {
+        let baseline = baseline(200);
+        assert!(baseline.is_active());
+
+        // 同一個範本，只有路徑不同
+        let admin = NOT_FOUND.replace("/redforge-probe-1", "/admin");
+        assert!(baseline.matches(200, admin.as_bytes()));
+        assert!(baseline.matches(200, NOT_FOUND.as_bytes()));
+    }
+
+    #[test]
+    fn different_status_or_content_is_a_real_page() {
+        let baseline = baseline(200);
+
+        assert!(!baseline.matches(403, NOT_FOUND.as_bytes()));
+        // 內容不同的頁面 (長度差異也超過一倍)
+        assert!(!baseline.matches(200, b"<html><body><h1>Admin login</h1><form><input name=user></form></body></html>"));
+    }
+
+    #[test]
+    fn real_404s_and_missing_samples_never_filter() {
+        let baseline = baseline(404);
+        assert!(!baseline.is_active());
+        assert!(!baseline.matches(200, NOT_FOUND.as_bytes()));
+
+        let empty = Soft404::default();
+        assert!(!empty.is_active());
+        assert!(!empty.matches(200, NOT_FOUND.as_bytes()));
+    }
+}
@@ -0,0 +1,107 @@
+/**
+ * Subresource Integrity
+ *
+ * 解析基礎頁面的 DOM，列出每個外部 `<script src>` 與 `<link rel=stylesheet>`：是否跨來源、
+ * integrity 屬性是否為可辨識的雜湊 (sha256 / sha384 / sha512 且長度正確)、是否以 HTTP 載入。
+ *
+ * 只有跨來源的資源需要 SRI：內嵌腳本與同源資源由網站自己控制，不列入未保護的計數。
+ * 清單本身也附在報告中，作為網站使用的第三方資源盤點。
+ */
+
+use base64::Engine;
+use schemars::JsonSchema;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Script,
+    Stylesheet,
+}
+
+/// 頁面載入的外部資源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExternalResource {
+    pub url: String,
+    pub kind: ResourceKind,
+    // 與頁面不同來源 (scheme、主機、連接埠)
+    pub cross_origin: bool,
+    // integrity 屬性中可辨識的雜湊演算法；沒有或無效時為空
+    pub integrity_algorithms: Vec<String>,
+    // 以 HTTP 載入 (頁面為 HTTPS 或資源跨來源時)
+    pub insecure: bool,
+}
+
+impl ExternalResource {
+    /// 跨來源且沒有有效的 integrity 屬性
+    pub fn unprotected(&self) -> bool {
+        self.cross_origin && self.integrity_algorithms.is_empty()
+    }
+
+    pub fn origin(&self) -> String {
+        reqwest::Url::parse(&self.url).map(|url| url.origin().ascii_serialization()).unwrap_or_default()
+    }
+}
+
+/// 頁面中的外部腳本與樣式表，依出現順序且不重複
+pub fn inventory(page_url: &str, html: &str) -> Vec<ExternalResource> {
+    let Ok(base) = reqwest::Url::parse(page_url) else {
+        return Vec::new();
+    };
+    let document = Html::parse_document(html);
+    let scripts = Selector::parse("script[src]").unwrap();
+    let links = Selector::parse("link[rel][href]").unwrap();
+
+    let elements = document
+        .select(&scripts)
+        .map(|element| (ResourceKind::Script, element, "src"))
+        .chain(document.select(&links).filter_map(|element| {
+            let rel = element.value().attr("rel")?;
+            rel.split_ascii_whitespace()
+                .any(|rel| rel.eq_ignore_ascii_case("stylesheet"))
+                .then_some((ResourceKind::Stylesheet, element, "href"))
+        }));
+
+    let mut resources: Vec<ExternalResource> = Vec::new();
+    for (kind, element, attribute) in elements {
+        let Some(value) = element.value().attr(attribute) else { continue };
+        let Ok(url) = base.join(value.trim()) else { continue };
+        if !matches!(url.scheme(), "http" | "https") || resources.iter().any(|r| r.url == url.as_str()) {
+            continue;
+        }
+        let cross_origin = url.origin() != base.origin();
+        resources.push(ExternalResource {
+            url: url.to_string(),
+            kind,
+            cross_origin,
+            integrity_algorithms: element.value().attr("integrity").map(integrity_algorithms).unwrap_or_default(),
+            insecure: url.scheme() == "http" && (base.scheme() == "https" || cross_origin),
+        });
+    }
+    resources
+}
+
+/// integrity 屬性中格式正確的雜湊的演算法 (例如 "sha384-<base64>")
+fn integrity_algorithms(value: &str) -> Vec<String> {
+    let mut algorithms: Vec<String> = Vec::new();
+    for token in value.split_ascii_whitespace() {
+        let Some((algorithm, digest)) = token.split_once('-') else { continue };
+        let algorithm = algorithm.to_ascii_lowercase();
+        let expected = match algorithm.as_str() {
+            "sha256" => 32,
+            "sha384" => 48,
+            "sha512" => 64,
+            _ => continue,
+        };
+        // 雜湊後可以帶 "?" 開頭的選項
+        let digest = digest.split('?').next().unwrap_or_default();
+        let valid = base64::engine::general_purpose::STANDARD
+            .decode(digest)
+            .is_ok_and(|bytes| bytes.len() == expected);
+        if valid && !algorithms.contains(&algorithm) {
+            algorithms.push(algorithm);
+        }
+    }
+    algorithms
+}
@@ -1,113 +1,571 @@
 use crate::models::*;
-use crate::scanners::ScannerResult;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::{ScannerError, ScannerResult};
+use crate::scanners::certificate;
+use crate::scanners::{control, hsts};
+use crate::scanners::tls_probe;
 use uuid::Uuid;
 use chrono::Utc;
 
+/// SSL 評級規則表
+///
+/// 分數從 100 起算，先扣分換算等級，再套用等級上限，最後依 HSTS 加上 "+"。
+///
+/// | 規則 | 影響 |
+/// |------|------|
+/// | 支援 SSL 3.0 / TLS 1.0 / 1.1 | -20 分 |
+/// | 未支援 TLS 1.3 | -10 分 |
+/// | 使用 RC4 / 3DES 密碼套件 | -30 分 |
+/// | 接受 EXPORT / NULL 密碼套件 | 上限 F |
+/// | 憑證過期、尚未生效或憑證鏈驗證失敗 | 上限 F |
+/// | 憑證 30 天內到期 | 上限 B |
+/// | 金鑰長度低於 2048 位元 | 上限 C |
+/// | 使用 SHA-1 簽章 | 上限 B |
+/// | 啟用 HSTS | 等級加上 "+"（F 除外） |
+pub const GRADE_RUBRIC: &[(&str, &str)] = &[
+    ("legacy_tls", "支援 SSL 3.0 / TLS 1.0 / 1.1: -20 分"),
+    ("missing_tls13", "未支援 TLS 1.3: -10 分"),
+    ("weak_cipher", "使用 RC4 / 3DES 密碼套件: -30 分"),
+    ("insecure_cipher", "接受 EXPORT / NULL 密碼套件: 上限 F"),
+    ("invalid_certificate", "憑證過期、尚未生效或憑證鏈驗證失敗: 上限 F"),
+    ("expiring_certificate", "憑證 30 天內到期: 上限 B"),
+    ("weak_key", "金鑰長度低於 2048 位元: 上限 C"),
+    ("sha1_signature", "使用 SHA-1 簽章: 上限 B"),
+    ("hsts", "啟用 HSTS: 等級加上 \"+\""),
+];
+
+const GRADE_ORDER: [&str; 5] = ["A", "B", "C", "D", "F"];
+
+/// 協定版本列舉的總時間上限；部分交握被丟棄 (不回應) 時也會在此時間內結束
+const PROTOCOL_PROBE_BUDGET: std::time::Duration = std::time::Duration::from_secs(20);
+
+/// 弱密碼套件分類與各類別探測的套件 (IANA 編號與名稱)
+///
+/// 每個類別以一次交握只提供該類別的套件，伺服器選中任何一個即代表接受
+const WEAK_CIPHER_SUITES: &[(&str, &[(u16, &str)])] = &[
+    ("NULL", &[
+        (0x0001, "TLS_RSA_WITH_NULL_MD5"),
+        (0x0002, "TLS_RSA_WITH_NULL_SHA"),
+        (0x003b, "TLS_RSA_WITH_NULL_SHA256"),
+        (0xc006, "TLS_ECDHE_ECDSA_WITH_NULL_SHA"),
+        (0xc010, "TLS_ECDHE_RSA_WITH_NULL_SHA"),
+    ]),
+    ("EXPORT", &[
+        (0x0003, "TLS_RSA_EXPORT_WITH_RC4_40_MD5"),
+        (0x0006, "TLS_RSA_EXPORT_WITH_RC2_CBC_40_MD5"),
+        (0x0008, "TLS_RSA_EXPORT_WITH_DES40_CBC_SHA"),
+        (0x0014, "TLS_DHE_RSA_EXPORT_WITH_DES40_CBC_SHA"),
+    ]),
+    ("RC4", &[
+        (0x0004, "TLS_RSA_WITH_RC4_128_MD5"),
+        (0x0005, "TLS_RSA_WITH_RC4_128_SHA"),
+        (0xc007, "TLS_ECDHE_ECDSA_WITH_RC4_128_SHA"),
+        (0xc011, "TLS_ECDHE_RSA_WITH_RC4_128_SHA"),
+    ]),
+    ("3DES", &[
+        (0x000a, "TLS_RSA_WITH_3DES_EDE_CBC_SHA"),
+        (0x0016, "TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA"),
+        (0xc008, "TLS_ECDHE_ECDSA_WITH_3DES_EDE_CBC_SHA"),
+        (0xc012, "TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA"),
+    ]),
+];
+
+/// 密碼套件所屬的弱類別；EXPORT 優先於 RC4 (例如 RC4_40 的 EXPORT 套件)
+pub fn weak_cipher_family(suite: &str) -> Option<&'static str> {
+    ["NULL", "EXPORT", "RC4", "3DES"].into_iter().find(|family| suite.contains(family))
+}
+
 pub struct SslScanner {
-    client: reqwest::Client,
+    client: ScanClient,
+    insecure_client: ScanClient,
+    active_probes: bool,
 }
 
 impl SslScanner {
-    pub fn new() -> ScannerResult<Self> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()?;
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        // 評估憑證是否有效，不受 verify_tls 影響一律驗證
+        let client = client::client_builder(options)?
+            .danger_accept_invalid_certs(false)
+            .timeout(client::timeout(options, 10))
+            .build()?.into();
+
+        // 憑證驗證失敗時用來確認服務本身是否可連線
+        let insecure_client = client::client_builder(options)?
+            .danger_accept_invalid_certs(true)
+            .timeout(client::timeout(options, 10))
+            .build()?.into();
+
+        // 主動探測 (例如 Heartbleed) 只在掃描設定明確啟用時執行
+        Ok(Self { client, insecure_client, active_probes: options.tls_active_probes })
+    }
 
-        Ok(Self { client })
+    /// 目標主機是否在 https_url 提供 HTTPS；只要完成 TLS 交握並取得回應即視為提供 (不驗證憑證)
+    pub async fn https_available(&self, https_url: &str) -> bool {
+        self.insecure_client.get(https_url).send().await.is_ok()
     }
 
-    pub async fn scan_ssl(&self, task_id: &str, hostname: &str) -> ScannerResult<SslAnalysis> {
+    /// 分析 hostname (或 URL) 的 SSL/TLS；port 指定時取代 URL 中的連接埠 (例如連接埠掃描找到的 TLS 服務)
+    pub async fn scan_ssl(&self, task_id: &str, hostname: &str, port: Option<u16>) -> ScannerResult<SslAnalysis> {
         let url = if hostname.starts_with("http") {
             hostname.to_string()
         } else {
             format!("https://{}", hostname)
         };
+        let mut parsed = reqwest::Url::parse(&url).map_err(|e| ScannerError::InvalidTarget(format!("無效的 URL {}: {}", url, e)))?;
+        let url = match port {
+            Some(port) => {
+                parsed
+                    .set_port(Some(port))
+                    .map_err(|_| ScannerError::InvalidTarget(format!("無法在 {} 設定連接埠 {}", url, port)))?;
+                parsed.to_string()
+            }
+            None => url,
+        };
+        let host = parsed.host_str().unwrap_or(hostname).to_string();
+        let port = parsed.port_or_known_default();
+        let https = parsed.scheme() == "https";
 
-        // 簡單檢查 HTTPS 連接
-        let _response = self.client.get(&url).send().await?;
+        // 直接交握取得葉憑證；目標沒有提供 TLS 服務時在此以明確的錯誤結束
+        let certificate = if https {
+            Some(certificate::fetch(&host, port.unwrap_or(443)).await?)
+        } else {
+            None
+        };
+
+        // 先以驗證憑證的連線測試，失敗時改用不驗證的連線判斷是否為憑證問題
+        let (response, chain_valid) = match self.client.get(&url).send().await {
+            Ok(response) => (response, true),
+            Err(_) => (self.insecure_client.get(&url).send().await?, false),
+        };
+
+        let hsts_enabled = response.headers()
+            .get("strict-transport-security")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| hsts::parse(v).is_enabled())
+            .unwrap_or(false);
 
         // 基本的 SSL 分析
         let mut analysis = SslAnalysis {
             id: Uuid::new_v4().to_string(),
             task_id: task_id.to_string(),
-            certificate_issuer: None,
-            certificate_subject: Some(hostname.to_string()),
-            valid_from: None,
-            valid_to: None,
-            signature_algorithm: None,
-            tls_versions: Some(vec!["TLS 1.2+".to_string()]), // 基本假設
+            certificate_issuer: certificate.as_ref().map(|c| c.issuer.clone()),
+            certificate_subject: Some(certificate.as_ref().map(|c| c.subject.clone()).unwrap_or_else(|| host.clone())),
+            valid_from: certificate.as_ref().map(|c| c.not_before),
+            valid_to: certificate.as_ref().map(|c| c.not_after),
+            signature_algorithm: certificate.as_ref().map(|c| c.signature_algorithm.clone()),
+            // 先記錄交握協商出的版本，下方列舉成功時以完整清單取代
+            tls_versions: certificate.as_ref().and_then(|c| c.negotiated_version.clone()).map(|v| vec![v]),
             cipher_suites: None,
-            vulnerabilities: Some(Vec::new()),
+            vulnerabilities: None,
             grade: None,
+            grade_factors: None,
+            chain_valid: Some(chain_valid),
+            key_size: certificate.as_ref().and_then(|c| c.key_size),
+            hsts_enabled: Some(hsts_enabled),
+            tls_vulnerabilities: None,
             created_at: Utc::now(),
+            analyzed_url: Some(url.clone()),
+            analyzed_port: port,
         };
 
-        // 檢查是否使用 HTTPS
-        if !url.starts_with("https://") {
-            analysis.vulnerabilities = Some(vec![
-                "未使用 HTTPS 加密傳輸".to_string()
-            ]);
+        if https {
+            let versions = self.enumerate_protocols(&host, port.unwrap_or(443)).await;
+            if !versions.is_empty() {
+                tracing::info!("🔐 {} 接受的協定版本: {}", host, versions.join(", "));
+                analysis.tls_versions = Some(versions.clone());
+            }
+
+            // 預設交握協商出的套件，加上伺服器接受的弱套件
+            let mut suites: Vec<String> = certificate.iter().filter_map(|c| c.negotiated_cipher.clone()).collect();
+            for suite in self.probe_weak_ciphers(&host, port.unwrap_or(443), &versions).await {
+                if !suites.contains(&suite) {
+                    suites.push(suite);
+                }
+            }
+            if !suites.is_empty() {
+                analysis.cipher_suites = Some(suites);
+            }
+        }
+
+        let mut tls_vulnerabilities = Vec::new();
+        if !https {
+            tls_vulnerabilities.push(Self::issue(
+                "no_https",
+                "未使用 HTTPS",
+                Severity::High,
+                "未使用 HTTPS 加密傳輸",
+                Some(url.clone()),
+            ));
             analysis.grade = Some("F".to_string());
+            analysis.grade_factors = Some(vec!["未使用 HTTPS: 等級 F".to_string()]);
         } else {
-            analysis.grade = Some("A".to_string());
+            tls_vulnerabilities.extend(self.check_vulnerabilities(&analysis));
+            for (id, severity, description) in certificate.iter().flat_map(|c| c.validity_issues(Utc::now())) {
+                let evidence = analysis.valid_from.zip(analysis.valid_to).map(|(from, to)| {
+                    format!("有效期間: {} ~ {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"))
+                });
+                tls_vulnerabilities.push(Self::issue(id, "憑證有效期間", severity, &description, evidence));
+            }
+            if !chain_valid {
+                tls_vulnerabilities.push(Self::issue(
+                    "invalid_chain",
+                    "憑證鏈驗證失敗",
+                    Severity::High,
+                    "憑證鏈驗證失敗 (自簽、簽發者不受信任或主機名稱不符)",
+                    analysis.certificate_issuer.as_ref().map(|issuer| format!("簽發者: {}", issuer)),
+                ));
+            }
+
+            let (grade, factors) = Self::calculate_grade(&analysis);
+            analysis.grade = Some(grade);
+            analysis.grade_factors = Some(factors);
+        }
+
+        tls_vulnerabilities.extend(self.infer_tls_vulnerabilities(&analysis));
+        if self.active_probes && https && control::pace().await.is_ok() {
+            control::record_request("HANDSHAKE", &format!("tls://{}:{}", host, port.unwrap_or(443)), None);
+            match tls_probe::probe_heartbleed(&host, port.unwrap_or(443)).await {
+                Ok(true) => tls_vulnerabilities.push(Self::heartbleed()),
+                Ok(false) => {}
+                Err(e) => tracing::warn!("⚠️  Heartbleed 探測失敗: {}", e),
+            }
         }
+        analysis.tls_vulnerabilities = Some(tls_vulnerabilities);
 
         Ok(analysis)
     }
 
-    fn calculate_grade(&self, analysis: &SslAnalysis) -> String {
-        let mut score = 100;
+    /// 個別以 SSL 3.0、TLS 1.0、1.1、1.2、1.3 交握，回傳伺服器接受的版本 (SSL3、TLS1_0 … TLS1_3)
+    ///
+    /// 所有嘗試同時進行，每個嘗試都受 [`PROTOCOL_PROBE_BUDGET`] 限制；連線失敗的版本視為不接受
+    pub async fn enumerate_protocols(&self, host: &str, port: u16) -> Vec<String> {
+        if control::checkpoint().await.is_err() {
+            return Vec::new();
+        }
+        let target = format!("tls://{}:{}", host, port);
+        let (ssl3, tls10, tls11, tls12, tls13) = tokio::join!(
+            Self::accepted_within_budget(&target, tls_probe::accepts_version(host, port, tls_probe::SSL3)),
+            Self::accepted_within_budget(&target, tls_probe::accepts_version(host, port, 0x0301)),
+            Self::accepted_within_budget(&target, tls_probe::accepts_version(host, port, 0x0302)),
+            Self::accepted_within_budget(&target, tls_probe::accepts_version(host, port, 0x0303)),
+            Self::accepted_within_budget(&target, tls_probe::accepts_tls13(host, port)),
+        );
+
+        [("SSL3", ssl3), ("TLS1_0", tls10), ("TLS1_1", tls11), ("TLS1_2", tls12), ("TLS1_3", tls13)]
+            .into_iter()
+            .filter(|(_, accepted)| *accepted)
+            .map(|(version, _)| version.to_string())
+            .collect()
+    }
+
+    /// 以 versions 中最高的 TLS 1.0 - 1.2 版本逐類別探測弱密碼套件，回傳伺服器選中的套件名稱
+    ///
+    /// 弱套件在 TLS 1.3 不存在；伺服器只接受 TLS 1.3 時不探測
+    pub async fn probe_weak_ciphers(&self, host: &str, port: u16, versions: &[String]) -> Vec<String> {
+        let Some(version) = [("TLS1_2", 0x0303u16), ("TLS1_1", 0x0302), ("TLS1_0", 0x0301)]
+            .into_iter()
+            .find(|(name, _)| versions.iter().any(|v| v == name))
+            .map(|(_, version)| version)
+        else {
+            return Vec::new();
+        };
+        if control::checkpoint().await.is_err() {
+            return Vec::new();
+        }
+
+        // 各類別同時探測
+        let probe = |index: usize| Self::accepted_weak_suite(host, port, version, WEAK_CIPHER_SUITES[index].1);
+        let (null, export, rc4, triple_des) = tokio::join!(probe(0), probe(1), probe(2), probe(3));
+        [null, export, rc4, triple_des].into_iter().flatten().collect()
+    }
+
+    /// 只提供 suites 交握，回傳伺服器選中的套件名稱
+    async fn accepted_weak_suite(host: &str, port: u16, version: u16, suites: &[(u16, &str)]) -> Option<String> {
+        control::pace().await.ok()?;
+        control::record_request("HANDSHAKE", &format!("tls://{}:{}", host, port), None);
+        let ids: Vec<u16> = suites.iter().map(|(id, _)| *id).collect();
+        match tokio::time::timeout(PROTOCOL_PROBE_BUDGET, tls_probe::negotiated_suite(host, port, version, &ids)).await {
+            Ok(Ok(Some(chosen))) => suites.iter().find(|(id, _)| *id == chosen).map(|(_, name)| name.to_string()),
+            _ => None,
+        }
+    }
+
+    /// 伺服器接受的弱密碼套件轉成掃描發現，與 OWASP 等其他發現一起列在報告中
+    pub fn weak_cipher_findings(&self, task_id: &str, analysis: &SslAnalysis) -> Vec<ScanResult> {
+        let suites = analysis.cipher_suites.clone().unwrap_or_default();
+        suites
+            .iter()
+            .filter_map(|suite| weak_cipher_family(suite).map(|family| (suite, family)))
+            .map(|(suite, family)| {
+                let (severity, reason) = match family {
+                    "NULL" => (Severity::Critical, "NULL 套件不加密，傳輸內容以明文送出"),
+                    "EXPORT" => (Severity::Critical, "EXPORT 套件只使用 40-56 位元金鑰，可在短時間內被暴力破解 (FREAK / Logjam)"),
+                    "RC4" => (Severity::High, "RC4 串流加密存在統計偏差，長時間連線可還原明文 (RFC 7465 已禁用)"),
+                    _ => (Severity::Medium, "3DES 使用 64 位元區塊，長連線下可遭受 SWEET32 生日攻擊"),
+                };
+                ScanResult {
+                    id: Uuid::new_v4().to_string(),
+                    task_id: task_id.to_string(),
+                    result_type: ResultType::Ssl,
+                    severity: Some(severity),
+                    title: format!("伺服器接受弱密碼套件: {}", suite),
+                    description: Some(format!("{}。建議: 在伺服器設定中移除 {} 類別的密碼套件", reason, family)),
+                    raw_data: Some(serde_json::json!({
+                        "check_id": "weak_cipher",
+                        "cipher_suite": suite,
+                        "family": family,
+                        "url": analysis.analyzed_url,
+                    }).to_string()),
+                    created_at: Utc::now(),
+                    low_confidence: false,
+                    cwe_ids: Vec::new(),
+                    details: Some(FindingDetails {
+                        affected_url: analysis.analyzed_url.clone(),
+                        ..FindingDetails::default()
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    async fn accepted_within_budget(target: &str, probe: impl std::future::Future<Output = ScannerResult<bool>>) -> bool {
+        if control::pace().await.is_err() {
+            return false;
+        }
+        control::record_request("HANDSHAKE", target, None);
+        matches!(tokio::time::timeout(PROTOCOL_PROBE_BUDGET, probe).await, Ok(Ok(true)))
+    }
+
+    /// 依已接受的協定版本與密碼套件推論已知 TLS 漏洞
+    pub fn infer_tls_vulnerabilities(&self, analysis: &SslAnalysis) -> Vec<TlsVulnerability> {
+        let versions = analysis.tls_versions.clone().unwrap_or_default();
+        let suites = analysis.cipher_suites.clone().unwrap_or_default();
+        let accepts = |version: &str| versions.iter().any(|v| v.contains(version));
+        let has_cbc = suites.iter().any(|s| s.contains("CBC"));
+        let matching = |pattern: &str| suites.iter().filter(|s| s.contains(pattern)).cloned().collect::<Vec<_>>().join(", ");
+        let mut vulns = Vec::new();
+
+        // POODLE: 只要接受 SSLv3 即受影響
+        if accepts("SSL3") {
+            vulns.push(TlsVulnerability {
+                id: "poodle".to_string(),
+                name: "POODLE".to_string(),
+                severity: Severity::High,
+                cve_ids: vec!["CVE-2014-3566".to_string()],
+                references: vec!["https://nvd.nist.gov/vuln/detail/CVE-2014-3566".to_string()],
+                description: "伺服器接受 SSLv3，CBC 填充可被中間人逐位元組解密。建議: 停用 SSLv3".to_string(),
+                active_probe: false,
+                evidence: Some(format!("接受的協定版本: {}", versions.join(", "))),
+            });
+        }
+
+        // BEAST: TLS 1.0 搭配 CBC 密碼套件
+        if accepts("TLS1_0") && has_cbc {
+            vulns.push(TlsVulnerability {
+                id: "beast".to_string(),
+                name: "BEAST".to_string(),
+                severity: Severity::Medium,
+                cve_ids: vec!["CVE-2011-3389".to_string()],
+                references: vec!["https://nvd.nist.gov/vuln/detail/CVE-2011-3389".to_string()],
+                description: "伺服器在 TLS 1.0 下接受 CBC 密碼套件，可遭受 BEAST 選擇明文攻擊。建議: 停用 TLS 1.0".to_string(),
+                active_probe: false,
+                evidence: Some(format!("接受的協定版本: {}；CBC 套件: {}", versions.join(", "), matching("CBC"))),
+            });
+        }
+
+        // SWEET32: 接受 64 位元區塊的 3DES
+        if suites.iter().any(|s| s.contains("3DES")) {
+            vulns.push(TlsVulnerability {
+                id: "sweet32".to_string(),
+                name: "SWEET32".to_string(),
+                severity: Severity::Medium,
+                cve_ids: vec!["CVE-2016-2183".to_string(), "CVE-2016-6329".to_string()],
+                references: vec!["https://sweet32.info/".to_string()],
+                description: "伺服器接受 3DES 密碼套件，長連線下可透過生日攻擊還原明文。建議: 移除 3DES".to_string(),
+                active_probe: false,
+                evidence: Some(format!("3DES 套件: {}", matching("3DES"))),
+            });
+        }
+
+        // LUCKY13: TLS 1.2 以下使用 CBC 模式的時序側通道
+        let pre_tls13 = versions.iter().any(|v| !v.contains("TLS1_3"));
+        if has_cbc && pre_tls13 {
+            vulns.push(TlsVulnerability {
+                id: "lucky13".to_string(),
+                name: "LUCKY13".to_string(),
+                severity: Severity::Low,
+                cve_ids: vec!["CVE-2013-0169".to_string()],
+                references: vec!["https://www.isg.rhul.ac.uk/tls/Lucky13.html".to_string()],
+                description: "伺服器使用 CBC 模式密碼套件，理論上可遭受 Lucky Thirteen 時序攻擊。建議: 優先使用 AEAD (GCM / ChaCha20)".to_string(),
+                active_probe: false,
+                evidence: Some(format!("CBC 套件: {}", matching("CBC"))),
+            });
+        }
+
+        vulns
+    }
+
+    fn heartbleed() -> TlsVulnerability {
+        TlsVulnerability {
+            id: "heartbleed".to_string(),
+            name: "Heartbleed".to_string(),
+            severity: Severity::Critical,
+            cve_ids: vec!["CVE-2014-0160".to_string()],
+            references: vec![
+                "https://heartbleed.com/".to_string(),
+                "https://nvd.nist.gov/vuln/detail/CVE-2014-0160".to_string(),
+            ],
+            description: "伺服器回應的 heartbeat 超出請求長度，OpenSSL 記憶體可被遠端讀取。建議: 立即升級 OpenSSL 並更換私鑰與憑證".to_string(),
+            active_probe: true,
+            // 只記錄判斷方式，洩漏的記憶體內容在探測時已丟棄
+            evidence: Some("heartbeat 回應長度超過送出的請求".to_string()),
+        }
+    }
+
+    /// 依 [`GRADE_RUBRIC`] 計算等級，並回傳影響等級的因素清單
+    pub fn calculate_grade(analysis: &SslAnalysis) -> (String, Vec<String>) {
+        let mut score: i32 = 100;
+        let mut factors = Vec::new();
 
         // 檢查 TLS 版本
         if let Some(versions) = &analysis.tls_versions {
-            if versions.iter().any(|v| v.contains("TLS1_0") || v.contains("TLS1_1")) {
+            if versions.iter().any(|v| v.contains("SSL3") || v.contains("TLS1_0") || v.contains("TLS1_1")) {
                 score -= 20;
+                factors.push(Self::rubric_rule("legacy_tls"));
             }
             if !versions.iter().any(|v| v.contains("TLS1_3")) {
                 score -= 10;
+                factors.push(Self::rubric_rule("missing_tls13"));
             }
         }
 
         // 檢查密碼套件
+        let mut insecure_cipher = false;
         if let Some(suites) = &analysis.cipher_suites {
             if suites.iter().any(|s| s.contains("RC4") || s.contains("3DES")) {
                 score -= 30;
+                factors.push(Self::rubric_rule("weak_cipher"));
             }
+            insecure_cipher = suites.iter().any(|s| s.contains("EXPORT") || s.contains("NULL"));
         }
 
-        match score {
-            90..=100 => "A+".to_string(),
-            80..=89 => "A".to_string(),
-            70..=79 => "B".to_string(),
-            60..=69 => "C".to_string(),
-            50..=59 => "D".to_string(),
-            _ => "F".to_string(),
+        let mut grade = match score {
+            80..=100 => "A",
+            70..=79 => "B",
+            60..=69 => "C",
+            50..=59 => "D",
+            _ => "F",
+        };
+
+        if insecure_cipher {
+            grade = Self::cap_grade(grade, "F");
+            factors.push(Self::rubric_rule("insecure_cipher"));
+        }
+
+        // 憑證問題直接限制等級上限
+        let now = Utc::now();
+        let expired = analysis.valid_to.map(|t| t < now).unwrap_or(false);
+        let not_yet_valid = analysis.valid_from.map(|t| t > now).unwrap_or(false);
+        if expired || not_yet_valid || analysis.chain_valid == Some(false) {
+            grade = Self::cap_grade(grade, "F");
+            factors.push(Self::rubric_rule("invalid_certificate"));
+        } else if analysis.valid_to.is_some_and(|t| t < now + chrono::Duration::days(certificate::EXPIRY_WARNING_DAYS)) {
+            grade = Self::cap_grade(grade, "B");
+            factors.push(Self::rubric_rule("expiring_certificate"));
+        }
+
+        if analysis.key_size.map(|bits| bits < 2048).unwrap_or(false) {
+            grade = Self::cap_grade(grade, "C");
+            factors.push(Self::rubric_rule("weak_key"));
+        }
+
+        let sha1 = analysis.signature_algorithm
+            .as_ref()
+            .map(|alg| alg.to_lowercase().replace('-', "").contains("sha1"))
+            .unwrap_or(false);
+        if sha1 {
+            grade = Self::cap_grade(grade, "B");
+            factors.push(Self::rubric_rule("sha1_signature"));
         }
+
+        let mut grade = grade.to_string();
+        if analysis.hsts_enabled == Some(true) && grade != "F" {
+            grade.push('+');
+            factors.push(Self::rubric_rule("hsts"));
+        }
+
+        (grade, factors)
     }
 
-    fn check_vulnerabilities(&self, analysis: &SslAnalysis) -> Vec<String> {
+    fn rubric_rule(key: &str) -> String {
+        GRADE_RUBRIC.iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, description)| description.to_string())
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    fn cap_grade(grade: &'static str, cap: &'static str) -> &'static str {
+        if grade_rank(grade) < grade_rank(cap) { cap } else { grade }
+    }
+
+    /// 已棄用的協定版本與不安全的密碼套件 (3DES 由 SWEET32 涵蓋)
+    fn check_vulnerabilities(&self, analysis: &SslAnalysis) -> Vec<TlsVulnerability> {
+        let versions = analysis.tls_versions.clone().unwrap_or_default();
+        let suites = analysis.cipher_suites.clone().unwrap_or_default();
         let mut vulns = Vec::new();
 
-        if let Some(versions) = &analysis.tls_versions {
-            if versions.iter().any(|v| v.contains("TLS1_0")) {
-                vulns.push("支援已棄用的 TLS 1.0 協定（易受 POODLE 攻擊）".to_string());
-            }
-            if versions.iter().any(|v| v.contains("TLS1_1")) {
-                vulns.push("支援已棄用的 TLS 1.1 協定".to_string());
+        for (version, id, name) in [("TLS1_0", "deprecated_tls10", "TLS 1.0"), ("TLS1_1", "deprecated_tls11", "TLS 1.1")] {
+            if versions.iter().any(|v| v == version) {
+                vulns.push(Self::issue(
+                    id,
+                    &format!("支援 {}", name),
+                    Severity::Medium,
+                    &format!("支援已棄用的 {} 協定 (RFC 8996)。建議: 只啟用 TLS 1.2 以上", name),
+                    Some(format!("接受的協定版本: {}", versions.join(", "))),
+                ));
             }
         }
 
-        if let Some(suites) = &analysis.cipher_suites {
-            if suites.iter().any(|s| s.contains("RC4")) {
-                vulns.push("使用不安全的 RC4 加密算法".to_string());
-            }
-            if suites.iter().any(|s| s.contains("3DES")) {
-                vulns.push("使用弱加密的 3DES 算法".to_string());
+        let weak = [
+            ("rc4_cipher", "RC4 密碼套件", "RC4", Severity::High, "使用不安全的 RC4 加密算法 (RFC 7465 已禁用)", &[][..]),
+            (
+                "export_cipher",
+                "EXPORT 密碼套件",
+                "EXPORT",
+                Severity::Critical,
+                "接受 EXPORT 等級的密碼套件，易受 FREAK / Logjam 攻擊",
+                &["CVE-2015-0204", "CVE-2015-4000"][..],
+            ),
+            ("null_cipher", "NULL 密碼套件", "NULL", Severity::Critical, "接受不加密的 NULL 密碼套件", &[][..]),
+        ];
+        for (id, name, pattern, severity, description, cve_ids) in weak {
+            let accepted: Vec<&str> = suites.iter().filter(|s| s.contains(pattern)).map(String::as_str).collect();
+            if !accepted.is_empty() {
+                let evidence = Some(format!("接受的套件: {}", accepted.join(", ")));
+                let mut vulnerability = Self::issue(id, name, severity, description, evidence);
+                vulnerability.cve_ids = cve_ids.iter().map(|id| id.to_string()).collect();
+                vulnerability.references =
+                    cve_ids.iter().map(|id| format!("https://nvd.nist.gov/vuln/detail/{}", id)).collect();
+                vulns.push(vulnerability);
             }
         }
 
         vulns
     }
+
+    /// 沒有 CVE 的設定問題
+    fn issue(id: &str, name: &str, severity: Severity, description: &str, evidence: Option<String>) -> TlsVulnerability {
+        TlsVulnerability {
+            id: id.to_string(),
+            name: name.to_string(),
+            severity,
+            cve_ids: Vec::new(),
+            references: Vec::new(),
+            description: description.to_string(),
+            active_probe: false,
+            evidence,
+        }
+    }
 }
 
 pub async fn quick_ssl_check(url: &str) -> ScannerResult<bool> {
@@ -118,3 +576,203 @@ pub async fn quick_ssl_check(url: &str) -> ScannerResult<bool> {
     let response = client.get(url).send().await?;
     Ok(response.url().scheme() == "https")
 }
+
+/// 評級由好到壞的順序 (A = 0)，忽略 "+" 後綴，無法辨識的評級視為 F
+pub fn grade_rank(grade: &str) -> usize {
+    let base = grade.trim_end_matches('+');
+    GRADE_ORDER.iter().position(|o| *o == base).unwrap_or(GRADE_ORDER.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    /// TLS 1.2 + 1.3、強密碼套件、一年後到期的 2048 位元 SHA-256 憑證：A，沒有任何因素
+    fn analysis() -> SslAnalysis {
+        let now = Utc::now();
+        SslAnalysis {
+            id: "ssl-1".to_string(),
+            task_id: "scan-1".to_string(),
+            certificate_issuer: None,
+            certificate_subject: None,
+            valid_from: Some(now - Duration::days(30)),
+            valid_to: Some(now + Duration::days(365)),
+            signature_algorithm: Some("sha256WithRSAEncryption".to_string()),
+            tls_versions: Some(vec!["TLS1_2".to_string(), "TLS1_3".to_string()]),
+            cipher_suites: Some(vec!["TLS_AES_128_GCM_SHA256".to_string(), "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384".to_string()]),
+            vulnerabilities: None,
+            grade: None,
+            grade_factors: None,
+            chain_valid: Some(true),
+            key_size: Some(2048),
+            hsts_enabled: Some(false),
+            tls_vulnerabilities: None,
+            created_at: now,
+            analyzed_url: None,
+            analyzed_port: None,
+        }
+    }
+
+    #[test]
+    fn active_probes_follow_the_scan_option() {
+        assert!(!SslScanner::new(&ScanOptions::default()).unwrap().active_probes);
+
+        let options = ScanOptions { tls_active_probes: true, ..ScanOptions::default() };
+        assert!(SslScanner::new(&options).unwrap().active_probes);
+    }
+
+    fn versions(versions: &[&str]) -> Option<Vec<String>> {
+        Some(versions.iter().map(|v| v.to_string()).collect())
+    }
+
+    fn with_suite(mut analysis: SslAnalysis, suite: &str) -> SslAnalysis {
+        analysis.cipher_suites.get_or_insert_with(Vec::new).push(suite.to_string());
+        analysis
+    }
+
+    fn grade(analysis: &SslAnalysis) -> String {
+        SslScanner::calculate_grade(analysis).0
+    }
+
+    fn factors(analysis: &SslAnalysis) -> Vec<String> {
+        SslScanner::calculate_grade(analysis).1
+    }
+
+    #[test]
+    fn clean_configuration_is_a() {
+        assert_eq!(SslScanner::calculate_grade(&analysis()), ("A".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn legacy_protocols_deduct_twenty() {
+        for legacy in ["SSL3", "TLS1_0", "TLS1_1"] {
+            let analysis = SslAnalysis { tls_versions: versions(&[legacy, "TLS1_2", "TLS1_3"]), ..analysis() };
+            // 80 分仍為 A
+            assert_eq!(grade(&analysis), "A", "{}", legacy);
+            assert_eq!(factors(&analysis), vec![SslScanner::rubric_rule("legacy_tls")]);
+        }
+    }
+
+    #[test]
+    fn missing_tls13_deducts_ten() {
+        let analysis = SslAnalysis { tls_versions: versions(&["TLS1_2"]), ..analysis() };
+        assert_eq!(grade(&analysis), "A");
+        assert_eq!(factors(&analysis), vec![SslScanner::rubric_rule("missing_tls13")]);
+
+        // 100 - 20 - 10 = 70，B 的下限
+        let analysis = SslAnalysis { tls_versions: versions(&["TLS1_0", "TLS1_2"]), ..analysis };
+        assert_eq!(grade(&analysis), "B");
+    }
+
+    #[test]
+    fn weak_ciphers_deduct_thirty() {
+        for suite in ["TLS_RSA_WITH_RC4_128_SHA", "TLS_RSA_WITH_3DES_EDE_CBC_SHA"] {
+            let analysis = with_suite(analysis(), suite);
+            assert_eq!(grade(&analysis), "B", "{}", suite);
+            assert_eq!(factors(&analysis), vec![SslScanner::rubric_rule("weak_cipher")]);
+        }
+    }
+
+    #[test]
+    fn score_boundaries() {
+        let weak = with_suite(analysis(), "TLS_RSA_WITH_RC4_128_SHA");
+        // 100 - 30 - 10 = 60
+        assert_eq!(grade(&SslAnalysis { tls_versions: versions(&["TLS1_2"]), ..weak.clone() }), "C");
+        // 100 - 30 - 20 = 50
+        assert_eq!(grade(&SslAnalysis { tls_versions: versions(&["TLS1_1", "TLS1_2", "TLS1_3"]), ..weak.clone() }), "D");
+        // 100 - 30 - 20 - 10 = 40
+        assert_eq!(grade(&SslAnalysis { tls_versions: versions(&["TLS1_0", "TLS1_2"]), ..weak }), "F");
+    }
+
+    #[test]
+    fn export_and_null_ciphers_cap_at_f() {
+        for suite in ["TLS_RSA_EXPORT_WITH_RC4_40_MD5", "TLS_RSA_WITH_NULL_SHA"] {
+            let analysis = with_suite(analysis(), suite);
+            assert_eq!(grade(&analysis), "F", "{}", suite);
+            assert!(factors(&analysis).contains(&SslScanner::rubric_rule("insecure_cipher")));
+        }
+    }
+
+    #[test]
+    fn invalid_certificates_cap_at_f() {
+        let now = Utc::now();
+        let cases = [
+            SslAnalysis { valid_to: Some(now - Duration::days(1)), ..analysis() },
+            SslAnalysis { valid_from: Some(now + Duration::days(1)), ..analysis() },
+            SslAnalysis { chain_valid: Some(false), ..analysis() },
+        ];
+        for analysis in cases {
+            assert_eq!(grade(&analysis), "F");
+            assert_eq!(factors(&analysis), vec![SslScanner::rubric_rule("invalid_certificate")]);
+        }
+    }
+
+    #[test]
+    fn expiring_certificate_caps_at_b() {
+        let now = Utc::now();
+        let expiring = SslAnalysis { valid_to: Some(now + Duration::days(certificate::EXPIRY_WARNING_DAYS - 1)), ..analysis() };
+        assert_eq!(grade(&expiring), "B");
+        assert_eq!(factors(&expiring), vec![SslScanner::rubric_rule("expiring_certificate")]);
+
+        let outside_window = SslAnalysis { valid_to: Some(now + Duration::days(certificate::EXPIRY_WARNING_DAYS + 1)), ..analysis() };
+        assert_eq!(grade(&outside_window), "A");
+    }
+
+    #[test]
+    fn weak_key_caps_at_c() {
+        let weak = SslAnalysis { key_size: Some(2047), ..analysis() };
+        assert_eq!(grade(&weak), "C");
+        assert_eq!(factors(&weak), vec![SslScanner::rubric_rule("weak_key")]);
+        assert_eq!(grade(&SslAnalysis { key_size: Some(2048), ..analysis() }), "A");
+    }
+
+    #[test]
+    fn sha1_signature_caps_at_b() {
+        for algorithm in ["sha1WithRSAEncryption", "SHA-1", "ecdsa-with-SHA1"] {
+            let analysis = SslAnalysis { signature_algorithm: Some(algorithm.to_string()), ..analysis() };
+            assert_eq!(grade(&analysis), "B", "{}", algorithm);
+            assert_eq!(factors(&analysis), vec![SslScanner::rubric_rule("sha1_signature")]);
+        }
+    }
+
+    #[test]
+    fn caps_never_raise_a_grade() {
+        // 50 分的 D 遇到上限 B 仍為 D
+        let analysis = SslAnalysis {
+            tls_versions: versions(&["TLS1_0", "TLS1_2", "TLS1_3"]),
+            signature_algorithm: Some("sha1WithRSAEncryption".to_string()),
+            ..with_suite(analysis(), "TLS_RSA_WITH_3DES_EDE_CBC_SHA")
+        };
+        assert_eq!(grade(&analysis), "D");
+    }
+
+    #[test]
+    fn hsts_adds_plus_except_to_f() {
+        assert_eq!(grade(&SslAnalysis { hsts_enabled: Some(true), ..analysis() }), "A+");
+        assert_eq!(grade(&SslAnalysis { hsts_enabled: Some(true), key_size: Some(1024), ..analysis() }), "C+");
+
+        let failed = SslAnalysis { hsts_enabled: Some(true), chain_valid: Some(false), ..analysis() };
+        assert_eq!(grade(&failed), "F");
+        assert!(!factors(&failed).contains(&SslScanner::rubric_rule("hsts")));
+    }
+
+    #[test]
+    fn every_rubric_rule_is_reachable() {
+        let now = Utc::now();
+        let worst = SslAnalysis {
+            tls_versions: versions(&["TLS1_0"]),
+            valid_to: Some(now + Duration::days(1)),
+            key_size: Some(1024),
+            signature_algorithm: Some("sha1WithRSAEncryption".to_string()),
+            hsts_enabled: Some(true),
+            ..with_suite(with_suite(analysis(), "TLS_RSA_WITH_RC4_128_SHA"), "TLS_RSA_WITH_NULL_SHA")
+        };
+        let mut reported = factors(&worst);
+        reported.extend(factors(&SslAnalysis { chain_valid: Some(false), ..analysis() }));
+        reported.extend(factors(&SslAnalysis { hsts_enabled: Some(true), ..analysis() }));
+        for (key, _) in GRADE_RUBRIC {
+            assert!(reported.contains(&SslScanner::rubric_rule(key)), "{}", key);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+/**
+ * Target Normalization
+ *
+ * 將使用者輸入的目標 (完整 URL、主機名稱、IP、host:port) 轉換為實際掃描的 URL
+ */
+
+use crate::models::ScanOptions;
+use crate::scanners::client;
+use std::time::Duration;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 正規化結果：實際掃描的 URL，以及使用者原始輸入 (與實際 URL 不同時)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedTarget {
+    pub url: String,
+    pub requested: Option<String>,
+}
+
+/// 正規化掃描目標
+///
+/// 已帶 http/https 的輸入直接使用；沒有通訊協定的主機名稱或 IP 依序嘗試 https 與 http，
+/// 以第一個有回應的作為實際目標。其他通訊協定 (例如 ftp://) 會回傳指出該協定的錯誤。
+pub async fn normalize_target(input: &str, options: &ScanOptions) -> Result<NormalizedTarget, String> {
+    let candidates = match plan_target(input)? {
+        TargetPlan::Direct(target) => return Ok(target),
+        TargetPlan::Probe(candidates) => candidates,
+    };
+
+    // 沒有通訊協定：先試 https 再試 http
+    let probe_client = client::client_builder(options)
+        .and_then(|builder| {
+            builder
+                .timeout(PROBE_TIMEOUT)
+                .build()
+                .map_err(|e| e.into())
+        })
+        .map_err(|e| format!("無法建立 HTTP 用戶端: {}", e))?;
+
+    for url in candidates {
+        match probe_client.get(&url).send().await {
+            Ok(_) => {
+                tracing::info!("🔎 目標 {} 正規化為 {}", input.trim(), url);
+                return Ok(NormalizedTarget { url, requested: Some(input.to_string()) });
+            }
+            Err(e) => tracing::warn!("⚠️  {} 無回應: {}", url, e),
+        }
+    }
+
+    Err(format!("無法連線到 {} (已嘗試 https:// 與 http://)", input.trim()))
+}
+
+/// 不需連線即可決定的部分：直接掃描的目標，或依序探測的候選 URL
+#[derive(Debug, PartialEq)]
+enum TargetPlan {
+    Direct(NormalizedTarget),
+    Probe(Vec<String>),
+}
+
+fn plan_target(input: &str) -> Result<TargetPlan, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err("請輸入掃描目標".to_string());
+    }
+
+    if let Some((scheme, _)) = trimmed.split_once("://") {
+        let scheme = scheme.to_lowercase();
+        if scheme != "http" && scheme != "https" {
+            return Err(format!("不支援的通訊協定「{}」，目前僅支援 http:// 與 https:// 目標", scheme));
+        }
+
+        // 只有實際內容改變 (例如前後空白、unicode 網域) 時才記錄原始輸入
+        let url = parse_url(trimmed)?;
+        if url.trim_end_matches('/') == trimmed.trim_end_matches('/') && trimmed == input {
+            return Ok(TargetPlan::Direct(NormalizedTarget { url: trimmed.to_string(), requested: None }));
+        }
+        return Ok(TargetPlan::Direct(NormalizedTarget { url, requested: Some(input.to_string()) }));
+    }
+
+    Ok(TargetPlan::Probe(vec![
+        parse_url(&format!("https://{}", trimmed))?,
+        parse_url(&format!("http://{}", trimmed))?,
+    ]))
+}
+
+// reqwest::Url 會把 unicode 網域轉為 punycode，並補上結尾的 "/"
+fn parse_url(value: &str) -> Result<String, String> {
+    let url = reqwest::Url::parse(value).map_err(|e| format!("無效的目標「{}」: {}", value, e))?;
+    if url.host_str().map_or(true, |host| host.is_empty()) {
+        return Err(format!("無效的目標「{}」: 缺少主機名稱", value));
+    }
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn direct(url: &str, requested: Option<&str>) -> TargetPlan {
+        TargetPlan::Direct(NormalizedTarget { url: url.to_string(), requested: requested.map(str::to_string) })
+    }
+
+    fn probe(https: &str, http: &str) -> TargetPlan {
+        TargetPlan::Probe(vec![https.to_string(), http.to_string()])
+    }
+
+    #[test]
+    fn plans_each_kind_of_input() {
+        let cases = [
+            // 完整 URL 原樣使用
+            ("https://example.com", direct("https://example.com", None)),
+            ("http://example.com/app/", direct("http://example.com/app/", None)),
+            // 裸主機名稱、IP 與 host:port 先試 https 再試 http
+            ("example.com", probe("https://example.com/", "http://example.com/")),
+            ("example.com:8080", probe("https://example.com:8080/", "http://example.com:8080/")),
+            ("192.168.1.10", probe("https://192.168.1.10/", "http://192.168.1.10/")),
+            ("192.168.1.10:8443", probe("https://192.168.1.10:8443/", "http://192.168.1.10:8443/")),
+            ("example.com/login", probe("https://example.com/login", "http://example.com/login")),
+            // 前後空白會被去除，並記錄原始輸入
+            ("  example.com \n", probe("https://example.com/", "http://example.com/")),
+            ("https://example.com  ", direct("https://example.com/", Some("https://example.com  "))),
+            // unicode 網域轉為 punycode
+            ("例子.測試", probe("https://xn--fsqu00a.xn--g6w251d/", "http://xn--fsqu00a.xn--g6w251d/")),
+            ("https://bücher.example", direct("https://xn--bcher-kva.example/", Some("https://bücher.example"))),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(plan_target(input).as_ref(), Ok(&expected), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_and_empty_input() {
+        let ftp = plan_target("ftp://example.com").unwrap_err();
+        assert!(ftp.contains("「ftp」"), "{}", ftp);
+        let ws = plan_target("  WS://example.com").unwrap_err();
+        assert!(ws.contains("「ws」"), "{}", ws);
+
+        assert_eq!(plan_target("   ").unwrap_err(), "請輸入掃描目標");
+        assert!(plan_target("https://").is_err());
+        assert!(plan_target("example.com:99999").is_err());
+    }
+}
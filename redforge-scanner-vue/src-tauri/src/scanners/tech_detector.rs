@@ -1,21 +1,24 @@
 use crate::models::*;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::analysis;
+use crate::scanners::language_inference;
 use crate::scanners::ScannerResult;
-use reqwest::Client;
+use regex::Regex;
+use std::sync::OnceLock;
 use uuid::Uuid;
 use chrono::Utc;
 
 pub struct TechDetector {
-    client: Client,
+    client: ScanClient,
 }
 
 impl TechDetector {
-    pub fn new() -> Self {
-        Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap(),
-        }
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 10))
+                .build()?.into(),
+        })
     }
 
     pub async fn detect(&self, task_id: &str, url: &str) -> ScannerResult<Vec<DetectedTechnology>> {
@@ -23,24 +26,40 @@ impl TechDetector {
         let headers = response.headers().clone();
         let body = response.text().await?;
 
+        // 指紋比對在 blocking 執行緒池執行，頁面內容整個移交過去
+        let task_id = task_id.to_string();
+        let url = url.to_string();
+        Ok(analysis::offload(move || Self::analyze(&task_id, &url, &body, &headers)).await)
+    }
+
+    /// 以頁面內容與回應標頭比對技術指紋 (CPU 密集)
+    fn analyze(task_id: &str, url: &str, body: &str, headers: &reqwest::header::HeaderMap) -> Vec<DetectedTechnology> {
         let mut technologies = Vec::new();
 
         // JavaScript 框架檢測
-        technologies.extend(self.detect_js_frameworks(task_id, &body));
+        technologies.extend(Self::detect_js_frameworks(task_id, body));
+
+        // CMS 檢測
+        technologies.extend(Self::detect_cms(task_id, body));
 
         // CSS 框架檢測
-        technologies.extend(self.detect_css_frameworks(task_id, &body));
+        technologies.extend(Self::detect_css_frameworks(task_id, body));
 
         // 分析工具檢測
-        technologies.extend(self.detect_analytics(task_id, &body));
+        technologies.extend(Self::detect_analytics(task_id, body));
 
         // CDN 檢測
-        technologies.extend(self.detect_cdn(task_id, &body, &headers));
+        technologies.extend(Self::detect_cdn(task_id, body, headers));
+
+        // 後端語言推測 (Server 標頭被隱藏時仍可判斷)
+        let mut urls = language_inference::page_urls(body, url);
+        urls.push(url.to_string());
+        technologies.extend(language_inference::infer_languages(task_id, headers, &urls));
 
-        Ok(technologies)
+        technologies
     }
 
-    fn detect_js_frameworks(&self, task_id: &str, html: &str) -> Vec<DetectedTechnology> {
+    fn detect_js_frameworks(task_id: &str, html: &str) -> Vec<DetectedTechnology> {
         let mut techs = Vec::new();
         let html_lower = html.to_lowercase();
 
@@ -54,7 +73,8 @@ impl TechDetector {
         ];
 
         for (name, patterns, category, confidence) in frameworks {
-            if patterns.iter().any(|p| html_lower.contains(p)) {
+            let evidence = html_evidence(html, &html_lower, &patterns);
+            if !evidence.is_empty() {
                 techs.push(DetectedTechnology {
                     id: Uuid::new_v4().to_string(),
                     task_id: task_id.to_string(),
@@ -63,6 +83,7 @@ impl TechDetector {
                     category,
                     confidence,
                     created_at: Utc::now(),
+                    evidence,
                 });
             }
         }
@@ -70,11 +91,41 @@ impl TechDetector {
         techs
     }
 
-    fn detect_css_frameworks(&self, task_id: &str, html: &str) -> Vec<DetectedTechnology> {
+    fn detect_cms(task_id: &str, html: &str) -> Vec<DetectedTechnology> {
         let mut techs = Vec::new();
         let html_lower = html.to_lowercase();
 
-        if html_lower.contains("bootstrap") || html_lower.contains("btn btn-") {
+        let cms = vec![
+            ("WordPress", vec!["wp-content/", "wp-includes/", "content=\"wordpress"], 90),
+            ("Drupal", vec!["drupal-settings-json", "/sites/default/files/", "content=\"drupal"], 85),
+            ("Joomla", vec!["/media/jui/", "content=\"joomla"], 85),
+        ];
+
+        for (name, patterns, confidence) in cms {
+            let evidence = html_evidence(html, &html_lower, &patterns);
+            if !evidence.is_empty() {
+                techs.push(DetectedTechnology {
+                    id: Uuid::new_v4().to_string(),
+                    task_id: task_id.to_string(),
+                    technology_name: name.to_string(),
+                    technology_version: None,
+                    category: TechnologyCategory::Cms,
+                    confidence,
+                    created_at: Utc::now(),
+                    evidence,
+                });
+            }
+        }
+
+        techs
+    }
+
+    fn detect_css_frameworks(task_id: &str, html: &str) -> Vec<DetectedTechnology> {
+        let mut techs = Vec::new();
+        let html_lower = html.to_lowercase();
+
+        let bootstrap = html_evidence(html, &html_lower, &["bootstrap", "btn btn-"]);
+        if !bootstrap.is_empty() {
             techs.push(DetectedTechnology {
                 id: Uuid::new_v4().to_string(),
                 task_id: task_id.to_string(),
@@ -83,10 +134,11 @@ impl TechDetector {
                 category: TechnologyCategory::Framework,
                 confidence: 80,
                 created_at: Utc::now(),
+                evidence: bootstrap,
             });
         }
 
-        if self.has_tailwind_classes(html) {
+        if Self::has_tailwind_classes(html) {
             techs.push(DetectedTechnology {
                 id: Uuid::new_v4().to_string(),
                 task_id: task_id.to_string(),
@@ -95,13 +147,17 @@ impl TechDetector {
                 category: TechnologyCategory::Framework,
                 confidence: 75,
                 created_at: Utc::now(),
+                evidence: vec![TechnologyEvidence {
+                    kind: EvidenceKind::HtmlPattern,
+                    matched: "tailwind utility classes".to_string(),
+                }],
             });
         }
 
         techs
     }
 
-    fn detect_analytics(&self, task_id: &str, html: &str) -> Vec<DetectedTechnology> {
+    fn detect_analytics(task_id: &str, html: &str) -> Vec<DetectedTechnology> {
         let mut techs = Vec::new();
         let html_lower = html.to_lowercase();
 
@@ -114,7 +170,8 @@ impl TechDetector {
         ];
 
         for (name, patterns, confidence) in analytics {
-            if patterns.iter().any(|p| html_lower.contains(p)) {
+            let evidence = html_evidence(html, &html_lower, &patterns);
+            if !evidence.is_empty() {
                 techs.push(DetectedTechnology {
                     id: Uuid::new_v4().to_string(),
                     task_id: task_id.to_string(),
@@ -123,6 +180,7 @@ impl TechDetector {
                     category: TechnologyCategory::Analytics,
                     confidence,
                     created_at: Utc::now(),
+                    evidence,
                 });
             }
         }
@@ -130,7 +188,7 @@ impl TechDetector {
         techs
     }
 
-    fn detect_cdn(&self, task_id: &str, html: &str, headers: &reqwest::header::HeaderMap) -> Vec<DetectedTechnology> {
+    fn detect_cdn(task_id: &str, html: &str, headers: &reqwest::header::HeaderMap) -> Vec<DetectedTechnology> {
         let mut techs = Vec::new();
         let html_lower = html.to_lowercase();
 
@@ -143,14 +201,16 @@ impl TechDetector {
         ];
 
         for (name, patterns, confidence) in cdns {
-            let in_html = patterns.iter().any(|p| html_lower.contains(p));
-            let in_headers = headers.iter().any(|(k, v)| {
+            let mut evidence = html_evidence(html, &html_lower, &patterns);
+            for (k, v) in headers.iter() {
                 let key = k.as_str().to_lowercase();
                 let value = v.to_str().unwrap_or("").to_lowercase();
-                patterns.iter().any(|p| key.contains(p) || value.contains(p))
-            });
+                if patterns.iter().any(|p| key.contains(p) || value.contains(p)) {
+                    evidence.push(header_evidence(&key, &value));
+                }
+            }
 
-            if in_html || in_headers {
+            if !evidence.is_empty() {
                 techs.push(DetectedTechnology {
                     id: Uuid::new_v4().to_string(),
                     task_id: task_id.to_string(),
@@ -159,6 +219,7 @@ impl TechDetector {
                     category: TechnologyCategory::Cdn,
                     confidence,
                     created_at: Utc::now(),
+                    evidence,
                 });
             }
         }
@@ -166,7 +227,7 @@ impl TechDetector {
         techs
     }
 
-    fn has_tailwind_classes(&self, html: &str) -> bool {
+    fn has_tailwind_classes(html: &str) -> bool {
         let tailwind_patterns = [
             "flex-", "grid-", "bg-", "text-", "p-", "m-", "w-", "h-",
             "rounded-", "shadow-", "hover:", "focus:", "md:", "lg:"
@@ -175,3 +236,193 @@ impl TechDetector {
         count >= 3 // 如果有 3 個以上的 Tailwind 類別，就認為使用了 Tailwind
     }
 }
+
+/// 從 HTML 找出符合的特徵；若特徵出現在 <script src> 中則記錄腳本網址
+pub(crate) fn html_evidence(html: &str, html_lower: &str, patterns: &[&str]) -> Vec<TechnologyEvidence> {
+    static SCRIPT_SRC: OnceLock<Regex> = OnceLock::new();
+    let script_src = SCRIPT_SRC.get_or_init(|| Regex::new(r#"(?i)<script[^>]+src\s*=\s*["']([^"']+)["']"#).unwrap());
+    let scripts: Vec<&str> = script_src
+        .captures_iter(html)
+        .filter_map(|caps| caps.get(1).map(|m| m.as_str()))
+        .collect();
+
+    let mut evidence = Vec::new();
+    for pattern in patterns.iter().filter(|p| html_lower.contains(*p)) {
+        let item = match scripts.iter().find(|src| src.to_lowercase().contains(pattern)) {
+            Some(src) => TechnologyEvidence { kind: EvidenceKind::ScriptUrl, matched: src.to_string() },
+            None => TechnologyEvidence { kind: EvidenceKind::HtmlPattern, matched: pattern.to_string() },
+        };
+        if !evidence.contains(&item) {
+            evidence.push(item);
+        }
+    }
+    evidence
+}
+
+pub(crate) fn header_evidence(name: &str, value: &str) -> TechnologyEvidence {
+    TechnologyEvidence {
+        kind: EvidenceKind::Header,
+        matched: format!("{}: {}", name.to_lowercase(), value),
+    }
+}
+
+// 同一技術的不同寫法，左邊是正規化後的 key
+const NAME_ALIASES: &[(&str, &str)] = &[
+    ("vue", "Vue.js"),
+    ("react", "React"),
+    ("next", "Next.js"),
+    ("nuxt", "Nuxt.js"),
+    ("angular", "Angular"),
+    ("svelte", "Svelte"),
+    ("nginx", "Nginx"),
+    ("apache", "Apache"),
+    ("apachehttpd", "Apache"),
+    ("php", "PHP"),
+    ("tailwind", "Tailwind CSS"),
+    ("tailwindcss", "Tailwind CSS"),
+    ("bootstrap", "Bootstrap"),
+    ("googleanalytics", "Google Analytics"),
+    ("cloudflare", "Cloudflare"),
+];
+
+fn technology_key(name: &str) -> String {
+    let key: String = name
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    // "Vue.js" / "VueJS" / "Vue" 視為同一個
+    match key.strip_suffix("js") {
+        Some(stripped) if stripped.len() > 1 => stripped.to_string(),
+        _ => key,
+    }
+}
+
+/// 技術名稱的標準寫法，不認得的名稱保持原樣
+pub fn canonical_technology_name(name: &str) -> String {
+    let key = technology_key(name);
+    NAME_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, canonical)| canonical.to_string())
+        .unwrap_or_else(|| name.trim().to_string())
+}
+
+/// 合併同一次掃描中不同檢測器的結果
+///
+/// 依正規化後的名稱分組：保留最高信心度 (及其分類)、第一個取得的版本，以及所有判定依據的聯集。
+/// 結果依第一次出現的順序排列。
+pub fn merge_technologies(technologies: Vec<DetectedTechnology>) -> Vec<DetectedTechnology> {
+    let mut merged: Vec<(String, DetectedTechnology)> = Vec::new();
+
+    for mut tech in technologies {
+        let key = technology_key(&tech.technology_name);
+        tech.technology_name = canonical_technology_name(&tech.technology_name);
+
+        let Some((_, existing)) = merged.iter_mut().find(|(k, _)| *k == key) else {
+            merged.push((key, tech));
+            continue;
+        };
+
+        if tech.confidence > existing.confidence {
+            existing.confidence = tech.confidence;
+            existing.category = tech.category;
+        }
+        if existing.technology_version.is_none() {
+            existing.technology_version = tech.technology_version;
+        }
+        for item in tech.evidence {
+            if !existing.evidence.contains(&item) {
+                existing.evidence.push(item);
+            }
+        }
+    }
+
+    merged.into_iter().map(|(_, tech)| tech).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanners::http_scanner::HttpScanner;
+
+    fn tech(name: &str, version: Option<&str>, category: TechnologyCategory, confidence: u8, matched: &str) -> DetectedTechnology {
+        DetectedTechnology {
+            id: Uuid::new_v4().to_string(),
+            task_id: "scan-1".to_string(),
+            technology_name: name.to_string(),
+            technology_version: version.map(str::to_string),
+            category,
+            confidence,
+            created_at: Utc::now(),
+            evidence: vec![TechnologyEvidence { kind: EvidenceKind::HtmlPattern, matched: matched.to_string() }],
+        }
+    }
+
+    #[test]
+    fn both_detectors_finding_react_yield_one_merged_entry() {
+        let html = r#"<div id="_reactRoot"></div><script src="/static/react-dom.production.min.js"></script>"#;
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("server", "nginx/1.24.0".parse().unwrap());
+
+        let http = HttpScanner::new(&ScanOptions::default()).unwrap().detect_from_html("scan-1", html);
+        let detector = TechDetector::analyze("scan-1", "https://example.com/", html, &headers);
+        assert!(http.iter().any(|t| t.technology_name == "React"));
+        assert!(detector.iter().any(|t| t.technology_name == "React"));
+
+        let merged = merge_technologies(http.into_iter().chain(detector).collect());
+        let react: Vec<&DetectedTechnology> = merged.iter().filter(|t| t.technology_name == "React").collect();
+        assert_eq!(react.len(), 1);
+        assert_eq!(react[0].confidence, 85);
+        assert_eq!(
+            react[0].evidence,
+            vec![
+                TechnologyEvidence { kind: EvidenceKind::ScriptUrl, matched: "/static/react-dom.production.min.js".to_string() },
+                TechnologyEvidence { kind: EvidenceKind::HtmlPattern, matched: "_reactroot".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_name_variants_keeping_max_confidence_and_all_evidence() {
+        let merged = merge_technologies(vec![
+            tech("Vue", None, TechnologyCategory::Framework, 70, "data-v-"),
+            tech("nginx", Some("1.24"), TechnologyCategory::Server, 95, "server: nginx/1.24"),
+            tech("VueJS", Some("3.4"), TechnologyCategory::Framework, 90, "__vue__"),
+            tech("Vue.js", Some("2.7"), TechnologyCategory::Framework, 80, "data-v-"),
+            tech("Nginx", None, TechnologyCategory::Cdn, 60, "server: nginx/1.24"),
+        ]);
+
+        let names: Vec<&str> = merged.iter().map(|t| t.technology_name.as_str()).collect();
+        assert_eq!(names, vec!["Vue.js", "Nginx"]);
+
+        let vue = &merged[0];
+        assert_eq!(vue.confidence, 90);
+        assert_eq!(vue.technology_version.as_deref(), Some("3.4"));
+        let matched: Vec<&str> = vue.evidence.iter().map(|e| e.matched.as_str()).collect();
+        assert_eq!(matched, vec!["data-v-", "__vue__"]);
+
+        // 分類跟著信心度最高的一筆
+        let nginx = &merged[1];
+        assert!(matches!(nginx.category, TechnologyCategory::Server));
+        assert_eq!(nginx.evidence.len(), 1);
+    }
+
+    #[test]
+    fn canonical_names() {
+        let cases = [
+            ("Vue", "Vue.js"),
+            ("vue.js", "Vue.js"),
+            ("VueJS", "Vue.js"),
+            ("tailwindcss", "Tailwind CSS"),
+            ("Apache HTTPD", "Apache"),
+            ("  Google Analytics ", "Google Analytics"),
+            // 不認得的名稱保持原樣，只去除前後空白
+            ("Ember.js ", "Ember.js"),
+            ("JS", "JS"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(canonical_technology_name(input), expected, "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+/**
+ * Time-boxed Scans
+ *
+ * 設定 max_duration_secs 時，檢查依優先順序執行：便宜且訊號明確的檢查 (標頭、TLS、敏感檔案) 先做，
+ * 大量送出 payload 的檢查最後做。時間用完後不再啟動新的檢查，進行中的檢查照常完成，
+ * 未執行的檢查記錄在涵蓋範圍與報告的時間限制摘要中。
+ */
+
+use crate::scanners::control;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 檢查的執行優先順序，數字越小越先執行；id 與階段的 message_key 相同
+const CHECK_PRIORITIES: &[(&str, u8)] = &[
+    ("headers", 0),
+    ("ssl", 10),
+    ("tech_detection", 20),
+    ("ports", 25),
+    // A05 包含敏感檔案與設定錯誤，請求少且結果明確
+    ("owasp.a05", 30),
+    ("owasp.a02", 35),
+    ("owasp.a06", 40),
+    ("owasp.a07", 45),
+    ("owasp.a08", 50),
+    ("owasp.a09", 55),
+    ("owasp.a04", 60),
+    ("owasp.a01", 65),
+    ("legacy", 70),
+    ("proxy_disagreement", 75),
+    ("upload", 85),
+    // 參數掃描與注入 payload 最昂貴
+    ("owasp.a10", 90),
+    ("owasp.a03", 95),
+];
+
+/// 未登錄的檢查排在已登錄的便宜檢查之後、payload 掃描之前
+const DEFAULT_PRIORITY: u8 = 80;
+
+pub fn priority(check_id: &str) -> u8 {
+    CHECK_PRIORITIES
+        .iter()
+        .find(|(id, _)| *id == check_id)
+        .map(|(_, priority)| *priority)
+        .unwrap_or(DEFAULT_PRIORITY)
+}
+
+/// 依優先順序排列檢查 (穩定排序，同優先順序保留原順序)
+pub fn ordered<T, F: Fn(&T) -> String>(mut checks: Vec<T>, check_id: F) -> Vec<T> {
+    checks.sort_by_key(|check| priority(&check_id(check)));
+    checks
+}
+
+/// 掃描的時間限制，由 ScanControl 持有
+pub struct TimeBox {
+    limit: Duration,
+    started: Instant,
+    skipped: Mutex<Vec<String>>,
+}
+
+impl TimeBox {
+    pub fn new(max_duration_secs: u64) -> Self {
+        Self {
+            limit: Duration::from_secs(max_duration_secs),
+            started: Instant::now(),
+            skipped: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn exhausted(&self) -> bool {
+        self.started.elapsed() >= self.limit
+    }
+
+    /// 時間仍有剩餘時回傳 true；已用完時記錄為略過的檢查
+    pub fn admit(&self, check_id: &str) -> bool {
+        if !self.exhausted() {
+            return true;
+        }
+        let mut skipped = self.skipped.lock().unwrap_or_else(|e| e.into_inner());
+        if !skipped.iter().any(|id| id == check_id) {
+            control::log(format!("⏱️  時間限制已用盡，略過檢查 {}", check_id));
+            skipped.push(check_id.to_string());
+        }
+        false
+    }
+
+    pub fn report(&self) -> TimeBoxReport {
+        let skipped = self.skipped.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let max_duration_secs = self.limit.as_secs();
+        let banner = if skipped.is_empty() {
+            format!("限時掃描 ({} 秒)：所有檢查皆在時間內完成", max_duration_secs)
+        } else {
+            format!(
+                "限時掃描 ({} 秒)：時間用盡，{} 項檢查未執行: {}",
+                max_duration_secs,
+                skipped.len(),
+                skipped.join(", ")
+            )
+        };
+
+        TimeBoxReport {
+            max_duration_secs,
+            elapsed_secs: self.started.elapsed().as_secs(),
+            exhausted: !skipped.is_empty(),
+            skipped,
+            banner,
+        }
+    }
+}
+
+/// 限時掃描的摘要，附在報告上
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimeBoxReport {
+    pub max_duration_secs: u64,
+    pub elapsed_secs: u64,
+    /// 有檢查因時間用盡而未執行
+    pub exhausted: bool,
+    /// 未執行的檢查 id，依原本的執行順序
+    pub skipped: Vec<String>,
+    /// 報告頂端顯示的說明
+    pub banner: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CoverageStatus;
+    use crate::scanners::control::ScanControl;
+
+    fn time_box(limit: Duration) -> TimeBox {
+        TimeBox { limit, started: Instant::now(), skipped: Mutex::new(Vec::new()) }
+    }
+
+    #[test]
+    fn cheap_checks_run_before_payload_sweeps() {
+        let checks = vec!["owasp.a03", "legacy", "custom.one", "headers", "owasp.a05", "custom.two", "ssl"];
+        let ordered = ordered(checks, |id| id.to_string());
+        assert_eq!(ordered, vec!["headers", "ssl", "owasp.a05", "legacy", "custom.one", "custom.two", "owasp.a03"]);
+    }
+
+    #[tokio::test]
+    async fn exhausted_time_box_finishes_in_flight_checks_and_skips_the_rest() {
+        // 每個模擬檢查耗時 200ms，時間限制 300ms：第二個檢查在時間內啟動並照常完成，之後的檢查都不啟動
+        let time_box = time_box(Duration::from_millis(300));
+        let checks = ordered(vec!["owasp.a03", "ssl", "owasp.a05", "headers"], |id| id.to_string());
+        let mut finished = Vec::new();
+        for check in checks {
+            if !time_box.admit(check) {
+                continue;
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            finished.push(check);
+        }
+
+        assert_eq!(finished, vec!["headers", "ssl"]);
+        let report = time_box.report();
+        assert!(report.exhausted);
+        assert_eq!(report.skipped, vec!["owasp.a05", "owasp.a03"]);
+        assert!(report.banner.contains("2 項檢查未執行"), "{}", report.banner);
+        assert!(report.banner.contains("owasp.a05, owasp.a03"), "{}", report.banner);
+    }
+
+    #[test]
+    fn unexhausted_time_box_skips_nothing() {
+        let time_box = time_box(Duration::from_secs(600));
+        assert!(time_box.admit("headers"));
+        assert!(time_box.admit("owasp.a03"));
+
+        let report = time_box.report();
+        assert!(!report.exhausted);
+        assert!(report.skipped.is_empty());
+        assert!(report.banner.contains("所有檢查皆在時間內完成"), "{}", report.banner);
+    }
+
+    #[test]
+    fn skipped_checks_are_recorded_once_in_the_coverage_matrix() {
+        let control = ScanControl::default();
+        assert!(control.admit("owasp.a03"), "沒有時間限制時一律允許");
+
+        control.start_time_box(0);
+        assert!(!control.admit("owasp.a03"));
+        assert!(!control.admit("owasp.a03"));
+        assert!(!control.admit("headers"));
+
+        let coverage = control.coverage();
+        let checks: Vec<&str> = coverage.iter().map(|entry| entry.check.as_str()).collect();
+        assert_eq!(checks, vec!["headers", "owasp.a03"]);
+        for entry in &coverage {
+            assert_eq!(entry.status, CoverageStatus::Skipped);
+            assert!(entry.message.as_deref().unwrap_or_default().contains("限時掃描 (0 秒)"), "{:?}", entry.message);
+        }
+        assert_eq!(control.time_box_report().unwrap().skipped, vec!["owasp.a03", "headers"]);
+    }
+}
@@ -0,0 +1,305 @@
+/**
+ * Raw TLS Probes
+ *
+ * 以手工組裝的 TLS 封包直接與目標交握，用於 rustls 無法涵蓋的檢測
+ * (例如舊版協定或 Heartbleed)。只解析封包標頭與長度，不保留伺服器回傳的內容。
+ */
+
+use crate::scanners::proxy::Connector;
+use crate::scanners::raw_http::{self, TlsIdentity};
+use crate::scanners::{control, ScannerError, ScannerResult};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+const CONTENT_HANDSHAKE: u8 = 0x16;
+const CONTENT_ALERT: u8 = 0x15;
+const CONTENT_HEARTBEAT: u8 = 0x18;
+const HANDSHAKE_SERVER_HELLO: u8 = 0x02;
+const HANDSHAKE_SERVER_HELLO_DONE: u8 = 0x0e;
+
+/// Heartbeat 請求實際攜帶的 payload 長度
+const HEARTBEAT_PAYLOAD_LEN: usize = 16;
+/// 宣告長度比實際多出的位元組數，只用來判斷伺服器是否多回傳資料
+const HEARTBEAT_OVERCLAIM: usize = 16;
+
+/// 常見的 TLS 1.0 - 1.2 密碼套件，確保大多數伺服器都能完成交握
+const DEFAULT_CIPHER_SUITES: &[u16] = &[
+    0xc02f, 0xc030, 0xc013, 0xc014, 0x009c, 0x009d, 0x002f, 0x0035, 0x000a,
+];
+
+/// 協定版本列舉使用的密碼套件，額外包含 ECDSA 與 ChaCha20，只使用 ECDSA 憑證的伺服器也能回應
+const VERSION_PROBE_CIPHER_SUITES: &[u16] = &[
+    0xc02b, 0xc02c, 0xc02f, 0xc030, 0xcca8, 0xcca9, 0xc009, 0xc00a, 0xc013, 0xc014,
+    0x009c, 0x009d, 0x002f, 0x0035, 0x000a,
+];
+
+/// SSL 3.0 探測使用的密碼套件 (SSL 3.0 沒有 GCM 與 ECDHE-ECDSA)
+const SSL3_CIPHER_SUITES: &[u16] = &[0x002f, 0x0035, 0x0033, 0x0039, 0x000a, 0x0016, 0x0005, 0x0004];
+
+/// SSL 3.0 的協定版本
+pub const SSL3: u16 = 0x0300;
+
+/// 協定版本列舉時每次連線與讀取的逾時
+const VERSION_PROBE_WAIT: Duration = Duration::from_secs(5);
+
+/// 將 "host" 或 "host:port" 拆成主機與埠號 (預設 443)
+pub fn split_host_port(target: &str) -> (String, u16) {
+    match target.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') || host.ends_with(']') => {
+            match port.parse::<u16>() {
+                Ok(port) => (host.trim_matches(|c| c == '[' || c == ']').to_string(), port),
+                Err(_) => (target.to_string(), 443),
+            }
+        }
+        _ => (target.to_string(), 443),
+    }
+}
+
+/// 組裝 ClientHello 記錄
+///
+/// `version` 為協定版本 (0x0300 = SSL 3.0, 0x0301 = TLS 1.0, 0x0302 = TLS 1.1, 0x0303 = TLS 1.2)；
+/// SSL 3.0 沒有擴充欄位，不送 SNI 等擴充
+pub fn build_client_hello(host: &str, version: u16, cipher_suites: &[u16], heartbeat: bool) -> Vec<u8> {
+    let mut extensions = Vec::new();
+
+    // server_name (SNI)，IP 位址不送
+    if host.parse::<std::net::IpAddr>().is_err() {
+        let name = host.as_bytes();
+        extensions.extend_from_slice(&0x0000u16.to_be_bytes());
+        extensions.extend_from_slice(&((name.len() + 5) as u16).to_be_bytes());
+        extensions.extend_from_slice(&((name.len() + 3) as u16).to_be_bytes());
+        extensions.push(0x00);
+        extensions.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        extensions.extend_from_slice(name);
+    }
+
+    // supported_groups: secp256r1, secp384r1
+    extensions.extend_from_slice(&[0x00, 0x0a, 0x00, 0x06, 0x00, 0x04, 0x00, 0x17, 0x00, 0x18]);
+    // ec_point_formats: uncompressed
+    extensions.extend_from_slice(&[0x00, 0x0b, 0x00, 0x02, 0x01, 0x00]);
+
+    if version >= 0x0303 {
+        // signature_algorithms (TLS 1.2 起)：ECDSA、RSA-PSS、RSA PKCS#1 的 SHA-256 / SHA-384，以及 SHA-1
+        extensions.extend_from_slice(&[
+            0x00, 0x0d, 0x00, 0x12, 0x00, 0x10,
+            0x04, 0x03, 0x05, 0x03, 0x08, 0x04, 0x08, 0x05, 0x04, 0x01, 0x05, 0x01, 0x02, 0x01, 0x02, 0x03,
+        ]);
+    }
+
+    if heartbeat {
+        // heartbeat: peer_allowed_to_send
+        extensions.extend_from_slice(&[0x00, 0x0f, 0x00, 0x01, 0x01]);
+    }
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&version.to_be_bytes());
+    body.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    body.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    body.push(0x00); // session id
+    body.extend_from_slice(&((cipher_suites.len() * 2) as u16).to_be_bytes());
+    for suite in cipher_suites {
+        body.extend_from_slice(&suite.to_be_bytes());
+    }
+    body.extend_from_slice(&[0x01, 0x00]); // compression: null
+    if version > SSL3 {
+        body.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        body.extend_from_slice(&extensions);
+    }
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&body);
+
+    // 記錄層版本：SSL 3.0 伺服器可能拒絕 TLS 1.0 的記錄，其他版本一律使用 TLS 1.0 以相容舊伺服器
+    let mut record = vec![CONTENT_HANDSHAKE];
+    record.extend_from_slice(&version.min(0x0301).to_be_bytes());
+    record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+    record.extend_from_slice(&handshake);
+    record
+}
+
+/// TLS 記錄標頭與內容
+pub struct TlsRecord {
+    pub content_type: u8,
+    pub payload: Vec<u8>,
+}
+
+/// 連線到目標；掃描設定代理時經過代理的通道
+pub async fn connect(host: &str, port: u16, wait: Duration) -> ScannerResult<TcpStream> {
+    Connector::current().connect(host, port, wait).await
+}
+
+/// 錯誤訊息與稽核紀錄中的連線目標
+pub fn endpoint(host: &str, port: u16) -> String {
+    format!("tls://{}:{}", host, port)
+}
+
+/// 讀取一筆 TLS 記錄，逾時或連線關閉時回傳 None
+pub async fn read_record(stream: &mut TcpStream, wait: Duration) -> Option<TlsRecord> {
+    let mut header = [0u8; 5];
+    match timeout(wait, stream.read_exact(&mut header)).await {
+        Ok(Ok(_)) => {}
+        _ => return None,
+    }
+
+    let length = u16::from_be_bytes([header[3], header[4]]) as usize;
+    let mut payload = vec![0u8; length];
+    match timeout(wait, stream.read_exact(&mut payload)).await {
+        Ok(Ok(_)) => Some(TlsRecord {
+            content_type: header[0],
+            payload,
+        }),
+        _ => None,
+    }
+}
+
+/// 讀到 ServerHelloDone 為止，回傳伺服器是否完成 ServerHello 階段
+pub async fn read_until_server_hello_done(stream: &mut TcpStream, wait: Duration) -> bool {
+    // 交握訊息可能跨越多筆記錄，只需追蹤訊息邊界
+    let mut buffer: Vec<u8> = Vec::new();
+
+    while let Some(record) = read_record(stream, wait).await {
+        if record.content_type == CONTENT_ALERT {
+            return false;
+        }
+        if record.content_type != CONTENT_HANDSHAKE {
+            continue;
+        }
+
+        buffer.extend_from_slice(&record.payload);
+        let mut offset = 0;
+        while offset + 4 <= buffer.len() {
+            let msg_type = buffer[offset];
+            let msg_len = u32::from_be_bytes([0, buffer[offset + 1], buffer[offset + 2], buffer[offset + 3]]) as usize;
+            if offset + 4 + msg_len > buffer.len() {
+                break;
+            }
+            if msg_type == HANDSHAKE_SERVER_HELLO_DONE {
+                return true;
+            }
+            offset += 4 + msg_len;
+        }
+        buffer.drain(..offset);
+    }
+
+    false
+}
+
+/// Heartbleed (CVE-2014-0160) 探測
+///
+/// 送出宣告長度僅比實際 payload 多 16 bytes 的 heartbeat 請求：
+/// 修補過的伺服器會直接丟棄，存在漏洞的伺服器則會回傳超出送出內容的資料。
+/// 只比較回應長度，回應內容讀取後立即丟棄，不會儲存任何洩漏的記憶體。
+pub async fn probe_heartbleed(host: &str, port: u16) -> ScannerResult<bool> {
+    let wait = Duration::from_secs(5);
+    let mut stream = connect(host, port, wait).await?;
+
+    let version = 0x0302;
+    stream
+        .write_all(&build_client_hello(host, version, DEFAULT_CIPHER_SUITES, true))
+        .await
+        .map_err(|e| ScannerError::io(e, endpoint(host, port), wait))?;
+    if !read_until_server_hello_done(&mut stream, wait).await {
+        return Err(ScannerError::Tls("TLS 交握未完成，無法進行 Heartbleed 探測".to_string()));
+    }
+
+    let declared = HEARTBEAT_PAYLOAD_LEN + HEARTBEAT_OVERCLAIM;
+    let mut message = vec![0x01]; // heartbeat_request
+    message.extend_from_slice(&(declared as u16).to_be_bytes());
+    message.extend_from_slice(&[0x52; HEARTBEAT_PAYLOAD_LEN]);
+    message.extend_from_slice(&[0x00; 16]); // padding
+
+    let mut heartbeat = vec![CONTENT_HEARTBEAT];
+    heartbeat.extend_from_slice(&version.to_be_bytes());
+    heartbeat.extend_from_slice(&(message.len() as u16).to_be_bytes());
+    heartbeat.extend_from_slice(&message);
+    stream.write_all(&heartbeat).await.map_err(|e| ScannerError::io(e, endpoint(host, port), wait))?;
+
+    while let Some(record) = read_record(&mut stream, wait).await {
+        match record.content_type {
+            CONTENT_HEARTBEAT => {
+                // 只看長度: 回應比送出的 heartbeat 訊息長，代表伺服器多讀了記憶體
+                let leaked = record.payload.len() > message.len();
+                drop(record);
+                return Ok(leaked);
+            }
+            CONTENT_ALERT => return Ok(false),
+            _ => continue,
+        }
+    }
+
+    Ok(false)
+}
+
+/// 伺服器是否接受 SSL 3.0 或 TLS 1.0 - 1.2 的指定版本
+///
+/// ClientHello 只宣告該版本，ServerHello 回應相同版本即接受；不支援的伺服器會回 alert 或改用其他版本。
+pub async fn accepts_version(host: &str, port: u16, version: u16) -> ScannerResult<bool> {
+    let suites = if version == SSL3 { SSL3_CIPHER_SUITES } else { VERSION_PROBE_CIPHER_SUITES };
+    let mut stream = connect(host, port, VERSION_PROBE_WAIT).await?;
+    stream
+        .write_all(&build_client_hello(host, version, suites, false))
+        .await
+        .map_err(|e| ScannerError::io(e, endpoint(host, port), VERSION_PROBE_WAIT))?;
+
+    match read_record(&mut stream, VERSION_PROBE_WAIT).await {
+        Some(record)
+            if record.content_type == CONTENT_HANDSHAKE
+                && record.payload.len() >= 6
+                && record.payload[0] == HANDSHAKE_SERVER_HELLO =>
+        {
+            Ok(u16::from_be_bytes([record.payload[4], record.payload[5]]) == version)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// 伺服器是否接受 TLS 1.3：以只啟用 TLS 1.3 的 rustls 設定完成交握
+pub async fn accepts_tls13(host: &str, port: u16) -> ScannerResult<bool> {
+    let target = (host.to_string(), port);
+    let connector = Connector::current();
+    let identity = control::tls_identity();
+    // 同步 socket 與 rustls 在 blocking 執行緒上執行
+    tokio::task::spawn_blocking(move || tls13_handshake(&connector, identity.as_deref(), &target.0, target.1))
+        .await
+        .map_err(|e| ScannerError::Internal(format!("TLS 1.3 探測中斷: {}", e)))?
+}
+
+fn tls13_handshake(connector: &Connector, identity: Option<&TlsIdentity>, host: &str, port: u16) -> ScannerResult<bool> {
+    let mut tcp = connector.connect_blocking(host, port, VERSION_PROBE_WAIT)?;
+    let config = raw_http::tls_config_with_versions(&[&rustls::version::TLS13], identity)?;
+    let mut connection = rustls::ClientConnection::new(config, raw_http::server_name(host)?)
+        .map_err(|e| ScannerError::Tls(format!("TLS 初始化失敗: {}", e)))?;
+    while connection.is_handshaking() {
+        if connection.complete_io(&mut tcp).is_err() {
+            return Ok(false);
+        }
+    }
+    Ok(connection.protocol_version() == Some(rustls::ProtocolVersion::TLSv1_3))
+}
+
+/// 以指定版本只提供 cipher_suites 交握，回傳伺服器選擇的套件；伺服器不接受其中任何一個時回傳 None
+pub async fn negotiated_suite(host: &str, port: u16, version: u16, cipher_suites: &[u16]) -> ScannerResult<Option<u16>> {
+    let mut stream = connect(host, port, VERSION_PROBE_WAIT).await?;
+    stream
+        .write_all(&build_client_hello(host, version, cipher_suites, false))
+        .await
+        .map_err(|e| ScannerError::io(e, endpoint(host, port), VERSION_PROBE_WAIT))?;
+
+    let Some(record) = read_record(&mut stream, VERSION_PROBE_WAIT).await else {
+        return Ok(None);
+    };
+    let payload = &record.payload;
+    // ServerHello: 類型 (1) + 長度 (3) + 版本 (2) + random (32) + session id 長度 (1) + session id + 密碼套件 (2)
+    if record.content_type != CONTENT_HANDSHAKE || payload.len() < 39 || payload[0] != HANDSHAKE_SERVER_HELLO {
+        return Ok(None);
+    }
+    let offset = 39 + payload[38] as usize;
+    if payload.len() < offset + 2 {
+        return Ok(None);
+    }
+    let suite = u16::from_be_bytes([payload[offset], payload[offset + 1]]);
+    Ok(cipher_suites.contains(&suite).then_some(suite))
+}
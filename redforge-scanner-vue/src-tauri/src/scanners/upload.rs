@@ -0,0 +1,279 @@
+/**
+ * Upload Functionality Checks
+ *
+ * 偵測頁面中的檔案上傳表單 (input type=file)，在使用者啟用 upload_tests 時做保守的測試：
+ *
+ * - 上傳只含 canary 的小型文字檔，檢查回應或可猜測的路徑是否公開該檔案 (未驗證的上傳公開，Medium)
+ * - 上傳只含 canary 的 .html 檔，若之後以 text/html 從同源提供 (經上傳的 stored XSS，High)
+ *
+ * 永遠不上傳執行檔或伺服器端程式碼。所有上傳的檔名記錄在報告上，測試人員可事後移除。
+ */
+
+use crate::models::*;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::control;
+use crate::scanners::ScannerResult;
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// 每個目標最多測試的上傳表單數量
+const MAX_FORMS: usize = 2;
+/// 上傳後嘗試的公開目錄
+const GUESS_DIRECTORIES: &[&str] = &["/uploads/", "/upload/", "/files/", "/media/", "/static/uploads/", "/images/"];
+
+/// 頁面中的上傳表單
+#[derive(Debug, Clone)]
+struct UploadForm {
+    action: String,
+    file_field: String,
+    // 其他欄位 (隱藏的 CSRF token 等) 原樣送出
+    fields: Vec<(String, String)>,
+}
+
+/// 上傳的檔案，供測試人員事後清除
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadedFile {
+    pub form_action: String,
+    pub field: String,
+    pub filename: String,
+    /// 伺服器以 2xx 回應上傳請求
+    pub accepted: bool,
+    /// 找到可公開存取的位置
+    #[serde(default)]
+    pub exposed_url: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct UploadOutcome {
+    pub findings: Vec<ScanResult>,
+    pub uploads: Vec<UploadedFile>,
+    pub forms_found: usize,
+}
+
+pub struct UploadScanner {
+    client: ScanClient,
+}
+
+impl UploadScanner {
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 15))
+                .build()?
+                .into(),
+        })
+    }
+
+    /// 偵測上傳表單；enabled 為 false 時只回報表單數量，不上傳任何檔案
+    pub async fn scan(&self, task_id: &str, url: &str, enabled: bool) -> ScannerResult<UploadOutcome> {
+        let page = self.client.get(url).send().await?.text().await?;
+        let forms = find_upload_forms(url, &page);
+        let mut outcome = UploadOutcome { forms_found: forms.len(), ..UploadOutcome::default() };
+        if !enabled || forms.is_empty() {
+            return Ok(outcome);
+        }
+
+        for form in forms.iter().take(MAX_FORMS) {
+            // 文字檔：未驗證的上傳公開
+            let canary = control::canary("upload");
+            let text = self.upload(form, &format!("{}.txt", canary), "text/plain", canary.as_bytes()).await;
+            if let Some(exposed) = &text.exposed_url {
+                outcome.findings.push(finding(
+                    task_id,
+                    Severity::Medium,
+                    "上傳的檔案可公開存取".to_string(),
+                    format!("上傳到 {} 的檔案可從 {} 直接取得，其他使用者上傳的內容可能被任意讀取或被用來散布惡意檔案。建議: 將上傳檔案存放在網站根目錄之外，並以授權檢查後的下載端點提供", form.action, exposed),
+                    serde_json::json!({ "type": "Upload Exposure", "check_id": "upload_exposure", "form": form.action, "field": form.file_field, "filename": text.filename, "exposed_url": exposed, "canary": canary }),
+                ));
+            }
+            outcome.uploads.push(text);
+
+            // HTML 檔：只含 canary，確認是否以 text/html 從同源提供
+            let canary = control::canary("upload_html");
+            let html_body = format!("<p>{}</p>", canary);
+            let mut html = self.upload(form, &format!("{}.html", canary), "text/html", html_body.as_bytes()).await;
+            if html.accepted {
+                let served_as_html = match &html.exposed_url {
+                    Some(exposed) => self.served_as_html(exposed).await,
+                    None => false,
+                };
+                let (severity, title, description) = if served_as_html {
+                    (
+                        Severity::High,
+                        "上傳的 HTML 檔案以 text/html 從同源提供 (Stored XSS)".to_string(),
+                        format!("{} 接受 .html 檔案並從同源以 text/html 提供，攻擊者可上傳含腳本的頁面在網站的來源下執行。建議: 限制可上傳的副檔名、強制 Content-Disposition: attachment 並從獨立網域提供使用者內容", form.action),
+                    )
+                } else {
+                    html.exposed_url = None;
+                    (
+                        Severity::Low,
+                        "上傳功能接受 HTML 檔案".to_string(),
+                        format!("{} 接受 .html 檔案，但未找到以 text/html 提供的位置。建議確認上傳檔案的提供方式與副檔名白名單", form.action),
+                    )
+                };
+                outcome.findings.push(finding(
+                    task_id,
+                    severity,
+                    title,
+                    description,
+                    serde_json::json!({ "type": "HTML Upload", "check_id": "upload_html", "form": form.action, "field": form.file_field, "filename": html.filename, "served_as_html": served_as_html, "exposed_url": html.exposed_url, "canary": canary }),
+                ));
+            }
+            outcome.uploads.push(html);
+        }
+
+        Ok(outcome)
+    }
+
+    async fn upload(&self, form: &UploadForm, filename: &str, content_type: &str, content: &[u8]) -> UploadedFile {
+        let mut uploaded = UploadedFile {
+            form_action: form.action.clone(),
+            field: form.file_field.clone(),
+            filename: filename.to_string(),
+            accepted: false,
+            exposed_url: None,
+        };
+
+        let boundary = format!("----RedForge{}", Uuid::new_v4().simple());
+        let body = multipart_body(&boundary, &form.fields, &form.file_field, filename, content_type, content);
+        let response = match self
+            .client
+            .post(&form.action)
+            .header("content-type", &format!("multipart/form-data; boundary={}", boundary))
+            .body(body)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("⚠️  上傳測試失敗 {}: {}", form.action, e);
+                return uploaded;
+            }
+        };
+        uploaded.accepted = response.status().is_success();
+        let final_url = response.url().clone();
+        let text = response.text().await.unwrap_or_default();
+        if !uploaded.accepted {
+            return uploaded;
+        }
+        control::log(format!("📤 已上傳測試檔案 {} 到 {}", filename, form.action));
+
+        // 回應中提到的連結優先，其次是常見的上傳目錄
+        let mut candidates: Vec<String> = Regex::new(&format!(r#"["'(]([^"'()\s]*{})["')]"#, regex::escape(filename)))
+            .map(|re| re.captures_iter(&text).filter_map(|c| final_url.join(&c[1]).ok()).map(|u| u.to_string()).collect())
+            .unwrap_or_default();
+        candidates.extend(GUESS_DIRECTORIES.iter().filter_map(|dir| final_url.join(&format!("{}{}", dir, filename)).ok()).map(|u| u.to_string()));
+        if let Ok(action) = reqwest::Url::parse(&form.action) {
+            if let Ok(sibling) = action.join(filename) {
+                candidates.push(sibling.to_string());
+            }
+        }
+
+        let expected = String::from_utf8_lossy(content).to_string();
+        for candidate in candidates {
+            // 只接受同源的位置
+            if reqwest::Url::parse(&candidate).ok().and_then(|u| u.host_str().map(str::to_string)).as_deref() != final_url.host_str() {
+                continue;
+            }
+            let Ok(response) = self.client.get(&candidate).send().await else { continue };
+            if response.status().is_success() && response.text().await.unwrap_or_default().contains(&expected) {
+                uploaded.exposed_url = Some(candidate);
+                break;
+            }
+        }
+        uploaded
+    }
+
+    async fn served_as_html(&self, url: &str) -> bool {
+        match self.client.get(url).send().await {
+            Ok(response) => response
+                .headers()
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.to_lowercase().starts_with("text/html")),
+            Err(_) => false,
+        }
+    }
+}
+
+/// 頁面中含 input type=file 的同源 POST 表單
+fn find_upload_forms(page_url: &str, html: &str) -> Vec<UploadForm> {
+    let Ok(base) = reqwest::Url::parse(page_url) else {
+        return Vec::new();
+    };
+    static FORM: OnceLock<Regex> = OnceLock::new();
+    static INPUT: OnceLock<Regex> = OnceLock::new();
+    let form_re = FORM.get_or_init(|| Regex::new(r"(?is)<form\b([^>]*)>(.*?)</form>").unwrap());
+    let input_re = INPUT.get_or_init(|| Regex::new(r"(?is)<input\b([^>]*)>").unwrap());
+
+    form_re
+        .captures_iter(html)
+        .filter_map(|form| {
+            let attrs = &form[1];
+            if !attr(attrs, "method").is_some_and(|m| m.eq_ignore_ascii_case("post")) {
+                return None;
+            }
+            let action = base.join(&attr(attrs, "action").unwrap_or_default()).ok()?;
+            if action.host_str() != base.host_str() {
+                return None;
+            }
+
+            let mut file_field = None;
+            let mut fields = Vec::new();
+            for input in input_re.captures_iter(&form[2]) {
+                let Some(name) = attr(&input[1], "name") else { continue };
+                match attr(&input[1], "type").unwrap_or_default().to_lowercase().as_str() {
+                    "file" => file_field = file_field.or(Some(name)),
+                    "hidden" | "text" => fields.push((name, attr(&input[1], "value").unwrap_or_default())),
+                    _ => {}
+                }
+            }
+
+            Some(UploadForm { action: action.to_string(), file_field: file_field?, fields })
+        })
+        .collect()
+}
+
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    Regex::new(&format!(r#"(?i)\b{}\s*=\s*["']([^"']*)["']"#, name))
+        .ok()?
+        .captures(attrs)
+        .map(|c| c[1].to_string())
+}
+
+fn multipart_body(boundary: &str, fields: &[(String, String)], file_field: &str, filename: &str, content_type: &str, content: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value).as_bytes());
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary, file_field, filename, content_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+fn finding(task_id: &str, severity: Severity, title: String, description: String, raw_data: serde_json::Value) -> ScanResult {
+    ScanResult {
+        id: Uuid::new_v4().to_string(),
+        task_id: task_id.to_string(),
+        result_type: ResultType::Vulnerability,
+        severity: Some(severity),
+        title,
+        description: Some(description),
+        raw_data: Some(raw_data.to_string()),
+        created_at: Utc::now(),
+        low_confidence: false,
+        cwe_ids: Vec::new(),
+        details: None,
+    }
+}
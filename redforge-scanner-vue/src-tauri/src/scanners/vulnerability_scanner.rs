@@ -1,23 +1,34 @@
 use crate::models::*;
+use crate::scanners::control;
+use crate::scanners::sensitive_files::{self, FileValidation};
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::preconditions::TechContext;
+use crate::scanners::analysis::{self, PatternSet};
 use crate::scanners::ScannerResult;
-use reqwest::Client;
 use uuid::Uuid;
+use std::sync::OnceLock;
 use chrono::Utc;
 
 pub struct VulnerabilityScanner {
-    client: Client,
+    client: ScanClient,
+    context: TechContext,
 }
 
 impl VulnerabilityScanner {
-    pub fn new() -> Self {
-        Self {
-            client: Client::builder()
-                .danger_accept_invalid_certs(true)
-                .timeout(std::time::Duration::from_secs(15))
-                .redirect(reqwest::redirect::Policy::limited(5))
-                .build()
-                .unwrap(),
-        }
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 15))
+                .redirect(client::redirects(options, Some(5)))
+                .build()?.into(),
+            context: TechContext::default(),
+        })
+    }
+
+    /// 設定技術檢測結果，用於略過前置條件不符的探測
+    pub fn with_tech_context(mut self, context: TechContext) -> Self {
+        self.context = context;
+        self
     }
 
     /// 執行完整的 OWASP Top 10 掃描
@@ -86,6 +97,9 @@ impl VulnerabilityScanner {
                                 "error_indicators": sql_errors
                             })).unwrap()),
                             created_at: Utc::now(),
+                            low_confidence: false,
+                            cwe_ids: Vec::new(),
+                            details: None,
                         });
                         break; // 找到一個就足夠
                     }
@@ -95,14 +109,15 @@ impl VulnerabilityScanner {
         }
 
         // XSS 檢測
+        let canary = control::canary("xss");
         let xss_payloads = vec![
-            "<script>alert('XSS')</script>",
-            "<img src=x onerror=alert('XSS')>",
-            "javascript:alert('XSS')",
-            "<svg onload=alert('XSS')>",
+            format!("<script>alert('{}')</script>", canary),
+            format!("<img src=x onerror=alert('{}')>", canary),
+            format!("javascript:alert('{}')", canary),
+            format!("<svg onload=alert('{}')>", canary),
         ];
 
-        for payload in xss_payloads {
+        for payload in &xss_payloads {
             let test_url = format!("{}?q={}", base_url, urlencoding::encode(payload));
 
             match self.client.get(&test_url).send().await {
@@ -110,7 +125,7 @@ impl VulnerabilityScanner {
                     let body = response.text().await.unwrap_or_default();
 
                     // 檢查 payload 是否未經編碼直接出現在響應中
-                    if body.contains(payload) || body.contains(&payload.replace("'", "\"")) {
+                    if body.contains(payload.as_str()) || body.contains(&payload.replace("'", "\"")) {
                         results.push(ScanResult {
                             id: Uuid::new_v4().to_string(),
                             task_id: task_id.to_string(),
@@ -122,10 +137,14 @@ impl VulnerabilityScanner {
                             )),
                             raw_data: Some(serde_json::to_string(&serde_json::json!({
                                 "payload": payload,
+                                "canary": canary,
                                 "url": test_url,
                                 "reflected": true
                             })).unwrap()),
                             created_at: Utc::now(),
+                            low_confidence: false,
+                            cwe_ids: Vec::new(),
+                            details: None,
                         });
                         break;
                     }
@@ -156,11 +175,26 @@ impl VulnerabilityScanner {
         ];
 
         for path in sensitive_paths {
+            if !self.context.allows_path(path) {
+                tracing::info!("⏭️  {} 前置條件不符，略過", path);
+                continue;
+            }
             let test_url = format!("{}{}", base_url.trim_end_matches('/'), path);
 
             match self.client.get(&test_url).send().await {
                 Ok(response) => {
                     if response.status().is_success() {
+                        let status = response.status().as_u16();
+                        let body = response.bytes().await.unwrap_or_default();
+                        let (validator, excerpt) = match sensitive_files::validate(path, &body) {
+                            FileValidation::Confirmed { validator, excerpt } => (Some(validator), Some(excerpt)),
+                            FileValidation::Rejected { validator, reason } => {
+                                tracing::info!("ℹ️  {} 回傳 {} 但內容驗證失敗 ({}: {})，視為誤判", test_url, status, validator, reason);
+                                continue;
+                            }
+                            FileValidation::Unverified => (None, None),
+                        };
+
                         let severity = if path.contains(".env") || path.contains("config") {
                             Severity::Critical
                         } else if path.contains(".git") {
@@ -182,9 +216,14 @@ impl VulnerabilityScanner {
                             raw_data: Some(serde_json::to_string(&serde_json::json!({
                                 "path": path,
                                 "url": test_url,
-                                "status": response.status().as_u16()
+                                "status": status,
+                                "validator": validator,
+                                "excerpt": excerpt
                             })).unwrap()),
                             created_at: Utc::now(),
+                            low_confidence: false,
+                            cwe_ids: Vec::new(),
+                            details: None,
                         });
                     }
                 },
@@ -218,6 +257,9 @@ impl VulnerabilityScanner {
                                 "url": test_url
                             })).unwrap()),
                             created_at: Utc::now(),
+                            low_confidence: false,
+                            cwe_ids: Vec::new(),
+                            details: None,
                         });
                     }
                 },
@@ -248,6 +290,9 @@ impl VulnerabilityScanner {
                     "protocol": "http"
                 })).unwrap()),
                 created_at: Utc::now(),
+                low_confidence: false,
+                cwe_ids: Vec::new(),
+                details: None,
             });
         }
 
@@ -257,33 +302,28 @@ impl VulnerabilityScanner {
                 let body = response.text().await.unwrap_or_default();
 
                 // 檢查 API keys, tokens
-                let patterns = vec![
-                    (r#"api[_-]?key['\"]?\s*[:=]\s*['\"]([a-zA-Z0-9]{20,})"#, "API Key"),
-                    (r#"access[_-]?token['\"]?\s*[:=]\s*['\"]([a-zA-Z0-9]{20,})"#, "Access Token"),
-                    (r#"password['\"]?\s*[:=]\s*['\"]([^'\"]{3,})"#, "密碼"),
-                ];
-
-                for (pattern, name) in patterns {
-                    if let Ok(re) = regex::Regex::new(pattern) {
-                        if re.is_match(&body) {
-                            results.push(ScanResult {
-                                id: Uuid::new_v4().to_string(),
-                                task_id: task_id.to_string(),
-                                result_type: ResultType::Vulnerability,
-                                severity: Some(Severity::Critical),
-                                title: format!("HTML 原始碼中發現 {}", name),
-                                description: Some(format!(
-                                    "網頁原始碼中包含 {}，這可能導致敏感資訊洩露",
-                                    name
-                                )),
-                                raw_data: Some(serde_json::to_string(&serde_json::json!({
-                                    "type": name,
-                                    "pattern": pattern
-                                })).unwrap()),
-                                created_at: Utc::now(),
-                            });
-                        }
-                    }
+                let secrets = analysis::offload(move || legacy_secret_patterns().matches(&body)).await;
+
+                for (pattern, name) in secrets {
+                    results.push(ScanResult {
+                        id: Uuid::new_v4().to_string(),
+                        task_id: task_id.to_string(),
+                        result_type: ResultType::Vulnerability,
+                        severity: Some(Severity::Critical),
+                        title: format!("HTML 原始碼中發現 {}", name),
+                        description: Some(format!(
+                            "網頁原始碼中包含 {}，這可能導致敏感資訊洩露",
+                            name
+                        )),
+                        raw_data: Some(serde_json::to_string(&serde_json::json!({
+                            "type": name,
+                            "pattern": pattern
+                        })).unwrap()),
+                        created_at: Utc::now(),
+                        low_confidence: false,
+                        cwe_ids: Vec::new(),
+                        details: None,
+                    });
                 }
             },
             Err(_) => {},
@@ -318,6 +358,9 @@ impl VulnerabilityScanner {
                                 "value_prefix": &value[..20.min(value.len())]
                             })).unwrap()),
                             created_at: Utc::now(),
+                            low_confidence: false,
+                            cwe_ids: Vec::new(),
+                            details: None,
                         });
                     }
                 }
@@ -362,6 +405,9 @@ impl VulnerabilityScanner {
                                 "issue": issue
                             })).unwrap()),
                             created_at: Utc::now(),
+                            low_confidence: false,
+                            cwe_ids: Vec::new(),
+                            details: None,
                         });
                     }
                 }
@@ -372,3 +418,15 @@ impl VulnerabilityScanner {
         Ok(results)
     }
 }
+
+/// 舊掃描器的密鑰樣式 (區分大小寫，與 OWASP 掃描器的樣式分開保留)
+fn legacy_secret_patterns() -> &'static PatternSet {
+    static PATTERNS: OnceLock<PatternSet> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        PatternSet::new(&[
+            (r#"api[_-]?key['"]?\s*[:=]\s*['"]([a-zA-Z0-9]{20,})"#, "API Key"),
+            (r#"access[_-]?token['"]?\s*[:=]\s*['"]([a-zA-Z0-9]{20,})"#, "Access Token"),
+            (r#"password['"]?\s*[:=]\s*['"]([^'"]{3,})"#, "密碼"),
+        ])
+    })
+}
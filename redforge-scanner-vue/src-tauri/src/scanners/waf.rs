@@ -0,0 +1,174 @@
+/**
+ * WAF / Block Page Fingerprints
+ *
+ * 辨識 WAF、CDN 挑戰頁與地區封鎖的回應，避免把封鎖頁當成目標本身的內容分析。
+ * 指紋由標頭與內容特徵組成，只有狀態碼屬於封鎖類 (403、406、429、451、503) 或內容明確是挑戰頁時才判定。
+ */
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// 封鎖類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    /// WAF 拒絕請求
+    WafBlock,
+    /// JavaScript / CAPTCHA 挑戰頁
+    Challenge,
+    /// 依來源地區封鎖
+    GeoBlock,
+    /// 流量限制
+    RateLimited,
+}
+
+/// 符合的指紋
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BlockMatch {
+    pub kind: BlockKind,
+    pub vendor: Option<String>,
+    /// 符合的特徵，例如 "cf-mitigated: challenge"
+    pub matched: String,
+}
+
+struct Fingerprint {
+    vendor: &'static str,
+    kind: BlockKind,
+    /// 標頭名稱 (小寫) 與值需包含的字串 (空字串表示只需存在)
+    headers: &'static [(&'static str, &'static str)],
+    /// 內容特徵 (小寫比對)
+    body: &'static [&'static str],
+    /// 挑戰頁可能以 200 回傳，內容特徵足以判定 (一般頁面也可能嵌入 CAPTCHA，因此預設需要封鎖類狀態碼)
+    matches_ok_status: bool,
+}
+
+const FINGERPRINTS: &[Fingerprint] = &[
+    Fingerprint {
+        vendor: "Cloudflare",
+        kind: BlockKind::Challenge,
+        headers: &[("cf-mitigated", "challenge")],
+        body: &["cf-chl-", "challenge-platform", "just a moment...", "attention required! | cloudflare"],
+        matches_ok_status: true,
+    },
+    Fingerprint {
+        vendor: "Cloudflare",
+        kind: BlockKind::WafBlock,
+        headers: &[],
+        body: &["cloudflare ray id", "sorry, you have been blocked"],
+        matches_ok_status: false,
+    },
+    Fingerprint {
+        vendor: "Akamai",
+        kind: BlockKind::WafBlock,
+        headers: &[],
+        body: &["you don't have permission to access", "errors.edgesuite.net"],
+        matches_ok_status: false,
+    },
+    Fingerprint {
+        vendor: "AWS WAF",
+        kind: BlockKind::WafBlock,
+        headers: &[("x-amzn-waf-action", "")],
+        body: &["request blocked. we can't connect to the server for this app"],
+        matches_ok_status: false,
+    },
+    Fingerprint {
+        vendor: "Imperva Incapsula",
+        kind: BlockKind::WafBlock,
+        headers: &[("x-iinfo", "")],
+        body: &["incapsula incident id", "_incapsula_resource"],
+        matches_ok_status: false,
+    },
+    Fingerprint {
+        vendor: "Sucuri",
+        kind: BlockKind::WafBlock,
+        headers: &[("x-sucuri-block", "")],
+        body: &["sucuri website firewall", "access denied - sucuri"],
+        matches_ok_status: false,
+    },
+    Fingerprint {
+        vendor: "F5 BIG-IP ASM",
+        kind: BlockKind::WafBlock,
+        headers: &[],
+        body: &["the requested url was rejected. please consult with your administrator"],
+        matches_ok_status: false,
+    },
+    Fingerprint {
+        vendor: "ModSecurity",
+        kind: BlockKind::WafBlock,
+        headers: &[],
+        body: &["mod_security", "modsecurity", "this error was generated by mod_security"],
+        matches_ok_status: false,
+    },
+    Fingerprint {
+        vendor: "DDoS-Guard",
+        kind: BlockKind::Challenge,
+        headers: &[],
+        body: &["check.ddos-guard.net"],
+        matches_ok_status: true,
+    },
+    Fingerprint {
+        vendor: "CAPTCHA",
+        kind: BlockKind::Challenge,
+        headers: &[],
+        body: &["g-recaptcha", "h-captcha", "hcaptcha.com/1/api.js", "challenges.cloudflare.com/turnstile"],
+        matches_ok_status: false,
+    },
+];
+
+/// 地區封鎖頁的常見字句
+const GEO_BLOCK_PHRASES: &[&str] = &[
+    "not available in your country",
+    "not available in your region",
+    "access from your country",
+    "access from your location",
+    "geo-blocked",
+    "unavailable for legal reasons",
+];
+
+/// 狀態碼本身代表請求被擋
+fn is_block_status(status: u16) -> bool {
+    matches!(status, 403 | 406 | 418 | 429 | 451 | 503)
+}
+
+/// 判斷回應是否為封鎖或挑戰頁；正常回應回傳 None
+pub fn detect_block(status: u16, headers: &HeaderMap, body: &str) -> Option<BlockMatch> {
+    let body_lower = body.to_lowercase();
+
+    if status == 451 || (is_block_status(status) && GEO_BLOCK_PHRASES.iter().any(|p| body_lower.contains(p))) {
+        let matched = GEO_BLOCK_PHRASES
+            .iter()
+            .find(|p| body_lower.contains(*p))
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "HTTP 451".to_string());
+        return Some(BlockMatch { kind: BlockKind::GeoBlock, vendor: None, matched });
+    }
+
+    for fingerprint in FINGERPRINTS {
+        let header_match = fingerprint.headers.iter().find_map(|(name, needle)| {
+            let value = headers.get(*name)?.to_str().ok()?.to_lowercase();
+            value.contains(needle).then(|| format!("{}: {}", name, value))
+        });
+        let body_match = fingerprint.body.iter().find(|p| body_lower.contains(*p)).map(|p| p.to_string());
+
+        // 只有內容特徵時需搭配封鎖類狀態碼，除非該指紋本身就是挑戰頁
+        let confirmed = match (&header_match, &body_match) {
+            (Some(_), _) => true,
+            (None, Some(_)) => is_block_status(status) || fingerprint.matches_ok_status,
+            (None, None) => false,
+        };
+        if confirmed {
+            return Some(BlockMatch {
+                kind: fingerprint.kind,
+                vendor: Some(fingerprint.vendor.to_string()),
+                matched: header_match.or(body_match).unwrap_or_default(),
+            });
+        }
+    }
+
+    if status == 429 {
+        return Some(BlockMatch { kind: BlockKind::RateLimited, vendor: None, matched: "HTTP 429".to_string() });
+    }
+
+    None
+}
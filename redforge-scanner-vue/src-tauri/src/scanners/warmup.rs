@@ -0,0 +1,158 @@
+/**
+ * Warm-up Connectivity Probe
+ *
+ * 掃描開始前請求目標首頁與一個應該存在的靜態資源，判斷掃描流量是否被 WAF 或地區封鎖整個擋下。
+ * 被擋下時所有後續請求都只會拿到挑戰頁，報告會充滿誤導的「缺少標頭」與「未發現問題」，
+ * 因此直接讓掃描失敗；使用者可選擇仍要繼續，此時所有發現標記為低信心度。
+ */
+
+use crate::models::*;
+use crate::scanners::client::{self, ScanClient};
+use crate::scanners::crawl_policy::CrawlPolicy;
+use crate::scanners::page_identity::{self, PageIdentity};
+use crate::scanners::waf::{self, BlockMatch};
+use crate::scanners::ScannerResult;
+use regex::Regex;
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// 首頁沒有可用的資源連結時改用的路徑
+const FALLBACK_ASSET: &str = "/favicon.ico";
+
+/// 單一探測請求的結果
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WarmupProbe {
+    pub url: String,
+    pub status: Option<u16>,
+    pub block: Option<BlockMatch>,
+    pub error: Option<String>,
+}
+
+impl WarmupProbe {
+    /// 被封鎖：符合封鎖指紋，或沒有指紋但狀態碼為 403 / 406
+    fn is_blocked(&self) -> bool {
+        self.block.is_some() || matches!(self.status, Some(403) | Some(406))
+    }
+}
+
+/// 暖身結果，附在報告上
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WarmupResult {
+    /// 所有探測都被擋下
+    pub blocked: bool,
+    /// 被擋下但使用者選擇繼續掃描
+    #[serde(default)]
+    pub proceeded: bool,
+    pub probes: Vec<WarmupProbe>,
+    /// 基礎頁面的標題與圖示位置
+    #[serde(default)]
+    pub identity: Option<PageIdentity>,
+}
+
+impl WarmupResult {
+    /// 封鎖的描述，例如 "Cloudflare challenge"
+    pub fn summary(&self) -> String {
+        let blocks: Vec<String> = self
+            .probes
+            .iter()
+            .map(|probe| match &probe.block {
+                Some(block) => format!(
+                    "{} {}",
+                    block.vendor.as_deref().unwrap_or("unknown"),
+                    serde_json::to_value(block.kind).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+                ),
+                None => format!("HTTP {}", probe.status.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string())),
+            })
+            .collect();
+        let mut unique: Vec<String> = Vec::new();
+        for block in blocks {
+            if !unique.contains(&block) {
+                unique.push(block);
+            }
+        }
+        unique.join(", ")
+    }
+}
+
+pub struct WarmupProber {
+    client: ScanClient,
+}
+
+impl WarmupProber {
+    pub fn new(options: &ScanOptions) -> ScannerResult<Self> {
+        Ok(Self {
+            client: client::client_builder(options)?
+                .timeout(client::timeout(options, 10))
+                .build()?
+                .into(),
+        })
+    }
+
+    /// 請求首頁與一個靜態資源並分類
+    ///
+    /// 連線失敗不視為封鎖 (由後續掃描回報錯誤)；只有所有探測都取得回應且都被擋下才判定為封鎖。
+    /// 頁面中的資源連結須通過爬取規則，否則改用 favicon。
+    pub async fn probe(&self, url: &str, policy: &mut CrawlPolicy) -> WarmupResult {
+        let (base, body) = self.fetch(url).await;
+        let mut asset = asset_url(url, body.as_deref().unwrap_or_default());
+        if !policy.permit("GET", &asset) {
+            asset = fallback_url(url);
+        }
+        let (asset_probe, _) = self.fetch(&asset).await;
+
+        let probes = vec![base, asset_probe];
+        let blocked = probes.iter().all(|p| p.error.is_none() && p.is_blocked());
+        // 被封鎖時取得的是阻擋頁，標題沒有意義
+        let identity = body.filter(|_| !blocked).map(|body| page_identity::extract(url, &body));
+
+        WarmupResult { blocked, proceeded: false, probes, identity }
+    }
+
+    async fn fetch(&self, url: &str) -> (WarmupProbe, Option<String>) {
+        let mut probe = WarmupProbe { url: url.to_string(), status: None, block: None, error: None };
+
+        let response = match self.client.get(url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                probe.error = Some(e.to_string());
+                return (probe, None);
+            }
+        };
+
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response.text().await.unwrap_or_default();
+
+        probe.status = Some(status);
+        probe.block = waf::detect_block(status, &headers, &body);
+        (probe, Some(body))
+    }
+}
+
+fn fallback_url(page_url: &str) -> String {
+    reqwest::Url::parse(page_url)
+        .and_then(|base| base.join(FALLBACK_ASSET))
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| page_url.to_string())
+}
+
+/// 首頁中第一個同網域的 CSS / 圖片 / 腳本；沒有時使用 favicon
+fn asset_url(page_url: &str, html: &str) -> String {
+    let Ok(base) = reqwest::Url::parse(page_url) else {
+        return page_url.to_string();
+    };
+    static ASSET: OnceLock<Regex> = OnceLock::new();
+    let asset = ASSET.get_or_init(|| Regex::new(r#"(?i)<(?:link|script|img)[^>]+(?:href|src)\s*=\s*["']([^"']+\.(?:css|js|png|jpe?g|gif|svg|ico|woff2?))(?:\?[^"']*)?["']"#).unwrap());
+
+    let found = asset
+        .captures_iter(html)
+        .filter_map(|caps| caps.get(1))
+        .filter_map(|m| base.join(m.as_str()).ok())
+        .find(|url| url.host_str() == base.host_str());
+
+    found
+        .or_else(|| base.join(FALLBACK_ASSET).ok())
+        .map(|url| url.to_string())
+        .unwrap_or_else(|| page_url.to_string())
+}
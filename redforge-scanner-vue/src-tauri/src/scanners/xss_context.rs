@@ -0,0 +1,186 @@
+/**
+ * Reflected XSS Context Analysis
+ *
+ * 注入唯一的 canary 標記 (rfxss + 8 碼) 並以幾種跳脫字元包裝，再依標記在回應中的位置判斷反射的語境：
+ * script 區塊、HTML 屬性、HTML 文字、註解或非 HTML 內容。
+ * 只有可執行的語境才回報 (script 區塊中的程式碼、跳出引號的屬性、成功開啟的標籤)，
+ * JSON 內容與經過編碼的反射不算，避免把無害的回顯當成漏洞。
+ */
+
+use uuid::Uuid;
+
+/// 證據擷取的前後字元數 (共約 100 個字元)
+const SNIPPET_RADIUS: usize = 50;
+
+/// 反射的語境
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReflectionContext {
+    /// script 區塊中的程式碼 (不在字串內)
+    Script,
+    /// script 區塊中的字串常值
+    ScriptString,
+    /// 標籤內、不在引號中 (未加引號的屬性值，或已跳出引號)
+    UnquotedAttribute,
+    /// 標籤內的引號屬性值
+    QuotedAttribute,
+    /// 標籤之間的文字 (角括號被編碼或移除)
+    HtmlText,
+    /// HTML 註解
+    Comment,
+    /// 回應不是 HTML (JSON、純文字…)，瀏覽器不會解析為頁面
+    NonHtml,
+}
+
+impl ReflectionContext {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReflectionContext::Script => "script",
+            ReflectionContext::ScriptString => "script_string",
+            ReflectionContext::UnquotedAttribute => "unquoted_attribute",
+            ReflectionContext::QuotedAttribute => "quoted_attribute",
+            ReflectionContext::HtmlText => "html_text",
+            ReflectionContext::Comment => "comment",
+            ReflectionContext::NonHtml => "non_html",
+        }
+    }
+
+    /// 報告中使用的語境說明
+    pub fn description(&self) -> &'static str {
+        match self {
+            ReflectionContext::Script => "script 區塊的程式碼中",
+            ReflectionContext::ScriptString => "script 區塊的字串中",
+            ReflectionContext::UnquotedAttribute => "HTML 標籤的屬性中 (未加引號或已跳出引號)",
+            ReflectionContext::QuotedAttribute => "HTML 標籤的引號屬性值中",
+            ReflectionContext::HtmlText => "HTML 文字中 (已編碼)",
+            ReflectionContext::Comment => "HTML 註解中",
+            ReflectionContext::NonHtml => "非 HTML 的回應中",
+        }
+    }
+
+    /// 攻擊者能在此語境執行腳本
+    pub fn is_executable(&self) -> bool {
+        matches!(self, ReflectionContext::Script | ReflectionContext::UnquotedAttribute)
+    }
+}
+
+/// 一次反射與其周圍內容
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reflection {
+    pub context: ReflectionContext,
+    pub snippet: String,
+}
+
+/// 唯一的 canary 標記；只含英數字，不會被編碼
+pub fn marker() -> String {
+    format!("rfxss{}", &Uuid::new_v4().simple().to_string()[..8])
+}
+
+/// 以不同跳脫方式包裝標記的 payload 樣板：跳出雙引號、跳出單引號屬性並關閉標籤、開啟新標籤
+///
+/// {marker} 會換成每個請求各自產生的標記，避免前一次請求的反射被誤認為這次的
+pub const PROBES: [&str; 3] = ["\"{marker}", "'>{marker}", "<img src=x onerror={marker}>"];
+
+pub fn payload(template: &str, marker: &str) -> String {
+    template.replace("{marker}", marker)
+}
+
+/// 回應是否會被瀏覽器當作 HTML；沒有 Content-Type 時瀏覽器會自行判斷，視為 HTML
+pub fn is_html(headers: &reqwest::header::HeaderMap) -> bool {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map_or(true, |v| {
+            let v = v.to_ascii_lowercase();
+            v.contains("text/html") || v.contains("application/xhtml")
+        })
+}
+
+/// 找出回應中所有標記出現的位置與語境；is_html 為回應的 Content-Type 是否為 HTML
+pub fn analyze(body: &str, marker: &str, is_html: bool) -> Vec<Reflection> {
+    body.match_indices(marker)
+        .map(|(index, _)| Reflection {
+            context: if is_html { context_at(body, index) } else { ReflectionContext::NonHtml },
+            snippet: snippet(body, index, marker.len()),
+        })
+        .collect()
+}
+
+/// 第一個可執行的反射
+pub fn executable(reflections: &[Reflection]) -> Option<&Reflection> {
+    reflections.iter().find(|r| r.context.is_executable())
+}
+
+fn context_at(body: &str, index: usize) -> ReflectionContext {
+    let before = &body[..index];
+    let lower = before.to_ascii_lowercase();
+
+    let comment_open = lower.rfind("<!--");
+    if comment_open.is_some() && comment_open > lower.rfind("-->") {
+        return ReflectionContext::Comment;
+    }
+
+    if let Some(script_open) = lower.rfind("<script") {
+        if Some(script_open) > lower.rfind("</script") {
+            // 標記在 <script ...> 開始標籤本身之內時視為屬性，之後才是程式碼
+            if let Some(tag_end) = before[script_open..].find('>') {
+                let code = &before[script_open + tag_end + 1..];
+                return if in_js_string(code) { ReflectionContext::ScriptString } else { ReflectionContext::Script };
+            }
+        }
+    }
+
+    let tag_open = before.rfind('<');
+    if tag_open.is_some() && tag_open > before.rfind('>') {
+        let tag = &before[tag_open.unwrap_or(0)..];
+        return if in_quotes(tag) { ReflectionContext::QuotedAttribute } else { ReflectionContext::UnquotedAttribute };
+    }
+
+    ReflectionContext::HtmlText
+}
+
+/// 標籤內容到標記為止是否仍在引號屬性值中
+fn in_quotes(tag: &str) -> bool {
+    let mut quote: Option<char> = None;
+    for c in tag.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None => {}
+        }
+    }
+    quote.is_some()
+}
+
+/// 程式碼到標記為止是否仍在字串常值中 (處理反斜線跳脫)
+fn in_js_string(code: &str) -> bool {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in code.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match quote {
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if matches!(c, '"' | '\'' | '`') => quote = Some(c),
+            None => {}
+        }
+    }
+    quote.is_some()
+}
+
+/// 標記前後約 100 個字元，邊界對齊 UTF-8 字元
+fn snippet(body: &str, index: usize, len: usize) -> String {
+    let mut start = index.saturating_sub(SNIPPET_RADIUS);
+    while !body.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (index + len + SNIPPET_RADIUS).min(body.len());
+    while !body.is_char_boundary(end) {
+        end += 1;
+    }
+    body[start..end].to_string()
+}
@@ -0,0 +1,99 @@
+/**
+ * App Settings
+ *
+ * 不屬於單次掃描的全域設定，存放在設定目錄的 settings.json。目前只有掃描流量的代理：
+ * 掃描設定沒有指定代理時使用此處的代理。
+ *
+ * 代理密碼也保存在此檔 (權限限制為只有使用者可讀)，但和掃描設定一樣不會序列化回前端；
+ * 重新儲存時沒有提供密碼，而代理網址與帳號沒有變動時保留原本的密碼。
+ */
+
+use crate::models::ProxySettings;
+use crate::output::write_atomic;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const SETTINGS_FILE: &str = "settings.json";
+/// 設定快照中遮蔽的密碼；由快照重新掃描時改用全域設定保存的密碼
+const REDACTED: &str = "<redacted>";
+
+static SETTINGS_PATH: OnceLock<PathBuf> = OnceLock::new();
+// 讀取後修改再寫回，避免兩個儲存請求互相覆蓋
+static SETTINGS_LOCK: Mutex<()> = Mutex::new(());
+
+/// 啟動時呼叫：設定檔存放目錄
+pub fn init(config_dir: &Path) {
+    let _ = SETTINGS_PATH.set(config_dir.join(SETTINGS_FILE));
+}
+
+/// 全域設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct AppSettings {
+    // 掃描設定沒有指定代理時使用
+    pub proxy: Option<ProxySettings>,
+}
+
+fn settings_path() -> Result<&'static PathBuf, String> {
+    SETTINGS_PATH.get().ok_or_else(|| "設定目錄未初始化".to_string())
+}
+
+/// 讀取全域設定 (包含代理密碼)；檔案不存在時為預設值
+pub fn load() -> Result<AppSettings, String> {
+    let path = settings_path()?;
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("設定檔格式無效 {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AppSettings::default()),
+        Err(e) => Err(format!("無法讀取設定檔 {}: {}", path.display(), e)),
+    }
+}
+
+/// 儲存全域設定；代理設定必須有效
+pub fn save(mut settings: AppSettings) -> Result<(), String> {
+    if let Some(proxy) = &settings.proxy {
+        proxy.validate()?;
+    }
+    let path = settings_path()?;
+    let _guard = SETTINGS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let saved = load()?.proxy;
+    settings.proxy = settings.proxy.map(|proxy| with_saved_password(proxy, &saved));
+
+    // 密碼不會序列化，另外寫入檔案
+    let mut value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    if let Some(password) = settings.proxy.as_ref().and_then(|proxy| proxy.password.as_ref()) {
+        value["proxy"]["password"] = serde_json::Value::String(password.clone());
+    }
+    let content = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("無法建立設定目錄: {}", e))?;
+    }
+    write_atomic(path, content.as_bytes(), true)?;
+    crate::keys::restrict_permissions(path);
+    Ok(())
+}
+
+/// 掃描實際使用的代理：掃描設定沒有指定時使用全域設定；
+/// 掃描設定的代理沒有密碼 (或是快照中遮蔽的密碼) 時，沿用全域設定中同一代理與帳號的密碼
+pub fn resolve_proxy(proxy: Option<ProxySettings>) -> Result<Option<ProxySettings>, String> {
+    let saved = load()?.proxy;
+    match proxy {
+        None => Ok(saved),
+        Some(proxy) => Ok(Some(with_saved_password(proxy, &saved))),
+    }
+}
+
+fn with_saved_password(mut proxy: ProxySettings, saved: &Option<ProxySettings>) -> ProxySettings {
+    if proxy.password.as_deref() == Some(REDACTED) {
+        proxy.password = None;
+    }
+    if proxy.password.is_none() && proxy.username.is_some() {
+        proxy.password = saved
+            .as_ref()
+            .filter(|saved| saved.url.trim() == proxy.url.trim() && saved.username == proxy.username)
+            .and_then(|saved| saved.password.clone());
+    }
+    proxy
+}
@@ -0,0 +1,179 @@
+/**
+ * Report Summary
+ *
+ * 嚴重度、檢查、OWASP 類別與信心度的統計在掃描完成時計算一次，存放在報告與資料庫上。
+ * 摘要卡片、匯出與儀表板都讀取這份統計，不再各自從 vulnerabilities 重新計數
+ * (各自處理抑制與誤報的方式不同，數字會互相矛盾)。
+ *
+ * 發現的狀態或抑制變更時以同一個 summarize 重新計算。
+ */
+
+use crate::attack_chains::finding_check_id;
+use crate::models::ScanResult;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+
+/// 沒有 check id 的發現歸在此鍵下
+const UNKNOWN_CHECK: &str = "unknown";
+
+/// 報告的統計摘要
+///
+/// 已抑制或標記為誤報的發現不計入 total 與各項分類，只計入 suppressed / false_positive
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReportSummary {
+    pub total: u32,
+    /// critical / high / medium / low / info
+    pub by_severity: BTreeMap<String, u32>,
+    pub by_check_id: BTreeMap<String, u32>,
+    /// OWASP Top 10 類別 (例如 A03)，沒有對應類別的發現不列入
+    pub by_owasp: BTreeMap<String, u32>,
+    /// CWE (例如 CWE-89)；對應多個 CWE 的發現在每個 CWE 各計一次
+    #[serde(default)]
+    pub by_cwe: BTreeMap<String, u32>,
+    /// open / confirmed / fixed / accepted_risk
+    pub by_status: BTreeMap<String, u32>,
+    pub suppressed: u32,
+    pub false_positive: u32,
+    pub confidence: ConfidenceBreakdown,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ConfidenceBreakdown {
+    pub normal: u32,
+    /// 目標封鎖掃描流量時仍繼續掃描所得的發現
+    pub low: u32,
+}
+
+/// 計算摘要需要的發現欄位，來源可以是記憶體中的報告或資料庫
+#[derive(Debug, Clone)]
+pub struct SummaryInput {
+    pub severity: Option<String>,
+    pub check_id: Option<String>,
+    pub owasp: Option<String>,
+    pub cwe_ids: Vec<String>,
+    pub status: String,
+    pub suppressed: bool,
+    pub low_confidence: bool,
+}
+
+impl SummaryInput {
+    /// 剛完成的掃描中的發現：尚未分類，狀態為 open
+    pub fn from_result(finding: &ScanResult) -> Self {
+        Self {
+            severity: finding.severity.as_ref().map(|s| s.to_string()),
+            check_id: finding_check_id(finding),
+            owasp: owasp_category(finding.raw_data.as_deref()),
+            cwe_ids: finding.cwe_ids.clone(),
+            status: "open".to_string(),
+            suppressed: false,
+            low_confidence: finding.low_confidence,
+        }
+    }
+}
+
+/// 從 raw_data 的 "owasp" 欄位 (例如 "A02:2021") 取出類別
+pub fn owasp_category(raw_data: Option<&str>) -> Option<String> {
+    let raw: serde_json::Value = serde_json::from_str(raw_data?).ok()?;
+    let owasp = raw.get("owasp")?.as_str()?;
+    let category = owasp.split(':').next()?.trim().to_uppercase();
+    (category.len() == 3 && category.starts_with('A')).then_some(category)
+}
+
+pub fn summarize(findings: impl IntoIterator<Item = SummaryInput>) -> ReportSummary {
+    let mut summary = ReportSummary {
+        total: 0,
+        by_severity: BTreeMap::new(),
+        by_check_id: BTreeMap::new(),
+        by_owasp: BTreeMap::new(),
+        by_cwe: BTreeMap::new(),
+        by_status: BTreeMap::new(),
+        suppressed: 0,
+        false_positive: 0,
+        confidence: ConfidenceBreakdown::default(),
+        computed_at: Utc::now(),
+    };
+
+    for finding in findings {
+        if finding.suppressed {
+            summary.suppressed += 1;
+            continue;
+        }
+        if finding.status == "false_positive" {
+            summary.false_positive += 1;
+            continue;
+        }
+
+        summary.total += 1;
+        *summary.by_severity.entry(finding.severity.unwrap_or_else(|| "info".to_string())).or_insert(0) += 1;
+        *summary.by_check_id.entry(finding.check_id.unwrap_or_else(|| UNKNOWN_CHECK.to_string())).or_insert(0) += 1;
+        if let Some(owasp) = finding.owasp {
+            *summary.by_owasp.entry(owasp).or_insert(0) += 1;
+        }
+        for cwe in finding.cwe_ids {
+            *summary.by_cwe.entry(cwe).or_insert(0) += 1;
+        }
+        *summary.by_status.entry(finding.status).or_insert(0) += 1;
+        if finding.low_confidence {
+            summary.confidence.low += 1;
+        } else {
+            summary.confidence.normal += 1;
+        }
+    }
+
+    summary
+}
+
+impl ReportSummary {
+    pub fn count(&self, severity: &str) -> u32 {
+        self.by_severity.get(severity).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(severity: Option<&str>, status: &str, suppressed: bool) -> SummaryInput {
+        SummaryInput {
+            severity: severity.map(str::to_string),
+            check_id: None,
+            owasp: None,
+            cwe_ids: vec!["CWE-89".to_string(), "CWE-943".to_string()],
+            status: status.to_string(),
+            suppressed,
+            low_confidence: false,
+        }
+    }
+
+    #[test]
+    fn reads_the_owasp_category_from_raw_data() {
+        assert_eq!(owasp_category(Some(r#"{"owasp":"A02:2021"}"#)), Some("A02".to_string()));
+        assert_eq!(owasp_category(Some(r#"{"owasp":"a10"}"#)), Some("A10".to_string()));
+        assert_eq!(owasp_category(Some(r#"{"owasp":"Injection"}"#)), None);
+        assert_eq!(owasp_category(Some(r#"{"check_id":"xss"}"#)), None);
+        assert_eq!(owasp_category(Some("not json")), None);
+        assert_eq!(owasp_category(None), None);
+    }
+
+    #[test]
+    fn suppressed_and_false_positive_findings_are_only_counted_separately() {
+        let summary = summarize([
+            input(Some("high"), "open", false),
+            input(None, "confirmed", false),
+            input(Some("high"), "open", true),
+            input(Some("critical"), "false_positive", false),
+        ]);
+
+        assert_eq!(summary.total, 2);
+        assert_eq!((summary.suppressed, summary.false_positive), (1, 1));
+        assert_eq!((summary.count("high"), summary.count("info"), summary.count("critical")), (1, 1, 0));
+        assert_eq!(summary.by_check_id.get(UNKNOWN_CHECK), Some(&2));
+        // 對應多個 CWE 的發現在每個 CWE 各計一次
+        assert_eq!(summary.by_cwe.get("CWE-89"), Some(&2));
+        assert_eq!(summary.by_cwe.get("CWE-943"), Some(&2));
+        assert_eq!(summary.by_status.values().sum::<u32>(), summary.total);
+    }
+}
@@ -12,12 +12,13 @@ import {
   type DbScanTask,
   type DbScanResult,
 } from '@/services/database';
+import type { ReportSummary } from '@/types/findings';
 
 interface ScanTask {
   id: string;
   target_url: string;
   scan_type: string;
-  status: 'pending' | 'running' | 'completed' | 'failed';
+  status: 'pending' | 'running' | 'completed' | 'failed' | 'cancelled';
   started_at?: string;
   completed_at?: string;
   created_at: string;
@@ -26,9 +27,11 @@ interface ScanTask {
 interface ScanReport {
   task: ScanTask;
   headers: any[];
-  ssl_analysis: any;
+  ssl_analysis: any[]; // 每個分析的 TLS 連接埠一筆，第一筆為掃描目標
   technologies: any[];
   vulnerabilities: any[];
+  summary?: ReportSummary;
+  check_metrics?: unknown[];
 }
 
 /**
@@ -51,6 +54,8 @@ export async function saveScanToDatabase(taskId: string): Promise<void> {
       created_at: task.created_at,
       started_at: task.started_at,
       completed_at: task.completed_at,
+      summary: report.summary ? JSON.stringify(report.summary) : undefined,
+      check_metrics: report.check_metrics?.length ? JSON.stringify(report.check_metrics) : undefined,
     });
 
     // Save scan results (vulnerabilities)
@@ -64,6 +69,7 @@ export async function saveScanToDatabase(taskId: string): Promise<void> {
         description: vuln.description,
         raw_data: vuln.raw_data,
         created_at: vuln.created_at,
+        cwe_ids: vuln.cwe_ids ?? [],
       }));
 
       await insertScanResults(results);
@@ -116,11 +122,11 @@ export async function pollScanAndSave(taskId: string, onUpdate?: (task: ScanTask
         }
 
         // 檢查是否完成
-        if (task.status === 'completed' || task.status === 'failed') {
+        if (task.status === 'completed' || task.status === 'failed' || task.status === 'cancelled') {
           clearInterval(intervalId);
 
           // 自動保存到資料庫
-          if (task.status === 'completed') {
+          if (task.status === 'completed' || task.status === 'cancelled') {
             try {
               await saveScanToDatabase(taskId);
             } catch (error) {
@@ -6,19 +6,123 @@
  */
 
 import Database from '@tauri-apps/plugin-sql';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 let db: Database | null = null;
 
+// =============================================================================
+// Storage Status
+// =============================================================================
+
+export type StorageProblem = 'locked' | 'corrupted' | 'disk_full' | 'unavailable';
+
+export interface StorageStatus {
+  mode: 'persistent' | 'read_only_fallback';
+  path?: string;
+  problem?: StorageProblem;
+  message?: string;
+  recovery_actions: string[];
+  restored_from?: string;
+}
+
+/**
+ * Typed persistence error, thrown instead of raw plugin errors
+ */
+export class StorageError extends Error {
+  constructor(public kind: StorageProblem | 'query', message: string) {
+    super(message);
+    this.name = 'StorageError';
+  }
+}
+
+let storageStatus: StorageStatus | null = null;
+
+export function getStorageStatus(): StorageStatus | null {
+  return storageStatus;
+}
+
+/**
+ * History is unavailable in read-only fallback mode; scans still run in memory
+ */
+export function isPersistenceAvailable(): boolean {
+  return db !== null && storageStatus?.mode !== 'read_only_fallback';
+}
+
+function classifyError(error: unknown): StorageError {
+  const message = String(error);
+  const lower = message.toLowerCase();
+  if (lower.includes('database is locked') || lower.includes('busy')) {
+    return new StorageError('locked', message);
+  }
+  if (lower.includes('malformed') || lower.includes('not a database')) {
+    return new StorageError('corrupted', message);
+  }
+  if (lower.includes('disk is full') || lower.includes('no space left')) {
+    return new StorageError('disk_full', message);
+  }
+  return new StorageError('query', message);
+}
+
+function enterFallback(problem: StorageProblem, message: string) {
+  console.warn(`⚠️  資料庫改為唯讀記憶體模式 (${problem}): ${message}`);
+  storageStatus = {
+    ...(storageStatus ?? { recovery_actions: [] }),
+    mode: 'read_only_fallback',
+    problem,
+    message,
+  };
+  db = null;
+}
+
+/**
+ * Run a database operation, converting failures into StorageError.
+ * Corruption and a full disk switch the session to fallback mode, like at startup.
+ */
+async function run<T>(operation: (database: Database) => Promise<T>): Promise<T> {
+  const database = getDb();
+  try {
+    return await operation(database);
+  } catch (error) {
+    const storageError = classifyError(error);
+    if (storageError.kind === 'corrupted' || storageError.kind === 'disk_full') {
+      enterFallback(storageError.kind, storageError.message);
+    }
+    throw storageError;
+  }
+}
+
 /**
  * Initialize database connection
+ *
+ * Skips loading when the backend reports the database as locked or corrupted
  */
 export async function initDatabase(): Promise<void> {
+  await listen<StorageStatus>('storage-status', (event) => {
+    storageStatus = event.payload;
+    if (event.payload.mode === 'read_only_fallback') {
+      enterFallback(event.payload.problem ?? 'unavailable', event.payload.message ?? '');
+    }
+  });
+
+  storageStatus = await invoke<StorageStatus>('get_storage_status');
+  if (storageStatus.mode === 'read_only_fallback') {
+    console.warn('⚠️  資料庫無法使用，歷史紀錄停用:', storageStatus.message);
+    storageStatus.recovery_actions.forEach((action) => console.warn(`   - ${action}`));
+    return;
+  }
+  if (storageStatus.restored_from) {
+    console.warn('⚠️  資料庫已從備份還原:', storageStatus.restored_from);
+  }
+
   try {
     db = await Database.load('sqlite:redforge.db');
     console.log('✅ Database initialized successfully');
   } catch (error) {
     console.error('❌ Failed to initialize database:', error);
-    throw error;
+    const storageError = classifyError(error);
+    enterFallback(storageError.kind === 'query' ? 'unavailable' : storageError.kind, storageError.message);
+    throw storageError;
   }
 }
 
@@ -27,7 +131,10 @@ export async function initDatabase(): Promise<void> {
  */
 function getDb(): Database {
   if (!db) {
-    throw new Error('Database not initialized. Call initDatabase() first.');
+    throw new StorageError(
+      storageStatus?.problem ?? 'unavailable',
+      storageStatus?.message ?? 'Database not initialized. Call initDatabase() first.'
+    );
   }
   return db;
 }
@@ -49,6 +156,8 @@ export interface DbScanTask {
 
 /**
  * Insert scan task into database
+ *
+ * Scans run by the backend are already persisted there; existing rows are left unchanged
  */
 export async function insertScanTask(task: {
   id: string;
@@ -58,12 +167,12 @@ export async function insertScanTask(task: {
   created_at: string;
   started_at?: string;
   completed_at?: string;
+  summary?: string; // ReportSummary JSON
+  check_metrics?: string; // CheckMetrics[] JSON
 }): Promise<void> {
-  const database = getDb();
-
-  await database.execute(
-    `INSERT INTO scan_tasks (id, target_url, scan_type, status, started_at, completed_at, created_at)
-     VALUES ($1, $2, $3, $4, $5, $6, $7)`,
+  await run((database) => database.execute(
+    `INSERT OR IGNORE INTO scan_tasks (id, target_url, scan_type, status, started_at, completed_at, created_at, summary, check_metrics)
+     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)`,
     [
       task.id,
       task.target_url,
@@ -72,8 +181,10 @@ export async function insertScanTask(task: {
       task.started_at || null,
       task.completed_at || null,
       task.created_at,
+      task.summary || null,
+      task.check_metrics || null,
     ]
-  );
+  ));
 
   console.log(`✅ Inserted scan task: ${task.id}`);
 }
@@ -87,14 +198,12 @@ export async function updateScanTaskStatus(
   startedAt?: string,
   completedAt?: string
 ): Promise<void> {
-  const database = getDb();
-
-  await database.execute(
+  await run((database) => database.execute(
     `UPDATE scan_tasks
      SET status = $1, started_at = $2, completed_at = $3
      WHERE id = $4`,
     [status, startedAt || null, completedAt || null, taskId]
-  );
+  ));
 
   console.log(`✅ Updated scan task ${taskId} status to: ${status}`);
 }
@@ -103,11 +212,9 @@ export async function updateScanTaskStatus(
  * Get all scan tasks
  */
 export async function getAllScanTasks(): Promise<DbScanTask[]> {
-  const database = getDb();
-
-  const result = await database.select<DbScanTask[]>(
+  const result = await run((database) => database.select<DbScanTask[]>(
     'SELECT * FROM scan_tasks ORDER BY created_at DESC'
-  );
+  ));
 
   return result;
 }
@@ -116,12 +223,10 @@ export async function getAllScanTasks(): Promise<DbScanTask[]> {
  * Get scan task by ID
  */
 export async function getScanTask(taskId: string): Promise<DbScanTask | null> {
-  const database = getDb();
-
-  const result = await database.select<DbScanTask[]>(
+  const result = await run((database) => database.select<DbScanTask[]>(
     'SELECT * FROM scan_tasks WHERE id = $1',
     [taskId]
-  );
+  ));
 
   return result[0] || null;
 }
@@ -130,9 +235,7 @@ export async function getScanTask(taskId: string): Promise<DbScanTask | null> {
  * Delete scan task
  */
 export async function deleteScanTask(taskId: string): Promise<void> {
-  const database = getDb();
-
-  await database.execute('DELETE FROM scan_tasks WHERE id = $1', [taskId]);
+  await run((database) => database.execute('DELETE FROM scan_tasks WHERE id = $1', [taskId]));
 
   console.log(`✅ Deleted scan task: ${taskId}`);
 }
@@ -150,6 +253,7 @@ export interface DbScanResult {
   description?: string;
   raw_data?: string;
   created_at: string;
+  cwe_ids?: string[];
 }
 
 /**
@@ -164,12 +268,11 @@ export async function insertScanResult(result: {
   description?: string;
   raw_data?: string;
   created_at: string;
+  cwe_ids?: string[];
 }): Promise<void> {
-  const database = getDb();
-
-  await database.execute(
-    `INSERT INTO scan_results (id, task_id, result_type, severity, title, description, raw_data, created_at)
-     VALUES ($1, $2, $3, $4, $5, $6, $7, $8)`,
+  await run((database) => database.execute(
+    `INSERT OR IGNORE INTO scan_results (id, task_id, result_type, severity, title, description, raw_data, created_at, cwe_ids)
+     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)`,
     [
       result.id,
       result.task_id,
@@ -179,8 +282,9 @@ export async function insertScanResult(result: {
       result.description || null,
       result.raw_data || null,
       result.created_at,
+      JSON.stringify(result.cwe_ids ?? []),
     ]
-  );
+  ));
 }
 
 /**
@@ -197,12 +301,10 @@ export async function insertScanResults(results: DbScanResult[]): Promise<void>
  * Get scan results by task ID
  */
 export async function getScanResultsByTask(taskId: string): Promise<DbScanResult[]> {
-  const database = getDb();
-
-  const results = await database.select<DbScanResult[]>(
+  const results = await run((database) => database.select<DbScanResult[]>(
     'SELECT * FROM scan_results WHERE task_id = $1 ORDER BY created_at DESC',
     [taskId]
-  );
+  ));
 
   return results;
 }
@@ -219,22 +321,20 @@ export async function getDatabaseStats(): Promise<{
   lowFindings: number;
   infoFindings: number;
 }> {
-  const database = getDb();
-
   // Count scans
-  const scanCount = await database.select<Array<{ count: number }>>(
+  const scanCount = await run((database) => database.select<Array<{ count: number }>>(
     'SELECT COUNT(*) as count FROM scan_tasks'
-  );
+  ));
 
   // Count all findings
-  const findingCount = await database.select<Array<{ count: number }>>(
+  const findingCount = await run((database) => database.select<Array<{ count: number }>>(
     'SELECT COUNT(*) as count FROM scan_results'
-  );
+  ));
 
   // Count by severity
-  const severityCounts = await database.select<Array<{ severity: string; count: number }>>(
+  const severityCounts = await run((database) => database.select<Array<{ severity: string; count: number }>>(
     'SELECT severity, COUNT(*) as count FROM scan_results WHERE severity IS NOT NULL GROUP BY severity'
-  );
+  ));
 
   const stats = {
     totalScans: scanCount[0]?.count || 0,
@@ -99,6 +99,15 @@ export class MarkdownService {
         if (scan.completed_at) {
           lines.push(`- **Completed**: ${new Date(scan.completed_at).toLocaleString('zh-TW')}`);
         }
+        // 未完整執行的檢查列出原因，避免沒有發現被誤讀為沒有問題
+        const incomplete = (scan.coverage ?? []).filter((entry) => entry.status !== 'completed');
+        if (scan.coverage?.length) {
+          lines.push(`- **Coverage**: ${scan.coverage.length - incomplete.length}/${scan.coverage.length} 項檢查完成`);
+        }
+        incomplete.forEach((entry) => {
+          const reason = entry.message ? `: ${entry.message}` : '';
+          lines.push(`  - ${this.getCoverageEmoji(entry.status)} ${entry.check} (${entry.status}, ${entry.requests} 個請求)${reason}`);
+        });
         lines.push('');
       });
     }
@@ -317,6 +326,20 @@ export class MarkdownService {
       'running': '▶️',
       'completed': '✅',
       'failed': '❌',
+      'cancelled': '🛑',
+    };
+    return emojiMap[status] || '❓';
+  }
+
+  /**
+   * Get emoji for check coverage status
+   */
+  private getCoverageEmoji(status: string): string {
+    const emojiMap: Record<string, string> = {
+      'completed': '✅',
+      'skipped': '⏭️',
+      'partial': '⚠️',
+      'error': '❌',
     };
     return emojiMap[status] || '❓';
   }
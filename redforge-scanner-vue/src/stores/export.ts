@@ -8,8 +8,7 @@
 import { defineStore } from 'pinia';
 import { invoke } from '@tauri-apps/api/core';
 import { save } from '@tauri-apps/plugin-dialog';
-import { writeTextFile } from '@tauri-apps/plugin-fs';
-import type { ExportOptions, ExportData } from '../types/offline-collaboration';
+import type { ExportOptions, ExportData, RedactionInfo, WrittenFile } from '../types/offline-collaboration';
 import { encryptionService } from '../services/encryption';
 import { markdownService } from '../services/markdown';
 
@@ -66,7 +65,7 @@ export const useExportStore = defineStore('export', {
 
         // Step 4: Save file (80%)
         this.progress = 80;
-        await this.saveMarkdownFile(markdown, options.encrypt);
+        await this.saveMarkdownFile(markdown, options.encrypt, exportData.metadata.redaction);
 
         // Step 5: Complete (100%)
         this.progress = 100;
@@ -88,10 +87,13 @@ export const useExportStore = defineStore('export', {
       try {
         const data = await invoke<ExportData>('export_scan_data', {
           scanIds: options.scanIds,
-          includeFindingsOnly: options.includeFindingsOnly ?? false,
+          includeScans: options.includeScans ?? !(options.includeFindingsOnly ?? false),
+          includeFindings: options.includeFindings ?? true,
           includeAnnotations: options.includeAnnotations ?? true,
           includeAssets: options.includeAssets ?? true,
+          includeBaselines: options.includeBaselines ?? false,
           since: options.since?.toISOString(),
+          redactionProfile: options.redactionProfile,
         });
 
         // Add metadata
@@ -102,6 +104,7 @@ export const useExportStore = defineStore('export', {
           exported_by: options.exportedBy,
           team_id: options.teamId,
           exported_at: new Date().toISOString(),
+          redaction: data.metadata.redaction,
         };
 
         return data;
@@ -113,7 +116,11 @@ export const useExportStore = defineStore('export', {
     /**
      * Save Markdown file using Tauri dialog
      */
-    async saveMarkdownFile(markdown: string, encrypted: boolean = false): Promise<void> {
+    async saveMarkdownFile(
+      markdown: string,
+      encrypted: boolean = false,
+      redaction?: RedactionInfo
+    ): Promise<void> {
       try {
         const defaultFilename = `redforge_export_${Date.now()}${encrypted ? '.md.enc' : '.md'}`;
 
@@ -131,10 +138,19 @@ export const useExportStore = defineStore('export', {
           throw new Error('File save cancelled');
         }
 
-        await writeTextFile(filePath, markdown);
-        this.lastExportPath = filePath;
+        // 由後端以暫存檔 + rename 寫入，避免中斷時留下不完整的檔案
+        // 儲存對話框已確認覆寫，因此傳入 overwrite
+        const written = await invoke<WrittenFile>('export_scan_data_to_file', {
+          content: markdown,
+          path: filePath,
+          overwrite: true,
+          // 遮蔽過的匯出在寫入前再以同一設定檔與假名對照表處理一次
+          redactionProfile: redaction?.profile,
+          redactionMappingId: redaction?.mapping_id,
+        });
+        this.lastExportPath = written.path;
 
-        console.log(`File saved to: ${filePath}`);
+        console.log(`File saved to: ${written.path} (${written.size} bytes, sha256 ${written.sha256})`);
       } catch (error) {
         throw new Error(`Failed to save file: ${error}`);
       }
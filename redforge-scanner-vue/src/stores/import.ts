@@ -13,6 +13,7 @@ import type {
   ImportOptions,
   ImportResult,
   ExportData,
+  DeduplicationResult,
 } from '../types/offline-collaboration';
 import { encryptionService } from '../services/encryption';
 import { markdownService } from '../services/markdown';
@@ -174,18 +175,30 @@ export const useImportStore = defineStore('import', {
     ): Promise<ImportResult> {
       try {
         // First, deduplicate the data
-        const deduplicatedData = await invoke<ExportData>(
+        const deduplicated = await invoke<DeduplicationResult>(
           'deduplicate_import_data',
           { data }
         );
 
         // Then import to database
         const result = await invoke<ImportResult>('import_scan_data', {
-          data: deduplicatedData,
+          data: deduplicated.unique,
           skipDuplicates: options.skipDuplicates ?? true,
           mergeStrategy: options.mergeStrategy ?? 'skip',
+          dryRun: options.dryRun ?? false,
+          planToken: options.planToken,
+          confirmLargeImport: options.confirmLargeImport ?? false,
+          maxOverwrites: options.maxOverwrites,
+          maxOverwritePercent: options.maxOverwritePercent,
         });
 
+        // Records removed during deduplication never reach the import command
+        const { duplicates } = deduplicated;
+        result.skipped.scans += duplicates.scans.length;
+        result.skipped.findings += duplicates.findings.length;
+        result.skipped.annotations += duplicates.annotations.length;
+        result.skipped.assets += duplicates.assets.length;
+
         return result;
       } catch (error) {
         throw new Error(`Failed to import to database: ${error}`);
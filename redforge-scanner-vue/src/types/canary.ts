@@ -0,0 +1,13 @@
+/**
+ * Canary Type Definitions
+ *
+ * 對應後端 lookup_canary 回傳的 canary 來源
+ */
+
+export interface CanaryRecord {
+  value: string; // rfg-<掃描 id 前 6 碼>-<序號>
+  scan_id: string;
+  check_id: string; // 產生時的階段，例如 owasp.a03
+  purpose: string; // 例如 xss、ssrf、open_redirect
+  issued_at: string;
+}
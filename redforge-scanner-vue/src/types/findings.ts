@@ -0,0 +1,73 @@
+/**
+ * Findings Query Type Definitions
+ *
+ * 對應後端 get_findings 的篩選條件與回傳的分頁結果
+ */
+
+export type FindingSeverity = 'critical' | 'high' | 'medium' | 'low' | 'info';
+
+export type FindingStatus = 'open' | 'confirmed' | 'fixed' | 'accepted_risk' | 'false_positive';
+
+export type FindingSort = 'severity' | 'discovered_at' | 'title' | 'target';
+
+export interface FindingFilters {
+  severities?: FindingSeverity[];
+  statuses?: FindingStatus[];
+  check_ids?: string[];
+  cwe_ids?: string[]; // 例如 CWE-89，符合任一即可
+  target?: string; // 主機名稱完全相符，或目標 URL 的任一部分
+  project?: string;
+  since?: string; // RFC 3339，包含
+  until?: string; // RFC 3339，不包含
+  text?: string; // 標題與描述，不分大小寫
+  suppressed?: boolean; // 省略時包含已抑制的發現
+  sort?: FindingSort;
+  descending?: boolean;
+  offset?: number;
+  limit?: number; // 預設 50，上限 500
+}
+
+export interface FindingScanContext {
+  task_id: string;
+  target_url: string;
+  hostname?: string;
+  project?: string;
+  scan_type: string;
+  scanned_at: string;
+}
+
+export interface FindingRecord {
+  id: string;
+  result_type: string;
+  severity?: FindingSeverity;
+  title: string;
+  description?: string;
+  evidence?: string;
+  status: FindingStatus;
+  check_id?: string;
+  cwe_ids: string[];
+  suppressed: boolean;
+  discovered_at: string;
+  scan: FindingScanContext;
+}
+
+/** 報告的統計摘要；已抑制與誤報的發現不計入 total 與各項分類 */
+export interface ReportSummary {
+  total: number;
+  by_severity: Partial<Record<FindingSeverity, number>>;
+  by_check_id: Record<string, number>;
+  by_owasp: Record<string, number>;
+  by_cwe: Record<string, number>;
+  by_status: Partial<Record<FindingStatus, number>>;
+  suppressed: number;
+  false_positive: number;
+  confidence: { normal: number; low: number };
+  computed_at: string;
+}
+
+export interface FindingPage {
+  total: number;
+  offset: number;
+  limit: number;
+  findings: FindingRecord[];
+}
@@ -0,0 +1,16 @@
+/**
+ * Log Type Definitions
+ *
+ * 對應後端 get_scan_logs 回傳的掃描日誌與 set_log_level 的層級
+ */
+
+export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error';
+
+export interface ScanLogEntry {
+  timestamp: string;
+  level: LogLevel;
+  target: string; // 產生日誌的模組
+  check?: string | null; // 事件所在的檢查，例如 owasp.a03
+  message: string;
+  fields?: Record<string, string>;
+}
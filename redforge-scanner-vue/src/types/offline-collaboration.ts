@@ -17,17 +17,53 @@ export interface ExportMetadata {
   team_id?: string;
   exported_at: string;
   checksum?: string;
+  redaction?: RedactionInfo; // Set when a redaction profile was applied
+}
+
+export type RedactedField =
+  | 'evidence'
+  | 'description'
+  | 'recommendation'
+  | 'annotations'
+  | 'assets'
+  | 'baselines'
+  | 'scan_config';
+
+export interface RedactionInfo {
+  profile: string;
+  applied_at: string;
+  mapping_id?: string; // Local pseudonym mapping, see get_redaction_mapping
+  dropped_fields: RedactedField[];
+}
+
+export interface RedactionProfile {
+  name: string;
+  description?: string;
+  rules: { pattern: string; replacement: string }[];
+  drop_fields: RedactedField[];
+  pseudonymize_hosts: boolean;
+  strip_credentials: boolean;
 }
 
 export interface ScanTask {
   id: string;
   name: string;
   target: string;
-  status: 'pending' | 'running' | 'completed' | 'failed';
+  status: 'pending' | 'running' | 'paused' | 'completed' | 'failed' | 'cancelled';
   created_at: string;
   started_at?: string;
   completed_at?: string;
   created_by: string;
+  config?: Record<string, unknown>; // Resolved scan configuration, secrets redacted
+  config_hash?: string;
+  coverage?: CheckCoverage[]; // What each check actually tested; absent in exports from older versions
+}
+
+export interface CheckCoverage {
+  check: string; // Check id, e.g. "headers", "owasp.a03"
+  status: 'completed' | 'skipped' | 'partial' | 'error';
+  requests: number;
+  message?: string;
 }
 
 export interface SecurityHeader {
@@ -55,6 +91,9 @@ export interface Finding {
   discovered_by: string;
   cvss_score?: number;
   cve_id?: string;
+  cwe_ids?: string[]; // e.g. ['CWE-89']
+  owasp_category?: string; // e.g. 'A03:2021'
+  payload?: string;
 }
 
 export interface Annotation {
@@ -83,6 +122,24 @@ export interface ExportData {
   findings: Finding[];
   annotations?: Annotation[];
   assets?: Asset[];
+  baselines?: Baseline[];
+}
+
+export interface Baseline {
+  target: string;
+  source_task_id: string;
+  findings: { fingerprint: string; title: string; severity?: string }[];
+  secure_headers: string[];
+  ssl_grade?: string;
+  created_at: string;
+}
+
+/** Result of an atomic export write (export_scan_data_to_file) */
+export interface WrittenFile {
+  path: string;
+  size: number;
+  sha256: string;
+  renamed: boolean; // True when a numbered filename was used to avoid overwriting
 }
 
 // ============================================================================
@@ -106,20 +163,45 @@ export interface EncryptionOptions {
 
 export interface ExportOptions {
   scanIds?: string[];
+  includeScans?: boolean; // Default: true
+  includeFindings?: boolean; // Default: true
+  /** @deprecated use includeScans: false, includeFindings: true */
   includeFindingsOnly?: boolean;
   includeAnnotations?: boolean;
   includeAssets?: boolean;
+  includeBaselines?: boolean;
   since?: Date;
   encrypt?: boolean;
   passphrase?: string;
   teamId?: string;
   exportedBy: string;
+  redactionProfile?: string; // e.g. 'third-party'
 }
 
 export interface ImportOptions {
   passphrase?: string;
   skipDuplicates?: boolean;
   mergeStrategy?: 'skip' | 'overwrite' | 'merge';
+  /** Compute the plan without importing anything */
+  dryRun?: boolean;
+  /** Token from a dry run; the import applies exactly that plan or fails */
+  planToken?: string;
+  /** Apply an import that overwrites more existing scans than the limits allow */
+  confirmLargeImport?: boolean;
+  /** Overwritten scans allowed without confirmation (default 10) */
+  maxOverwrites?: number;
+  /** Share of loaded scans allowed to be overwritten without confirmation (default 20) */
+  maxOverwritePercent?: number;
+}
+
+export interface ImportPlan {
+  scans: string[];
+  findings: string[];
+  skipped_scans: string[];
+  skipped_findings: string[];
+  overwritten_scans: string[];
+  merged_baselines: string[];
+  skipped_baselines: string[];
 }
 
 export interface ImportResult {
@@ -137,6 +219,22 @@ export interface ImportResult {
     assets: number;
   };
   errors: string[];
+  dry_run: boolean;
+  plan?: ImportPlan;
+  plan_token?: string;
+  impact?: ImportImpact;
+  /** Nothing was written; show the impact and retry with confirmLargeImport */
+  requires_confirmation: boolean;
+}
+
+export interface ImportImpact {
+  overwritten_scans: number;
+  overwritten_findings: number;
+  existing_scans: number;
+  overwritten_percent: number;
+  max_overwrites: number;
+  max_overwrite_percent: number;
+  exceeds_threshold: boolean;
 }
 
 // ============================================================================
@@ -172,10 +270,19 @@ export interface ExportSummary {
 // Deduplication
 // ============================================================================
 
+export type DuplicateSource = 'import_file' | 'in_memory' | 'persisted';
+
+export interface DuplicateRecord {
+  id: string;
+  source: DuplicateSource;
+}
+
 export interface DeduplicationResult {
   duplicates: {
-    scans: string[];
-    findings: string[];
+    scans: DuplicateRecord[];
+    findings: DuplicateRecord[];
+    annotations: DuplicateRecord[];
+    assets: DuplicateRecord[];
   };
   unique: ExportData;
 }
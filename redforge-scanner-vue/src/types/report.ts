@@ -0,0 +1,14 @@
+/**
+ * Report Type Definitions
+ *
+ * 對應後端 get_executive_summary 的執行摘要
+ */
+
+export type RiskRating = 'critical' | 'high' | 'medium' | 'low' | 'none';
+
+export interface ExecutiveSummary {
+  task_id: string;
+  paragraphs: string[]; // 概況、最嚴重的發現、憑證與技術觀察 (沒有時省略) 與整體風險
+  risk_rating: RiskRating;
+  risk_score: number; // 加權嚴重度分數 (Critical 40、High 15、Medium 5、Low 1)
+}
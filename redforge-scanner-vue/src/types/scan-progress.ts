@@ -0,0 +1,104 @@
+/**
+ * Scan Progress Type Definitions
+ *
+ * 對應後端 models::ScanStage / ScanProgress，階段 id 與 Rust 端 snake_case 序列化一致
+ */
+
+export type ScanStageId =
+  | 'queued'
+  | 'resolving'
+  | 'headers'
+  | 'ssl'
+  | 'ports'
+  | 'crawling'
+  | 'owasp'
+  | 'tech_detection'
+  | 'reporting'
+  | 'finalizing'
+  | 'paused'
+  | 'completed'
+  | 'failed'
+  | 'cancelled';
+
+export type ScanStage =
+  | { id: Exclude<ScanStageId, 'owasp'> }
+  | { id: 'owasp'; category: string };
+
+export interface ScanProgress {
+  task_id: string;
+  stage: ScanStage;
+  label: string;
+  progress: number;
+  message: string;
+}
+
+export interface StageInfo {
+  stage: ScanStage;
+  label: string;
+}
+
+export interface StageTiming {
+  stage: ScanStage;
+  started_at: string;
+  duration_ms: number;
+  skipped?: string; // 階段被略過時的原因
+}
+
+// scan-log 事件：每 250ms 批次送出的日誌行
+export interface ScanLogLine {
+  timestamp: string;
+  message: string;
+}
+
+export interface ScanLogBatch {
+  task_id: string;
+  lines: ScanLogLine[];
+  dropped_lines?: number; // 超過 1000 行上限而捨棄的較舊日誌行
+}
+
+// scan-finding 事件：每個發現只送出一次
+export interface FindingEvent {
+  task_id: string;
+  finding: {
+    id: string;
+    task_id: string;
+    result_type: string;
+    severity?: 'critical' | 'high' | 'medium' | 'low' | 'info';
+    title: string;
+    description?: string;
+    raw_data?: string;
+    created_at: string;
+    low_confidence?: boolean;
+    cwe_ids?: string[];
+    details?: FindingDetails;
+  };
+}
+
+// 發現的結構化中繼資料，完整證據仍在 raw_data
+export interface FindingDetails {
+  owasp_category?: string; // 例如 'A03:2021'
+  cwe_id?: string;
+  affected_url?: string;
+  payload?: string;
+  confidence?: number; // 0-100
+  reflection_context?: string; // XSS 反射語境，例如 'script'、'unquoted_attribute'
+  evidence?: string; // payload 周圍的回應內容
+  exchange?: HttpExchange; // 產生此發現的請求與回應
+}
+
+// 產生發現的 HTTP 請求與回應，憑證類的請求標頭已遮蔽
+export interface HttpExchange {
+  request_line: string;
+  request_headers: EvidenceHeader[];
+  request_body?: string;
+  response_status?: number;
+  response_headers: EvidenceHeader[];
+  response_body_excerpt?: string; // 截斷到 evidence_body_bytes
+  response_body_bytes?: number;
+  response_body_truncated: boolean;
+}
+
+export interface EvidenceHeader {
+  name: string;
+  value: string;
+}
@@ -0,0 +1,27 @@
+/**
+ * Settings Type Definitions
+ *
+ * 對應後端 get_app_settings / save_app_settings 的全域設定與 test_proxy_connection 的結果
+ */
+
+// 掃描流量經過的代理；password 只會送到後端，讀取設定時不會回傳
+export interface ProxySettings {
+  url: string; // http://、https://、socks5:// 或 socks5h://
+  username?: string | null;
+  password?: string | null;
+  accept_invalid_certs: boolean; // 攔截代理 (Burp 等) 以自己的 CA 重新簽發憑證時啟用
+}
+
+export interface AppSettings {
+  proxy?: ProxySettings | null; // 掃描設定沒有指定代理時使用
+}
+
+export interface ProxyTestResult {
+  success: boolean;
+  proxy: string; // 不含帳密的代理網址
+  url: string;
+  status?: number | null;
+  elapsed_ms: number;
+  error?: string | null;
+  error_kind?: string | null;
+}